@@ -0,0 +1,65 @@
+#!/bin/sh
+# Regenerate the OpenSSL interop vectors (see `rustopals::testsupport::interop`):
+#
+#     data/openssl/generate.sh > data/openssl/vectors.txt
+#
+# Keys, IVs and plaintexts are deterministic, but salted vectors get a random
+# salt every time.
+set -eu
+
+TEXT="Cooking MC's like a pound of bacon. Burning 'em, if you ain't quick and nimble."
+PASSWORD="YELLOW SUBMARINE"
+
+# Hex SHA-256 of `$1`, truncated to `$2` hex digits
+hash() {
+    printf '%s' "$1" | openssl dgst -sha256 -r | cut -c1-"$2"
+}
+
+hex() {
+    xxd -p | tr -d '\n'
+}
+
+# Print a `$1 = $2` line (without trailing whitespace for empty values)
+field() {
+    if [ -n "$2" ]; then
+        echo "$1 = $2"
+    else
+        echo "$1 ="
+    fi
+}
+
+echo "# Generated by data/openssl/generate.sh with $(openssl version | cut -d' ' -f1-2)"
+
+for bits in 128 192 256; do
+    for mode in ecb cbc ctr; do
+        cipher="aes-$bits-$mode"
+
+        for len in 0 1 15 16 17 33 64; do
+            key=$(hash "key-$cipher-$len" $((bits / 4)))
+            plaintext=$(printf '%s' "$TEXT" | head -c "$len" | hex)
+
+            if [ "$mode" = ecb ]; then
+                iv=""
+                ciphertext=$(printf '%s' "$TEXT" | head -c "$len" | openssl enc "-$cipher" -K "$key" | hex)
+            else
+                iv=$(hash "iv-$cipher-$len" 32)
+                ciphertext=$(printf '%s' "$TEXT" | head -c "$len" | openssl enc "-$cipher" -K "$key" -iv "$iv" | hex)
+            fi
+
+            echo
+            field cipher "$cipher"
+            field key "$key"
+            field iv "$iv"
+            field plaintext "$plaintext"
+            field ciphertext "$ciphertext"
+        done
+
+        salted=$(printf '%s' "$TEXT" | openssl enc "-$cipher" -md sha256 -pass "pass:$PASSWORD" 2>/dev/null | hex)
+
+        echo
+        field cipher "$cipher"
+        field password "$(printf '%s' "$PASSWORD" | hex)"
+        field plaintext "$(printf '%s' "$TEXT" | hex)"
+        field salted "$salted"
+    done
+done
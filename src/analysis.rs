@@ -0,0 +1,352 @@
+//! Automated analysis of unknown ciphertexts ("crack this file").
+//!
+//! [`auto_crack`] runs the whole detection/attack stack on some input: it
+//! detects a hex or base64 encoding, and then tries single-byte XOR, repeating
+//! XOR and ECB detection. Every attack that finds something reports a
+//! [`Finding`] with a confidence and, if it recovered one, a plaintext.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::analysis::{auto_crack, Encoding, FindingKind};
+//!
+//! let report = auto_crack(b"1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736");
+//!
+//! assert_eq!(report.encoding, Encoding::Hex);
+//! assert_eq!(report.plaintext(), Some("Cooking MC's like a pound of bacon"));
+//! assert!(matches!(
+//!     report.best().unwrap().kind,
+//!     FindingKind::SingleXor { key: 88, .. }
+//! ));
+//! ```
+
+use std::convert::TryFrom;
+use std::str;
+
+use crate::block::{count_repeated, BlockCipher, AES128};
+use crate::stream::{RepeatingXORCipher, SingleXORCipher, StreamCipher};
+use crate::util::fixtures::{decode_base64_multiline, decode_hex_lines};
+use crate::util::pool::ThreadPool;
+use crate::util::text::printable_ratio;
+use crate::util::NaiveTextScorer;
+
+/// Findings below this confidence are left out of [`Report`]s.
+pub const MIN_CONFIDENCE: f32 = 0.5;
+
+/// Maximum key size tried for repeating XOR.
+const MAX_KEYSIZE: usize = 40;
+
+/// Minimum length (in bytes) to try repeating XOR on, since key sizes can't be
+/// told apart on shorter inputs.
+const MIN_REPEATING_XOR_LEN: usize = 64;
+
+/// How the input was encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Not encoded (or in an unknown encoding).
+    Raw,
+
+    /// A single hex value.
+    Hex,
+
+    /// One hex value per line, each one analyzed on its own.
+    HexLines,
+
+    /// A single base64 value (possibly wrapped over multiple lines).
+    Base64,
+}
+
+/// What an attack found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FindingKind {
+    /// The input is plaintext already.
+    Plaintext { plaintext: String },
+
+    /// Single-byte XOR, cracked.
+    SingleXor { key: u8, plaintext: String },
+
+    /// Repeating-key XOR, cracked.
+    RepeatingXor { key: Vec<u8>, plaintext: String },
+
+    /// ECB mode (repeated ciphertext blocks).
+    Ecb {
+        block_size: usize,
+        repeated_blocks: usize,
+    },
+}
+
+impl FindingKind {
+    /// Get the recovered plaintext, if any.
+    #[must_use]
+    pub fn plaintext(&self) -> Option<&str> {
+        match self {
+            FindingKind::Plaintext { plaintext }
+            | FindingKind::SingleXor { plaintext, .. }
+            | FindingKind::RepeatingXor { plaintext, .. } => Some(plaintext),
+            FindingKind::Ecb { .. } => None,
+        }
+    }
+}
+
+/// Something found by [`auto_crack`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    /// What was found.
+    pub kind: FindingKind,
+
+    /// Line it was found in (0-based), for [`Encoding::HexLines`].
+    pub line: Option<usize>,
+
+    /// How confident the finding is, in `[0, 1]`.
+    pub confidence: f32,
+}
+
+/// The result of [`auto_crack`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    /// How the input was encoded.
+    pub encoding: Encoding,
+
+    /// Findings with at least [`MIN_CONFIDENCE`], most confident first.
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Get the most confident finding.
+    #[must_use]
+    pub fn best(&self) -> Option<&Finding> {
+        self.findings.first()
+    }
+
+    /// Get the plaintext of the most confident finding recovering one.
+    #[must_use]
+    pub fn plaintext(&self) -> Option<&str> {
+        self.findings
+            .iter()
+            .find_map(|finding| finding.kind.plaintext())
+    }
+}
+
+/// Analyze some `input` (see the [module docs](self)).
+#[must_use]
+pub fn auto_crack(input: &[u8]) -> Report {
+    let (encoding, records) = decode(input);
+
+    let mut findings = ThreadPool::available()
+        .parallel_map(&records, |record| analyze(record))
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, found)| {
+            found.into_iter().map(move |(kind, confidence)| Finding {
+                kind,
+                line: (encoding == Encoding::HexLines).then_some(i),
+                confidence,
+            })
+        })
+        .filter(|finding| finding.confidence >= MIN_CONFIDENCE)
+        .collect::<Vec<_>>();
+
+    findings.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    Report { encoding, findings }
+}
+
+/// Detect the encoding of `input` and decode it into records to analyze.
+fn decode(input: &[u8]) -> (Encoding, Vec<Vec<u8>>) {
+    let raw = (Encoding::Raw, vec![input.to_vec()]);
+
+    let text = match str::from_utf8(input) {
+        Ok(text) => text.trim(),
+        Err(_) => return raw,
+    };
+
+    if text.is_empty() {
+        return raw;
+    }
+
+    if let Ok(records) = decode_hex_lines(text) {
+        let encoding = if records.len() == 1 {
+            Encoding::Hex
+        } else {
+            Encoding::HexLines
+        };
+
+        return (encoding, records);
+    }
+
+    if let Ok(bytes) = decode_base64_multiline(text) {
+        return (Encoding::Base64, vec![bytes]);
+    }
+
+    raw
+}
+
+/// How much `text` looks like English, in `[0, 1]`: the ratio of printable
+/// bytes times the ratio of letters and spaces.
+#[allow(clippy::cast_precision_loss)]
+fn englishness(text: &str) -> f32 {
+    if text.is_empty() {
+        return 0.0;
+    }
+
+    let letters = text
+        .bytes()
+        .filter(|byte| byte.is_ascii_alphabetic() || *byte == b' ')
+        .count();
+
+    printable_ratio(text.as_bytes()) * letters as f32 / text.len() as f32
+}
+
+/// Run every attack on a single `record`.
+fn analyze(record: &[u8]) -> Vec<(FindingKind, f32)> {
+    let mut found = vec![];
+
+    // Do not bother cracking plaintext (XOR would just flip its case)
+    if let Ok(plaintext) = str::from_utf8(record) {
+        let confidence = englishness(plaintext);
+        let kind = FindingKind::Plaintext {
+            plaintext: plaintext.to_string(),
+        };
+
+        if confidence >= MIN_CONFIDENCE {
+            return vec![(kind, confidence)];
+        }
+
+        found.push((kind, confidence));
+    }
+
+    // A zero key means plaintext, which is reported above
+    if let Some((key, plaintext)) = SingleXORCipher::<u8>::crack(&NaiveTextScorer, record) {
+        if key != 0 {
+            let confidence = englishness(&plaintext);
+
+            found.push((FindingKind::SingleXor { key, plaintext }, confidence));
+        }
+    }
+
+    if record.len() >= MIN_REPEATING_XOR_LEN {
+        found.extend(crack_repeating_xor(record));
+    }
+
+    let block_size = AES128::BLOCK_SIZE;
+
+    if record.len().is_multiple_of(block_size) {
+        let repeated_blocks = count_repeated(record, block_size);
+
+        // Repeated blocks are next to impossible in random-looking data, so
+        // every one of them halves the doubt
+        if repeated_blocks > 0 {
+            found.push((
+                FindingKind::Ecb {
+                    block_size,
+                    repeated_blocks,
+                },
+                1.0 - 0.5_f32.powi(i32::try_from(repeated_blocks).unwrap_or(i32::MAX)),
+            ));
+        }
+    }
+
+    found
+}
+
+/// Crack `record` as repeating-key XOR, with the best-ranked key size.
+fn crack_repeating_xor(record: &[u8]) -> Option<(FindingKind, f32)> {
+    let keysize = RepeatingXORCipher::rank_keysizes(record, MAX_KEYSIZE)
+        .first()?
+        .keysize;
+
+    let key = RepeatingXORCipher::<u8>::guess_key(&NaiveTextScorer, record, keysize);
+
+    // Constant keys are single-byte XOR (or plaintext), reported elsewhere
+    if key.len() != keysize || key.iter().all(|&byte| byte == key[0]) {
+        return None;
+    }
+
+    let plaintext = String::from_utf8(RepeatingXORCipher(&key).process(record).collect()).ok()?;
+    let confidence = englishness(&plaintext);
+
+    Some((FindingKind::RepeatingXor { key, plaintext }, confidence))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{auto_crack, Encoding, FindingKind};
+    use crate::stream::{RepeatingXORCipher, StreamCipher};
+
+    #[test]
+    fn test_plaintext() {
+        let report = auto_crack(b"Now that the party is jumping");
+
+        assert_eq!(report.encoding, Encoding::Raw);
+        assert_eq!(report.plaintext(), Some("Now that the party is jumping"));
+        assert!(matches!(
+            report.best().unwrap().kind,
+            FindingKind::Plaintext { .. }
+        ));
+    }
+
+    #[test]
+    fn test_repeating_xor() {
+        const SOLUTION: &str = include_str!("../tests/set1/6.solution.txt");
+
+        let report = auto_crack(include_bytes!("../tests/set1/6.txt"));
+        let best = report.best().unwrap();
+
+        assert_eq!(report.encoding, Encoding::Base64);
+        assert_eq!(
+            best.kind,
+            FindingKind::RepeatingXor {
+                key: b"Terminator X: Bring the noise".to_vec(),
+                plaintext: SOLUTION.to_string(),
+            }
+        );
+        assert!(best.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_hex_lines() {
+        let report = auto_crack(include_bytes!("../tests/set1/4.txt"));
+        let best = report.best().unwrap();
+
+        assert_eq!(report.encoding, Encoding::HexLines);
+        assert_eq!(best.line, Some(170));
+        assert_eq!(report.plaintext(), Some("Now that the party is jumping\n"));
+
+        let report = auto_crack(include_bytes!("../tests/set1/8.txt"));
+
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].line, Some(132));
+        assert!(matches!(
+            report.findings[0].kind,
+            FindingKind::Ecb {
+                block_size: 16,
+                repeated_blocks: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn test_nothing_found() {
+        let report = auto_crack(include_bytes!("../tests/set1/7.txt"));
+
+        assert_eq!(report.encoding, Encoding::Base64);
+        assert_eq!(report.findings, vec![]);
+
+        let report = auto_crack(b"");
+
+        assert_eq!(report.encoding, Encoding::Raw);
+        assert_eq!(report.findings, vec![]);
+    }
+
+    #[test]
+    fn test_raw_repeating_xor() {
+        let plaintext = "I'm back and I'm ringin' the bell\nA rockin' on the mike while the fly girls yell\nIn ecstasy in the back of me\nWell that's my DJ Deshay cuttin' all them Z's\nHittin' hard and the girlies goin' crazy\nVanilla's on the mike, man I'm not lazy.\n";
+        let ciphertext = RepeatingXORCipher(b"ICE")
+            .process(plaintext.as_bytes())
+            .collect::<Vec<_>>();
+
+        let report = auto_crack(&ciphertext);
+
+        assert_eq!(report.plaintext(), Some(plaintext));
+    }
+}
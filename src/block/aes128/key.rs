@@ -49,6 +49,41 @@ pub fn expand(key: &[u8; 16]) -> ExpandedKey {
     unsafe { expanded.assume_init() }
 }
 
+/// Expand a key of any AES length (128, 192 or 256 bits) into its round
+/// keys, as in FIPS 197 (section 5.2).
+pub fn expand_any(key: &[u8]) -> Vec<super::State> {
+    let key_words = key.len() / 4;
+    let rounds = key_words + 6;
+
+    let mut words = key
+        .chunks(4)
+        .map(|word| [word[0], word[1], word[2], word[3]])
+        .collect::<Vec<_>>();
+
+    for i in key_words..4 * (rounds + 1) {
+        let mut temp = words[i - 1];
+
+        if i % key_words == 0 {
+            temp = key_core(temp, i / key_words);
+        } else if key_words > 6 && i % key_words == 4 {
+            for byte in &mut temp {
+                *byte = super::S[*byte as usize];
+            }
+        }
+
+        for j in 0..4 {
+            temp[j] ^= words[i - key_words][j];
+        }
+
+        words.push(temp);
+    }
+
+    words
+        .chunks(4)
+        .map(|round_key| [round_key[0], round_key[1], round_key[2], round_key[3]])
+        .collect()
+}
+
 fn key_core(input: [u8; 4], iteration: usize) -> [u8; 4] {
     let mut output = input;
 
@@ -147,5 +182,25 @@ mod test {
         ];
 
         assert_eq!(super::expand(&KEY), EXPECTED_EXPANDED_KEY,);
+        assert_eq!(super::expand_any(&KEY), EXPECTED_EXPANDED_KEY,);
+    }
+
+    // From FIPS 197, Appendix A
+    #[test]
+    fn expand_any() {
+        let expanded = super::expand_any(
+            &hex::decode("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b").unwrap(),
+        );
+
+        assert_eq!(expanded.len(), 13);
+        assert_eq!(expanded[12][3], [0x01, 0x00, 0x22, 0x02]);
+
+        let expanded = super::expand_any(
+            &hex::decode("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+                .unwrap(),
+        );
+
+        assert_eq!(expanded.len(), 15);
+        assert_eq!(expanded[14][3], [0x70, 0x6c, 0x63, 0x1e]);
     }
 }
@@ -1,14 +1,19 @@
-//! [AES-128](https://en.wikipedia.org/wiki/Advanced_Encryption_Standard) block-cipher.
+//! [AES](https://en.wikipedia.org/wiki/Advanced_Encryption_Standard) block-ciphers
+//! (AES-128, AES-192 and AES-256).
+//!
+//! All three share the same round function, and only differ in their key
+//! schedule and number of rounds (10, 12 and 14).
 
 mod key;
 
 use itertools::iproduct;
 
-use crate::block::BlockCipher;
+use crate::block::{BlockCipher, Key, KeySchedule};
 
 type State = [[u8; 4]; 4];
 
-const S: [u8; 256] = [
+/// The S-box.
+pub(crate) const S: [u8; 256] = [
     0x63, 0x7C, 0x77, 0x7B, 0xF2, 0x6B, 0x6F, 0xC5, 0x30, 0x01, 0x67, 0x2B, 0xFE, 0xD7, 0xAB, 0x76,
     0xCA, 0x82, 0xC9, 0x7D, 0xFA, 0x59, 0x47, 0xF0, 0xAD, 0xD4, 0xA2, 0xAF, 0x9C, 0xA4, 0x72, 0xC0,
     0xB7, 0xFD, 0x93, 0x26, 0x36, 0x3F, 0xF7, 0xCC, 0x34, 0xA5, 0xE5, 0xF1, 0x71, 0xD8, 0x31, 0x15,
@@ -177,66 +182,143 @@ impl BlockCipher for AES128 {
 
     /// Encrypt `plaintext` in AES128 with `key`.
     fn encrypt_impl(&self, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
-        let expanded_key = key::expand(&AES128::key_from_slice(key));
-
-        let mut state = [
-            [plaintext[0], plaintext[1], plaintext[2], plaintext[3]],
-            [plaintext[4], plaintext[5], plaintext[6], plaintext[7]],
-            [plaintext[8], plaintext[9], plaintext[10], plaintext[11]],
-            [plaintext[12], plaintext[13], plaintext[14], plaintext[15]],
-        ];
-
-        add_round_key(&mut state, expanded_key[0]);
-
-        for i in 1..10 {
-            sub_bytes(&mut state);
-            shift_rows(&mut state);
-            mix_columns(&mut state);
-            add_round_key(&mut state, expanded_key[i]);
-        }
+        encrypt_rounds(&key::expand(&AES128::key_from_slice(key)), plaintext)
+    }
 
-        sub_bytes(&mut state);
-        shift_rows(&mut state);
-        add_round_key(&mut state, expanded_key[10]);
+    /// Decrypt `ciphertext` in AES128 with `key`.
+    fn decrypt_impl(&self, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
+        decrypt_rounds(&key::expand(&AES128::key_from_slice(key)), ciphertext)
+    }
+}
+
+/// [AES-192](https://en.wikipedia.org/wiki/Advanced_Encryption_Standard) block-cipher implementation.
+pub struct AES192;
+
+impl BlockCipher for AES192 {
+    const BLOCK_SIZE: usize = 16;
+    const KEY_SIZE: usize = 24;
 
-        iproduct!(0..4, 0..4)
-            .map(|(col, row)| state[col][row])
-            .collect()
+    /// Encrypt `plaintext` in AES192 with `key`.
+    fn encrypt_impl(&self, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
+        encrypt_rounds(&key::expand_any(key), plaintext)
     }
 
-    /// Decrypt `ciphertext` in AES128 with `key`.
+    /// Decrypt `ciphertext` in AES192 with `key`.
     fn decrypt_impl(&self, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
-        let expanded_key = key::expand(&AES128::key_from_slice(key));
-
-        let mut state = [
-            [ciphertext[0], ciphertext[1], ciphertext[2], ciphertext[3]],
-            [ciphertext[4], ciphertext[5], ciphertext[6], ciphertext[7]],
-            [ciphertext[8], ciphertext[9], ciphertext[10], ciphertext[11]],
-            [
-                ciphertext[12],
-                ciphertext[13],
-                ciphertext[14],
-                ciphertext[15],
-            ],
-        ];
-
-        add_round_key(&mut state, expanded_key[10]);
-
-        for i in (1..10).rev() {
-            inv_shift_rows(&mut state);
-            inv_sub_bytes(&mut state);
-            add_round_key(&mut state, expanded_key[i]);
-            inv_mix_columns(&mut state);
+        decrypt_rounds(&key::expand_any(key), ciphertext)
+    }
+}
+
+/// [AES-256](https://en.wikipedia.org/wiki/Advanced_Encryption_Standard) block-cipher implementation.
+pub struct AES256;
+
+impl BlockCipher for AES256 {
+    const BLOCK_SIZE: usize = 16;
+    const KEY_SIZE: usize = 32;
+
+    /// Encrypt `plaintext` in AES256 with `key`.
+    fn encrypt_impl(&self, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
+        encrypt_rounds(&key::expand_any(key), plaintext)
+    }
+
+    /// Decrypt `ciphertext` in AES256 with `key`.
+    fn decrypt_impl(&self, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
+        decrypt_rounds(&key::expand_any(key), ciphertext)
+    }
+}
+
+/// Expanded AES key (for any key length), as returned by
+/// [`KeySchedule::expand_key`].
+#[derive(Clone)]
+pub struct RoundKeys(Vec<State>);
+
+impl std::fmt::Debug for RoundKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RoundKeys").finish_non_exhaustive()
+    }
+}
+
+macro_rules! impl_key_schedule {
+    ($cipher:ty) => {
+        impl KeySchedule for $cipher {
+            type RoundKeys = RoundKeys;
+
+            fn expand_key(&self, key: &Key<Self>) -> RoundKeys {
+                RoundKeys(key::expand_any(key))
+            }
+
+            fn encrypt_expanded(&self, plaintext: &[u8], round_keys: &RoundKeys) -> Vec<u8> {
+                encrypt_rounds(&round_keys.0, plaintext)
+            }
+
+            fn decrypt_expanded(&self, ciphertext: &[u8], round_keys: &RoundKeys) -> Vec<u8> {
+                decrypt_rounds(&round_keys.0, ciphertext)
+            }
         }
+    };
+}
+
+impl_key_schedule!(AES128);
+impl_key_schedule!(AES192);
+impl_key_schedule!(AES256);
+
+fn load_state(block: &[u8]) -> State {
+    [
+        [block[0], block[1], block[2], block[3]],
+        [block[4], block[5], block[6], block[7]],
+        [block[8], block[9], block[10], block[11]],
+        [block[12], block[13], block[14], block[15]],
+    ]
+}
+
+fn store_state(state: &State) -> Vec<u8> {
+    iproduct!(0..4, 0..4)
+        .map(|(col, row)| state[col][row])
+        .collect()
+}
+
+/// Encrypt a `plaintext` block with the expanded `round_keys` (one more than
+/// the number of rounds).
+fn encrypt_rounds(round_keys: &[State], plaintext: &[u8]) -> Vec<u8> {
+    let rounds = round_keys.len() - 1;
+    let mut state = load_state(plaintext);
+
+    add_round_key(&mut state, round_keys[0]);
+
+    for i in 1..rounds {
+        sub_bytes(&mut state);
+        shift_rows(&mut state);
+        mix_columns(&mut state);
+        add_round_key(&mut state, round_keys[i]);
+    }
+
+    sub_bytes(&mut state);
+    shift_rows(&mut state);
+    add_round_key(&mut state, round_keys[rounds]);
+
+    store_state(&state)
+}
 
+/// Decrypt a `ciphertext` block with the expanded `round_keys` (one more
+/// than the number of rounds).
+fn decrypt_rounds(round_keys: &[State], ciphertext: &[u8]) -> Vec<u8> {
+    let rounds = round_keys.len() - 1;
+    let mut state = load_state(ciphertext);
+
+    add_round_key(&mut state, round_keys[rounds]);
+
+    for i in (1..rounds).rev() {
         inv_shift_rows(&mut state);
         inv_sub_bytes(&mut state);
-        add_round_key(&mut state, expanded_key[0]);
-
-        iproduct!(0..4, 0..4)
-            .map(|(col, row)| state[col][row])
-            .collect()
+        add_round_key(&mut state, round_keys[i]);
+        inv_mix_columns(&mut state);
     }
+
+    inv_shift_rows(&mut state);
+    inv_sub_bytes(&mut state);
+    add_round_key(&mut state, round_keys[0]);
+
+    store_state(&state)
 }
 
 fn add_round_key(state: &mut State, round_key: State) {
@@ -325,7 +407,7 @@ fn inv_mix_columns(state: &mut State) {
 mod test {
     use test::Bencher;
 
-    use crate::block::BlockCipher;
+    use crate::block::{BlockCipher, Iv, Key, KeySchedule};
 
     const PLAINTEXT: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
     const KEY: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
@@ -335,16 +417,80 @@ mod test {
 
     #[test]
     fn encrypt() {
-        let encrypted = super::AES128.encrypt_block(&PLAINTEXT, &KEY);
+        let encrypted = super::AES128.encrypt_block(&PLAINTEXT, &Key::new(&KEY).unwrap());
         assert_eq!(encrypted, CIPHERTEXT);
     }
 
     #[test]
     fn decrypt() {
-        let decrypted = super::AES128.decrypt_block(&CIPHERTEXT, &KEY);
+        let decrypted = super::AES128.decrypt_block(&CIPHERTEXT, &Key::new(&KEY).unwrap());
         assert_eq!(decrypted, PLAINTEXT);
     }
 
+    // From FIPS 197, Appendix C
+    const FIPS_PLAINTEXT: &str = "00112233445566778899aabbccddeeff";
+
+    fn fips_key<C: BlockCipher>() -> Key<C> {
+        Key::new(&(0..C::KEY_SIZE as u8).collect::<Vec<_>>()).unwrap()
+    }
+
+    fn check_fips<C: KeySchedule>(cipher: &C, expected: &str) {
+        let plaintext = hex::decode(FIPS_PLAINTEXT).unwrap();
+        let key = fips_key::<C>();
+
+        let ciphertext = cipher.encrypt_block(&plaintext, &key);
+
+        assert_eq!(hex::encode(&ciphertext), expected);
+        assert_eq!(cipher.decrypt_block(&ciphertext, &key), plaintext);
+
+        let round_keys = cipher.expand_key(&key);
+
+        assert_eq!(cipher.encrypt_expanded(&plaintext, &round_keys), ciphertext);
+        assert_eq!(cipher.decrypt_expanded(&ciphertext, &round_keys), plaintext);
+    }
+
+    #[test]
+    fn fips_aes128() {
+        check_fips(&super::AES128, "69c4e0d86a7b0430d8cdb78070b4c55a");
+    }
+
+    #[test]
+    fn fips_aes192() {
+        check_fips(&super::AES192, "dda97ca4864cdfe06eaf70a0ec0d7191");
+    }
+
+    #[test]
+    fn fips_aes256() {
+        check_fips(&super::AES256, "8ea2b7ca516745bfeafc49904b496089");
+    }
+
+    #[test]
+    fn modes_aes256() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        use crate::block::{BlockMode, CBC};
+        use crate::testsupport::fuzz_ctr;
+
+        const MESSAGE: &[u8] = b"Bigger keys work with block modes too";
+
+        let key = fips_key::<super::AES256>();
+        let iv = Iv::new(&[0; 16]).unwrap();
+        let cbc = CBC::new(&iv);
+
+        let ciphertext = cbc.encrypt(&super::AES256, MESSAGE, &key);
+
+        assert_eq!(
+            cbc.decrypt(&super::AES256, &ciphertext, &key).unwrap(),
+            MESSAGE
+        );
+
+        let mut rng = StdRng::seed_from_u64(1337);
+
+        assert_eq!(fuzz_ctr(&super::AES192, 8, 100, &mut rng), Ok(()));
+        assert_eq!(fuzz_ctr(&super::AES256, 8, 100, &mut rng), Ok(()));
+    }
+
     const SUB_BYTES_INPUT: super::State = [
         [0x0f, 0x14, 0x73, 0xca],
         [0x43, 0xdc, 0xee, 0x5e],
@@ -431,11 +577,15 @@ mod test {
 
     #[bench]
     fn bench_aes128_encrypt(b: &mut Bencher) {
-        b.iter(|| super::AES128.encrypt_block(&BENCH_BUFFER, &BENCH_BUFFER))
+        let key = Key::new(&BENCH_BUFFER).unwrap();
+
+        b.iter(|| super::AES128.encrypt_block(&BENCH_BUFFER, &key))
     }
 
     #[bench]
     fn bench_aes128_decrypt(b: &mut Bencher) {
-        b.iter(|| super::AES128.decrypt_block(&BENCH_BUFFER, &BENCH_BUFFER))
+        let key = Key::new(&BENCH_BUFFER).unwrap();
+
+        b.iter(|| super::AES128.decrypt_block(&BENCH_BUFFER, &key))
     }
 }
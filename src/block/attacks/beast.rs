@@ -0,0 +1,180 @@
+//! [BEAST](https://en.wikipedia.org/wiki/Transport_Layer_Security#BEAST_attack)-style
+//! chosen-plaintext attack on CBC with predictable IVs.
+//!
+//! SSL 3.0 and TLS 1.0 derive the first IV from the key material and then use
+//! the last ciphertext block of each record as the IV of the next one. Since
+//! the attacker sees that block on the wire, they know the IV of the next
+//! record _before_ choosing its plaintext, which turns CBC into an ECB-like
+//! oracle: encrypting `P ^ C_prev ^ IV_next` yields the same block as `P` did
+//! after `C_prev`.
+
+use crate::block::params::{Iv, Key};
+use crate::block::{BlockCipher, BlockMode, CBC};
+
+/// A TLS 1.0-like encrypted channel, where every record continues the CBC
+/// chain of the previous one.
+///
+/// The victim's requests end with a `secret` (think of a session cookie) and
+/// the attacker can both choose a prefix for them (e.g. the request path) and
+/// inject records of their own (e.g. through a WebSocket).
+pub struct ChainedCBCOracle<C: BlockCipher> {
+    cipher: C,
+    key: Key<C>,
+    next_iv: Iv<C>,
+    secret: Vec<u8>,
+}
+
+impl<C: BlockCipher> ChainedCBCOracle<C> {
+    /// Create a channel with a random key and initial IV.
+    #[must_use]
+    pub fn new(cipher: C, secret: &[u8]) -> Self {
+        ChainedCBCOracle {
+            cipher,
+            key: Key::random(),
+            next_iv: Iv::random(),
+            secret: secret.to_vec(),
+        }
+    }
+
+    /// Encrypt a victim request (`prefix || secret`) as the next record.
+    pub fn send_request(&mut self, prefix: &[u8]) -> Vec<u8> {
+        let plaintext = [prefix, &self.secret].concat();
+
+        self.send_record(&plaintext)
+    }
+
+    /// Encrypt an attacker-controlled `plaintext` as the next record.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn send_record(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let ciphertext = CBC::new(&self.next_iv).encrypt(&self.cipher, plaintext, &self.key);
+
+        self.next_iv = Iv::new(&ciphertext[ciphertext.len() - C::BLOCK_SIZE..])
+            .expect("CBC ciphertexts are made of whole blocks");
+
+        ciphertext
+    }
+}
+
+/// Given an encryption `oracle` that is fed attacker-controlled records, detect
+/// whether each record's IV is the last ciphertext block of the previous one.
+pub fn has_chained_iv<O>(mut oracle: O, block_size: usize) -> bool
+where
+    O: FnMut(&[u8]) -> Vec<u8>,
+{
+    let probe = vec![0; block_size];
+
+    let first_iv = last_block(&oracle(&probe), block_size);
+
+    let expected = oracle(&probe);
+    let second_iv = last_block(&expected, block_size);
+
+    let replayed = probe
+        .iter()
+        .zip(&first_iv)
+        .zip(&second_iv)
+        .map(|((p, a), b)| p ^ a ^ b)
+        .collect::<Vec<_>>();
+
+    oracle(&replayed)[..block_size] == expected[..block_size]
+}
+
+/// Recover the secret appended to the victim's requests, byte by byte.
+///
+/// For each byte, a request is sent with a prefix that leaves it as the last
+/// byte of a block (preceded by already known bytes). Then every candidate for
+/// that block is injected, adjusted to the (predictable) IV of the injected
+/// record, until one encrypts to the same ciphertext block.
+///
+/// Returns `None` if no candidate matches (e.g. the IVs are not predictable).
+pub fn recover_secret<C: BlockCipher>(oracle: &mut ChainedCBCOracle<C>) -> Option<Vec<u8>> {
+    let block_size = C::BLOCK_SIZE;
+
+    let base_len = oracle.send_request(b"").len();
+
+    let secret_len = (1..=block_size).find_map(|prefix_len| {
+        let len = oracle.send_request(&vec![0; prefix_len]).len();
+
+        (len > base_len).then(|| base_len - prefix_len)
+    })?;
+
+    // Any record going by reveals the IV of the next one
+    let mut next_iv = last_block(&oracle.send_record(b""), block_size);
+
+    let mut known = Vec::with_capacity(secret_len);
+
+    for i in 0..secret_len {
+        let prefix_len = block_size - 1 - i % block_size;
+        let prefix = vec![b'A'; prefix_len];
+        let block_index = (prefix_len + i) / block_size;
+
+        let request_iv = next_iv.clone();
+        let ciphertext = oracle.send_request(&prefix);
+        next_iv = last_block(&ciphertext, block_size);
+
+        let previous = if block_index == 0 {
+            &request_iv
+        } else {
+            &ciphertext[(block_index - 1) * block_size..block_index * block_size]
+        };
+        let target = &ciphertext[block_index * block_size..(block_index + 1) * block_size];
+
+        let mut candidate = [prefix, known.clone()].concat()[block_index * block_size..].to_vec();
+        candidate.push(0);
+
+        let byte = (0..=255).find(|&byte| {
+            candidate[block_size - 1] = byte;
+
+            let injected = candidate
+                .iter()
+                .zip(previous)
+                .zip(&next_iv)
+                .map(|((c, p), iv)| c ^ p ^ iv)
+                .collect::<Vec<_>>();
+
+            let injected_ciphertext = oracle.send_record(&injected);
+            next_iv = last_block(&injected_ciphertext, block_size);
+
+            &injected_ciphertext[..block_size] == target
+        })?;
+
+        known.push(byte);
+    }
+
+    Some(known)
+}
+
+fn last_block(ciphertext: &[u8], block_size: usize) -> Vec<u8> {
+    ciphertext[ciphertext.len() - block_size..].to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{has_chained_iv, recover_secret, ChainedCBCOracle};
+    use crate::block::{BlockCipher, Key, AES128, CBC};
+
+    const SECRET: &[u8] = b"Cookie: session=7d6f5a3e9b01c2d4";
+
+    #[test]
+    fn test_has_chained_iv() {
+        let mut oracle = ChainedCBCOracle::new(AES128, SECRET);
+
+        assert!(has_chained_iv(
+            |plaintext| oracle.send_record(plaintext),
+            AES128::BLOCK_SIZE
+        ));
+
+        let key = Key::new(b"YELLOW SUBMARINE").unwrap();
+
+        assert!(!has_chained_iv(
+            |plaintext| CBC::encrypt_with_random_iv(&AES128, plaintext, &key),
+            AES128::BLOCK_SIZE
+        ));
+    }
+
+    #[test]
+    fn test_recover_secret() {
+        let mut oracle = ChainedCBCOracle::new(AES128, SECRET);
+
+        assert_eq!(recover_secret(&mut oracle).as_deref(), Some(SECRET));
+    }
+}
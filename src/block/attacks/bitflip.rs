@@ -0,0 +1,285 @@
+//! Bit-flipping attacks on malleable encryption, as in [Cryptopals challenge
+//! 16](https://cryptopals.com/sets/2/challenges/16) (CBC) and [challenge
+//! 26](https://cryptopals.com/sets/4/challenges/26) (CTR).
+//!
+//! XORing a ciphertext byte XORs the same value into some plaintext byte: the
+//! same position in CTR, or the same position of the _next_ block in CBC (at
+//! the cost of garbling the block whose ciphertext was flipped).
+//!
+//! [`make_bitflip_mask`] plans those flips, checking the edit will survive
+//! decryption: no flips through the IV, no targets in garbled blocks, and
+//! valid PKCS#7 padding afterwards.
+
+use crate::block::pkcs7;
+
+/// How flipping ciphertext bits affects the plaintext.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlipMode {
+    /// Flips the next plaintext block, garbling the current one.
+    CBC,
+
+    /// Flips the same plaintext byte.
+    CTR,
+}
+
+/// Why a bit-flip plan could not be made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitflipError {
+    /// There are not as many XOR values as target positions.
+    LengthMismatch,
+
+    /// The target position is past the end of the padded plaintext.
+    OutOfRange { position: usize },
+
+    /// The target position is in the first CBC block (it would need flipping
+    /// the IV).
+    FirstBlock { position: usize },
+
+    /// The target position is in a block garbled by another flip.
+    Garbled { position: usize },
+
+    /// The target position is the last padding byte, so no valid padding can
+    /// be kept.
+    BreaksPadding { position: usize },
+}
+
+/// A planned edit of a ciphertext.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitflipPlan {
+    flips: Vec<(usize, u8)>,
+    garbled_blocks: Vec<usize>,
+}
+
+impl BitflipPlan {
+    /// Ciphertext positions and the values to XOR them with.
+    #[must_use]
+    pub fn flips(&self) -> &[(usize, u8)] {
+        &self.flips
+    }
+
+    /// Indices of the plaintext blocks that will decrypt to garbage.
+    #[must_use]
+    pub fn garbled_blocks(&self) -> &[usize] {
+        &self.garbled_blocks
+    }
+
+    /// Apply the planned flips to `ciphertext`.
+    ///
+    /// # Panics
+    ///
+    /// If `ciphertext` is shorter than the plan expects.
+    #[must_use]
+    pub fn apply(&self, ciphertext: &[u8]) -> Vec<u8> {
+        let mut flipped = ciphertext.to_vec();
+
+        for &(position, xor) in &self.flips {
+            flipped[position] ^= xor;
+        }
+
+        flipped
+    }
+}
+
+/// Plan the ciphertext flips XORing `desired_xor[i]` into the plaintext byte
+/// at `target_positions[i]`.
+///
+/// `plaintext_len` is the length of the plaintext before PKCS#7 padding. If
+/// targets fall into the padding, the rest of it is rewritten so that it is
+/// still valid (shortened to end right after the last target).
+///
+/// # Errors
+///
+/// See [`BitflipError`].
+///
+/// # Example
+///
+/// ```
+/// use rustopals::block::attacks::bitflip::{make_bitflip_mask, FlipMode};
+/// use rustopals::block::{BlockMode, Iv, Key, AES128, CBC};
+///
+/// let key = Key::new(b"YELLOW SUBMARINE").unwrap();
+/// let cbc = CBC::new(&Iv::zero());
+///
+/// let plaintext = b"0123456789abcdef;admin=0";
+/// let ciphertext = cbc.encrypt(&AES128, plaintext, &key);
+///
+/// let plan = make_bitflip_mask(FlipMode::CBC, &[23], &[b'0' ^ b'1'], 16, plaintext.len())
+///     .unwrap();
+///
+/// let decrypted = cbc
+///     .decrypt(&AES128, &plan.apply(&ciphertext), &key)
+///     .unwrap();
+///
+/// assert_eq!(plan.garbled_blocks(), &[0]);
+/// assert_eq!(&decrypted[16..], b";admin=1");
+/// ```
+pub fn make_bitflip_mask(
+    mode: FlipMode,
+    target_positions: &[usize],
+    desired_xor: &[u8],
+    block_size: usize,
+    plaintext_len: usize,
+) -> Result<BitflipPlan, BitflipError> {
+    if target_positions.len() != desired_xor.len() {
+        return Err(BitflipError::LengthMismatch);
+    }
+
+    let padded_len = pkcs7::pad(&vec![0; plaintext_len], block_size as u8).len();
+
+    let mut edits = target_positions
+        .iter()
+        .copied()
+        .zip(desired_xor.iter().copied())
+        .collect::<Vec<_>>();
+
+    if let Some(position) = target_positions.iter().copied().find(|&p| p >= padded_len) {
+        return Err(BitflipError::OutOfRange { position });
+    }
+
+    // Keep a valid padding, ending right after the last target
+    if let Some(last) = target_positions
+        .iter()
+        .copied()
+        .filter(|&p| p >= plaintext_len)
+        .max()
+    {
+        if last == padded_len - 1 {
+            return Err(BitflipError::BreaksPadding { position: last });
+        }
+
+        let old_padding = (padded_len - plaintext_len) as u8;
+        let new_padding = (padded_len - last - 1) as u8;
+
+        edits.extend((last + 1..padded_len).map(|position| (position, old_padding ^ new_padding)));
+    }
+
+    let garbled_blocks = match mode {
+        FlipMode::CTR => vec![],
+        FlipMode::CBC => {
+            let mut blocks = edits
+                .iter()
+                .map(|&(position, _)| position / block_size)
+                .collect::<Vec<_>>();
+            blocks.sort_unstable();
+            blocks.dedup();
+
+            if blocks.first() == Some(&0) {
+                let position = edits.iter().map(|&(p, _)| p).min().unwrap_or(0);
+
+                return Err(BitflipError::FirstBlock { position });
+            }
+
+            let garbled = blocks.iter().map(|block| block - 1).collect::<Vec<_>>();
+
+            if let Some(&(position, _)) = edits
+                .iter()
+                .find(|&&(position, _)| garbled.contains(&(position / block_size)))
+            {
+                return Err(BitflipError::Garbled { position });
+            }
+
+            garbled
+        },
+    };
+
+    let flips = edits
+        .into_iter()
+        .map(|(position, xor)| match mode {
+            FlipMode::CTR => (position, xor),
+            FlipMode::CBC => (position - block_size, xor),
+        })
+        .collect();
+
+    Ok(BitflipPlan {
+        flips,
+        garbled_blocks,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{make_bitflip_mask, BitflipError, FlipMode};
+    use crate::block::{BlockMode, Iv, Key, Nonce, AES128, CBC};
+    use crate::stream::{StreamCipher, CTR};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const IV: &[u8] = &[0; 16];
+    const PLAINTEXT: &[u8] = b"YELLOW SUBMARINE1234";
+
+    fn key() -> Key<AES128> {
+        Key::new(KEY).unwrap()
+    }
+
+    fn xors(from: &[u8], to: &[u8]) -> Vec<u8> {
+        from.iter().zip(to).map(|(a, b)| a ^ b).collect()
+    }
+
+    #[test]
+    fn test_cbc_into_padding() {
+        let cbc = CBC::new(&Iv::new(IV).unwrap());
+        let ciphertext = cbc.encrypt(&AES128, PLAINTEXT, &key());
+
+        // Padding is 12 bytes of 0x0c
+        let plan = make_bitflip_mask(
+            FlipMode::CBC,
+            &[20, 21, 22],
+            &xors(&[0x0c; 3], b"ABC"),
+            16,
+            20,
+        )
+        .unwrap();
+
+        let decrypted = cbc
+            .decrypt(&AES128, &plan.apply(&ciphertext), &key())
+            .unwrap();
+
+        assert_eq!(&decrypted[16..], b"1234ABC");
+    }
+
+    #[test]
+    fn test_ctr_into_padding() {
+        let padded = crate::block::pkcs7::pad(PLAINTEXT, 16);
+        let nonce = Nonce::zero();
+        let ciphertext = CTR::from_nonce(&AES128, &key(), &nonce)
+            .process(&padded)
+            .collect::<Vec<_>>();
+
+        let plan = make_bitflip_mask(FlipMode::CTR, &[0, 20], &[b'Y' ^ b'M', 0x0c ^ b'!'], 16, 20)
+            .unwrap();
+
+        assert_eq!(plan.garbled_blocks(), &[]);
+
+        let mut decrypted = CTR::from_nonce(&AES128, &key(), &nonce)
+            .process(plan.apply(&ciphertext))
+            .collect::<Vec<_>>();
+
+        crate::block::pkcs7::unpad_vec(&mut decrypted, 16).unwrap();
+
+        assert_eq!(decrypted, b"MELLOW SUBMARINE1234!");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            make_bitflip_mask(FlipMode::CBC, &[20], &[], 16, 20),
+            Err(BitflipError::LengthMismatch)
+        );
+        assert_eq!(
+            make_bitflip_mask(FlipMode::CTR, &[32], &[1], 16, 20),
+            Err(BitflipError::OutOfRange { position: 32 })
+        );
+        assert_eq!(
+            make_bitflip_mask(FlipMode::CTR, &[31], &[1], 16, 20),
+            Err(BitflipError::BreaksPadding { position: 31 })
+        );
+        assert_eq!(
+            make_bitflip_mask(FlipMode::CBC, &[3], &[1], 16, 20),
+            Err(BitflipError::FirstBlock { position: 3 })
+        );
+        assert_eq!(
+            make_bitflip_mask(FlipMode::CBC, &[17, 33], &[1, 1], 16, 40),
+            Err(BitflipError::Garbled { position: 17 })
+        );
+        assert!(make_bitflip_mask(FlipMode::CTR, &[17, 33], &[1, 1], 16, 40).is_ok());
+    }
+}
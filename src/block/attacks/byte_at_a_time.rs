@@ -0,0 +1,358 @@
+//! Byte-at-a-time decryption of a secret appended by an ECB oracle, as in
+//! [Cryptopals challenges 12 and
+//! 14](https://cryptopals.com/sets/2/challenges/12).
+//!
+//! Padding the input so that an unknown byte is the last one of its block
+//! leaves a block whose first bytes are known. Guessing the last byte means
+//! finding which `window || guess` block encrypts the same way.
+//!
+//! The textbook attack asks the oracle once per guess (~128 calls per byte).
+//! [`QueryPlanner`] batches them instead, since ECB encrypts each block on
+//! its own:
+//!
+//! - All 256 guesses for a window go into a single input, one per block.
+//! - The target blocks only depend on the input length, so one query per
+//!   alignment reveals them for every byte.
+//! - Every block encrypted so far is kept in a codebook, so a repeated block
+//!   is decrypted without asking again.
+//!
+//! So it needs at most one call per byte (plus one per alignment).
+//!
+//! If the oracle prepends some unknown data to the input, its length can be
+//! found first with [`discover_prefix_len`].
+
+use std::collections::HashMap;
+
+/// Byte used to fill the input up to the desired alignment.
+const FILLER: u8 = 0;
+
+/// Pairs of probe bytes for [`discover_prefix_len`].
+const PROBES: [(u8, u8); 3] = [(b'A', b'B'), (b'C', b'D'), (b'E', b'F')];
+
+/// Result of [`discover_prefix_len`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrefixDiscovery {
+    /// The prefix length found by most probes, if any.
+    pub prefix_len: Option<usize>,
+
+    /// Fraction of the probes agreeing on `prefix_len`.
+    pub confidence: f64,
+
+    /// The prefix length found by each probe (`None` if it never aligned).
+    pub probes: Vec<Option<usize>>,
+
+    /// Total oracle calls.
+    pub oracle_calls: usize,
+}
+
+/// Find the length of the data an ECB `oracle` prepends to the input.
+///
+/// Each probe grows some filler before two blocks of a byte followed by two
+/// blocks of another byte, until those 4 blocks get aligned (i.e. they
+/// encrypt to `X X Y Y`). Since the runs of each byte are bounded by
+/// different bytes, this can only happen when they are exactly aligned, even
+/// if the prefix or the secret contain the probe bytes.
+///
+/// Repeated blocks around the input could look like a match too, so the 4
+/// blocks must also change when using the next probe's bytes. Every probe
+/// votes, so that a coincidence does not go unnoticed.
+pub fn discover_prefix_len(
+    oracle: impl Fn(&[u8]) -> Vec<u8>,
+    block_size: usize,
+) -> PrefixDiscovery {
+    let mut probes = vec![None; PROBES.len()];
+    let mut oracle_calls = 0;
+
+    for filler_len in 0..block_size {
+        let ciphertexts = PROBES
+            .iter()
+            .map(|&(a, b)| {
+                oracle_calls += 1;
+
+                oracle(
+                    &[
+                        vec![FILLER; filler_len],
+                        vec![a; 2 * block_size],
+                        vec![b; 2 * block_size],
+                    ]
+                    .concat(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (k, probe) in probes.iter_mut().enumerate() {
+            if probe.is_some() {
+                continue;
+            }
+
+            let blocks = ciphertexts[k].chunks_exact(block_size).collect::<Vec<_>>();
+            let reference = ciphertexts[(k + 1) % PROBES.len()]
+                .chunks_exact(block_size)
+                .collect::<Vec<_>>();
+
+            *probe = (0..blocks.len().saturating_sub(3))
+                .find(|&i| {
+                    let window = &blocks[i..i + 4];
+
+                    window[0] == window[1]
+                        && window[2] == window[3]
+                        && window[0] != window[2]
+                        && (i..i + 4).all(|j| reference.get(j) != Some(&blocks[j]))
+                })
+                .and_then(|i| (i * block_size).checked_sub(filler_len));
+        }
+
+        if probes.iter().all(Option::is_some) {
+            break;
+        }
+    }
+
+    let (prefix_len, votes) = probes
+        .iter()
+        .flatten()
+        .map(|&candidate| {
+            (
+                candidate,
+                probes.iter().filter(|&&p| p == Some(candidate)).count(),
+            )
+        })
+        .max_by_key(|&(_, votes)| votes)
+        .map_or((None, 0), |(prefix_len, votes)| (Some(prefix_len), votes));
+
+    PrefixDiscovery {
+        prefix_len,
+        confidence: votes as f64 / probes.len() as f64,
+        probes,
+        oracle_calls,
+    }
+}
+
+/// Plans the oracle queries for byte-at-a-time ECB decryption, caching their
+/// results.
+pub struct QueryPlanner<F> {
+    oracle: F,
+    block_size: usize,
+    prefix_len: usize,
+    calls: usize,
+    targets: HashMap<usize, Vec<u8>>,
+    codebook: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl<F: Fn(&[u8]) -> Vec<u8>> QueryPlanner<F> {
+    /// Plan queries to an `oracle` encrypting `prefix || input || secret`
+    /// with ECB, where `prefix` is `prefix_len` bytes long.
+    pub fn new(oracle: F, block_size: usize, prefix_len: usize) -> QueryPlanner<F> {
+        QueryPlanner {
+            oracle,
+            block_size,
+            prefix_len,
+            calls: 0,
+            targets: HashMap::new(),
+            codebook: HashMap::new(),
+        }
+    }
+
+    /// Amount of oracle calls so far.
+    #[must_use]
+    pub const fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// Decrypt the first `secret_len` bytes of the secret.
+    ///
+    /// Returns `None` if some byte does not match any guess (e.g. if the
+    /// oracle is not ECB or the parameters are wrong).
+    pub fn decrypt(&mut self, secret_len: usize) -> Option<Vec<u8>> {
+        let mut known = Vec::with_capacity(secret_len);
+
+        for _ in 0..secret_len {
+            let byte = self.decrypt_next(&known)?;
+
+            known.push(byte);
+        }
+
+        Some(known)
+    }
+
+    /// Decrypt the secret byte right after the `known` ones.
+    pub fn decrypt_next(&mut self, known: &[u8]) -> Option<u8> {
+        let block_size = self.block_size;
+        let position = known.len();
+
+        // Push the byte to the end of its block
+        let shift = block_size - 1 - position % block_size;
+        let block = (self.prefix_len + self.align_len() + shift + position) / block_size;
+
+        let target = self
+            .target(shift)
+            .get(block * block_size..(block + 1) * block_size)?
+            .to_vec();
+
+        let window = [&vec![FILLER; shift][..], known].concat();
+        let window = &window[window.len() - (block_size - 1)..];
+
+        if !self.codebook.contains_key(&target) {
+            self.query_guesses(window);
+        }
+
+        let plaintext = self.codebook.get(&target)?;
+
+        (&plaintext[..block_size - 1] == window).then(|| plaintext[block_size - 1])
+    }
+
+    /// Amount of filler bytes needed to start the input at a block boundary.
+    const fn align_len(&self) -> usize {
+        (self.block_size - self.prefix_len % self.block_size) % self.block_size
+    }
+
+    fn query(&mut self, input: &[u8]) -> Vec<u8> {
+        self.calls += 1;
+
+        (self.oracle)(&[&vec![FILLER; self.align_len()][..], input].concat())
+    }
+
+    /// Ciphertext for an input of `shift` filler bytes (memoized).
+    fn target(&mut self, shift: usize) -> &[u8] {
+        if !self.targets.contains_key(&shift) {
+            let ciphertext = self.query(&vec![FILLER; shift]);
+
+            self.learn(&vec![FILLER; shift], &ciphertext);
+            self.targets.insert(shift, ciphertext);
+        }
+
+        &self.targets[&shift]
+    }
+
+    /// Encrypt `window || guess` for every possible `guess`, in one query.
+    fn query_guesses(&mut self, window: &[u8]) {
+        let input = (0..=u8::MAX)
+            .flat_map(|guess| [window, &[guess]].concat())
+            .collect::<Vec<_>>();
+
+        let ciphertext = self.query(&input);
+
+        self.learn(&input, &ciphertext);
+    }
+
+    /// Add the blocks of a (block-aligned) `input` to the codebook.
+    fn learn(&mut self, input: &[u8], ciphertext: &[u8]) {
+        let first = (self.prefix_len + self.align_len()) / self.block_size;
+
+        for (plaintext, ciphertext) in input
+            .chunks_exact(self.block_size)
+            .zip(ciphertext.chunks_exact(self.block_size).skip(first))
+        {
+            self.codebook
+                .insert(ciphertext.to_vec(), plaintext.to_vec());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::{discover_prefix_len, QueryPlanner};
+    use crate::block::{pkcs7, BlockCipher, BlockMode, Iv, Key, AES128, CBC, ECB};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const SECRET: &[u8] = b"Rollin' in my 5.0\nWith my rag-top down so my hair can blow\n\
+        The girlies on standby waving just to say hi\nDid you stop? No, I just drove by\n";
+
+    fn key() -> Key<AES128> {
+        Key::new(KEY).unwrap()
+    }
+
+    /// Calls needed by the textbook attack: one for the target block and one
+    /// per guess, trying them in order.
+    fn naive_calls(secret: &[u8]) -> usize {
+        secret.iter().map(|&byte| 2 + usize::from(byte)).sum()
+    }
+
+    #[test]
+    fn test_decrypt() {
+        for prefix_len in [0, 1, 15, 16, 21] {
+            let prefix = vec![0x42; prefix_len];
+            let calls = Cell::new(0);
+
+            let oracle = |input: &[u8]| {
+                calls.set(calls.get() + 1);
+                ECB.encrypt(&AES128, &[&prefix, input, SECRET].concat(), &key())
+            };
+
+            let mut planner = QueryPlanner::new(oracle, AES128::BLOCK_SIZE, prefix_len);
+
+            assert_eq!(planner.decrypt(SECRET.len()), Some(SECRET.to_vec()));
+            assert_eq!(planner.calls(), calls.get());
+            assert!(planner.calls() <= SECRET.len() + AES128::BLOCK_SIZE);
+            assert!(planner.calls() < naive_calls(SECRET));
+        }
+    }
+
+    #[test]
+    fn test_memoization() {
+        // Repeated blocks are decrypted from the codebook
+        let secret = [&b"0123456789abcdef"[..]; 8].concat();
+
+        let oracle = |input: &[u8]| ECB.encrypt(&AES128, &[input, &secret].concat(), &key());
+        let mut planner = QueryPlanner::new(oracle, AES128::BLOCK_SIZE, 0);
+
+        assert_eq!(planner.decrypt(secret.len()), Some(secret.clone()));
+        assert!(planner.calls() <= 3 * 16);
+        assert!(planner.calls() * 100 < naive_calls(&secret));
+    }
+
+    #[test]
+    fn test_not_ecb() {
+        let oracle = |input: &[u8]| {
+            CBC::new(&Iv::zero()).encrypt(&AES128, &[input, SECRET].concat(), &key())
+        };
+
+        assert_eq!(
+            QueryPlanner::new(oracle, AES128::BLOCK_SIZE, 0).decrypt(SECRET.len()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_discover_prefix_len() {
+        for prefix_len in 0..64 {
+            let prefix = vec![0x42; prefix_len];
+
+            let oracle =
+                |input: &[u8]| ECB.encrypt(&AES128, &[&prefix, input, SECRET].concat(), &key());
+            let discovery = discover_prefix_len(oracle, AES128::BLOCK_SIZE);
+
+            assert_eq!(discovery.prefix_len, Some(prefix_len));
+            assert!((discovery.confidence - 1.0).abs() < f64::EPSILON);
+            assert!(discovery.oracle_calls <= 3 * AES128::BLOCK_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_discover_prefix_len_probe_bytes() {
+        // Data around the input made of the probe bytes (and "encrypted" as
+        // is, which is still ECB)
+        for prefix_len in 0..64 {
+            let oracle = |input: &[u8]| {
+                pkcs7::pad(&[&vec![b'A'; prefix_len], input, &[b'B'; 64]].concat(), 16)
+            };
+
+            assert_eq!(discover_prefix_len(oracle, 16).prefix_len, Some(prefix_len));
+        }
+    }
+
+    #[test]
+    fn test_discover_prefix_len_not_ecb() {
+        let oracle = |input: &[u8]| {
+            CBC::new(&Iv::zero()).encrypt(&AES128, &[input, SECRET].concat(), &key())
+        };
+
+        let discovery = discover_prefix_len(oracle, AES128::BLOCK_SIZE);
+
+        assert_eq!(discovery.prefix_len, None);
+        assert!(discovery.confidence.abs() < f64::EPSILON);
+        assert_eq!(discovery.probes, vec![None; 3]);
+        assert_eq!(discovery.oracle_calls, 3 * AES128::BLOCK_SIZE);
+    }
+}
@@ -0,0 +1,232 @@
+//! ECB cut-and-paste attack on encrypted [`kv`]-encoded records, as in
+//! [Cryptopals challenge 13](https://cryptopals.com/sets/2/challenges/13).
+//!
+//! ECB encrypts each block independently, so ciphertext blocks from
+//! different encryptions can be spliced together. Given an oracle encrypting
+//! a record with an attacker-controlled field:
+//!
+//! 1. **Cut**: align some input to a block boundary and fill the next block(s)
+//!    with the PKCS#7-padded target value.
+//! 2. **Paste**: choose an input length so the record is block-aligned right
+//!    after `target_key=`, truncate there and append the cut block(s).
+//!
+//! Metacharacters cannot be typed, so this only works if the target field
+//! comes **after** the controlled one (otherwise no block ever starts right
+//! after `target_key=`). Fields after the target are dropped.
+
+use crate::block::pkcs7;
+use crate::util::kv;
+
+/// Why a forgery could not be built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CutAndPasteError {
+    /// The block size could not be found.
+    BlockSize,
+
+    /// The length of the data before the input could not be found.
+    PrefixLength,
+
+    /// The input or target key are not in the layout.
+    MissingField,
+
+    /// The target field comes before the input field.
+    TargetBeforeInput,
+
+    /// The target value contains metacharacters.
+    UnencodableValue,
+}
+
+/// Maximum block size to look for.
+const MAX_BLOCK_SIZE: usize = 64;
+
+/// Find the block size as the jump in ciphertext length while growing the
+/// input.
+fn discover_block_size(oracle: &impl Fn(&str) -> Vec<u8>) -> Option<usize> {
+    let empty_len = oracle("").len();
+
+    (1..=MAX_BLOCK_SIZE).find_map(|len| {
+        let jump = oracle(&"A".repeat(len)).len() - empty_len;
+
+        (jump > 0).then_some(jump)
+    })
+}
+
+/// Find the length of the data before the input by growing it until two
+/// identical input blocks get aligned.
+///
+/// Assumes the data around the input has no identical adjacent blocks.
+fn discover_prefix_length(oracle: &impl Fn(&str) -> Vec<u8>, block_size: usize) -> Option<usize> {
+    (0..block_size).find_map(|filler_len| {
+        let input = ["A".repeat(filler_len), "X".repeat(2 * block_size)].concat();
+        let ciphertext = oracle(&input);
+        let blocks = ciphertext.chunks(block_size).collect::<Vec<_>>();
+
+        let aligned = blocks.windows(2).position(|pair| pair[0] == pair[1])?;
+
+        (aligned * block_size).checked_sub(filler_len)
+    })
+}
+
+/// Forge a ciphertext whose `target_key` field decrypts to `target_value`.
+///
+/// `oracle` encrypts the record [encoded](kv::encode) from `layout`, with
+/// the value of `input_key` replaced by its input. Only the layout after the
+/// input field (up to the target) needs to be accurate: block size and
+/// everything before the input are discovered from the oracle.
+///
+/// # Errors
+///
+/// See [`CutAndPasteError`].
+///
+/// # Panics
+///
+/// If the oracle does not encrypt as described (e.g. its ciphertexts are
+/// shorter than the discovered layout).
+pub fn forge_field(
+    oracle: impl Fn(&str) -> Vec<u8>,
+    layout: &[(&str, &str)],
+    input_key: &str,
+    target_key: &str,
+    target_value: &str,
+) -> Result<Vec<u8>, CutAndPasteError> {
+    if kv::sanitize(target_value) != target_value {
+        return Err(CutAndPasteError::UnencodableValue);
+    }
+
+    let position = |key| layout.iter().position(|&(k, _)| k == key);
+
+    let (Some(input_idx), Some(target_idx)) = (position(input_key), position(target_key)) else {
+        return Err(CutAndPasteError::MissingField);
+    };
+
+    if target_idx <= input_idx {
+        return Err(CutAndPasteError::TargetBeforeInput);
+    }
+
+    // `&...&target_key=` between the input value and the target value
+    let gap = layout[input_idx + 1..target_idx]
+        .iter()
+        .copied()
+        .chain([(target_key, "")]);
+    let gap_len = 1 + kv::encode(gap).len();
+
+    let block_size = discover_block_size(&oracle).ok_or(CutAndPasteError::BlockSize)?;
+    let prefix_len =
+        discover_prefix_length(&oracle, block_size).ok_or(CutAndPasteError::PrefixLength)?;
+
+    // Cut
+    let filler_len = (block_size - prefix_len % block_size) % block_size;
+    let padded_value = pkcs7::pad(target_value.as_bytes(), block_size as u8);
+    let cut_input = [
+        "A".repeat(filler_len),
+        String::from_utf8(padded_value.clone()).expect("PKCS#7 padding is ASCII"),
+    ]
+    .concat();
+
+    let cut_start = prefix_len + filler_len;
+    let cut = oracle(&cut_input)[cut_start..cut_start + padded_value.len()].to_vec();
+
+    // Paste
+    let paste_len = (block_size - (prefix_len + gap_len) % block_size) % block_size;
+    let mut pasted = oracle(&"A".repeat(paste_len));
+
+    pasted.truncate(prefix_len + paste_len + gap_len);
+    pasted.extend(cut);
+
+    Ok(pasted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{forge_field, CutAndPasteError};
+    use crate::block::{BlockMode, Key, AES128, ECB};
+    use crate::util::kv;
+
+    /// Encrypts `layout` with the attacker's email in place.
+    struct ProfileOracle<'a> {
+        key: Key<AES128>,
+        layout: &'a [(&'a str, &'a str)],
+    }
+
+    impl<'a> ProfileOracle<'a> {
+        fn new(layout: &'a [(&'a str, &'a str)]) -> Self {
+            ProfileOracle {
+                key: Key::random(),
+                layout,
+            }
+        }
+
+        fn encrypt(&self, email: &str) -> Vec<u8> {
+            let profile = kv::encode(
+                self.layout
+                    .iter()
+                    .map(|&(key, value)| (key, if key == "email" { email } else { value })),
+            );
+
+            ECB.encrypt(&AES128, profile.as_bytes(), &self.key)
+        }
+
+        fn role(&self, ciphertext: &[u8]) -> Option<String> {
+            let plaintext = ECB.decrypt(&AES128, ciphertext, &self.key).ok()?;
+            let profile = String::from_utf8(plaintext).ok()?;
+
+            kv::parse(&profile).get("role").map(ToString::to_string)
+        }
+    }
+
+    fn forge(layout: &[(&str, &str)], role: &str) -> Result<Option<String>, CutAndPasteError> {
+        let oracle = ProfileOracle::new(layout);
+        let forged = forge_field(|email| oracle.encrypt(email), layout, "email", "role", role)?;
+
+        Ok(oracle.role(&forged))
+    }
+
+    #[test]
+    fn test_forge_challenge_layout() {
+        let layout = [("email", ""), ("uid", "10"), ("role", "user")];
+
+        assert_eq!(forge(&layout, "admin"), Ok(Some("admin".to_string())));
+        assert_eq!(
+            forge(&layout, "superuser-with-a-long-name"),
+            Ok(Some("superuser-with-a-long-name".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_forge_other_layouts() {
+        let layout = [
+            ("uid", "1337"),
+            ("session", "deadbeef"),
+            ("email", ""),
+            ("role", "user"),
+            ("created", "2021-01-01"),
+        ];
+
+        assert_eq!(forge(&layout, "admin"), Ok(Some("admin".to_string())));
+
+        let layout = [("email", ""), ("role", "guest"), ("uid", "10")];
+
+        assert_eq!(forge(&layout, "root"), Ok(Some("root".to_string())));
+    }
+
+    #[test]
+    fn test_forge_role_first() {
+        // No block ever starts right after `role=`
+        let layout = [("role", "user"), ("uid", "10"), ("email", "")];
+
+        assert_eq!(
+            forge(&layout, "admin"),
+            Err(CutAndPasteError::TargetBeforeInput)
+        );
+    }
+
+    #[test]
+    fn test_forge_unencodable() {
+        let layout = [("email", ""), ("role", "user")];
+
+        assert_eq!(
+            forge(&layout, "admin&uid=0"),
+            Err(CutAndPasteError::UnencodableValue)
+        );
+    }
+}
@@ -0,0 +1,6 @@
+//! Attacks on block-cipher modes of operation.
+
+pub mod beast;
+pub mod bitflip;
+pub mod byte_at_a_time;
+pub mod cut_and_paste;
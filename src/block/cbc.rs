@@ -9,66 +9,277 @@ use std::iter;
 
 use itertools::Itertools;
 
-use crate::block::{BlockCipher, BlockMode};
+use crate::block::streaming::IncrementalBlockMode;
+use crate::block::{pkcs7, BlockCipher, BlockMode, Iv, Key, PKCS7Error};
 use crate::util::iter::Xorable;
+use crate::util::pool::ThreadPool;
 
 /// [CBC block mode](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_Block_Chaining_\(CBC\)).
-pub struct CBC<'a> {
+pub struct CBC<C> {
     /// Initialization vector
-    iv: &'a [u8],
+    iv: Iv<C>,
 }
 
-impl<'a> CBC<'a> {
+impl<C: BlockCipher> CBC<C> {
     /// Create a [CBC block mode](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_Block_Chaining_\(CBC\))
     /// with initialization vector `iv`.
     #[must_use]
-    pub const fn new(iv: &'a [u8]) -> CBC<'a> {
-        CBC { iv }
+    pub fn new(iv: &Iv<C>) -> CBC<C> {
+        CBC { iv: iv.clone() }
     }
-}
 
-impl<'a> BlockMode for CBC<'a> {
-    /// Encrypt `plaintext` in CBC mode with `key` and initialization vector `iv`
-    /// using `BlockCipher`.
+    /// Encrypt `plaintext` with `key` using a fresh random IV, returned
+    /// prepended to the ciphertext (`IV || ciphertext`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustopals::block::{BlockCipher, Key, AES128, CBC};
+    ///
+    /// let key = Key::new(b"YELLOW SUBMARINE").unwrap();
+    ///
+    /// let encrypted = CBC::encrypt_with_random_iv(&AES128, b"Hello", &key);
+    ///
+    /// assert_eq!(encrypted.len(), 2 * AES128::BLOCK_SIZE);
+    /// assert_ne!(encrypted, CBC::encrypt_with_random_iv(&AES128, b"Hello", &key));
+    /// assert_eq!(
+    ///     CBC::decrypt_with_prepended_iv(&AES128, &encrypted, &key),
+    ///     Ok(b"Hello".to_vec()),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn encrypt_with_random_iv(cipher: &C, plaintext: &[u8], key: &Key<C>) -> Vec<u8> {
+        let iv = Iv::random();
+        let mut encrypted = iv.to_vec();
+
+        encrypted.extend(CBC::new(&iv).encrypt(cipher, plaintext, key));
+
+        encrypted
+    }
+
+    /// Decrypt `IV || ciphertext` (as output by
+    /// [`encrypt_with_random_iv`](CBC::encrypt_with_random_iv)) with `key`.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// - If `iv.len() != `[`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE).
-    fn encrypt_impl<C: BlockCipher>(&self, cipher: &C, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
-        assert_eq!(self.iv.len(), C::BLOCK_SIZE);
+    /// Due to wrong padding (see `PKCS7Error`), or
+    /// [`PKCS7Error::Empty`] if there is no ciphertext after the IV.
+    pub fn decrypt_with_prepended_iv(
+        cipher: &C,
+        encrypted: &[u8],
+        key: &Key<C>,
+    ) -> Result<Vec<u8>, PKCS7Error> {
+        if encrypted.len() <= C::BLOCK_SIZE {
+            return Err(PKCS7Error::Empty);
+        }
+
+        let (iv, ciphertext) = encrypted.split_at(C::BLOCK_SIZE);
+        let mut decrypted = decrypt_chain(cipher, iv, ciphertext, key);
+
+        pkcs7::unpad_vec(&mut decrypted, C::BLOCK_SIZE as u8)?;
+
+        Ok(decrypted)
+    }
 
-        let mut accum = Vec::new();
+    /// Like [`decrypt_impl`](BlockMode::decrypt_impl), but splitting the
+    /// `ciphertext` between all available cores.
+    ///
+    /// Unlike encryption, CBC decryption is parallelizable: each plaintext
+    /// block only depends on its ciphertext block and the preceding one.
+    pub fn decrypt_impl_parallel(&self, cipher: &C, ciphertext: &[u8], key: &Key<C>) -> Vec<u8>
+    where
+        C: Sync,
+    {
+        let pool = ThreadPool::available();
+        let blocks_per_worker = ciphertext
+            .len()
+            .div_ceil(C::BLOCK_SIZE * pool.workers())
+            .max(1);
+        let chunk_size = blocks_per_worker * C::BLOCK_SIZE;
 
-        for block in plaintext.chunks(C::BLOCK_SIZE) {
-            let xored = {
-                let prev = if accum.is_empty() {
-                    self.iv
+        let chunks = ciphertext
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let iv = if i == 0 {
+                    &self.iv[..]
                 } else {
-                    &accum[accum.len() - C::BLOCK_SIZE..]
+                    &ciphertext[i * chunk_size - C::BLOCK_SIZE..i * chunk_size]
                 };
 
-                block.xor_repeating(prev).collect::<Vec<_>>()
+                (iv, chunk)
+            })
+            .collect::<Vec<_>>();
+
+        pool.parallel_map(&chunks, |&(iv, chunk)| {
+            decrypt_chain(cipher, iv, chunk, key)
+        })
+        .concat()
+    }
+
+    /// Like [`decrypt`](BlockMode::decrypt), but splitting the `ciphertext`
+    /// between all available cores (see
+    /// [`decrypt_impl_parallel`](CBC::decrypt_impl_parallel)).
+    ///
+    /// # Errors
+    ///
+    /// Only due to wrong padding. See `PKCS7Error`.
+    pub fn decrypt_parallel(
+        &self,
+        cipher: &C,
+        ciphertext: &[u8],
+        key: &Key<C>,
+    ) -> Result<Vec<u8>, PKCS7Error>
+    where
+        C: Sync,
+    {
+        let mut decrypted = self.decrypt_impl_parallel(cipher, ciphertext, key);
+
+        pkcs7::unpad_vec(&mut decrypted, C::BLOCK_SIZE as u8)?;
+
+        Ok(decrypted)
+    }
+}
+
+/// CBC-encrypt `plaintext`, chaining from `iv` (a block, either the IV or
+/// the last ciphertext block).
+fn encrypt_chain<C: BlockCipher>(cipher: &C, iv: &[u8], plaintext: &[u8], key: &Key<C>) -> Vec<u8> {
+    let mut accum = Vec::new();
+
+    for block in plaintext.chunks(C::BLOCK_SIZE) {
+        let xored = {
+            let prev = if accum.is_empty() {
+                iv
+            } else {
+                &accum[accum.len() - C::BLOCK_SIZE..]
             };
 
-            accum.append(&mut cipher.encrypt_block(&xored, key));
-        }
+            block.xor_repeating(prev).collect::<Vec<_>>()
+        };
+
+        accum.append(&mut cipher.encrypt_block(&xored, key));
+    }
+
+    accum
+}
+
+/// CBC-decrypt `ciphertext`, chaining from `iv` (a block, either the IV or
+/// the preceding ciphertext block).
+fn decrypt_chain<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    ciphertext: &[u8],
+    key: &Key<C>,
+) -> Vec<u8> {
+    iter::once(iv)
+        .chain(ciphertext.chunks(C::BLOCK_SIZE))
+        .tuple_windows()
+        .flat_map(|(prev, block)| cipher.decrypt_block(block, key).xor_repeating(prev))
+        .collect()
+}
 
-        accum
+impl<C: BlockCipher> BlockMode<C> for CBC<C> {
+    /// Encrypt `plaintext` in CBC mode with `key` and initialization vector `iv`
+    /// using `BlockCipher`.
+    fn encrypt_impl(&self, cipher: &C, plaintext: &[u8], key: &Key<C>) -> Vec<u8> {
+        encrypt_chain(cipher, &self.iv, plaintext, key)
     }
 
     /// Decrypt `ciphertext` in CBC mode with `key` and initialization vector `iv`
     /// using `BlockCipher`.
-    ///
-    /// # Panics
-    ///
-    /// - If `iv.len() != `[`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE).
-    fn decrypt_impl<C: BlockCipher>(&self, cipher: &C, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
-        assert_eq!(self.iv.len(), C::BLOCK_SIZE);
-
-        iter::once(self.iv)
-            .chain(ciphertext.chunks(C::BLOCK_SIZE))
-            .tuple_windows()
-            .flat_map(|(prev, block)| cipher.decrypt_block(block, key).xor_repeating(prev))
-            .collect()
+    fn decrypt_impl(&self, cipher: &C, ciphertext: &[u8], key: &Key<C>) -> Vec<u8> {
+        decrypt_chain(cipher, &self.iv, ciphertext, key)
+    }
+}
+
+impl<C: BlockCipher> IncrementalBlockMode<C> for CBC<C> {
+    /// The state is the last ciphertext block (initially, the IV).
+    fn initial_state(&self) -> Vec<u8> {
+        self.iv.to_vec()
+    }
+
+    fn encrypt_blocks(
+        &self,
+        state: &mut Vec<u8>,
+        cipher: &C,
+        plaintext: &[u8],
+        key: &Key<C>,
+    ) -> Vec<u8> {
+        let ciphertext = encrypt_chain(cipher, state, plaintext, key);
+
+        if let Some(last) = ciphertext.chunks(C::BLOCK_SIZE).last() {
+            *state = last.to_vec();
+        }
+
+        ciphertext
+    }
+
+    fn decrypt_blocks(
+        &self,
+        state: &mut Vec<u8>,
+        cipher: &C,
+        ciphertext: &[u8],
+        key: &Key<C>,
+    ) -> Vec<u8> {
+        let plaintext = decrypt_chain(cipher, state, ciphertext, key);
+
+        if let Some(last) = ciphertext.chunks(C::BLOCK_SIZE).last() {
+            *state = last.to_vec();
+        }
+
+        plaintext
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test::Bencher;
+
+    use super::CBC;
+    use crate::block::{BlockMode, Iv, Key, AES128};
+    use crate::util::generate_bytes;
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const IV: &[u8] = &[0; 16];
+
+    fn cbc() -> CBC<AES128> {
+        CBC::new(&Iv::new(IV).unwrap())
+    }
+
+    const BENCH_LEN: usize = 4 * 1024 * 1024;
+
+    #[test]
+    fn test_decrypt_parallel() {
+        let key = Key::new(KEY).unwrap();
+
+        for len in [0, 1, 15, 16, 17, 100, 1000, 10_000] {
+            let plaintext = generate_bytes(len);
+            let ciphertext = cbc().encrypt(&AES128, &plaintext, &key);
+
+            assert_eq!(
+                cbc().decrypt_impl_parallel(&AES128, &ciphertext, &key),
+                cbc().decrypt_impl(&AES128, &ciphertext, &key)
+            );
+            assert_eq!(
+                cbc().decrypt_parallel(&AES128, &ciphertext, &key),
+                Ok(plaintext)
+            );
+        }
+    }
+
+    #[bench]
+    fn bench_cbc_decrypt(b: &mut Bencher) {
+        let key = Key::new(KEY).unwrap();
+        let ciphertext = vec![0; BENCH_LEN];
+
+        b.iter(|| cbc().decrypt_impl(&AES128, &ciphertext, &key));
+    }
+
+    #[bench]
+    fn bench_cbc_decrypt_parallel(b: &mut Bencher) {
+        let key = Key::new(KEY).unwrap();
+        let ciphertext = vec![0; BENCH_LEN];
+
+        b.iter(|| cbc().decrypt_impl_parallel(&AES128, &ciphertext, &key));
     }
 }
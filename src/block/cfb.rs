@@ -0,0 +1,162 @@
+//! [CFB block mode](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_feedback_\(CFB\)).
+//!
+//! The block cipher encrypts the previous ciphertext to get the keystream for
+//! the next segment of plaintext.
+//!
+//! Like a stream cipher, it needs no padding: [`encrypt_impl`](BlockMode::encrypt_impl)
+//! and [`decrypt_impl`](BlockMode::decrypt_impl) take texts of any length.
+//! Unlike [`CTR`](crate::stream::CTR), the keystream depends on the
+//! ciphertext, so it is no [`StreamCipher`](crate::stream::StreamCipher).
+
+use crate::block::{BlockCipher, BlockMode, Iv, Key};
+use crate::util::iter::Xorable;
+
+/// How much ciphertext is fed back into the shift register at a time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Feedback {
+    /// A whole block (e.g. CFB-128 for AES).
+    Block,
+
+    /// A single byte (CFB-8). Needs a block encryption per byte.
+    Byte,
+}
+
+/// [CFB block mode](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_feedback_\(CFB\)).
+pub struct CFB<C> {
+    /// Initialization vector
+    iv: Iv<C>,
+
+    feedback: Feedback,
+}
+
+impl<C: BlockCipher> CFB<C> {
+    /// Create a full-block [CFB block mode](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_feedback_\(CFB\))
+    /// with initialization vector `iv`.
+    #[must_use]
+    pub fn new(iv: &Iv<C>) -> CFB<C> {
+        CFB::with_feedback(iv, Feedback::Block)
+    }
+
+    /// Create an 8-bit [CFB block mode](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_feedback_\(CFB\))
+    /// with initialization vector `iv`.
+    #[must_use]
+    pub fn new_8bit(iv: &Iv<C>) -> CFB<C> {
+        CFB::with_feedback(iv, Feedback::Byte)
+    }
+
+    /// Create a [CFB block mode](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Cipher_feedback_\(CFB\))
+    /// with initialization vector `iv` and some `feedback` size.
+    #[must_use]
+    pub fn with_feedback(iv: &Iv<C>, feedback: Feedback) -> CFB<C> {
+        CFB {
+            iv: iv.clone(),
+            feedback,
+        }
+    }
+
+    /// Segment length (in bytes) for a `block_size`.
+    const fn segment_len(&self, block_size: usize) -> usize {
+        match self.feedback {
+            Feedback::Block => block_size,
+            Feedback::Byte => 1,
+        }
+    }
+
+    /// En/decrypt `text` segment by segment. `decrypting` selects which of
+    /// input or output is the ciphertext fed back.
+    fn process(&self, cipher: &C, text: &[u8], key: &Key<C>, decrypting: bool) -> Vec<u8> {
+        let segment_len = self.segment_len(C::BLOCK_SIZE);
+        let mut register = self.iv.to_vec();
+        let mut output = Vec::with_capacity(text.len());
+
+        for segment in text.chunks(segment_len) {
+            let keystream = cipher.encrypt_block(&register, key);
+            let processed = segment.iter().xor(keystream).collect::<Vec<_>>();
+
+            let ciphertext = if decrypting { segment } else { &processed };
+
+            register.drain(..segment_len);
+            register.extend_from_slice(ciphertext);
+
+            output.extend(processed);
+        }
+
+        output
+    }
+}
+
+impl<C: BlockCipher> BlockMode<C> for CFB<C> {
+    /// Encrypt `plaintext` (of any length) in CFB mode with `key` and
+    /// initialization vector `iv` using `BlockCipher`.
+    fn encrypt_impl(&self, cipher: &C, plaintext: &[u8], key: &Key<C>) -> Vec<u8> {
+        self.process(cipher, plaintext, key, false)
+    }
+
+    /// Decrypt `ciphertext` (of any length) in CFB mode with `key` and
+    /// initialization vector `iv` using `BlockCipher`.
+    fn decrypt_impl(&self, cipher: &C, ciphertext: &[u8], key: &Key<C>) -> Vec<u8> {
+        self.process(cipher, ciphertext, key, true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CFB;
+    use crate::block::{BlockMode, Iv, Key, AES128};
+    use crate::util::generate_bytes;
+
+    // From NIST SP 800-38A, appendix F.3
+    const KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const IV: &str = "000102030405060708090a0b0c0d0e0f";
+
+    fn check_vector(cfb: &CFB<AES128>, plaintext: &str, ciphertext: &str) {
+        let key = Key::new(&hex::decode(KEY).unwrap()).unwrap();
+        let plaintext = hex::decode(plaintext).unwrap();
+        let ciphertext = hex::decode(ciphertext).unwrap();
+
+        assert_eq!(cfb.encrypt_impl(&AES128, &plaintext, &key), ciphertext);
+        assert_eq!(cfb.decrypt_impl(&AES128, &ciphertext, &key), plaintext);
+    }
+
+    #[test]
+    fn test_cfb128_vector() {
+        let iv = Iv::new(&hex::decode(IV).unwrap()).unwrap();
+
+        check_vector(
+            &CFB::new(&iv),
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+            "3b3fd92eb72dad20333449f8e83cfb4ac8a64537a0b3a93fcde3cdad9f1ce58b",
+        );
+    }
+
+    #[test]
+    fn test_cfb8_vector() {
+        let iv = Iv::new(&hex::decode(IV).unwrap()).unwrap();
+
+        check_vector(
+            &CFB::new_8bit(&iv),
+            "6bc1bee22e409f96e93d7e117393172aae2d",
+            "3b79424c9c0dd436bace9e0ed4586a4f32b9",
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let key = Key::random();
+        let iv = Iv::random();
+
+        for cfb in [CFB::new(&iv), CFB::new_8bit(&iv)] {
+            for len in [0, 1, 15, 16, 17, 100] {
+                let plaintext = generate_bytes(len);
+                let ciphertext = cfb.encrypt_impl(&AES128, &plaintext, &key);
+
+                assert_eq!(ciphertext.len(), len);
+                assert_eq!(cfb.decrypt_impl(&AES128, &ciphertext, &key), plaintext);
+                assert_eq!(
+                    cfb.decrypt(&AES128, &cfb.encrypt(&AES128, &plaintext, &key), &key),
+                    Ok(plaintext)
+                );
+            }
+        }
+    }
+}
@@ -3,7 +3,8 @@
 
 use itertools::Itertools;
 
-use crate::block::{BlockCipher, BlockMode};
+use crate::block::streaming::IncrementalBlockMode;
+use crate::block::{BlockCipher, BlockMode, Key};
 use crate::util::Probability;
 
 /// [ECB block mode](https://en.wikipedi.org/wiki/Block_cipher_mode_of_operation#Electronic_Codebook_\(ECB\))
@@ -19,9 +20,9 @@ impl ECB {
     }
 }
 
-impl<'a> BlockMode for ECB {
+impl<C: BlockCipher> BlockMode<C> for ECB {
     /// Encrypt `plaintext` in ECB mode with `key` using `BlockCipher`.
-    fn encrypt_impl<C: BlockCipher>(&self, cipher: &C, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
+    fn encrypt_impl(&self, cipher: &C, plaintext: &[u8], key: &Key<C>) -> Vec<u8> {
         plaintext
             .chunks(C::BLOCK_SIZE)
             .flat_map(|chunk| cipher.encrypt_block(chunk, key))
@@ -29,10 +30,37 @@ impl<'a> BlockMode for ECB {
     }
 
     /// Decrypt `ciphertext` in ECB mode with `key` using `BlockCipher`.
-    fn decrypt_impl<C: BlockCipher>(&self, cipher: &C, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
+    fn decrypt_impl(&self, cipher: &C, ciphertext: &[u8], key: &Key<C>) -> Vec<u8> {
         ciphertext
             .chunks(C::BLOCK_SIZE)
             .flat_map(|chunk| cipher.decrypt_block(chunk, key))
             .collect()
     }
 }
+
+impl<C: BlockCipher> IncrementalBlockMode<C> for ECB {
+    /// ECB is stateless.
+    fn initial_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn encrypt_blocks(
+        &self,
+        _state: &mut Vec<u8>,
+        cipher: &C,
+        plaintext: &[u8],
+        key: &Key<C>,
+    ) -> Vec<u8> {
+        self.encrypt_impl(cipher, plaintext, key)
+    }
+
+    fn decrypt_blocks(
+        &self,
+        _state: &mut Vec<u8>,
+        cipher: &C,
+        ciphertext: &[u8],
+        key: &Key<C>,
+    ) -> Vec<u8> {
+        self.decrypt_impl(cipher, ciphertext, key)
+    }
+}
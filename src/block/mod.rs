@@ -2,14 +2,25 @@
 //! and related utilities.
 
 pub mod aes128;
+pub mod attacks;
 pub mod cbc;
+pub mod cfb;
 pub mod ecb;
+pub mod params;
+pub mod pipeline;
 pub mod pkcs7;
+pub mod streaming;
+pub mod wide;
 
-pub use aes128::AES128;
+pub use aes128::{RoundKeys, AES128, AES192, AES256};
 pub use cbc::CBC;
+pub use cfb::CFB;
 pub use ecb::ECB;
+pub use params::{Iv, Key, LengthError, Nonce};
+pub use pipeline::{Decryptor, Encryptor};
 pub use pkcs7::PKCS7Error;
+pub use streaming::{BlockModeDecryptor, BlockModeEncryptor};
+pub use wide::EME;
 
 /// Trait for block ciphers.
 ///
@@ -35,16 +46,17 @@ pub trait BlockCipher {
     /// Encrypt a block.
     ///
     /// Takes a `plaintext` block of [`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE)
-    /// length and a `key` of [`KEY_SIZE`](#associatedconstant.KEY_SIZE) length
-    /// and returns the ciphertext.
+    /// length and a [`Key`] for `Self`, and returns the ciphertext.
     ///
     /// # Panics
     ///
-    /// - If `plaintext.len() != `[`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE)
-    /// - If `key.len() != `[`KEY_SIZE`](#associatedconstant.KEY_SIZE).
-    fn encrypt_block(&self, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
+    /// If `plaintext` is not [`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE)
+    /// bytes long.
+    fn encrypt_block(&self, plaintext: &[u8], key: &Key<Self>) -> Vec<u8>
+    where
+        Self: Sized,
+    {
         assert_eq!(plaintext.len(), Self::BLOCK_SIZE);
-        assert_eq!(key.len(), Self::KEY_SIZE);
 
         self.encrypt_impl(plaintext, key)
     }
@@ -52,49 +64,70 @@ pub trait BlockCipher {
     /// Decrypt a block.
     ///
     /// Takes a `ciphertext` block of [`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE)
-    /// length and a `key` of [`KEY_SIZE`](#associatedconstant.KEY_SIZE) length
-    /// and returns the plaintext.
+    /// length and a [`Key`] for `Self`, and returns the plaintext.
     ///
     /// # Panics
     ///
-    /// - If `plaintext.len() != `[`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE)
-    /// - If `key.len() != `[`KEY_SIZE`](#associatedconstant.KEY_SIZE).
-    fn decrypt_block(&self, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
+    /// If `ciphertext` is not [`BLOCK_SIZE`](#associatedconstant.BLOCK_SIZE)
+    /// bytes long.
+    fn decrypt_block(&self, ciphertext: &[u8], key: &Key<Self>) -> Vec<u8>
+    where
+        Self: Sized,
+    {
         assert_eq!(ciphertext.len(), Self::BLOCK_SIZE);
-        assert_eq!(key.len(), Self::KEY_SIZE);
 
         self.decrypt_impl(ciphertext, key)
     }
 }
 
+/// Trait for block ciphers whose key schedule can be computed once and then
+/// reused across many blocks.
+///
+/// [`BlockCipher`] takes the raw key for every block, so it has to expand it
+/// each time. This is the extension point used by
+/// [`CtrContext`](crate::stream::ctr::CtrContext) to avoid that.
+pub trait KeySchedule: BlockCipher {
+    /// The expanded key.
+    type RoundKeys;
+
+    /// Expand a `key`.
+    fn expand_key(&self, key: &Key<Self>) -> Self::RoundKeys
+    where
+        Self: Sized;
+
+    /// Encrypt a `plaintext` block of [`BLOCK_SIZE`](BlockCipher::BLOCK_SIZE)
+    /// length with already expanded `round_keys`.
+    fn encrypt_expanded(&self, plaintext: &[u8], round_keys: &Self::RoundKeys) -> Vec<u8>;
+
+    /// Decrypt a `ciphertext` block of [`BLOCK_SIZE`](BlockCipher::BLOCK_SIZE)
+    /// length with already expanded `round_keys`.
+    fn decrypt_expanded(&self, ciphertext: &[u8], round_keys: &Self::RoundKeys) -> Vec<u8>;
+}
+
 /// Trait for [block-cipher modes of operation](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation)
-pub trait BlockMode {
+/// over block cipher `C`.
+pub trait BlockMode<C: BlockCipher> {
     /// The actual block-mode implementation.
     ///
     /// Encrypt `plaintext` with `key` using `BlockCipher`.
-    fn encrypt_impl<C: BlockCipher>(&self, cipher: &C, plaintext: &[u8], key: &[u8]) -> Vec<u8>;
+    fn encrypt_impl(&self, cipher: &C, plaintext: &[u8], key: &Key<C>) -> Vec<u8>;
 
     /// The actual block-mode implementation.
     ///
-    /// Decrypt `ciphertext` in ECB mode with `key` using `BlockCipher`.
-    fn decrypt_impl<C: BlockCipher>(&self, cipher: &C, ciphertext: &[u8], key: &[u8]) -> Vec<u8>;
+    /// Decrypt `ciphertext` with `key` using `BlockCipher`.
+    fn decrypt_impl(&self, cipher: &C, ciphertext: &[u8], key: &Key<C>) -> Vec<u8>;
 
     /// Encrypt `plaintext` with `key` using `BlockCipher`.
-    fn encrypt<C: BlockCipher>(&self, cipher: &C, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
+    fn encrypt(&self, cipher: &C, plaintext: &[u8], key: &Key<C>) -> Vec<u8> {
         self.encrypt_impl(cipher, &pkcs7::pad(plaintext, C::BLOCK_SIZE as u8), key)
     }
 
-    /// Decrypt `ciphertext` in ECB mode with `key` using `BlockCipher`.
+    /// Decrypt `ciphertext` with `key` using `BlockCipher`.
     ///
     /// # Errors
     ///
     /// Only due to wrong padding. See `PKCS7Error`.
-    fn decrypt<C: BlockCipher>(
-        &self,
-        cipher: &C,
-        ciphertext: &[u8],
-        key: &[u8],
-    ) -> Result<Vec<u8>, PKCS7Error> {
+    fn decrypt(&self, cipher: &C, ciphertext: &[u8], key: &Key<C>) -> Result<Vec<u8>, PKCS7Error> {
         let mut decrypted = self.decrypt_impl(cipher, ciphertext, key);
 
         pkcs7::unpad_vec(&mut decrypted, C::BLOCK_SIZE as u8)?;
@@ -0,0 +1,251 @@
+//! Typed keys, [IVs](https://en.wikipedia.org/wiki/Initialization_vector) and
+//! [nonces](https://en.wikipedia.org/wiki/Cryptographic_nonce) for block
+//! ciphers.
+//!
+//! Lengths are validated at construction time (instead of asserting deep
+//! inside block modes) and bytes are zeroized on drop. All of them dereference
+//! to `[u8]`, so they can be passed anywhere a `&[u8]` is expected.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::block::params::{Iv, Key, LengthError};
+//! use rustopals::block::{BlockMode, AES128, CBC};
+//!
+//! let key = Key::<AES128>::new(b"YELLOW SUBMARINE").unwrap();
+//! let iv = Iv::<AES128>::random();
+//!
+//! let ciphertext = CBC::new(&iv).encrypt(&AES128, b"Hello", &key);
+//!
+//! assert_eq!(
+//!     Key::<AES128>::new(b"SUBMARINE"),
+//!     Err(LengthError { expected: 16, actual: 9 }),
+//! );
+//! ```
+
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::{fmt, ptr};
+
+use rand::RngCore;
+
+use crate::block::BlockCipher;
+use crate::stream::ctr::CounterLayout;
+use crate::util::generate_bytes;
+use crate::util::secret::Redacted;
+
+/// Wrong length for a block cipher parameter.
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub struct LengthError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+/// Kind of block cipher parameter (determines its length).
+pub trait ParamKind {
+    /// Whether parameters of this kind must be redacted from `Debug` output.
+    const SECRET: bool = false;
+
+    /// Expected parameter length for cipher `C`.
+    fn len<C: BlockCipher>() -> usize;
+}
+
+/// Marker for keys ([`KEY_SIZE`](BlockCipher::KEY_SIZE) bytes).
+pub struct KeyKind;
+
+impl ParamKind for KeyKind {
+    const SECRET: bool = true;
+
+    fn len<C: BlockCipher>() -> usize {
+        C::KEY_SIZE
+    }
+}
+
+/// Marker for IVs ([`BLOCK_SIZE`](BlockCipher::BLOCK_SIZE) bytes).
+pub struct IvKind;
+
+impl ParamKind for IvKind {
+    fn len<C: BlockCipher>() -> usize {
+        C::BLOCK_SIZE
+    }
+}
+
+/// Marker for [`CTR`](crate::stream::CTR) nonces (a block minus the 64-bit
+/// counter).
+pub struct NonceKind;
+
+impl ParamKind for NonceKind {
+    fn len<C: BlockCipher>() -> usize {
+        CounterLayout::default().nonce_len(C::BLOCK_SIZE)
+    }
+}
+
+/// Length-checked, zeroize-on-drop block cipher parameter.
+pub struct Param<C, K> {
+    bytes: Vec<u8>,
+    kind: PhantomData<(C, K)>,
+}
+
+/// Key for block cipher `C`.
+pub type Key<C> = Param<C, KeyKind>;
+
+/// Initialization vector for block cipher `C`.
+pub type Iv<C> = Param<C, IvKind>;
+
+/// CTR nonce for block cipher `C`.
+pub type Nonce<C> = Param<C, NonceKind>;
+
+impl<C: BlockCipher, K: ParamKind> Param<C, K> {
+    /// Expected length in bytes.
+    #[must_use]
+    pub fn expected_len() -> usize {
+        K::len::<C>()
+    }
+
+    /// Create from `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// If `bytes` does not have the expected length.
+    pub fn new(bytes: &[u8]) -> Result<Self, LengthError> {
+        if bytes.len() != Self::expected_len() {
+            return Err(LengthError {
+                expected: Self::expected_len(),
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(Param {
+            bytes: bytes.to_vec(),
+            kind: PhantomData,
+        })
+    }
+
+    /// Generate a random parameter.
+    #[must_use]
+    pub fn random() -> Self {
+        Param {
+            bytes: generate_bytes(Self::expected_len()),
+            kind: PhantomData,
+        }
+    }
+
+    /// Draw a random parameter from `rng`.
+    #[must_use]
+    pub fn from_rng(rng: &mut impl RngCore) -> Self {
+        let mut bytes = vec![0; Self::expected_len()];
+        rng.fill_bytes(&mut bytes);
+
+        Param {
+            bytes,
+            kind: PhantomData,
+        }
+    }
+
+    /// All-zero parameter (e.g. the fixed IV in CBC-MAC).
+    #[must_use]
+    pub fn zero() -> Self {
+        Param {
+            bytes: vec![0; Self::expected_len()],
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<C, K> Deref for Param<C, K> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<C, K> AsRef<[u8]> for Param<C, K> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<C, K> Clone for Param<C, K> {
+    fn clone(&self) -> Self {
+        Param {
+            bytes: self.bytes.clone(),
+            kind: PhantomData,
+        }
+    }
+}
+
+impl<C, K> PartialEq for Param<C, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<C, K> Eq for Param<C, K> {}
+
+impl<C, K: ParamKind> fmt::Debug for Param<C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if K::SECRET {
+            f.debug_tuple("Param")
+                .field(&Redacted::bytes(&self.bytes))
+                .finish()
+        } else {
+            f.debug_tuple("Param").field(&self.bytes).finish()
+        }
+    }
+}
+
+impl<C: BlockCipher, K: ParamKind> TryFrom<&[u8]> for Param<C, K> {
+    type Error = LengthError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, LengthError> {
+        Param::new(bytes)
+    }
+}
+
+impl<C, K> Drop for Param<C, K> {
+    fn drop(&mut self) {
+        for byte in &mut self.bytes {
+            // Volatile so that the compiler does not optimize the dead store away
+            unsafe { ptr::write_volatile(byte, 0) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+
+    use super::{Iv, Key, LengthError, Nonce};
+    use crate::block::AES128;
+
+    #[test]
+    fn test_lengths() {
+        assert_eq!(Key::<AES128>::expected_len(), 16);
+        assert_eq!(Iv::<AES128>::expected_len(), 16);
+        assert_eq!(Nonce::<AES128>::expected_len(), 8);
+
+        assert_eq!(Key::<AES128>::random().len(), 16);
+        assert_eq!(Nonce::<AES128>::random().len(), 8);
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(&*Iv::<AES128>::new(&[1; 16]).unwrap(), &[1; 16]);
+        assert_eq!(
+            Nonce::<AES128>::new(&[0; 16]),
+            Err(LengthError {
+                expected: 8,
+                actual: 16
+            })
+        );
+        assert_eq!(
+            Key::<AES128>::try_from(&[0; 15][..]),
+            Err(LengthError {
+                expected: 16,
+                actual: 15
+            })
+        );
+    }
+}
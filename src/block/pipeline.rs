@@ -0,0 +1,353 @@
+//! Builders composing a block cipher, a [mode of operation](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation),
+//! a padding scheme and a key into a single encryption/decryption pipeline.
+//!
+//! Unlike calling [`BlockMode::encrypt`] directly, every parameter is checked
+//! up front (returning an error instead of panicking) and padding must be
+//! explicitly chosen, so padded and unpadded paths cannot be mixed by
+//! accident.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::block::{Decryptor, Encryptor};
+//!
+//! const KEY: &[u8] = b"YELLOW SUBMARINE";
+//! const IV: &[u8] = &[0; 16];
+//!
+//! let ciphertext = Encryptor::aes128()
+//!     .cbc(IV)
+//!     .pkcs7()
+//!     .key(KEY)
+//!     .encrypt(b"Hello, world!")
+//!     .unwrap();
+//!
+//! let plaintext = Decryptor::aes128()
+//!     .cbc(IV)
+//!     .pkcs7()
+//!     .key(KEY)
+//!     .decrypt(&ciphertext)
+//!     .unwrap();
+//!
+//! assert_eq!(plaintext, b"Hello, world!");
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::block::streaming::{BlockModeDecryptor, BlockModeEncryptor, IncrementalBlockMode};
+use crate::block::{pkcs7, BlockCipher, BlockMode, Iv, Key, PKCS7Error, AES128, CBC, ECB};
+
+/// Possible pipeline errors.
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub enum PipelineError {
+    /// No key was provided.
+    MissingKey,
+
+    /// No padding scheme was chosen.
+    MissingPadding,
+
+    /// The key length does not match the cipher key size.
+    BadKeyLength,
+
+    /// The IV length does not match the cipher block size.
+    BadIVLength,
+
+    /// The input is not block-aligned (and no padding was requested).
+    UnalignedInput,
+
+    /// Wrong padding after decryption.
+    Padding(PKCS7Error),
+}
+
+impl From<PKCS7Error> for PipelineError {
+    fn from(error: PKCS7Error) -> Self {
+        PipelineError::Padding(error)
+    }
+}
+
+/// Padding scheme applied by a pipeline.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Padding {
+    /// [PKCS7](crate::block::pkcs7) padding.
+    PKCS7,
+
+    /// No padding: input must be block-aligned.
+    None,
+}
+
+/// Placeholder for a pipeline without a chosen block mode yet.
+pub struct NoMode;
+
+/// Marker for encryption pipelines.
+pub struct Encrypt;
+
+/// Marker for decryption pipelines.
+pub struct Decrypt;
+
+/// Block-mode pipeline builder. See [`Encryptor`] and [`Decryptor`].
+#[must_use]
+pub struct Pipeline<'a, C, M, D> {
+    cipher: C,
+    /// `None` if the mode was given a wrong-sized IV.
+    mode: Option<M>,
+    padding: Option<Padding>,
+    key: Option<&'a [u8]>,
+    direction: PhantomData<D>,
+}
+
+/// Encryption pipeline builder.
+pub type Encryptor<'a, C, M> = Pipeline<'a, C, M, Encrypt>;
+
+/// Decryption pipeline builder.
+pub type Decryptor<'a, C, M> = Pipeline<'a, C, M, Decrypt>;
+
+impl<D> Pipeline<'_, AES128, NoMode, D> {
+    /// Start a pipeline using [`AES128`].
+    pub const fn aes128() -> Self {
+        Pipeline::with_cipher(AES128)
+    }
+}
+
+impl<'a, C: BlockCipher, D> Pipeline<'a, C, NoMode, D> {
+    /// Start a pipeline using `cipher`.
+    pub const fn with_cipher(cipher: C) -> Self {
+        Pipeline {
+            cipher,
+            mode: Some(NoMode),
+            padding: None,
+            key: None,
+            direction: PhantomData,
+        }
+    }
+
+    /// Use an arbitrary block `mode`.
+    pub fn mode<M: BlockMode<C>>(self, mode: M) -> Pipeline<'a, C, M, D> {
+        Pipeline {
+            cipher: self.cipher,
+            mode: Some(mode),
+            padding: self.padding,
+            key: self.key,
+            direction: PhantomData,
+        }
+    }
+
+    /// Use [`ECB`] mode.
+    pub fn ecb(self) -> Pipeline<'a, C, ECB, D> {
+        self.mode(ECB)
+    }
+
+    /// Use [`CBC`] mode with initialization vector `iv`.
+    pub fn cbc(self, iv: &[u8]) -> Pipeline<'a, C, CBC<C>, D> {
+        Pipeline {
+            cipher: self.cipher,
+            mode: Iv::new(iv).ok().map(|iv| CBC::new(&iv)),
+            padding: self.padding,
+            key: self.key,
+            direction: PhantomData,
+        }
+    }
+}
+
+impl<'a, C: BlockCipher, M, D> Pipeline<'a, C, M, D> {
+    /// Use [PKCS7](crate::block::pkcs7) padding.
+    pub const fn pkcs7(self) -> Self {
+        self.padding(Padding::PKCS7)
+    }
+
+    /// Use no padding (input must be block-aligned).
+    pub const fn no_padding(self) -> Self {
+        self.padding(Padding::None)
+    }
+
+    /// Use `padding`.
+    pub const fn padding(mut self, padding: Padding) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Use `key`.
+    pub const fn key(mut self, key: &'a [u8]) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Check the pipeline parameters, returning the mode, key and padding.
+    fn check(&self) -> Result<(&M, Key<C>, Padding), PipelineError> {
+        let key = self.key.ok_or(PipelineError::MissingKey)?;
+        let padding = self.padding.ok_or(PipelineError::MissingPadding)?;
+        let key = Key::new(key).map_err(|_| PipelineError::BadKeyLength)?;
+        let mode = self.mode.as_ref().ok_or(PipelineError::BadIVLength)?;
+
+        Ok((mode, key, padding))
+    }
+}
+
+impl<C: BlockCipher, M: BlockMode<C>> Encryptor<'_, C, M> {
+    /// Encrypt `plaintext`.
+    ///
+    /// # Errors
+    ///
+    /// If the pipeline is missing parameters, has wrong-sized parameters, or
+    /// if `plaintext` is not block-aligned when not padding.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, PipelineError> {
+        let (mode, key, padding) = self.check()?;
+
+        match padding {
+            Padding::PKCS7 => Ok(mode.encrypt(&self.cipher, plaintext, &key)),
+            Padding::None if plaintext.len().is_multiple_of(C::BLOCK_SIZE) => {
+                Ok(mode.encrypt_impl(&self.cipher, plaintext, &key))
+            },
+            Padding::None => Err(PipelineError::UnalignedInput),
+        }
+    }
+}
+
+impl<C: BlockCipher, M: BlockMode<C>> Decryptor<'_, C, M> {
+    /// Decrypt `ciphertext`.
+    ///
+    /// # Errors
+    ///
+    /// If the pipeline is missing parameters, has wrong-sized parameters, if
+    /// `ciphertext` is not block-aligned, or due to wrong padding.
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, PipelineError> {
+        let (mode, key, padding) = self.check()?;
+
+        if !ciphertext.len().is_multiple_of(C::BLOCK_SIZE) {
+            return Err(PipelineError::UnalignedInput);
+        }
+
+        let mut decrypted = mode.decrypt_impl(&self.cipher, ciphertext, &key);
+
+        if padding == Padding::PKCS7 {
+            pkcs7::unpad_vec(&mut decrypted, C::BLOCK_SIZE as u8)?;
+        }
+
+        Ok(decrypted)
+    }
+}
+
+impl<C: BlockCipher, M: IncrementalBlockMode<C>> Encryptor<'_, C, M> {
+    /// Start a [streaming](crate::block::streaming) encryption.
+    ///
+    /// # Errors
+    ///
+    /// If the pipeline is missing parameters or has wrong-sized parameters.
+    pub fn streaming(self) -> Result<BlockModeEncryptor<C, M>, PipelineError> {
+        let (_, key, padding) = self.check()?;
+        let mode = self.mode.ok_or(PipelineError::BadIVLength)?;
+
+        Ok(BlockModeEncryptor::new(self.cipher, mode, key, padding))
+    }
+}
+
+impl<C: BlockCipher, M: IncrementalBlockMode<C>> Decryptor<'_, C, M> {
+    /// Start a [streaming](crate::block::streaming) decryption.
+    ///
+    /// # Errors
+    ///
+    /// If the pipeline is missing parameters or has wrong-sized parameters.
+    pub fn streaming(self) -> Result<BlockModeDecryptor<C, M>, PipelineError> {
+        let (_, key, padding) = self.check()?;
+        let mode = self.mode.ok_or(PipelineError::BadIVLength)?;
+
+        Ok(BlockModeDecryptor::new(self.cipher, mode, key, padding))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Decryptor, Encryptor, PipelineError};
+    use crate::block::{BlockMode, Iv, Key, PKCS7Error, AES128, CBC, ECB, EME};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const IV: &[u8] = b"SUBMARINE YELLOW";
+    const PLAINTEXT: &[u8] = b"We all live in a yellow submarine";
+
+    #[test]
+    fn test_matches_block_mode() {
+        let key = Key::new(KEY).unwrap();
+
+        assert_eq!(
+            Encryptor::aes128()
+                .cbc(IV)
+                .pkcs7()
+                .key(KEY)
+                .encrypt(PLAINTEXT),
+            Ok(CBC::new(&Iv::new(IV).unwrap()).encrypt(&AES128, PLAINTEXT, &key))
+        );
+        assert_eq!(
+            Encryptor::aes128()
+                .ecb()
+                .no_padding()
+                .key(KEY)
+                .encrypt(&PLAINTEXT[..32]),
+            Ok(ECB.encrypt_impl(&AES128, &PLAINTEXT[..32], &key))
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let ciphertext = Encryptor::aes128()
+            .mode(EME::new(IV))
+            .pkcs7()
+            .key(KEY)
+            .encrypt(PLAINTEXT)
+            .unwrap();
+
+        assert_eq!(
+            Decryptor::aes128()
+                .mode(EME::new(IV))
+                .pkcs7()
+                .key(KEY)
+                .decrypt(&ciphertext),
+            Ok(PLAINTEXT.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Encryptor::aes128().ecb().pkcs7().encrypt(PLAINTEXT),
+            Err(PipelineError::MissingKey)
+        );
+        assert_eq!(
+            Encryptor::aes128().ecb().key(KEY).encrypt(PLAINTEXT),
+            Err(PipelineError::MissingPadding)
+        );
+        assert_eq!(
+            Encryptor::aes128()
+                .ecb()
+                .pkcs7()
+                .key(&KEY[1..])
+                .encrypt(PLAINTEXT),
+            Err(PipelineError::BadKeyLength)
+        );
+        assert_eq!(
+            Encryptor::aes128()
+                .cbc(&IV[1..])
+                .pkcs7()
+                .key(KEY)
+                .encrypt(PLAINTEXT),
+            Err(PipelineError::BadIVLength)
+        );
+        assert_eq!(
+            Encryptor::aes128()
+                .ecb()
+                .no_padding()
+                .key(KEY)
+                .encrypt(PLAINTEXT),
+            Err(PipelineError::UnalignedInput)
+        );
+        assert_eq!(
+            Decryptor::aes128()
+                .ecb()
+                .pkcs7()
+                .key(KEY)
+                .decrypt(PLAINTEXT),
+            Err(PipelineError::UnalignedInput)
+        );
+        assert_eq!(
+            Decryptor::aes128().ecb().pkcs7().key(KEY).decrypt(&[0; 16]),
+            Err(PipelineError::Padding(PKCS7Error::BadByte))
+        );
+    }
+}
@@ -22,6 +22,41 @@ pub enum PKCS7Error {
     BadPadding,
 }
 
+/// Ways of corrupting valid padding, for [`corrupt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Corruption {
+    /// The last byte is larger than the block length.
+    WrongLastByte,
+
+    /// The last byte is right, but some other byte in the run differs.
+    InconsistentRun,
+
+    /// The last byte is `0x00`.
+    ZeroByte,
+
+    /// The last byte claims more padding than there are bytes.
+    OverLength,
+}
+
+impl Corruption {
+    /// Every corruption, to iterate over.
+    pub const ALL: [Corruption; 4] = [
+        Corruption::WrongLastByte,
+        Corruption::InconsistentRun,
+        Corruption::ZeroByte,
+        Corruption::OverLength,
+    ];
+
+    /// The error [`unpad`] must return for this corruption.
+    #[must_use]
+    pub const fn expected_error(self) -> PKCS7Error {
+        match self {
+            Corruption::WrongLastByte | Corruption::ZeroByte => PKCS7Error::BadByte,
+            Corruption::InconsistentRun | Corruption::OverLength => PKCS7Error::BadPadding,
+        }
+    }
+}
+
 fn get_padding_length(payload: &[u8], block_length: u8) -> Result<usize, PKCS7Error> {
     let pad_byte = *match payload.last() {
         Some(b) => b,
@@ -34,6 +69,10 @@ fn get_padding_length(payload: &[u8], block_length: u8) -> Result<usize, PKCS7Er
         return Err(PKCS7Error::BadByte);
     };
 
+    if pad_len > payload.len() {
+        return Err(PKCS7Error::BadPadding);
+    }
+
     let is_valid_padding = payload[payload.len() - pad_len..]
         .iter()
         .all(|x| *x == pad_byte);
@@ -297,3 +336,61 @@ pub fn unpad_vec(payload: &mut Vec<u8>, block_length: u8) -> Result<(), PKCS7Err
 
     Ok(())
 }
+
+/// Pad `payload` to a multiple of `block_length` and then break the padding
+/// in a specific way, e.g. to test unpadding robustness or padding oracle
+/// attacks against near-valid padding.
+///
+/// - [`WrongLastByte`](Corruption::WrongLastByte) sets the last byte to
+///   `block_length + 1` (wrapping around to `0x00` for `block_length = 255`).
+/// - [`InconsistentRun`](Corruption::InconsistentRun) flips a bit in the
+///   first byte of the run (after growing the run to 2 bytes, if needed).
+/// - [`ZeroByte`](Corruption::ZeroByte) sets the last byte to `0x00`.
+/// - [`OverLength`](Corruption::OverLength) keeps only the last
+///   `block_length - 1` bytes, and sets the last one to `block_length`.
+///
+/// # Examples
+///
+/// ```
+/// use rustopals::block::pkcs7::{self, Corruption};
+///
+/// assert_eq!(
+///     pkcs7::corrupt(b"YELLOW SUBMARINE", 20, Corruption::InconsistentRun),
+///     b"YELLOW SUBMARINE\x05\x04\x04\x04",
+/// );
+///
+/// for corruption in Corruption::ALL {
+///     assert_eq!(
+///         pkcs7::unpad(&pkcs7::corrupt(b"YELLOW", 16, corruption), 16),
+///         Err(corruption.expected_error()),
+///     );
+/// }
+/// ```
+///
+/// # Panics
+///
+/// If `block_length < 2`, since there would be no way to break the padding.
+#[must_use]
+pub fn corrupt(payload: &[u8], block_length: u8, corruption: Corruption) -> Vec<u8> {
+    assert!(block_length >= 2, "block length must be at least 2");
+
+    let mut padded = pad(payload, block_length);
+    let len = padded.len();
+
+    match corruption {
+        Corruption::WrongLastByte => padded[len - 1] = block_length.wrapping_add(1),
+        Corruption::ZeroByte => padded[len - 1] = 0x00,
+        Corruption::InconsistentRun => {
+            let run_len = padded[len - 1].max(2);
+
+            padded[len - usize::from(run_len)..].fill(run_len);
+            padded[len - usize::from(run_len)] ^= 0x01;
+        },
+        Corruption::OverLength => {
+            padded.drain(..=len - usize::from(block_length));
+            padded[usize::from(block_length) - 2] = block_length;
+        },
+    }
+
+    padded
+}
@@ -0,0 +1,303 @@
+//! Incremental encryption and decryption, for messages that do not fit (or
+//! are not fully available) in memory.
+//!
+//! Input is fed in arbitrarily-sized chunks through `update`, which outputs
+//! every block that can already be processed. Partial blocks are buffered
+//! until more input arrives or `finalize` is called, which handles padding.
+//!
+//! Streaming pipelines are built like regular ones (see
+//! [`Pipeline::streaming`](super::pipeline::Pipeline::streaming)).
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::block::{Decryptor, Encryptor};
+//!
+//! const KEY: &[u8] = b"YELLOW SUBMARINE";
+//! const IV: &[u8] = &[0; 16];
+//!
+//! let mut encryptor = Encryptor::aes128()
+//!     .cbc(IV)
+//!     .pkcs7()
+//!     .key(KEY)
+//!     .streaming()
+//!     .unwrap();
+//!
+//! let mut ciphertext = encryptor.update(b"Hello, ");
+//! ciphertext.extend(encryptor.update(b"world!"));
+//! ciphertext.extend(encryptor.finalize().unwrap());
+//!
+//! let plaintext = Decryptor::aes128()
+//!     .cbc(IV)
+//!     .pkcs7()
+//!     .key(KEY)
+//!     .decrypt(&ciphertext)
+//!     .unwrap();
+//!
+//! assert_eq!(plaintext, b"Hello, world!");
+//! ```
+
+use crate::block::pipeline::{Padding, PipelineError};
+use crate::block::{pkcs7, BlockCipher, BlockMode, Key, PKCS7Error};
+
+/// Trait for block modes that can process a message across several calls.
+///
+/// Modes carry their chaining state (e.g. the previous ciphertext block in
+/// CBC) in an opaque byte buffer between calls.
+pub trait IncrementalBlockMode<C: BlockCipher>: BlockMode<C> {
+    /// State before processing any block.
+    fn initial_state(&self) -> Vec<u8>;
+
+    /// Encrypt block-aligned `plaintext` with `key`, updating `state`.
+    fn encrypt_blocks(
+        &self,
+        state: &mut Vec<u8>,
+        cipher: &C,
+        plaintext: &[u8],
+        key: &Key<C>,
+    ) -> Vec<u8>;
+
+    /// Decrypt block-aligned `ciphertext` with `key`, updating `state`.
+    fn decrypt_blocks(
+        &self,
+        state: &mut Vec<u8>,
+        cipher: &C,
+        ciphertext: &[u8],
+        key: &Key<C>,
+    ) -> Vec<u8>;
+}
+
+/// Stateful encryptor. Build it with
+/// [`Pipeline::streaming`](super::pipeline::Pipeline::streaming).
+#[must_use]
+pub struct BlockModeEncryptor<C, M> {
+    cipher: C,
+    mode: M,
+    key: Key<C>,
+    padding: Padding,
+    state: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl<C: BlockCipher, M: IncrementalBlockMode<C>> BlockModeEncryptor<C, M> {
+    pub(crate) fn new(cipher: C, mode: M, key: Key<C>, padding: Padding) -> Self {
+        BlockModeEncryptor {
+            state: mode.initial_state(),
+            cipher,
+            mode,
+            key,
+            padding,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Feed `plaintext`, returning the ciphertext for every complete block
+    /// so far.
+    pub fn update(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        self.buffer.extend_from_slice(plaintext);
+
+        let aligned_len = self.buffer.len() - self.buffer.len() % C::BLOCK_SIZE;
+        let blocks = self.buffer.drain(..aligned_len).collect::<Vec<_>>();
+
+        self.mode
+            .encrypt_blocks(&mut self.state, &self.cipher, &blocks, &self.key)
+    }
+
+    /// Pad (if needed) and encrypt the remaining input.
+    ///
+    /// # Errors
+    ///
+    /// If the input was not block-aligned when not padding.
+    pub fn finalize(mut self) -> Result<Vec<u8>, PipelineError> {
+        let blocks = match self.padding {
+            Padding::PKCS7 => pkcs7::pad(&self.buffer, C::BLOCK_SIZE as u8),
+            Padding::None if self.buffer.is_empty() => return Ok(Vec::new()),
+            Padding::None => return Err(PipelineError::UnalignedInput),
+        };
+
+        Ok(self
+            .mode
+            .encrypt_blocks(&mut self.state, &self.cipher, &blocks, &self.key))
+    }
+}
+
+/// Stateful decryptor. Build it with
+/// [`Pipeline::streaming`](super::pipeline::Pipeline::streaming).
+#[must_use]
+pub struct BlockModeDecryptor<C, M> {
+    cipher: C,
+    mode: M,
+    key: Key<C>,
+    padding: Padding,
+    state: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl<C: BlockCipher, M: IncrementalBlockMode<C>> BlockModeDecryptor<C, M> {
+    pub(crate) fn new(cipher: C, mode: M, key: Key<C>, padding: Padding) -> Self {
+        BlockModeDecryptor {
+            state: mode.initial_state(),
+            cipher,
+            mode,
+            key,
+            padding,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Feed `ciphertext`, returning the plaintext for every complete block
+    /// so far.
+    ///
+    /// When padding, the last complete block is held back until
+    /// [`finalize`](BlockModeDecryptor::finalize) since it could be the
+    /// padding block.
+    pub fn update(&mut self, ciphertext: &[u8]) -> Vec<u8> {
+        self.buffer.extend_from_slice(ciphertext);
+
+        let held_back = match self.padding {
+            Padding::PKCS7 => 1,
+            Padding::None => 0,
+        };
+
+        let aligned_len =
+            self.buffer.len().saturating_sub(held_back) / C::BLOCK_SIZE * C::BLOCK_SIZE;
+        let blocks = self.buffer.drain(..aligned_len).collect::<Vec<_>>();
+
+        self.mode
+            .decrypt_blocks(&mut self.state, &self.cipher, &blocks, &self.key)
+    }
+
+    /// Decrypt and unpad (if needed) the remaining input.
+    ///
+    /// # Errors
+    ///
+    /// If the input was not block-aligned, or due to wrong padding.
+    pub fn finalize(mut self) -> Result<Vec<u8>, PipelineError> {
+        if !self.buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+            return Err(PipelineError::UnalignedInput);
+        }
+
+        let mut decrypted =
+            self.mode
+                .decrypt_blocks(&mut self.state, &self.cipher, &self.buffer, &self.key);
+
+        if self.padding == Padding::PKCS7 {
+            if decrypted.is_empty() {
+                return Err(PKCS7Error::Empty.into());
+            }
+
+            pkcs7::unpad_vec(&mut decrypted, C::BLOCK_SIZE as u8)?;
+        }
+
+        Ok(decrypted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::block::pipeline::PipelineError;
+    use crate::block::{BlockMode, Decryptor, Encryptor, Iv, Key, PKCS7Error, AES128, CBC, ECB};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const IV: &[u8] = b"SUBMARINE YELLOW";
+    const PLAINTEXT: &[u8] =
+        b"We all live in a yellow submarine, a yellow submarine, a yellow submarine";
+
+    #[test]
+    fn test_matches_block_mode() {
+        for chunk_size in 1..40 {
+            let mut encryptor = Encryptor::aes128()
+                .cbc(IV)
+                .pkcs7()
+                .key(KEY)
+                .streaming()
+                .unwrap();
+
+            let mut ciphertext = PLAINTEXT
+                .chunks(chunk_size)
+                .flat_map(|chunk| encryptor.update(chunk))
+                .collect::<Vec<_>>();
+            ciphertext.extend(encryptor.finalize().unwrap());
+
+            assert_eq!(
+                ciphertext,
+                CBC::new(&Iv::new(IV).unwrap()).encrypt(
+                    &AES128,
+                    PLAINTEXT,
+                    &Key::new(KEY).unwrap()
+                )
+            );
+
+            let mut decryptor = Decryptor::aes128()
+                .cbc(IV)
+                .pkcs7()
+                .key(KEY)
+                .streaming()
+                .unwrap();
+
+            let mut plaintext = ciphertext
+                .chunks(chunk_size)
+                .flat_map(|chunk| decryptor.update(chunk))
+                .collect::<Vec<_>>();
+            plaintext.extend(decryptor.finalize().unwrap());
+
+            assert_eq!(plaintext, PLAINTEXT);
+        }
+    }
+
+    #[test]
+    fn test_ecb_unpadded() {
+        let mut encryptor = Encryptor::aes128()
+            .ecb()
+            .no_padding()
+            .key(KEY)
+            .streaming()
+            .unwrap();
+
+        assert_eq!(encryptor.update(&PLAINTEXT[..20]).len(), 16);
+        assert_eq!(encryptor.update(&PLAINTEXT[20..32]).len(), 16);
+        assert_eq!(encryptor.finalize(), Ok(vec![]));
+
+        let mut decryptor = Decryptor::aes128()
+            .ecb()
+            .no_padding()
+            .key(KEY)
+            .streaming()
+            .unwrap();
+
+        let ciphertext = ECB.encrypt_impl(&AES128, &PLAINTEXT[..32], &Key::new(KEY).unwrap());
+
+        assert_eq!(decryptor.update(&ciphertext), &PLAINTEXT[..32]);
+        assert_eq!(decryptor.finalize(), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            Encryptor::aes128().ecb().pkcs7().streaming(),
+            Err(PipelineError::MissingKey)
+        ));
+
+        let mut encryptor = Encryptor::aes128()
+            .ecb()
+            .no_padding()
+            .key(KEY)
+            .streaming()
+            .unwrap();
+        encryptor.update(PLAINTEXT);
+
+        assert_eq!(encryptor.finalize(), Err(PipelineError::UnalignedInput));
+
+        let decryptor = Decryptor::aes128()
+            .cbc(IV)
+            .pkcs7()
+            .key(KEY)
+            .streaming()
+            .unwrap();
+
+        assert_eq!(
+            decryptor.finalize(),
+            Err(PipelineError::Padding(PKCS7Error::Empty))
+        );
+    }
+}
@@ -0,0 +1,241 @@
+//! [Wide-block](https://en.wikipedia.org/wiki/Disk_encryption_theory#Wide-block_encryption)
+//! EME-style mode, where the whole message is encrypted as a single
+//! permutation.
+//!
+//! Unlike CBC or CTR, flipping a single bit in the ciphertext scrambles the
+//! whole decrypted message, so there are no targeted bitflipping attacks.
+//!
+//! Follows [EME](https://eprint.iacr.org/2003/147) (ECB-Mask-ECB) by Halevi and
+//! Rogaway, but uses little-endian `GF(2^128)` doubling and has not been
+//! checked against any test vectors. Do not expect interoperability.
+
+use crate::block::{BlockCipher, BlockMode, Key};
+use crate::util::iter::Xorable;
+
+/// EME-style wide-block mode with a `tweak` (e.g. a sector number).
+pub struct EME<'a> {
+    /// Tweak (must be exactly one block long)
+    tweak: &'a [u8],
+}
+
+impl<'a> EME<'a> {
+    /// Create an EME-style wide-block mode with a `tweak` block.
+    #[must_use]
+    pub const fn new(tweak: &'a [u8]) -> EME<'a> {
+        EME { tweak }
+    }
+
+    /// Common encryption/decryption skeleton (EME is symmetric except for the
+    /// direction of the block cipher).
+    fn process<F, G>(&self, data: &[u8], mut forward: F, mut core: G) -> Vec<u8>
+    where
+        F: FnMut(&[u8]) -> Vec<u8>,
+        G: FnMut(&[u8]) -> Vec<u8>,
+    {
+        assert_eq!(self.tweak.len(), BLOCK_SIZE, "Tweak must be one block long");
+        assert_eq!(data.len() % BLOCK_SIZE, 0, "Data must be block-aligned");
+
+        let block_count = data.len() / BLOCK_SIZE;
+
+        assert!(
+            (1..=BLOCK_SIZE * 8).contains(&block_count),
+            "EME needs between 1 and 128 blocks",
+        );
+
+        let l = double(&forward(&[0; BLOCK_SIZE]));
+
+        // First ECB layer, masked with 2^i * L
+        let mut mask = l.clone();
+        let mut blocks = Vec::with_capacity(block_count);
+
+        for block in data.chunks(BLOCK_SIZE) {
+            blocks.push(core(&block.xor(&mask).collect::<Vec<_>>()));
+            mask = double(&mask);
+        }
+
+        // Middle layer, mixing all blocks together
+        let mp = blocks
+            .iter()
+            .fold(self.tweak.to_vec(), |acc, block| xor(&acc, block));
+        let mc = core(&mp);
+        let m = xor(&mp, &mc);
+
+        let mut mask = m;
+
+        for block in blocks.iter_mut().skip(1) {
+            mask = double(&mask);
+            *block = xor(block, &mask);
+        }
+
+        blocks[0] = blocks
+            .iter()
+            .skip(1)
+            .fold(xor(&mc, self.tweak), |acc, block| xor(&acc, block));
+
+        // Last ECB layer, masked again with 2^i * L
+        let mut mask = l;
+        let mut output = Vec::with_capacity(data.len());
+
+        for block in blocks {
+            output.extend(core(&block).xor(&mask));
+            mask = double(&mask);
+        }
+
+        output
+    }
+}
+
+/// Only 128-bit blocks are supported (for `GF(2^128)` doubling).
+const BLOCK_SIZE: usize = 16;
+
+/// Multiply by `x` in `GF(2^128)` (little-endian, modulo `x^128 + x^7 + x^2 + x + 1`).
+fn double(block: &[u8]) -> Vec<u8> {
+    let carry = block[BLOCK_SIZE - 1] >> 7;
+
+    let mut doubled = block
+        .iter()
+        .scan(0, |previous_msb, &byte| {
+            let shifted = (byte << 1) | *previous_msb;
+            *previous_msb = byte >> 7;
+
+            Some(shifted)
+        })
+        .collect::<Vec<_>>();
+
+    doubled[0] ^= 0x87 * carry;
+
+    doubled
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.xor(b).collect()
+}
+
+impl<C: BlockCipher> BlockMode<C> for EME<'_> {
+    /// Encrypt `plaintext` in EME mode with `key` and `tweak` using
+    /// `BlockCipher`.
+    ///
+    /// # Panics
+    ///
+    /// - If `C::BLOCK_SIZE != 16`.
+    /// - If `tweak.len() != 16`.
+    /// - If `plaintext` is not between 1 and 128 blocks long.
+    fn encrypt_impl(&self, cipher: &C, plaintext: &[u8], key: &Key<C>) -> Vec<u8> {
+        assert_eq!(C::BLOCK_SIZE, BLOCK_SIZE);
+
+        self.process(
+            plaintext,
+            |block| cipher.encrypt_block(block, key),
+            |block| cipher.encrypt_block(block, key),
+        )
+    }
+
+    /// Decrypt `ciphertext` in EME mode with `key` and `tweak` using
+    /// `BlockCipher`.
+    ///
+    /// # Panics
+    ///
+    /// - If `C::BLOCK_SIZE != 16`.
+    /// - If `tweak.len() != 16`.
+    /// - If `ciphertext` is not between 1 and 128 blocks long.
+    fn decrypt_impl(&self, cipher: &C, ciphertext: &[u8], key: &Key<C>) -> Vec<u8> {
+        assert_eq!(C::BLOCK_SIZE, BLOCK_SIZE);
+
+        self.process(
+            ciphertext,
+            |block| cipher.encrypt_block(block, key),
+            |block| cipher.decrypt_block(block, key),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EME;
+    use crate::block::{BlockCipher, BlockMode, Iv, Key, AES128, CBC};
+    use crate::util::generate_bytes;
+
+    const SECTOR_BLOCKS: usize = 32;
+
+    fn differing_blocks(a: &[u8], b: &[u8]) -> usize {
+        a.chunks(AES128::BLOCK_SIZE)
+            .zip(b.chunks(AES128::BLOCK_SIZE))
+            .filter(|(x, y)| x != y)
+            .count()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let key = Key::<AES128>::random();
+        let tweak = generate_bytes(AES128::BLOCK_SIZE);
+
+        for len in &[0, 1, 15, 16, 17, 100, 512] {
+            let plaintext = generate_bytes(*len);
+
+            let ciphertext = EME::new(&tweak).encrypt(&AES128, &plaintext, &key);
+            let decrypted = EME::new(&tweak).decrypt(&AES128, &ciphertext, &key);
+
+            assert_eq!(decrypted, Ok(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_tweak_changes_ciphertext() {
+        let key = Key::<AES128>::random();
+        let plaintext = generate_bytes(AES128::BLOCK_SIZE * SECTOR_BLOCKS);
+
+        let a = EME::new(&[0; 16]).encrypt_impl(&AES128, &plaintext, &key);
+        let b = EME::new(&[1; 16]).encrypt_impl(&AES128, &plaintext, &key);
+
+        assert_eq!(differing_blocks(&a, &b), SECTOR_BLOCKS);
+    }
+
+    #[test]
+    fn test_plaintext_bitflip_diffuses() {
+        let key = Key::<AES128>::random();
+        let tweak = generate_bytes(AES128::BLOCK_SIZE);
+        let plaintext = generate_bytes(AES128::BLOCK_SIZE * SECTOR_BLOCKS);
+
+        let mut flipped = plaintext.clone();
+        flipped[plaintext.len() - 1] ^= 1;
+
+        let a = EME::new(&tweak).encrypt_impl(&AES128, &plaintext, &key);
+        let b = EME::new(&tweak).encrypt_impl(&AES128, &flipped, &key);
+
+        assert_eq!(differing_blocks(&a, &b), SECTOR_BLOCKS);
+    }
+
+    #[test]
+    fn test_ciphertext_bitflip_scrambles_everything() {
+        let key = Key::<AES128>::random();
+        let tweak = generate_bytes(AES128::BLOCK_SIZE);
+        let plaintext = generate_bytes(AES128::BLOCK_SIZE * SECTOR_BLOCKS);
+
+        // CBC: a flip only garbles its block and flips the same bit in the next one
+        let iv = Iv::new(&tweak).unwrap();
+        let mut ciphertext = CBC::new(&iv).encrypt_impl(&AES128, &plaintext, &key);
+        ciphertext[AES128::BLOCK_SIZE] ^= 1;
+        let decrypted = CBC::new(&iv).decrypt_impl(&AES128, &ciphertext, &key);
+
+        assert_eq!(differing_blocks(&plaintext, &decrypted), 2);
+        assert_eq!(
+            decrypted[AES128::BLOCK_SIZE * 2] ^ plaintext[AES128::BLOCK_SIZE * 2],
+            1
+        );
+
+        // EME: the whole sector is garbled
+        let mut ciphertext = EME::new(&tweak).encrypt_impl(&AES128, &plaintext, &key);
+        ciphertext[AES128::BLOCK_SIZE] ^= 1;
+        let decrypted = EME::new(&tweak).decrypt_impl(&AES128, &ciphertext, &key);
+
+        assert_eq!(differing_blocks(&plaintext, &decrypted), SECTOR_BLOCKS);
+    }
+
+    #[test]
+    #[should_panic(expected = "EME needs between 1 and 128 blocks")]
+    fn test_too_many_blocks() {
+        let key = Key::<AES128>::random();
+
+        EME::new(&[0; 16]).encrypt_impl(&AES128, &[0; 16 * 129], &key);
+    }
+}
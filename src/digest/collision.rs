@@ -0,0 +1,299 @@
+//! Known [collisions](https://en.wikipedia.org/wiki/Collision_attack) in
+//! Merkle–Damgård digests, and detection of their blocks in messages.
+//!
+//! The [SHAttered](https://shattered.io/) SHA-1 collision is an
+//! identical-prefix collision: both PDFs share a prefix, differ in two
+//! near-collision blocks that leave the same internal state, and share the
+//! rest. So, once the colliding blocks are known:
+//!
+//! - Any common suffix can be [appended](Collision::extend) and the results
+//!   still collide.
+//! - Messages reusing them can be [flagged](detect_collision_blocks) by
+//!   looking for those exact blocks, a cheap stand-in for the
+//!   counter-cryptanalysis in hardened SHA-1 (which detects *unknown*
+//!   collision attempts from their disturbance vectors).
+//!
+//! The collision files are not bundled: download `shattered-1.pdf` and
+//! `shattered-2.pdf` and load them with [`Collision::new`].
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use crate::digest::Digest;
+
+/// Why two messages are not a collision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CollisionError {
+    /// The messages have different lengths.
+    LengthMismatch,
+
+    /// The messages are equal.
+    Identical,
+
+    /// The messages do not hash to the same digest (once trimmed after the
+    /// last differing block).
+    DigestMismatch,
+}
+
+/// A verified identical-prefix collision for digest `D`, trimmed right after
+/// its last differing block.
+pub struct Collision<D> {
+    a: Vec<u8>,
+    b: Vec<u8>,
+    blocks: Range<usize>,
+    digest: PhantomData<D>,
+}
+
+impl<D: Digest> Collision<D> {
+    /// Verify that `a` and `b` (e.g. the contents of both SHAttered PDFs)
+    /// collide.
+    ///
+    /// Any common data after the last differing block is trimmed, and the
+    /// trimmed messages must collide too (i.e. they must reach the same
+    /// internal state).
+    ///
+    /// # Errors
+    ///
+    /// See [`CollisionError`].
+    pub fn new(a: &[u8], b: &[u8]) -> Result<Collision<D>, CollisionError> {
+        if a.len() != b.len() {
+            return Err(CollisionError::LengthMismatch);
+        }
+
+        let differs = |(x, y): (&u8, &u8)| x != y;
+
+        let first = a
+            .iter()
+            .zip(b)
+            .position(differs)
+            .ok_or(CollisionError::Identical)?;
+        let last = a.iter().zip(b).rposition(differs).unwrap_or(first);
+
+        let blocks = first / D::BLOCK_LENGTH..last / D::BLOCK_LENGTH + 1;
+        let len = (blocks.end * D::BLOCK_LENGTH).min(a.len());
+
+        let collision = Collision {
+            a: a[..len].to_vec(),
+            b: b[..len].to_vec(),
+            blocks,
+            digest: PhantomData,
+        };
+
+        if D::digest(&collision.a).as_ref() != D::digest(&collision.b).as_ref() {
+            return Err(CollisionError::DigestMismatch);
+        }
+
+        Ok(collision)
+    }
+
+    /// Both colliding messages (up to the last differing block).
+    #[must_use]
+    pub fn messages(&self) -> (&[u8], &[u8]) {
+        (&self.a, &self.b)
+    }
+
+    /// Indices of the blocks that differ between both messages.
+    #[must_use]
+    pub fn differing_blocks(&self) -> Range<usize> {
+        self.blocks.clone()
+    }
+
+    /// The differing blocks of both messages.
+    #[must_use]
+    pub fn colliding_blocks(&self) -> (&[u8], &[u8]) {
+        let bytes = self.byte_range();
+
+        (&self.a[bytes.clone()], &self.b[bytes])
+    }
+
+    /// A new pair of colliding messages, by appending `suffix` to both.
+    ///
+    /// Only works if the collision is block-aligned (otherwise, the internal
+    /// state is not the same yet after the differing blocks).
+    #[must_use]
+    pub fn extend(&self, suffix: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        ([&self.a, suffix].concat(), [&self.b, suffix].concat())
+    }
+
+    fn byte_range(&self) -> Range<usize> {
+        self.blocks.start * D::BLOCK_LENGTH..(self.blocks.end * D::BLOCK_LENGTH).min(self.a.len())
+    }
+}
+
+// Not derived, since that would need `D` to implement these traits too
+
+impl<D> Clone for Collision<D> {
+    fn clone(&self) -> Self {
+        Collision {
+            a: self.a.clone(),
+            b: self.b.clone(),
+            blocks: self.blocks.clone(),
+            digest: PhantomData,
+        }
+    }
+}
+
+impl<D> fmt::Debug for Collision<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collision")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .field("blocks", &self.blocks)
+            .finish()
+    }
+}
+
+impl<D> PartialEq for Collision<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.a == other.a && self.b == other.b
+    }
+}
+
+impl<D> Eq for Collision<D> {}
+
+/// Find the blocks of any `known` collision in `message`.
+///
+/// Returns the index of the first block (aligned to
+/// [`BLOCK_LENGTH`](Digest::BLOCK_LENGTH)) where either side of a known
+/// collision's differing blocks starts.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::digest::collision::{detect_collision_blocks, Collision};
+/// use rustopals::digest::ToyHash;
+///
+/// let (a, b, _) = ToyHash::find_block_collision(ToyHash::INITIAL_STATE);
+/// let collision = Collision::<ToyHash>::new(&a, &b).unwrap();
+///
+/// let (_, forged) = collision.extend(b"pay 1000 EUR");
+///
+/// assert_eq!(detect_collision_blocks(&forged, std::slice::from_ref(&collision)), Some(0));
+/// assert_eq!(detect_collision_blocks(b"pay 1000 EUR", &[collision]), None);
+/// ```
+#[must_use]
+pub fn detect_collision_blocks<D: Digest>(message: &[u8], known: &[Collision<D>]) -> Option<usize> {
+    let blocks = message.len().div_ceil(D::BLOCK_LENGTH);
+
+    (0..blocks).find(|&block| {
+        let rest = &message[block * D::BLOCK_LENGTH..];
+
+        known.iter().any(|collision| {
+            let (a, b) = collision.colliding_blocks();
+
+            rest.starts_with(a) || rest.starts_with(b)
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{detect_collision_blocks, Collision, CollisionError};
+    use crate::digest::{Digest, ToyHash, SHA1};
+
+    fn toy_collision() -> Collision<ToyHash> {
+        // Common prefix, colliding blocks, common (trimmed) suffix
+        let prefix = ToyHash::new().chain(b"common prefix!!!");
+        let (a, b, _) = ToyHash::find_block_collision(prefix.state());
+
+        Collision::new(
+            &[b"common prefix!!!", &a[..], b"common suffix"].concat(),
+            &[b"common prefix!!!", &b[..], b"common suffix"].concat(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let collision = toy_collision();
+        let (a, b) = collision.messages();
+
+        assert_eq!(collision.differing_blocks(), 1..2);
+        assert_eq!(a.len(), 32);
+        assert_eq!(&a[..16], b"common prefix!!!");
+        assert_ne!(a, b);
+        assert_eq!(ToyHash::digest(a), ToyHash::digest(b));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Collision::<SHA1>::new(b"foo", b"foobar"),
+            Err(CollisionError::LengthMismatch)
+        );
+        assert_eq!(
+            Collision::<SHA1>::new(b"foo", b"foo"),
+            Err(CollisionError::Identical)
+        );
+        assert_eq!(
+            Collision::<SHA1>::new(b"foo", b"bar"),
+            Err(CollisionError::DigestMismatch)
+        );
+    }
+
+    #[test]
+    fn test_extend() {
+        let collision = toy_collision();
+
+        for suffix in [
+            &b""[..],
+            b"x",
+            b"a much longer suffix spanning several blocks",
+        ] {
+            let (a, b) = collision.extend(suffix);
+
+            assert_ne!(a, b);
+            assert_eq!(ToyHash::digest(&a), ToyHash::digest(&b));
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let collision = toy_collision();
+        let (a, b) = collision.colliding_blocks();
+        let known = std::slice::from_ref(&collision);
+
+        let message = [&[0x42; 48][..], b, b"trailer"].concat();
+
+        assert_eq!(detect_collision_blocks(&message, known), Some(3));
+        assert_eq!(detect_collision_blocks(a, known), Some(0));
+
+        // Not block-aligned
+        let message = [&[0x42; 47][..], b, b"trailer"].concat();
+
+        assert_eq!(detect_collision_blocks(&message, known), None);
+        assert_eq!(detect_collision_blocks(b"", known), None);
+    }
+
+    // Needs both PDFs from https://shattered.io, e.g.:
+    //
+    //     SHATTERED_1=shattered-1.pdf SHATTERED_2=shattered-2.pdf \
+    //         cargo test -- --ignored test_shattered
+    #[test]
+    #[ignore = "needs the SHAttered PDFs"]
+    fn test_shattered() {
+        let read = |var| std::fs::read(std::env::var(var).unwrap()).unwrap();
+
+        let (pdf_1, pdf_2) = (read("SHATTERED_1"), read("SHATTERED_2"));
+
+        assert_ne!(pdf_1, pdf_2);
+        assert_eq!(
+            hex::encode(SHA1::digest(&pdf_1)),
+            "38762cf7f55934b34d179ae6a4c80cadccbb7f0a"
+        );
+        assert_eq!(SHA1::digest(&pdf_1), SHA1::digest(&pdf_2));
+
+        let collision = Collision::<SHA1>::new(&pdf_1, &pdf_2).unwrap();
+
+        assert_eq!(collision.differing_blocks(), 3..5);
+        assert_eq!(
+            detect_collision_blocks(&pdf_2, std::slice::from_ref(&collision)),
+            Some(3)
+        );
+
+        let (a, b) = collision.extend(b"any suffix");
+
+        assert_eq!(SHA1::digest(&a), SHA1::digest(&b));
+    }
+}
@@ -0,0 +1,108 @@
+//! [Domain separation](https://en.wikipedia.org/wiki/Domain_separation) helpers
+//! for hashing.
+//!
+//! Naively hashing concatenated values (`H(a || b)`) is ambiguous: `("ab", "c")`
+//! and `("a", "bc")` hash to the same value. Here every input is length-prefixed
+//! and tagged with a label, so different contexts (or different splits of the
+//! same bytes) never collide.
+
+use super::Digest;
+
+/// Unambiguously encode a `label` and a list of `parts`.
+///
+/// Each element is prefixed by its length as a big-endian `u64`.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::digest::ds::encode_labeled;
+///
+/// assert_eq!(
+///     encode_labeled(b"L", &[b"ab", b""]),
+///     &[
+///         0, 0, 0, 0, 0, 0, 0, 1, b'L',
+///         0, 0, 0, 0, 0, 0, 0, 2, b'a', b'b',
+///         0, 0, 0, 0, 0, 0, 0, 0,
+///     ],
+/// );
+///
+/// assert_ne!(
+///     encode_labeled(b"L", &[b"ab", b"c"]),
+///     encode_labeled(b"L", &[b"a", b"bc"]),
+/// );
+/// ```
+#[must_use]
+pub fn encode_labeled(label: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+
+    for part in std::iter::once(&label).chain(parts) {
+        encoded.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        encoded.extend_from_slice(part);
+    }
+
+    encoded
+}
+
+/// Hash a list of `parts` under a `label`, using [`encode_labeled`].
+///
+/// # Example
+///
+/// ```
+/// use rustopals::digest::ds::hash_labeled;
+/// use rustopals::digest::SHA256;
+///
+/// assert_ne!(
+///     hash_labeled::<SHA256>(b"context A", &[b"data"]),
+///     hash_labeled::<SHA256>(b"context B", &[b"data"]),
+/// );
+/// ```
+#[must_use]
+pub fn hash_labeled<D: Digest>(label: &[u8], parts: &[&[u8]]) -> D::Output {
+    let mut digest = D::default();
+
+    for part in std::iter::once(&label).chain(parts) {
+        digest.update(&(part.len() as u64).to_be_bytes());
+        digest.update(part);
+    }
+
+    digest.finalize()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_labeled, hash_labeled};
+    use crate::digest::{Digest, SHA1, SHA256};
+
+    #[test]
+    fn test_matches_encoding() {
+        let parts: &[&[u8]] = &[b"hello", b"", b"world"];
+
+        assert_eq!(
+            hash_labeled::<SHA256>(b"label", parts),
+            SHA256::digest(&encode_labeled(b"label", parts))
+        );
+    }
+
+    #[test]
+    fn test_no_ambiguity() {
+        let candidates: &[(&[u8], &[&[u8]])] = &[
+            (b"", &[b"abc"]),
+            (b"a", &[b"bc"]),
+            (b"ab", &[b"c"]),
+            (b"abc", &[]),
+            (b"abc", &[b""]),
+            (b"a", &[b"b", b"c"]),
+            (b"a", &[b"bc", b""]),
+            (b"a", &[b"", b"bc"]),
+        ];
+
+        for (i, (label_a, parts_a)) in candidates.iter().enumerate() {
+            for (label_b, parts_b) in &candidates[i + 1..] {
+                assert_ne!(
+                    hash_labeled::<SHA1>(label_a, parts_a),
+                    hash_labeled::<SHA1>(label_b, parts_b)
+                );
+            }
+        }
+    }
+}
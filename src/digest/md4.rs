@@ -110,10 +110,7 @@ impl Default for MD4 {
 impl Digest for MD4 {
     const OUTPUT_LENGTH: usize = 16;
     const BLOCK_LENGTH: usize = 64;
-    const ASN1_PREFIX: &'static [u8] = &[
-        0x30, 0x20, 0x30, 0x0c, 0x06, 0x08, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x04, 0x05,
-        0x00, 0x04, 0x10,
-    ];
+    const OID: &'static [u64] = &[1, 2, 840, 113_549, 2, 4];
 
     type Output = [u8; Self::OUTPUT_LENGTH];
 
@@ -223,7 +220,7 @@ impl Digest for MD4 {
         // Add zero-padding
         let padding_len = Self::BLOCK_LENGTH
             - ((1 + ml.len() as u64 + message_len) % Self::BLOCK_LENGTH as u64) as usize;
-        self.update(&vec![0; padding_len as usize]);
+        self.update(&vec![0; padding_len % Self::BLOCK_LENGTH]);
 
         // Add message length
         self.update(&ml);
@@ -250,20 +247,26 @@ impl ExtensibleDigest for MD4 {
         let mut ml = [0; 8];
         LittleEndian::write_u64(&mut ml, 8 * guessed_payload_length as u64);
 
-        let guessed_padding_len =
-            Self::BLOCK_LENGTH - ((1 + ml.len() + guessed_payload_length) % Self::BLOCK_LENGTH);
-        let guessed_block_len = 1 + guessed_payload_length as u64 / Self::BLOCK_LENGTH as u64;
+        let guessed_padding_len = (Self::BLOCK_LENGTH
+            - ((1 + ml.len() + guessed_payload_length) % Self::BLOCK_LENGTH))
+            % Self::BLOCK_LENGTH;
+        let guessed_payload = [[0x80].as_ref(), &vec![0; guessed_padding_len], &ml].concat();
+        let guessed_block_len =
+            ((guessed_payload_length + guessed_payload.len()) / Self::BLOCK_LENGTH) as u64;
 
         let cracked_digest = MD4::new_from_hash(digest_output, guessed_block_len);
-        let cracked_payload = [[0x80].as_ref(), &vec![0; guessed_padding_len], &ml].concat();
 
-        (cracked_digest, cracked_payload)
+        (cracked_digest, guessed_payload)
+    }
+
+    fn new_from_iv(iv: Self::Output) -> Self {
+        MD4::new_from_hash(iv, 0)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::digest::{Digest, MD4};
+    use crate::digest::{Digest, ExtensibleDigest, MD4};
 
     const EMPTY_STRING_MD4: [u8; 16] = [
         0x31, 0xd6, 0xcf, 0xe0, 0xd1, 0x6a, 0xe9, 0x31, 0xb7, 0x3c, 0x59, 0xd7, 0xe0, 0xc0, 0x89,
@@ -282,6 +285,14 @@ mod test {
         0x9f,
     ];
 
+    // 55 bytes + 0x80 + 8-byte length fill the last block exactly, so the
+    // zero-padding length must be reduced modulo the block length (otherwise
+    // a spurious all-zero block gets hashed)
+    const A_55_MD4: [u8; 16] = [
+        0xc8, 0x89, 0xc8, 0x1d, 0xd8, 0x6c, 0x4d, 0x2e, 0x02, 0x57, 0x78, 0x94, 0x4e, 0xa0, 0x28,
+        0x81,
+    ];
+
     #[test]
     fn basic_md4() {
         // ""
@@ -333,4 +344,38 @@ mod test {
             SET1_SOLUTION_6_MD4
         );
     }
+
+    #[test]
+    fn padding_boundaries() {
+        assert_eq!(MD4::digest(&[0x61; 55]), A_55_MD4);
+
+        // Lengths around the point where the length field spills into a new block
+        for len in 50..=70 {
+            let message = vec![0x61; len];
+
+            let mut digest = MD4::new();
+            digest.update(&message[..len / 3]);
+            digest.update(&message[len / 3..]);
+
+            assert_eq!(digest.finalize(), MD4::digest(&message));
+        }
+    }
+
+    #[test]
+    fn length_extension() {
+        const EXTENSION: &[u8] = b";admin=true";
+
+        // Including lengths where the padding spills into a new block
+        for secret_len in [0, 27, 55, 56, 64, 120, 300] {
+            let secret = vec![0x42; secret_len];
+            let original = MD4::digest(&secret);
+
+            let (digest, padding) = MD4::extend_digest(original, secret.len());
+
+            assert_eq!(
+                digest.chain(EXTENSION).finalize(),
+                MD4::digest_parts(&[&secret, &padding, EXTENSION])
+            );
+        }
+    }
 }
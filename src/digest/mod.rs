@@ -1,21 +1,39 @@
 //! [Message digest](https://en.wikipedia.org/wiki/Message_digest) implementations
 //! and related utilities.
 
+pub mod collision;
+pub mod ds;
 pub mod md4;
 pub mod sha1;
 pub mod sha256;
+pub mod sha512;
+pub mod toy;
+
+use std::convert::TryInto;
+use std::fmt;
 
 pub use md4::MD4;
 pub use sha1::SHA1;
 pub use sha256::SHA256;
+pub use sha512::SHA512;
+pub use toy::ToyHash;
+
+use crate::util::asn1::DigestInfoPrefix;
 
 /// Trait for [message digest](https://en.wikipedia.org/wiki/Message_digest) implementations.
 pub trait Digest: Default {
     const OUTPUT_LENGTH: usize;
     const BLOCK_LENGTH: usize;
-    const ASN1_PREFIX: &'static [u8];
 
-    type Output: AsRef<[u8]>;
+    /// Object identifier arcs (empty if the digest has none).
+    const OID: &'static [u64];
+
+    /// DER-encoded `DigestInfo` prefix for PKCS#1 v1.5 signatures, derived
+    /// from [`OID`](Digest::OID).
+    const ASN1_PREFIX: &'static [u8] =
+        DigestInfoPrefix::new(Self::OID, Self::OUTPUT_LENGTH).as_slice();
+
+    type Output: DigestOutput + Into<Vec<u8>> + Copy + Eq + fmt::Debug;
 
     /// Update the digest with `message` bytes
     fn update(&mut self, message: &[u8]);
@@ -33,6 +51,25 @@ pub trait Digest: Default {
         self
     }
 
+    /// Update the digest with every fragment in `parts`, in order, as if
+    /// they were concatenated (but without allocating).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustopals::digest::{Digest, SHA256};
+    ///
+    /// let mut digest = SHA256::default();
+    /// digest.update_iter([&b"foo"[..], b"bar"]);
+    ///
+    /// assert_eq!(digest.finalize(), SHA256::digest(b"foobar"));
+    /// ```
+    fn update_iter<'a>(&mut self, parts: impl IntoIterator<Item = &'a [u8]>) {
+        for part in parts {
+            self.update(part);
+        }
+    }
+
     /// Convenience method to create, update and finalize the digest with `message` bytes
     #[must_use]
     fn digest(message: &[u8]) -> Self::Output
@@ -41,6 +78,66 @@ pub trait Digest: Default {
     {
         Self::default().chain(message).finalize()
     }
+
+    /// Convenience method to create, update and finalize the digest with the
+    /// concatenation of `parts` (without allocating it).
+    ///
+    /// Note that this is ambiguous: `["ab", "c"]` and `["a", "bc"]` hash the
+    /// same. Use [`hash_labeled`](ds::hash_labeled) to hash structured data.
+    #[must_use]
+    fn digest_parts(parts: &[&[u8]]) -> Self::Output {
+        let mut digest = Self::default();
+        digest.update_iter(parts.iter().copied());
+        digest.finalize()
+    }
+}
+
+/// Conveniences for digest outputs, i.e. byte arrays.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::digest::{Digest, DigestOutput, SHA1};
+///
+/// let digest = SHA1::digest(b"abc");
+///
+/// assert_eq!(
+///     digest.hex().to_string(),
+///     "a9993e364706816aba3e25717850c26c9cd0d89d"
+/// );
+/// assert_eq!(DigestOutput::try_from_hex(&digest.hex().to_string()), Some(digest));
+/// assert_eq!(Vec::from(digest), digest.to_vec());
+/// ```
+pub trait DigestOutput: AsRef<[u8]> + Sized {
+    /// Parse a hex-encoded output (of the right length).
+    fn try_from_hex(hex: &str) -> Option<Self>;
+
+    /// Wrap to display as lowercase hex.
+    fn hex(&self) -> Hex<'_> {
+        Hex(self.as_ref())
+    }
+}
+
+impl<const N: usize> DigestOutput for [u8; N] {
+    fn try_from_hex(hex: &str) -> Option<Self> {
+        hex::decode(hex).ok()?.try_into().ok()
+    }
+}
+
+/// Displays some bytes as lowercase hex (without allocating).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl fmt::Debug for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hex({self})")
+    }
 }
 
 /// Trait for digests that can be subject to
@@ -65,4 +162,11 @@ pub trait ExtensibleDigest: Digest + Sized {
         digest_output: <Self as Digest>::Output,
         guessed_payload_length: usize,
     ) -> (Self, Vec<u8>);
+
+    /// Create an instance whose chaining value starts at `iv` instead of the
+    /// standard initial values.
+    ///
+    /// This keys the digest through its IV, as in
+    /// [`Nmac`](crate::mac::nmac::Nmac).
+    fn new_from_iv(iv: <Self as Digest>::Output) -> Self;
 }
@@ -75,9 +75,7 @@ impl Default for SHA1 {
 impl Digest for SHA1 {
     const OUTPUT_LENGTH: usize = 20;
     const BLOCK_LENGTH: usize = 64;
-    const ASN1_PREFIX: &'static [u8] = &[
-        0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
-    ];
+    const OID: &'static [u64] = &[1, 3, 14, 3, 2, 26];
 
     type Output = [u8; Self::OUTPUT_LENGTH];
 
@@ -153,7 +151,7 @@ impl Digest for SHA1 {
         // Add zero-padding
         let padding_len = Self::BLOCK_LENGTH
             - ((1 + ml.len() as u64 + message_len) % Self::BLOCK_LENGTH as u64) as usize;
-        self.update(&vec![0; padding_len as usize]);
+        self.update(&vec![0; padding_len % Self::BLOCK_LENGTH]);
 
         // Add message length
         self.update(&ml);
@@ -181,20 +179,26 @@ impl ExtensibleDigest for SHA1 {
         let mut ml = [0; 8];
         BigEndian::write_u64(&mut ml, 8 * guessed_payload_length as u64);
 
-        let guessed_padding_len =
-            Self::BLOCK_LENGTH - ((1 + ml.len() + guessed_payload_length) % Self::BLOCK_LENGTH);
-        let guessed_block_len = 1 + guessed_payload_length as u64 / Self::BLOCK_LENGTH as u64;
+        let guessed_padding_len = (Self::BLOCK_LENGTH
+            - ((1 + ml.len() + guessed_payload_length) % Self::BLOCK_LENGTH))
+            % Self::BLOCK_LENGTH;
+        let guessed_payload = [[0x80].as_ref(), &vec![0; guessed_padding_len], &ml].concat();
+        let guessed_block_len =
+            ((guessed_payload_length + guessed_payload.len()) / Self::BLOCK_LENGTH) as u64;
 
         let cracked_digest = SHA1::new_from_hash(digest_output, guessed_block_len);
-        let cracked_payload = [[0x80].as_ref(), &vec![0; guessed_padding_len], &ml].concat();
 
-        (cracked_digest, cracked_payload)
+        (cracked_digest, guessed_payload)
+    }
+
+    fn new_from_iv(iv: Self::Output) -> Self {
+        SHA1::new_from_hash(iv, 0)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::digest::{Digest, SHA1};
+    use crate::digest::{Digest, ExtensibleDigest, SHA1};
 
     const EMPTY_STRING_SHA1: [u8; 20] = [
         0xda, 0x39, 0xa3, 0xee, 0x5e, 0x6b, 0x4b, 0x0d, 0x32, 0x55, 0xbf, 0xef, 0x95, 0x60, 0x18,
@@ -213,6 +217,14 @@ mod test {
         0x32, 0x5c, 0x3f, 0x91, 0xa6,
     ];
 
+    // 55 bytes + 0x80 + 8-byte length fill the last block exactly, so the
+    // zero-padding length must be reduced modulo the block length (otherwise
+    // a spurious all-zero block gets hashed)
+    const A_55_SHA1: [u8; 20] = [
+        0xc1, 0xc8, 0xbb, 0xdc, 0x22, 0x79, 0x6e, 0x28, 0xc0, 0xe1, 0x51, 0x63, 0xd2, 0x08, 0x99,
+        0xb6, 0x56, 0x21, 0xd6, 0x5a,
+    ];
+
     #[test]
     fn basic_sha1() {
         // ""
@@ -264,4 +276,38 @@ mod test {
             SET1_SOLUTION_6_SHA1
         );
     }
+
+    #[test]
+    fn padding_boundaries() {
+        assert_eq!(SHA1::digest(&[0x61; 55]), A_55_SHA1);
+
+        // Lengths around the point where the length field spills into a new block
+        for len in 50..=70 {
+            let message = vec![0x61; len];
+
+            let mut digest = SHA1::new();
+            digest.update(&message[..len / 3]);
+            digest.update(&message[len / 3..]);
+
+            assert_eq!(digest.finalize(), SHA1::digest(&message));
+        }
+    }
+
+    #[test]
+    fn length_extension() {
+        const EXTENSION: &[u8] = b";admin=true";
+
+        // Including lengths where the padding spills into a new block
+        for secret_len in [0, 27, 55, 56, 64, 120, 300] {
+            let secret = vec![0x42; secret_len];
+            let original = SHA1::digest(&secret);
+
+            let (digest, padding) = SHA1::extend_digest(original, secret.len());
+
+            assert_eq!(
+                digest.chain(EXTENSION).finalize(),
+                SHA1::digest_parts(&[&secret, &padding, EXTENSION])
+            );
+        }
+    }
 }
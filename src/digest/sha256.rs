@@ -102,10 +102,7 @@ impl Default for SHA256 {
 impl Digest for SHA256 {
     const OUTPUT_LENGTH: usize = 32;
     const BLOCK_LENGTH: usize = 64;
-    const ASN1_PREFIX: &'static [u8] = &[
-        0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
-        0x05, 0x00, 0x04, 0x20,
-    ];
+    const OID: &'static [u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 1];
 
     type Output = [u8; Self::OUTPUT_LENGTH];
 
@@ -192,7 +189,7 @@ impl Digest for SHA256 {
         // Add zero-padding
         let padding_len = Self::BLOCK_LENGTH
             - ((1 + ml.len() as u64 + message_len) % Self::BLOCK_LENGTH as u64) as usize;
-        self.update(&vec![0; padding_len as usize]);
+        self.update(&vec![0; padding_len % Self::BLOCK_LENGTH]);
 
         // Add message length
         self.update(&ml);
@@ -223,20 +220,26 @@ impl ExtensibleDigest for SHA256 {
         let mut ml = [0; 8];
         BigEndian::write_u64(&mut ml, 8 * guessed_payload_length as u64);
 
-        let guessed_padding_len =
-            Self::BLOCK_LENGTH - ((1 + ml.len() + guessed_payload_length) % Self::BLOCK_LENGTH);
-        let guessed_block_len = 1 + guessed_payload_length as u64 / Self::BLOCK_LENGTH as u64;
+        let guessed_padding_len = (Self::BLOCK_LENGTH
+            - ((1 + ml.len() + guessed_payload_length) % Self::BLOCK_LENGTH))
+            % Self::BLOCK_LENGTH;
+        let guessed_payload = [[0x80].as_ref(), &vec![0; guessed_padding_len], &ml].concat();
+        let guessed_block_len =
+            ((guessed_payload_length + guessed_payload.len()) / Self::BLOCK_LENGTH) as u64;
 
         let cracked_digest = SHA256::new_from_hash(digest_output, guessed_block_len);
-        let cracked_payload = [[0x80].as_ref(), &vec![0; guessed_padding_len], &ml].concat();
 
-        (cracked_digest, cracked_payload)
+        (cracked_digest, guessed_payload)
+    }
+
+    fn new_from_iv(iv: Self::Output) -> Self {
+        SHA256::new_from_hash(iv, 0)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::digest::{Digest, SHA256};
+    use crate::digest::{Digest, ExtensibleDigest, SHA256};
 
     const EMPTY_STRING_SHA256: [u8; 32] = [
         0xe3, 0xb0, 0xc4, 0x42, 0x98, 0xfc, 0x1c, 0x14, 0x9a, 0xfb, 0xf4, 0xc8, 0x99, 0x6f, 0xb9,
@@ -258,6 +261,15 @@ mod test {
         0x69, 0xb6,
     ];
 
+    // 55 bytes + 0x80 + 8-byte length fill the last block exactly, so the
+    // zero-padding length must be reduced modulo the block length (otherwise
+    // a spurious all-zero block gets hashed)
+    const A_55_SHA256: [u8; 32] = [
+        0x9f, 0x43, 0x90, 0xf8, 0xd3, 0x0c, 0x2d, 0xd9, 0x2e, 0xc9, 0xf0, 0x95, 0xb6, 0x5e, 0x2b,
+        0x9a, 0xe9, 0xb0, 0xa9, 0x25, 0xa5, 0x25, 0x8e, 0x24, 0x1c, 0x9f, 0x1e, 0x91, 0x0f, 0x73,
+        0x43, 0x18,
+    ];
+
     #[test]
     fn basic_sha256() {
         // ""
@@ -309,4 +321,38 @@ mod test {
             SET1_SOLUTION_6_SHA256
         );
     }
+
+    #[test]
+    fn padding_boundaries() {
+        assert_eq!(SHA256::digest(&[0x61; 55]), A_55_SHA256);
+
+        // Lengths around the point where the length field spills into a new block
+        for len in 50..=70 {
+            let message = vec![0x61; len];
+
+            let mut digest = SHA256::new();
+            digest.update(&message[..len / 3]);
+            digest.update(&message[len / 3..]);
+
+            assert_eq!(digest.finalize(), SHA256::digest(&message));
+        }
+    }
+
+    #[test]
+    fn length_extension() {
+        const EXTENSION: &[u8] = b";admin=true";
+
+        // Including lengths where the padding spills into a new block
+        for secret_len in [0, 27, 55, 56, 64, 120, 300] {
+            let secret = vec![0x42; secret_len];
+            let original = SHA256::digest(&secret);
+
+            let (digest, padding) = SHA256::extend_digest(original, secret.len());
+
+            assert_eq!(
+                digest.chain(EXTENSION).finalize(),
+                SHA256::digest_parts(&[&secret, &padding, EXTENSION])
+            );
+        }
+    }
 }
@@ -0,0 +1,411 @@
+//! [SHA-512](https://en.wikipedia.org/wiki/SHA-2) hash function.
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::digest::{Digest, ExtensibleDigest};
+
+const K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// [SHA-512](https://en.wikipedia.org/wiki/SHA-2) hash implementation.
+#[must_use]
+pub struct SHA512 {
+    h0: u64,
+    h1: u64,
+    h2: u64,
+    h3: u64,
+    h4: u64,
+    h5: u64,
+    h6: u64,
+    h7: u64,
+    block_count: u64,
+    current_block: Vec<u8>,
+}
+
+impl SHA512 {
+    /// Create a reset SHA512 instance (initial values).
+    pub const fn new() -> SHA512 {
+        SHA512 {
+            h0: 0x6a09e667f3bcc908,
+            h1: 0xbb67ae8584caa73b,
+            h2: 0x3c6ef372fe94f82b,
+            h3: 0xa54ff53a5f1d36f1,
+            h4: 0x510e527fade682d1,
+            h5: 0x9b05688c2b3e6c1f,
+            h6: 0x1f83d9abfb41bd6b,
+            h7: 0x5be0cd19137e2179,
+            block_count: 0,
+            current_block: vec![],
+        }
+    }
+
+    /// Create a SHA512 from specific internal-state values.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_from_state(
+        h0: u64,
+        h1: u64,
+        h2: u64,
+        h3: u64,
+        h4: u64,
+        h5: u64,
+        h6: u64,
+        h7: u64,
+        block_count: u64,
+        current_block: &[u8],
+    ) -> SHA512 {
+        SHA512 {
+            h0,
+            h1,
+            h2,
+            h3,
+            h4,
+            h5,
+            h6,
+            h7,
+            block_count,
+            current_block: current_block.to_vec(),
+        }
+    }
+
+    /// Create a SHA512 instance from a previous hash value (the value obtained
+    /// after calling `.finalize()` on it).
+    fn new_from_hash(hash: [u8; 64], block_count: u64) -> SHA512 {
+        SHA512 {
+            h0: BigEndian::read_u64(&hash[0..8]),
+            h1: BigEndian::read_u64(&hash[8..16]),
+            h2: BigEndian::read_u64(&hash[16..24]),
+            h3: BigEndian::read_u64(&hash[24..32]),
+            h4: BigEndian::read_u64(&hash[32..40]),
+            h5: BigEndian::read_u64(&hash[40..48]),
+            h6: BigEndian::read_u64(&hash[48..56]),
+            h7: BigEndian::read_u64(&hash[56..64]),
+            block_count,
+            current_block: vec![],
+        }
+    }
+}
+
+impl Default for SHA512 {
+    fn default() -> Self {
+        SHA512::new()
+    }
+}
+
+impl Digest for SHA512 {
+    const OUTPUT_LENGTH: usize = 64;
+    const BLOCK_LENGTH: usize = 128;
+    const OID: &'static [u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 3];
+
+    type Output = [u8; Self::OUTPUT_LENGTH];
+
+    #[allow(clippy::many_single_char_names)]
+    fn update(&mut self, message: &[u8]) {
+        let blocks = [&self.current_block, message].concat();
+
+        self.current_block = vec![];
+
+        for chunk in blocks.chunks(Self::BLOCK_LENGTH) {
+            if chunk.len() != Self::BLOCK_LENGTH {
+                self.current_block = chunk.to_vec();
+                break;
+            }
+
+            let mut w = [0_u64; 80];
+
+            for i in 0..16 {
+                w[i] = BigEndian::read_u64(&chunk[8 * i..8 * (i + 1)]);
+            }
+
+            for i in 16..80 {
+                let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+                let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+
+                w[i] = w[i - 16]
+                    .wrapping_add(s0)
+                    .wrapping_add(w[i - 7])
+                    .wrapping_add(s1);
+            }
+
+            let mut a = self.h0;
+            let mut b = self.h1;
+            let mut c = self.h2;
+            let mut d = self.h3;
+            let mut e = self.h4;
+            let mut f = self.h5;
+            let mut g = self.h6;
+            let mut h = self.h7;
+
+            for i in 0..80 {
+                let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+                let ch = (e & f) ^ ((!e) & g);
+                let temp1 = h
+                    .wrapping_add(s1)
+                    .wrapping_add(ch)
+                    .wrapping_add(K[i])
+                    .wrapping_add(w[i]);
+                let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+                let maj = (a & b) ^ (a & c) ^ (b & c);
+                let temp2 = s0.wrapping_add(maj);
+
+                h = g;
+                g = f;
+                f = e;
+                e = d.wrapping_add(temp1);
+                d = c;
+                c = b;
+                b = a;
+                a = temp1.wrapping_add(temp2);
+            }
+
+            self.h0 = self.h0.wrapping_add(a);
+            self.h1 = self.h1.wrapping_add(b);
+            self.h2 = self.h2.wrapping_add(c);
+            self.h3 = self.h3.wrapping_add(d);
+            self.h4 = self.h4.wrapping_add(e);
+            self.h5 = self.h5.wrapping_add(f);
+            self.h6 = self.h6.wrapping_add(g);
+            self.h7 = self.h7.wrapping_add(h);
+            self.block_count += 1;
+        }
+    }
+
+    fn finalize(mut self) -> Self::Output {
+        let message_len =
+            self.block_count * Self::BLOCK_LENGTH as u64 + self.current_block.len() as u64;
+        let mut ml = [0; 16];
+        BigEndian::write_u128(&mut ml, 8 * u128::from(message_len));
+
+        // Add a 1 bit (message end)
+        self.update(&[0x80]);
+
+        // Add zero-padding
+        let padding_len = Self::BLOCK_LENGTH
+            - ((1 + ml.len() as u64 + message_len) % Self::BLOCK_LENGTH as u64) as usize;
+        self.update(&vec![0; padding_len % Self::BLOCK_LENGTH]);
+
+        // Add message length
+        self.update(&ml);
+
+        // Output
+        assert_eq!(self.current_block, &[]);
+
+        let mut hh = [0; Self::OUTPUT_LENGTH];
+
+        BigEndian::write_u64(&mut hh[0..8], self.h0);
+        BigEndian::write_u64(&mut hh[8..16], self.h1);
+        BigEndian::write_u64(&mut hh[16..24], self.h2);
+        BigEndian::write_u64(&mut hh[24..32], self.h3);
+        BigEndian::write_u64(&mut hh[32..40], self.h4);
+        BigEndian::write_u64(&mut hh[40..48], self.h5);
+        BigEndian::write_u64(&mut hh[48..56], self.h6);
+        BigEndian::write_u64(&mut hh[56..64], self.h7);
+
+        hh
+    }
+}
+
+impl ExtensibleDigest for SHA512 {
+    fn extend_digest(
+        digest_output: Self::Output,
+        guessed_payload_length: usize,
+    ) -> (Self, Vec<u8>) {
+        let mut ml = [0; 16];
+        BigEndian::write_u128(&mut ml, 8 * guessed_payload_length as u128);
+
+        let guessed_padding_len = (Self::BLOCK_LENGTH
+            - ((1 + ml.len() + guessed_payload_length) % Self::BLOCK_LENGTH))
+            % Self::BLOCK_LENGTH;
+        let guessed_payload = [[0x80].as_ref(), &vec![0; guessed_padding_len], &ml].concat();
+        let guessed_block_len =
+            ((guessed_payload_length + guessed_payload.len()) / Self::BLOCK_LENGTH) as u64;
+
+        let cracked_digest = SHA512::new_from_hash(digest_output, guessed_block_len);
+
+        (cracked_digest, guessed_payload)
+    }
+
+    fn new_from_iv(iv: Self::Output) -> Self {
+        SHA512::new_from_hash(iv, 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::digest::{Digest, ExtensibleDigest, SHA512};
+
+    const EMPTY_STRING_SHA512: [u8; 64] = [
+        0xcf, 0x83, 0xe1, 0x35, 0x7e, 0xef, 0xb8, 0xbd, 0xf1, 0x54, 0x28, 0x50, 0xd6, 0x6d, 0x80,
+        0x07, 0xd6, 0x20, 0xe4, 0x05, 0x0b, 0x57, 0x15, 0xdc, 0x83, 0xf4, 0xa9, 0x21, 0xd3, 0x6c,
+        0xe9, 0xce, 0x47, 0xd0, 0xd1, 0x3c, 0x5d, 0x85, 0xf2, 0xb0, 0xff, 0x83, 0x18, 0xd2, 0x87,
+        0x7e, 0xec, 0x2f, 0x63, 0xb9, 0x31, 0xbd, 0x47, 0x41, 0x7a, 0x81, 0xa5, 0x38, 0x32, 0x7a,
+        0xf9, 0x27, 0xda, 0x3e,
+    ];
+
+    const ASDF_STRING_SHA512: [u8; 64] = [
+        0x40, 0x1b, 0x09, 0xea, 0xb3, 0xc0, 0x13, 0xd4, 0xca, 0x54, 0x92, 0x2b, 0xb8, 0x02, 0xbe,
+        0xc8, 0xfd, 0x53, 0x18, 0x19, 0x2b, 0x0a, 0x75, 0xf2, 0x01, 0xd8, 0xb3, 0x72, 0x74, 0x29,
+        0x08, 0x0f, 0xb3, 0x37, 0x59, 0x1a, 0xbd, 0x3e, 0x44, 0x45, 0x3b, 0x95, 0x45, 0x55, 0xb7,
+        0xa0, 0x81, 0x2e, 0x10, 0x81, 0xc3, 0x9b, 0x74, 0x02, 0x93, 0xf7, 0x65, 0xea, 0xe7, 0x31,
+        0xf5, 0xa6, 0x5e, 0xd1,
+    ];
+
+    const SET1_SOLUTION_6_BYTES: &[u8] = include_bytes!("../../tests/set1/6.solution.txt");
+
+    const SET1_SOLUTION_6_SHA512: [u8; 64] = [
+        0x59, 0xe2, 0x7d, 0x6f, 0x1c, 0x04, 0x39, 0x53, 0x08, 0x4b, 0x4e, 0xc0, 0x4b, 0x11, 0x4e,
+        0x26, 0x0d, 0xa4, 0x5d, 0xc2, 0x71, 0xfe, 0x79, 0x83, 0x59, 0x24, 0x86, 0x0c, 0xd8, 0xb1,
+        0x2b, 0xde, 0xea, 0x0a, 0xc5, 0x5c, 0x6d, 0xdd, 0xb5, 0x24, 0x96, 0xf6, 0x71, 0x33, 0x94,
+        0x8f, 0x5a, 0xbe, 0x3b, 0x8b, 0x43, 0x83, 0x49, 0x43, 0x14, 0x92, 0x2a, 0x47, 0xeb, 0x11,
+        0x1c, 0x66, 0xfd, 0x03,
+    ];
+
+    // 111 bytes leave no room for the length field in the last block
+    const A_111_SHA512: [u8; 64] = [
+        0xfa, 0x91, 0x21, 0xc7, 0xb3, 0x2b, 0x9e, 0x01, 0x73, 0x3d, 0x03, 0x4c, 0xfc, 0x78, 0xcb,
+        0xf6, 0x7f, 0x92, 0x6c, 0x7e, 0xd8, 0x3e, 0x82, 0x20, 0x0e, 0xf8, 0x68, 0x18, 0x19, 0x69,
+        0x21, 0x76, 0x0b, 0x4b, 0xef, 0xf4, 0x84, 0x04, 0xdf, 0x81, 0x1b, 0x95, 0x38, 0x28, 0x27,
+        0x44, 0x61, 0x67, 0x3c, 0x68, 0xd0, 0x4e, 0x29, 0x7b, 0x0e, 0xb7, 0xb2, 0xb4, 0xd6, 0x0f,
+        0xc6, 0xb5, 0x66, 0xa2,
+    ];
+
+    #[test]
+    fn basic_sha512() {
+        // ""
+        assert_eq!(SHA512::new().finalize(), EMPTY_STRING_SHA512);
+
+        let mut digest = SHA512::new();
+        digest.update(b"");
+        assert_eq!(digest.finalize(), EMPTY_STRING_SHA512);
+
+        // "asdf"
+        let mut digest = SHA512::new();
+        digest.update(b"as");
+        digest.update(b"df");
+        assert_eq!(digest.finalize(), ASDF_STRING_SHA512);
+
+        assert_eq!(SHA512::new().chain(b"asdf").finalize(), ASDF_STRING_SHA512);
+
+        // Set 1 Challenge 6 Solution File
+        assert_eq!(
+            SHA512::new()
+                .chain(&SET1_SOLUTION_6_BYTES[0..50])
+                .chain(&SET1_SOLUTION_6_BYTES[50..])
+                .finalize(),
+            SET1_SOLUTION_6_SHA512
+        );
+    }
+
+    #[test]
+    fn padding_boundaries() {
+        assert_eq!(SHA512::digest(&[0x61; 111]), A_111_SHA512);
+
+        // Lengths around the point where the length field spills into a new block
+        for len in 100..=130 {
+            let message = vec![0x61; len];
+
+            let mut digest = SHA512::new();
+            digest.update(&message[..len / 3]);
+            digest.update(&message[len / 3..]);
+
+            assert_eq!(digest.finalize(), SHA512::digest(&message));
+        }
+    }
+
+    #[test]
+    fn length_extension() {
+        const EXTENSION: &[u8] = b";admin=true";
+
+        // Including lengths where the padding spills into a new block
+        for secret_len in [0, 27, 111, 112, 128, 300] {
+            let secret = vec![0x42; secret_len];
+            let original = SHA512::digest(&secret);
+
+            let (digest, padding) = SHA512::extend_digest(original, secret.len());
+
+            assert_eq!(
+                digest.chain(EXTENSION).finalize(),
+                SHA512::digest_parts(&[&secret, &padding, EXTENSION])
+            );
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! A deliberately weak toy hash function, small enough to find
+//! [collisions](https://en.wikipedia.org/wiki/Collision_attack) in by brute
+//! force.
+//!
+//! It is a [Merkle–Damgård](https://en.wikipedia.org/wiki/Merkle%E2%80%93Damg%C3%A5rd_construction)
+//! construction with a 16-bit state, using [`AES128`] as the compression
+//! function: the state (zero-padded) is the key and the message block is the
+//! plaintext, truncating the ciphertext back to 16 bits.
+
+use std::collections::HashMap;
+
+use byteorder::{BigEndian, ByteOrder};
+use rand::Rng;
+
+use crate::block::{BlockCipher, Key, AES128};
+use crate::digest::Digest;
+use crate::rand::rng;
+
+/// Toy 16-bit Merkle–Damgård hash implementation.
+#[must_use]
+pub struct ToyHash {
+    state: u16,
+    block_count: u64,
+    current_block: Vec<u8>,
+}
+
+impl ToyHash {
+    /// Initial state.
+    pub const INITIAL_STATE: u16 = 0x6373;
+
+    /// Create a reset instance (initial state).
+    pub const fn new() -> ToyHash {
+        ToyHash::new_from_state(Self::INITIAL_STATE, 0)
+    }
+
+    /// Create an instance from a specific state, after processing
+    /// `block_count` full blocks.
+    pub const fn new_from_state(state: u16, block_count: u64) -> ToyHash {
+        ToyHash {
+            state,
+            block_count,
+            current_block: vec![],
+        }
+    }
+
+    /// Current chaining state.
+    ///
+    /// Only meaningful at block boundaries, since partial blocks are buffered.
+    #[must_use]
+    pub const fn state(&self) -> u16 {
+        self.state
+    }
+
+    /// Run the compression function on a single `block`.
+    ///
+    /// # Panics
+    ///
+    /// If `block` is not [`BLOCK_LENGTH`](#associatedconstant.BLOCK_LENGTH) bytes long.
+    #[must_use]
+    pub fn compress(state: u16, block: &[u8]) -> u16 {
+        let mut key = [0; 16];
+        BigEndian::write_u16(&mut key, state);
+
+        let key = Key::new(&key).expect("AES-128 keys are 16 bytes long");
+
+        BigEndian::read_u16(&AES128.encrypt_block(block, &key))
+    }
+
+    /// Find two different blocks that compress to the same state when
+    /// starting from `state`, by brute force (thanks to the
+    /// [birthday paradox](https://en.wikipedia.org/wiki/Birthday_attack) it
+    /// takes around `2^8` attempts).
+    ///
+    /// Returns both blocks and the resulting state.
+    #[must_use]
+    pub fn find_block_collision(state: u16) -> ([u8; 16], [u8; 16], u16) {
+        let mut seen = HashMap::new();
+
+        loop {
+            let block: [u8; 16] = rng().gen();
+            let next_state = Self::compress(state, &block);
+
+            match seen.insert(next_state, block) {
+                Some(other) if other != block => return (other, block, next_state),
+                _ => {},
+            }
+        }
+    }
+}
+
+impl Default for ToyHash {
+    fn default() -> Self {
+        ToyHash::new()
+    }
+}
+
+impl Digest for ToyHash {
+    const OUTPUT_LENGTH: usize = 2;
+    const BLOCK_LENGTH: usize = 16;
+    const OID: &'static [u64] = &[];
+
+    type Output = [u8; Self::OUTPUT_LENGTH];
+
+    fn update(&mut self, message: &[u8]) {
+        let blocks = [&self.current_block, message].concat();
+
+        self.current_block = vec![];
+
+        for chunk in blocks.chunks(Self::BLOCK_LENGTH) {
+            if chunk.len() != Self::BLOCK_LENGTH {
+                self.current_block = chunk.to_vec();
+                break;
+            }
+
+            self.state = Self::compress(self.state, chunk);
+            self.block_count += 1;
+        }
+    }
+
+    fn finalize(mut self) -> Self::Output {
+        let message_len =
+            self.block_count * Self::BLOCK_LENGTH as u64 + self.current_block.len() as u64;
+        let mut ml = [0; 8];
+        BigEndian::write_u64(&mut ml, 8 * message_len);
+
+        // Add a 1 bit (message end)
+        self.update(&[0x80]);
+
+        // Add zero-padding
+        let padding_len = Self::BLOCK_LENGTH
+            - ((1 + ml.len() as u64 + message_len) % Self::BLOCK_LENGTH as u64) as usize;
+        self.update(&vec![0; padding_len % Self::BLOCK_LENGTH]);
+
+        // Add message length
+        self.update(&ml);
+
+        // Output
+        assert_eq!(self.current_block, &[]);
+
+        self.state.to_be_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ToyHash;
+    use crate::digest::Digest;
+
+    #[test]
+    fn test_incremental() {
+        let message = b"Some message that spans a few toy hash blocks".repeat(3);
+
+        for split in 0..message.len() {
+            assert_eq!(
+                ToyHash::new()
+                    .chain(&message[..split])
+                    .chain(&message[split..])
+                    .finalize(),
+                ToyHash::digest(&message)
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_block_collision() {
+        let (a, b, state) = ToyHash::find_block_collision(ToyHash::INITIAL_STATE);
+
+        assert_ne!(a, b);
+        assert_eq!(ToyHash::compress(ToyHash::INITIAL_STATE, &a), state);
+        assert_eq!(ToyHash::compress(ToyHash::INITIAL_STATE, &b), state);
+        assert_eq!(ToyHash::digest(&a), ToyHash::digest(&b));
+    }
+}
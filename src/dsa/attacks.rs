@@ -0,0 +1,175 @@
+//! Attacks on DSA.
+
+use std::ops::Range;
+
+use num_bigint::BigUint;
+
+use super::{DSAPrivateKey, DSAPublicKey, DSASignature, DSA};
+use crate::digest::Digest;
+use crate::util::pool::ThreadPool;
+
+/// Amount of `k` candidates each worker scans between checkpoints.
+const BATCH_SIZE_PER_WORKER: u64 = 4096;
+
+/// Outcome of [`brute_force_k`].
+#[derive(PartialEq, Eq, Debug)]
+pub enum BruteForceOutcome {
+    /// The nonce and the private key have been recovered.
+    Found { k: u64, private_key: DSAPrivateKey },
+
+    /// The whole range was scanned without success.
+    Exhausted,
+
+    /// The search was paused by the checkpoint callback. Resume it by calling
+    /// [`brute_force_k`] again starting at `next_k`.
+    Paused { next_k: u64 },
+}
+
+/// Scan `k_range` for the nonce used in `signature` (of a message hashing to
+/// `h_m`), recovering the private key.
+///
+/// Candidates are checked by comparing `g^k mod p mod q` to `r` (computing
+/// `g^k` incrementally, so each candidate costs a single multiplication) and
+/// confirmed by checking the recovered private key against
+/// `expected_public_key` (`y = g^x mod p`).
+///
+/// The scan is split between all available cores. After each batch,
+/// `checkpoint` is called with the next `k` to scan (every candidate below it
+/// has been checked). Returning `false` pauses the search.
+pub fn brute_force_k<D: Digest>(
+    dsa: &DSA<D>,
+    signature: &DSASignature,
+    h_m: &BigUint,
+    k_range: Range<u64>,
+    expected_public_key: &DSAPublicKey,
+    mut checkpoint: impl FnMut(u64) -> bool,
+) -> BruteForceOutcome {
+    let pool = ThreadPool::available();
+    let batch_size = BATCH_SIZE_PER_WORKER * pool.workers() as u64;
+
+    let mut next_k = k_range.start;
+
+    while next_k < k_range.end {
+        let batch_end = k_range.end.min(next_k.saturating_add(batch_size));
+
+        let found = scan_batch(dsa, &signature.r, next_k..batch_end, pool);
+
+        for k in found {
+            let private_key = dsa.crack_private_key_guess(signature, h_m, &BigUint::from(k));
+
+            if dsa.private_key_matches(&private_key, expected_public_key) {
+                return BruteForceOutcome::Found { k, private_key };
+            }
+        }
+
+        next_k = batch_end;
+
+        if next_k < k_range.end && !checkpoint(next_k) {
+            return BruteForceOutcome::Paused { next_k };
+        }
+    }
+
+    BruteForceOutcome::Exhausted
+}
+
+/// Scan `range` in parallel, returning (in order) every `k` such that
+/// `g^k mod p mod q == r`.
+fn scan_batch<D: Digest>(
+    dsa: &DSA<D>,
+    r: &BigUint,
+    range: Range<u64>,
+    pool: ThreadPool,
+) -> Vec<u64> {
+    // Borrow the parameters alone, so that `D` does not need to be `Sync`
+    let DSA { p, q, g, .. } = dsa;
+
+    let chunk_size = ((range.end - range.start) / pool.workers() as u64).max(1);
+
+    let chunks = range
+        .clone()
+        .step_by(chunk_size as usize)
+        .map(|start| start..range.end.min(start + chunk_size))
+        .collect::<Vec<_>>();
+
+    pool.parallel_map(&chunks, |chunk| {
+        let mut g_k = g.modpow(&BigUint::from(chunk.start), p);
+        let mut found = Vec::new();
+
+        for k in chunk.clone() {
+            if &(&g_k % q) == r {
+                found.push(k);
+            }
+
+            g_k = (g_k * g) % p;
+        }
+
+        found
+    })
+    .concat()
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{brute_force_k, BruteForceOutcome};
+    use crate::dsa::{CHALLENGE_DSA, DSA};
+
+    const PLAINTEXT: &[u8] = b"THIS IS MY PLAINTEXT";
+
+    #[test]
+    fn test_brute_force_k() {
+        let dsa: &DSA<_> = &CHALLENGE_DSA;
+        let (public_key, private_key) = dsa.gen_keypair();
+
+        let k = BigUint::from(12345_usize);
+        let signature = dsa.sign_with_k(&private_key, PLAINTEXT, &k).unwrap();
+        let h_m = dsa.hash_message(PLAINTEXT);
+
+        assert_eq!(
+            brute_force_k(dsa, &signature, &h_m, 0..20000, &public_key, |_| true),
+            BruteForceOutcome::Found {
+                k: 12345,
+                private_key
+            }
+        );
+        assert_eq!(
+            brute_force_k(dsa, &signature, &h_m, 12346..20000, &public_key, |_| true),
+            BruteForceOutcome::Exhausted
+        );
+    }
+
+    #[test]
+    fn test_brute_force_k_resume() {
+        let dsa: &DSA<_> = &CHALLENGE_DSA;
+        let (public_key, private_key) = dsa.gen_keypair();
+
+        let k = BigUint::from(200_000_usize);
+        let signature = dsa.sign_with_k(&private_key, PLAINTEXT, &k).unwrap();
+        let h_m = dsa.hash_message(PLAINTEXT);
+
+        let mut checkpoints = Vec::new();
+
+        let outcome = brute_force_k(dsa, &signature, &h_m, 0..300_000, &public_key, |next_k| {
+            checkpoints.push(next_k);
+            false
+        });
+
+        let next_k = match outcome {
+            BruteForceOutcome::Paused { next_k } => next_k,
+            outcome => panic!("Expected a pause, got {:?}", outcome),
+        };
+
+        assert_eq!(checkpoints, [next_k]);
+
+        assert_eq!(
+            brute_force_k(dsa, &signature, &h_m, next_k..300_000, &public_key, |_| {
+                true
+            }),
+            BruteForceOutcome::Found {
+                k: 200_000,
+                private_key
+            }
+        );
+    }
+}
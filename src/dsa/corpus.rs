@@ -0,0 +1,202 @@
+//! Parsing of signature dumps, like the one in [challenge
+//! 44](https://cryptopals.com/sets/6/challenges/44):
+//!
+//! ```text
+//! msg: Listen for me, you better listen for me now.
+//! s: 1267396447369736888040262262183731677867615804316
+//! r: 1105520928110492191417703162650245113664610474875
+//! m: a4db3de27e2db3e5ef085ced2bced91b82e0df19
+//! ```
+//!
+//! `s` and `r` are decimal, and `m` is the hex digest of `msg` (which is
+//! taken verbatim, trailing whitespace included). Blank lines between records
+//! are ignored.
+
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+use num_traits::Num;
+
+use super::DSASignature;
+use crate::digest::Digest;
+use crate::util::os2ip;
+
+/// A message along with its signature and hash.
+#[derive(PartialEq, Eq, Debug)]
+pub struct SignedMessage {
+    pub message: String,
+    pub signature: DSASignature,
+    pub hash: BigUint,
+}
+
+impl SignedMessage {
+    /// Get the `(signature, hash)` pair, as taken by
+    /// [`DSA::crack_private_key_repeated_nonce`](super::DSA::crack_private_key_repeated_nonce).
+    #[must_use]
+    pub const fn as_pair(&self) -> (&DSASignature, &BigUint) {
+        (&self.signature, &self.hash)
+    }
+}
+
+/// Reasons for a corpus to be rejected. Lines are 1-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CorpusError {
+    /// A line does not start with the expected `field: ` prefix.
+    UnexpectedLine { line: usize, field: &'static str },
+
+    /// A number could not be parsed.
+    BadNumber { line: usize },
+
+    /// `m` is not the digest of `msg`.
+    HashMismatch { line: usize },
+
+    /// The input ends in the middle of a record.
+    Truncated,
+}
+
+/// Record fields, in order.
+const FIELDS: [&str; 4] = ["msg", "s", "r", "m"];
+
+/// Parse every record in `input`, checking `m` against the digest `D` of
+/// `msg`.
+///
+/// # Errors
+///
+/// On the first malformed record.
+pub fn parse<D: Digest>(input: &str) -> Result<Vec<SignedMessage>, CorpusError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let mut messages = Vec::new();
+
+    while let Some(first) = lines.next() {
+        let mut values = [(0, ""); 4];
+
+        for (i, field) in FIELDS.iter().enumerate() {
+            let (line, contents) = if i == 0 {
+                first
+            } else {
+                lines.next().ok_or(CorpusError::Truncated)?
+            };
+
+            let value = contents
+                .strip_prefix(field)
+                .and_then(|rest| rest.strip_prefix(": "))
+                .ok_or(CorpusError::UnexpectedLine { line, field })?;
+
+            values[i] = (line, value);
+        }
+
+        let [(_, message), s, r, m] = values;
+
+        let parse_number = |(line, value): (usize, &str), radix| {
+            BigUint::from_str_radix(value, radix).map_err(|_| CorpusError::BadNumber { line })
+        };
+
+        let hash = parse_number(m, 16)?;
+
+        if hash != os2ip(D::digest(message.as_bytes()).as_ref()) {
+            return Err(CorpusError::HashMismatch { line: m.0 });
+        }
+
+        messages.push(SignedMessage {
+            message: message.to_string(),
+            signature: DSASignature {
+                r: parse_number(r, 10)?,
+                s: parse_number(s, 10)?,
+            },
+            hash,
+        });
+    }
+
+    Ok(messages)
+}
+
+/// Find two messages signed with the same `r` (i.e. a repeated nonce).
+#[must_use]
+pub fn find_repeated_nonce(messages: &[SignedMessage]) -> Option<[&SignedMessage; 2]> {
+    let mut seen = HashMap::new();
+
+    for message in messages {
+        if let Some(previous) = seen.insert(&message.signature.r, message) {
+            return Some([previous, message]);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_repeated_nonce, parse, CorpusError};
+    use crate::digest::SHA1;
+
+    const CORPUS: &str = "\
+msg: Listen for me, you better listen for me now. \n\
+s: 1267396447369736888040262262183731677867615804316\n\
+r: 1105520928110492191417703162650245113664610474875\n\
+m: a4db3de27e2db3e5ef085ced2bced91b82e0df19\n\
+\n\
+msg: Listen for me, you better listen for me now. \n\
+s: 29097472083055673620219739525237952924429516683\n\
+r: 51241962016175933742870323080382366896234169532\n\
+m: a4db3de27e2db3e5ef085ced2bced91b82e0df19\n\
+msg: Listen for me, you better listen for me now. \n\
+s: 1021643638653719618255840562522049391608552714967\n\
+r: 1105520928110492191417703162650245113664610474875\n\
+m: a4db3de27e2db3e5ef085ced2bced91b82e0df19\n";
+
+    #[test]
+    fn test_parse() {
+        let messages = parse::<SHA1>(CORPUS).unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[0].message,
+            "Listen for me, you better listen for me now. "
+        );
+        assert_eq!(
+            messages[1].signature.s.to_string(),
+            "29097472083055673620219739525237952924429516683"
+        );
+
+        let [first, second] = find_repeated_nonce(&messages).unwrap();
+
+        assert_eq!(first, &messages[0]);
+        assert_eq!(second, &messages[2]);
+
+        assert_eq!(find_repeated_nonce(&messages[..2]), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        // Trailing space is part of the message
+        assert_eq!(
+            parse::<SHA1>(&CORPUS.replacen("now. ", "now.", 1)),
+            Err(CorpusError::HashMismatch { line: 4 })
+        );
+
+        assert_eq!(
+            parse::<SHA1>(&CORPUS.replacen("r: ", "x: ", 1)),
+            Err(CorpusError::UnexpectedLine {
+                line: 3,
+                field: "r"
+            })
+        );
+
+        assert_eq!(
+            parse::<SHA1>(&CORPUS.replacen("s: 1", "s: x", 1)),
+            Err(CorpusError::BadNumber { line: 2 })
+        );
+
+        assert_eq!(
+            parse::<SHA1>(&CORPUS[..CORPUS.find("m: ").unwrap()]),
+            Err(CorpusError::Truncated)
+        );
+
+        assert_eq!(parse::<SHA1>(""), Ok(vec![]));
+    }
+}
@@ -1,15 +1,20 @@
 //! [DSA](https://en.wikipedia.org/wiki/Digital_Signature_Algorithm) signatures.
 
+pub mod attacks;
+pub mod corpus;
+
+use std::fmt;
 use std::marker::PhantomData;
 
 use num_bigint::{BigInt, BigUint, RandBigInt};
-use num_traits::{Num, Zero};
+use num_traits::{Num, One, Zero};
 use once_cell::sync::Lazy;
-use rand::thread_rng;
 
 use crate::digest::{Digest, SHA1};
-use crate::util::iter::ToHexable;
-use crate::util::{inv_mod, math_mod};
+use crate::rand::rng;
+use crate::rsa::primes::{gen_prime, is_probable_prime};
+use crate::util::secret::Redacted;
+use crate::util::{binary_inv_mod, bit_len, inv_mod, math_mod, os2ip, BatchVerification, MultiExp};
 
 /// Pre-chosen `p` parameter for DSA. Used in Cryptopals challenges as well as
 /// in tests.
@@ -34,6 +39,26 @@ pub static CHALLENGE_DSA_Q: Lazy<BigUint> =
 
 /// Pre-chosen `g` parameter for DSA. Used in Cryptopals challenges as well as
 /// in tests.
+///
+/// Along with [`CHALLENGE_DSA_P`] and [`CHALLENGE_DSA_Q`], useful to build
+/// tampered instances of [`CHALLENGE_DSA`]:
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::digest::SHA1;
+/// use rustopals::dsa::{CHALLENGE_DSA, CHALLENGE_DSA_P, CHALLENGE_DSA_Q, DSA};
+///
+/// let tampered = DSA::<SHA1>::new_from_params(
+///     CHALLENGE_DSA_P.clone(),
+///     CHALLENGE_DSA_Q.clone(),
+///     &*CHALLENGE_DSA_P + BigUint::from(1_u32),
+/// );
+///
+/// let (public_key, _) = CHALLENGE_DSA.gen_keypair();
+/// let magic_signature = tampered.gen_magic_signature(&public_key).unwrap();
+///
+/// assert!(tampered.verify(&public_key, b"Hello, world", &magic_signature));
+/// ```
 pub static CHALLENGE_DSA_G: Lazy<BigUint> = Lazy::new(|| {
     BigUint::from_str_radix(
         "\
@@ -66,15 +91,79 @@ pub struct DSA<D: Digest> {
     digest: PhantomData<D>,
 }
 
+/// Bit length of `p` for a `q` of `q_bits`, following the (L, N) pairs in
+/// [FIPS 186-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf)
+/// (section 4.2).
+const fn p_bits_for(q_bits: u64) -> u64 {
+    if q_bits <= 160 {
+        1024
+    } else if q_bits <= 256 {
+        2048
+    } else {
+        3072
+    }
+}
+
 impl<D: Digest> DSA<D> {
     /// Generate a new DSA instance with randomly-generated parameters.
     ///
+    /// `q` is as long as the digest output, and `p` as long as FIPS 186-4
+    /// requires for it (e.g. 1024 bits for SHA-1, 2048 bits for SHA-256).
+    /// See [`new_with_sizes`](DSA::new_with_sizes).
+    #[must_use]
+    pub fn new() -> DSA<D> {
+        let q_bits = D::OUTPUT_LENGTH as u64 * 8;
+
+        DSA::new_with_sizes(p_bits_for(q_bits), q_bits)
+    }
+
+    /// Generate a new DSA instance with randomly-generated parameters of
+    /// `p_bits` and `q_bits`.
+    ///
+    /// Follows [FIPS 186-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf)
+    /// (appendix A.1.1), minus the seeds that make the parameters verifiable:
+    /// `q` is a random prime, `p = k * q + 1` a prime found by trying random
+    /// `k`, and `g = h^((p - 1) / q) mod p` for a random `h`, so that `g`
+    /// generates the order `q` subgroup.
+    ///
     /// # Panics
     ///
-    /// Always (not implemented yet!)
+    /// If `p_bits <= q_bits`.
     #[must_use]
-    pub fn new() -> DSA<D> {
-        unimplemented!()
+    pub fn new_with_sizes(p_bits: u64, q_bits: u64) -> DSA<D> {
+        assert!(p_bits > q_bits, "p must be longer than q");
+
+        let mut rng = rng();
+
+        #[allow(clippy::cast_possible_truncation)]
+        let q = gen_prime(q_bits as u32);
+        let two_q = &q << 1;
+
+        let p = loop {
+            let mut x = rng.gen_biguint(p_bits);
+            x.set_bit(p_bits - 1, true);
+
+            // `p = 1 (mod 2q)`, so `q` divides `p - 1`
+            let p: BigUint = &x - &x % &two_q + 1_u32;
+
+            if p.bits() == p_bits && is_probable_prime(&p) {
+                break p;
+            }
+        };
+
+        let exponent = (&p - 1_u32) / &q;
+        let two = BigUint::from(2_u32);
+
+        let g = loop {
+            let h = rng.gen_biguint_range(&two, &(&p - 1_u32));
+            let g = h.modpow(&exponent, &p);
+
+            if !g.is_one() {
+                break g;
+            }
+        };
+
+        DSA::new_from_params(p, q, g)
     }
 
     /// Generate a new DSA instance specifying its parameters.
@@ -93,7 +182,7 @@ impl<D: Digest> DSA<D> {
     pub fn gen_keypair(&self) -> (DSAPublicKey, DSAPrivateKey) {
         let one = BigUint::from(1_usize);
 
-        let x = thread_rng().gen_biguint_range(&one, &(&self.q - &one));
+        let x = rng().gen_biguint_range(&one, &(&self.q - &one));
         let y = self.g.modpow(&x, &self.p);
 
         (DSAPublicKey(y), DSAPrivateKey(x))
@@ -103,9 +192,9 @@ impl<D: Digest> DSA<D> {
     fn hash_message(&self, message: &[u8]) -> BigUint {
         let hash_out = D::digest(message);
         let hash = hash_out.as_ref();
-        let hash_int = BigUint::from_bytes_be(hash);
+        let hash_int = os2ip(hash);
         #[allow(clippy::cast_possible_wrap)]
-        let hash_excess_bits = (D::OUTPUT_LENGTH * 8) as isize - self.p.bits() as isize;
+        let hash_excess_bits = (D::OUTPUT_LENGTH * 8) as isize - bit_len(&self.p) as isize;
 
         if hash_excess_bits > 0 {
             hash_int >> hash_excess_bits
@@ -124,22 +213,41 @@ impl<D: Digest> DSA<D> {
     ) -> (DSASignature, BigUint) {
         let one = BigUint::from(1_usize);
 
-        let (k, r) = loop {
-            let k = thread_rng().gen_biguint_range(&one, &(&self.q - &one));
-            let r = self.g.modpow(&k, &self.p) % &self.q;
+        loop {
+            let k = rng().gen_biguint_range(&one, &(&self.q - &one));
 
-            if !r.is_zero() {
-                break (k, r);
+            if let Some(signature) = self.sign_with_k(&DSAPrivateKey(x.clone()), message, &k) {
+                break (signature, k);
             }
-        };
+        }
+    }
+
+    /// Sign a `message` with a `private_key` and a chosen nonce `k`.
+    ///
+    /// Returns `None` if `k` yields a degenerate signature. Never reuse `k`!
+    fn sign_with_k(
+        &self,
+        DSAPrivateKey(x): &DSAPrivateKey,
+        message: &[u8],
+        k: &BigUint,
+    ) -> Option<DSASignature> {
+        let r = self.g.modpow(k, &self.p) % &self.q;
+
+        if r.is_zero() {
+            return None;
+        }
 
-        let k_inv = inv_mod(k.clone(), &self.q).expect("No k^-1 found");
+        let k_inv = binary_inv_mod(k, &self.q)?;
 
         let h_m = self.hash_message(message);
 
         let s = (k_inv * (h_m + x * &r)) % &self.q;
 
-        (DSASignature { r, s }, k)
+        if s.is_zero() {
+            return None;
+        }
+
+        Some(DSASignature { r, s })
     }
 
     /// Sign a `message` with a `private_key`.
@@ -155,12 +263,76 @@ impl<D: Digest> DSA<D> {
         DSAPublicKey(y): &DSAPublicKey,
         message: &[u8],
         DSASignature { r, s }: &DSASignature,
+    ) -> bool {
+        self.verify_multi_exp(
+            &MultiExp::new(&[self.g.clone(), y.clone()], &self.p),
+            message,
+            r,
+            s,
+        )
+    }
+
+    /// Verify a signature against `message`, like [`DSA::verify`], but
+    /// rejecting degenerate parameters and public keys first.
+    ///
+    /// Both `g` and `y` must be in the order `q` subgroup, excluding `1` (and
+    /// not just congruent to an element in it). This rejects tampered
+    /// parameters like `g = 0` or `g = p + 1`, which make `r` or `v` constant,
+    /// as well as trivial keys like `y = 1`.
+    #[must_use]
+    pub fn verify_strict(
+        &self,
+        public_key: &DSAPublicKey,
+        message: &[u8],
+        signature: &DSASignature,
+    ) -> bool {
+        self.in_subgroup(&self.g)
+            && self.in_subgroup(&public_key.0)
+            && self.verify(public_key, message, signature)
+    }
+
+    /// Check whether `1 < x < p` and `x^q mod p == 1`.
+    fn in_subgroup(&self, x: &BigUint) -> bool {
+        !x.is_zero() && !x.is_one() && x < &self.p && x.modpow(&self.q, &self.p).is_one()
+    }
+
+    /// Verify a batch of `(message, signature)` pairs made with `public_key`.
+    ///
+    /// Unlike schemes that transmit the full `g^k mod p`, DSA reduces `r` mod
+    /// `q`, so signatures cannot be folded into a single randomized check.
+    /// Instead, each one is checked by simultaneous exponentiation, sharing
+    /// the precomputed products of `g` and `y` for the whole batch.
+    #[must_use]
+    pub fn verify_batch(
+        &self,
+        DSAPublicKey(y): &DSAPublicKey,
+        items: &[(&[u8], &DSASignature)],
+    ) -> BatchVerification {
+        let multi_exp = MultiExp::new(&[self.g.clone(), y.clone()], &self.p);
+
+        BatchVerification {
+            results: items
+                .iter()
+                .map(|&(message, DSASignature { r, s })| {
+                    self.verify_multi_exp(&multi_exp, message, r, s)
+                })
+                .collect(),
+        }
+    }
+
+    /// Verify `(r, s)` against `message`, given a [`MultiExp`] for `g` and `y`.
+    fn verify_multi_exp(
+        &self,
+        multi_exp: &MultiExp<'_>,
+        message: &[u8],
+        r: &BigUint,
+        s: &BigUint,
     ) -> bool {
         if r.is_zero() || s.is_zero() || r >= &self.q || s >= &self.q {
             return false;
         }
 
-        let w = match inv_mod(s.clone(), &self.q) {
+        let w = match binary_inv_mod(s, &self.q) {
             Some(w) => w,
             None => return false,
         };
@@ -170,11 +342,24 @@ impl<D: Digest> DSA<D> {
         let u_1 = (h_m * &w) % &self.q;
         let u_2 = (r * &w) % &self.q;
 
-        let v = (self.g.modpow(&u_1, &self.p) * y.modpow(&u_2, &self.p)) % &self.p % &self.q;
+        let v = multi_exp.pow(&[u_1, u_2]) % &self.q;
 
         &v == r
     }
 
+    /// Check whether `private_key` corresponds to `public_key` (i.e. whether
+    /// `y = g^x mod p`).
+    ///
+    /// Useful to confirm private keys recovered by attacks.
+    #[must_use]
+    pub fn private_key_matches(
+        &self,
+        DSAPrivateKey(x): &DSAPrivateKey,
+        DSAPublicKey(y): &DSAPublicKey,
+    ) -> bool {
+        &self.g.modpow(x, &self.p) == y
+    }
+
     /// Generate a private key given a signature, a hash message integer, and a
     /// (guessed) `k` value.
     ///
@@ -224,12 +409,17 @@ impl<D: Digest> DSA<D> {
         Some(self.crack_private_key_guess(pairs[0].0, h_m_1, &k))
     }
 
-    /// Generates a magic signature that valiates against any message.
+    /// Generates a magic signature that validates against any message.
     ///
-    /// Requires tampered DSA parameters. Specifically, `g` must be `p + 1`.
+    /// Requires tampered DSA parameters. Specifically, `g` must be congruent
+    /// to `1 mod p` (e.g. `p + 1`), so that `g^u_1` vanishes from `v` and
+    /// `r = (y^z mod p) mod q`, `s = r / z mod q` checks out for any `z`.
+    ///
+    /// Returns `None` for untampered parameters. [`DSA::verify_strict`]
+    /// rejects these signatures.
     #[must_use]
     pub fn gen_magic_signature(&self, DSAPublicKey(y): &DSAPublicKey) -> Option<DSASignature> {
-        if self.g != &self.p + BigUint::from(1_usize) {
+        if !(&self.g % &self.p).is_one() {
             return None;
         }
 
@@ -249,17 +439,22 @@ impl<D: Digest> Default for DSA<D> {
 }
 
 /// A DSA private key. Used for message signing.
-#[derive(PartialEq, Eq, Debug)]
+#[derive(PartialEq, Eq)]
 pub struct DSAPrivateKey(BigUint);
 
+impl fmt::Debug for DSAPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DSAPrivateKey")
+            .field(&Redacted::biguint(&self.0))
+            .finish()
+    }
+}
+
 impl DSAPrivateKey {
-    /// Get a private key fingerprint after converting it to a hex string.
-    ///
-    /// Used for tests only.
+    /// Get the private key `x`.
     #[must_use]
-    pub fn fingerprint_after_hex<D: Digest>(&self) -> D::Output {
-        let hex_bytes = self.0.to_bytes_be().into_hex();
-        D::digest(hex_bytes.as_bytes())
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.0
     }
 }
 
@@ -277,7 +472,44 @@ pub struct DSASignature {
 
 #[cfg(test)]
 mod test {
-    use super::{CHALLENGE_DSA, DSA};
+    use num_bigint::BigUint;
+    use num_traits::{One, Zero};
+
+    use super::{DSAPublicKey, CHALLENGE_DSA, DSA};
+    use crate::digest::{SHA1, SHA256};
+    use crate::rsa::primes::is_probable_prime;
+
+    fn assert_valid_params<D: crate::digest::Digest>(dsa: &DSA<D>, p_bits: u64, q_bits: u64) {
+        assert_eq!(dsa.p.bits(), p_bits);
+        assert_eq!(dsa.q.bits(), q_bits);
+        assert!(is_probable_prime(&dsa.p));
+        assert!(is_probable_prime(&dsa.q));
+        assert!(((&dsa.p - 1_u32) % &dsa.q).is_zero());
+        assert!(!dsa.g.is_one());
+        assert!(dsa.g.modpow(&dsa.q, &dsa.p).is_one());
+
+        let (public_key, private_key) = dsa.gen_keypair();
+        let signature = dsa.sign(&private_key, b"MESSAGE");
+
+        assert!(dsa.verify(&public_key, b"MESSAGE", &signature));
+        assert!(!dsa.verify(&public_key, b"OTHER MESSAGE", &signature));
+    }
+
+    #[test]
+    fn test_dsa_new() {
+        assert_valid_params(&DSA::<SHA1>::new(), 1024, 160);
+    }
+
+    #[test]
+    fn test_dsa_new_with_sizes() {
+        assert_valid_params(&DSA::<SHA256>::new_with_sizes(512, 256), 512, 256);
+
+        let dsa = DSA::<SHA1>::new_with_sizes(256, 64);
+        assert_valid_params(&dsa, 256, 64);
+
+        // Parameters are random
+        assert_ne!(dsa.q, DSA::<SHA1>::new_with_sizes(256, 64).q);
+    }
 
     #[test]
     fn test_dsa_pregen() {
@@ -291,6 +523,27 @@ mod test {
         assert!(dsa.verify(&public_key, PLAINTEXT, &signature))
     }
 
+    #[test]
+    fn test_dsa_verify_batch() {
+        let dsa: &DSA<_> = &CHALLENGE_DSA;
+        let (public_key, private_key) = dsa.gen_keypair();
+
+        let signature_1 = dsa.sign(&private_key, b"MESSAGE 1");
+        let signature_2 = dsa.sign(&private_key, b"MESSAGE 2");
+
+        let batch = dsa.verify_batch(
+            &public_key,
+            &[
+                (b"MESSAGE 1", &signature_1),
+                (b"MESSAGE 3", &signature_1),
+                (b"MESSAGE 2", &signature_2),
+            ],
+        );
+
+        assert_eq!(batch.results, [true, false, true]);
+        assert_eq!(batch.invalid().collect::<Vec<_>>(), [1]);
+    }
+
     #[test]
     fn test_dsa_pregen_fail() {
         const PLAINTEXT: &[u8] = b"THIS IS MY PLAINTEXT";
@@ -304,6 +557,42 @@ mod test {
         assert!(!dsa.verify(&public_key, BAD_PLAINTEXT, &signature))
     }
 
+    #[test]
+    fn test_dsa_verify_strict() {
+        const PLAINTEXT: &[u8] = b"THIS IS MY PLAINTEXT";
+
+        let dsa: &DSA<_> = &CHALLENGE_DSA;
+        let (public_key, private_key) = dsa.gen_keypair();
+
+        let signature = dsa.sign(&private_key, PLAINTEXT);
+
+        assert!(dsa.verify_strict(&public_key, PLAINTEXT, &signature));
+        assert!(!dsa.verify_strict(&public_key, b"OTHER PLAINTEXT", &signature));
+        assert!(!dsa.verify_strict(&DSAPublicKey(BigUint::one()), PLAINTEXT, &signature));
+        assert!(!dsa.verify_strict(&DSAPublicKey(&public_key.0 + &dsa.p), PLAINTEXT, &signature));
+    }
+
+    #[test]
+    fn test_gen_magic_signature() {
+        let (public_key, _) = CHALLENGE_DSA.gen_keypair();
+
+        assert_eq!(CHALLENGE_DSA.gen_magic_signature(&public_key), None);
+
+        for g in &[&CHALLENGE_DSA.p + 1_u32, 2_u32 * &CHALLENGE_DSA.p + 1_u32] {
+            let dsa = DSA::<SHA1>::new_from_params(
+                CHALLENGE_DSA.p.clone(),
+                CHALLENGE_DSA.q.clone(),
+                g.clone(),
+            );
+
+            let magic_signature = dsa.gen_magic_signature(&public_key).unwrap();
+
+            assert!(dsa.verify(&public_key, b"Hello, world", &magic_signature));
+            assert!(dsa.verify(&public_key, b"Goodbye, world", &magic_signature));
+            assert!(!dsa.verify_strict(&public_key, b"Hello, world", &magic_signature));
+        }
+    }
+
     #[test]
     fn test_crack_private_key() {
         const PLAINTEXT: &[u8] = b"THIS IS MY PLAINTEXT";
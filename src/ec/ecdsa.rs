@@ -0,0 +1,314 @@
+//! [ECDSA](https://en.wikipedia.org/wiki/Elliptic_Curve_Digital_Signature_Algorithm),
+//! with the same API as [`DSA`](crate::dsa::DSA).
+//!
+//! ```
+//! use rustopals::digest::SHA256;
+//! use rustopals::ec::{ECDSA, P256};
+//!
+//! let ecdsa = ECDSA::<SHA256>::new(P256.clone());
+//! let (public_key, private_key) = ecdsa.gen_keypair();
+//!
+//! let signature = ecdsa.sign(&private_key, b"Hello");
+//!
+//! assert!(ecdsa.verify(&public_key, b"Hello", &signature));
+//! assert!(!ecdsa.verify(&public_key, b"Bye", &signature));
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_traits::Zero;
+
+use super::{Curve, Point};
+use crate::digest::Digest;
+use crate::rand::rng;
+use crate::util::secret::Redacted;
+use crate::util::{binary_inv_mod, math_mod, os2ip};
+
+/// ECDSA instance over some curve.
+pub struct ECDSA<D: Digest> {
+    pub curve: Curve,
+    digest: PhantomData<D>,
+}
+
+impl<D: Digest> ECDSA<D> {
+    /// Use `curve`'s base point (which must have prime order).
+    #[must_use]
+    pub const fn new(curve: Curve) -> ECDSA<D> {
+        ECDSA {
+            curve,
+            digest: PhantomData,
+        }
+    }
+
+    /// Generate an ECDSA keypair.
+    #[must_use]
+    pub fn gen_keypair(&self) -> (ECDSAPublicKey, ECDSAPrivateKey) {
+        let d = rng().gen_biguint_range(&BigUint::from(1_u32), &self.curve.n);
+
+        (ECDSAPublicKey(self.curve.mul_g(&d)), ECDSAPrivateKey(d))
+    }
+
+    /// Hash a `message` into an integer, keeping the leftmost bits of the
+    /// digest (as many as `n` has).
+    fn hash_message(&self, message: &[u8]) -> BigUint {
+        let hash = os2ip(D::digest(message).as_ref());
+        let hash_bits = D::OUTPUT_LENGTH as u64 * 8;
+        let n_bits = self.curve.n.bits();
+
+        if hash_bits > n_bits {
+            hash >> (hash_bits - n_bits)
+        } else {
+            hash
+        }
+    }
+
+    /// Sign a `message` with a `private_key` and a chosen nonce `k`.
+    ///
+    /// Returns `None` if `k` yields a degenerate signature. Never reuse `k`!
+    fn sign_with_k(
+        &self,
+        ECDSAPrivateKey(d): &ECDSAPrivateKey,
+        message: &[u8],
+        k: &BigUint,
+    ) -> Option<ECDSASignature> {
+        let n = &self.curve.n;
+
+        let r = match self.curve.mul_g(k) {
+            Point::Infinity => return None,
+            Point::Affine { x, .. } => x % n,
+        };
+
+        if r.is_zero() {
+            return None;
+        }
+
+        let k_inv = binary_inv_mod(k, n)?;
+
+        let s = (k_inv * (self.hash_message(message) + d * &r)) % n;
+
+        if s.is_zero() {
+            return None;
+        }
+
+        Some(ECDSASignature { r, s })
+    }
+
+    /// Sign a `message` with a `private_key`.
+    #[must_use]
+    pub fn sign(&self, private_key: &ECDSAPrivateKey, message: &[u8]) -> ECDSASignature {
+        loop {
+            let k = rng().gen_biguint_range(&BigUint::from(1_u32), &self.curve.n);
+
+            if let Some(signature) = self.sign_with_k(private_key, message, &k) {
+                break signature;
+            }
+        }
+    }
+
+    /// Verify a signature against `message`.
+    ///
+    /// Public keys off the curve (or at infinity) are rejected.
+    #[must_use]
+    pub fn verify(
+        &self,
+        ECDSAPublicKey(q): &ECDSAPublicKey,
+        message: &[u8],
+        ECDSASignature { r, s }: &ECDSASignature,
+    ) -> bool {
+        let n = &self.curve.n;
+
+        if q == &Point::Infinity || !self.curve.contains(q) {
+            return false;
+        }
+
+        if r.is_zero() || s.is_zero() || r >= n || s >= n {
+            return false;
+        }
+
+        let Some(w) = binary_inv_mod(s, n) else {
+            return false;
+        };
+
+        let u_1 = (self.hash_message(message) * &w) % n;
+        let u_2 = (r * &w) % n;
+
+        match self
+            .curve
+            .add(&self.curve.mul_g(&u_1), &self.curve.mul(q, &u_2))
+        {
+            Point::Infinity => false,
+            Point::Affine { x, .. } => &(x % n) == r,
+        }
+    }
+
+    /// Check whether `private_key` corresponds to `public_key` (i.e. whether
+    /// `Q = d * G`).
+    ///
+    /// Useful to confirm private keys recovered by attacks.
+    #[must_use]
+    pub fn private_key_matches(
+        &self,
+        ECDSAPrivateKey(d): &ECDSAPrivateKey,
+        ECDSAPublicKey(q): &ECDSAPublicKey,
+    ) -> bool {
+        &self.curve.mul_g(d) == q
+    }
+
+    /// Generate a private key given a signature, a hash message integer, and a
+    /// (guessed) `k` value.
+    ///
+    /// Returns `None` if `r` is not invertible (i.e. not a valid signature).
+    #[must_use]
+    pub fn crack_private_key_guess(
+        &self,
+        ECDSASignature { r, s }: &ECDSASignature,
+        h_m: &BigUint,
+        k: &BigUint,
+    ) -> Option<ECDSAPrivateKey> {
+        let n = &self.curve.n;
+        let r_inv = binary_inv_mod(r, n)?;
+
+        Some(ECDSAPrivateKey(math_mod(
+            &((BigInt::from(s * k) - BigInt::from(h_m.clone())) * BigInt::from(r_inv)),
+            n,
+        )))
+    }
+
+    /// Generate a private key from a pair of signatures and hashes that are
+    /// known to have been generated by a repeated nonce.
+    #[must_use]
+    pub fn crack_private_key_repeated_nonce(
+        &self,
+        pairs: [(&ECDSASignature, &BigUint); 2],
+    ) -> Option<ECDSAPrivateKey> {
+        let n = &self.curve.n;
+
+        let (ECDSASignature { r: r_1, s: s_1 }, h_m_1) = pairs[0];
+        let (ECDSASignature { r: r_2, s: s_2 }, h_m_2) = pairs[1];
+
+        if r_1 != r_2 {
+            return None;
+        }
+
+        let m_sub = math_mod(
+            &(BigInt::from(h_m_1.clone()) - BigInt::from(h_m_2.clone())),
+            n,
+        );
+        let s_sub = math_mod(&(BigInt::from(s_1.clone()) - BigInt::from(s_2.clone())), n);
+
+        let k = (m_sub * binary_inv_mod(&s_sub, n)?) % n;
+
+        self.crack_private_key_guess(pairs[0].0, h_m_1, &k)
+    }
+}
+
+/// An ECDSA private key. Used for message signing.
+#[derive(PartialEq, Eq)]
+pub struct ECDSAPrivateKey(BigUint);
+
+impl fmt::Debug for ECDSAPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ECDSAPrivateKey")
+            .field(&Redacted::biguint(&self.0))
+            .finish()
+    }
+}
+
+impl ECDSAPrivateKey {
+    /// Get the private key `d`.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+/// An ECDSA public key (the point `Q = d * G`). Used for signature verifying.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ECDSAPublicKey(pub Point);
+
+/// An ECDSA signature. Proves a message has been signed by the private key
+/// corresponding to a known public key.
+#[derive(PartialEq, Eq, Debug)]
+pub struct ECDSASignature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+    use num_traits::Num;
+
+    use super::{ECDSAPrivateKey, ECDSAPublicKey, ECDSA};
+    use crate::digest::{SHA1, SHA256};
+    use crate::ec::{Point, CHALLENGE_CURVE, P256};
+
+    fn from_hex(hex: &str) -> BigUint {
+        BigUint::from_str_radix(hex, 16).unwrap()
+    }
+
+    // From RFC 6979, appendix A.2.5 (P-256 with SHA-256, message "sample")
+    #[test]
+    fn test_rfc6979_vector() {
+        let ecdsa = ECDSA::<SHA256>::new(P256.clone());
+
+        let private_key = ECDSAPrivateKey(from_hex(
+            "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+        ));
+        let public_key = ECDSAPublicKey(Point::Affine {
+            x: from_hex("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"),
+            y: from_hex("7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"),
+        });
+
+        assert!(ecdsa.private_key_matches(&private_key, &public_key));
+
+        let k = from_hex("a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60");
+        let signature = ecdsa.sign_with_k(&private_key, b"sample", &k).unwrap();
+
+        assert_eq!(
+            signature.r,
+            from_hex("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716")
+        );
+        assert_eq!(
+            signature.s,
+            from_hex("f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8")
+        );
+        assert!(ecdsa.verify(&public_key, b"sample", &signature));
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let ecdsa = ECDSA::<SHA1>::new(CHALLENGE_CURVE.clone());
+        let (public_key, private_key) = ecdsa.gen_keypair();
+        let (other_public_key, _) = ecdsa.gen_keypair();
+
+        let signature = ecdsa.sign(&private_key, b"Hello");
+
+        assert!(ecdsa.verify(&public_key, b"Hello", &signature));
+        assert!(!ecdsa.verify(&public_key, b"Hello!", &signature));
+        assert!(!ecdsa.verify(&other_public_key, b"Hello", &signature));
+        assert!(!ecdsa.verify(&ECDSAPublicKey(Point::Infinity), b"Hello", &signature));
+    }
+
+    #[test]
+    fn test_crack_repeated_nonce() {
+        let ecdsa = ECDSA::<SHA256>::new(P256.clone());
+        let (public_key, private_key) = ecdsa.gen_keypair();
+
+        let k = BigUint::from(1337_u32);
+        let signature_1 = ecdsa.sign_with_k(&private_key, b"first", &k).unwrap();
+        let signature_2 = ecdsa.sign_with_k(&private_key, b"second", &k).unwrap();
+
+        let cracked = ecdsa
+            .crack_private_key_repeated_nonce([
+                (&signature_1, &ecdsa.hash_message(b"first")),
+                (&signature_2, &ecdsa.hash_message(b"second")),
+            ])
+            .unwrap();
+
+        assert_eq!(cracked, private_key);
+        assert!(ecdsa.private_key_matches(&cracked, &public_key));
+    }
+}
@@ -0,0 +1,406 @@
+//! [Elliptic curves](https://en.wikipedia.org/wiki/Elliptic_curve) in short
+//! Weierstrass form (`y^2 = x^3 + ax + b`) over prime fields.
+//!
+//! Points are handed around in affine coordinates ([`Point`]), but scalar
+//! multiplication works in [Jacobian coordinates](JacobianPoint) to avoid an
+//! inversion per addition.
+//!
+//! Scalars are not reduced modulo the order of the base point, and points
+//! are not checked to be on the curve unless asked for (see
+//! [`Curve::contains`]), so that invalid-curve attacks can be mounted.
+//!
+//! ```
+//! use num_bigint::BigUint;
+//! use rustopals::ec::{Point, P256};
+//!
+//! let two_g = P256.add(&P256.g, &P256.g);
+//!
+//! assert!(P256.contains(&two_g));
+//! assert_eq!(P256.mul(&P256.g, &BigUint::from(2_u32)), two_g);
+//! assert_eq!(P256.mul(&P256.g, &P256.n), Point::Infinity);
+//! ```
+
+pub mod ecdsa;
+
+use num_bigint::BigUint;
+use num_traits::{Num, One, Zero};
+use once_cell::sync::Lazy;
+
+pub use self::ecdsa::{ECDSAPrivateKey, ECDSAPublicKey, ECDSASignature, ECDSA};
+use crate::util::field::{Field, ModP};
+
+fn from_hex(hex: &str) -> BigUint {
+    BigUint::from_str_radix(hex, 16).unwrap()
+}
+
+fn from_dec(dec: &str) -> BigUint {
+    BigUint::from_str_radix(dec, 10).unwrap()
+}
+
+/// NIST P-256 (a.k.a. `secp256r1`), from
+/// [FIPS 186-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf)
+/// (appendix D.1.2.3).
+pub static P256: Lazy<Curve> = Lazy::new(|| {
+    let p = from_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff");
+    let a = &p - 3_u32;
+
+    Curve::new(
+        p,
+        a,
+        from_hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+        Point::Affine {
+            x: from_hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+            y: from_hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+        },
+        from_hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+        BigUint::one(),
+    )
+});
+
+/// The toy curve from [challenge 59](https://toadstyle.org/cryptopals/59.txt)
+/// (`y^2 = x^3 - 95051x + 11279326`), with a base point of (prime) order `n`
+/// and cofactor `8`.
+pub static CHALLENGE_CURVE: Lazy<Curve> = Lazy::new(|| {
+    let p = from_dec("233970423115425145524320034830162017933");
+    let a = &p - 95051_u32;
+
+    Curve::new(
+        p,
+        a,
+        BigUint::from(11279326_u32),
+        Point::Affine {
+            x: BigUint::from(182_u32),
+            y: from_dec("85518893674295321206118380980485522083"),
+        },
+        from_dec("29246302889428143187362802287225875743"),
+        BigUint::from(8_u32),
+    )
+});
+
+/// A point on some [`Curve`], in affine coordinates.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Point {
+    /// The point at infinity (the identity).
+    Infinity,
+
+    /// A point `(x, y)`.
+    Affine { x: BigUint, y: BigUint },
+}
+
+/// A point `(X / Z^2, Y / Z^3)` in Jacobian coordinates. The point at
+/// infinity has `Z = 0`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct JacobianPoint {
+    pub x: BigUint,
+    pub y: BigUint,
+    pub z: BigUint,
+}
+
+/// A curve `y^2 = x^3 + ax + b` modulo a prime `p`, with a base point `g`
+/// of order `n`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Curve {
+    pub field: ModP,
+    pub a: BigUint,
+    pub b: BigUint,
+    pub g: Point,
+    pub n: BigUint,
+    /// Cofactor (the curve has `h * n` points).
+    pub h: BigUint,
+}
+
+impl Curve {
+    /// Build a curve specifying its parameters.
+    #[must_use]
+    pub const fn new(
+        p: BigUint,
+        a: BigUint,
+        b: BigUint,
+        g: Point,
+        n: BigUint,
+        h: BigUint,
+    ) -> Curve {
+        Curve {
+            field: ModP(p),
+            a,
+            b,
+            g,
+            n,
+            h,
+        }
+    }
+
+    /// The field modulus.
+    #[must_use]
+    pub const fn p(&self) -> &BigUint {
+        &self.field.0
+    }
+
+    /// Whether `point` is on the curve (the point at infinity always is).
+    #[must_use]
+    pub fn contains(&self, point: &Point) -> bool {
+        match point {
+            Point::Infinity => true,
+            Point::Affine { x, y } => {
+                if x >= self.p() || y >= self.p() {
+                    return false;
+                }
+
+                let f = &self.field;
+
+                let rhs = f.add(&f.mul(&f.add(&f.mul(x, x), &self.a), x), &self.b);
+
+                f.mul(y, y) == rhs
+            },
+        }
+    }
+
+    /// Compute `-point`.
+    #[must_use]
+    pub fn neg(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine { x, y } => Point::Affine {
+                x: x.clone(),
+                y: self.field.neg(y),
+            },
+        }
+    }
+
+    /// Compute `p_1 + p_2`.
+    ///
+    /// # Panics
+    ///
+    /// If `p` is not prime (so that some inverse is missing).
+    #[must_use]
+    pub fn add(&self, p_1: &Point, p_2: &Point) -> Point {
+        let f = &self.field;
+
+        let (x_1, y_1, x_2, y_2) = match (p_1, p_2) {
+            (Point::Infinity, _) => return p_2.clone(),
+            (_, Point::Infinity) => return p_1.clone(),
+            (Point::Affine { x: x_1, y: y_1 }, Point::Affine { x: x_2, y: y_2 }) => {
+                (x_1, y_1, x_2, y_2)
+            },
+        };
+
+        let lambda = if x_1 == x_2 {
+            if f.add(y_1, y_2).is_zero() {
+                return Point::Infinity;
+            }
+
+            // Tangent: (3x^2 + a) / 2y
+            let numerator = f.add(&f.mul(&BigUint::from(3_u32), &f.mul(x_1, x_1)), &self.a);
+
+            f.mul(&numerator, &f.inv(&f.add(y_1, y_1)).unwrap())
+        } else {
+            f.mul(&f.sub(y_2, y_1), &f.inv(&f.sub(x_2, x_1)).unwrap())
+        };
+
+        let x_3 = f.sub(&f.sub(&f.mul(&lambda, &lambda), x_1), x_2);
+        let y_3 = f.sub(&f.mul(&lambda, &f.sub(x_1, &x_3)), y_1);
+
+        Point::Affine { x: x_3, y: y_3 }
+    }
+
+    /// Compute `point * k` (by double-and-add, in Jacobian coordinates).
+    #[must_use]
+    pub fn mul(&self, point: &Point, k: &BigUint) -> Point {
+        let base = self.to_jacobian(point);
+        let mut result = self.to_jacobian(&Point::Infinity);
+
+        for i in (0..k.bits()).rev() {
+            result = self.double_jacobian(&result);
+
+            if k.bit(i) {
+                result = self.add_jacobian(&result, &base);
+            }
+        }
+
+        self.to_affine(&result)
+    }
+
+    /// Compute `g * k`.
+    #[must_use]
+    pub fn mul_g(&self, k: &BigUint) -> Point {
+        self.mul(&self.g, k)
+    }
+
+    /// Convert `point` to Jacobian coordinates.
+    #[must_use]
+    pub fn to_jacobian(&self, point: &Point) -> JacobianPoint {
+        match point {
+            Point::Infinity => JacobianPoint {
+                x: BigUint::one(),
+                y: BigUint::one(),
+                z: BigUint::zero(),
+            },
+            Point::Affine { x, y } => JacobianPoint {
+                x: x.clone(),
+                y: y.clone(),
+                z: BigUint::one(),
+            },
+        }
+    }
+
+    /// Convert `point` back to affine coordinates.
+    #[must_use]
+    pub fn to_affine(&self, point: &JacobianPoint) -> Point {
+        let f = &self.field;
+
+        let Some(z_inv) = f.inv(&point.z) else {
+            return Point::Infinity;
+        };
+
+        let z_inv_2 = f.mul(&z_inv, &z_inv);
+        let z_inv_3 = f.mul(&z_inv_2, &z_inv);
+
+        Point::Affine {
+            x: f.mul(&point.x, &z_inv_2),
+            y: f.mul(&point.y, &z_inv_3),
+        }
+    }
+
+    /// Compute `point + point` in Jacobian coordinates (`dbl-2007-bl` from
+    /// the [EFD](https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html)).
+    #[must_use]
+    pub fn double_jacobian(&self, point: &JacobianPoint) -> JacobianPoint {
+        let f = &self.field;
+        let JacobianPoint { x, y, z } = point;
+
+        if z.is_zero() || y.is_zero() {
+            return self.to_jacobian(&Point::Infinity);
+        }
+
+        let xx = f.mul(x, x);
+        let yy = f.mul(y, y);
+        let yyyy = f.mul(&yy, &yy);
+        let zz = f.mul(z, z);
+
+        let s = f.mul(&BigUint::from(4_u32), &f.mul(x, &yy));
+        let m = f.add(
+            &f.mul(&BigUint::from(3_u32), &xx),
+            &f.mul(&self.a, &f.mul(&zz, &zz)),
+        );
+
+        let x_3 = f.sub(&f.mul(&m, &m), &f.add(&s, &s));
+        let y_3 = f.sub(
+            &f.mul(&m, &f.sub(&s, &x_3)),
+            &f.mul(&BigUint::from(8_u32), &yyyy),
+        );
+        let z_3 = f.mul(&BigUint::from(2_u32), &f.mul(y, z));
+
+        JacobianPoint {
+            x: x_3,
+            y: y_3,
+            z: z_3,
+        }
+    }
+
+    /// Compute `p_1 + p_2` in Jacobian coordinates (`add-2007-bl` from the
+    /// [EFD](https://hyperelliptic.org/EFD/g1p/auto-shortw-jacobian.html)).
+    #[must_use]
+    pub fn add_jacobian(&self, p_1: &JacobianPoint, p_2: &JacobianPoint) -> JacobianPoint {
+        let f = &self.field;
+
+        if p_1.z.is_zero() {
+            return p_2.clone();
+        }
+
+        if p_2.z.is_zero() {
+            return p_1.clone();
+        }
+
+        let z_1_z_1 = f.mul(&p_1.z, &p_1.z);
+        let z_2_z_2 = f.mul(&p_2.z, &p_2.z);
+
+        let u_1 = f.mul(&p_1.x, &z_2_z_2);
+        let u_2 = f.mul(&p_2.x, &z_1_z_1);
+        let s_1 = f.mul(&p_1.y, &f.mul(&p_2.z, &z_2_z_2));
+        let s_2 = f.mul(&p_2.y, &f.mul(&p_1.z, &z_1_z_1));
+
+        if u_1 == u_2 {
+            return if s_1 == s_2 {
+                self.double_jacobian(p_1)
+            } else {
+                self.to_jacobian(&Point::Infinity)
+            };
+        }
+
+        let h = f.sub(&u_2, &u_1);
+        let r = f.sub(&s_2, &s_1);
+        let hh = f.mul(&h, &h);
+        let hhh = f.mul(&h, &hh);
+        let v = f.mul(&u_1, &hh);
+
+        let x_3 = f.sub(&f.sub(&f.mul(&r, &r), &hhh), &f.add(&v, &v));
+        let y_3 = f.sub(&f.mul(&r, &f.sub(&v, &x_3)), &f.mul(&s_1, &hhh));
+        let z_3 = f.mul(&f.mul(&p_1.z, &p_2.z), &h);
+
+        JacobianPoint {
+            x: x_3,
+            y: y_3,
+            z: z_3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+    use num_traits::Zero;
+
+    use super::{Curve, Point, CHALLENGE_CURVE, P256};
+
+    fn check_curve(curve: &Curve) {
+        assert!(curve.contains(&curve.g));
+        assert_eq!(curve.mul_g(&curve.n), Point::Infinity);
+        assert_eq!(curve.mul_g(&(&curve.n + 1_u32)), curve.g);
+        assert_eq!(curve.mul_g(&BigUint::zero()), Point::Infinity);
+    }
+
+    #[test]
+    fn test_named_curves() {
+        check_curve(&P256);
+        check_curve(&CHALLENGE_CURVE);
+    }
+
+    #[test]
+    fn test_affine_matches_jacobian() {
+        let curve: &Curve = &CHALLENGE_CURVE;
+
+        let mut affine = Point::Infinity;
+
+        for k in 0..50_u32 {
+            assert_eq!(curve.mul_g(&BigUint::from(k)), affine);
+            assert!(curve.contains(&affine));
+
+            affine = curve.add(&affine, &curve.g);
+        }
+
+        let p = curve.mul_g(&BigUint::from(1337_u32));
+
+        assert_eq!(curve.add(&p, &curve.neg(&p)), Point::Infinity);
+        assert_eq!(curve.add(&p, &p), curve.mul(&p, &BigUint::from(2_u32)));
+        assert_eq!(
+            curve.to_affine(&curve.add_jacobian(&curve.to_jacobian(&p), &curve.to_jacobian(&p))),
+            curve.add(&p, &p)
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let curve: &Curve = &P256;
+
+        let off_curve = match &curve.g {
+            Point::Affine { x, y } => Point::Affine {
+                x: x.clone(),
+                y: y + 1_u32,
+            },
+            Point::Infinity => unreachable!(),
+        };
+
+        assert!(!curve.contains(&off_curve));
+        assert!(curve.contains(&Point::Infinity));
+    }
+}
@@ -0,0 +1,170 @@
+//! [Public key fingerprints](https://en.wikipedia.org/wiki/Public_key_fingerprint),
+//! to compare and pin keys by a short value.
+//!
+//! Fingerprints are the SHA-256 of a canonical, [domain-separated](crate::digest::ds)
+//! encoding of the public key, so keys of different kinds never share a
+//! fingerprint.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::fingerprint::{Fingerprint, KeyFingerprint};
+//! use rustopals::key_exchange::dh::DHPublicValue;
+//! use rustopals::key_exchange::DHOffer;
+//!
+//! let offer = DHOffer::new();
+//! let pin = DHPublicValue(offer.get_public()).fingerprint().to_string();
+//!
+//! // ...later, when receiving the public value from the network:
+//! let pin = KeyFingerprint::from_hex(&pin).unwrap();
+//!
+//! assert!(DHPublicValue(offer.get_public()).matches_pin(&pin));
+//! assert!(!DHPublicValue(DHOffer::new().get_public()).matches_pin(&pin));
+//! ```
+
+use std::convert::TryInto;
+use std::fmt;
+
+use crate::digest::ds::hash_labeled;
+use crate::digest::{Digest, DigestOutput, SHA256};
+use crate::dsa::DSAPublicKey;
+use crate::key_exchange::dh::DHPublicValue;
+use crate::rsa::RSAPublicKey;
+
+/// A SHA-256 key fingerprint.
+///
+/// Displays as lowercase hex.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyFingerprint(pub [u8; SHA256::OUTPUT_LENGTH]);
+
+impl KeyFingerprint {
+    /// Parse a hex-encoded fingerprint.
+    #[must_use]
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        DigestOutput::try_from_hex(hex).map(KeyFingerprint)
+    }
+
+    /// Parse a base64-encoded fingerprint.
+    #[must_use]
+    pub fn from_base64(base64: &str) -> Option<Self> {
+        base64::decode(base64)
+            .ok()?
+            .try_into()
+            .ok()
+            .map(KeyFingerprint)
+    }
+
+    /// Encode as lowercase hex.
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        self.0.hex().to_string()
+    }
+
+    /// Encode as (padded) base64.
+    #[must_use]
+    pub fn to_base64(&self) -> String {
+        base64::encode(self.0)
+    }
+}
+
+impl fmt::Display for KeyFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl fmt::Debug for KeyFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KeyFingerprint({})", self.to_hex())
+    }
+}
+
+/// Trait for public keys that can be fingerprinted.
+pub trait Fingerprint {
+    /// Label identifying the key kind (for domain separation).
+    const LABEL: &'static [u8];
+
+    /// Canonical big-endian encoding of every key component, in order.
+    fn fingerprint_parts(&self) -> Vec<Vec<u8>>;
+
+    /// Compute the key fingerprint.
+    fn fingerprint(&self) -> KeyFingerprint {
+        let parts = self.fingerprint_parts();
+        let parts = parts.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        KeyFingerprint(hash_labeled::<SHA256>(Self::LABEL, &parts))
+    }
+
+    /// Check the key against a `pin`ned fingerprint.
+    fn matches_pin(&self, pin: &KeyFingerprint) -> bool {
+        &self.fingerprint() == pin
+    }
+}
+
+impl Fingerprint for RSAPublicKey {
+    const LABEL: &'static [u8] = b"rustopals-fingerprint-rsa";
+
+    fn fingerprint_parts(&self) -> Vec<Vec<u8>> {
+        vec![self.e.to_bytes_be(), self.n.to_bytes_be()]
+    }
+}
+
+impl Fingerprint for DSAPublicKey {
+    const LABEL: &'static [u8] = b"rustopals-fingerprint-dsa";
+
+    fn fingerprint_parts(&self) -> Vec<Vec<u8>> {
+        vec![self.0.to_bytes_be()]
+    }
+}
+
+impl Fingerprint for DHPublicValue<'_> {
+    const LABEL: &'static [u8] = b"rustopals-fingerprint-dh";
+
+    fn fingerprint_parts(&self) -> Vec<Vec<u8>> {
+        vec![self.0.to_bytes_be()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{Fingerprint, KeyFingerprint};
+    use crate::dsa::DSAPublicKey;
+    use crate::key_exchange::dh::DHPublicValue;
+    use crate::testkeys::{RSA_KEYPAIR_0, RSA_KEYPAIR_1};
+
+    #[test]
+    fn test_fingerprint() {
+        let (public_key_0, _) = &*RSA_KEYPAIR_0;
+        let (public_key_1, _) = &*RSA_KEYPAIR_1;
+
+        assert_eq!(public_key_0.fingerprint(), public_key_0.fingerprint());
+        assert_ne!(public_key_0.fingerprint(), public_key_1.fingerprint());
+
+        let value = BigUint::from(12345_usize);
+
+        assert_ne!(
+            DSAPublicKey(value.clone()).fingerprint(),
+            DHPublicValue(&value).fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_encodings() {
+        let (public_key, _) = &*RSA_KEYPAIR_0;
+        let fingerprint = public_key.fingerprint();
+
+        assert_eq!(fingerprint.to_string().len(), 64);
+        assert_eq!(
+            KeyFingerprint::from_hex(&fingerprint.to_string()),
+            Some(fingerprint)
+        );
+        assert_eq!(
+            KeyFingerprint::from_base64(&fingerprint.to_base64()),
+            Some(fingerprint)
+        );
+        assert_eq!(KeyFingerprint::from_hex("abcd"), None);
+        assert!(public_key.matches_pin(&fingerprint));
+    }
+}
@@ -0,0 +1,127 @@
+//! **INTENTIONALLY BROKEN** primitives and oracles.
+//!
+//! The challenges need flawed implementations to attack (lenient padding
+//! checks, secret-prefix MACs, leaky oracles...). They all live here instead
+//! of alongside the real implementations, and are only available with the
+//! `insecure` feature (enabled by default).
+//!
+//! Every broken type implements the [`Insecure`] marker. Types known to *not*
+//! be intentionally broken implement [`Vetted`] instead, so downstream code
+//! can statically assert it never touches broken ones:
+//!
+//! ```
+//! use rustopals::digest::SHA256;
+//! use rustopals::insecure::assert_vetted;
+//! use rustopals::mac::Hmac;
+//! use rustopals::rsa::PKCS1v1_5;
+//!
+//! const _: () = assert_vetted::<PKCS1v1_5>();
+//! const _: () = assert_vetted::<Hmac<SHA256>>();
+//! ```
+//!
+//! ```compile_fail
+//! use rustopals::insecure::{assert_vetted, BadPKCS1v1_5};
+//!
+//! const _: () = assert_vetted::<BadPKCS1v1_5>();
+//! ```
+
+#[cfg(feature = "insecure")]
+pub mod oracles;
+#[cfg(feature = "insecure")]
+pub mod sidechannel;
+
+#[cfg(feature = "insecure")]
+use crate::digest::Digest;
+#[cfg(feature = "insecure")]
+use crate::mac::Mac;
+use crate::mac::{Hmac, PrefixMac, SuffixMac};
+#[cfg(feature = "insecure")]
+pub use crate::rsa::padding::no_padding::BadNoPadding;
+#[cfg(feature = "insecure")]
+pub use crate::rsa::padding::pkcs1v1_5::BadPKCS1v1_5;
+use crate::rsa::PKCS1v1_5;
+
+/// Marker for intentionally-broken primitives and oracles.
+pub trait Insecure {}
+
+/// Marker for primitives that are **not** intentionally broken.
+///
+/// This is no security guarantee: nothing in this crate should be used to
+/// protect real data.
+pub trait Vetted {}
+
+/// Statically assert `T` is [`Vetted`] (e.g. in a `const _: () = ...;` item).
+pub const fn assert_vetted<T: Vetted + ?Sized>() {}
+
+#[cfg(feature = "insecure")]
+impl Insecure for BadNoPadding {}
+
+#[cfg(feature = "insecure")]
+impl Insecure for BadPKCS1v1_5 {}
+
+#[cfg(feature = "insecure")]
+impl Insecure for oracles::EcbSuffixOracle {}
+
+#[cfg(feature = "insecure")]
+impl Insecure for oracles::CbcPaddingOracle {}
+
+#[cfg(feature = "insecure")]
+impl Insecure for sidechannel::LeakyCompare {}
+
+#[cfg(feature = "insecure")]
+impl Insecure for sidechannel::LeakyModExp {}
+
+#[cfg(feature = "insecure")]
+impl Insecure for sidechannel::LeakyTable {}
+
+impl<D> Insecure for PrefixMac<D> {}
+
+impl<D> Insecure for SuffixMac<D> {}
+
+impl Vetted for PKCS1v1_5 {}
+
+impl<D> Vetted for Hmac<D> {}
+
+/// A very bad MAC implementation that nobody should use.
+///
+/// Prefixes the `message` with the provided `key` and hashes it. Shorthand
+/// for [`PrefixMac`].
+#[cfg(feature = "insecure")]
+#[must_use]
+pub fn bad_mac<D: Digest>(key: &[u8], message: &[u8]) -> D::Output {
+    PrefixMac::<D>::new(key).tag(message)
+}
+
+#[cfg(all(test, feature = "insecure"))]
+mod test {
+    use super::{bad_mac, Insecure};
+    use crate::digest::{Digest, SHA1};
+    use crate::mac::{Mac, PrefixMac};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const MESSAGE: &[u8] = b"comment1=cooking%20MCs;userdata=foo";
+
+    const fn assert_insecure<T: Insecure + ?Sized>() {}
+
+    #[test]
+    fn test_bad_mac() {
+        assert_eq!(
+            bad_mac::<SHA1>(KEY, MESSAGE),
+            PrefixMac::<SHA1>::new(KEY).tag(MESSAGE)
+        );
+        assert_eq!(
+            bad_mac::<SHA1>(KEY, MESSAGE),
+            SHA1::digest_parts(&[KEY, MESSAGE])
+        );
+    }
+
+    #[test]
+    fn test_markers() {
+        assert_insecure::<super::BadNoPadding>();
+        assert_insecure::<super::BadPKCS1v1_5>();
+        assert_insecure::<super::oracles::EcbSuffixOracle>();
+        assert_insecure::<super::oracles::CbcPaddingOracle>();
+        assert_insecure::<super::sidechannel::LeakyTable>();
+        assert_insecure::<PrefixMac<SHA1>>();
+    }
+}
@@ -0,0 +1,251 @@
+//! Ready-made adversary oracles for the chosen-plaintext/ciphertext attacks.
+//!
+//! Each oracle holds secret random state (keys, prefixes...) and draws all its
+//! randomness from an internal RNG seeded at construction. `new` seeds it from
+//! the [library RNG](crate::rand::rng) (so [`with_seed`](crate::rand::with_seed)
+//! also applies), while `with_rng` takes any RNG, making the whole run
+//! replayable. [`state_fingerprint`](EcbSuffixOracle::state_fingerprint)
+//! identifies the secret state in logs without revealing it.
+//!
+//! # Example
+//!
+//! ```
+//! use rand::rngs::StdRng;
+//! use rand::SeedableRng;
+//! use rustopals::insecure::oracles::{EcbSuffixOracle, EncryptionOracle};
+//!
+//! let a = EcbSuffixOracle::with_rng(b"SECRET", 0..=16, &mut StdRng::seed_from_u64(1337));
+//! let b = EcbSuffixOracle::with_rng(b"SECRET", 0..=16, &mut StdRng::seed_from_u64(1337));
+//!
+//! assert_eq!(a.state_fingerprint(), b.state_fingerprint());
+//! assert_eq!(a.encrypt(b"HELLO"), b.encrypt(b"HELLO"));
+//! ```
+
+use std::cell::RefCell;
+use std::ops::RangeInclusive;
+
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+use crate::block::{BlockMode, Iv, Key, AES128, CBC, ECB};
+use crate::digest::ds::hash_labeled;
+use crate::digest::SHA256;
+use crate::rand::rng;
+
+/// Domain separation label for [state fingerprints](EcbSuffixOracle::state_fingerprint).
+pub const STATE_FINGERPRINT_LABEL: &[u8] = b"rustopals-oracle-state";
+
+/// Length (in bytes) of state fingerprints.
+const STATE_FINGERPRINT_LEN: usize = 8;
+
+/// An oracle encrypting attacker-controlled plaintexts.
+pub trait EncryptionOracle {
+    /// Encrypt `plaintext` (usually along with some secret data).
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8>;
+}
+
+/// Seed an oracle RNG from `rng`.
+pub(super) fn seed_from(rng: &mut impl RngCore) -> StdRng {
+    StdRng::from_rng(rng).expect("Seeding from an infallible RNG")
+}
+
+/// Get `len` random bytes from `rng`.
+pub(super) fn random_bytes(rng: &mut impl RngCore, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Hex-encoded short hash of some secret state `parts`.
+fn state_fingerprint(parts: &[&[u8]]) -> String {
+    hex::encode(&hash_labeled::<SHA256>(STATE_FINGERPRINT_LABEL, parts)[..STATE_FINGERPRINT_LEN])
+}
+
+/// AES-128-ECB oracle encrypting `random-prefix || plaintext || secret`
+/// under a random key, as in [Cryptopals challenges 12 and
+/// 14](https://cryptopals.com/sets/2/challenges/12).
+pub struct EcbSuffixOracle {
+    key: Key<AES128>,
+    prefix: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl EcbSuffixOracle {
+    /// Create an oracle appending `secret`, with a random prefix whose length
+    /// is in `prefix_len` (use `0..=0` for no prefix).
+    #[must_use]
+    pub fn new(secret: &[u8], prefix_len: RangeInclusive<usize>) -> Self {
+        EcbSuffixOracle::with_rng(secret, prefix_len, &mut rng())
+    }
+
+    /// Like [`new`](Self::new), but drawing all randomness from `rng`.
+    #[must_use]
+    pub fn with_rng(
+        secret: &[u8],
+        prefix_len: RangeInclusive<usize>,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let mut rng = seed_from(rng);
+        let prefix_len = rng.gen_range(prefix_len);
+
+        EcbSuffixOracle {
+            key: Key::from_rng(&mut rng),
+            prefix: random_bytes(&mut rng, prefix_len),
+            secret: secret.to_vec(),
+        }
+    }
+
+    /// Get the length of the random prefix (to check attacks against).
+    #[must_use]
+    pub const fn prefix_len(&self) -> usize {
+        self.prefix.len()
+    }
+
+    /// Short hash of the secret state, to tell runs apart in logs.
+    #[must_use]
+    pub fn state_fingerprint(&self) -> String {
+        state_fingerprint(&[&self.key, &self.prefix, &self.secret])
+    }
+}
+
+impl EncryptionOracle for EcbSuffixOracle {
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let extended_plaintext = [&self.prefix, plaintext, &self.secret].concat();
+
+        ECB.encrypt(&AES128, &extended_plaintext, &self.key)
+    }
+}
+
+/// AES-128-CBC padding oracle, as in [Cryptopals challenge
+/// 17](https://cryptopals.com/sets/3/challenges/17).
+///
+/// Encrypts under a random key and IV, and leaks whether a ciphertext
+/// decrypts to valid PKCS#7 padding.
+pub struct CbcPaddingOracle {
+    key: Key<AES128>,
+    rng: RefCell<StdRng>,
+}
+
+impl CbcPaddingOracle {
+    /// Create an oracle with a random key.
+    #[must_use]
+    pub fn new() -> Self {
+        CbcPaddingOracle::with_rng(&mut rng())
+    }
+
+    /// Like [`new`](Self::new), but drawing all randomness (including IVs)
+    /// from `rng`.
+    #[must_use]
+    pub fn with_rng(rng: &mut impl RngCore) -> Self {
+        let mut rng = seed_from(rng);
+
+        CbcPaddingOracle {
+            key: Key::from_rng(&mut rng),
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Encrypt `plaintext` under a random IV.
+    ///
+    /// Returns `(ciphertext, iv)`.
+    #[must_use]
+    pub fn encrypt(&self, plaintext: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let iv = Iv::from_rng(&mut *self.rng.borrow_mut());
+        let ciphertext = CBC::new(&iv).encrypt(&AES128, plaintext, &self.key);
+
+        (ciphertext, iv.to_vec())
+    }
+
+    /// Does `ciphertext` decrypt (under `iv`) to valid padding?
+    ///
+    /// A wrong-sized `iv` never does.
+    #[must_use]
+    pub fn is_valid_padding(&self, ciphertext: &[u8], iv: &[u8]) -> bool {
+        Iv::new(iv).is_ok_and(|iv| {
+            CBC::new(&iv)
+                .decrypt(&AES128, ciphertext, &self.key)
+                .is_ok()
+        })
+    }
+
+    /// Short hash of the secret state, to tell runs apart in logs.
+    #[must_use]
+    pub fn state_fingerprint(&self) -> String {
+        state_fingerprint(&[&self.key])
+    }
+}
+
+impl Default for CbcPaddingOracle {
+    fn default() -> Self {
+        CbcPaddingOracle::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{CbcPaddingOracle, EcbSuffixOracle, EncryptionOracle};
+    use crate::block::{BlockCipher, BlockMode, AES128, ECB};
+    use crate::rand::with_seed;
+
+    #[test]
+    fn test_ecb_suffix_oracle_replay() {
+        let oracle = || EcbSuffixOracle::with_rng(b"SECRET", 0..=32, &mut StdRng::seed_from_u64(7));
+
+        assert_eq!(oracle().state_fingerprint(), oracle().state_fingerprint());
+        assert_eq!(oracle().prefix_len(), oracle().prefix_len());
+        assert_eq!(oracle().encrypt(b"ATTACK"), oracle().encrypt(b"ATTACK"));
+
+        let other = EcbSuffixOracle::with_rng(b"SECRET", 0..=32, &mut StdRng::seed_from_u64(8));
+
+        assert_ne!(oracle().state_fingerprint(), other.state_fingerprint());
+
+        // `new` follows `with_seed`
+        let seeded = || with_seed(1337, || EcbSuffixOracle::new(b"SECRET", 0..=32));
+
+        assert_eq!(seeded().state_fingerprint(), seeded().state_fingerprint());
+    }
+
+    #[test]
+    fn test_ecb_suffix_oracle_no_prefix() {
+        let oracle = EcbSuffixOracle::new(b"SECRET", 0..=0);
+
+        assert_eq!(oracle.prefix_len(), 0);
+
+        let ciphertext = oracle.encrypt(&[0; AES128::BLOCK_SIZE * 2]);
+
+        assert_eq!(
+            ciphertext[..AES128::BLOCK_SIZE],
+            ciphertext[AES128::BLOCK_SIZE..AES128::BLOCK_SIZE * 2]
+        );
+        assert_eq!(
+            ECB.decrypt(&AES128, &ciphertext[AES128::BLOCK_SIZE * 2..], &oracle.key),
+            Ok(b"SECRET".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_cbc_padding_oracle_replay() {
+        let oracle = || CbcPaddingOracle::with_rng(&mut StdRng::seed_from_u64(7));
+
+        let (a, b) = (oracle(), oracle());
+
+        assert_eq!(a.state_fingerprint(), b.state_fingerprint());
+
+        // IVs are replayed too
+        for _ in 0..3 {
+            assert_eq!(a.encrypt(b"HELLO"), b.encrypt(b"HELLO"));
+        }
+
+        let (ciphertext, iv) = a.encrypt(b"HELLO");
+
+        assert!(a.is_valid_padding(&ciphertext, &iv));
+
+        let mut bad_iv = iv;
+        bad_iv[AES128::BLOCK_SIZE - 1] ^= 0xff;
+
+        assert!(!a.is_valid_padding(&ciphertext, &bad_iv));
+    }
+}
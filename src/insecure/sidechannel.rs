@@ -0,0 +1,419 @@
+//! **INTENTIONALLY LEAKY** primitives, each paired with the attack that
+//! exploits its leak.
+//!
+//! Real side channels are noisy and machine-dependent, so instead of being
+//! measured these primitives report their leak alongside their result
+//! (simulated time, executed operations, touched cache lines), with
+//! configurable noise. Attacks take closures, so they work just the same
+//! against real measurements.
+//!
+//! - [`LeakyCompare`]: early-exit comparison taking longer the longer the
+//!   matching prefix is (as in the HMAC timing leak of [Cryptopals challenges
+//!   31 and 32](https://cryptopals.com/sets/4/challenges/31)). Broken by
+//!   [`crack_leaky_compare`].
+//! - [`LeakyModExp`]: square-and-multiply exponentiation which only multiplies
+//!   for set exponent bits. Broken by [`crack_modexp_trace`].
+//! - [`LeakyTable`]: secret-indexed table lookups, leaking which cache lines
+//!   are touched. Broken by [`crack_table_lookup`].
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::insecure::sidechannel::{crack_leaky_compare, LeakyCompare};
+//!
+//! let oracle = LeakyCompare::new(b"YELLOW SUBMARINE", 100, 50);
+//! let secret = crack_leaky_compare(16, 4, |guess| oracle.compare(guess));
+//!
+//! assert_eq!(secret.as_deref(), Some(&b"YELLOW SUBMARINE"[..]));
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore};
+
+use super::oracles::{random_bytes, seed_from};
+use crate::block::aes128::S;
+use crate::rand::rng;
+
+/// Byte comparison which exits on the first mismatch, taking `delay` ticks
+/// of simulated time per compared byte.
+pub struct LeakyCompare {
+    secret: Vec<u8>,
+    delay: u64,
+    jitter: u64,
+    rng: RefCell<StdRng>,
+}
+
+impl LeakyCompare {
+    /// Create a comparison against `secret`, adding up to `jitter` ticks of
+    /// random noise to every measurement.
+    #[must_use]
+    pub fn new(secret: &[u8], delay: u64, jitter: u64) -> Self {
+        LeakyCompare::with_rng(secret, delay, jitter, &mut rng())
+    }
+
+    /// Like [`new`](Self::new), but drawing the noise from `rng`.
+    #[must_use]
+    pub fn with_rng(secret: &[u8], delay: u64, jitter: u64, rng: &mut impl RngCore) -> Self {
+        LeakyCompare {
+            secret: secret.to_vec(),
+            delay,
+            jitter,
+            rng: RefCell::new(seed_from(rng)),
+        }
+    }
+
+    /// Compare `guess` against the secret.
+    ///
+    /// Returns whether they are equal and how many ticks it took.
+    #[must_use]
+    pub fn compare(&self, guess: &[u8]) -> (bool, u64) {
+        let mut equal = guess.len() == self.secret.len();
+        let mut ticks = 0;
+
+        if equal {
+            for (a, b) in guess.iter().zip(&self.secret) {
+                ticks += self.delay;
+
+                if a != b {
+                    equal = false;
+                    break;
+                }
+            }
+        }
+
+        (
+            equal,
+            ticks + self.rng.borrow_mut().gen_range(0..=self.jitter),
+        )
+    }
+}
+
+/// Recover a `len`-byte secret from an early-exit comparison, given a
+/// `measure` function returning whether some guess is equal to the secret
+/// and how long it took to compare.
+///
+/// Guesses byte by byte, keeping the one which takes the longest (summed over
+/// `samples` measurements, which should be at least one).
+///
+/// Returns `None` if no guess was found to be equal (e.g. too few `samples`
+/// for the noise).
+#[must_use]
+pub fn crack_leaky_compare(
+    len: usize,
+    samples: usize,
+    mut measure: impl FnMut(&[u8]) -> (bool, u64),
+) -> Option<Vec<u8>> {
+    let mut guess = vec![0; len];
+
+    for i in 0..len {
+        let mut slowest = (0, 0);
+
+        for byte in 0..=u8::MAX {
+            guess[i] = byte;
+
+            let mut total = 0;
+
+            for _ in 0..samples {
+                let (equal, ticks) = measure(&guess);
+
+                if equal {
+                    return Some(guess);
+                }
+
+                total += ticks;
+            }
+
+            if total > slowest.0 {
+                slowest = (total, byte);
+            }
+        }
+
+        guess[i] = slowest.1;
+    }
+
+    None
+}
+
+/// An operation executed by [`LeakyModExp`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    Square,
+    Multiply,
+}
+
+/// Left-to-right square-and-multiply exponentiation by a secret exponent,
+/// which only multiplies for set exponent bits.
+pub struct LeakyModExp {
+    modulus: BigUint,
+    exponent: BigUint,
+}
+
+impl LeakyModExp {
+    /// Create an exponentiation by a secret `exponent` mod `modulus`.
+    #[must_use]
+    pub const fn new(modulus: BigUint, exponent: BigUint) -> Self {
+        LeakyModExp { modulus, exponent }
+    }
+
+    /// Get `base ^ exponent mod modulus`.
+    ///
+    /// Returns the result and the executed operations, in order.
+    #[must_use]
+    pub fn pow(&self, base: &BigUint) -> (BigUint, Vec<Op>) {
+        let mut result = BigUint::one() % &self.modulus;
+        let mut trace = vec![];
+
+        for i in (0..self.exponent.bits()).rev() {
+            result = &result * &result % &self.modulus;
+            trace.push(Op::Square);
+
+            if self.exponent.bit(i) {
+                result = result * base % &self.modulus;
+                trace.push(Op::Multiply);
+            }
+        }
+
+        (result, trace)
+    }
+}
+
+/// Recover the exponent from a [`LeakyModExp`] operation `trace`.
+///
+/// Every square shifts in a new exponent bit, and a multiplication right
+/// after it means that bit was set.
+#[must_use]
+pub fn crack_modexp_trace(trace: &[Op]) -> BigUint {
+    trace.iter().fold(BigUint::zero(), |exponent, op| match op {
+        Op::Square => exponent << 1,
+        Op::Multiply => exponent + 1_u32,
+    })
+}
+
+/// Byte-wise AES S-box lookup of `input ^ key`, like in the first AES round.
+///
+/// Leaks which cache lines (of `line_size` table entries) each lookup
+/// touches, along with `noise` random extra lines per lookup (as if other
+/// code had touched them).
+pub struct LeakyTable {
+    key: Vec<u8>,
+    line_size: usize,
+    noise: usize,
+    rng: RefCell<StdRng>,
+}
+
+impl LeakyTable {
+    /// Create a lookup with a random `key_len`-byte key.
+    ///
+    /// # Panics
+    ///
+    /// If `line_size` is not a power of two or is bigger than the table.
+    #[must_use]
+    pub fn new(key_len: usize, line_size: usize, noise: usize) -> Self {
+        LeakyTable::with_rng(key_len, line_size, noise, &mut rng())
+    }
+
+    /// Like [`new`](Self::new), but drawing all randomness (including the
+    /// noise) from `rng`.
+    ///
+    /// # Panics
+    ///
+    /// If `line_size` is not a power of two or is bigger than the table.
+    #[must_use]
+    pub fn with_rng(
+        key_len: usize,
+        line_size: usize,
+        noise: usize,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        assert!(line_size.is_power_of_two() && line_size <= S.len());
+
+        let mut rng = seed_from(rng);
+
+        LeakyTable {
+            key: random_bytes(&mut rng, key_len),
+            line_size,
+            noise,
+            rng: RefCell::new(rng),
+        }
+    }
+
+    /// Get the key (to check attacks against).
+    #[must_use]
+    pub fn expose_secret(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// Look every `input ^ key` byte up (`input` is cycled over the key).
+    ///
+    /// Returns the looked-up bytes and, for every lookup, the set of touched
+    /// cache lines.
+    #[must_use]
+    pub fn lookup(&self, input: &[u8]) -> (Vec<u8>, Vec<BTreeSet<usize>>) {
+        let mut rng = self.rng.borrow_mut();
+        let lines = S.len() / self.line_size;
+
+        input
+            .iter()
+            .zip(self.key.iter().cycle())
+            .map(|(input, key)| {
+                let index = usize::from(input ^ key);
+                let mut touched = (0..self.noise)
+                    .map(|_| rng.gen_range(0..lines))
+                    .collect::<BTreeSet<_>>();
+
+                touched.insert(index / self.line_size);
+
+                (S[index], touched)
+            })
+            .unzip()
+    }
+}
+
+/// Recover the top bits of a `key_len`-byte [`LeakyTable`] key, given a
+/// `lookup` function returning the sets of touched cache lines for some input
+/// (of `key_len` bytes).
+///
+/// Touching line `l` on input byte `p` means `(p ^ k) / line_size == l`,
+/// which pins the key bits above the in-line offset. Noisy lines are weeded
+/// out by intersecting candidates over random inputs.
+///
+/// The low `log2(line_size)` bits of every key byte do not leak, and are
+/// returned as zero. Returns `None` if the candidates are not narrowed down
+/// after `max_queries`.
+///
+/// # Panics
+///
+/// If `line_size` is not a power of two or is bigger than the table.
+#[must_use]
+pub fn crack_table_lookup(
+    key_len: usize,
+    line_size: usize,
+    max_queries: usize,
+    lookup: impl Fn(&[u8]) -> Vec<BTreeSet<usize>>,
+) -> Option<Vec<u8>> {
+    assert!(line_size.is_power_of_two() && line_size <= S.len());
+
+    // Bits of a key byte that lead to the touched line
+    let mask = !((line_size - 1) as u8);
+
+    let mut candidates = vec![
+        (0..=u8::MAX)
+            .map(|byte| byte & mask)
+            .collect::<BTreeSet<_>>();
+        key_len
+    ];
+
+    for _ in 0..max_queries {
+        if candidates.iter().all(|candidates| candidates.len() == 1) {
+            break;
+        }
+
+        let input = random_bytes(&mut rng(), key_len);
+
+        for ((candidates, &byte), touched) in candidates.iter_mut().zip(&input).zip(lookup(&input))
+        {
+            let leaked = touched
+                .iter()
+                .map(|line| ((line * line_size) as u8 ^ byte) & mask)
+                .collect::<BTreeSet<_>>();
+
+            candidates.retain(|candidate| leaked.contains(candidate));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|candidates| {
+            if candidates.len() == 1 {
+                candidates.into_iter().next()
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::{BigUint, RandBigInt};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{
+        crack_leaky_compare, crack_modexp_trace, crack_table_lookup, LeakyCompare, LeakyModExp,
+        LeakyTable, Op,
+    };
+    use crate::rand::rng;
+
+    #[test]
+    fn test_leaky_compare() {
+        let oracle = LeakyCompare::with_rng(b"SECRET", 10, 0, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(oracle.compare(b"SECRET"), (true, 60));
+        assert_eq!(oracle.compare(b"SEXRET"), (false, 30));
+        assert_eq!(oracle.compare(b"SECRE"), (false, 0));
+    }
+
+    #[test]
+    fn test_crack_leaky_compare() {
+        let secret = b"this is a 20 byte mac";
+        let oracle = LeakyCompare::with_rng(secret, 10, 20, &mut StdRng::seed_from_u64(1337));
+
+        assert_eq!(
+            crack_leaky_compare(secret.len(), 16, |guess| oracle.compare(guess)).as_deref(),
+            Some(&secret[..])
+        );
+    }
+
+    #[test]
+    fn test_crack_modexp_trace() {
+        let modulus = rng().gen_biguint(256);
+        let exponent = rng().gen_biguint(256);
+        let base = rng().gen_biguint_below(&modulus);
+
+        let (result, trace) = LeakyModExp::new(modulus.clone(), exponent.clone()).pow(&base);
+
+        assert_eq!(result, base.modpow(&exponent, &modulus));
+        assert_eq!(crack_modexp_trace(&trace), exponent);
+
+        let five = BigUint::from(0b101_u32);
+        let (result, trace) = LeakyModExp::new(modulus.clone(), five.clone()).pow(&base);
+
+        assert_eq!(result, base.modpow(&five, &modulus));
+        assert_eq!(
+            trace,
+            [
+                Op::Square,
+                Op::Multiply,
+                Op::Square,
+                Op::Square,
+                Op::Multiply
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crack_table_lookup() {
+        for &(line_size, noise) in &[(1, 0), (16, 3), (64, 1)] {
+            let oracle = LeakyTable::with_rng(16, line_size, noise, &mut StdRng::seed_from_u64(7));
+            let mask = !((line_size - 1) as u8);
+
+            let top_bits =
+                crack_table_lookup(16, line_size, 100, |input| oracle.lookup(input).1).unwrap();
+
+            assert_eq!(
+                top_bits,
+                oracle
+                    .expose_secret()
+                    .iter()
+                    .map(|byte| byte & mask)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -22,16 +22,23 @@
 //! )
 //! ```
 
+use std::fmt;
+
 use num_bigint::{BigUint, RandBigInt};
-use num_traits::Zero;
+use num_traits::One;
 use once_cell::sync::Lazy;
-use rand::thread_rng;
 
+use crate::digest::ds::hash_labeled;
 use crate::digest::Digest;
+use crate::rand::rng;
+use crate::rsa::primes::{gen_prime, is_probable_prime};
+use crate::util::bigint::{convert, BigIntBackend};
+use crate::util::secret::Redacted;
+use crate::util::{byte_len, i2osp, os2ip};
 
 /// NIST-recommended modulus for DH.
 pub static NIST_MODULUS: Lazy<BigUint> = Lazy::new(|| {
-    let bytes = base64::decode(
+    let bytes = hex::decode(
         "\
         ffffffffffffffffc90fdaa22168c234c4c6628b80dc1cd129024\
         e088a67cc74020bbea63b139b22514a08798e3404ddef9519b3cd\
@@ -44,14 +51,93 @@ pub static NIST_MODULUS: Lazy<BigUint> = Lazy::new(|| {
     )
     .unwrap();
 
-    BigUint::from_bytes_be(&bytes)
+    os2ip(&bytes)
 });
 
 /// NIST-recommended base for DH.
 pub static NIST_BASE: Lazy<BigUint> = Lazy::new(|| BigUint::from(2_usize));
 
+/// Domain separation label for [`DHSession::to_key_material`].
+pub const KEY_MATERIAL_LABEL: &[u8] = b"rustopals-dh-key-material";
+
+/// Attempts at finding a modulus for each subgroup order in
+/// [`DHGroup::gen_weak`].
+const WEAK_MODULUS_ATTEMPTS: usize = 1024;
+
+/// Diffie-Hellman group parameters.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DHGroup {
+    /// Prime modulus.
+    pub modulus: BigUint,
+    /// Base (generator).
+    pub base: BigUint,
+}
+
+impl DHGroup {
+    /// Create a random offer in this group.
+    pub fn offer(&self) -> DHOffer {
+        DHOffer::new_custom(self.modulus.clone(), &self.base)
+    }
+
+    /// Generate a deliberately weak group: a random `modulus_bits`-long prime
+    /// modulus, with a base generating a subgroup of random
+    /// `order_bits`-long prime order.
+    ///
+    /// Think of the old export-grade groups, only smaller (e.g. 64 to 80-bit
+    /// moduli) so that [breaking them](crate::util::dlog) fits in a test.
+    /// Discrete logs take around `2^(order_bits / 2)` steps.
+    ///
+    /// Returns `None` unless `16 <= order_bits < modulus_bits`.
+    #[must_use]
+    pub fn gen_weak(modulus_bits: u32, order_bits: u32) -> Option<DHGroup> {
+        if order_bits < 16 || order_bits >= modulus_bits {
+            return None;
+        }
+
+        let low = BigUint::one() << (modulus_bits - 1);
+        let high = BigUint::one() << modulus_bits;
+
+        loop {
+            let order = gen_prime(order_bits);
+
+            // `p = k * q + 1` for some even `k`
+            let (low_cofactor, high_cofactor) = (&low / &order, &high / &order + 1_u32);
+
+            for _ in 0..WEAK_MODULUS_ATTEMPTS {
+                let mut cofactor = rng().gen_biguint_range(&low_cofactor, &high_cofactor);
+                cofactor.set_bit(0, false);
+
+                let modulus = &cofactor * &order + 1_u32;
+
+                if modulus < low || modulus >= high || !is_probable_prime(&modulus) {
+                    continue;
+                }
+
+                let mut h = BigUint::from(2_usize);
+
+                let base = loop {
+                    let base = h.modpow(&cofactor, &modulus);
+
+                    if !base.is_one() {
+                        break base;
+                    }
+
+                    h += 1_u32;
+                };
+
+                return Some(DHGroup { modulus, base });
+            }
+        }
+    }
+}
+
+/// A Diffie-Hellman public value (ours or the other party's), e.g. to
+/// [fingerprint](crate::fingerprint) it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DHPublicValue<'a>(pub &'a BigUint);
+
 /// A Diffie-Hellman local offer.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 #[must_use]
 pub struct DHOffer {
     modulus: BigUint,
@@ -78,7 +164,7 @@ impl DHOffer {
     /// Create a new Diffie-Hellman offer with a random private key,
     /// specifying custom DH parameters.
     pub fn new_custom(modulus: BigUint, base: &BigUint) -> DHOffer {
-        let my_private = thread_rng().gen_biguint_range(&BigUint::zero(), &modulus);
+        let my_private = BigIntBackend::random_below(&modulus);
 
         DHOffer::new_custom_from_private(modulus, base, my_private)
             .expect("Private key should have been valid")
@@ -101,7 +187,7 @@ impl DHOffer {
         }
         */
 
-        let my_public = base.modpow(&my_private, &modulus);
+        let my_public = dh_modpow(base, &my_private, &modulus);
 
         Some(DHOffer {
             modulus,
@@ -116,6 +202,12 @@ impl DHOffer {
         &self.my_public
     }
 
+    /// Get the offer's private key.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.my_private
+    }
+
     /// Establish a DH session by passing the other party's public key.
     #[must_use]
     pub fn establish(self, their_public: &BigUint) -> Option<DHSession> {
@@ -128,7 +220,7 @@ impl DHOffer {
         }
         */
 
-        let shared_secret = their_public.modpow(&self.my_private, &self.modulus);
+        let shared_secret = dh_modpow(their_public, &self.my_private, &self.modulus);
 
         Some(DHSession {
             modulus: self.modulus,
@@ -138,6 +230,41 @@ impl DHOffer {
             shared_secret,
         })
     }
+
+    /// Compute the shared secret with the other party's public key on the
+    /// backend `B` (e.g. a fixed-width
+    /// [`U2048`](crate::util::fixed_uint::U2048)), without establishing a
+    /// session.
+    ///
+    /// Unlike [`establish`](DHOffer::establish), `their_public` is checked to
+    /// be below the modulus, as fixed-width backends need reduced operands.
+    ///
+    /// Returns `None` if `their_public` is out of range or the parameters do
+    /// not fit in `B`.
+    #[must_use]
+    pub fn shared_secret_on<B: BigIntBackend>(&self, their_public: &B) -> Option<B> {
+        let modulus = convert(&self.modulus)?;
+
+        if !their_public.less_than(&modulus) {
+            return None;
+        }
+
+        Some(dh_modpow(
+            their_public,
+            &convert(&self.my_private)?,
+            &modulus,
+        ))
+    }
+}
+
+impl fmt::Debug for DHOffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DHOffer")
+            .field("modulus", &self.modulus)
+            .field("my_private", &Redacted::biguint(&self.my_private))
+            .field("my_public", &self.my_public)
+            .finish()
+    }
 }
 
 impl Default for DHOffer {
@@ -147,7 +274,7 @@ impl Default for DHOffer {
 }
 
 /// A Diffie-Hellman already-established session.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 #[must_use]
 pub struct DHSession {
     modulus: BigUint,
@@ -157,6 +284,18 @@ pub struct DHSession {
     shared_secret: BigUint,
 }
 
+impl fmt::Debug for DHSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DHSession")
+            .field("modulus", &self.modulus)
+            .field("my_private", &Redacted::biguint(&self.my_private))
+            .field("my_public", &self.my_public)
+            .field("their_public", &self.their_public)
+            .field("shared_secret", &Redacted::biguint(&self.shared_secret))
+            .finish()
+    }
+}
+
 impl DHSession {
     /// Get the established shared secret.
     ///
@@ -191,9 +330,102 @@ impl DHSession {
     }
 
     /// Establish some key material from the shared secret using `D` as a digest.
+    ///
+    /// See [`derive_key_material`].
     #[must_use]
     pub fn to_key_material<D: Digest>(&self) -> Vec<u8> {
-        let bytes = self.shared_secret.to_bytes_be();
-        D::digest(&bytes).as_ref().to_vec()
+        derive_key_material::<D>(&self.modulus, &self.shared_secret)
+    }
+}
+
+/// Compute `value^private mod modulus` on any [`BigIntBackend`]: the public
+/// value when `value` is the base, or the shared secret when it is the other
+/// party's public value.
+#[must_use]
+pub fn dh_modpow<B: BigIntBackend>(value: &B, private: &B, modulus: &B) -> B {
+    value.modpow(private, modulus)
+}
+
+/// Derive key material from a DH `shared_secret` using `D` as a digest.
+///
+/// The shared secret is encoded with the same length as the `modulus` (so that
+/// its length does not depend on its value) and hashed under
+/// [`KEY_MATERIAL_LABEL`].
+///
+/// # Panics
+///
+/// If `shared_secret` is bigger than `modulus`.
+#[must_use]
+pub fn derive_key_material<D: Digest>(modulus: &BigUint, shared_secret: &BigUint) -> Vec<u8> {
+    let modulus_len = byte_len(modulus);
+    let bytes = i2osp(shared_secret, modulus_len)
+        .expect("Shared secret should be smaller than the modulus");
+
+    hash_labeled::<D>(KEY_MATERIAL_LABEL, &[&bytes])
+        .as_ref()
+        .to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DHGroup, DHOffer, NIST_MODULUS};
+    use crate::rand::with_seed;
+    use crate::rsa::primes::is_probable_prime;
+    use crate::util::bigint::convert;
+    use crate::util::dlog::order_factors;
+    use crate::util::fixed_uint::U2048;
+
+    #[test]
+    fn test_shared_secret_on_fixed_uint() {
+        let offer = DHOffer::new();
+        let other = DHOffer::new();
+
+        let their_public = convert::<_, U2048>(other.get_public()).unwrap();
+        let shared_secret = offer.shared_secret_on(&their_public).unwrap();
+
+        assert_eq!(
+            Some(convert(&shared_secret).unwrap()),
+            offer
+                .establish(other.get_public())
+                .map(|session| session.get_shared_secret().clone())
+        );
+
+        let modulus = convert::<_, U2048>(&*NIST_MODULUS).unwrap();
+
+        assert_eq!(other.shared_secret_on(&modulus), None);
+    }
+
+    #[test]
+    fn test_gen_weak() {
+        for (modulus_bits, order_bits) in [(64, 32), (80, 40), (17, 16)] {
+            let group = with_seed(1337, || DHGroup::gen_weak(modulus_bits, order_bits)).unwrap();
+
+            assert_eq!(group.modulus.bits(), u64::from(modulus_bits));
+            assert!(is_probable_prime(&group.modulus));
+
+            let order = order_factors(&group.base, &group.modulus);
+
+            assert_eq!(order.len(), 1);
+            assert_eq!(order[0].0.bits(), u64::from(order_bits));
+            assert_eq!(order[0].1, 1);
+
+            let offer = group.offer();
+            let other = group.offer();
+
+            assert_eq!(
+                offer
+                    .clone()
+                    .establish(other.get_public())
+                    .unwrap()
+                    .get_shared_secret(),
+                other
+                    .establish(offer.get_public())
+                    .unwrap()
+                    .get_shared_secret()
+            );
+        }
+
+        assert_eq!(DHGroup::gen_weak(64, 64), None);
+        assert_eq!(DHGroup::gen_weak(64, 8), None);
     }
 }
@@ -0,0 +1,251 @@
+//! [Elliptic-curve Diffie-Hellman](https://en.wikipedia.org/wiki/Elliptic-curve_Diffie%E2%80%93Hellman)
+//! key exchange, with the same API as [`dh`](super::dh).
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::key_exchange::ecdh::ECDHOffer;
+//!
+//! let alice_offer = ECDHOffer::new();
+//! let bob_offer = ECDHOffer::new();
+//!
+//! let alice_session = alice_offer
+//!     .clone()
+//!     .establish(bob_offer.get_public())
+//!     .unwrap();
+//! let bob_session = bob_offer
+//!     .establish(alice_offer.get_public())
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     alice_session.get_shared_secret(),
+//!     bob_session.get_shared_secret(),
+//! )
+//! ```
+
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::One;
+
+use super::dh::derive_key_material;
+use crate::digest::Digest;
+use crate::ec::{Curve, Point, P256};
+use crate::rand::rng;
+use crate::util::secret::Redacted;
+
+/// An ECDH local offer.
+#[derive(Clone)]
+#[must_use]
+pub struct ECDHOffer {
+    curve: Curve,
+    my_private: BigUint,
+    my_public: Point,
+}
+
+impl ECDHOffer {
+    /// Create a new ECDH offer with a random private key.
+    ///
+    /// Uses [`P256`].
+    pub fn new() -> ECDHOffer {
+        ECDHOffer::new_custom(P256.clone())
+    }
+
+    /// Create a new ECDH offer specifying its private key.
+    ///
+    /// Uses [`P256`].
+    #[must_use]
+    pub fn new_from_private(my_private: BigUint) -> Option<ECDHOffer> {
+        ECDHOffer::new_custom_from_private(P256.clone(), my_private)
+    }
+
+    /// Create a new ECDH offer with a random private key, specifying a custom
+    /// curve.
+    ///
+    /// # Panics
+    ///
+    /// If `curve.n` is not above one, or is not the order of the generator
+    /// (so that some private key maps to the point at infinity).
+    pub fn new_custom(curve: Curve) -> ECDHOffer {
+        let my_private = rng().gen_biguint_range(&BigUint::one(), &curve.n);
+
+        ECDHOffer::new_custom_from_private(curve, my_private)
+            .expect("Private key should have been valid")
+    }
+
+    /// Create a new ECDH offer specifying its private key and a custom curve.
+    ///
+    /// Returns `None` if the public key would be the point at infinity.
+    #[must_use]
+    pub fn new_custom_from_private(curve: Curve, my_private: BigUint) -> Option<ECDHOffer> {
+        let my_public = curve.mul_g(&my_private);
+
+        if my_public == Point::Infinity {
+            return None;
+        }
+
+        Some(ECDHOffer {
+            curve,
+            my_private,
+            my_public,
+        })
+    }
+
+    /// Get the offer's public key.
+    #[must_use]
+    pub const fn get_public(&self) -> &Point {
+        &self.my_public
+    }
+
+    /// Get the offer's private key.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.my_private
+    }
+
+    /// Establish an ECDH session by passing the other party's public key.
+    ///
+    /// Like in [`DHOffer::establish`](super::dh::DHOffer::establish),
+    /// `their_public` is **not** validated (it could be off the curve, or in
+    /// a small subgroup). Check it with [`Curve::contains`] first.
+    ///
+    /// Returns `None` if the shared point is the point at infinity.
+    #[must_use]
+    pub fn establish(self, their_public: &Point) -> Option<ECDHSession> {
+        let shared_secret = match self.curve.mul(their_public, &self.my_private) {
+            Point::Infinity => return None,
+            Point::Affine { x, .. } => x,
+        };
+
+        Some(ECDHSession {
+            curve: self.curve,
+            my_private: self.my_private,
+            my_public: self.my_public,
+            their_public: their_public.clone(),
+            shared_secret,
+        })
+    }
+}
+
+impl fmt::Debug for ECDHOffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ECDHOffer")
+            .field("curve", &self.curve)
+            .field("my_private", &Redacted::biguint(&self.my_private))
+            .field("my_public", &self.my_public)
+            .finish()
+    }
+}
+
+impl Default for ECDHOffer {
+    fn default() -> ECDHOffer {
+        ECDHOffer::new()
+    }
+}
+
+/// An ECDH already-established session.
+#[derive(Clone)]
+#[must_use]
+pub struct ECDHSession {
+    curve: Curve,
+    my_private: BigUint,
+    my_public: Point,
+    their_public: Point,
+    shared_secret: BigUint,
+}
+
+impl fmt::Debug for ECDHSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ECDHSession")
+            .field("curve", &self.curve)
+            .field("my_private", &Redacted::biguint(&self.my_private))
+            .field("my_public", &self.my_public)
+            .field("their_public", &self.their_public)
+            .field("shared_secret", &Redacted::biguint(&self.shared_secret))
+            .finish()
+    }
+}
+
+impl ECDHSession {
+    /// Get the established shared secret (the `x` coordinate of the shared
+    /// point).
+    ///
+    /// Once a session is established by both parties (after exchanging their
+    /// public keys) this value should be the same in both sessions.
+    #[must_use]
+    pub const fn get_shared_secret(&self) -> &BigUint {
+        &self.shared_secret
+    }
+
+    /// Get my public key.
+    #[must_use]
+    pub const fn get_public(&self) -> &Point {
+        &self.my_public
+    }
+
+    /// Get the other party's public key.
+    #[must_use]
+    pub const fn get_their_public(&self) -> &Point {
+        &self.their_public
+    }
+
+    /// Clone this session into an unestablish ECDH offer.
+    ///
+    /// Useful if you want to re-establish the session.
+    pub fn clone_to_offer(&self) -> ECDHOffer {
+        ECDHOffer {
+            curve: self.curve.clone(),
+            my_private: self.my_private.clone(),
+            my_public: self.my_public.clone(),
+        }
+    }
+
+    /// Establish some key material from the shared secret using `D` as a digest.
+    ///
+    /// Like for DH (see [`derive_key_material`]), with the field modulus
+    /// fixing the encoded length.
+    #[must_use]
+    pub fn to_key_material<D: Digest>(&self) -> Vec<u8> {
+        derive_key_material::<D>(self.curve.p(), &self.shared_secret)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::ECDHOffer;
+    use crate::digest::SHA256;
+    use crate::ec::{Point, CHALLENGE_CURVE};
+
+    #[test]
+    fn test_shared_secret() {
+        let alice_offer = ECDHOffer::new_custom(CHALLENGE_CURVE.clone());
+        let bob_offer = ECDHOffer::new_custom(CHALLENGE_CURVE.clone());
+
+        let alice_session = alice_offer
+            .clone()
+            .establish(bob_offer.get_public())
+            .unwrap();
+        let bob_session = bob_offer.establish(alice_offer.get_public()).unwrap();
+
+        assert_eq!(
+            alice_session.get_shared_secret(),
+            bob_session.get_shared_secret()
+        );
+        assert_eq!(
+            alice_session.to_key_material::<SHA256>(),
+            bob_session.to_key_material::<SHA256>()
+        );
+        assert_eq!(
+            alice_session.clone_to_offer().get_public(),
+            alice_offer.get_public()
+        );
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert!(ECDHOffer::new_from_private(BigUint::from(0_u32)).is_none());
+        assert!(ECDHOffer::new().establish(&Point::Infinity).is_none());
+    }
+}
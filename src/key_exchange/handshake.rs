@@ -0,0 +1,593 @@
+//! A TLS-style handshake: DH group and [suite](super::suite) negotiation,
+//! and Finished messages.
+//!
+//! ```text
+//! Client -> Server: ClientHello { [(p, g, A)...], suites }
+//! Server -> Client: ServerHello { suite, (p, g), B, server Finished }
+//! Client -> Server: client Finished
+//! ```
+//!
+//! The client offers one or more DH groups in order of preference, each with
+//! its own public value (a [`KeyShare`]), and the server picks the first one.
+//!
+//! Session keys only depend on the DH shared secret and the negotiated
+//! suite, so a man in the middle who tampers with the hellos (like the
+//! parameter injection attacks in challenges 34 and 35, or a suite
+//! downgrade) can still leave both parties with matching keys. Finished
+//! messages close that gap: each side MACs its own view of the whole
+//! transcript and checks the peer's, so any tampering makes the handshake
+//! fail with [`HandshakeError::BadFinished`].
+//!
+//! Note this only holds while the attacker cannot compute the shared secret.
+//! Key fixing attacks force a known secret, so the attacker could forge
+//! Finished messages too. Real protocols also validate (and
+//! authenticate) the DH values; here Finished only catches attackers that
+//! relay them untouched.
+//!
+//! The same goes for an attacker who can break the discrete log in some
+//! offered group, e.g. a weak one kept around for compatibility. Stripping the
+//! strong groups from the offer (see [`GroupDowngrade`]) forces the weak one,
+//! and then the attacker can compute the session keys and forge both Finished
+//! messages, like in [Logjam](https://weakdh.org/). Real protocols also sign
+//! the transcript, but nothing can save a client that accepts a weak group.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::key_exchange::dh::{NIST_BASE, NIST_MODULUS};
+//! use rustopals::key_exchange::handshake::{Client, Server};
+//! use rustopals::key_exchange::Suite;
+//!
+//! let client = Client::new(NIST_MODULUS.clone(), NIST_BASE.clone(), Suite::ALL);
+//! let (server, server_hello) = Server::respond(&client.hello(), Suite::ALL).unwrap();
+//! let (client_keys, client_finished) = client.finish(&server_hello).unwrap();
+//! let server_keys = server.finish(&client_finished).unwrap();
+//!
+//! assert_eq!(client_keys, server_keys);
+//! ```
+
+use num_bigint::BigUint;
+
+use super::dh::{DHGroup, DHOffer};
+use super::suite::{Direction, SessionKeys, Suite};
+use crate::digest::ds::encode_labeled;
+
+/// Domain separation label for the handshake transcript.
+pub const TRANSCRIPT_LABEL: &[u8] = b"rustopals-handshake-transcript";
+
+/// Handshake errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HandshakeError {
+    /// The server supports none of the offered suites.
+    NoCommonSuite,
+
+    /// The server picked a suite that was not offered.
+    UnexpectedSuite,
+
+    /// The client offered no DH groups.
+    NoKeyShare,
+
+    /// The server picked a DH group that was not offered.
+    UnexpectedGroup,
+
+    /// The DH exchange failed.
+    KeyExchange,
+
+    /// The peer's Finished message does not match our transcript.
+    BadFinished,
+}
+
+/// A DH group offered by the client, with the client public value in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyShare {
+    /// DH group.
+    pub group: DHGroup,
+    /// Client DH public value.
+    pub public: BigUint,
+}
+
+/// First client message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientHello {
+    /// Offered DH groups, in order of preference.
+    pub shares: Vec<KeyShare>,
+    /// Offered suites, in order of preference.
+    pub suites: Vec<Suite>,
+}
+
+/// Server response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerHello {
+    /// Negotiated suite.
+    pub suite: Suite,
+    /// Negotiated DH group.
+    pub group: DHGroup,
+    /// Server DH public value.
+    pub public: BigUint,
+    /// Server Finished message.
+    pub finished: Vec<u8>,
+}
+
+/// Encode everything both parties should agree on (i.e. what Finished
+/// messages are computed over).
+#[must_use]
+pub fn transcript(
+    client_hello: &ClientHello,
+    suite: Suite,
+    group: &DHGroup,
+    server_public: &BigUint,
+) -> Vec<u8> {
+    let shares = client_hello
+        .shares
+        .iter()
+        .flat_map(|share| {
+            encode_labeled(
+                b"",
+                &[
+                    &share.group.modulus.to_bytes_be(),
+                    &share.group.base.to_bytes_be(),
+                    &share.public.to_bytes_be(),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+
+    encode_labeled(
+        TRANSCRIPT_LABEL,
+        &[
+            &shares,
+            &Suite::encode_list(&client_hello.suites),
+            &suite.id().to_be_bytes(),
+            &group.modulus.to_bytes_be(),
+            &group.base.to_bytes_be(),
+            &server_public.to_bytes_be(),
+        ],
+    )
+}
+
+/// Client side of the handshake.
+#[must_use]
+pub struct Client {
+    /// One per key share.
+    offers: Vec<DHOffer>,
+    hello: ClientHello,
+    verify_finished: bool,
+}
+
+impl Client {
+    /// Start a handshake offering `suites` with the given DH parameters.
+    pub fn new(modulus: BigUint, base: BigUint, suites: &[Suite]) -> Client {
+        Client::new_with_groups(&[DHGroup { modulus, base }], suites)
+    }
+
+    /// Start a handshake offering `suites` and DH `groups` (both in order of
+    /// preference).
+    pub fn new_with_groups(groups: &[DHGroup], suites: &[Suite]) -> Client {
+        let offers = groups.iter().map(DHGroup::offer).collect::<Vec<_>>();
+
+        let shares = groups
+            .iter()
+            .zip(&offers)
+            .map(|(group, offer)| KeyShare {
+                group: group.clone(),
+                public: offer.get_public().clone(),
+            })
+            .collect();
+
+        Client {
+            hello: ClientHello {
+                shares,
+                suites: suites.to_vec(),
+            },
+            offers,
+            verify_finished: true,
+        }
+    }
+
+    /// Do not check the server Finished message (i.e. behave like a
+    /// protocol without them).
+    pub const fn without_finished_verification(mut self) -> Client {
+        self.verify_finished = false;
+        self
+    }
+
+    /// Get the message to send to the server.
+    #[must_use]
+    pub fn hello(&self) -> ClientHello {
+        self.hello.clone()
+    }
+
+    /// Process the server response, returning the session keys and the
+    /// client Finished message to send back.
+    ///
+    /// # Errors
+    ///
+    /// If the server picked a suite or group we did not offer, or its
+    /// Finished message does not match our transcript.
+    pub fn finish(
+        mut self,
+        server_hello: &ServerHello,
+    ) -> Result<(SessionKeys, Vec<u8>), HandshakeError> {
+        if !self.hello.suites.contains(&server_hello.suite) {
+            return Err(HandshakeError::UnexpectedSuite);
+        }
+
+        let share = self
+            .hello
+            .shares
+            .iter()
+            .position(|share| share.group == server_hello.group)
+            .ok_or(HandshakeError::UnexpectedGroup)?;
+
+        let session = self
+            .offers
+            .swap_remove(share)
+            .establish(&server_hello.public)
+            .ok_or(HandshakeError::KeyExchange)?;
+
+        let keys = SessionKeys::derive(&session, server_hello.suite, &[]);
+        let transcript = transcript(
+            &self.hello,
+            server_hello.suite,
+            &server_hello.group,
+            &server_hello.public,
+        );
+
+        if self.verify_finished
+            && !keys.verify_finished(
+                Direction::ServerToClient,
+                &transcript,
+                &server_hello.finished,
+            )
+        {
+            return Err(HandshakeError::BadFinished);
+        }
+
+        let finished = keys.finished(Direction::ClientToServer, &transcript);
+
+        Ok((keys, finished))
+    }
+}
+
+/// Server side of the handshake, waiting for the client Finished message.
+#[must_use]
+pub struct Server {
+    keys: SessionKeys,
+    transcript: Vec<u8>,
+    verify_finished: bool,
+}
+
+impl Server {
+    /// Answer a `client_hello`, picking the client's preferred suite among
+    /// the `supported` ones and the client's preferred DH group.
+    ///
+    /// # Errors
+    ///
+    /// If there is no common suite, or the client offered no groups.
+    pub fn respond(
+        client_hello: &ClientHello,
+        supported: &[Suite],
+    ) -> Result<(Server, ServerHello), HandshakeError> {
+        let suite = Suite::negotiate(&client_hello.suites, supported)
+            .ok_or(HandshakeError::NoCommonSuite)?;
+
+        let share = client_hello
+            .shares
+            .first()
+            .ok_or(HandshakeError::NoKeyShare)?;
+
+        let offer = share.group.offer();
+        let public = offer.get_public().clone();
+
+        let session = offer
+            .establish(&share.public)
+            .ok_or(HandshakeError::KeyExchange)?;
+
+        let keys = SessionKeys::derive(&session, suite, &[]);
+        let transcript = transcript(client_hello, suite, &share.group, &public);
+        let finished = keys.finished(Direction::ServerToClient, &transcript);
+
+        Ok((
+            Server {
+                keys,
+                transcript,
+                verify_finished: true,
+            },
+            ServerHello {
+                suite,
+                group: share.group.clone(),
+                public,
+                finished,
+            },
+        ))
+    }
+
+    /// Do not check the client Finished message (i.e. behave like a
+    /// protocol without them).
+    pub const fn without_finished_verification(mut self) -> Server {
+        self.verify_finished = false;
+        self
+    }
+
+    /// Check the client Finished message, returning the session keys.
+    ///
+    /// # Errors
+    ///
+    /// If the client Finished message does not match our transcript.
+    pub fn finish(self, client_finished: &[u8]) -> Result<SessionKeys, HandshakeError> {
+        if self.verify_finished
+            && !self.keys.verify_finished(
+                Direction::ClientToServer,
+                &self.transcript,
+                client_finished,
+            )
+        {
+            return Err(HandshakeError::BadFinished);
+        }
+
+        Ok(self.keys)
+    }
+}
+
+/// A man in the middle of a [`relay`]ed handshake, able to tamper with
+/// every message.
+pub trait Mitm {
+    /// Tamper with the client hello before the server gets it.
+    fn client_hello(&mut self, _hello: &mut ClientHello) {}
+
+    /// Tamper with the server hello before the client gets it.
+    fn server_hello(&mut self, _hello: &mut ServerHello) {}
+
+    /// Tamper with the client Finished message before the server gets it.
+    fn client_finished(&mut self, _finished: &mut Vec<u8>) {}
+}
+
+/// [`Mitm`] stripping every DH group with a modulus over `max_bits` from
+/// the client offer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GroupDowngrade {
+    /// Biggest modulus (in bits) to leave in the offer.
+    pub max_bits: u64,
+}
+
+impl Mitm for GroupDowngrade {
+    fn client_hello(&mut self, hello: &mut ClientHello) {
+        hello
+            .shares
+            .retain(|share| share.group.modulus.bits() <= self.max_bits);
+    }
+}
+
+/// Run a whole handshake between `client` and a server supporting the
+/// `supported` suites, through a `mitm`.
+///
+/// Returns the client and server session keys.
+///
+/// # Errors
+///
+/// If either side aborts the handshake.
+pub fn relay(
+    client: Client,
+    supported: &[Suite],
+    mitm: &mut impl Mitm,
+) -> Result<(SessionKeys, SessionKeys), HandshakeError> {
+    let mut client_hello = client.hello();
+    mitm.client_hello(&mut client_hello);
+
+    let (server, mut server_hello) = Server::respond(&client_hello, supported)?;
+    mitm.server_hello(&mut server_hello);
+
+    let (client_keys, mut client_finished) = client.finish(&server_hello)?;
+    mitm.client_finished(&mut client_finished);
+
+    let server_keys = server.finish(&client_finished)?;
+
+    Ok((client_keys, server_keys))
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{
+        relay, Client, ClientHello, GroupDowngrade, HandshakeError, Mitm, Server, ServerHello,
+    };
+    use crate::key_exchange::dh::{DHGroup, NIST_BASE, NIST_MODULUS};
+    use crate::key_exchange::suite::{Direction, SessionKeys, Suite};
+    use crate::key_exchange::DHOffer;
+    use crate::rand::with_seed;
+
+    /// Run a handshake through a man in the middle that can tamper with
+    /// both hellos, but relays Finished messages untouched.
+    fn mitm_handshake(
+        tamper_client_hello: impl FnOnce(&mut ClientHello),
+        tamper_server_hello: impl FnOnce(&mut ServerHello),
+        verify_finished: bool,
+    ) -> Result<(SessionKeys, SessionKeys), HandshakeError> {
+        let mut client = Client::new(NIST_MODULUS.clone(), NIST_BASE.clone(), Suite::ALL);
+
+        if !verify_finished {
+            client = client.without_finished_verification();
+        }
+
+        let mut client_hello = client.hello();
+        tamper_client_hello(&mut client_hello);
+
+        let (mut server, mut server_hello) = Server::respond(&client_hello, Suite::ALL)?;
+
+        if !verify_finished {
+            server = server.without_finished_verification();
+        }
+
+        tamper_server_hello(&mut server_hello);
+
+        let (client_keys, client_finished) = client.finish(&server_hello)?;
+        let server_keys = server.finish(&client_finished)?;
+
+        Ok((client_keys, server_keys))
+    }
+
+    /// Keys Eve can derive for a known shared `secret`.
+    fn keys_for_secret(secret: usize, suite: Suite) -> SessionKeys {
+        // `(p + secret)^1 mod p = secret`
+        let session = DHOffer::new_custom_from_private(
+            NIST_MODULUS.clone(),
+            &(&*NIST_MODULUS + secret),
+            BigUint::from(1_usize),
+        )
+        .unwrap()
+        .establish(&(&*NIST_MODULUS + secret))
+        .unwrap();
+
+        SessionKeys::derive(&session, suite, &[])
+    }
+
+    #[test]
+    fn test_handshake() {
+        let (client_keys, server_keys) = mitm_handshake(|_| {}, |_| {}, true).unwrap();
+
+        assert_eq!(client_keys, server_keys);
+        assert_eq!(client_keys.suite(), Suite::AES128_CBC_HMAC_SHA256);
+
+        let record = client_keys.seal(Direction::ClientToServer, b"Hello");
+
+        assert_eq!(
+            server_keys.open(Direction::ClientToServer, &record),
+            Some(b"Hello".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_key_fixing() {
+        // Challenge 34: replace both public values with `p`
+        let fix_client = |hello: &mut ClientHello| hello.shares[0].public = NIST_MODULUS.clone();
+        let fix_server = |hello: &mut ServerHello| hello.public = NIST_MODULUS.clone();
+
+        let (client_keys, server_keys) = mitm_handshake(fix_client, fix_server, false).unwrap();
+
+        assert_eq!(client_keys, server_keys);
+        assert_eq!(client_keys, keys_for_secret(0, client_keys.suite()));
+
+        assert_eq!(
+            mitm_handshake(fix_client, fix_server, true),
+            Err(HandshakeError::BadFinished)
+        );
+    }
+
+    #[test]
+    fn test_base_injection() {
+        // Challenge 35: `g = 1` makes the server public value `1`
+        let inject_client = |hello: &mut ClientHello| {
+            hello.shares[0].group.base = BigUint::from(1_usize);
+            hello.shares[0].public = BigUint::from(1_usize);
+        };
+        // The server echoes the injected group, so restore it for the client
+        let restore_server = |hello: &mut ServerHello| hello.group.base = NIST_BASE.clone();
+
+        let (client_keys, server_keys) =
+            mitm_handshake(inject_client, restore_server, false).unwrap();
+
+        assert_eq!(client_keys, server_keys);
+        assert_eq!(client_keys, keys_for_secret(1, client_keys.suite()));
+
+        assert_eq!(
+            mitm_handshake(inject_client, restore_server, true),
+            Err(HandshakeError::BadFinished)
+        );
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let downgrade = |hello: &mut ClientHello| hello.suites.retain(|suite| suite.is_weak());
+
+        let (client_keys, _) = mitm_handshake(downgrade, |_| {}, false).unwrap();
+
+        assert_eq!(client_keys.suite(), Suite::NULL_HMAC_SHA1);
+
+        assert_eq!(
+            mitm_handshake(downgrade, |_| {}, true),
+            Err(HandshakeError::BadFinished)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let client = Client::new(NIST_MODULUS.clone(), NIST_BASE.clone(), Suite::ALL);
+
+        assert!(matches!(
+            Server::respond(&client.hello(), &[]),
+            Err(HandshakeError::NoCommonSuite)
+        ));
+
+        let client = Client::new(
+            NIST_MODULUS.clone(),
+            NIST_BASE.clone(),
+            &[Suite::AES128_CTR_HMAC_SHA1],
+        );
+        let (_, mut server_hello) = Server::respond(&client.hello(), Suite::ALL).unwrap();
+        server_hello.suite = Suite::NULL_HMAC_SHA1;
+
+        assert!(matches!(
+            client.finish(&server_hello),
+            Err(HandshakeError::UnexpectedSuite)
+        ));
+
+        let client = Client::new(NIST_MODULUS.clone(), NIST_BASE.clone(), Suite::ALL);
+        let mut client_hello = client.hello();
+        client_hello.shares.clear();
+
+        assert!(matches!(
+            Server::respond(&client_hello, Suite::ALL),
+            Err(HandshakeError::NoKeyShare)
+        ));
+
+        let (_, mut server_hello) = Server::respond(&client.hello(), Suite::ALL).unwrap();
+        server_hello.group.base = BigUint::from(5_usize);
+
+        assert!(matches!(
+            client.finish(&server_hello),
+            Err(HandshakeError::UnexpectedGroup)
+        ));
+    }
+
+    #[test]
+    fn test_group_downgrade() {
+        struct Passive;
+
+        impl Mitm for Passive {}
+
+        let weak = with_seed(1337, || DHGroup::gen_weak(64, 32)).unwrap();
+        let groups = [
+            DHGroup {
+                modulus: NIST_MODULUS.clone(),
+                base: NIST_BASE.clone(),
+            },
+            weak.clone(),
+        ];
+
+        let client = Client::new_with_groups(&groups, Suite::ALL);
+        let (client_keys, server_keys) = relay(client, Suite::ALL, &mut Passive).unwrap();
+
+        assert_eq!(client_keys, server_keys);
+
+        // The server ends up picking the weak group...
+        let mut downgrade = GroupDowngrade { max_bits: 64 };
+
+        let client = Client::new_with_groups(&groups, Suite::ALL);
+        let mut client_hello = client.hello();
+        downgrade.client_hello(&mut client_hello);
+
+        let (_, server_hello) = Server::respond(&client_hello, Suite::ALL).unwrap();
+
+        assert_eq!(server_hello.group, weak);
+
+        // ...but Finished messages catch it, unless the attacker can also
+        // break the weak group (see the Logjam test in challenge 35)
+        let client = Client::new_with_groups(&groups, Suite::ALL);
+
+        assert_eq!(
+            relay(client, Suite::ALL, &mut downgrade),
+            Err(HandshakeError::BadFinished)
+        );
+    }
+}
@@ -2,5 +2,23 @@
 //! related utilities.
 
 pub mod dh;
+#[cfg(feature = "ec")]
+pub mod ecdh;
+#[cfg(feature = "protocol")]
+pub mod handshake;
+pub mod srp;
+#[cfg(feature = "protocol")]
+pub mod suite;
+#[cfg(feature = "protocol")]
+pub mod ticket;
+#[cfg(feature = "ec")]
+pub mod x25519;
 
-pub use dh::DHOffer;
+pub use dh::{DHGroup, DHOffer};
+#[cfg(feature = "ec")]
+pub use ecdh::ECDHOffer;
+pub use srp::{SrpClient, SrpServer, SrpVerifier};
+#[cfg(feature = "protocol")]
+pub use suite::{SessionKeys, Suite};
+#[cfg(feature = "ec")]
+pub use x25519::X25519Offer;
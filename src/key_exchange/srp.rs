@@ -0,0 +1,580 @@
+//! [Secure Remote Password](https://en.wikipedia.org/wiki/Secure_Remote_Password_protocol)
+//! (SRP) password-authenticated key exchange.
+//!
+//! ```text
+//! Registration:     Server stores SrpVerifier { salt, v = g^x }
+//! Client -> Server: A = g^a
+//! Server -> Client: SrpChallenge { salt, B = k * v + g^b }
+//! Client -> Server: M1 = HMAC(K, salt)
+//! Server -> Client: M2 = H(A, M1, K)
+//! ```
+//!
+//! Where `x = H(salt, password)`, `u = H(A, B)` and both parties agree on the
+//! session key `K = H(S)`, computed from `S = (B - k * g^x)^(a + u * x)` by the
+//! client and from `S = (A * v^u)^b` by the server.
+//!
+//! The client proves it knows `K` first (M1). Only then does the server prove
+//! it too (M2), which authenticates the server as one knowing the verifier.
+//!
+//! Setting [`SrpChallenge::u`] switches to the simplified SRP from challenge
+//! 38, where `B = g^b` and the server picks `u` at random.
+//!
+//! Like in [`dh`](super::dh), public values are **not** validated by default,
+//! so a client sending `A = 0` (or any multiple of `N`) forces `S = 0` and can
+//! authenticate without the password. Mutual authentication does not help
+//! there (the server happily proves it knows `K = H(0)` too): use
+//! [`SrpServer::with_public_validation`].
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::key_exchange::srp::{SrpClient, SrpServer, SrpVerifier};
+//!
+//! let verifier = SrpVerifier::new(b"hunter2");
+//!
+//! let server = SrpServer::new(verifier);
+//! let client = SrpClient::new();
+//!
+//! let challenge = server.challenge();
+//! let client_proof = client.proof(b"hunter2", &challenge);
+//!
+//! let server_proof = server
+//!     .respond(client.get_public(), &client_proof)
+//!     .unwrap();
+//!
+//! assert!(client.verify_server(b"hunter2", &challenge, &server_proof));
+//! ```
+
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::Zero;
+use once_cell::sync::Lazy;
+use rand::Rng;
+
+use super::dh::{NIST_BASE, NIST_MODULUS};
+use crate::digest::ds::hash_labeled;
+use crate::digest::{Digest, SHA256};
+use crate::mac::hmac;
+use crate::rand::rng;
+use crate::util::secret::Redacted;
+use crate::util::{byte_len, ct_eq, generate_bytes, i2osp};
+
+/// Domain separation label for `x`.
+pub const X_LABEL: &[u8] = b"srp-x";
+
+/// Domain separation label for `u`.
+pub const U_LABEL: &[u8] = b"srp-u";
+
+/// Domain separation label for the server proof.
+pub const SERVER_PROOF_LABEL: &[u8] = b"srp-server-proof";
+
+/// Length (in bytes) of random salts.
+pub const SALT_LEN: usize = 32;
+
+/// The multiplier parameter `k`.
+pub static K: Lazy<BigUint> = Lazy::new(|| BigUint::from(3_usize));
+
+/// An SRP session key.
+pub type SessionKey = <SHA256 as Digest>::Output;
+
+/// An SRP proof of knowledge of the session key (either `M1` or `M2`).
+pub type Proof = <SHA256 as Digest>::Output;
+
+/// Compute the private key `x` from a `salt` and a `password`.
+#[must_use]
+pub fn compute_x(salt: &[u8], password: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(&hash_labeled::<SHA256>(X_LABEL, &[salt, password]))
+}
+
+/// Compute the scrambling parameter `u` from both public values.
+#[must_use]
+pub fn compute_u(client_public: &BigUint, server_public: &BigUint) -> BigUint {
+    BigUint::from_bytes_be(&hash_labeled::<SHA256>(
+        U_LABEL,
+        &[&pad(client_public), &pad(server_public)],
+    ))
+}
+
+/// Compute the session key `K` from the shared secret `S`.
+#[must_use]
+pub fn compute_session_key(shared_secret: &BigUint) -> SessionKey {
+    SHA256::digest(&shared_secret.to_bytes_be())
+}
+
+/// Compute the client proof for a `session_key`, i.e. `M1 = HMAC(K, salt)`.
+#[must_use]
+pub fn compute_proof(session_key: &SessionKey, salt: &[u8]) -> Proof {
+    hmac::<SHA256>(session_key, salt)
+}
+
+/// Compute the server proof for a `session_key`, i.e. `M2 = H(A, M1, K)`.
+#[must_use]
+pub fn compute_server_proof(
+    client_public: &BigUint,
+    client_proof: &Proof,
+    session_key: &SessionKey,
+) -> Proof {
+    hash_labeled::<SHA256>(
+        SERVER_PROOF_LABEL,
+        &[&pad(client_public), client_proof, session_key],
+    )
+}
+
+/// `PAD()` from RFC 5054: fixed-width encoding (reduced first, so that a
+/// malicious `A = k * N` can still be hashed).
+fn pad(x: &BigUint) -> Vec<u8> {
+    i2osp(&(x % &*NIST_MODULUS), byte_len(&NIST_MODULUS)).expect("Reduced values should always fit")
+}
+
+/// What the server stores at registration (instead of the password).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SrpVerifier {
+    salt: Vec<u8>,
+    verifier: BigUint,
+}
+
+impl SrpVerifier {
+    /// Register a `password` with a random salt.
+    #[must_use]
+    pub fn new(password: &[u8]) -> SrpVerifier {
+        SrpVerifier::new_with_salt(generate_bytes(SALT_LEN), password)
+    }
+
+    /// Register a `password` specifying its salt.
+    #[must_use]
+    pub fn new_with_salt(salt: Vec<u8>, password: &[u8]) -> SrpVerifier {
+        let verifier = NIST_BASE.modpow(&compute_x(&salt, password), &NIST_MODULUS);
+
+        SrpVerifier { salt, verifier }
+    }
+
+    /// Get the salt.
+    #[must_use]
+    pub fn get_salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    /// Get the verifier `v = g^x`.
+    #[must_use]
+    pub const fn get_verifier(&self) -> &BigUint {
+        &self.verifier
+    }
+}
+
+/// Server response to a client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SrpChallenge {
+    /// Salt the password was registered with.
+    pub salt: Vec<u8>,
+    /// Server public value `B`.
+    pub public: BigUint,
+    /// Scrambling parameter `u`, only sent in simplified SRP.
+    pub u: Option<u128>,
+}
+
+/// Server side of SRP.
+#[derive(Clone)]
+#[must_use]
+pub struct SrpServer {
+    verifier: SrpVerifier,
+    my_private: BigUint,
+    my_public: BigUint,
+    simplified_u: Option<u128>,
+    validate_public: bool,
+}
+
+impl SrpServer {
+    /// Create a new SRP server for a registered `verifier`, with a random
+    /// private key.
+    pub fn new(verifier: SrpVerifier) -> SrpServer {
+        let my_private = rng().gen_biguint_range(&BigUint::zero(), &NIST_MODULUS);
+        let my_public = (&*K * verifier.get_verifier()
+            + NIST_BASE.modpow(&my_private, &NIST_MODULUS))
+            % &*NIST_MODULUS;
+
+        SrpServer {
+            verifier,
+            my_private,
+            my_public,
+            simplified_u: None,
+            validate_public: false,
+        }
+    }
+
+    /// Create a new simplified SRP server for a registered `verifier`, with
+    /// random private key and `u`.
+    pub fn new_simplified(verifier: SrpVerifier) -> SrpServer {
+        let mut rng = rng();
+
+        let my_private = rng.gen_biguint_range(&BigUint::zero(), &NIST_MODULUS);
+        let my_public = NIST_BASE.modpow(&my_private, &NIST_MODULUS);
+
+        SrpServer {
+            verifier,
+            my_private,
+            my_public,
+            simplified_u: Some(rng.gen()),
+            validate_public: false,
+        }
+    }
+
+    /// Reject client public values `A` which are `0 mod N` (e.g. `0` or
+    /// `N`), as required by RFC 5054.
+    pub const fn with_public_validation(mut self) -> SrpServer {
+        self.validate_public = true;
+        self
+    }
+
+    /// Get the challenge to send to a client.
+    #[must_use]
+    pub fn challenge(&self) -> SrpChallenge {
+        SrpChallenge {
+            salt: self.verifier.salt.clone(),
+            public: self.my_public.clone(),
+            u: self.simplified_u,
+        }
+    }
+
+    /// Get the server's public value `B`.
+    #[must_use]
+    pub const fn get_public(&self) -> &BigUint {
+        &self.my_public
+    }
+
+    /// Get the server's private value `b`.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.my_private
+    }
+
+    /// Compute the session key shared with the client owning `client_public`.
+    #[must_use]
+    pub fn session_key(&self, client_public: &BigUint) -> SessionKey {
+        let u = self
+            .simplified_u
+            .map_or_else(|| compute_u(client_public, &self.my_public), BigUint::from);
+
+        let shared_secret = (client_public * self.verifier.verifier.modpow(&u, &NIST_MODULUS))
+            .modpow(&self.my_private, &NIST_MODULUS);
+
+        compute_session_key(&shared_secret)
+    }
+
+    /// Check a client `proof` (`M1`) for the client owning `client_public`.
+    #[must_use]
+    pub fn verify(&self, client_public: &BigUint, proof: &Proof) -> bool {
+        if self.validate_public && (client_public % &*NIST_MODULUS).is_zero() {
+            return false;
+        }
+
+        let expected = compute_proof(&self.session_key(client_public), &self.verifier.salt);
+
+        ct_eq(&expected, proof)
+    }
+
+    /// Check a client `proof` (`M1`) for the client owning `client_public`
+    /// and, if valid, get the server proof (`M2`) to respond with.
+    #[must_use]
+    pub fn respond(&self, client_public: &BigUint, proof: &Proof) -> Option<Proof> {
+        if !self.verify(client_public, proof) {
+            return None;
+        }
+
+        Some(compute_server_proof(
+            client_public,
+            proof,
+            &self.session_key(client_public),
+        ))
+    }
+}
+
+impl fmt::Debug for SrpServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SrpServer")
+            .field("verifier", &self.verifier)
+            .field("my_private", &Redacted::biguint(&self.my_private))
+            .field("my_public", &self.my_public)
+            .field("simplified_u", &self.simplified_u)
+            .field("validate_public", &self.validate_public)
+            .finish()
+    }
+}
+
+/// Client side of SRP.
+#[derive(Clone)]
+#[must_use]
+pub struct SrpClient {
+    my_private: BigUint,
+    my_public: BigUint,
+}
+
+impl SrpClient {
+    /// Create a new SRP client with a random private key.
+    pub fn new() -> SrpClient {
+        SrpClient::new_from_private(rng().gen_biguint_range(&BigUint::zero(), &NIST_MODULUS))
+    }
+
+    /// Create a new SRP client specifying its private key `a`.
+    pub fn new_from_private(my_private: BigUint) -> SrpClient {
+        let my_public = NIST_BASE.modpow(&my_private, &NIST_MODULUS);
+
+        SrpClient {
+            my_private,
+            my_public,
+        }
+    }
+
+    /// Get the client's public value `A`.
+    #[must_use]
+    pub const fn get_public(&self) -> &BigUint {
+        &self.my_public
+    }
+
+    /// Get the client's private value `a`.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.my_private
+    }
+
+    /// Compute the session key shared with the server that sent `challenge`.
+    #[must_use]
+    pub fn session_key(&self, password: &[u8], challenge: &SrpChallenge) -> SessionKey {
+        let x = compute_x(&challenge.salt, password);
+
+        let (base, u) = challenge.u.map_or_else(
+            || {
+                let k_g_x = (&*K * NIST_BASE.modpow(&x, &NIST_MODULUS)) % &*NIST_MODULUS;
+
+                // Add `N` first so the subtraction cannot underflow
+                (
+                    &challenge.public % &*NIST_MODULUS + &*NIST_MODULUS - k_g_x,
+                    compute_u(&self.my_public, &challenge.public),
+                )
+            },
+            |u| (challenge.public.clone(), BigUint::from(u)),
+        );
+
+        let shared_secret = base.modpow(&(&self.my_private + u * x), &NIST_MODULUS);
+
+        compute_session_key(&shared_secret)
+    }
+
+    /// Compute the proof (`M1`) to send to the server that sent `challenge`.
+    #[must_use]
+    pub fn proof(&self, password: &[u8], challenge: &SrpChallenge) -> Proof {
+        compute_proof(&self.session_key(password, challenge), &challenge.salt)
+    }
+
+    /// Check the `server_proof` (`M2`) the server that sent `challenge`
+    /// responded to our proof with.
+    #[must_use]
+    pub fn verify_server(
+        &self,
+        password: &[u8],
+        challenge: &SrpChallenge,
+        server_proof: &Proof,
+    ) -> bool {
+        let session_key = self.session_key(password, challenge);
+        let client_proof = compute_proof(&session_key, &challenge.salt);
+
+        ct_eq(
+            &compute_server_proof(&self.my_public, &client_proof, &session_key),
+            server_proof,
+        )
+    }
+}
+
+impl fmt::Debug for SrpClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SrpClient")
+            .field("my_private", &Redacted::biguint(&self.my_private))
+            .field("my_public", &self.my_public)
+            .finish()
+    }
+}
+
+impl Default for SrpClient {
+    fn default() -> SrpClient {
+        SrpClient::new()
+    }
+}
+
+/// Offline dictionary attack on simplified SRP (challenge 38).
+///
+/// A malicious server sends the client a challenge with `g` as its public
+/// value `B` (i.e. `b = 1`) along with some `salt` and `u`. The client's
+/// shared secret then becomes `S = g^(a + u * x) = A * v^u`, which the server
+/// can compute for every candidate password from `client_public` alone, until
+/// one matches `client_proof`.
+///
+/// Returns the first password in `dictionary` that matches, if any.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::key_exchange::dh::NIST_BASE;
+/// use rustopals::key_exchange::srp::{crack_simple_srp, SrpChallenge, SrpClient};
+///
+/// let client = SrpClient::new();
+/// let proof = client.proof(
+///     b"hunter2",
+///     &SrpChallenge {
+///         salt: vec![],
+///         public: NIST_BASE.clone(),
+///         u: Some(1),
+///     },
+/// );
+///
+/// assert_eq!(
+///     crack_simple_srp(&[], 1, client.get_public(), &proof, &["123456", "hunter2"]),
+///     Some(&"hunter2")
+/// );
+/// ```
+pub fn crack_simple_srp<I>(
+    salt: &[u8],
+    u: u128,
+    client_public: &BigUint,
+    client_proof: &Proof,
+    dictionary: I,
+) -> Option<I::Item>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let u = BigUint::from(u);
+
+    dictionary.into_iter().find(|password| {
+        let verifier = NIST_BASE.modpow(&compute_x(salt, password.as_ref()), &NIST_MODULUS);
+        let shared_secret = (client_public * verifier.modpow(&u, &NIST_MODULUS)) % &*NIST_MODULUS;
+
+        ct_eq(
+            &compute_proof(&compute_session_key(&shared_secret), salt),
+            client_proof,
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+    use num_traits::Zero;
+
+    use super::{
+        compute_proof, compute_session_key, crack_simple_srp, SrpChallenge, SrpClient, SrpServer,
+        SrpVerifier,
+    };
+    use crate::key_exchange::dh::{NIST_BASE, NIST_MODULUS};
+
+    const PASSWORD: &[u8] = b"In west Philadelphia, born and raised";
+
+    #[test]
+    fn test_handshake() {
+        let server = SrpServer::new(SrpVerifier::new(PASSWORD));
+        let client = SrpClient::new();
+
+        let challenge = server.challenge();
+
+        assert_eq!(
+            client.session_key(PASSWORD, &challenge),
+            server.session_key(client.get_public())
+        );
+        assert!(server.verify(client.get_public(), &client.proof(PASSWORD, &challenge)));
+        assert!(!server.verify(
+            client.get_public(),
+            &client.proof(b"NOT THE CORRECT PASSWORD", &challenge)
+        ));
+    }
+
+    #[test]
+    fn test_mutual_authentication() {
+        let server = SrpServer::new(SrpVerifier::new(PASSWORD));
+        let client = SrpClient::new();
+
+        let challenge = server.challenge();
+        let client_proof = client.proof(PASSWORD, &challenge);
+        let server_proof = server.respond(client.get_public(), &client_proof).unwrap();
+
+        assert!(client.verify_server(PASSWORD, &challenge, &server_proof));
+        assert!(!client.verify_server(PASSWORD, &challenge, &client_proof));
+
+        // An impostor server without the verifier cannot prove itself
+        let impostor = SrpServer::new(SrpVerifier::new_with_salt(
+            challenge.salt,
+            b"NOT THE CORRECT PASSWORD",
+        ));
+        let impostor_challenge = impostor.challenge();
+        let client_proof = client.proof(PASSWORD, &impostor_challenge);
+
+        assert_eq!(impostor.respond(client.get_public(), &client_proof), None);
+        assert!(!client.verify_server(
+            PASSWORD,
+            &impostor_challenge,
+            &impostor.session_key(client.get_public())
+        ));
+    }
+
+    #[test]
+    fn test_public_validation() {
+        let server = SrpServer::new(SrpVerifier::new(PASSWORD)).with_public_validation();
+        let client = SrpClient::new();
+
+        let proof = client.proof(PASSWORD, &server.challenge());
+
+        assert!(server.verify(client.get_public(), &proof));
+
+        let forged_proof = compute_proof(
+            &compute_session_key(&BigUint::zero()),
+            &server.challenge().salt,
+        );
+
+        assert!(!server.verify(&BigUint::zero(), &forged_proof));
+        assert!(!server.verify(&NIST_MODULUS, &forged_proof));
+    }
+
+    #[test]
+    fn test_simplified_handshake() {
+        let server = SrpServer::new_simplified(SrpVerifier::new(PASSWORD));
+        let client = SrpClient::new();
+
+        let challenge = server.challenge();
+
+        assert!(challenge.u.is_some());
+        assert!(server.verify(client.get_public(), &client.proof(PASSWORD, &challenge)));
+        assert!(!server.verify(
+            client.get_public(),
+            &client.proof(b"NOT QUITE THE PASSWORD", &challenge)
+        ));
+    }
+
+    #[test]
+    fn test_crack_simple_srp() {
+        const DICTIONARY: &[&[u8]] = &[b"password", b"letmein", PASSWORD, b"hunter2"];
+
+        let client = SrpClient::new();
+        let salt = b"salt".to_vec();
+        let u = 1337;
+
+        let proof = client.proof(
+            PASSWORD,
+            &SrpChallenge {
+                salt: salt.clone(),
+                public: NIST_BASE.clone(),
+                u: Some(u),
+            },
+        );
+
+        assert_eq!(
+            crack_simple_srp(&salt, u, client.get_public(), &proof, DICTIONARY),
+            Some(&PASSWORD)
+        );
+        assert_eq!(
+            crack_simple_srp(&salt, u + 1, client.get_public(), &proof, DICTIONARY),
+            None
+        );
+        assert_eq!(
+            crack_simple_srp(&salt, u, client.get_public(), &proof, &DICTIONARY[..2]),
+            None
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! [Cipher suite](https://en.wikipedia.org/wiki/Cipher_suite) negotiation and
+//! session key derivation on top of a [DH session](super::dh::DHSession).
+//!
+//! The client offers a list of suites in order of preference, the server
+//! picks the first one it supports (see [`Suite::negotiate`]) and both sides
+//! derive [`SessionKeys`] from the DH shared secret and a `transcript` of the
+//! handshake. Each direction gets its own encryption and MAC keys, so a
+//! record sent by the client can never be reflected back to it as if the
+//! server sent it.
+//!
+//! Nothing here authenticates the negotiation itself: a man in the middle
+//! can strip the strong suites from the client offer and force a weak one,
+//! unless the offer is bound into the key derivation `transcript` or checked
+//! with [Finished messages](SessionKeys::finished) (see
+//! [`handshake`](super::handshake)).
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::key_exchange::suite::{Direction, SessionKeys, Suite};
+//! use rustopals::key_exchange::DHOffer;
+//!
+//! let suite = Suite::negotiate(Suite::ALL, &[Suite::AES128_CTR_HMAC_SHA1]).unwrap();
+//!
+//! let (client_offer, server_offer) = (DHOffer::new(), DHOffer::new());
+//! let client_session = client_offer.clone().establish(server_offer.get_public()).unwrap();
+//! let server_session = server_offer.establish(client_offer.get_public()).unwrap();
+//!
+//! let client_keys = SessionKeys::derive(&client_session, suite, b"transcript");
+//! let server_keys = SessionKeys::derive(&server_session, suite, b"transcript");
+//!
+//! let record = client_keys.seal(Direction::ClientToServer, b"Hello");
+//!
+//! assert_eq!(
+//!     server_keys.open(Direction::ClientToServer, &record),
+//!     Some(b"Hello".to_vec()),
+//! );
+//! assert_eq!(server_keys.open(Direction::ServerToClient, &record), None);
+//! ```
+
+use std::fmt;
+
+use super::dh::DHSession;
+use crate::block::{BlockCipher, Key, AES128, CBC};
+use crate::digest::ds::{encode_labeled, hash_labeled};
+use crate::digest::{Digest, SHA1, SHA256};
+use crate::mac::{Hmac, Mac};
+use crate::stream::CTR;
+use crate::util::ct_eq;
+use crate::util::secret::Redacted;
+
+/// Domain separation label for [`SessionKeys::finished`].
+pub const FINISHED_LABEL: &[u8] = b"rustopals-suite-finished";
+
+/// Supported cipher suites (encryption and MAC algorithms).
+///
+/// Records are encrypted-then-MACed.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Suite {
+    /// AES-128 in CBC mode (random IV, PKCS7 padding) with HMAC-SHA256.
+    AES128_CBC_HMAC_SHA256,
+
+    /// AES-128 in CTR mode (random nonce) with HMAC-SHA1.
+    AES128_CTR_HMAC_SHA1,
+
+    /// No encryption at all, just HMAC-SHA1. Only here to be downgraded to.
+    NULL_HMAC_SHA1,
+}
+
+impl Suite {
+    /// Every suite, strongest first.
+    pub const ALL: &'static [Suite] = &[
+        Suite::AES128_CBC_HMAC_SHA256,
+        Suite::AES128_CTR_HMAC_SHA1,
+        Suite::NULL_HMAC_SHA1,
+    ];
+
+    /// Wire identifier.
+    #[must_use]
+    pub const fn id(self) -> u16 {
+        match self {
+            Suite::AES128_CBC_HMAC_SHA256 => 0x0001,
+            Suite::AES128_CTR_HMAC_SHA1 => 0x0002,
+            Suite::NULL_HMAC_SHA1 => 0x00ff,
+        }
+    }
+
+    /// Parse a wire identifier.
+    #[must_use]
+    pub fn from_id(id: u16) -> Option<Suite> {
+        Suite::ALL.iter().copied().find(|suite| suite.id() == id)
+    }
+
+    /// Whether this suite provides no confidentiality.
+    #[must_use]
+    pub const fn is_weak(self) -> bool {
+        matches!(self, Suite::NULL_HMAC_SHA1)
+    }
+
+    /// Encryption key length (in bytes).
+    #[must_use]
+    pub const fn key_len(self) -> usize {
+        match self {
+            Suite::AES128_CBC_HMAC_SHA256 | Suite::AES128_CTR_HMAC_SHA1 => AES128::KEY_SIZE,
+            Suite::NULL_HMAC_SHA1 => 0,
+        }
+    }
+
+    /// MAC key (and tag) length (in bytes).
+    #[must_use]
+    pub const fn mac_len(self) -> usize {
+        match self {
+            Suite::AES128_CBC_HMAC_SHA256 => SHA256::OUTPUT_LENGTH,
+            Suite::AES128_CTR_HMAC_SHA1 | Suite::NULL_HMAC_SHA1 => SHA1::OUTPUT_LENGTH,
+        }
+    }
+
+    /// Server-side negotiation: pick the first suite in the client `offer`
+    /// that the server `supported`s.
+    #[must_use]
+    pub fn negotiate(offer: &[Suite], supported: &[Suite]) -> Option<Suite> {
+        offer
+            .iter()
+            .copied()
+            .find(|suite| supported.contains(suite))
+    }
+
+    /// Encode a suite list for the wire (e.g. to include it in a transcript).
+    #[must_use]
+    pub fn encode_list(suites: &[Suite]) -> Vec<u8> {
+        suites
+            .iter()
+            .flat_map(|suite| suite.id().to_be_bytes())
+            .collect()
+    }
+
+    fn tag(self, mac_key: &[u8], data: &[u8]) -> Vec<u8> {
+        match self {
+            Suite::AES128_CBC_HMAC_SHA256 => Hmac::<SHA256>::new(mac_key).tag(data).to_vec(),
+            Suite::AES128_CTR_HMAC_SHA1 | Suite::NULL_HMAC_SHA1 => {
+                Hmac::<SHA1>::new(mac_key).tag(data).to_vec()
+            },
+        }
+    }
+
+    fn encrypt(self, key: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let aes_key = || Key::new(key).expect("Keys are derived with the suite key length");
+
+        match self {
+            Suite::AES128_CBC_HMAC_SHA256 => {
+                CBC::encrypt_with_random_iv(&AES128, plaintext, &aes_key())
+            },
+            Suite::AES128_CTR_HMAC_SHA1 => {
+                CTR::encrypt_with_random_nonce(&AES128, &aes_key(), plaintext)
+            },
+            Suite::NULL_HMAC_SHA1 => plaintext.to_vec(),
+        }
+    }
+
+    fn decrypt(self, key: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Suite::AES128_CBC_HMAC_SHA256 => {
+                CBC::decrypt_with_prepended_iv(&AES128, ciphertext, &Key::new(key).ok()?).ok()
+            },
+            Suite::AES128_CTR_HMAC_SHA1 => {
+                CTR::decrypt_with_prepended_nonce(&AES128, &Key::new(key).ok()?, ciphertext)
+            },
+            Suite::NULL_HMAC_SHA1 => Some(ciphertext.to_vec()),
+        }
+    }
+}
+
+/// Direction of a record, which selects the keys (and KDF label) to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Sent by the client.
+    ClientToServer,
+
+    /// Sent by the server.
+    ServerToClient,
+}
+
+impl Direction {
+    /// Domain separation label used to derive this direction's keys.
+    #[must_use]
+    pub const fn label(self) -> &'static [u8] {
+        match self {
+            Direction::ClientToServer => b"rustopals-suite-client-to-server",
+            Direction::ServerToClient => b"rustopals-suite-server-to-client",
+        }
+    }
+}
+
+/// Encryption and MAC keys for one direction.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DirectionKeys {
+    key: Vec<u8>,
+    mac_key: Vec<u8>,
+}
+
+impl DirectionKeys {
+    /// Get the encryption and MAC keys.
+    #[must_use]
+    pub fn expose_secret(&self) -> (&[u8], &[u8]) {
+        (&self.key, &self.mac_key)
+    }
+}
+
+impl fmt::Debug for DirectionKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectionKeys")
+            .field("key", &Redacted::bytes(&self.key))
+            .field("mac_key", &Redacted::bytes(&self.mac_key))
+            .finish()
+    }
+}
+
+/// Keys for both directions of a session using a negotiated [`Suite`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionKeys {
+    suite: Suite,
+    client_to_server: DirectionKeys,
+    server_to_client: DirectionKeys,
+}
+
+impl SessionKeys {
+    /// Derive the session keys for `suite` from a DH `session` and the
+    /// handshake `transcript`.
+    ///
+    /// Both parties only get the same keys if they agree on the shared
+    /// secret, the suite and the transcript.
+    #[must_use]
+    pub fn derive(session: &DHSession, suite: Suite, transcript: &[u8]) -> SessionKeys {
+        let secret = session.to_key_material::<SHA256>();
+
+        let derive_direction = |direction: Direction| {
+            let key_material = expand(
+                direction.label(),
+                &[&secret, transcript, &suite.id().to_be_bytes()[..]],
+                suite.key_len() + suite.mac_len(),
+            );
+            let (key, mac_key) = key_material.split_at(suite.key_len());
+
+            DirectionKeys {
+                key: key.to_vec(),
+                mac_key: mac_key.to_vec(),
+            }
+        };
+
+        SessionKeys {
+            suite,
+            client_to_server: derive_direction(Direction::ClientToServer),
+            server_to_client: derive_direction(Direction::ServerToClient),
+        }
+    }
+
+    /// Serialize (e.g. to store it in a [ticket](super::ticket)): the suite
+    /// identifier followed by every key.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [
+            &self.suite.id().to_be_bytes()[..],
+            &self.client_to_server.key,
+            &self.client_to_server.mac_key,
+            &self.server_to_client.key,
+            &self.server_to_client.mac_key,
+        ]
+        .concat()
+    }
+
+    /// Deserialize the output of [`to_bytes`](SessionKeys::to_bytes).
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<SessionKeys> {
+        if bytes.len() < 2 {
+            return None;
+        }
+
+        let (id, keys) = bytes.split_at(2);
+        let suite = Suite::from_id(u16::from_be_bytes([id[0], id[1]]))?;
+
+        let direction_len = suite.key_len() + suite.mac_len();
+
+        if keys.len() != 2 * direction_len {
+            return None;
+        }
+
+        let parse_direction = |bytes: &[u8]| {
+            let (key, mac_key) = bytes.split_at(suite.key_len());
+
+            DirectionKeys {
+                key: key.to_vec(),
+                mac_key: mac_key.to_vec(),
+            }
+        };
+
+        Some(SessionKeys {
+            suite,
+            client_to_server: parse_direction(&keys[..direction_len]),
+            server_to_client: parse_direction(&keys[direction_len..]),
+        })
+    }
+
+    /// Get the negotiated suite.
+    #[must_use]
+    pub const fn suite(&self) -> Suite {
+        self.suite
+    }
+
+    /// Get the keys for `direction`.
+    #[must_use]
+    pub const fn keys(&self, direction: Direction) -> &DirectionKeys {
+        match direction {
+            Direction::ClientToServer => &self.client_to_server,
+            Direction::ServerToClient => &self.server_to_client,
+        }
+    }
+
+    /// Encrypt and authenticate a record sent in `direction`.
+    #[must_use]
+    pub fn seal(&self, direction: Direction, plaintext: &[u8]) -> Vec<u8> {
+        let keys = self.keys(direction);
+        let ciphertext = self.suite.encrypt(&keys.key, plaintext);
+        let tag = self.suite.tag(&keys.mac_key, &ciphertext);
+
+        [ciphertext, tag].concat()
+    }
+
+    /// Check and decrypt a `record` sent in `direction`.
+    ///
+    /// Returns `None` if the record is malformed or its MAC is wrong.
+    #[must_use]
+    pub fn open(&self, direction: Direction, record: &[u8]) -> Option<Vec<u8>> {
+        let keys = self.keys(direction);
+
+        if record.len() < self.suite.mac_len() {
+            return None;
+        }
+
+        let (ciphertext, tag) = record.split_at(record.len() - self.suite.mac_len());
+
+        if !ct_eq(&self.suite.tag(&keys.mac_key, ciphertext), tag) {
+            return None;
+        }
+
+        self.suite.decrypt(&keys.key, ciphertext)
+    }
+
+    /// Compute the TLS-style Finished message for `direction`: a MAC over
+    /// the whole handshake `transcript`, proving the sender saw the same
+    /// handshake (and derived the same keys).
+    #[must_use]
+    pub fn finished(&self, direction: Direction, transcript: &[u8]) -> Vec<u8> {
+        self.suite.tag(
+            &self.keys(direction).mac_key,
+            &encode_labeled(FINISHED_LABEL, &[transcript]),
+        )
+    }
+
+    /// Check the peer's Finished message for `direction` against our own
+    /// view of the `transcript`.
+    #[must_use]
+    pub fn verify_finished(
+        &self,
+        direction: Direction,
+        transcript: &[u8],
+        finished: &[u8],
+    ) -> bool {
+        ct_eq(&self.finished(direction, transcript), finished)
+    }
+}
+
+/// Expand `parts` into `len` bytes of key material, hashing them under
+/// `label` along with a block counter.
+fn expand(label: &[u8], parts: &[&[u8]], len: usize) -> Vec<u8> {
+    let mut output = Vec::with_capacity(len);
+    let mut counter = 0_u32;
+
+    while output.len() < len {
+        let counter_bytes = counter.to_be_bytes();
+        let block_parts = [parts, &[&counter_bytes[..]]].concat();
+
+        output.extend_from_slice(&hash_labeled::<SHA256>(label, &block_parts));
+        counter += 1;
+    }
+
+    output.truncate(len);
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Direction, SessionKeys, Suite};
+    use crate::key_exchange::DHOffer;
+
+    /// Run a handshake where a man in the middle can `tamper` with the
+    /// client offer, returning the client and server keys.
+    ///
+    /// When `bind_offer` is set, the offer (as each party saw it) is part of
+    /// the key derivation transcript.
+    fn handshake(
+        tamper: impl FnOnce(Vec<Suite>) -> Vec<Suite>,
+        bind_offer: bool,
+    ) -> (SessionKeys, SessionKeys) {
+        const CLIENT_SUITES: &[Suite] = Suite::ALL;
+        const SERVER_SUITES: &[Suite] = Suite::ALL;
+
+        let (client_offer, server_offer) = (DHOffer::new(), DHOffer::new());
+
+        // Client -> Server: offered suites and public value (through Eve)
+        let client_hello = CLIENT_SUITES.to_vec();
+        let received_hello = tamper(client_hello.clone());
+
+        // Server -> Client: chosen suite and public value
+        let suite = Suite::negotiate(&received_hello, SERVER_SUITES).unwrap();
+
+        let transcript = |offer: &[Suite]| {
+            let mut transcript = suite.id().to_be_bytes().to_vec();
+
+            if bind_offer {
+                transcript.extend(Suite::encode_list(offer));
+            }
+
+            transcript.extend(client_offer.get_public().to_bytes_be());
+            transcript.extend(server_offer.get_public().to_bytes_be());
+            transcript
+        };
+
+        let client_transcript = transcript(&client_hello);
+        let server_transcript = transcript(&received_hello);
+
+        let client_session = client_offer
+            .clone()
+            .establish(server_offer.get_public())
+            .unwrap();
+        let server_session = server_offer.establish(client_offer.get_public()).unwrap();
+
+        (
+            SessionKeys::derive(&client_session, suite, &client_transcript),
+            SessionKeys::derive(&server_session, suite, &server_transcript),
+        )
+    }
+
+    #[test]
+    fn test_session() {
+        let (client_keys, server_keys) = handshake(|offer| offer, true);
+
+        assert_eq!(client_keys, server_keys);
+        assert_eq!(client_keys.suite(), Suite::AES128_CBC_HMAC_SHA256);
+        assert_ne!(
+            client_keys.keys(Direction::ClientToServer),
+            client_keys.keys(Direction::ServerToClient)
+        );
+
+        for direction in [Direction::ClientToServer, Direction::ServerToClient] {
+            let record = client_keys.seal(direction, b"Hello, world!");
+
+            assert_eq!(
+                server_keys.open(direction, &record),
+                Some(b"Hello, world!".to_vec())
+            );
+        }
+
+        let mut record = client_keys.seal(Direction::ClientToServer, b"Hello, world!");
+        record[0] ^= 1;
+
+        assert_eq!(server_keys.open(Direction::ClientToServer, &record), None);
+        assert_eq!(server_keys.open(Direction::ClientToServer, &[]), None);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let (keys, _) = handshake(|offer| offer, true);
+        let bytes = keys.to_bytes();
+
+        assert_eq!(SessionKeys::from_bytes(&bytes), Some(keys));
+        assert_eq!(SessionKeys::from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(SessionKeys::from_bytes(&[0x12, 0x34]), None);
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let strip_strong = |offer: Vec<Suite>| {
+            offer
+                .into_iter()
+                .filter(|suite| suite.is_weak())
+                .collect::<Vec<_>>()
+        };
+
+        // Unbound offer: both agree on the weak suite, and Eve reads along
+        let (client_keys, server_keys) = handshake(strip_strong, false);
+
+        assert_eq!(client_keys.suite(), Suite::NULL_HMAC_SHA1);
+        assert_eq!(client_keys, server_keys);
+
+        let record = client_keys.seal(Direction::ClientToServer, b"attack at dawn");
+
+        assert!(record.starts_with(b"attack at dawn"));
+
+        // Bound offer: the transcripts differ, so the session is unusable
+        let (client_keys, server_keys) = handshake(strip_strong, true);
+
+        let record = client_keys.seal(Direction::ClientToServer, b"attack at dawn");
+
+        assert_eq!(server_keys.open(Direction::ClientToServer, &record), None);
+    }
+
+    #[test]
+    fn test_suite_ids() {
+        for &suite in Suite::ALL {
+            assert_eq!(Suite::from_id(suite.id()), Some(suite));
+        }
+
+        assert_eq!(Suite::from_id(0x1234), None);
+    }
+}
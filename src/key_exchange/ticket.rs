@@ -0,0 +1,409 @@
+//! [Session tickets](https://en.wikipedia.org/wiki/Transport_Layer_Security#Session_tickets),
+//! to keep servers stateless.
+//!
+//! The server seals the [`SessionKeys`] under a key only it knows and hands
+//! them to the client, which sends them back to resume the session. Tickets are encrypted (AES-128-CBC) then MACed (HMAC-SHA256) under a
+//! [`TicketKey`], and carry the time they were issued so they expire after
+//! the [`TicketKeyring`] lifetime. Keyrings keep a few previous keys after a
+//! [rotation](TicketKeyring::rotate), so recently issued tickets stay valid.
+//!
+//! Tickets undermine [forward secrecy](https://en.wikipedia.org/wiki/Forward_secrecy):
+//! ephemeral DH keys are thrown away after the handshake, but the session
+//! keys are stored in every ticket, protected by a long-lived key. Whoever
+//! recorded the traffic and later gets (or [guesses](crack_ticket_key)) the
+//! ticket key can decrypt every past session sealed under it.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::key_exchange::suite::{SessionKeys, Suite};
+//! use rustopals::key_exchange::ticket::{Ticket, TicketError, TicketKey, TicketKeyring};
+//! use rustopals::key_exchange::DHOffer;
+//! use rustopals::util::clock::MockClock;
+//!
+//! # let (client_offer, server_offer) = (DHOffer::new(), DHOffer::new());
+//! # let session = server_offer.establish(client_offer.get_public()).unwrap();
+//! let keys = SessionKeys::derive(&session, Suite::AES128_CBC_HMAC_SHA256, &[]);
+//!
+//! let clock = MockClock::new(1_000_000);
+//! let keyring = TicketKeyring::new(TicketKey::generate(1), 3600);
+//!
+//! let ticket = keyring.seal(&keys, &clock);
+//!
+//! assert_eq!(ticket.open(&keyring, &clock), Ok(keys));
+//!
+//! clock.advance(3601);
+//!
+//! assert_eq!(ticket.open(&keyring, &clock), Err(TicketError::Expired));
+//! ```
+
+use std::fmt;
+
+use super::suite::SessionKeys;
+use crate::block::{BlockCipher, BlockMode, Iv, Key, AES128, CBC};
+use crate::digest::ds::hash_labeled;
+use crate::digest::{Digest, SHA256};
+use crate::mac::{Hmac, Mac};
+use crate::util::clock::Clock;
+use crate::util::generate_bytes;
+use crate::util::secret::Redacted;
+
+/// Domain separation label for [`TicketKey::from_seed`] encryption keys.
+pub const ENCRYPTION_KEY_LABEL: &[u8] = b"rustopals-ticket-encryption-key";
+
+/// Domain separation label for [`TicketKey::from_seed`] MAC keys.
+pub const MAC_KEY_LABEL: &[u8] = b"rustopals-ticket-mac-key";
+
+/// Amount of keys (including the current one) kept by a [`TicketKeyring`].
+pub const RETAINED_KEYS: usize = 3;
+
+/// Errors when opening a [`Ticket`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TicketError {
+    /// The ticket is too short or its contents are not a session.
+    Malformed,
+
+    /// The ticket was sealed with a key not in the keyring (e.g. rotated
+    /// out).
+    UnknownKey,
+
+    /// The ticket MAC is wrong.
+    BadMac,
+
+    /// The ticket is older than the keyring lifetime.
+    Expired,
+}
+
+/// Server-side key to seal tickets.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TicketKey {
+    id: u32,
+    key: Key<AES128>,
+    mac_key: Vec<u8>,
+}
+
+impl TicketKey {
+    /// Generate a random key, identified by `id`.
+    #[must_use]
+    pub fn generate(id: u32) -> TicketKey {
+        TicketKey {
+            id,
+            key: Key::random(),
+            mac_key: generate_bytes(SHA256::OUTPUT_LENGTH),
+        }
+    }
+
+    /// Derive a key (identified by `id`) from a `seed`.
+    ///
+    /// The key is only as strong as the seed: low-entropy seeds can be
+    /// [brute-forced](crack_ticket_key).
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn from_seed(id: u32, seed: &[u8]) -> TicketKey {
+        TicketKey {
+            id,
+            key: Key::new(
+                &hash_labeled::<SHA256>(ENCRYPTION_KEY_LABEL, &[seed])[..AES128::KEY_SIZE],
+            )
+            .expect("SHA-256 digests are longer than AES-128 keys"),
+            mac_key: hash_labeled::<SHA256>(MAC_KEY_LABEL, &[seed]).to_vec(),
+        }
+    }
+
+    /// Get the key identifier.
+    #[must_use]
+    pub const fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn tag(&self, data: &[u8]) -> <SHA256 as Digest>::Output {
+        Hmac::<SHA256>::new(&self.mac_key).tag(data)
+    }
+}
+
+impl fmt::Debug for TicketKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TicketKey")
+            .field("id", &self.id)
+            .field("key", &Redacted::bytes(&self.key))
+            .field("mac_key", &Redacted::bytes(&self.mac_key))
+            .finish()
+    }
+}
+
+/// Current and recent [`TicketKey`]s, along with the ticket lifetime.
+#[derive(Clone, Debug)]
+pub struct TicketKeyring {
+    /// Most recent first.
+    keys: Vec<TicketKey>,
+    lifetime: u64,
+}
+
+impl TicketKeyring {
+    /// Create a keyring with an initial `key`, accepting tickets for
+    /// `lifetime` seconds.
+    #[must_use]
+    pub fn new(key: TicketKey, lifetime: u64) -> TicketKeyring {
+        TicketKeyring {
+            keys: vec![key],
+            lifetime,
+        }
+    }
+
+    /// Start sealing with `key`. Only the last [`RETAINED_KEYS`] keys are
+    /// kept to open tickets.
+    pub fn rotate(&mut self, key: TicketKey) {
+        self.keys.insert(0, key);
+        self.keys.truncate(RETAINED_KEYS);
+    }
+
+    /// Get the key used to seal new tickets.
+    #[must_use]
+    pub fn current(&self) -> &TicketKey {
+        &self.keys[0]
+    }
+
+    /// Seal `state` with the current key.
+    #[must_use]
+    pub fn seal(&self, state: &SessionKeys, clock: &impl Clock) -> Ticket {
+        Ticket::seal(state, self.current(), clock)
+    }
+}
+
+/// A sealed session state. Opaque to the client.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ticket(pub Vec<u8>);
+
+impl Ticket {
+    /// Length of the key identifier prefix.
+    const ID_LEN: usize = 4;
+
+    /// Seal `state` under `key`, stamped with the current time.
+    ///
+    /// Layout: `key id || IV || AES-CBC(issue time || state) || HMAC`.
+    #[must_use]
+    pub fn seal(state: &SessionKeys, key: &TicketKey, clock: &impl Clock) -> Ticket {
+        let plaintext = [&clock.unix_time().to_be_bytes()[..], &state.to_bytes()].concat();
+
+        let iv = Iv::random();
+        let ciphertext = CBC::new(&iv).encrypt(&AES128, &plaintext, &key.key);
+
+        let sealed = [&key.id.to_be_bytes()[..], &iv, &ciphertext].concat();
+        let tag = key.tag(&sealed);
+
+        Ticket([sealed, tag.to_vec()].concat())
+    }
+
+    /// Get the identifier of the key the ticket claims to be sealed with.
+    #[must_use]
+    pub fn key_id(&self) -> Option<u32> {
+        let id = self.0.get(..Self::ID_LEN)?;
+
+        Some(u32::from_be_bytes([id[0], id[1], id[2], id[3]]))
+    }
+
+    /// Check whether the ticket MAC is valid under `key`.
+    #[must_use]
+    pub fn verify(&self, key: &TicketKey) -> bool {
+        if self.0.len() < Self::ID_LEN + AES128::BLOCK_SIZE + SHA256::OUTPUT_LENGTH {
+            return false;
+        }
+
+        let (sealed, tag) = self.0.split_at(self.0.len() - SHA256::OUTPUT_LENGTH);
+
+        Hmac::<SHA256>::new(&key.mac_key).verify(sealed, tag)
+    }
+
+    /// Open the ticket with the matching key in `keyring`.
+    ///
+    /// # Errors
+    ///
+    /// See [`TicketError`].
+    pub fn open(
+        &self,
+        keyring: &TicketKeyring,
+        clock: &impl Clock,
+    ) -> Result<SessionKeys, TicketError> {
+        let id = self.key_id().ok_or(TicketError::Malformed)?;
+
+        let key = keyring
+            .keys
+            .iter()
+            .find(|key| key.id == id)
+            .ok_or(TicketError::UnknownKey)?;
+
+        let (issued_at, state) = self.decrypt(key)?;
+
+        if clock.unix_time().saturating_sub(issued_at) > keyring.lifetime {
+            return Err(TicketError::Expired);
+        }
+
+        Ok(state)
+    }
+
+    /// Check and decrypt the ticket with `key`, ignoring its age.
+    ///
+    /// Returns the issue time and the session state.
+    ///
+    /// # Errors
+    ///
+    /// If the MAC is wrong or the contents are malformed.
+    pub fn decrypt(&self, key: &TicketKey) -> Result<(u64, SessionKeys), TicketError> {
+        if !self.verify(key) {
+            return Err(TicketError::BadMac);
+        }
+
+        let sealed = &self.0[Self::ID_LEN..self.0.len() - SHA256::OUTPUT_LENGTH];
+        let (iv, ciphertext) = sealed.split_at(AES128::BLOCK_SIZE);
+
+        let plaintext = CBC::new(&Iv::new(iv).map_err(|_| TicketError::Malformed)?)
+            .decrypt(&AES128, ciphertext, &key.key)
+            .map_err(|_| TicketError::Malformed)?;
+
+        if plaintext.len() < 8 {
+            return Err(TicketError::Malformed);
+        }
+
+        let (issued_at, state) = plaintext.split_at(8);
+        let mut issued_at_bytes = [0; 8];
+        issued_at_bytes.copy_from_slice(issued_at);
+
+        let state = SessionKeys::from_bytes(state).ok_or(TicketError::Malformed)?;
+
+        Ok((u64::from_be_bytes(issued_at_bytes), state))
+    }
+}
+
+/// Find the key that sealed `ticket` among those derived (with
+/// [`TicketKey::from_seed`]) from a list of candidate `seeds`.
+///
+/// Only the MAC is checked, so each guess costs a couple of hashes: a
+/// ticket key derived from a timestamp, a PID or a short passphrase falls
+/// quickly. Every ticket sealed under it (past or future) can then be
+/// [decrypted](Ticket::decrypt).
+pub fn crack_ticket_key<S: AsRef<[u8]>>(
+    ticket: &Ticket,
+    seeds: impl IntoIterator<Item = S>,
+) -> Option<TicketKey> {
+    let id = ticket.key_id()?;
+
+    seeds
+        .into_iter()
+        .map(|seed| TicketKey::from_seed(id, seed.as_ref()))
+        .find(|key| ticket.verify(key))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crack_ticket_key, Ticket, TicketError, TicketKey, TicketKeyring};
+    use crate::key_exchange::suite::{Direction, SessionKeys, Suite};
+    use crate::key_exchange::DHOffer;
+    use crate::util::clock::MockClock;
+
+    const LIFETIME: u64 = 3600;
+
+    fn session_keys() -> SessionKeys {
+        let (client_offer, server_offer) = (DHOffer::new(), DHOffer::new());
+        let session = server_offer.establish(client_offer.get_public()).unwrap();
+
+        SessionKeys::derive(&session, Suite::AES128_CTR_HMAC_SHA1, &[])
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let clock = MockClock::new(1_000_000);
+        let keys = session_keys();
+        let keyring = TicketKeyring::new(TicketKey::generate(1), LIFETIME);
+
+        let ticket = keyring.seal(&keys, &clock);
+
+        assert_eq!(ticket.key_id(), Some(1));
+        assert_eq!(ticket.open(&keyring, &clock), Ok(keys.clone()));
+
+        clock.advance(LIFETIME);
+        assert_eq!(ticket.open(&keyring, &clock), Ok(keys));
+
+        clock.advance(1);
+        assert_eq!(ticket.open(&keyring, &clock), Err(TicketError::Expired));
+    }
+
+    #[test]
+    fn test_rotation() {
+        let clock = MockClock::new(1_000_000);
+        let keys = session_keys();
+        let mut keyring = TicketKeyring::new(TicketKey::generate(1), LIFETIME);
+
+        let old_ticket = keyring.seal(&keys, &clock);
+
+        keyring.rotate(TicketKey::generate(2));
+        let new_ticket = keyring.seal(&keys, &clock);
+
+        assert_eq!(new_ticket.key_id(), Some(2));
+        assert_eq!(old_ticket.open(&keyring, &clock), Ok(keys.clone()));
+        assert_eq!(new_ticket.open(&keyring, &clock), Ok(keys));
+
+        keyring.rotate(TicketKey::generate(3));
+        keyring.rotate(TicketKey::generate(4));
+
+        assert_eq!(
+            old_ticket.open(&keyring, &clock),
+            Err(TicketError::UnknownKey)
+        );
+    }
+
+    #[test]
+    fn test_tampering() {
+        let clock = MockClock::new(1_000_000);
+        let keyring = TicketKeyring::new(TicketKey::generate(1), LIFETIME);
+        let ticket = keyring.seal(&session_keys(), &clock);
+
+        let mut tampered = ticket.clone();
+        tampered.0[10] ^= 1;
+        assert_eq!(tampered.open(&keyring, &clock), Err(TicketError::BadMac));
+
+        let other_keyring = TicketKeyring::new(TicketKey::generate(1), LIFETIME);
+        assert_eq!(
+            ticket.open(&other_keyring, &clock),
+            Err(TicketError::BadMac)
+        );
+
+        assert_eq!(
+            Ticket(vec![0; 3]).open(&keyring, &clock),
+            Err(TicketError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_weak_key_compromises_past_sessions() {
+        let clock = MockClock::new(1_000_000);
+
+        // The server derives its ticket key from a 16-bit seed
+        let keyring = TicketKeyring::new(TicketKey::from_seed(7, &0xbeef_u16.to_be_bytes()), 60);
+
+        // Eve records a few sessions: tickets and encrypted traffic
+        let recorded = (0..3)
+            .map(|i| {
+                let keys = session_keys();
+                let ticket = keyring.seal(&keys, &clock);
+                let record = keys.seal(Direction::ClientToServer, format!("secret {i}").as_bytes());
+
+                clock.advance(3600);
+
+                (ticket, record)
+            })
+            .collect::<Vec<_>>();
+
+        // Long after every ticket expired, Eve brute-forces the seed...
+        let key = crack_ticket_key(&recorded[0].0, (0..=u16::MAX).map(u16::to_be_bytes)).unwrap();
+
+        // ...and decrypts every past session
+        for (i, (ticket, record)) in recorded.iter().enumerate() {
+            let (_, keys) = ticket.decrypt(&key).unwrap();
+
+            assert_eq!(
+                keys.open(Direction::ClientToServer, record),
+                Some(format!("secret {i}").into_bytes())
+            );
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! [X25519](https://datatracker.ietf.org/doc/html/rfc7748) key exchange
+//! (Diffie-Hellman over Curve25519), with the same API as [`dh`](super::dh).
+//!
+//! Keys are the 32-byte little-endian strings from RFC 7748. Only the `u`
+//! coordinate is ever used, via the Montgomery ladder in [`x25519`].
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::key_exchange::x25519::X25519Offer;
+//!
+//! let alice_offer = X25519Offer::new();
+//! let bob_offer = X25519Offer::new();
+//!
+//! let alice_session = alice_offer
+//!     .clone()
+//!     .establish(bob_offer.get_public())
+//!     .unwrap();
+//! let bob_session = bob_offer
+//!     .establish(alice_offer.get_public())
+//!     .unwrap();
+//!
+//! assert_eq!(
+//!     alice_session.get_shared_secret(),
+//!     bob_session.get_shared_secret(),
+//! )
+//! ```
+
+use std::{fmt, mem};
+
+use num_bigint::BigUint;
+use once_cell::sync::Lazy;
+use rand::RngCore;
+
+use super::dh::derive_key_material;
+use crate::digest::Digest;
+use crate::rand::rng;
+use crate::util::field::{Field, ModP};
+use crate::util::secret::Redacted;
+
+/// Length (in bytes) of scalars and `u` coordinates.
+pub const KEY_LEN: usize = 32;
+
+/// The base point (`u = 9`).
+pub const BASE_POINT: [u8; KEY_LEN] = {
+    let mut u = [0; KEY_LEN];
+    u[0] = 9;
+    u
+};
+
+/// The field prime, `2^255 - 19`.
+pub static P: Lazy<BigUint> = Lazy::new(|| (BigUint::from(1_u32) << 255_u32) - 19_u32);
+
+/// `(A - 2) / 4` for Curve25519's `A = 486662`.
+const A24: u32 = 121_665;
+
+/// Clamp a `scalar` as in RFC 7748 (clear the cofactor bits, clear the top
+/// bit and set the next one).
+#[must_use]
+pub const fn clamp(mut scalar: [u8; KEY_LEN]) -> [u8; KEY_LEN] {
+    scalar[0] &= 0b1111_1000;
+    scalar[KEY_LEN - 1] &= 0b0111_1111;
+    scalar[KEY_LEN - 1] |= 0b0100_0000;
+    scalar
+}
+
+/// Compute the X25519 function: multiply the point with `u` coordinate `u`
+/// by the (clamped) `scalar`.
+///
+/// This is **not** constant-time.
+#[allow(clippy::many_single_char_names)] // Names from RFC 7748
+#[must_use]
+pub fn x25519(scalar: &[u8; KEY_LEN], u: &[u8; KEY_LEN]) -> [u8; KEY_LEN] {
+    let field = ModP(P.clone());
+
+    let k = BigUint::from_bytes_le(&clamp(*scalar));
+
+    let mut u = *u;
+    u[KEY_LEN - 1] &= 0b0111_1111;
+    let x_1 = field.elem(&BigUint::from_bytes_le(&u));
+
+    let a24 = BigUint::from(A24);
+
+    let (mut x_2, mut z_2) = (field.one(), field.zero());
+    let (mut x_3, mut z_3) = (x_1.clone(), field.one());
+    let mut swap = false;
+
+    for t in (0..255).rev() {
+        let k_t = k.bit(t);
+
+        if swap != k_t {
+            mem::swap(&mut x_2, &mut x_3);
+            mem::swap(&mut z_2, &mut z_3);
+        }
+
+        swap = k_t;
+
+        let a = field.add(&x_2, &z_2);
+        let aa = field.mul(&a, &a);
+        let b = field.sub(&x_2, &z_2);
+        let bb = field.mul(&b, &b);
+        let e = field.sub(&aa, &bb);
+        let c = field.add(&x_3, &z_3);
+        let d = field.sub(&x_3, &z_3);
+        let da = field.mul(&d, &a);
+        let cb = field.mul(&c, &b);
+
+        let sum = field.add(&da, &cb);
+        let difference = field.sub(&da, &cb);
+
+        x_3 = field.mul(&sum, &sum);
+        z_3 = field.mul(&x_1, &field.mul(&difference, &difference));
+        x_2 = field.mul(&aa, &bb);
+        z_2 = field.mul(&e, &field.add(&aa, &field.mul(&a24, &e)));
+    }
+
+    if swap {
+        mem::swap(&mut x_2, &mut x_3);
+        mem::swap(&mut z_2, &mut z_3);
+    }
+
+    // `z_2^-1` (or zero for the point at infinity, as in RFC 7748)
+    let z_inv = z_2.modpow(&(&*P - 2_u32), &P);
+
+    let mut bytes = field.mul(&x_2, &z_inv).to_bytes_le();
+    bytes.resize(KEY_LEN, 0);
+
+    let mut result = [0; KEY_LEN];
+    result.copy_from_slice(&bytes);
+    result
+}
+
+/// An X25519 local offer.
+#[derive(Clone)]
+#[must_use]
+pub struct X25519Offer {
+    my_private: [u8; KEY_LEN],
+    my_public: [u8; KEY_LEN],
+}
+
+impl X25519Offer {
+    /// Create a new X25519 offer with a random private key.
+    pub fn new() -> X25519Offer {
+        let mut my_private = [0; KEY_LEN];
+        rng().fill_bytes(&mut my_private);
+
+        X25519Offer::new_from_private(my_private)
+    }
+
+    /// Create a new X25519 offer specifying its private key.
+    ///
+    /// Every private key is valid (they get [clamped](clamp)).
+    pub fn new_from_private(my_private: [u8; KEY_LEN]) -> X25519Offer {
+        X25519Offer {
+            my_private,
+            my_public: x25519(&my_private, &BASE_POINT),
+        }
+    }
+
+    /// Get the offer's public key.
+    #[must_use]
+    pub const fn get_public(&self) -> &[u8; KEY_LEN] {
+        &self.my_public
+    }
+
+    /// Get the offer's private key.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &[u8; KEY_LEN] {
+        &self.my_private
+    }
+
+    /// Establish an X25519 session by passing the other party's public key.
+    ///
+    /// Returns `None` if the shared secret is all zeros (i.e. `their_public`
+    /// is in a small subgroup), as recommended by RFC 7748.
+    #[must_use]
+    pub fn establish(self, their_public: &[u8; KEY_LEN]) -> Option<X25519Session> {
+        let shared_secret = x25519(&self.my_private, their_public);
+
+        if shared_secret == [0; KEY_LEN] {
+            return None;
+        }
+
+        Some(X25519Session {
+            my_private: self.my_private,
+            my_public: self.my_public,
+            their_public: *their_public,
+            shared_secret,
+        })
+    }
+}
+
+impl fmt::Debug for X25519Offer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("X25519Offer")
+            .field("my_private", &Redacted::bytes(&self.my_private))
+            .field("my_public", &hex::encode(self.my_public))
+            .finish()
+    }
+}
+
+impl Default for X25519Offer {
+    fn default() -> X25519Offer {
+        X25519Offer::new()
+    }
+}
+
+/// An X25519 already-established session.
+#[derive(Clone)]
+#[must_use]
+pub struct X25519Session {
+    my_private: [u8; KEY_LEN],
+    my_public: [u8; KEY_LEN],
+    their_public: [u8; KEY_LEN],
+    shared_secret: [u8; KEY_LEN],
+}
+
+impl fmt::Debug for X25519Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("X25519Session")
+            .field("my_private", &Redacted::bytes(&self.my_private))
+            .field("my_public", &hex::encode(self.my_public))
+            .field("their_public", &hex::encode(self.their_public))
+            .field("shared_secret", &Redacted::bytes(&self.shared_secret))
+            .finish()
+    }
+}
+
+impl X25519Session {
+    /// Get the established shared secret.
+    ///
+    /// Once a session is established by both parties (after exchanging their
+    /// public keys) this value should be the same in both sessions.
+    #[must_use]
+    pub const fn get_shared_secret(&self) -> &[u8; KEY_LEN] {
+        &self.shared_secret
+    }
+
+    /// Get my public key.
+    #[must_use]
+    pub const fn get_public(&self) -> &[u8; KEY_LEN] {
+        &self.my_public
+    }
+
+    /// Get the other party's public key.
+    #[must_use]
+    pub const fn get_their_public(&self) -> &[u8; KEY_LEN] {
+        &self.their_public
+    }
+
+    /// Clone this session into an unestablish X25519 offer.
+    ///
+    /// Useful if you want to re-establish the session.
+    pub const fn clone_to_offer(&self) -> X25519Offer {
+        X25519Offer {
+            my_private: self.my_private,
+            my_public: self.my_public,
+        }
+    }
+
+    /// Establish some key material from the shared secret using `D` as a digest.
+    ///
+    /// Like for DH (see [`derive_key_material`]), with [`P`] as the modulus.
+    #[must_use]
+    pub fn to_key_material<D: Digest>(&self) -> Vec<u8> {
+        derive_key_material::<D>(&P, &BigUint::from_bytes_le(&self.shared_secret))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{x25519, X25519Offer, BASE_POINT, KEY_LEN};
+    use crate::digest::SHA256;
+
+    fn from_hex(hex: &str) -> [u8; KEY_LEN] {
+        let mut bytes = [0; KEY_LEN];
+        bytes.copy_from_slice(&hex::decode(hex).unwrap());
+        bytes
+    }
+
+    // From RFC 7748, section 5.2
+    #[test]
+    fn test_rfc7748_vectors() {
+        assert_eq!(
+            x25519(
+                &from_hex("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                &from_hex("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c"),
+            ),
+            from_hex("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552")
+        );
+        assert_eq!(
+            x25519(
+                &from_hex("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d"),
+                &from_hex("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493"),
+            ),
+            from_hex("95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957")
+        );
+    }
+
+    // From RFC 7748, section 5.2
+    #[test]
+    fn test_rfc7748_iterated() {
+        let mut k = BASE_POINT;
+        let mut u = BASE_POINT;
+
+        for i in 1..=1000 {
+            let result = x25519(&k, &u);
+            u = k;
+            k = result;
+
+            if i == 1 {
+                assert_eq!(
+                    k,
+                    from_hex("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+                );
+            }
+        }
+
+        assert_eq!(
+            k,
+            from_hex("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+        );
+    }
+
+    // From RFC 7748, section 6.1
+    #[test]
+    fn test_rfc7748_exchange() {
+        let alice_offer = X25519Offer::new_from_private(from_hex(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+        ));
+        let bob_offer = X25519Offer::new_from_private(from_hex(
+            "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+        ));
+
+        assert_eq!(
+            alice_offer.get_public(),
+            &from_hex("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        assert_eq!(
+            bob_offer.get_public(),
+            &from_hex("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+        );
+
+        let alice_session = alice_offer
+            .clone()
+            .establish(bob_offer.get_public())
+            .unwrap();
+        let bob_session = bob_offer.establish(alice_offer.get_public()).unwrap();
+
+        assert_eq!(
+            alice_session.get_shared_secret(),
+            bob_session.get_shared_secret()
+        );
+        assert_eq!(
+            alice_session.to_key_material::<SHA256>(),
+            bob_session.to_key_material::<SHA256>()
+        );
+    }
+
+    #[test]
+    fn test_small_subgroup() {
+        assert!(X25519Offer::new().establish(&[0; KEY_LEN]).is_none());
+    }
+}
@@ -12,6 +12,19 @@
 //!
 //! This is **not** a crypto library _(don't roll your own crypto!)_ but it should
 //! serve as a real-world exercise.
+//!
+//! # Features
+//!
+//! The heavier subsystems can be left out with `default-features = false`:
+//!
+//! - `insecure` _(default)_: intentionally-broken primitives and oracles (see
+//!   [`insecure`]).
+//! - `protocol` _(default)_: protocols built on the primitives (DH handshakes,
+//!   session suites and tickets, zero-knowledge proofs).
+//! - `ec` _(default)_: elliptic curves (see [`ec`]).
+//! - `data` _(default)_: embedded data, like the wordlist in
+//!   [`util::wordlist`].
+//! - `wasm`: browser bindings (see the `wasm` module).
 
 #![feature(step_trait)]
 #![feature(test)]
@@ -34,12 +47,28 @@
 #[cfg(test)]
 extern crate test;
 
+pub mod analysis;
 pub mod block;
 pub mod digest;
 pub mod dsa;
+#[cfg(feature = "ec")]
+pub mod ec;
+pub mod fingerprint;
+pub mod insecure;
 pub mod key_exchange;
 pub mod mac;
+pub mod prelude;
 pub mod rand;
 pub mod rsa;
+pub mod selftest;
 pub mod stream;
+#[doc(hidden)]
+pub mod testkeys;
+#[doc(hidden)]
+pub mod testsupport;
 pub mod util;
+pub mod vrf;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "protocol")]
+pub mod zkp;
@@ -0,0 +1,224 @@
+//! Attacks on bad MAC constructs.
+
+use super::{CbcMac, Mac};
+use crate::block::{pkcs7, BlockCipher};
+use crate::digest::{Digest, ExtensibleDigest, ToyHash};
+use crate::util::iter::Xorable;
+
+/// Forge a [`SuffixMac`](super::SuffixMac) tag over [`ToyHash`] without
+/// knowing the key.
+///
+/// `H(message || key)` only mixes the key in after the whole message has been
+/// processed. Two equal-length messages that reach the same internal state keep
+/// colliding once the key (and the padding, which only depends on the length)
+/// is appended, so a tag for one is a valid tag for the other.
+///
+/// Extends `prefix` with a pair of colliding blocks, asks `tag_oracle` to
+/// authenticate the first message, and returns the second one along with the
+/// (equally valid) tag.
+///
+/// # Panics
+///
+/// If `prefix` is not a whole number of [`ToyHash`] blocks.
+pub fn forge_suffix_mac(
+    prefix: &[u8],
+    tag_oracle: impl FnOnce(&[u8]) -> <ToyHash as Digest>::Output,
+) -> (Vec<u8>, <ToyHash as Digest>::Output) {
+    assert_eq!(prefix.len() % ToyHash::BLOCK_LENGTH, 0);
+
+    let state = ToyHash::new().chain(prefix).state();
+    let (authenticated_block, forged_block, _) = ToyHash::find_block_collision(state);
+
+    let tag = tag_oracle(&[prefix, &authenticated_block].concat());
+
+    ([prefix, &forged_block].concat(), tag)
+}
+
+/// Forge a tag for `message || glue padding || suffix` from the `tag` of
+/// `message`, by [length extension](ExtensibleDigest).
+///
+/// Works against [`PrefixMac`](super::PrefixMac) when `key_len` is right,
+/// since its tag is the whole digest state after hashing
+/// `key || message || padding`. Against [`Hmac`](super::Hmac) or
+/// [`Nmac`](super::nmac::Nmac) the forgery is just a wrong tag: the outer hash
+/// is keyed, so extending the inner one does not help.
+///
+/// Returns the forged message and tag.
+pub fn extend_prefix_mac<D: ExtensibleDigest>(
+    message: &[u8],
+    tag: D::Output,
+    key_len: usize,
+    suffix: &[u8],
+) -> (Vec<u8>, D::Output) {
+    let (digest, glue_padding) = D::extend_digest(tag, key_len + message.len());
+
+    (
+        [message, &glue_padding, suffix].concat(),
+        digest.chain(suffix).finalize(),
+    )
+}
+
+/// Forge a [`CbcMac`] whose IV is chosen by the sender: replace the first
+/// block of `message` with `first_block` and return the new message along
+/// with an IV that keeps the original tag valid.
+///
+/// The first CBC block is `E(iv ^ m_1)`, so flipping bits of `m_1` is undone
+/// by flipping the same bits of the IV.
+///
+/// # Panics
+///
+/// If `message` is shorter than a block, or if `first_block` is not as long
+/// as `iv`.
+#[must_use]
+pub fn forge_cbc_mac_iv(message: &[u8], iv: &[u8], first_block: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let block_size = iv.len();
+
+    assert!(message.len() >= block_size);
+    assert_eq!(first_block.len(), block_size);
+
+    let forged_iv = iv
+        .iter()
+        .xor(&message[..block_size])
+        .xor(first_block)
+        .collect();
+
+    ([first_block, &message[block_size..]].concat(), forged_iv)
+}
+
+/// Splice two [`CbcMac`]-authenticated messages (zero IV, same key) into a
+/// new one: `message || padding || suffix'`, where only the first block of
+/// `suffix` is altered.
+///
+/// After the padded `message`, the CBC state is its `tag`. XORing it into the
+/// first block of `suffix` makes the rest of the chain match that of `suffix`
+/// alone, so `suffix_tag` authenticates the splice.
+///
+/// The forged message has the same tag as `suffix`.
+///
+/// # Panics
+///
+/// If `suffix` is shorter than a block.
+#[must_use]
+pub fn splice_cbc_mac(message: &[u8], tag: &[u8], suffix: &[u8]) -> Vec<u8> {
+    let block_size = tag.len();
+
+    assert!(suffix.len() >= block_size);
+
+    let glued_block = suffix[..block_size].iter().xor(tag).collect::<Vec<_>>();
+
+    [
+        &pkcs7::pad(message, block_size as u8),
+        &glued_block,
+        &suffix[block_size..],
+    ]
+    .concat()
+}
+
+/// Find a message starting with `prefix` (plus padding and a glue block) with
+/// the same [`CbcMac`] as `target`, using the MAC key.
+///
+/// This is [`splice_cbc_mac`] with the attacker computing the tag of `prefix`
+/// themselves.
+///
+/// # Panics
+///
+/// If `target` is shorter than a block.
+#[must_use]
+pub fn collide_cbc_mac<C: BlockCipher>(mac: &CbcMac<C>, prefix: &[u8], target: &[u8]) -> Vec<u8> {
+    splice_cbc_mac(prefix, &mac.tag(prefix), target)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        collide_cbc_mac, extend_prefix_mac, forge_cbc_mac_iv, forge_suffix_mac, splice_cbc_mac,
+    };
+    use crate::block::{Iv, Key, AES128};
+    use crate::digest::{ToyHash, SHA1};
+    use crate::mac::nmac::Nmac;
+    use crate::mac::{CbcMac, Hmac, Mac, PrefixMac, SuffixMac};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const MESSAGE: &[u8] = b"user=alice;role=user";
+    const SUFFIX: &[u8] = b";role=admin";
+
+    fn forges<M: Mac<Tag = [u8; 20]>>(mac: &M) -> bool {
+        let (forged, tag) = extend_prefix_mac::<SHA1>(MESSAGE, mac.tag(MESSAGE), KEY.len(), SUFFIX);
+
+        assert!(forged.starts_with(MESSAGE) && forged.ends_with(SUFFIX));
+
+        mac.verify(&forged, &tag)
+    }
+
+    #[test]
+    fn test_extend_prefix_mac() {
+        assert!(forges(&PrefixMac::<SHA1>::new(KEY)));
+        assert!(!forges(&Hmac::<SHA1>::new(KEY)));
+        assert!(!forges(&Nmac::<SHA1>::new([0x11; 20], [0x22; 20])));
+    }
+
+    #[test]
+    fn test_forge_suffix_mac() {
+        const PREFIX: &[u8] = b"from=alice;to=eve;amount=100000;";
+
+        let mac = SuffixMac::<ToyHash>::new(b"SECRET KEY NOBODY KNOWS");
+
+        let mut queried = Vec::new();
+
+        let (forged, tag) = forge_suffix_mac(PREFIX, |message| {
+            queried = message.to_vec();
+            mac.tag(message)
+        });
+
+        assert!(forged.starts_with(PREFIX));
+        assert_ne!(forged, queried);
+        assert!(mac.verify(&forged, &tag));
+    }
+
+    // Cryptopals challenge 49, with a sender-chosen IV
+    #[test]
+    fn test_forge_cbc_mac_iv() {
+        let mac = CbcMac::new(AES128, &Key::new(KEY).unwrap());
+
+        let message = b"from=1337&to=666&amount=1000000";
+        let iv = Iv::new(&[0x13; 16]).unwrap();
+        let tag = mac.tag_with_iv(message, &iv);
+
+        let (forged, forged_iv) = forge_cbc_mac_iv(message, &iv, b"from=9999&to=666");
+
+        assert_eq!(forged, b"from=9999&to=666&amount=1000000");
+        assert!(mac.verify_with_iv(&forged, &Iv::new(&forged_iv).unwrap(), &tag));
+    }
+
+    // Cryptopals challenge 49, with a fixed IV
+    #[test]
+    fn test_splice_cbc_mac() {
+        let mac = CbcMac::new(AES128, &Key::new(KEY).unwrap());
+
+        let message = b"from=1&tx_list=2:100;3:50";
+        let tag = mac.tag(message);
+
+        let suffix = b";666:1000000;666:1000000";
+        let suffix_tag = mac.tag(suffix);
+
+        let forged = splice_cbc_mac(message, &tag, suffix);
+
+        assert!(forged.starts_with(message));
+        assert!(forged.ends_with(&suffix[16..]));
+        assert!(mac.verify(&forged, &suffix_tag));
+    }
+
+    // Cryptopals challenge 50
+    #[test]
+    fn test_collide_cbc_mac() {
+        const TARGET: &[u8] = b"alert('MZA who was that?');\n";
+
+        let mac = CbcMac::new(AES128, &Key::new(b"YELLOW SUBMARINE").unwrap());
+
+        let forged = collide_cbc_mac(&mac, b"alert('Ayo, the Wu is back!');//", TARGET);
+
+        assert!(forged.starts_with(b"alert('Ayo, the Wu is back!');//"));
+        assert!(forged.ends_with(&TARGET[16..]));
+        assert_eq!(mac.tag(&forged), mac.tag(TARGET));
+    }
+}
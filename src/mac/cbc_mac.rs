@@ -0,0 +1,84 @@
+//! [CBC-MAC](https://en.wikipedia.org/wiki/CBC-MAC): the last block of a CBC
+//! encryption.
+//!
+//! It is only secure for fixed-length messages and a fixed (zero) IV. Letting
+//! the sender choose the IV, or authenticating messages of different
+//! lengths under the same key, allows forgeries (see
+//! [`forge_cbc_mac_iv`](super::attacks::forge_cbc_mac_iv) and
+//! [`splice_cbc_mac`](super::attacks::splice_cbc_mac)), and anybody knowing
+//! the key can [find collisions](super::attacks::collide_cbc_mac), so it is
+//! no hash function either.
+
+use super::{tags_match, Mac};
+use crate::block::{BlockCipher, BlockMode, Iv, Key, CBC};
+
+/// CBC-MAC over any [`BlockCipher`] (with PKCS#7 padding).
+pub struct CbcMac<C: BlockCipher> {
+    cipher: C,
+    key: Key<C>,
+}
+
+impl<C: BlockCipher> CbcMac<C> {
+    /// Create a CBC-MAC with `cipher` under `key`.
+    #[must_use]
+    pub fn new(cipher: C, key: &Key<C>) -> Self {
+        CbcMac {
+            cipher,
+            key: key.clone(),
+        }
+    }
+
+    /// Compute the tag for `message` with a custom `iv` (instead of zero).
+    #[must_use]
+    pub fn tag_with_iv(&self, message: &[u8], iv: &Iv<C>) -> Vec<u8> {
+        let ciphertext = CBC::new(iv).encrypt(&self.cipher, message, &self.key);
+
+        ciphertext[ciphertext.len() - C::BLOCK_SIZE..].to_vec()
+    }
+
+    /// Check whether `tag` authenticates `message` with a custom `iv`, as
+    /// in APIs where the sender picks it.
+    #[must_use]
+    pub fn verify_with_iv(&self, message: &[u8], iv: &Iv<C>, tag: &[u8]) -> bool {
+        tags_match(&self.tag_with_iv(message, iv), tag)
+    }
+}
+
+impl<C: BlockCipher> Mac for CbcMac<C> {
+    type Tag = Vec<u8>;
+
+    fn tag(&self, message: &[u8]) -> Vec<u8> {
+        self.tag_with_iv(message, &Iv::zero())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CbcMac;
+    use crate::block::{Iv, Key, AES128};
+    use crate::mac::Mac;
+
+    // From https://cryptopals.com/sets/7/challenges/50
+    #[test]
+    fn test_cbc_mac() {
+        let mac = CbcMac::new(AES128, &Key::new(b"YELLOW SUBMARINE").unwrap());
+
+        assert_eq!(
+            hex::encode(mac.tag(b"alert('MZA who was that?');\n")),
+            "296b8d7cb78a243dda4d0a61d33bbdd1"
+        );
+    }
+
+    #[test]
+    fn test_cbc_mac_iv() {
+        let mac = CbcMac::new(AES128, &Key::new(b"YELLOW SUBMARINE").unwrap());
+        let iv = Iv::new(&[0x42; 16]).unwrap();
+
+        let tag = mac.tag_with_iv(b"MESSAGE", &iv);
+
+        assert_eq!(tag.len(), 16);
+        assert!(mac.verify_with_iv(b"MESSAGE", &iv, &tag));
+        assert!(!mac.verify(b"MESSAGE", &tag));
+        assert!(mac.verify(b"MESSAGE", &mac.tag_with_iv(b"MESSAGE", &Iv::zero())));
+    }
+}
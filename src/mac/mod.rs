@@ -1,56 +1,262 @@
 //! [Message authentication code](https://en.wikipedia.org/wiki/Message_authentication_code)
 //! implementations and related utilities.
 
+pub mod attacks;
+pub mod cbc_mac;
+pub mod nmac;
+
 use std::iter;
+use std::marker::PhantomData;
+
+pub use cbc_mac::CbcMac;
 
 use crate::digest::Digest;
 use crate::util::iter::Xorable;
 
-/// A very bad MAC implementation that nobody should use.
+/// A keyed message authentication code.
+pub trait Mac {
+    /// Authentication tag.
+    type Tag: AsRef<[u8]>;
+
+    /// Compute the tag for `message`.
+    fn tag(&self, message: &[u8]) -> Self::Tag;
+
+    /// Check whether `tag` authenticates `message`.
+    ///
+    /// The comparison does not short-circuit on the first mismatching byte.
+    fn verify(&self, message: &[u8], tag: &[u8]) -> bool {
+        tags_match(self.tag(message).as_ref(), tag)
+    }
+}
+
+/// Compare tags without short-circuiting on the first mismatching byte.
+fn tags_match(expected: &[u8], tag: &[u8]) -> bool {
+    expected.len() == tag.len()
+        && expected
+            .iter()
+            .zip(tag)
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// A very bad MAC that nobody should use: `H(key || message)`.
 ///
-/// Prefixes the `message` with the provided `key` and hashes it.
-#[must_use]
-pub fn bad_mac<D: Digest>(key: &[u8], message: &[u8]) -> D::Output {
-    <D as Default>::default()
-        .chain(key)
-        .chain(message)
-        .finalize()
+/// Vulnerable to [length-extension attacks](crate::digest::ExtensibleDigest).
+pub struct PrefixMac<D> {
+    key: Vec<u8>,
+    digest: PhantomData<D>,
+}
+
+impl<D: Digest> PrefixMac<D> {
+    #[must_use]
+    pub fn new(key: &[u8]) -> Self {
+        PrefixMac {
+            key: key.to_vec(),
+            digest: PhantomData,
+        }
+    }
+}
+
+impl<D: Digest> Mac for PrefixMac<D> {
+    type Tag = D::Output;
+
+    fn tag(&self, message: &[u8]) -> D::Output {
+        D::digest_parts(&[&self.key, message])
+    }
+}
+
+/// Another bad MAC: `H(message || key)`.
+///
+/// Immune to length extension, but any collision in the underlying digest
+/// (for messages of equal length) is a forgery. See
+/// [`forge_suffix_mac`](attacks::forge_suffix_mac).
+pub struct SuffixMac<D> {
+    key: Vec<u8>,
+    digest: PhantomData<D>,
+}
+
+impl<D: Digest> SuffixMac<D> {
+    #[must_use]
+    pub fn new(key: &[u8]) -> Self {
+        SuffixMac {
+            key: key.to_vec(),
+            digest: PhantomData,
+        }
+    }
+}
+
+impl<D: Digest> Mac for SuffixMac<D> {
+    type Tag = D::Output;
+
+    fn tag(&self, message: &[u8]) -> D::Output {
+        D::digest_parts(&[message, &self.key])
+    }
 }
 
 /// [HMAC](https://en.wikipedia.org/wiki/HMAC) implementation.
+///
+/// See [`nmac`] for why it is built this way.
+///
+/// Besides one-shot [`tag`](Mac::tag)s, messages can be fed incrementally
+/// with [`update`](Hmac::update) and then [`finalize`](Hmac::finalize)d, so
+/// streams don't need to be buffered.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::digest::SHA256;
+/// use rustopals::mac::{hmac, Hmac};
+///
+/// let mut mac = Hmac::<SHA256>::new(b"KEY");
+///
+/// for chunk in [&b"streamed "[..], b"in ", b"chunks"] {
+///     mac.update(chunk);
+/// }
+///
+/// assert_eq!(mac.finalize(), hmac::<SHA256>(b"KEY", b"streamed in chunks"));
+/// ```
+pub struct Hmac<D> {
+    i_key_pad: Vec<u8>,
+    o_key_pad: Vec<u8>,
+    inner: D,
+}
+
+impl<D: Digest> Hmac<D> {
+    #[must_use]
+    pub fn new(key: &[u8]) -> Self {
+        let key = if key.len() > D::BLOCK_LENGTH {
+            D::digest(key).into()
+        } else {
+            key.to_vec()
+        };
+
+        let key = if key.len() < D::BLOCK_LENGTH {
+            let padding_len = D::BLOCK_LENGTH - key.len();
+            [key, vec![0; padding_len]].concat()
+        } else {
+            key
+        };
+
+        let i_key_pad = key.iter().xor(iter::repeat(0x36)).collect::<Vec<_>>();
+
+        Hmac {
+            inner: <D as Default>::default().chain(&i_key_pad),
+            i_key_pad,
+            o_key_pad: key.iter().xor(iter::repeat(0x5c)).collect(),
+        }
+    }
+
+    /// Feed `message` bytes into the incremental tag.
+    pub fn update(&mut self, message: &[u8]) {
+        self.inner.update(message);
+    }
+
+    /// Convenience method to [`update`](Hmac::update) in a chainable fashion.
+    #[must_use]
+    pub fn chain(mut self, message: &[u8]) -> Self {
+        self.update(message);
+        self
+    }
+
+    /// Get the tag of everything fed with [`update`](Hmac::update).
+    pub fn finalize(self) -> D::Output {
+        Self::outer(&self.o_key_pad, &self.inner.finalize())
+    }
+
+    /// Check whether `tag` authenticates everything fed with
+    /// [`update`](Hmac::update).
+    ///
+    /// Like [`Mac::verify`], the comparison does not short-circuit.
+    #[must_use]
+    pub fn finalize_verify(self, tag: &[u8]) -> bool {
+        tags_match(self.finalize().as_ref(), tag)
+    }
+
+    fn outer(o_key_pad: &[u8], inner_hash: &D::Output) -> D::Output {
+        D::digest_parts(&[o_key_pad, inner_hash.as_ref()])
+    }
+}
+
+impl<D: Digest> Mac for Hmac<D> {
+    type Tag = D::Output;
+
+    fn tag(&self, message: &[u8]) -> D::Output {
+        let inner_hash = D::digest_parts(&[&self.i_key_pad, message]);
+
+        Self::outer(&self.o_key_pad, &inner_hash)
+    }
+}
+
+/// [HMAC](https://en.wikipedia.org/wiki/HMAC) implementation. Shorthand for
+/// [`Hmac`].
 #[must_use]
 pub fn hmac<D: Digest>(key: &[u8], message: &[u8]) -> D::Output {
-    let key = if key.len() > D::BLOCK_LENGTH {
-        D::digest(key).as_ref().to_vec()
-    } else {
-        key.to_vec()
-    };
-
-    let key = if key.len() < D::BLOCK_LENGTH {
-        let padding_len = D::BLOCK_LENGTH - key.len();
-        [key, vec![0; padding_len]].concat()
-    } else {
-        key
-    };
-
-    let o_key_pad = key.iter().xor(iter::repeat(0x5c)).collect::<Vec<_>>();
-    let i_key_pad = key.iter().xor(iter::repeat(0x36)).collect::<Vec<_>>();
-
-    let inner_hash = <D as Default>::default()
-        .chain(&i_key_pad)
-        .chain(message)
-        .finalize();
-
-    <D as Default>::default()
-        .chain(&o_key_pad)
-        .chain(inner_hash.as_ref())
-        .finalize()
+    Hmac::<D>::new(key).tag(message)
 }
 
 #[cfg(test)]
 mod test {
-    use super::hmac;
-    use crate::digest::SHA256;
+    use super::{hmac, Hmac, Mac, PrefixMac, SuffixMac};
+    use crate::digest::{Digest, DigestOutput, Hex, SHA1, SHA256};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const MESSAGE: &[u8] = b"comment1=cooking%20MCs;userdata=foo";
+
+    #[test]
+    fn test_prefix_suffix_mac() {
+        assert_eq!(
+            PrefixMac::<SHA1>::new(KEY).tag(MESSAGE),
+            SHA1::digest_parts(&[KEY, MESSAGE])
+        );
+        assert_eq!(
+            SuffixMac::<SHA1>::new(KEY).tag(MESSAGE),
+            SHA1::digest_parts(&[MESSAGE, KEY])
+        );
+    }
+
+    #[test]
+    fn test_hmac_incremental() {
+        let mac = Hmac::<SHA256>::new(KEY);
+
+        for split in [0, 1, 20, MESSAGE.len()] {
+            let (head, tail) = MESSAGE.split_at(split);
+
+            let mut incremental = Hmac::<SHA256>::new(KEY);
+            incremental.update(head);
+            incremental.update(tail);
+
+            assert_eq!(incremental.finalize(), mac.tag(MESSAGE));
+        }
+
+        // One-shot tags don't touch the incremental state
+        let mut incremental = Hmac::<SHA256>::new(KEY).chain(MESSAGE);
+        assert_eq!(incremental.tag(b"other"), mac.tag(b"other"));
+        incremental.update(b"!");
+
+        let tag = mac.tag(&[MESSAGE, b"!"].concat());
+
+        assert!(incremental.finalize_verify(&tag));
+        assert!(!Hmac::<SHA256>::new(KEY)
+            .chain(MESSAGE)
+            .finalize_verify(&tag));
+        assert!(!Hmac::<SHA256>::new(KEY)
+            .chain(MESSAGE)
+            .chain(b"!")
+            .finalize_verify(&tag[..16]));
+    }
+
+    #[test]
+    fn test_verify() {
+        let mac = Hmac::<SHA256>::new(KEY);
+        let mut tag = mac.tag(MESSAGE).to_vec();
+
+        assert!(mac.verify(MESSAGE, &tag));
+        assert!(!mac.verify(b"another message", &tag));
+        assert!(!mac.verify(MESSAGE, &tag[..16]));
+
+        tag[31] ^= 1;
+        assert!(!mac.verify(MESSAGE, &tag));
+    }
 
     // From https://tools.ietf.org/html/rfc4231
     #[test]
@@ -60,13 +266,10 @@ mod test {
 
             const MESSAGE: &[u8] = b"Hi There";
 
-            const EXPECTED: &[u8] = &[
-                0xb0, 0x34, 0x4c, 0x61, 0xd8, 0xdb, 0x38, 0x53, 0x5c, 0xa8, 0xaf, 0xce, 0xaf, 0x0b,
-                0xf1, 0x2b, 0x88, 0x1d, 0xc2, 0x00, 0xc9, 0x83, 0x3d, 0xa7, 0x26, 0xe9, 0x37, 0x6c,
-                0x2e, 0x32, 0xcf, 0xf7,
-            ];
+            const EXPECTED: &str =
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7";
 
-            assert_eq!(hmac::<SHA256>(KEY, MESSAGE), EXPECTED);
+            assert_eq!(hmac::<SHA256>(KEY, MESSAGE).hex().to_string(), EXPECTED);
         }
 
         {
@@ -74,13 +277,10 @@ mod test {
 
             const MESSAGE: &[u8] = b"what do ya want for nothing?";
 
-            const EXPECTED: &[u8] = &[
-                0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
-                0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
-                0x64, 0xec, 0x38, 0x43,
-            ];
+            const EXPECTED: &str =
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
 
-            assert_eq!(hmac::<SHA256>(KEY, MESSAGE), EXPECTED);
+            assert_eq!(hmac::<SHA256>(KEY, MESSAGE).hex().to_string(), EXPECTED);
         }
 
         {
@@ -88,13 +288,10 @@ mod test {
 
             const MESSAGE: &[u8] = &[0xdd; 50];
 
-            const EXPECTED: &[u8] = &[
-                0x77, 0x3e, 0xa9, 0x1e, 0x36, 0x80, 0x0e, 0x46, 0x85, 0x4d, 0xb8, 0xeb, 0xd0, 0x91,
-                0x81, 0xa7, 0x29, 0x59, 0x09, 0x8b, 0x3e, 0xf8, 0xc1, 0x22, 0xd9, 0x63, 0x55, 0x14,
-                0xce, 0xd5, 0x65, 0xfe,
-            ];
+            const EXPECTED: &str =
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe";
 
-            assert_eq!(hmac::<SHA256>(KEY, MESSAGE), EXPECTED);
+            assert_eq!(hmac::<SHA256>(KEY, MESSAGE).hex().to_string(), EXPECTED);
         }
 
         {
@@ -105,13 +302,10 @@ mod test {
 
             const MESSAGE: &[u8] = &[0xcd; 50];
 
-            const EXPECTED: &[u8] = &[
-                0x82, 0x55, 0x8a, 0x38, 0x9a, 0x44, 0x3c, 0x0e, 0xa4, 0xcc, 0x81, 0x98, 0x99, 0xf2,
-                0x08, 0x3a, 0x85, 0xf0, 0xfa, 0xa3, 0xe5, 0x78, 0xf8, 0x07, 0x7a, 0x2e, 0x3f, 0xf4,
-                0x67, 0x29, 0x66, 0x5b,
-            ];
+            const EXPECTED: &str =
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b";
 
-            assert_eq!(hmac::<SHA256>(KEY, MESSAGE), EXPECTED);
+            assert_eq!(hmac::<SHA256>(KEY, MESSAGE).hex().to_string(), EXPECTED);
         }
 
         {
@@ -119,12 +313,12 @@ mod test {
 
             const MESSAGE: &[u8] = b"Test With Truncation";
 
-            const EXPECTED: &[u8] = &[
-                0xa3, 0xb6, 0x16, 0x74, 0x73, 0x10, 0x0e, 0xe0, 0x6e, 0x0c, 0x79, 0x6c, 0x29, 0x55,
-                0x55, 0x2b,
-            ];
+            const EXPECTED: &str = "a3b6167473100ee06e0c796c2955552b";
 
-            assert_eq!(&hmac::<SHA256>(KEY, MESSAGE)[..16], EXPECTED);
+            assert_eq!(
+                Hex(&hmac::<SHA256>(KEY, MESSAGE)[..16]).to_string(),
+                EXPECTED
+            );
         }
 
         {
@@ -132,13 +326,10 @@ mod test {
 
             const MESSAGE: &[u8] = b"Test Using Larger Than Block-Size Key - Hash Key First";
 
-            const EXPECTED: &[u8] = &[
-                0x60, 0xe4, 0x31, 0x59, 0x1e, 0xe0, 0xb6, 0x7f, 0x0d, 0x8a, 0x26, 0xaa, 0xcb, 0xf5,
-                0xb7, 0x7f, 0x8e, 0x0b, 0xc6, 0x21, 0x37, 0x28, 0xc5, 0x14, 0x05, 0x46, 0x04, 0x0f,
-                0x0e, 0xe3, 0x7f, 0x54,
-            ];
+            const EXPECTED: &str =
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54";
 
-            assert_eq!(hmac::<SHA256>(KEY, MESSAGE), EXPECTED);
+            assert_eq!(hmac::<SHA256>(KEY, MESSAGE).hex().to_string(), EXPECTED);
         }
 
         {
@@ -146,13 +337,10 @@ mod test {
 
             const MESSAGE: &[u8] = b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.";
 
-            const EXPECTED: &[u8] = &[
-                0x9b, 0x09, 0xff, 0xa7, 0x1b, 0x94, 0x2f, 0xcb, 0x27, 0x63, 0x5f, 0xbc, 0xd5, 0xb0,
-                0xe9, 0x44, 0xbf, 0xdc, 0x63, 0x64, 0x4f, 0x07, 0x13, 0x93, 0x8a, 0x7f, 0x51, 0x53,
-                0x5c, 0x3a, 0x35, 0xe2,
-            ];
+            const EXPECTED: &str =
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2";
 
-            assert_eq!(hmac::<SHA256>(KEY, MESSAGE), EXPECTED);
+            assert_eq!(hmac::<SHA256>(KEY, MESSAGE).hex().to_string(), EXPECTED);
         }
     }
 }
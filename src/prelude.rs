@@ -0,0 +1,28 @@
+//! The core traits and most-used types, to import at once.
+//!
+//! ```
+//! use rustopals::prelude::*;
+//!
+//! let key = Key::new(b"YELLOW SUBMARINE").unwrap();
+//! let ciphertext = CBC::new(&Iv::zero()).encrypt(&AES128, b"Hello", &key);
+//!
+//! assert_eq!(ciphertext.len(), AES128::BLOCK_SIZE);
+//! assert_eq!(
+//!     SHA1::digest(b"").iter().into_hex(),
+//!     "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+//! );
+//! ```
+//!
+//! The integration tests import these items from here (instead of from their
+//! own modules), so that the prelude stays sufficient for the challenges.
+
+pub use crate::block::{BlockCipher, BlockMode, Iv, Key, Nonce, PKCS7Error, AES128, CBC, ECB};
+pub use crate::digest::{Digest, DigestOutput, ExtensibleDigest, MD4, SHA1, SHA256};
+pub use crate::mac::Mac;
+pub use crate::rsa::{EncrytionPadding, PKCS1v1_5, RSAPrivateKey, RSAPublicKey, SignaturePadding};
+pub use crate::stream::{
+    RepeatingXORCipher, SeekableStreamCipher, SingleXORCipher, StreamCipher, CTR,
+};
+pub use crate::util::clock::Clock;
+pub use crate::util::iter::{Hammingable, Occurrenceable, ToHexable, Xorable};
+pub use crate::util::TextScorer;
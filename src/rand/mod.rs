@@ -0,0 +1,518 @@
+//! [Pseudo-random number generators](https://en.wikipedia.org/wiki/Pseudorandom_number_generator)
+//! and related utilities.
+
+use std::cell::RefCell;
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::util::clock::{Clock, SystemClock};
+
+pub mod seed_table;
+
+/// Environment variable read by [`replayable_seed`].
+pub const SEED_ENV_VAR: &str = "RUSTOPALS_SEED";
+
+thread_local! {
+    /// Deterministic RNG overriding [`rand::thread_rng`] inside [`with_seed`].
+    static SEEDED_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Source of randomness for the whole library.
+///
+/// Draws from [`rand::thread_rng`], unless called inside [`with_seed`] (in the
+/// same thread), in which case it draws from a deterministic seeded RNG.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LibraryRng;
+
+/// Get the [`LibraryRng`]. All library randomness should flow through here.
+#[must_use]
+pub const fn rng() -> LibraryRng {
+    LibraryRng
+}
+
+impl LibraryRng {
+    fn with_current<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+        SEEDED_RNG.with(|seeded| match seeded.borrow_mut().as_mut() {
+            Some(rng) => f(rng),
+            None => f(&mut rand::thread_rng()),
+        })
+    }
+}
+
+impl RngCore for LibraryRng {
+    fn next_u32(&mut self) -> u32 {
+        LibraryRng::with_current(|rng| rng.next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        LibraryRng::with_current(|rng| rng.next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        LibraryRng::with_current(|rng| rng.fill_bytes(dest));
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        LibraryRng::with_current(|rng| rng.try_fill_bytes(dest))
+    }
+}
+
+/// Run `f` with all library randomness (see [`rng`]) deterministically
+/// derived from `seed`, so that randomized code becomes reproducible.
+///
+/// The override is scoped to `f` and to the current thread. Calls can be
+/// nested: the previous RNG is restored on exit (even on panic).
+///
+/// # Example
+///
+/// ```
+/// use rustopals::rand::with_seed;
+/// use rustopals::util::generate_bytes;
+///
+/// let a = with_seed(1337, || generate_bytes(16));
+/// let b = with_seed(1337, || generate_bytes(16));
+/// let c = with_seed(7331, || generate_bytes(16));
+///
+/// assert_eq!(a, b);
+/// assert_ne!(a, c);
+/// ```
+pub fn with_seed<T>(seed: u64, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<StdRng>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+
+            SEEDED_RNG.with(|seeded| *seeded.borrow_mut() = previous);
+        }
+    }
+
+    let _restore =
+        Restore(SEEDED_RNG.with(|seeded| seeded.borrow_mut().replace(StdRng::seed_from_u64(seed))));
+
+    f()
+}
+
+/// If called inside [`with_seed`], draw a seed from the current thread's
+/// seeded RNG, so work moved to another thread can stay deterministic.
+pub(crate) fn fork_seed() -> Option<u64> {
+    SEEDED_RNG.with(|seeded| seeded.borrow_mut().as_mut().map(RngCore::next_u64))
+}
+
+/// Get a seed for [`with_seed`] from the [`SEED_ENV_VAR`] environment
+/// variable, or a random one if unset (or unparseable).
+///
+/// The seed is printed to `stderr` so that failing runs can be replayed.
+#[must_use]
+pub fn replayable_seed() -> u64 {
+    let seed = std::env::var(SEED_ENV_VAR)
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(|| rand::thread_rng().next_u64());
+
+    eprintln!("{SEED_ENV_VAR}={seed}");
+
+    seed
+}
+
+const MERSENNE_TEMPER_MASK_1: u32 = 0x9d2c5680;
+const MERSENNE_TEMPER_MASK_2: u32 = 0xefc60000;
+
+/// Apply MT19937's output tempering to a state word.
+const fn temper(mut y: u32) -> u32 {
+    y ^= y >> 11;
+    y ^= (y << 7) & MERSENNE_TEMPER_MASK_1;
+    y ^= (y << 15) & MERSENNE_TEMPER_MASK_2;
+    y ^= y >> 18;
+
+    y
+}
+
+/// [Mersenne Twister](https://en.wikipedia.org/wiki/Mersenne_Twister) (MT19937) over 32 bits.
+#[derive(Clone)]
+#[must_use]
+pub struct MT19937 {
+    state: [u32; 624],
+    index: usize,
+}
+
+impl MT19937 {
+    pub fn new(seed: u32) -> MT19937 {
+        use byteorder::{ByteOrder, NativeEndian};
+
+        let mut seed_bytes = [0; 4];
+        NativeEndian::write_u32(&mut seed_bytes, seed);
+
+        MT19937::new_from_bytes(seed_bytes)
+    }
+
+    pub fn new_from_bytes(seed: [u8; 4]) -> MT19937 {
+        SeedableRng::from_seed(seed)
+    }
+
+    /// Seed from the current (system) time.
+    pub fn new_unseeded() -> MT19937 {
+        MT19937::new_from_clock(&SystemClock)
+    }
+
+    /// Seed from the current time according to `clock`.
+    pub fn new_from_clock(clock: &impl Clock) -> MT19937 {
+        MT19937::new(clock.unix_time() as u32)
+    }
+
+    pub fn from_tap(tap: &[u32]) -> MT19937 {
+        let mut state = [0_u32; 624];
+
+        for i in 0..624 {
+            let mut y = tap[i];
+
+            y ^= y >> 18;
+            y ^= (y << 15) & MERSENNE_TEMPER_MASK_2;
+            y = {
+                let mut t = y;
+                for _ in 0..5 {
+                    t = y ^ (t << 7) & MERSENNE_TEMPER_MASK_1;
+                }
+                t
+            };
+            y = {
+                let mut t = y;
+                t = y ^ (t >> 11);
+                t = y ^ (t >> 11);
+                t
+            };
+
+            state[i] = y;
+        }
+
+        MT19937 { state, index: 0 }
+    }
+
+    /// Get the first output of `MT19937::new(seed)`.
+    ///
+    /// Much faster than seeding a whole generator: the first output only
+    /// depends on the first 398 words of the initial state, so the rest are
+    /// never computed.
+    #[must_use]
+    pub fn first_output(seed: u32) -> u32 {
+        let mut state = [0_u32; 398];
+
+        state[0] = seed;
+        for i in 1..398 {
+            state[i] = (0x6c078965_u32)
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+
+        let y = (state[0] & 0x80000000) + (state[1] & 0x7fffffff);
+        let mut first = state[397] ^ (y >> 1);
+
+        if (y % 2) != 0 {
+            first ^= 0x9908b0df;
+        }
+
+        temper(first)
+    }
+
+    fn initial_state(seed: [u8; 4]) -> [u32; 624] {
+        use byteorder::{ByteOrder, NativeEndian};
+
+        let seed = NativeEndian::read_u32(&seed);
+
+        let mut state = [0_u32; 624];
+
+        state[0] = seed;
+        for i in 1..624 {
+            state[i] = (0x6c078965_u32)
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+
+        state
+    }
+
+    fn generate_numbers(&mut self) {
+        for i in 0..624 {
+            let y = (self.state[i] & 0x80000000) + (self.state[(i + 1) % 624] & 0x7fffffff);
+
+            self.state[i] = self.state[(i + 397) % 624] ^ (y >> 1);
+
+            if !y.is_multiple_of(2) {
+                self.state[i] ^= 0x9908b0df;
+            }
+        }
+    }
+}
+
+impl RngCore for MT19937 {
+    fn next_u32(&mut self) -> u32 {
+        if self.index == 0 {
+            self.generate_numbers();
+        }
+
+        let y = temper(self.state[self.index]);
+
+        self.index = (self.index + 1) % 624;
+
+        y
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        rand_core::impls::fill_bytes_via_next(self, dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for MT19937 {
+    type Seed = [u8; 4];
+
+    fn from_seed(seed: [u8; 4]) -> MT19937 {
+        MT19937 {
+            state: MT19937::initial_state(seed),
+            index: 0,
+        }
+    }
+}
+
+const MERSENNE_64_TEMPER_MASK_D: u64 = 0x5555555555555555;
+const MERSENNE_64_TEMPER_MASK_1: u64 = 0x71d67fffeda60000;
+const MERSENNE_64_TEMPER_MASK_2: u64 = 0xfff7eee000000000;
+
+/// Apply MT19937-64's output tempering to a state word.
+const fn temper_64(mut y: u64) -> u64 {
+    y ^= (y >> 29) & MERSENNE_64_TEMPER_MASK_D;
+    y ^= (y << 17) & MERSENNE_64_TEMPER_MASK_1;
+    y ^= (y << 37) & MERSENNE_64_TEMPER_MASK_2;
+    y ^= y >> 43;
+
+    y
+}
+
+/// [Mersenne Twister](https://en.wikipedia.org/wiki/Mersenne_Twister) (MT19937-64) over 64 bits.
+#[allow(non_camel_case_types)]
+#[derive(Clone)]
+#[must_use]
+pub struct MT19937_64 {
+    state: [u64; 312],
+    index: usize,
+}
+
+impl MT19937_64 {
+    pub fn new(seed: u64) -> MT19937_64 {
+        use byteorder::{ByteOrder, NativeEndian};
+
+        let mut seed_bytes = [0; 8];
+        NativeEndian::write_u64(&mut seed_bytes, seed);
+
+        MT19937_64::new_from_bytes(seed_bytes)
+    }
+
+    pub fn new_from_bytes(seed: [u8; 8]) -> MT19937_64 {
+        SeedableRng::from_seed(seed)
+    }
+
+    pub fn from_tap(tap: &[u64]) -> MT19937_64 {
+        let mut state = [0_u64; 312];
+
+        for i in 0..312 {
+            let mut y = tap[i];
+
+            y ^= y >> 43;
+            y ^= (y << 37) & MERSENNE_64_TEMPER_MASK_2;
+            y = {
+                let mut t = y;
+                for _ in 0..4 {
+                    t = y ^ (t << 17) & MERSENNE_64_TEMPER_MASK_1;
+                }
+                t
+            };
+            y = {
+                let mut t = y;
+                for _ in 0..3 {
+                    t = y ^ (t >> 29) & MERSENNE_64_TEMPER_MASK_D;
+                }
+                t
+            };
+
+            state[i] = y;
+        }
+
+        MT19937_64 { state, index: 0 }
+    }
+
+    fn initial_state(seed: [u8; 8]) -> [u64; 312] {
+        use byteorder::{ByteOrder, NativeEndian};
+
+        let seed = NativeEndian::read_u64(&seed);
+
+        let mut state = [0_u64; 312];
+
+        state[0] = seed;
+        for i in 1..312 {
+            state[i] = (0x5851f42d4c957f2d_u64)
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 62))
+                .wrapping_add(i as u64);
+        }
+
+        state
+    }
+
+    fn generate_numbers(&mut self) {
+        for i in 0..312 {
+            let y = (self.state[i] & 0xffffffff80000000) + (self.state[(i + 1) % 312] & 0x7fffffff);
+
+            self.state[i] = self.state[(i + 156) % 312] ^ (y >> 1);
+
+            if !y.is_multiple_of(2) {
+                self.state[i] ^= 0xb5026f5aa96619e9;
+            }
+        }
+    }
+}
+
+impl RngCore for MT19937_64 {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.index == 0 {
+            self.generate_numbers();
+        }
+
+        let y = temper_64(self.state[self.index]);
+
+        self.index = (self.index + 1) % 312;
+
+        y
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        rand_core::impls::fill_bytes_via_next(self, dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for MT19937_64 {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: [u8; 8]) -> MT19937_64 {
+        MT19937_64 {
+            state: MT19937_64::initial_state(seed),
+            index: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::panic;
+
+    use rand::RngCore;
+
+    use super::{rng, with_seed, MT19937, MT19937_64};
+    use crate::util::clock::MockClock;
+
+    #[test]
+    fn test_with_seed_is_deterministic() {
+        let a = with_seed(1337, || (rng().next_u64(), rng().next_u64()));
+        let b = with_seed(1337, || (rng().next_u64(), rng().next_u64()));
+        let c = with_seed(7331, || (rng().next_u64(), rng().next_u64()));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a.0, a.1);
+    }
+
+    #[test]
+    fn test_with_seed_nested() {
+        let expected_outer = with_seed(1, || [rng().next_u32(), rng().next_u32()]);
+        let expected_inner = with_seed(2, || rng().next_u32());
+
+        let (outer, inner) = with_seed(1, || {
+            let first = rng().next_u32();
+            let inner = with_seed(2, || rng().next_u32());
+            let second = rng().next_u32();
+
+            ([first, second], inner)
+        });
+
+        assert_eq!(outer, expected_outer);
+        assert_eq!(inner, expected_inner);
+    }
+
+    #[test]
+    fn test_with_seed_restores_on_panic() {
+        let expected = with_seed(1, || [rng().next_u32(), rng().next_u32()]);
+
+        let actual = with_seed(1, || {
+            let first = rng().next_u32();
+
+            panic::catch_unwind(|| with_seed(2, || panic!("Inner panic"))).unwrap_err();
+
+            [first, rng().next_u32()]
+        });
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_mt19937_first_output() {
+        for seed in [0, 1, 5489, 1_600_000_000, u32::MAX] {
+            assert_eq!(MT19937::first_output(seed), MT19937::new(seed).next_u32());
+        }
+    }
+
+    #[test]
+    fn test_mt19937_new_from_clock() {
+        let clock = MockClock::new(1_600_000_000);
+
+        assert_eq!(
+            MT19937::new_from_clock(&clock).next_u32(),
+            MT19937::new(1_600_000_000).next_u32()
+        );
+    }
+
+    #[test]
+    fn test_mt19937_64() {
+        // Reference `init_genrand64(5489)` outputs
+        let mut rng = MT19937_64::new(5489);
+
+        assert_eq!(rng.next_u64(), 14514284786278117030);
+        assert_eq!(rng.next_u64(), 4620546740167642908);
+
+        // C++'s `std::mt19937_64` is required to yield this as its 10000th output
+        let mut rng = MT19937_64::new(5489);
+
+        for _ in 0..9999 {
+            rng.next_u64();
+        }
+
+        assert_eq!(rng.next_u64(), 9981545732273789042);
+    }
+
+    #[test]
+    fn test_mt19937_64_from_tap() {
+        let mut rng = MT19937_64::new(1337);
+        let tap = (0..312).map(|_| rng.next_u64()).collect::<Vec<_>>();
+
+        let mut clone = MT19937_64::from_tap(&tap);
+
+        for _ in 0..1000 {
+            assert_eq!(clone.next_u64(), rng.next_u64());
+        }
+    }
+}
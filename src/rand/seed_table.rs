@@ -0,0 +1,398 @@
+//! Precomputed [`MT19937`] first output → seed tables, to recover
+//! timestamp-like seeds (as in challenge 22) with a lookup instead of a scan.
+//!
+//! A [`SeedTable`] covers a range of seeds (e.g. every timestamp in a year).
+//! Entries are grouped in buckets by the top bits of their first output, so a
+//! lookup only checks the handful of seeds in one bucket: O(1) on average.
+//!
+//! Two [`SeedTableParams`] trade size for lookup work:
+//!
+//! - `bucket_bits`: more buckets mean fewer seeds per bucket, but a bigger
+//!   bucket index (4 bytes per bucket).
+//! - `tag_bytes`: every entry can store some low bits of its output, so that
+//!   most candidates in a bucket are discarded without regenerating their
+//!   output (which is comparatively slow). Each tag byte costs a byte per seed.
+//!
+//! Building the table is dominated by generating every output, which is done
+//! in parallel.
+//!
+//! # Example
+//!
+//! ```
+//! use rand::Rng;
+//! use rustopals::rand::seed_table::{SeedTable, SeedTableParams};
+//! use rustopals::rand::MT19937;
+//!
+//! let table = SeedTable::build(1_600_000_000..1_600_010_000, SeedTableParams::default());
+//!
+//! let output: u32 = MT19937::new(1_600_001_337).gen();
+//!
+//! assert_eq!(table.lookup(output), vec![1_600_001_337]);
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Range;
+
+use super::MT19937;
+use crate::util::pool::ThreadPool;
+
+/// Magic bytes at the start of serialized tables.
+pub const MAGIC: &[u8; 4] = b"MTST";
+
+/// Trade-off knobs for [`SeedTable`]s (see the [module docs](self)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SeedTableParams {
+    bucket_bits: u8,
+    tag_bytes: u8,
+}
+
+impl SeedTableParams {
+    /// Maximum `bucket_bits`.
+    pub const MAX_BUCKET_BITS: u8 = 24;
+
+    /// Maximum `tag_bytes`.
+    pub const MAX_TAG_BYTES: u8 = 2;
+
+    /// Create new parameters.
+    ///
+    /// Returns `None` if `bucket_bits` is not in `1..=MAX_BUCKET_BITS` or
+    /// `tag_bytes` is greater than `MAX_TAG_BYTES`.
+    #[must_use]
+    pub const fn new(bucket_bits: u8, tag_bytes: u8) -> Option<SeedTableParams> {
+        if bucket_bits == 0
+            || bucket_bits > SeedTableParams::MAX_BUCKET_BITS
+            || tag_bytes > SeedTableParams::MAX_TAG_BYTES
+        {
+            return None;
+        }
+
+        Some(SeedTableParams {
+            bucket_bits,
+            tag_bytes,
+        })
+    }
+
+    /// Get the number of output bits used to pick a bucket.
+    #[must_use]
+    pub const fn bucket_bits(&self) -> u8 {
+        self.bucket_bits
+    }
+
+    /// Get the number of output bytes stored along each seed.
+    #[must_use]
+    pub const fn tag_bytes(&self) -> u8 {
+        self.tag_bytes
+    }
+
+    const fn bucket(self, output: u32) -> usize {
+        (output >> (32 - self.bucket_bits)) as usize
+    }
+
+    const fn buckets(self) -> usize {
+        1 << self.bucket_bits
+    }
+}
+
+impl Default for SeedTableParams {
+    /// 2^16 buckets and one tag byte: a good fit for about a day's worth of
+    /// seeds up to a few years' worth.
+    fn default() -> SeedTableParams {
+        SeedTableParams {
+            bucket_bits: 16,
+            tag_bytes: 1,
+        }
+    }
+}
+
+/// A precomputed [`MT19937`] first output → seed table.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SeedTable {
+    seeds: Range<u32>,
+    params: SeedTableParams,
+    /// Entries in bucket `i` are at `offsets[i]..offsets[i + 1]`.
+    offsets: Vec<u32>,
+    /// Seeds (relative to `seeds.start`) grouped by bucket.
+    entries: Vec<u32>,
+    /// `params.tag_bytes` bytes per entry.
+    tags: Vec<u8>,
+}
+
+impl SeedTable {
+    /// Build a table for every seed in `seeds`.
+    #[must_use]
+    pub fn build(seeds: Range<u32>, params: SeedTableParams) -> SeedTable {
+        let pool = ThreadPool::available();
+
+        let len = seeds.end.saturating_sub(seeds.start);
+        let chunk_size = (len / pool.workers() as u32).max(1);
+
+        let chunks = seeds
+            .clone()
+            .step_by(chunk_size as usize)
+            .map(|start| start..seeds.end.min(start.saturating_add(chunk_size)))
+            .collect::<Vec<_>>();
+
+        let outputs = pool
+            .parallel_map(&chunks, |chunk| {
+                chunk.clone().map(MT19937::first_output).collect::<Vec<_>>()
+            })
+            .concat();
+
+        // Counting sort by bucket (stable, so seeds stay sorted in buckets)
+        let mut offsets = vec![0_u32; params.buckets() + 1];
+
+        for &output in &outputs {
+            offsets[params.bucket(output) + 1] += 1;
+        }
+
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+
+        let tag_bytes = usize::from(params.tag_bytes);
+
+        let mut cursors = offsets.clone();
+        let mut entries = vec![0; outputs.len()];
+        let mut tags = vec![0; outputs.len() * tag_bytes];
+
+        for (i, &output) in (0_u32..).zip(&outputs) {
+            let cursor = &mut cursors[params.bucket(output)];
+            let position = *cursor as usize;
+
+            entries[position] = i;
+            tags[position * tag_bytes..(position + 1) * tag_bytes]
+                .copy_from_slice(&tag(output)[..tag_bytes]);
+
+            *cursor += 1;
+        }
+
+        SeedTable {
+            seeds,
+            params,
+            offsets,
+            entries,
+            tags,
+        }
+    }
+
+    /// Get the range of seeds covered by this table.
+    #[must_use]
+    pub fn seeds(&self) -> Range<u32> {
+        self.seeds.clone()
+    }
+
+    /// Get the parameters this table was built with.
+    #[must_use]
+    pub const fn params(&self) -> SeedTableParams {
+        self.params
+    }
+
+    /// Get every seed in the table whose first output is `output`, in
+    /// ascending order.
+    ///
+    /// There's usually zero or one, but different seeds can collide.
+    #[must_use]
+    pub fn lookup(&self, output: u32) -> Vec<u32> {
+        let bucket = self.params.bucket(output);
+        let tag_bytes = usize::from(self.params.tag_bytes);
+        let output_tag = &tag(output)[..tag_bytes];
+
+        (self.offsets[bucket] as usize..self.offsets[bucket + 1] as usize)
+            .filter(|&i| &self.tags[i * tag_bytes..(i + 1) * tag_bytes] == output_tag)
+            .map(|i| self.seeds.start + self.entries[i])
+            .filter(|&seed| MT19937::first_output(seed) == output)
+            .collect()
+    }
+
+    /// Serialize the table.
+    ///
+    /// Seeds are stored with as few bytes as the range needs (e.g. 4 for a
+    /// year of timestamps, 3 for a month).
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let seed_bytes = seed_bytes(&self.seeds);
+
+        let mut bytes = Vec::with_capacity(
+            MAGIC.len()
+                + 10
+                + 4 * self.offsets.len()
+                + seed_bytes * self.entries.len()
+                + self.tags.len(),
+        );
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.seeds.start.to_be_bytes());
+        bytes.extend_from_slice(&self.seeds.end.to_be_bytes());
+        bytes.push(self.params.bucket_bits);
+        bytes.push(self.params.tag_bytes);
+
+        for offset in &self.offsets {
+            bytes.extend_from_slice(&offset.to_be_bytes());
+        }
+
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.to_be_bytes()[4 - seed_bytes..]);
+        }
+
+        bytes.extend_from_slice(&self.tags);
+
+        bytes
+    }
+
+    /// Deserialize a table serialized with [`SeedTable::to_bytes`].
+    ///
+    /// Returns `None` if `bytes` is not a valid table.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Option<SeedTable> {
+        let mut reader = Reader(bytes);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return None;
+        }
+
+        let seeds = reader.u32()?..reader.u32()?;
+        let params = SeedTableParams::new(reader.take(1)?[0], reader.take(1)?[0])?;
+
+        let offsets = (0..=params.buckets())
+            .map(|_| reader.u32())
+            .collect::<Option<Vec<_>>>()?;
+
+        let len = seeds.end.saturating_sub(seeds.start);
+
+        if offsets[0] != 0
+            || offsets.last() != Some(&len)
+            || offsets.windows(2).any(|pair| pair[0] > pair[1])
+        {
+            return None;
+        }
+
+        let seed_bytes = seed_bytes(&seeds);
+
+        let entries = (0..len)
+            .map(|_| {
+                let mut entry = [0; 4];
+                entry[4 - seed_bytes..].copy_from_slice(reader.take(seed_bytes)?);
+
+                Some(u32::from_be_bytes(entry)).filter(|&entry| entry < len)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let tags = reader
+            .take(usize::try_from(len).ok()? * usize::from(params.tag_bytes))?
+            .to_vec();
+
+        if !reader.0.is_empty() {
+            return None;
+        }
+
+        Some(SeedTable {
+            seeds,
+            params,
+            offsets,
+            entries,
+            tags,
+        })
+    }
+}
+
+impl fmt::Debug for SeedTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeedTable")
+            .field("seeds", &self.seeds)
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Get up to [`SeedTableParams::MAX_TAG_BYTES`] bytes to tag `output` with.
+const fn tag(output: u32) -> [u8; SeedTableParams::MAX_TAG_BYTES as usize] {
+    let [.., high, low] = output.to_be_bytes();
+
+    [low, high]
+}
+
+/// Bytes needed to store any seed in `seeds` (relative to its start).
+fn seed_bytes(seeds: &Range<u32>) -> usize {
+    let max = seeds.end.saturating_sub(seeds.start).saturating_sub(1);
+
+    (4 - max.leading_zeros() as usize / 8).max(1)
+}
+
+/// Reads a serialized table.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    const fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+
+        Some(u32::from_be_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SeedTable, SeedTableParams};
+    use crate::rand::MT19937;
+
+    const SEEDS: std::ops::Range<u32> = 1_600_000_000..1_600_020_000;
+
+    #[test]
+    fn test_lookup() {
+        for &(bucket_bits, tag_bytes) in &[(1, 0), (8, 1), (16, 2), (24, 0)] {
+            let params = SeedTableParams::new(bucket_bits, tag_bytes).unwrap();
+            let table = SeedTable::build(SEEDS, params);
+
+            for seed in SEEDS.step_by(997) {
+                assert!(table.lookup(MT19937::first_output(seed)).contains(&seed));
+            }
+
+            assert_eq!(table.lookup(MT19937::first_output(SEEDS.start - 1)), vec![]);
+            assert_eq!(table.lookup(MT19937::first_output(SEEDS.end)), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let table = SeedTable::build(SEEDS, SeedTableParams::default());
+        let bytes = table.to_bytes();
+
+        // Magic, range, params, 2^16 + 1 offsets and 2 + 1 bytes per seed
+        assert_eq!(bytes.len(), 4 + 8 + 2 + 4 * 65537 + 3 * 20_000);
+        assert_eq!(SeedTable::from_bytes(&bytes), Some(table));
+
+        assert_eq!(SeedTable::from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(
+            SeedTable::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+            None
+        );
+        assert_eq!(SeedTable::from_bytes(b"MTSX"), None);
+    }
+
+    #[test]
+    fn test_empty() {
+        let table = SeedTable::build(10..10, SeedTableParams::default());
+
+        assert_eq!(table.lookup(MT19937::first_output(10)), vec![]);
+        assert_eq!(SeedTable::from_bytes(&table.to_bytes()), Some(table));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert_eq!(SeedTableParams::new(0, 1), None);
+        assert_eq!(SeedTableParams::new(25, 1), None);
+        assert_eq!(SeedTableParams::new(16, 3), None);
+    }
+}
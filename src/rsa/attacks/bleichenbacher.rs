@@ -0,0 +1,665 @@
+//! Building blocks for [Bleichenbacher's
+//! attack](http://archiv.infsec.ethz.ch/education/fs08/secsem/bleichenbacher98.pdf)
+//! on PKCS#1 v1.5 encryption padding.
+//!
+//! See [Cryptopals challenges 47](https://cryptopals.com/sets/6/challenges/47)
+//! and [48](https://cryptopals.com/sets/6/challenges/48).
+//!
+//! Every padding oracle call is an RSA decryption, so the attack time is
+//! mostly spent in its step 2 searches for a PKCS-conforming `s`.
+//! [`find_conforming_s`] splits them between all available cores, and
+//! [`Strategy::Bardou`] needs far fewer of them (see [Bardou et al.,
+//! "Efficient Padding Oracle Attacks on Cryptographic
+//! Hardware"](https://hal.inria.fr/hal-00691958/document)).
+
+use std::cmp::{max, min};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_iter::range_inclusive;
+use num_traits::{One, ToPrimitive};
+
+use crate::rsa::RSAPublicKey;
+use crate::util::inv_mod;
+use crate::util::pool::ThreadPool;
+
+/// Amount of `s` candidates each worker scans between checkpoints.
+const BATCH_SIZE_PER_WORKER: usize = 256;
+
+/// Amount of denominators `t` tried when looking for trimmers.
+const TRIMMER_DENOMINATORS: u32 = 250;
+
+/// Largest combined trimmer denominator.
+const MAX_TRIMMER_LCM: u32 = 1 << 12;
+
+/// Count of padding oracle calls, optionally limited.
+///
+/// Shared between all the scanning workers (and searches) of an attack.
+#[derive(Debug, Default)]
+pub struct OracleBudget {
+    calls: AtomicU64,
+    limit: Option<u64>,
+}
+
+impl OracleBudget {
+    /// A budget allowing at most `limit` oracle calls.
+    #[must_use]
+    pub const fn new(limit: u64) -> OracleBudget {
+        OracleBudget {
+            calls: AtomicU64::new(0),
+            limit: Some(limit),
+        }
+    }
+
+    /// A budget only counting oracle calls.
+    #[must_use]
+    pub const fn unlimited() -> OracleBudget {
+        OracleBudget {
+            calls: AtomicU64::new(0),
+            limit: None,
+        }
+    }
+
+    /// Amount of oracle calls made so far.
+    #[must_use]
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Whether the limit has been reached.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.limit.is_some_and(|limit| self.calls() >= limit)
+    }
+
+    /// Account for an oracle call, unless that would exceed the limit.
+    fn spend(&self) -> bool {
+        let previous = self.calls.fetch_add(1, Ordering::Relaxed);
+
+        if self.limit.is_some_and(|limit| previous >= limit) {
+            self.calls.fetch_sub(1, Ordering::Relaxed);
+
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Outcome of [`find_conforming_s`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScanOutcome {
+    /// A PKCS-conforming `s` has been found.
+    Found(BigUint),
+
+    /// The whole range was scanned without success.
+    Exhausted,
+
+    /// The oracle call budget ran out before finding any `s`.
+    OutOfBudget,
+}
+
+/// Find the first `s` in `start..end` for which the padding `oracle`
+/// succeeds.
+///
+/// If `end` is `None`, the scan goes on until found (or out of `budget`).
+///
+/// Candidates are scanned in batches, interleaved between all available
+/// cores. Workers stop as soon as some smaller candidate succeeds, so the
+/// result is the same as a serial scan (unless the budget runs out
+/// mid-batch, in which case any conforming `s` found is returned).
+pub fn find_conforming_s(
+    start: &BigUint,
+    end: Option<&BigUint>,
+    oracle: impl Fn(&BigUint) -> bool + Sync,
+    budget: &OracleBudget,
+) -> ScanOutcome {
+    scan(start, end, &oracle, budget, ThreadPool::available())
+}
+
+/// [`find_conforming_s`] with some worker `pool`.
+fn scan(
+    start: &BigUint,
+    end: Option<&BigUint>,
+    oracle: &(impl Fn(&BigUint) -> bool + Sync),
+    budget: &OracleBudget,
+    pool: ThreadPool,
+) -> ScanOutcome {
+    let batch_size = BATCH_SIZE_PER_WORKER * pool.workers();
+
+    let mut batch_start = start.clone();
+
+    loop {
+        let batch_len = match end {
+            Some(end) if &batch_start >= end => return ScanOutcome::Exhausted,
+            Some(end) => (end - &batch_start)
+                .to_usize()
+                .map_or(batch_size, |left| left.min(batch_size)),
+            None => batch_size,
+        };
+
+        if let Some(offset) = scan_batch(&batch_start, batch_len, pool, oracle, budget) {
+            return ScanOutcome::Found(batch_start + offset);
+        }
+
+        if budget.is_exhausted() {
+            return ScanOutcome::OutOfBudget;
+        }
+
+        batch_start += batch_len;
+    }
+}
+
+/// Scan `batch_len` candidates from `batch_start` in parallel, returning the
+/// smallest offset for which `oracle` succeeds.
+fn scan_batch(
+    batch_start: &BigUint,
+    batch_len: usize,
+    pool: ThreadPool,
+    oracle: &(impl Fn(&BigUint) -> bool + Sync),
+    budget: &OracleBudget,
+) -> Option<usize> {
+    let first_found = AtomicUsize::new(usize::MAX);
+    let out_of_budget = AtomicBool::new(false);
+
+    let workers = pool.workers();
+
+    pool.scope(|scope| {
+        for worker in 0..workers.min(batch_len) {
+            let first_found = &first_found;
+            let out_of_budget = &out_of_budget;
+
+            scope.spawn(move || {
+                for offset in (worker..batch_len).step_by(workers) {
+                    if offset >= first_found.load(Ordering::Relaxed)
+                        || out_of_budget.load(Ordering::Relaxed)
+                    {
+                        return;
+                    }
+
+                    if !budget.spend() {
+                        out_of_budget.store(true, Ordering::Relaxed);
+
+                        return;
+                    }
+
+                    if oracle(&(batch_start + offset)) {
+                        first_found.fetch_min(offset, Ordering::Relaxed);
+
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    match first_found.into_inner() {
+        usize::MAX => None,
+        offset => Some(offset),
+    }
+}
+
+/// Which optimizations to use in [`attack`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Bleichenbacher's original attack, scanning for `s` serially.
+    Original,
+
+    /// The original attack, scanning for `s` in parallel. Needs the same
+    /// oracle calls, but takes less time.
+    Parallel,
+
+    /// Bardou et al.'s improvements, scanning for `s` in parallel:
+    ///
+    /// - Trimming the initial interval, by finding small fractions `u / t`
+    ///   such that `m * u / t` is PKCS-conforming too.
+    /// - Skipping the holes in step 2.a, i.e. the values of `s` for which
+    ///   `m * s` cannot be PKCS-conforming.
+    Bardou,
+}
+
+/// Result of a successful [`attack`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AttackReport {
+    /// The decrypted message (still padded, if the ciphertext was
+    /// PKCS-conforming).
+    pub message: BigUint,
+
+    /// Total oracle calls.
+    pub oracle_calls: u64,
+
+    /// Oracle calls spent trimming the initial interval.
+    pub trimming_calls: u64,
+
+    /// Iterations of steps 2 to 4.
+    pub iterations: usize,
+}
+
+/// State of an [`attack`] after each iteration.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress<'a> {
+    /// Iterations of steps 2 to 4 so far.
+    pub iteration: usize,
+
+    /// The last PKCS-conforming `s` found.
+    pub s: &'a BigUint,
+
+    /// Intervals that may contain the (blinded) message.
+    pub intervals: &'a [(BigUint, BigUint)],
+
+    /// Oracle calls so far.
+    pub oracle_calls: u64,
+}
+
+impl Progress<'_> {
+    /// Amount of bits still unknown, i.e. the logarithm of the amount of
+    /// candidates left.
+    #[must_use]
+    pub fn remaining_bits(&self) -> u64 {
+        self.intervals
+            .iter()
+            .map(|(a, b)| b - a + 1_u32)
+            .sum::<BigUint>()
+            .bits()
+            .saturating_sub(1)
+    }
+}
+
+/// Decrypt a `ciphertext` with a padding `oracle`, telling whether some
+/// ciphertext decrypts to a PKCS-conforming message.
+///
+/// If `ciphertext` is not PKCS-conforming itself, it is blinded first (step
+/// 1), so any RSA ciphertext (or signature to forge) can be attacked.
+/// `progress` is called after each iteration.
+///
+/// Returns `None` if `budget` runs out (or if the oracle is inconsistent).
+pub fn attack(
+    public_key: &RSAPublicKey,
+    ciphertext: &BigUint,
+    oracle: impl Fn(&BigUint) -> bool + Sync,
+    strategy: Strategy,
+    budget: &OracleBudget,
+    mut progress: impl FnMut(&Progress),
+) -> Option<AttackReport> {
+    let RSAPublicKey { e, n } = public_key;
+
+    let initial_calls = budget.calls();
+    let pool = match strategy {
+        Strategy::Original => ThreadPool::new(1),
+        Strategy::Parallel | Strategy::Bardou => ThreadPool::available(),
+    };
+
+    let upper_b = BigUint::one() << (8 * (public_key.len_bytes() - 2));
+    let two_b = &upper_b * 2_u32;
+    let three_b = &upper_b * 3_u32;
+
+    // Step 1: Blinding, i.e. finding a PKCS-conforming `m * s_0` (trying
+    // `s_0 = 1` first)
+    let s_0 = match scan(
+        &BigUint::one(),
+        None,
+        &|s: &BigUint| oracle(&(ciphertext * s.modpow(e, n) % n)),
+        budget,
+        pool,
+    ) {
+        ScanOutcome::Found(s_0) => s_0,
+        ScanOutcome::Exhausted | ScanOutcome::OutOfBudget => return None,
+    };
+
+    let blinded = ciphertext * s_0.modpow(e, n) % n;
+    let blinding_calls = budget.calls() - initial_calls;
+
+    // Whether `m * s_0 * s` is PKCS-conforming
+    let oracle = |s: &BigUint| oracle(&(&blinded * s.modpow(e, n) % n));
+
+    let find =
+        |start: &BigUint, end: Option<&BigUint>| match scan(start, end, &oracle, budget, pool) {
+            ScanOutcome::Found(s) => Some(s),
+            ScanOutcome::Exhausted | ScanOutcome::OutOfBudget => None,
+        };
+
+    let (a, b) = match strategy {
+        Strategy::Original | Strategy::Parallel => (two_b.clone(), &three_b - 1_u32),
+        Strategy::Bardou => trim(n, &two_b, &three_b, &oracle, budget)?,
+    };
+
+    let trimming_calls = budget.calls() - initial_calls - blinding_calls;
+
+    let mut s_prev = BigUint::one();
+    let mut m_prev = vec![(a, b)];
+
+    for iterations in 1.. {
+        // Step 2: Searching for PKCS conforming messages.
+        let s_i = if iterations == 1 {
+            // Step 2.a: Starting the search.
+            match strategy {
+                Strategy::Original | Strategy::Parallel => find(&n.div_ceil(&three_b), None)?,
+                Strategy::Bardou => {
+                    let (a, b) = &m_prev[0];
+                    let mut scanned = BigUint::one();
+
+                    range_inclusive(BigUint::one(), n.clone())
+                        .take_while(|_| !budget.is_exhausted())
+                        .find_map(|ref r| {
+                            let s_start = max((&two_b + r * n).div_ceil(b), scanned.clone());
+                            let s_end = (&three_b + r * n).div_ceil(a);
+
+                            scanned = max(scanned.clone(), s_end.clone());
+
+                            find(&s_start, Some(&s_end))
+                        })?
+                },
+            }
+        } else if m_prev.len() >= 2 {
+            // Step 2.b: Searching with more than one interval left.
+            find(&(&s_prev + 1_u32), None)?
+        } else {
+            // Step 2.c: Searching with one interval left.
+            let (a, b) = &m_prev[0];
+            let r_start = (b * &s_prev - &two_b).div_ceil(n) * 2_u32;
+
+            range_inclusive(r_start, n.clone())
+                .take_while(|_| !budget.is_exhausted())
+                .find_map(|ref r| {
+                    let s_start = (&two_b + r * n).div_ceil(b);
+                    let s_end = (&three_b + r * n).div_ceil(a);
+
+                    find(&s_start, Some(&s_end))
+                })?
+        };
+
+        // Step 3: Narrowing the set of solutions.
+        let m_i = narrow(m_prev, &s_i, n, &two_b, &three_b);
+
+        progress(&Progress {
+            iteration: iterations,
+            s: &s_i,
+            intervals: &m_i,
+            oracle_calls: budget.calls() - initial_calls,
+        });
+
+        // Step 4: Computing the solution.
+        match m_i.as_slice() {
+            [] => return None,
+            [(a, b)] if a == b => {
+                return Some(AttackReport {
+                    message: a * inv_mod(s_0, n)? % n,
+                    oracle_calls: budget.calls() - initial_calls,
+                    trimming_calls,
+                    iterations,
+                })
+            },
+            _ => {},
+        }
+
+        s_prev = s_i;
+        m_prev = m_i;
+    }
+
+    unreachable!()
+}
+
+/// Trim the initial interval `[2B, 3B - 1]` with fractions `u / t` such that
+/// `m * u / t` is PKCS-conforming too (so `m` is a multiple of `t`).
+///
+/// `oracle` tells whether `m * s` is PKCS-conforming.
+fn trim(
+    n: &BigUint,
+    two_b: &BigUint,
+    three_b: &BigUint,
+    oracle: &impl Fn(&BigUint) -> bool,
+    budget: &OracleBudget,
+) -> Option<(BigUint, BigUint)> {
+    let query = |u: u32, t: u32| -> Option<bool> {
+        if !budget.spend() {
+            return None;
+        }
+
+        let t_inv = inv_mod(BigUint::from(t), n)?;
+
+        Some(oracle(&(t_inv * u % n)))
+    };
+
+    let mut t_prime = 1_u32;
+
+    for t in 3..3 + TRIMMER_DENOMINATORS {
+        if query(t - 1, t)? || query(t + 1, t)? {
+            let lcm = t_prime.lcm(&t);
+
+            if lcm <= MAX_TRIMMER_LCM {
+                t_prime = lcm;
+            }
+        }
+    }
+
+    if t_prime == 1 {
+        return Some((two_b.clone(), three_b - 1_u32));
+    }
+
+    // Conforming fractions are contiguous, so scan away from `1` until not
+    // conforming anymore
+    let mut u_min = t_prime;
+
+    while u_min - 1 > 2 * t_prime / 3 && query(u_min - 1, t_prime)? {
+        u_min -= 1;
+    }
+
+    let mut u_max = t_prime;
+
+    while u_max + 1 < 3 * t_prime / 2 && query(u_max + 1, t_prime)? {
+        u_max += 1;
+    }
+
+    Some((
+        max(
+            two_b.clone(),
+            (two_b * t_prime).div_ceil(&BigUint::from(u_min)),
+        ),
+        min(
+            three_b - 1_u32,
+            ((three_b - 1_u32) * t_prime).div_floor(&BigUint::from(u_max)),
+        ),
+    ))
+}
+
+/// Step 3 of the [`attack`]: the intervals that may still contain the
+/// message, given that `m * s` is PKCS-conforming.
+fn narrow(
+    intervals: Vec<(BigUint, BigUint)>,
+    s: &BigUint,
+    n: &BigUint,
+    two_b: &BigUint,
+    three_b: &BigUint,
+) -> Vec<(BigUint, BigUint)> {
+    let mut narrowed = intervals
+        .into_iter()
+        .flat_map(|(a, b)| {
+            let r_start = (&a * s - three_b + 1_u32).div_ceil(n);
+            let r_end = (&b * s - two_b).div_floor(n);
+
+            range_inclusive(r_start, r_end)
+                .map(|ref r| {
+                    (
+                        max(a.clone(), (two_b + r * n).div_ceil(s)),
+                        min(b.clone(), (three_b - 1_u32 + r * n).div_floor(s)),
+                    )
+                })
+                .filter(|(a, b)| a <= b)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    narrowed.sort();
+    narrowed.dedup();
+
+    narrowed
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{attack, find_conforming_s, OracleBudget, ScanOutcome, Strategy};
+    use crate::rsa::{generate_rsa_keypair, EncrytionPadding, PKCS1v1_5, RSAPublicKey, E};
+    use crate::util::{i2osp, inv_mod};
+
+    #[test]
+    fn test_find_first() {
+        let budget = OracleBudget::unlimited();
+        let oracle =
+            |s: &BigUint| s % 1000_u32 == BigUint::from(999_u32) || s > &BigUint::from(5000_u32);
+
+        assert_eq!(
+            find_conforming_s(&BigUint::from(10_u32), None, oracle, &budget),
+            ScanOutcome::Found(BigUint::from(999_u32))
+        );
+        assert!(budget.calls() >= 990);
+
+        assert_eq!(
+            find_conforming_s(&BigUint::from(1000_u32), None, oracle, &budget),
+            ScanOutcome::Found(BigUint::from(1999_u32))
+        );
+    }
+
+    #[test]
+    fn test_exhausted() {
+        let budget = OracleBudget::unlimited();
+
+        assert_eq!(
+            find_conforming_s(
+                &BigUint::from(10_u32),
+                Some(&BigUint::from(2000_u32)),
+                |_: &BigUint| false,
+                &budget
+            ),
+            ScanOutcome::Exhausted
+        );
+        assert_eq!(budget.calls(), 1990);
+
+        assert_eq!(
+            find_conforming_s(
+                &BigUint::from(10_u32),
+                Some(&BigUint::from(10_u32)),
+                |_: &BigUint| true,
+                &budget
+            ),
+            ScanOutcome::Exhausted
+        );
+    }
+
+    #[test]
+    fn test_budget() {
+        let budget = OracleBudget::new(5000);
+
+        assert_eq!(
+            find_conforming_s(&BigUint::from(0_u32), None, |_: &BigUint| false, &budget),
+            ScanOutcome::OutOfBudget
+        );
+        assert_eq!(budget.calls(), 5000);
+        assert!(budget.is_exhausted());
+
+        assert_eq!(
+            find_conforming_s(&BigUint::from(0_u32), None, |_: &BigUint| true, &budget),
+            ScanOutcome::OutOfBudget
+        );
+    }
+
+    #[test]
+    fn test_attack() {
+        // Small keys and an oracle only checking the `00 02` prefix (as in
+        // challenge 47), so that it runs fast
+        let (public_key, private_key) = generate_rsa_keypair(128, &E);
+        let len = public_key.len_bytes();
+
+        let ciphertext = public_key.encrypt::<PKCS1v1_5>(b"hi").unwrap();
+        let oracle = |c: &BigUint| {
+            private_key
+                .textbook_process(c)
+                .and_then(|m| i2osp(&m, len))
+                .is_some_and(|m| m.starts_with(&[0x00, 0x02]))
+        };
+
+        for strategy in [Strategy::Original, Strategy::Parallel, Strategy::Bardou] {
+            let budget = OracleBudget::new(10_000_000);
+            let mut iterations = 0;
+            let mut remaining_bits = u64::MAX;
+
+            let report = attack(
+                &public_key,
+                &ciphertext,
+                oracle,
+                strategy,
+                &budget,
+                |progress| {
+                    iterations = progress.iteration;
+                    remaining_bits = progress.remaining_bits();
+                },
+            )
+            .unwrap();
+
+            assert_eq!(PKCS1v1_5::unpad(len, &report.message), Some(b"hi".to_vec()));
+            assert_eq!(report.oracle_calls, budget.calls());
+            assert_eq!(report.trimming_calls == 0, strategy != Strategy::Bardou);
+            assert_eq!(report.iterations, iterations);
+            assert_eq!(remaining_bits, 0);
+        }
+    }
+
+    #[test]
+    fn test_attack_blinding() {
+        let (public_key, private_key) = generate_rsa_keypair(128, &E);
+        let RSAPublicKey { e, n } = &public_key;
+        let len = public_key.len_bytes();
+
+        let oracle = |c: &BigUint| {
+            private_key
+                .textbook_process(c)
+                .and_then(|m| i2osp(&m, len))
+                .is_some_and(|m| m.starts_with(&[0x00, 0x02]))
+        };
+
+        // Not PKCS-conforming (almost surely), but it is when doubled
+        let conforming = public_key.encrypt::<PKCS1v1_5>(b"hi").unwrap();
+        let half = inv_mod(BigUint::from(2_u32), n).unwrap();
+        let ciphertext = conforming * half.modpow(e, n) % n;
+
+        let budget = OracleBudget::unlimited();
+        let report = attack(
+            &public_key,
+            &ciphertext,
+            oracle,
+            Strategy::Bardou,
+            &budget,
+            |_| {},
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(report.message),
+            private_key.textbook_process(&ciphertext)
+        );
+    }
+
+    #[test]
+    fn test_attack_budget() {
+        let (public_key, private_key) = generate_rsa_keypair(128, &E);
+        let ciphertext = public_key.encrypt::<PKCS1v1_5>(b"hi").unwrap();
+        let oracle = |c: &BigUint| private_key.decrypt::<PKCS1v1_5>(c).is_some();
+
+        let budget = OracleBudget::new(1000);
+
+        assert_eq!(
+            attack(
+                &public_key,
+                &ciphertext,
+                oracle,
+                Strategy::Bardou,
+                &budget,
+                |_| {}
+            ),
+            None
+        );
+        assert_eq!(budget.calls(), 1000);
+    }
+}
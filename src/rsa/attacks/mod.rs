@@ -0,0 +1,269 @@
+//! Attacks on RSA.
+
+pub mod bleichenbacher;
+
+use num_bigint::{BigInt, BigUint};
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+
+use self::bleichenbacher::{OracleBudget, Strategy};
+use super::{EncrytionPadding, PKCS1v1_5, RSAPublicKey};
+use crate::util::field::{Field, ModP};
+use crate::util::poly::Poly;
+
+/// [Franklin-Reiter related-message attack](https://en.wikipedia.org/wiki/Coppersmith%27s_attack#Franklin%E2%80%93Reiter_related-message_attack)
+/// on RSA with a low public exponent.
+///
+/// Given the encryptions `c1` and `c2` (under the same `public_key`) of two
+/// messages related by a known `linear_relation` `(a, b)` such that
+/// `m2 = a * m1 + b (mod n)`, recover `(m1, m2)` without factoring `n`.
+///
+/// `m1` is a root of both `x^e - c1` and `(a * x + b)^e - c2`, so their GCD
+/// over `Z_n` is (almost always) the linear polynomial `x - m1`. The cost
+/// grows with `e`, so this is only practical for small exponents like `3`.
+///
+/// Returns `None` if the GCD is not linear (e.g. if the messages are not
+/// actually related) or if `e` is too big.
+#[must_use]
+pub fn franklin_reiter(
+    public_key: &RSAPublicKey,
+    c1: &BigUint,
+    c2: &BigUint,
+    (a, b): (&BigUint, &BigUint),
+) -> Option<(BigUint, BigUint)> {
+    let RSAPublicKey { e, n } = public_key;
+    let e = e.to_u64()?;
+    let field = ModP(n.clone());
+
+    // x^e - c1
+    let f1 = Poly::x(&field)
+        .pow(e, &field)
+        .sub(&Poly::constant(field.elem(c1), &field), &field);
+
+    // (a * x + b)^e - c2
+    let f2 = field
+        .poly_from_biguints(&[b.clone(), a.clone()])
+        .pow(e, &field)
+        .sub(&Poly::constant(field.elem(c2), &field), &field);
+
+    let gcd = Poly::gcd(&f1, &f2, &field)?;
+
+    if gcd.degree() != Some(1) {
+        return None;
+    }
+
+    // Monic, so it's `x - m1`
+    let m1 = field.neg(&gcd.coeffs()[0]);
+    let m2 = (a * &m1 + b) % n;
+
+    Some((m1, m2))
+}
+
+/// [Bleichenbacher's
+/// attack](http://archiv.infsec.ethz.ch/education/fs08/secsem/bleichenbacher98.pdf)
+/// on PKCS#1 v1.5 encryption padding.
+///
+/// Decrypts a PKCS#1 v1.5 `ciphertext` with a padding `oracle`, telling
+/// whether some ciphertext decrypts to a PKCS-conforming message.
+///
+/// This uses the fastest [`Strategy`] and no oracle call limit. See
+/// [`bleichenbacher::attack`] to choose them or to track the progress.
+///
+/// # Example
+///
+/// ```no_run
+/// use rustopals::rsa::attacks::bleichenbacher_attack;
+/// use rustopals::rsa::{generate_rsa_keypair, PKCS1v1_5, E};
+///
+/// let (public_key, private_key) = generate_rsa_keypair(256, &E);
+/// let ciphertext = public_key.encrypt::<PKCS1v1_5>(b"kick it, CC").unwrap();
+///
+/// let oracle = |c: &_| private_key.decrypt::<PKCS1v1_5>(c).is_some();
+///
+/// assert_eq!(
+///     bleichenbacher_attack(&public_key, &ciphertext, oracle),
+///     Some(b"kick it, CC".to_vec())
+/// );
+/// ```
+#[must_use]
+pub fn bleichenbacher_attack(
+    public_key: &RSAPublicKey,
+    ciphertext: &BigUint,
+    oracle: impl Fn(&BigUint) -> bool + Sync,
+) -> Option<Vec<u8>> {
+    let report = bleichenbacher::attack(
+        public_key,
+        ciphertext,
+        oracle,
+        Strategy::Bardou,
+        &OracleBudget::unlimited(),
+        |_| {},
+    )?;
+
+    PKCS1v1_5::unpad(public_key.len_bytes(), &report.message)
+}
+
+/// Maximum amount of steps tried by [`fermat_factor`].
+const FERMAT_MAX_STEPS: usize = 100_000;
+
+/// [Fermat factorization](https://en.wikipedia.org/wiki/Fermat%27s_factorization_method)
+/// of a modulus whose primes are too close.
+///
+/// Writes `n = a^2 - b^2 = (a - b) * (a + b)`, trying every `a` from
+/// `ceil(sqrt(n))` up. `a = (p + q) / 2`, so it takes about
+/// `(p - q)^2 / (8 * sqrt(n))` steps: just one if `|p - q| < n^(1/4)`.
+///
+/// Returns `(p, q)` with `p <= q`, or `None` if no non-trivial factors were
+/// found in a reasonable amount of steps.
+#[must_use]
+pub fn fermat_factor(n: &BigUint) -> Option<(BigUint, BigUint)> {
+    let mut a = n.sqrt();
+
+    if &(&a * &a) != n {
+        a += 1_u32;
+    }
+
+    for _ in 0..FERMAT_MAX_STEPS {
+        let b_squared = &a * &a - n;
+        let b = b_squared.sqrt();
+
+        if b.pow(2) == b_squared {
+            let p = &a - &b;
+
+            return (p > BigUint::from(1_u32)).then(|| (p, a + b));
+        }
+
+        a += 1_u32;
+    }
+
+    None
+}
+
+/// [RSA parity oracle](https://cryptopals.com/sets/6/challenges/46) attack.
+///
+/// Decrypts a textbook RSA `ciphertext` with an `oracle` telling whether some
+/// ciphertext decrypts to an even plaintext.
+///
+/// Doubling the plaintext (by multiplying the ciphertext by `2^e`) makes it
+/// even iff it did not wrap around `n`, i.e. iff it was in the lower half of
+/// its candidate interval. Each call halves that interval, so it takes as
+/// many calls as bits in `n`. The bounds are exact fractions of `n`, so the
+/// last bits do not get lost to rounding.
+///
+/// Returns the plaintext bytes, without leading zeros.
+#[must_use]
+pub fn parity_oracle_attack(
+    public_key: &RSAPublicKey,
+    ciphertext: &BigUint,
+    oracle: impl Fn(&BigUint) -> bool,
+) -> Vec<u8> {
+    let RSAPublicKey { e, n } = public_key;
+
+    let double = BigUint::from(2_u32).modpow(e, n);
+    let mut ciphertext = ciphertext.clone();
+
+    let mut low = BigRational::zero();
+    let mut high = BigRational::from_integer(BigInt::from(n.clone()));
+
+    for _ in 0..n.bits() {
+        ciphertext = ciphertext * &double % n;
+
+        let middle = (&low + &high) / BigInt::from(2_u32);
+
+        if oracle(&ciphertext) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    // The plaintext is the only integer in `[low, high)`
+    low.ceil()
+        .to_integer()
+        .to_biguint()
+        .unwrap_or_default()
+        .to_bytes_be()
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::{BigUint, RandBigInt};
+
+    use super::{bleichenbacher_attack, fermat_factor, franklin_reiter, parity_oracle_attack};
+    use crate::rand::rng;
+    use crate::rsa::{generate_rsa_keypair, PKCS1v1_5, RSAPrivateKey, RSAPublicKey, E};
+    use crate::testkeys::RSA_KEYPAIR_0;
+    use crate::util::{i2osp, os2ip};
+
+    #[test]
+    fn test_franklin_reiter() {
+        let (public_key, _) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
+        let n = &public_key.n;
+
+        let m1 = rng().gen_biguint_below(n);
+        let a = BigUint::from(1_usize);
+        let b = BigUint::from(0x1337_usize);
+        let m2 = (&a * &m1 + &b) % n;
+
+        let c1 = public_key.textbook_process(&m1).unwrap();
+        let c2 = public_key.textbook_process(&m2).unwrap();
+
+        assert_eq!(
+            franklin_reiter(public_key, &c1, &c2, (&a, &b)),
+            Some((m1, m2))
+        );
+    }
+
+    #[test]
+    fn test_bleichenbacher_attack() {
+        let (public_key, private_key) = generate_rsa_keypair(128, &E);
+        let len = public_key.len_bytes();
+
+        let ciphertext = public_key.encrypt::<PKCS1v1_5>(b"hi").unwrap();
+
+        // Only checking the `00 02` prefix, so that it runs fast
+        let oracle = |c: &BigUint| {
+            private_key
+                .textbook_process(c)
+                .and_then(|m| i2osp(&m, len))
+                .is_some_and(|m| m.starts_with(&[0x00, 0x02]))
+        };
+
+        assert_eq!(
+            bleichenbacher_attack(&public_key, &ciphertext, oracle),
+            Some(b"hi".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_parity_oracle_attack() {
+        use num_integer::Integer;
+
+        let (public_key, private_key) = generate_rsa_keypair(256, &E);
+
+        let oracle = |c: &BigUint| private_key.textbook_process(c).unwrap().is_even();
+
+        for plaintext in [&b"\x01"[..], b"Hello, world!", &[0xff; 31]] {
+            let ciphertext = public_key.textbook_process(&os2ip(plaintext)).unwrap();
+
+            assert_eq!(
+                parity_oracle_attack(&public_key, &ciphertext, oracle),
+                plaintext
+            );
+        }
+    }
+
+    #[test]
+    fn test_fermat_factor() {
+        let p = BigUint::from(1_000_003_u32);
+        let q = BigUint::from(1_000_033_u32);
+
+        assert_eq!(fermat_factor(&(&p * &q)), Some((p.clone(), q)));
+        assert_eq!(fermat_factor(&(&p * &p)), Some((p.clone(), p.clone())));
+
+        // Primes or too far apart
+        assert_eq!(fermat_factor(&p), None);
+        assert_eq!(fermat_factor(&(&p * 101_u32)), None);
+        assert_eq!(fermat_factor(&RSA_KEYPAIR_0.0.n), None);
+    }
+}
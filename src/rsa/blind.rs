@@ -0,0 +1,187 @@
+//! [Chaum blind signatures](https://en.wikipedia.org/wiki/Blind_signature#Blind_RSA_signatures)
+//! on top of RSA.
+//!
+//! The requester multiplies the (padded) message by `r^e` before sending it to
+//! the signer, who signs it without learning anything about the message. Since
+//! `(m * r^e)^d = m^d * r`, multiplying the result by `r^-1` yields a regular
+//! signature of `m`.
+//!
+//! This is the same multiplicative malleability exploited by
+//! [`unpadded_message_recovery`](super::unpadded_message_recovery), put to
+//! constructive use.
+
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::One;
+
+use super::{RSAPrivateKey, RSAPublicKey, SignaturePadding};
+use crate::digest::Digest;
+use crate::rand::rng;
+use crate::util::binary_inv_mod;
+use crate::util::secret::Redacted;
+
+/// Secret needed to turn a blinded signature into a regular one (`r^-1 mod n`).
+///
+/// Must be kept by the requester and never sent to the signer.
+pub struct Unblinder(BigUint);
+
+impl fmt::Debug for Unblinder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Unblinder")
+            .field(&Redacted::biguint(&self.0))
+            .finish()
+    }
+}
+
+/// Pick a random invertible `r` modulo `n`, returning `(r, r^-1 mod n)`.
+pub(crate) fn random_unit(n: &BigUint) -> (BigUint, BigUint) {
+    let mut rng = rng();
+
+    loop {
+        let r = rng.gen_biguint_range(&BigUint::one(), n);
+
+        if let Some(r_inv) = binary_inv_mod(&r, n) {
+            return (r, r_inv);
+        }
+    }
+}
+
+/// Blind a `message` so that it can be signed without the signer learning it.
+///
+/// Returns the blinded message (to be sent to the signer) and the
+/// [`Unblinder`] (to be kept secret).
+#[must_use]
+pub fn blind<S, D>(public_key: &RSAPublicKey, message: &[u8]) -> Option<(BigUint, Unblinder)>
+where
+    S: SignaturePadding,
+    D: Digest,
+{
+    let padded = S::hash_pad::<D>(public_key.len_bytes(), message)?;
+
+    let (r, r_inv) = random_unit(&public_key.n);
+
+    let blinded = (padded * r.modpow(&public_key.e, &public_key.n)) % &public_key.n;
+
+    Some((blinded, Unblinder(r_inv)))
+}
+
+/// Sign a blinded message (signer side).
+///
+/// The signer learns nothing about the underlying message.
+#[must_use]
+pub fn sign_blinded(private_key: &RSAPrivateKey, blinded: &BigUint) -> Option<BigUint> {
+    private_key.textbook_process(blinded)
+}
+
+/// Turn a blinded signature into a regular signature of the original message.
+///
+/// The result can be checked with [`RSAPublicKey::verify`] as usual.
+#[must_use]
+pub fn unblind(
+    public_key: &RSAPublicKey,
+    blinded_signature: &BigUint,
+    unblinder: &Unblinder,
+) -> BigUint {
+    let Unblinder(r_inv) = unblinder;
+
+    (blinded_signature * r_inv) % &public_key.n
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use num_bigint::BigUint;
+
+    use super::{blind, sign_blinded, unblind};
+    use crate::digest::SHA256;
+    use crate::rsa::{PKCS1v1_5, SignaturePadding};
+    use crate::testkeys::RSA_KEYPAIR_0;
+
+    const MESSAGE: &[u8] = b"Coin #1337";
+
+    #[test]
+    fn test_blind_signature() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let (blinded, unblinder) = blind::<PKCS1v1_5, SHA256>(public_key, MESSAGE).unwrap();
+        let blinded_signature = sign_blinded(private_key, &blinded).unwrap();
+        let signature = unblind(public_key, &blinded_signature, &unblinder);
+
+        assert!(public_key.verify::<PKCS1v1_5, SHA256>(MESSAGE, &signature));
+        assert_eq!(
+            Some(signature),
+            private_key.sign::<PKCS1v1_5, SHA256>(MESSAGE)
+        );
+    }
+
+    #[test]
+    fn test_blinded_signature_is_not_valid() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let (blinded, _) = blind::<PKCS1v1_5, SHA256>(public_key, MESSAGE).unwrap();
+        let blinded_signature = sign_blinded(private_key, &blinded).unwrap();
+
+        assert!(!public_key.verify::<PKCS1v1_5, SHA256>(MESSAGE, &blinded_signature));
+    }
+
+    #[test]
+    fn test_signer_sees_nothing() {
+        let (public_key, _) = &*RSA_KEYPAIR_0;
+
+        let padded = PKCS1v1_5::hash_pad::<SHA256>(public_key.len_bytes(), MESSAGE).unwrap();
+
+        let (blinded_1, _) = blind::<PKCS1v1_5, SHA256>(public_key, MESSAGE).unwrap();
+        let (blinded_2, _) = blind::<PKCS1v1_5, SHA256>(public_key, MESSAGE).unwrap();
+
+        assert_ne!(blinded_1, padded);
+        assert_ne!(blinded_2, padded);
+        assert_ne!(blinded_1, blinded_2);
+    }
+
+    /// A toy e-cash bank: issues coins blindly, and rejects double-spends by
+    /// remembering spent serials. It cannot link issued coins to spent ones.
+    #[test]
+    fn test_double_spend() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let mut issued: Vec<BigUint> = Vec::new();
+        let mut spent_serials: HashSet<Vec<u8>> = HashSet::new();
+
+        let mut spend = |serial: &[u8], signature: &BigUint| -> bool {
+            public_key.verify::<PKCS1v1_5, SHA256>(serial, signature)
+                && spent_serials.insert(serial.to_vec())
+        };
+
+        // Withdraw
+        let coins = [&b"Coin #1"[..], b"Coin #2", b"Coin #3"]
+            .iter()
+            .map(|&serial| {
+                let (blinded, unblinder) = blind::<PKCS1v1_5, SHA256>(public_key, serial).unwrap();
+                issued.push(blinded.clone());
+                let blinded_signature = sign_blinded(private_key, &blinded).unwrap();
+
+                (serial, unblind(public_key, &blinded_signature, &unblinder))
+            })
+            .collect::<Vec<_>>();
+
+        // Nothing the bank saw on withdrawal matches what it sees on spending
+        for (_, signature) in &coins {
+            assert!(!issued.contains(signature));
+            assert!(!issued.contains(&signature.modpow(&public_key.e, &public_key.n)));
+        }
+
+        // Spend
+        for (serial, signature) in &coins {
+            assert!(spend(serial, signature));
+        }
+
+        // Double-spend
+        let (serial, signature) = &coins[1];
+        assert!(!spend(serial, signature));
+
+        // Forged coin
+        assert!(!spend(b"Coin #4", signature));
+    }
+}
@@ -0,0 +1,311 @@
+//! Key generation modes beyond [`generate_rsa_keypair`](super::generate_rsa_keypair).
+//!
+//! - **Strict** mode follows the
+//!   [FIPS 186-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf)
+//!   (appendix B.3.1) constraints, rejecting keys with a clear reason.
+//! - **Weak** mode does the opposite: it deliberately generates keys that
+//!   break those constraints, so attacks like
+//!   [Fermat factorization](https://en.wikipedia.org/wiki/Fermat%27s_factorization_method)
+//!   (close primes) or [Wiener's attack](https://en.wikipedia.org/wiki/Wiener%27s_attack)
+//!   (small `d`) can be tested against fresh keys.
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::One;
+
+use super::primes::{gen_prime, gen_rsa_prime, next_prime};
+use super::{generate_rsa_key_material_from_primes, RSAKeyMaterial, TotientKind};
+use crate::rand::rng;
+use crate::util::binary_inv_mod;
+
+/// FIPS 186-4 requires `|p - q| > 2^(nlen / 2 - PRIME_DISTANCE_MARGIN)`.
+pub const PRIME_DISTANCE_MARGIN: u64 = 100;
+
+/// Smallest public exponent allowed in strict mode is `2^MIN_E_BITS + 1`.
+pub const MIN_E_BITS: u64 = 16;
+
+/// Public exponents must be smaller than `2^MAX_E_BITS` in strict mode.
+pub const MAX_E_BITS: u64 = 256;
+
+/// Reasons for strict mode to reject some key material.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeygenRejection {
+    /// `e` is even.
+    EvenExponent,
+
+    /// `e` is not in `(2^16, 2^256)`.
+    ExponentOutOfRange,
+
+    /// `e` is not invertible modulo `lcm(p - 1, q - 1)`.
+    ExponentNotCoprime,
+
+    /// `p` and `q` are not both half the modulus size.
+    UnbalancedPrimes,
+
+    /// `|p - q|` is too small, so `n` falls to Fermat factorization.
+    PrimesTooClose,
+
+    /// `d` is not larger than `2^(nlen / 2)`, risking Wiener-style attacks.
+    PrivateExponentTooSmall,
+
+    /// `d` is not reduced modulo `lcm(p - 1, q - 1)`.
+    PrivateExponentTooLarge,
+}
+
+/// Whether `p` and `q` are too close, i.e. `|p - q| <= 2^(nlen / 2 - 100)`.
+///
+/// Their modulus would fall to [Fermat
+/// factorization](super::attacks::fermat_factor) (for small enough
+/// distances).
+#[must_use]
+pub fn primes_too_close(p: &BigUint, q: &BigUint) -> bool {
+    let n_bits = (p * q).bits();
+    let distance = if p > q { p - q } else { q - p };
+
+    distance.bits() <= (n_bits / 2).saturating_sub(PRIME_DISTANCE_MARGIN)
+}
+
+/// Check that a public exponent `e` is acceptable in strict mode.
+///
+/// # Errors
+///
+/// If `e` is even or out of range.
+pub fn check_exponent(e: &BigUint) -> Result<(), KeygenRejection> {
+    if e.is_even() {
+        return Err(KeygenRejection::EvenExponent);
+    }
+
+    if e.bits() <= MIN_E_BITS || e.bits() > MAX_E_BITS {
+        return Err(KeygenRejection::ExponentOutOfRange);
+    }
+
+    Ok(())
+}
+
+/// Check key `material` against the strict mode constraints.
+///
+/// # Errors
+///
+/// The first constraint the key fails to meet.
+pub fn check_strict(material: &RSAKeyMaterial) -> Result<(), KeygenRejection> {
+    let e = &material.public_key.e;
+    let d = material.private_key.expose_secret();
+    let (p, q) = (&material.p, &material.q);
+    let n_bits = material.public_key.n.bits();
+
+    check_exponent(e)?;
+
+    let lambda = TotientKind::Carmichael.totient(p, q);
+
+    if !e.gcd(&lambda).is_one() {
+        return Err(KeygenRejection::ExponentNotCoprime);
+    }
+
+    if p.bits() != n_bits.div_ceil(2) || q.bits() != n_bits.div_ceil(2) {
+        return Err(KeygenRejection::UnbalancedPrimes);
+    }
+
+    if primes_too_close(p, q) {
+        return Err(KeygenRejection::PrimesTooClose);
+    }
+
+    if d.bits() <= n_bits / 2 {
+        return Err(KeygenRejection::PrivateExponentTooSmall);
+    }
+
+    if d >= &lambda {
+        return Err(KeygenRejection::PrivateExponentTooLarge);
+    }
+
+    Ok(())
+}
+
+/// Randomly generate RSA key material meeting the strict mode constraints.
+///
+/// # Errors
+///
+/// If `e` itself is rejected (no amount of retries would fix it).
+pub fn generate_strict(bits: u32, e: &BigUint) -> Result<RSAKeyMaterial, KeygenRejection> {
+    check_exponent(e)?;
+
+    loop {
+        let p = gen_rsa_prime(bits / 2, e);
+        let q = gen_rsa_prime(bits / 2, e);
+
+        let Some(material) =
+            generate_rsa_key_material_from_primes(e.clone(), &p, &q, TotientKind::Carmichael)
+        else {
+            continue;
+        };
+
+        // FIPS 186-4 also wants exactly `bits` long moduli
+        if material.public_key.n.bits() == u64::from(bits) && check_strict(&material).is_ok() {
+            return Ok(material);
+        }
+    }
+}
+
+/// Generate weak RSA key material whose primes are at most `2^distance_bits`
+/// apart, falling to Fermat factorization.
+#[must_use]
+pub fn weak_close_primes(bits: u32, e: &BigUint, distance_bits: u64) -> RSAKeyMaterial {
+    let mut rng = rng();
+
+    loop {
+        let p = gen_rsa_prime(bits / 2, e);
+        let offset = rng.gen_biguint(distance_bits);
+        let q = next_prime(&(&p + offset + 1_u32));
+
+        if q.bits() != p.bits() || (&q % e).is_one() {
+            continue;
+        }
+
+        if let Some(material) =
+            generate_rsa_key_material_from_primes(e.clone(), &p, &q, TotientKind::Carmichael)
+        {
+            return material;
+        }
+    }
+}
+
+/// Generate weak RSA key material with a random private exponent small
+/// enough for Wiener's attack (`d < n^(1/4) / 3`).
+///
+/// The public exponent is derived from `d`, so it will be about as large as
+/// the modulus. Uses Euler's totient, like Wiener's paper.
+#[must_use]
+pub fn weak_small_d(bits: u32) -> RSAKeyMaterial {
+    let mut rng = rng();
+    let d_bits = u64::from(bits / 4 - 2);
+
+    loop {
+        let p = gen_prime(bits / 2);
+        let q = gen_prime(bits / 2);
+
+        if p == q {
+            continue;
+        }
+
+        let totient = TotientKind::Euler.totient(&p, &q);
+
+        let mut d = rng.gen_biguint(d_bits);
+        d.set_bit(0, true);
+
+        let Some(e) = binary_inv_mod(&d, &totient) else {
+            continue;
+        };
+
+        if let Some(material) = generate_rsa_key_material_from_primes(e, &p, &q, TotientKind::Euler)
+        {
+            return material;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+    use num_traits::One;
+
+    use super::{
+        check_exponent, check_strict, generate_strict, primes_too_close, weak_close_primes,
+        weak_small_d, KeygenRejection,
+    };
+    use crate::rsa::attacks::fermat_factor;
+    use crate::rsa::primes::next_prime;
+    use crate::rsa::{generate_rsa_key_material_from_primes, TotientKind, E};
+    use crate::testkeys::rsa_primes;
+
+    #[test]
+    fn test_check_exponent() {
+        assert_eq!(check_exponent(&E), Err(KeygenRejection::ExponentOutOfRange));
+        assert_eq!(
+            check_exponent(&BigUint::from(65536_usize)),
+            Err(KeygenRejection::EvenExponent)
+        );
+        assert_eq!(
+            check_exponent(&(BigUint::one() << 256_usize | BigUint::one())),
+            Err(KeygenRejection::ExponentOutOfRange)
+        );
+        assert_eq!(check_exponent(&BigUint::from(65537_usize)), Ok(()));
+    }
+
+    #[test]
+    fn test_generate_strict() {
+        let e = BigUint::from(65537_usize);
+        let material = generate_strict(1024, &e).unwrap();
+
+        assert_eq!(check_strict(&material), Ok(()));
+        assert_eq!(material.public_key.n.bits(), 1024);
+
+        assert_eq!(
+            generate_strict(1024, &E).map(|_| ()),
+            Err(KeygenRejection::ExponentOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_euler_d() {
+        let (p, q) = rsa_primes(0);
+        let e = BigUint::from(65537_usize);
+
+        let euler =
+            generate_rsa_key_material_from_primes(e.clone(), &p, &q, TotientKind::Euler).unwrap();
+        let carmichael =
+            generate_rsa_key_material_from_primes(e, &p, &q, TotientKind::Carmichael).unwrap();
+
+        assert_eq!(
+            check_strict(&euler),
+            Err(KeygenRejection::PrivateExponentTooLarge)
+        );
+        assert_eq!(check_strict(&carmichael), Ok(()));
+    }
+
+    #[test]
+    fn test_weak_close_primes() {
+        let e = BigUint::from(65537_usize);
+        let material = weak_close_primes(1024, &e, 64);
+
+        assert_eq!(
+            check_strict(&material),
+            Err(KeygenRejection::PrimesTooClose)
+        );
+
+        assert_eq!(
+            fermat_factor(&material.public_key.n),
+            Some((
+                material.p.clone().min(material.q.clone()),
+                material.p.clone().max(material.q.clone())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_primes_too_close() {
+        let (p, q) = rsa_primes(0);
+
+        assert!(!primes_too_close(&p, &q));
+        assert!(primes_too_close(&p, &next_prime(&(&p + 1_u32))));
+        assert!(primes_too_close(&p, &p));
+    }
+
+    #[test]
+    fn test_weak_small_d() {
+        let material = weak_small_d(1024);
+        let d = material.private_key.expose_secret();
+
+        assert!(d * 3_u32 < material.public_key.n.nth_root(4));
+        assert_eq!(
+            check_strict(&material),
+            Err(KeygenRejection::ExponentOutOfRange)
+        );
+
+        let plaintext = BigUint::from(0x1337_usize);
+        let ciphertext = material.public_key.textbook_process(&plaintext).unwrap();
+
+        assert_eq!(
+            material.private_key.textbook_process(&ciphertext),
+            Some(plaintext)
+        );
+    }
+}
@@ -2,17 +2,28 @@
 //!
 //! Enables asymmetric encryption and signatures.
 
+pub mod attacks;
+pub mod blind;
+pub mod keygen;
 pub mod padding;
-mod primes;
+pub mod pkcs8;
+pub(crate) mod primes;
+pub mod threshold;
 
-use num_bigint::{BigInt, BigUint};
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
 use num_integer::Integer;
+use num_traits::{One, Zero};
 use once_cell::sync::Lazy;
-pub use padding::{BadNoPadding, BadPKCS1v1_5, EncrytionPadding, PKCS1v1_5, SignaturePadding};
+pub use padding::{EncrytionPadding, PKCS1v1_5, SignaturePadding, OAEP};
 
 use self::primes::gen_rsa_prime;
 use crate::digest::Digest;
-use crate::util::{egcd, inv_mod};
+use crate::rand::rng;
+use crate::util::bigint::{convert, BigIntBackend};
+use crate::util::secret::Redacted;
+use crate::util::{bit_len, byte_len, inv_mod, BatchVerification};
 
 /// A not-very-safe default exponent (`3`).
 ///
@@ -26,7 +37,10 @@ pub static E: Lazy<BigUint> = Lazy::new(|| BigUint::from(3_usize));
 /// corresponding private key).
 #[derive(Debug, PartialEq, Eq)]
 pub struct RSAPublicKey {
+    /// Public exponent.
     pub e: BigUint,
+
+    /// Modulus.
     pub n: BigUint,
 }
 
@@ -46,6 +60,30 @@ impl RSAPublicKey {
         }
     }
 
+    /// Verify a batch of `(message, signature)` pairs.
+    ///
+    /// RSA signatures do not combine nicely, so this is a plain loop sharing
+    /// the per-key computations.
+    #[must_use]
+    pub fn verify_batch<S, D>(&self, items: &[(&[u8], &BigUint)]) -> BatchVerification
+    where
+        S: SignaturePadding,
+        D: Digest,
+    {
+        let len_bytes = self.len_bytes();
+
+        BatchVerification {
+            results: items
+                .iter()
+                .map(|&(message, signature)| {
+                    self.textbook_process(signature).is_some_and(|decrypted| {
+                        S::unpad_verify::<D>(len_bytes, message, &decrypted)
+                    })
+                })
+                .collect(),
+        }
+    }
+
     /// Encrypt a `plaintext`.
     #[must_use]
     pub fn encrypt<E>(&self, plaintext: &[u8]) -> Option<BigUint>
@@ -58,26 +96,32 @@ impl RSAPublicKey {
     /// Process a message with [textbook RSA](https://crypto.stackexchange.com/questions/1448/definition-of-textbook-rsa).
     ///
     /// Mostly used as a primitive, not intended as a public-facing API. Prefer using `verify`/`encrypt` which are safer.
-    /// If you need the textbook behavior just use `BadNoPadding` scheme.
+    /// If you need the textbook behavior just use the `insecure::BadNoPadding` scheme.
     #[must_use]
     pub fn textbook_process(&self, message: &BigUint) -> Option<BigUint> {
-        if message > &self.n {
-            return None;
-        }
+        textbook_rsa(message, &self.e, &self.n)
+    }
 
-        Some(message.modpow(&self.e, &self.n))
+    /// Same as [`textbook_process`](RSAPublicKey::textbook_process), but
+    /// running on the backend `B` (e.g. a fixed-width
+    /// [`U2048`](crate::util::fixed_uint::U2048)).
+    ///
+    /// Returns `None` if the key does not fit in `B`.
+    #[must_use]
+    pub fn textbook_process_on<B: BigIntBackend>(&self, message: &B) -> Option<B> {
+        textbook_rsa(message, &convert(&self.e)?, &convert(&self.n)?)
     }
 
     /// Get modulus length in bits.
     #[must_use]
     pub fn len_bits(&self) -> usize {
-        self.n.bits() as usize
+        bit_len(&self.n)
     }
 
     /// Get modulus length in bytes.
     #[must_use]
     pub fn len_bytes(&self) -> usize {
-        self.len_bits().div_ceil(&8) as usize
+        byte_len(&self.n)
     }
 }
 
@@ -86,13 +130,61 @@ impl RSAPublicKey {
 /// Allows decrypting a message (that was encrypted with its corresponding
 /// public key) or generating a signature (to be validated with its
 /// corresponding public key).
-#[derive(Debug, PartialEq, Eq)]
+///
+/// Also keeps the primes and the
+/// [CRT](https://en.wikipedia.org/wiki/RSA_(cryptosystem)#Using_the_Chinese_remainder_algorithm)
+/// exponents, so private operations take two half-size exponentiations
+/// instead of a full-size one (around 3-4x faster, see the `bench_2048_*`
+/// benchmarks).
 pub struct RSAPrivateKey {
+    e: BigUint,
     d: BigUint,
     n: BigUint,
+    p: BigUint,
+    q: BigUint,
+    dp: BigUint,
+    dq: BigUint,
+    qinv: BigUint,
 }
 
 impl RSAPrivateKey {
+    /// Build a private key from the exponents `e` and `d` and primes `p` and
+    /// `q`, precomputing the CRT values.
+    ///
+    /// Returns `None` if `p` and `q` are not coprime.
+    fn from_primes(e: BigUint, d: BigUint, p: &BigUint, q: &BigUint) -> Option<Self> {
+        let qinv = BigIntBackend::modinv(&(q % p), p)?;
+
+        Some(RSAPrivateKey {
+            e,
+            dp: &d % (p - 1_u32),
+            dq: &d % (q - 1_u32),
+            d,
+            n: p * q,
+            p: p.clone(),
+            q: q.clone(),
+            qinv,
+        })
+    }
+
+    /// Get the private exponent `d`.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.d
+    }
+
+    /// Get the primes `(p, q)`, in the order the key was built with.
+    #[must_use]
+    pub const fn expose_primes(&self) -> (&BigUint, &BigUint) {
+        (&self.p, &self.q)
+    }
+
+    /// Get the CRT values `(d mod (p - 1), d mod (q - 1), q^-1 mod p)`.
+    #[must_use]
+    pub const fn expose_crt(&self) -> (&BigUint, &BigUint, &BigUint) {
+        (&self.dp, &self.dq, &self.qinv)
+    }
+
     /// Sign a `message`.
     #[must_use]
     pub fn sign<S, D>(&self, message: &[u8]) -> Option<BigUint>
@@ -114,39 +206,141 @@ impl RSAPrivateKey {
             .and_then(|padded| E::unpad(self.len_bytes(), &padded))
     }
 
+    /// Decrypt a `ciphertext` with
+    /// [blinding](https://en.wikipedia.org/wiki/Blinding_(cryptography)).
+    ///
+    /// See [`textbook_process_blinded`](RSAPrivateKey::textbook_process_blinded).
+    #[must_use]
+    pub fn decrypt_blinded<E>(&self, ciphertext: &BigUint) -> Option<Vec<u8>>
+    where
+        E: EncrytionPadding,
+    {
+        self.textbook_process_blinded(ciphertext)
+            .and_then(|padded| E::unpad(self.len_bytes(), &padded))
+    }
+
     /// Process a message with [textbook RSA](https://crypto.stackexchange.com/questions/1448/definition-of-textbook-rsa).
     ///
     /// Mostly used as a primitive, not intended as a public-facing API. Prefer using `sign`/`decrypt` which are safer.
-    /// If you need the textbook behavior just use `BadNoPadding` scheme.
+    /// If you need the textbook behavior just use the `insecure::BadNoPadding` scheme.
     #[must_use]
     pub fn textbook_process(&self, message: &BigUint) -> Option<BigUint> {
-        if message > &self.n {
+        if message >= &self.n {
+            return None;
+        }
+
+        // Garner's recombination of `m mod p` and `m mod q`
+        let m_p = message.modpow(&self.dp, &self.p);
+        let m_q = message.modpow(&self.dq, &self.q);
+        let h = (&self.qinv * (m_p + &self.p - &m_q % &self.p)) % &self.p;
+
+        Some(m_q + h * &self.q)
+    }
+
+    /// Same as [`textbook_process`](RSAPrivateKey::textbook_process), but
+    /// blinding the `message` first.
+    ///
+    /// The message is multiplied by `r^e` for a fresh random `r`, and the
+    /// result by `r^-1` (since `(m * r^e)^d = m^d * r`). The exponentiation
+    /// then runs on a value unrelated to the input, so its timing leaks
+    /// nothing an attacker can correlate with chosen ciphertexts (as in
+    /// [Kocher's attack](https://paulkocher.com/doc/TimingAttacks.pdf)).
+    #[must_use]
+    pub fn textbook_process_blinded(&self, message: &BigUint) -> Option<BigUint> {
+        if message >= &self.n {
             return None;
         }
 
-        Some(message.modpow(&self.d, &self.n))
+        let (r, r_inv) = blind::random_unit(&self.n);
+
+        let blinded = (message * r.modpow(&self.e, &self.n)) % &self.n;
+
+        self.textbook_process(&blinded)
+            .map(|processed| (processed * r_inv) % &self.n)
+    }
+
+    /// Same as [`textbook_process`](RSAPrivateKey::textbook_process), but
+    /// exponentiating by `d` modulo `n` instead of using the CRT.
+    #[must_use]
+    pub fn textbook_process_without_crt(&self, message: &BigUint) -> Option<BigUint> {
+        textbook_rsa(message, &self.d, &self.n)
+    }
+
+    /// Same as
+    /// [`textbook_process_without_crt`](RSAPrivateKey::textbook_process_without_crt),
+    /// but running on the backend `B`.
+    ///
+    /// With a fixed-width backend like
+    /// [`U2048`](crate::util::fixed_uint::U2048) the exponentiation by `d`
+    /// takes the same time whatever the message and key (the CRT path needs
+    /// reductions modulo `p` and `q`, which are not part of
+    /// [`BigIntBackend`]).
+    ///
+    /// Returns `None` if the key does not fit in `B`.
+    #[must_use]
+    pub fn textbook_process_without_crt_on<B: BigIntBackend>(&self, message: &B) -> Option<B> {
+        textbook_rsa(message, &convert(&self.d)?, &convert(&self.n)?)
     }
 
     /// Get modulus length in bits.
     #[must_use]
     pub fn len_bits(&self) -> usize {
-        self.n.bits() as usize
+        bit_len(&self.n)
     }
 
     /// Get modulus length in bytes.
     #[must_use]
     pub fn len_bytes(&self) -> usize {
-        self.len_bits().div_ceil(&8) as usize
+        byte_len(&self.n)
+    }
+}
+
+// The CRT values follow from `d` and `n` (up to the order of the primes)
+impl PartialEq for RSAPrivateKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.d == other.d && self.n == other.n
+    }
+}
+
+impl Eq for RSAPrivateKey {}
+
+impl fmt::Debug for RSAPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RSAPrivateKey")
+            .field("d", &Redacted::biguint(&self.d))
+            .field("n", &self.n)
+            .field("p", &Redacted::biguint(&self.p))
+            .field("q", &Redacted::biguint(&self.q))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Compute `message^exponent mod modulus` on any [`BigIntBackend`], the core
+/// of [textbook RSA](https://crypto.stackexchange.com/questions/1448/definition-of-textbook-rsa).
+///
+/// Returns `None` unless `message < modulus`.
+#[must_use]
+pub fn textbook_rsa<B: BigIntBackend>(message: &B, exponent: &B, modulus: &B) -> Option<B> {
+    if !message.less_than(modulus) {
+        return None;
     }
+
+    Some(message.modpow(exponent, modulus))
 }
 
 /// Randomly generate an RSA keypair with an specific exponent `e`.
+///
+/// Prime pairs that are [too close](keygen::primes_too_close) are rejected.
 #[must_use]
 pub fn generate_rsa_keypair(bits: u32, e: &BigUint) -> (RSAPublicKey, RSAPrivateKey) {
     loop {
         let p = gen_rsa_prime(bits / 2, e);
         let q = gen_rsa_prime(bits / 2, e);
 
+        if keygen::primes_too_close(&p, &q) {
+            continue;
+        }
+
         match generate_rsa_keypair_from_primes(e.clone(), &p, &q) {
             Some(x) => return x,
             None => continue,
@@ -154,31 +348,178 @@ pub fn generate_rsa_keypair(bits: u32, e: &BigUint) -> (RSAPublicKey, RSAPrivate
     }
 }
 
+/// Totient function used to derive the private exponent from `e`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TotientKind {
+    /// [Euler's totient](https://en.wikipedia.org/wiki/Euler%27s_totient_function):
+    /// `(p - 1) * (q - 1)`. Used in the original RSA paper.
+    Euler,
+
+    /// [Carmichael's totient](https://en.wikipedia.org/wiki/Carmichael_function):
+    /// `lcm(p - 1, q - 1)`. Used by PKCS#1; yields a smaller `d`.
+    Carmichael,
+}
+
+impl TotientKind {
+    /// Compute the totient of `n = p * q`.
+    #[must_use]
+    pub fn totient(self, p: &BigUint, q: &BigUint) -> BigUint {
+        let p_1 = p - 1_u32;
+        let q_1 = q - 1_u32;
+
+        match self {
+            TotientKind::Euler => p_1 * q_1,
+            TotientKind::Carmichael => p_1.lcm(&q_1),
+        }
+    }
+}
+
+/// An RSA keypair along with the secret values used to generate it.
+///
+/// Meant for attack experiments: real code only needs the keypair.
+#[derive(PartialEq, Eq)]
+pub struct RSAKeyMaterial {
+    /// The public half of the keypair.
+    pub public_key: RSAPublicKey,
+
+    /// The private half of the keypair.
+    pub private_key: RSAPrivateKey,
+
+    /// First prime factor of the modulus.
+    pub p: BigUint,
+
+    /// Second prime factor of the modulus.
+    pub q: BigUint,
+
+    /// Which totient `d` was derived from.
+    pub totient_kind: TotientKind,
+
+    /// The totient of the modulus, `d` being the inverse of `e` modulo it.
+    pub totient: BigUint,
+}
+
+impl fmt::Debug for RSAKeyMaterial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RSAKeyMaterial")
+            .field("public_key", &self.public_key)
+            .field("private_key", &self.private_key)
+            .field("p", &Redacted::biguint(&self.p))
+            .field("q", &Redacted::biguint(&self.q))
+            .field("totient_kind", &self.totient_kind)
+            .field("totient", &Redacted::biguint(&self.totient))
+            .finish()
+    }
+}
+
 /// Generate an RSA keypair with an specific exponent `e` and primes `p` and `q`.
 ///
+/// Uses Carmichael's totient. See [`generate_rsa_key_material_from_primes`]
+/// to choose the totient and to keep the intermediate values.
+///
 /// Returns `None` if a keypair cannot be generated with the specified parameters.
 #[must_use]
-#[allow(clippy::many_single_char_names)]
 pub fn generate_rsa_keypair_from_primes(
     e: BigUint,
     p: &BigUint,
     q: &BigUint,
 ) -> Option<(RSAPublicKey, RSAPrivateKey)> {
-    let p_1 = p - BigUint::from(1_usize);
-    let q_1 = q - BigUint::from(1_usize);
+    generate_rsa_key_material_from_primes(e, p, q, TotientKind::Carmichael)
+        .map(|material| (material.public_key, material.private_key))
+}
+
+/// Generate RSA key material with an specific exponent `e`, primes `p` and
+/// `q`, and `totient_kind`.
+///
+/// Returns `None` if a keypair cannot be generated with the specified parameters.
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn generate_rsa_key_material_from_primes(
+    e: BigUint,
+    p: &BigUint,
+    q: &BigUint,
+    totient_kind: TotientKind,
+) -> Option<RSAKeyMaterial> {
+    let totient = totient_kind.totient(p, q);
+
+    let d = BigIntBackend::modinv(&(&e % &totient), &totient)?;
+    let private_key = RSAPrivateKey::from_primes(e.clone(), d, p, q)?;
+
+    Some(RSAKeyMaterial {
+        public_key: RSAPublicKey {
+            e,
+            n: private_key.n.clone(),
+        },
+        private_key,
+        p: p.clone(),
+        q: q.clone(),
+        totient_kind,
+        totient,
+    })
+}
+
+/// Maximum amount of random witnesses tried by [`recover_primes_from_d`].
+const RECOVER_PRIMES_MAX_TRIES: usize = 100;
+
+/// Factor `n` given the public exponent `e` and the private exponent `d`.
+///
+/// Standard probabilistic algorithm: `e * d - 1` is a multiple of the
+/// (Carmichael) totient, so for a random `g` some `g^(2^i * r)` is a
+/// non-trivial square root of 1 mod `n`, which reveals a factor. Works
+/// with `d` derived from either totient.
+///
+/// Returns `(p, q)` with `p <= q`, or `None` if no factor was found (e.g. if
+/// `d` is not a valid private exponent for `e` and `n`). Also `None` if `n`
+/// is not an odd number above 3, which can't be an RSA modulus.
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub fn recover_primes_from_d(e: &BigUint, d: &BigUint, n: &BigUint) -> Option<(BigUint, BigUint)> {
+    if n < &BigUint::from(4_usize) || n.is_even() {
+        return None;
+    }
+
+    let one = BigUint::one();
+    let n_1 = n - &one;
+
+    let k = e * d - &one;
+
+    if Zero::is_zero(&k) {
+        return None;
+    }
+
+    // `k = 2^t * r` with `r` odd
+    let t = k.trailing_zeros()?;
+    let r = &k >> t;
+
+    let mut rng = rng();
+
+    for _ in 0..RECOVER_PRIMES_MAX_TRIES {
+        let g = rng.gen_biguint_range(&BigUint::from(2_usize), &n_1);
+        let mut y = g.modpow(&r, n);
+
+        if y == one || y == n_1 {
+            continue;
+        }
 
-    // We use Carmichael's instead of Euler's totient
-    // Should work the same and be more compatible, and smaller
-    let (gcd_p_1_q_1, _, _) = egcd(BigInt::from(p_1.clone()), BigInt::from(q_1.clone()));
-    let totient = p_1 * q_1
-        / gcd_p_1_q_1
-            .to_biguint()
-            .expect("GCD shouldn't have been negative");
+        for _ in 0..t {
+            let x = (&y * &y) % n;
 
-    let n = p * q;
-    let d = inv_mod(e.clone(), &totient)?;
+            if x == one {
+                // `y` is a non-trivial square root of 1
+                let p = (&y - &one).gcd(n);
+                let q = n / &p;
 
-    Some((RSAPublicKey { e, n: n.clone() }, RSAPrivateKey { d, n }))
+                return Some(if p <= q { (p, q) } else { (q, p) });
+            }
+
+            if x == n_1 {
+                break;
+            }
+
+            y = x;
+        }
+    }
+
+    None
 }
 
 /// Perform an E=3 Broadcast attack given three pairs of `(public_key, ciphertext)`.
@@ -230,29 +571,28 @@ where
 
     let almost_recovered_plaintext = oracle(&malleated_ciphertext);
 
-    (almost_recovered_plaintext * inv_mod(s.clone(), &public_key.n).unwrap()) % &public_key.n
+    let s_inv = BigIntBackend::modinv(&(s % &public_key.n), &public_key.n).unwrap();
+
+    (almost_recovered_plaintext * s_inv) % &public_key.n
 }
 
 #[cfg(test)]
 mod test {
     use num_bigint::{BigUint, RandBigInt};
-    use num_traits::Num;
     use once_cell::sync::Lazy;
     use rand::thread_rng;
+    use test::Bencher;
 
     use super::{
-        generate_rsa_keypair, generate_rsa_keypair_from_primes, RSAPrivateKey, RSAPublicKey, E,
+        generate_rsa_key_material_from_primes, generate_rsa_keypair,
+        generate_rsa_keypair_from_primes, recover_primes_from_d, textbook_rsa, RSAPrivateKey,
+        RSAPublicKey, TotientKind, E,
     };
     use crate::digest::SHA256;
     use crate::rsa::PKCS1v1_5;
-
-    // Some 1024-bit RSA keypairs to avoid prime generation.
-    static RSA_KEYPAIR: Lazy<(RSAPublicKey, RSAPrivateKey)> = Lazy::new(|| {
-        let p = BigUint::from_str_radix("c2daf71206b801d0d0805d3cad91c650dfe06f1d92ac44c72b41f2a362ff54670639cec218353e3a54fa68f9e1469800dee331e4b71b0a02284d42b9fad9cee9", 16).unwrap();
-        let q = BigUint::from_str_radix("f4ea8ee535b3c80af47b902604742ad2db7af89d6e9e7bb75139839c50bf478f7fc5290d359acff41e23a680311c31afbd7aaec2814e3e73962a77036ebb608f", 16).unwrap();
-
-        generate_rsa_keypair_from_primes(E.clone(), &p, &q).unwrap()
-    });
+    use crate::testkeys::{rsa_primes, RSA_KEYPAIR_0};
+    use crate::util::bigint::convert;
+    use crate::util::fixed_uint::U2048;
 
     #[test]
     fn test_rsa_bad_keygen() {
@@ -265,6 +605,63 @@ mod test {
         assert_eq!(keypair, None);
     }
 
+    #[test]
+    fn test_rsa_key_material_totients() {
+        let p = BigUint::from(11_usize);
+        let q = BigUint::from(23_usize);
+
+        let euler =
+            generate_rsa_key_material_from_primes(E.clone(), &p, &q, TotientKind::Euler).unwrap();
+        let carmichael =
+            generate_rsa_key_material_from_primes(E.clone(), &p, &q, TotientKind::Carmichael)
+                .unwrap();
+
+        assert_eq!(euler.totient, BigUint::from(220_usize));
+        assert_eq!(carmichael.totient, BigUint::from(110_usize));
+        assert_eq!(euler.public_key, carmichael.public_key);
+        assert_eq!(
+            Some((carmichael.public_key, carmichael.private_key)),
+            generate_rsa_keypair_from_primes(E.clone(), &p, &q)
+        );
+
+        // Both private exponents decrypt the same
+        let ciphertext = euler.public_key.textbook_process(&BigUint::from(42_usize));
+
+        assert_eq!(
+            ciphertext.and_then(|c| euler.private_key.textbook_process(&c)),
+            Some(BigUint::from(42_usize))
+        );
+    }
+
+    #[test]
+    fn test_recover_primes_from_d() {
+        let (p, q) = rsa_primes(0);
+
+        for &totient_kind in &[TotientKind::Euler, TotientKind::Carmichael] {
+            let material =
+                generate_rsa_key_material_from_primes(E.clone(), &p, &q, totient_kind).unwrap();
+            let d = crate::util::binary_inv_mod(&E, &material.totient).unwrap();
+
+            assert_eq!(
+                recover_primes_from_d(&E, &d, &material.public_key.n),
+                Some((p.clone(), q.clone()))
+            );
+        }
+
+        assert_eq!(
+            recover_primes_from_d(&E, &BigUint::from(1_usize), &(&p * &q)),
+            None
+        );
+
+        // Too small or even moduli
+        for n in [0_usize, 1, 2, 3, 4, 22] {
+            assert_eq!(
+                recover_primes_from_d(&E, &BigUint::from(7_usize), &BigUint::from(n)),
+                None
+            );
+        }
+    }
+
     #[test]
     fn test_rsa_full() {
         let (public_key, private_key) = generate_rsa_keypair_from_primes(
@@ -316,7 +713,7 @@ mod test {
     fn test_rsa_pkcs1_v1_5_signature_full() {
         const SIGN_MESSAGE: &[u8] = b"THIS IS MY MESSAGE";
 
-        let (public_key, private_key) = &RSA_KEYPAIR as &(RSAPublicKey, RSAPrivateKey);
+        let (public_key, private_key) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
 
         let signature = private_key.sign::<PKCS1v1_5, SHA256>(SIGN_MESSAGE).unwrap();
         let is_valid = public_key.verify::<PKCS1v1_5, SHA256>(SIGN_MESSAGE, &signature);
@@ -324,15 +721,144 @@ mod test {
         assert!(is_valid);
     }
 
+    #[test]
+    fn test_rsa_pkcs1_v1_5_verify_batch() {
+        let (public_key, private_key) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
+
+        let signature_1 = private_key.sign::<PKCS1v1_5, SHA256>(b"MESSAGE 1").unwrap();
+        let signature_2 = private_key.sign::<PKCS1v1_5, SHA256>(b"MESSAGE 2").unwrap();
+
+        let batch = public_key.verify_batch::<PKCS1v1_5, SHA256>(&[
+            (b"MESSAGE 1", &signature_1),
+            (b"MESSAGE 2", &signature_2),
+            (b"MESSAGE 3", &signature_2),
+        ]);
+
+        assert_eq!(batch.results, [true, true, false]);
+        assert!(!batch.all_valid());
+        assert_eq!(batch.invalid().collect::<Vec<_>>(), [2]);
+    }
+
     #[test]
     fn test_rsa_pkcs1_v1_5_encryption_full() {
         const PLAINTEXT: &[u8] = b"THIS IS MY MESSAGE";
 
-        let (public_key, private_key) = &RSA_KEYPAIR as &(RSAPublicKey, RSAPrivateKey);
+        let (public_key, private_key) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
 
         let ciphertext = public_key.encrypt::<PKCS1v1_5>(PLAINTEXT).unwrap();
         let decrypted_plaintext = private_key.decrypt::<PKCS1v1_5>(&ciphertext);
 
         assert_eq!(decrypted_plaintext.unwrap(), PLAINTEXT);
     }
+
+    #[test]
+    fn test_crt_matches_without_crt() {
+        let (public_key, private_key) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
+
+        for message in [
+            BigUint::from(0_usize),
+            BigUint::from(1_usize),
+            &public_key.n - 1_usize,
+            thread_rng().gen_biguint_below(&public_key.n),
+        ] {
+            assert_eq!(
+                private_key.textbook_process(&message),
+                private_key.textbook_process_without_crt(&message)
+            );
+        }
+
+        let (p, q) = private_key.expose_primes();
+        let (_, _, qinv) = private_key.expose_crt();
+
+        assert_eq!(p * q, public_key.n);
+        assert_eq!((qinv * q) % p, BigUint::from(1_usize));
+    }
+
+    #[test]
+    fn test_textbook_on_fixed_uint() {
+        let (public_key, private_key) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
+
+        for message in [
+            BigUint::from(0_usize),
+            BigUint::from(1_usize),
+            &public_key.n - 1_usize,
+            thread_rng().gen_biguint_below(&public_key.n),
+        ] {
+            let fixed = convert::<_, U2048>(&message).unwrap();
+
+            let ciphertext = public_key.textbook_process_on(&fixed).unwrap();
+
+            assert_eq!(
+                Some(convert(&ciphertext).unwrap()),
+                public_key.textbook_process(&message)
+            );
+            assert_eq!(
+                private_key.textbook_process_without_crt_on(&ciphertext),
+                Some(fixed)
+            );
+        }
+
+        let n = convert::<_, U2048>(&public_key.n).unwrap();
+
+        assert_eq!(public_key.textbook_process_on(&n), None);
+        assert_eq!(
+            textbook_rsa(
+                &U2048::from_u64(2),
+                &U2048::from_u64(10),
+                &U2048::from_u64(1001)
+            ),
+            Some(U2048::from_u64(23))
+        );
+    }
+
+    #[test]
+    fn test_blinded() {
+        const PLAINTEXT: &[u8] = b"THIS IS MY MESSAGE";
+
+        let (public_key, private_key) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
+
+        for message in [
+            BigUint::from(0_usize),
+            BigUint::from(1_usize),
+            &public_key.n - 1_usize,
+            thread_rng().gen_biguint_below(&public_key.n),
+        ] {
+            assert_eq!(
+                private_key.textbook_process_blinded(&message),
+                private_key.textbook_process(&message)
+            );
+        }
+
+        assert_eq!(
+            private_key.textbook_process_blinded(&(&public_key.n + 1_usize)),
+            None
+        );
+
+        let ciphertext = public_key.encrypt::<PKCS1v1_5>(PLAINTEXT).unwrap();
+
+        assert_eq!(
+            private_key.decrypt_blinded::<PKCS1v1_5>(&ciphertext),
+            Some(PLAINTEXT.to_vec())
+        );
+    }
+
+    static KEYPAIR_2048: Lazy<(RSAPublicKey, RSAPrivateKey)> =
+        Lazy::new(|| generate_rsa_keypair(2048, &E));
+
+    fn bench_2048(b: &mut Bencher, process: fn(&RSAPrivateKey, &BigUint) -> Option<BigUint>) {
+        let (public_key, private_key) = &*KEYPAIR_2048;
+        let ciphertext = thread_rng().gen_biguint_below(&public_key.n);
+
+        b.iter(|| process(private_key, &ciphertext));
+    }
+
+    #[bench]
+    fn bench_2048_crt(b: &mut Bencher) {
+        bench_2048(b, RSAPrivateKey::textbook_process);
+    }
+
+    #[bench]
+    fn bench_2048_without_crt(b: &mut Bencher) {
+        bench_2048(b, RSAPrivateKey::textbook_process_without_crt);
+    }
 }
@@ -0,0 +1,89 @@
+//! **INTENTIONALLY UNSAFE** lack of padding (textbook RSA).
+
+use num_bigint::BigUint;
+
+use crate::digest::Digest;
+use crate::rsa::{EncrytionPadding, SignaturePadding};
+use crate::util::{i2osp, os2ip};
+
+/// **INTENTIONALLY UNSAFE** no-op padding scheme.
+pub struct BadNoPadding;
+
+impl SignaturePadding for BadNoPadding {
+    fn hash_pad<D>(block_len: usize, message: &[u8]) -> Option<BigUint>
+    where
+        D: Digest,
+    {
+        if block_len < D::OUTPUT_LENGTH {
+            return None;
+        }
+
+        let hash = D::digest(message);
+
+        Some(os2ip(hash.as_ref()))
+    }
+
+    fn unpad_verify<D>(block_len: usize, message: &[u8], signature: &BigUint) -> bool
+    where
+        D: Digest,
+    {
+        if block_len < D::OUTPUT_LENGTH {
+            return false;
+        }
+
+        let Some(signature_hash) = i2osp(signature, D::OUTPUT_LENGTH) else {
+            return false;
+        };
+
+        let message_hash = D::digest(message);
+
+        signature_hash == message_hash.as_ref()
+    }
+}
+
+impl EncrytionPadding for BadNoPadding {
+    fn pad(block_length: usize, plaintext: &[u8]) -> Option<BigUint> {
+        if plaintext.len() > block_length {
+            return None;
+        }
+
+        Some(os2ip(plaintext))
+    }
+
+    fn unpad(block_length: usize, ciphertext: &BigUint) -> Option<Vec<u8>> {
+        let bytes = i2osp(ciphertext, block_length)?;
+
+        // Without padding, leading zeros cannot be told apart from the message
+        let start = bytes.iter().position(|&x| x != 0).unwrap_or(bytes.len());
+
+        Some(bytes[start..].to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{BadNoPadding, EncrytionPadding};
+
+    #[test]
+    fn test_bad_no_padding_unpad_len() {
+        let padded = BadNoPadding::pad(128, &[0xff; 100]).unwrap();
+
+        assert_eq!(BadNoPadding::unpad(128, &padded), Some(vec![0xff; 100]));
+        assert_eq!(BadNoPadding::unpad(99, &padded), None);
+    }
+
+    #[test]
+    fn test_bad_no_padding_leading_zeros() {
+        let padded = BadNoPadding::pad(16, &[0x00, 0x00, 0x42]).unwrap();
+
+        assert_eq!(padded, BigUint::from(0x42_usize));
+        assert_eq!(BadNoPadding::unpad(16, &padded), Some(vec![0x42]));
+
+        assert_eq!(
+            BadNoPadding::unpad(16, &BadNoPadding::pad(16, &[]).unwrap()),
+            Some(vec![])
+        );
+    }
+}
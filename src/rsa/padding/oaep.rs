@@ -0,0 +1,262 @@
+//! [OAEP](https://en.wikipedia.org/wiki/Optimal_asymmetric_encryption_padding)
+//! padding, as in [RFC 8017](https://tools.ietf.org/html/rfc8017#section-7.1).
+
+use std::marker::PhantomData;
+
+use num_bigint::BigUint;
+
+use crate::digest::Digest;
+use crate::rsa::EncrytionPadding;
+use crate::util::{generate_bytes, i2osp, os2ip};
+
+/// [MGF1](https://tools.ietf.org/html/rfc8017#appendix-B.2.1) mask generation
+/// function: `len` bytes from hashing `seed || counter` with `D`.
+#[must_use]
+pub fn mgf1<D: Digest>(seed: &[u8], len: usize) -> Vec<u8> {
+    let mut mask = (0..len.div_ceil(D::OUTPUT_LENGTH) as u32)
+        .flat_map(|counter| {
+            D::digest_parts(&[seed, &counter.to_be_bytes()])
+                .as_ref()
+                .to_vec()
+        })
+        .collect::<Vec<_>>();
+
+    mask.truncate(len);
+
+    mask
+}
+
+fn xor_in_place(target: &mut [u8], mask: &[u8]) {
+    for (byte, mask) in target.iter_mut().zip(mask) {
+        *byte ^= mask;
+    }
+}
+
+/// [OAEP](https://tools.ietf.org/html/rfc8017#section-7.1) padding with
+/// digest `D` and [`mgf1`].
+///
+/// The [`EncrytionPadding`] implementation uses an empty label. See
+/// [`pad_with_label`](OAEP::pad_with_label) and
+/// [`unpad_with_label`](OAEP::unpad_with_label) otherwise.
+pub struct OAEP<D>(PhantomData<D>);
+
+impl<D: Digest> OAEP<D> {
+    /// Pad a `plaintext` for encryption, binding it to a `label`.
+    #[must_use]
+    pub fn pad_with_label(block_len: usize, plaintext: &[u8], label: &[u8]) -> Option<BigUint> {
+        let seed = generate_bytes(D::OUTPUT_LENGTH);
+
+        Self::encode(block_len, plaintext, label, &seed).map(|block| os2ip(&block))
+    }
+
+    /// Unpad a `ciphertext` for decryption, checking it was bound to `label`.
+    ///
+    /// Every check is done before deciding whether the padding is valid, so
+    /// that failures cannot be told apart (e.g. to mount [Manger's
+    /// attack](https://www.iacr.org/archive/crypto2001/21390229.pdf)).
+    #[must_use]
+    pub fn unpad_with_label(
+        block_len: usize,
+        ciphertext: &BigUint,
+        label: &[u8],
+    ) -> Option<Vec<u8>> {
+        let hash_len = D::OUTPUT_LENGTH;
+
+        if block_len < 2 * hash_len + 2 {
+            return None;
+        }
+
+        let block = i2osp(ciphertext, block_len)?;
+
+        let mut seed = block[1..=hash_len].to_vec();
+        let mut db = block[hash_len + 1..].to_vec();
+
+        xor_in_place(&mut seed, &mgf1::<D>(&db, hash_len));
+        xor_in_place(&mut db, &mgf1::<D>(&seed, block_len - hash_len - 1));
+
+        let label_hash = D::digest(label);
+
+        let mut bad = block[0];
+
+        for (a, b) in db[..hash_len].iter().zip(label_hash.as_ref()) {
+            bad |= a ^ b;
+        }
+
+        // Find the 0x01 separator after the zero padding, without branching
+        let mut found = 0_u8;
+        let mut separator = 0;
+
+        for (i, &byte) in db[hash_len..].iter().enumerate() {
+            let is_zero = u8::from(byte == 0x00);
+            let is_one = u8::from(byte == 0x01);
+            let not_found = found ^ 1;
+
+            separator |= i * usize::from(is_one & not_found);
+            bad |= not_found & ((is_zero | is_one) ^ 1);
+            found |= is_one;
+        }
+
+        bad |= found ^ 1;
+
+        if bad != 0 {
+            return None;
+        }
+
+        Some(db[hash_len + separator + 1..].to_vec())
+    }
+
+    /// Build the encoded block `0x00 || maskedSeed || maskedDB`.
+    fn encode(block_len: usize, plaintext: &[u8], label: &[u8], seed: &[u8]) -> Option<Vec<u8>> {
+        let hash_len = D::OUTPUT_LENGTH;
+
+        if block_len < plaintext.len() + 2 * hash_len + 2 {
+            return None;
+        }
+
+        let padding_len = block_len - plaintext.len() - 2 * hash_len - 2;
+
+        let mut db = [
+            D::digest(label).as_ref(),
+            &vec![0x00; padding_len],
+            &[0x01],
+            plaintext,
+        ]
+        .concat();
+        let mut masked_seed = seed.to_vec();
+
+        let db_mask = mgf1::<D>(seed, db.len());
+
+        xor_in_place(&mut db, &db_mask);
+        xor_in_place(&mut masked_seed, &mgf1::<D>(&db, hash_len));
+
+        Some([&[0x00][..], &masked_seed, &db].concat())
+    }
+}
+
+impl<D: Digest> EncrytionPadding for OAEP<D> {
+    fn pad(block_len: usize, plaintext: &[u8]) -> Option<BigUint> {
+        Self::pad_with_label(block_len, plaintext, &[])
+    }
+
+    fn unpad(block_len: usize, ciphertext: &BigUint) -> Option<Vec<u8>> {
+        Self::unpad_with_label(block_len, ciphertext, &[])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{mgf1, EncrytionPadding, OAEP};
+    use crate::digest::{SHA1, SHA256};
+    use crate::rsa::RSAPublicKey;
+    use crate::testkeys::RSA_KEYPAIR_0;
+    use crate::util::os2ip;
+
+    const BLOCK_LEN: usize = 128;
+
+    // From the PKCS #1 v2.1 test vectors (oaep-vect.txt), example 1.1
+    const MODULUS: &str = "a8b3b284af8eb50b387034a860f146c4919f318763cd6c5598c8ae4811a1e0abc4c7e0b082d693a5e7fced675cf4668512772c0cbc64a742c6c630f533c8cc72f62ae833c40bf25842e984bb78bdbf97c0107d55bdb662f5c4e0fab9845cb5148ef7392dd3aaff93ae1e6b667bb3d4247616d4f5ba10d4cfd226de88d39f16fb";
+    const MESSAGE: &str = "6628194e12073db03ba94cda9ef9532397d50dba79b987004afefe34";
+    const SEED: &str = "18b776ea21069d69776a33e96bad48e1dda0a5ef";
+    const ENCRYPTION: &str = "354fe67b4a126d5d35fe36c777791a3f7ba13def484e2d3908aff722fad468fb21696de95d0be911c2d3174f8afcc201035f7b6d8e69402de5451618c21a535fa9d7bfc5b8dd9fc243f8cf927db31322d6e881eaa91a996170e657a05a266426d98c88003f8477c1227094a0d9fa1e8c4024309ce1ecccb5210035d47ac72e8a";
+
+    #[test]
+    fn test_vector() {
+        let message = hex::decode(MESSAGE).unwrap();
+        let seed = hex::decode(SEED).unwrap();
+
+        let public_key = RSAPublicKey {
+            e: BigUint::from(65537_usize),
+            n: os2ip(&hex::decode(MODULUS).unwrap()),
+        };
+
+        let block = OAEP::<SHA1>::encode(BLOCK_LEN, &message, &[], &seed).unwrap();
+
+        assert_eq!(
+            public_key.textbook_process(&os2ip(&block)),
+            Some(os2ip(&hex::decode(ENCRYPTION).unwrap()))
+        );
+        assert_eq!(
+            OAEP::<SHA1>::unpad(BLOCK_LEN, &os2ip(&block)),
+            Some(message)
+        );
+    }
+
+    #[test]
+    fn test_mgf1() {
+        assert_eq!(mgf1::<SHA1>(b"seed", 0), vec![]);
+        assert_eq!(mgf1::<SHA1>(b"seed", 50).len(), 50);
+        assert_eq!(mgf1::<SHA1>(b"seed", 50)[..20], mgf1::<SHA1>(b"seed", 20));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        for plaintext in [&b""[..], b"THIS IS MY PLAINTEXT", &[0x00; 62]] {
+            let ciphertext = public_key.encrypt::<OAEP<SHA256>>(plaintext).unwrap();
+
+            assert_eq!(
+                private_key.decrypt::<OAEP<SHA256>>(&ciphertext),
+                Some(plaintext.to_vec())
+            );
+        }
+
+        // Random seed
+        assert_ne!(
+            public_key.encrypt::<OAEP<SHA256>>(b"foo"),
+            public_key.encrypt::<OAEP<SHA256>>(b"foo")
+        );
+    }
+
+    #[test]
+    fn test_label() {
+        let padded = OAEP::<SHA256>::pad_with_label(BLOCK_LEN, b"secret", b"label").unwrap();
+
+        assert_eq!(
+            OAEP::<SHA256>::unpad_with_label(BLOCK_LEN, &padded, b"label"),
+            Some(b"secret".to_vec())
+        );
+        assert_eq!(
+            OAEP::<SHA256>::unpad_with_label(BLOCK_LEN, &padded, b"other"),
+            None
+        );
+        assert_eq!(OAEP::<SHA256>::unpad(BLOCK_LEN, &padded), None);
+    }
+
+    #[test]
+    fn test_too_long() {
+        assert!(OAEP::<SHA256>::pad(BLOCK_LEN, &[0x42; 62]).is_some());
+        assert_eq!(OAEP::<SHA256>::pad(BLOCK_LEN, &[0x42; 63]), None);
+        assert_eq!(OAEP::<SHA256>::pad(65, &[]), None);
+    }
+
+    #[test]
+    fn test_reject_bad_blocks() {
+        let seed = [0x13; 20];
+        let block = OAEP::<SHA1>::encode(BLOCK_LEN, b"foo", &[], &seed).unwrap();
+
+        assert_eq!(
+            OAEP::<SHA1>::unpad(BLOCK_LEN, &os2ip(&block)),
+            Some(b"foo".to_vec())
+        );
+
+        // Leading byte
+        let mut bad = block.clone();
+        bad[0] = 0x01;
+        assert_eq!(OAEP::<SHA1>::unpad(BLOCK_LEN, &os2ip(&bad)), None);
+
+        // Any other byte garbles the label hash, padding or separator
+        for position in [1, 30, BLOCK_LEN - 4] {
+            let mut bad = block.clone();
+            bad[position] ^= 0x01;
+            assert_eq!(OAEP::<SHA1>::unpad(BLOCK_LEN, &os2ip(&bad)), None);
+        }
+
+        // No separator
+        let mut no_separator = OAEP::<SHA1>::encode(BLOCK_LEN, &[], &[], &seed).unwrap();
+        no_separator[BLOCK_LEN - 1] ^= 0x01;
+        assert_eq!(OAEP::<SHA1>::unpad(BLOCK_LEN, &os2ip(&no_separator)), None);
+    }
+}
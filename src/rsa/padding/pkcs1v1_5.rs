@@ -4,18 +4,22 @@ use std::iter;
 
 use num_bigint::BigUint;
 use rand::distributions::Standard;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::digest::Digest;
+use crate::rand::rng;
 use crate::rsa::{EncrytionPadding, SignaturePadding};
+use crate::util::{asn1, i2osp, os2ip};
 
 /// **INTENTIONALLY UNSAFE** [PKCS#1 v1.5](https://tools.ietf.org/html/rfc2313)
 /// padding implementation that stops parsing the block after the hash, even if
 /// there are bytes remaining.
 ///
 /// This was made intentionally bad to [Cryptopals challenge 42](https://cryptopals.com/sets/6/challenges/42)
+#[cfg(feature = "insecure")]
 pub struct BadPKCS1v1_5;
 
+#[cfg(feature = "insecure")]
 impl SignaturePadding for BadPKCS1v1_5 {
     fn hash_pad<D>(block_len: usize, message: &[u8]) -> Option<BigUint>
     where
@@ -28,19 +32,21 @@ impl SignaturePadding for BadPKCS1v1_5 {
     where
         D: Digest,
     {
-        let block = signature.to_bytes_be();
+        let Some(block) = i2osp(signature, block_len) else {
+            return false;
+        };
 
-        if block.len() + 1 != block_len || block[0] != 0x01 {
+        if block[0] != 0x00 || block[1] != 0x01 {
             return false;
         }
 
-        let mut padding_end = 1;
+        let mut padding_end = 2;
 
         while padding_end < block.len() && block[padding_end] == 0xff {
             padding_end += 1;
         }
 
-        if padding_end == 1 || block[padding_end] != 0x00 {
+        if padding_end == 2 || block[padding_end] != 0x00 {
             return false;
         }
 
@@ -87,58 +93,64 @@ impl SignaturePadding for PKCS1v1_5 {
             .copy_from_slice(D::ASN1_PREFIX);
         block[block_len - hash_len..].copy_from_slice(hash.as_ref());
 
-        Some(BigUint::from_bytes_be(&block))
+        Some(os2ip(&block))
     }
 
-    #[allow(clippy::shadow_unrelated)]
     fn unpad_verify<D>(block_len: usize, message: &[u8], signature: &BigUint) -> bool
     where
         D: Digest,
     {
-        let block = signature.to_bytes_be();
+        let Some(block) = i2osp(signature, block_len) else {
+            return false;
+        };
 
-        // - 1 because first 0x00 is dropped on `to_bytes_be`
-        if block.len() + 1 != block_len || block[0] != 0x01 {
+        if block[0] != 0x00 || block[1] != 0x01 {
             return false;
         }
 
         let hash_len = D::OUTPUT_LENGTH;
         let prefix_len = D::ASN1_PREFIX.len();
-        let block_len = block.len();
 
-        if block[block_len - hash_len - prefix_len - 1] != 0x00 {
+        if block_len < hash_len + prefix_len + 11 {
             return false;
         }
 
-        let padding_len = block_len - hash_len - prefix_len - 2;
-        if padding_len < 8 {
+        if block[block_len - hash_len - prefix_len - 1] != 0x00 {
             return false;
         }
 
-        #[allow(clippy::range_plus_one)]
-        let is_valid_padding = block[1..1 + padding_len].iter().all(|&x| x == 0xff);
+        let padding_len = block_len - hash_len - prefix_len - 3;
+
+        let is_valid_padding = block[2..2 + padding_len].iter().all(|&x| x == 0xff);
 
         if !is_valid_padding {
             return false;
         }
 
-        if block[padding_len + 1] != 0x00 {
+        if block[padding_len + 2] != 0x00 {
             return false;
         }
 
-        let asn1_prefix = &block[block_len - hash_len - prefix_len..block_len - hash_len];
+        let digest_info = &block[block_len - hash_len - prefix_len..];
 
-        if asn1_prefix != D::ASN1_PREFIX {
-            return false;
-        }
+        // Digests without an OID sign the bare hash
+        let signature_hash = if D::OID.is_empty() {
+            digest_info
+        } else {
+            match asn1::parse_digest_info(digest_info) {
+                Some((oid, hash)) if oid == D::OID => hash,
+                _ => return false,
+            }
+        };
 
-        let signature_hash = &block[block_len - hash_len..];
         let message_hash = D::digest(message);
 
         signature_hash == message_hash.as_ref()
     }
 }
 
+/// Block type 2 encryption padding: `00 02 || PS || 00 || plaintext`, where
+/// `PS` are (at least 8) random non-zero bytes.
 impl EncrytionPadding for PKCS1v1_5 {
     fn pad(block_len: usize, plaintext: &[u8]) -> Option<BigUint> {
         if block_len < plaintext.len() + 11 {
@@ -146,35 +158,40 @@ impl EncrytionPadding for PKCS1v1_5 {
         }
 
         let padding_len = block_len - 3 - plaintext.len();
-        let padding_bytes_iter = thread_rng()
+        let padding_bytes_iter = rng()
             .sample_iter::<u8, _>(Standard)
             .filter(|&x| x > 0)
             .take(padding_len);
 
-        let bytes = iter::once(0x02_u8)
+        let bytes = iter::once(0x00_u8)
+            .chain(iter::once(0x02_u8))
             .chain(padding_bytes_iter)
             .chain(iter::once(0x00_u8))
             .chain(plaintext.iter().copied())
             .collect::<Vec<_>>();
 
-        assert_eq!(bytes.len(), block_len - 1);
+        assert_eq!(bytes.len(), block_len);
 
-        Some(BigUint::from_bytes_be(&bytes))
+        Some(os2ip(&bytes))
     }
 
     fn unpad(block_len: usize, ciphertext: &BigUint) -> Option<Vec<u8>> {
-        let bytes = ciphertext.to_bytes_be();
+        if block_len < 11 {
+            return None;
+        }
+
+        let bytes = i2osp(ciphertext, block_len)?;
 
-        if bytes.len() + 1 != block_len || bytes[0] != 0x02 {
+        if bytes[0] != 0x00 || bytes[1] != 0x02 {
             return None;
         }
 
-        let padding_len = bytes[1..].iter().position(|&x| x == 0)?;
+        let padding_len = bytes[2..].iter().position(|&x| x == 0)?;
         if padding_len < 8 {
             return None;
         }
 
-        Some(bytes[1 + padding_len + 1..].to_vec())
+        Some(bytes[2 + padding_len + 1..].to_vec())
     }
 }
 
@@ -183,7 +200,7 @@ mod test_pkcs1_v1_5_signature {
     use num_bigint::BigUint;
 
     use super::{PKCS1v1_5, SignaturePadding};
-    use crate::digest::{Digest, SHA256};
+    use crate::digest::{Digest, SHA256, SHA512};
 
     const BITS: usize = 1024;
 
@@ -353,6 +370,27 @@ mod test_pkcs1_v1_5_signature {
 
         assert!(is_valid);
     }
+
+    #[test]
+    fn roundtrip_sha512() {
+        let padded = PKCS1v1_5::hash_pad::<SHA512>(BITS / 8, b"MESSAGE").unwrap();
+
+        assert!(PKCS1v1_5::unpad_verify::<SHA512>(
+            BITS / 8,
+            b"MESSAGE",
+            &padded
+        ));
+        assert!(!PKCS1v1_5::unpad_verify::<SHA512>(
+            BITS / 8,
+            b"MESSAGf",
+            &padded
+        ));
+        assert!(!PKCS1v1_5::unpad_verify::<SHA256>(
+            BITS / 8,
+            b"MESSAGE",
+            &padded
+        ));
+    }
 }
 
 #[cfg(test)]
@@ -360,6 +398,7 @@ mod test_pkcs1_v1_5_encryption {
     use num_bigint::BigUint;
 
     use super::{EncrytionPadding, PKCS1v1_5};
+    use crate::util::i2osp;
 
     const MESSAGE: &[u8] = b"THIS IS MY PLAINTEXT";
     const BITS: usize = 1024;
@@ -469,9 +508,73 @@ mod test_pkcs1_v1_5_encryption {
 
         assert_eq!(unpadded, MESSAGE);
     }
+
+    #[test]
+    fn roundtrip_leading_zeros() {
+        let message = [&[0x00_u8, 0x00] as &[u8], MESSAGE].concat();
+
+        let padded = PKCS1v1_5::pad(BITS / 8, &message).unwrap();
+        let unpadded = PKCS1v1_5::unpad(BITS / 8, &padded).unwrap();
+
+        assert_eq!(unpadded, message);
+    }
+
+    #[test]
+    fn pad_nonzero_padding() {
+        for _ in 0..32 {
+            let padded = PKCS1v1_5::pad(BITS / 8, MESSAGE).unwrap();
+            let bytes = i2osp(&padded, BITS / 8).unwrap();
+            let padding_end = BITS / 8 - MESSAGE.len() - 1;
+
+            assert_eq!(&bytes[..2], &[0x00, 0x02]);
+            assert!(bytes[2..padding_end].iter().all(|&x| x != 0x00));
+            assert_eq!(bytes[padding_end], 0x00);
+            assert_eq!(&bytes[padding_end + 1..], MESSAGE);
+        }
+    }
+
+    #[test]
+    fn pad_length_edge_cases() {
+        // Smallest block, empty message
+        let padded = PKCS1v1_5::pad(11, &[]).unwrap();
+        assert_eq!(PKCS1v1_5::unpad(11, &padded), Some(vec![]));
+        assert_eq!(PKCS1v1_5::pad(10, &[]), None);
+
+        // Longest message
+        let longest = [0x42; BITS / 8 - 11];
+        let padded = PKCS1v1_5::pad(BITS / 8, &longest).unwrap();
+        assert_eq!(PKCS1v1_5::unpad(BITS / 8, &padded), Some(longest.to_vec()));
+        assert_eq!(PKCS1v1_5::pad(BITS / 8, &[0x42; BITS / 8 - 10]), None);
+    }
+
+    #[test]
+    fn unpad_length_edge_cases() {
+        // Empty message
+        let bytes = [&[0x02_u8] as &[u8], &[0xff; 8], &[0x00]].concat();
+        assert_eq!(
+            PKCS1v1_5::unpad(11, &BigUint::from_bytes_be(&bytes)),
+            Some(vec![])
+        );
+
+        // Too short blocks
+        let bytes = [&[0x02_u8] as &[u8], &[0xff; 7], &[0x00]].concat();
+        assert_eq!(PKCS1v1_5::unpad(10, &BigUint::from_bytes_be(&bytes)), None);
+        assert_eq!(PKCS1v1_5::unpad(0, &BigUint::from(0_usize)), None);
+
+        // Zero within the minimum padding
+        let bytes = [
+            &[0x02_u8] as &[u8],
+            &[0xff; 7],
+            &[0x00],
+            &[0xff; 8],
+            &[0x00],
+        ]
+        .concat();
+        assert_eq!(PKCS1v1_5::unpad(19, &BigUint::from_bytes_be(&bytes)), None);
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "insecure"))]
 mod test_bad_pkcs1_v1_5_signature {
     use num_bigint::BigUint;
 
@@ -2,12 +2,23 @@ use num_bigint::{BigUint, RandBigInt};
 use num_integer::Integer;
 use num_traits::{One, Zero};
 use once_cell::sync::Lazy;
-use rand::thread_rng;
+
+use crate::rand::rng;
+use crate::util::pool::ThreadPool;
 
 const FIRST_PRIMES_COUNT: usize = 2048;
 const FERMAT_ROUNDS: usize = 5;
 const RABIN_MILLER_K: usize = 128; // Probability of false-positive is 2^(-k)
 
+/// Bits from which [`gen_prime`] tests candidates in parallel (smaller ones
+/// are too cheap to be worth it).
+const PARALLEL_BITS: u32 = 512;
+
+/// Candidates drawn at once by [`gen_prime`] when testing in parallel. Fixed
+/// (instead of one per core) so that seeded runs get the same primes
+/// everywhere.
+const PARALLEL_BATCH: usize = 16;
+
 pub static FIRST_PRIMES: Lazy<Vec<BigUint>> = Lazy::new(|| {
     let mut primes = Vec::with_capacity(FIRST_PRIMES_COUNT);
 
@@ -38,7 +49,7 @@ fn first_primes(candidate: &BigUint) -> bool {
 /// [Fermat primality test](https://en.wikipedia.org/wiki/Fermat_primality_test)
 fn fermat(candidate: &BigUint) -> bool {
     for _k in 0..FERMAT_ROUNDS {
-        let random = thread_rng().gen_biguint_below(candidate);
+        let random = rng().gen_biguint_below(candidate);
         let result = random.modpow(&(candidate - BigUint::one()), candidate);
 
         if !result.is_one() {
@@ -79,7 +90,7 @@ fn rabin_miller(candidate: &BigUint) -> bool {
     let (s, d) = rewrite(candidate_minus_one.clone());
 
     for _k in (0..RABIN_MILLER_K).step_by(2) {
-        let basis = thread_rng().gen_biguint_range(&two, candidate);
+        let basis = rng().gen_biguint_range(&two, candidate);
 
         let mut v = basis.modpow(&d, candidate);
 
@@ -101,21 +112,65 @@ fn rabin_miller(candidate: &BigUint) -> bool {
     true
 }
 
-fn gen_prime(bits: u32) -> BigUint {
+/// Probabilistic primality test for big (i.e. larger than [`FIRST_PRIMES`])
+/// candidates.
+pub fn is_probable_prime(candidate: &BigUint) -> bool {
+    first_primes(candidate) && fermat(candidate) && rabin_miller(candidate)
+}
+
+/// Smallest probable prime greater than or equal to `from`.
+pub fn next_prime(from: &BigUint) -> BigUint {
+    let mut candidate = from | BigUint::one();
+
+    while !is_probable_prime(&candidate) {
+        candidate += 2_u32;
+    }
+
+    candidate
+}
+
+/// Random `bits`-long probable prime.
+///
+/// Big candidates are drawn in batches (from the library RNG, in order) and
+/// tested in parallel, returning the first prime of the batch.
+pub fn gen_prime(bits: u32) -> BigUint {
     let one = BigUint::from(1_usize);
     let two = BigUint::from(2_usize);
 
-    loop {
-        let mut candidate =
-            thread_rng().gen_biguint_range(&(two.pow(bits - 1) + &one), &(two.pow(bits) - &one));
+    let (low, high) = (two.pow(bits - 1) + &one, two.pow(bits) - &one);
+
+    let candidate = || {
+        let mut candidate = rng().gen_biguint_range(&low, &high);
 
         candidate.set_bit(0, true); // Set LSB to 1 to ensure the number is odd
 
-        if !first_primes(&candidate) || !fermat(&candidate) || !rabin_miller(&candidate) {
-            continue;
+        candidate
+    };
+
+    if bits < PARALLEL_BITS {
+        loop {
+            let candidate = candidate();
+
+            if is_probable_prime(&candidate) {
+                return candidate;
+            }
         }
+    }
 
-        return candidate;
+    let pool = ThreadPool::available();
+
+    loop {
+        let batch = (0..PARALLEL_BATCH).map(|_| candidate()).collect::<Vec<_>>();
+
+        let is_prime = pool.parallel_map(&batch, is_probable_prime);
+
+        if let Some(prime) = batch
+            .into_iter()
+            .zip(is_prime)
+            .find_map(|(candidate, is_prime)| is_prime.then_some(candidate))
+        {
+            return prime;
+        }
     }
 }
 
@@ -130,3 +185,20 @@ pub fn gen_rsa_prime(bits: u32, e: &BigUint) -> BigUint {
         return candidate;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{gen_prime, is_probable_prime, PARALLEL_BITS};
+    use crate::rand::with_seed;
+
+    #[test]
+    fn test_gen_prime() {
+        for bits in [64, PARALLEL_BITS] {
+            let prime = with_seed(1337, || gen_prime(bits));
+
+            assert_eq!(prime.bits(), u64::from(bits));
+            assert!(is_probable_prime(&prime));
+            assert_eq!(with_seed(1337, || gen_prime(bits)), prime);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Toy 2-of-2 [threshold](https://en.wikipedia.org/wiki/Threshold_cryptosystem)
+//! RSA signatures.
+//!
+//! The private exponent `d` is split additively (`d = d_1 + d_2`) between two
+//! parties. Each one produces a partial signature `m^d_i mod n` and since
+//! `m^d_1 * m^d_2 = m^(d_1 + d_2) = m^d`, multiplying both yields a regular
+//! RSA signature that verifies against the original public key.
+//!
+//! Not suitable for real use: the dealer knows `d`, shares are sampled from
+//! `[1, d)` (so they leak a bound on `d`) and there is no verification of
+//! partial signatures.
+
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+
+use super::{RSAPrivateKey, RSAPublicKey, SignaturePadding};
+use crate::digest::Digest;
+use crate::rand::rng;
+use crate::util::byte_len;
+use crate::util::secret::Redacted;
+
+/// One share of a split RSA private exponent.
+#[derive(PartialEq, Eq)]
+pub struct RSAKeyShare {
+    d_i: BigUint,
+    n: BigUint,
+}
+
+impl fmt::Debug for RSAKeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RSAKeyShare")
+            .field("d_i", &Redacted::biguint(&self.d_i))
+            .field("n", &self.n)
+            .finish()
+    }
+}
+
+impl RSAKeyShare {
+    /// Get this share of the private exponent.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.d_i
+    }
+
+    /// Produce a partial signature of `message`.
+    ///
+    /// On its own it is not a valid signature. See [`combine_partial_signatures`].
+    #[must_use]
+    pub fn partial_sign<S, D>(&self, message: &[u8]) -> Option<BigUint>
+    where
+        S: SignaturePadding,
+        D: Digest,
+    {
+        S::hash_pad::<D>(self.len_bytes(), message)
+            .and_then(|padded| self.partial_textbook_process(&padded))
+    }
+
+    /// Raise `message` to this share's exponent (textbook, no padding).
+    #[must_use]
+    pub fn partial_textbook_process(&self, message: &BigUint) -> Option<BigUint> {
+        if message > &self.n {
+            return None;
+        }
+
+        Some(message.modpow(&self.d_i, &self.n))
+    }
+
+    /// Get modulus length in bytes.
+    #[must_use]
+    pub fn len_bytes(&self) -> usize {
+        byte_len(&self.n)
+    }
+}
+
+/// Split a private key into two additive shares.
+///
+/// Returns `None` if the private exponent is too small to be split.
+#[must_use]
+pub fn split_private_key(private_key: &RSAPrivateKey) -> Option<(RSAKeyShare, RSAKeyShare)> {
+    if private_key.d < BigUint::from(2_usize) {
+        return None;
+    }
+
+    let d_1 = rng().gen_biguint_range(&BigUint::from(1_usize), &private_key.d);
+    let d_2 = &private_key.d - &d_1;
+
+    Some((
+        RSAKeyShare {
+            d_i: d_1,
+            n: private_key.n.clone(),
+        },
+        RSAKeyShare {
+            d_i: d_2,
+            n: private_key.n.clone(),
+        },
+    ))
+}
+
+/// Combine two partial signatures (of the same message) into a full signature.
+#[must_use]
+pub fn combine_partial_signatures(
+    public_key: &RSAPublicKey,
+    partial_1: &BigUint,
+    partial_2: &BigUint,
+) -> BigUint {
+    (partial_1 * partial_2) % &public_key.n
+}
+
+#[cfg(test)]
+mod test {
+    use super::{combine_partial_signatures, split_private_key, RSAKeyShare};
+    use crate::digest::SHA256;
+    use crate::rsa::PKCS1v1_5;
+    use crate::testkeys::RSA_KEYPAIR_0;
+
+    const MESSAGE: &[u8] = b"Transfer 1000 coins to Mallory";
+
+    #[test]
+    fn test_threshold_signature() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let (share_1, share_2) = split_private_key(private_key).unwrap();
+
+        let partial_1 = share_1.partial_sign::<PKCS1v1_5, SHA256>(MESSAGE).unwrap();
+        let partial_2 = share_2.partial_sign::<PKCS1v1_5, SHA256>(MESSAGE).unwrap();
+
+        let signature = combine_partial_signatures(public_key, &partial_1, &partial_2);
+
+        assert!(public_key.verify::<PKCS1v1_5, SHA256>(MESSAGE, &signature));
+        assert_eq!(
+            Some(signature),
+            private_key.sign::<PKCS1v1_5, SHA256>(MESSAGE)
+        );
+    }
+
+    #[test]
+    fn test_single_share_cannot_sign() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let (share_1, share_2) = split_private_key(private_key).unwrap();
+
+        let can_sign = |share: RSAKeyShare| {
+            let partial = share.partial_sign::<PKCS1v1_5, SHA256>(MESSAGE).unwrap();
+
+            public_key.verify::<PKCS1v1_5, SHA256>(MESSAGE, &partial)
+        };
+
+        assert!(!can_sign(share_1));
+        assert!(!can_sign(share_2));
+    }
+
+    #[test]
+    fn test_mismatched_messages() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let (share_1, share_2) = split_private_key(private_key).unwrap();
+
+        let partial_1 = share_1.partial_sign::<PKCS1v1_5, SHA256>(MESSAGE).unwrap();
+        let partial_2 = share_2
+            .partial_sign::<PKCS1v1_5, SHA256>(b"Something else")
+            .unwrap();
+
+        let signature = combine_partial_signatures(public_key, &partial_1, &partial_2);
+
+        assert!(!public_key.verify::<PKCS1v1_5, SHA256>(MESSAGE, &signature));
+    }
+
+    #[test]
+    fn test_shares_from_different_splits_do_not_mix() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let (share_1, _) = split_private_key(private_key).unwrap();
+        let (_, share_2) = split_private_key(private_key).unwrap();
+
+        let partial_1 = share_1.partial_sign::<PKCS1v1_5, SHA256>(MESSAGE).unwrap();
+        let partial_2 = share_2.partial_sign::<PKCS1v1_5, SHA256>(MESSAGE).unwrap();
+
+        let signature = combine_partial_signatures(public_key, &partial_1, &partial_2);
+
+        assert!(!public_key.verify::<PKCS1v1_5, SHA256>(MESSAGE, &signature));
+    }
+}
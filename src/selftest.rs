@@ -0,0 +1,148 @@
+//! Runtime [known-answer tests](https://en.wikipedia.org/wiki/Known-answer_test)
+//! for the implemented primitives.
+//!
+//! Quick checks against published test vectors, meant to be run at startup or
+//! as a smoke test after porting the crate to a new target.
+//!
+//! # Example
+//!
+//! ```
+//! let report = rustopals::selftest::run();
+//!
+//! assert!(report.all_passed(), "{:?}", report.failures().collect::<Vec<_>>());
+//! ```
+
+use crate::block::{BlockCipher, Key, AES128};
+use crate::digest::{Digest, MD4, SHA1, SHA256};
+use crate::mac::hmac;
+use crate::rsa::{generate_rsa_keypair_from_primes, PKCS1v1_5, E};
+use crate::testkeys::rsa_primes;
+use crate::util::ct_codec;
+use crate::util::iter::ToHexable;
+
+/// Outcome of a single known-answer test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfTestResult {
+    /// Name of the checked primitive and vector.
+    pub name: &'static str,
+    /// Whether the primitive produced the expected answer.
+    pub passed: bool,
+}
+
+/// Outcome of a full self-test run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub results: Vec<SelfTestResult>,
+}
+
+impl SelfTestReport {
+    /// Whether every known-answer test passed.
+    #[must_use]
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(|result| result.passed)
+    }
+
+    /// Iterate over the failed known-answer tests.
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestResult> {
+        self.results.iter().filter(|result| !result.passed)
+    }
+}
+
+/// A registered known-answer test.
+type SelfTest = (&'static str, fn() -> bool);
+
+/// All registered known-answer tests.
+const SELF_TESTS: &[SelfTest] = &[
+    ("AES-128 encrypt (FIPS-197 C.1)", aes128_encrypt),
+    ("AES-128 decrypt (FIPS-197 C.1)", aes128_decrypt),
+    ("MD4 (RFC 1320)", md4),
+    ("SHA-1 (FIPS 180-2)", sha1),
+    ("SHA-256 (FIPS 180-2)", sha256),
+    ("HMAC-SHA256 (RFC 4231 case 2)", hmac_sha256),
+    ("RSA PKCS#1 v1.5 sign/verify", rsa_sign_verify),
+];
+
+/// Run every known-answer test and collect the results.
+#[must_use]
+pub fn run() -> SelfTestReport {
+    SelfTestReport {
+        results: SELF_TESTS
+            .iter()
+            .map(|&(name, test)| SelfTestResult {
+                name,
+                passed: test(),
+            })
+            .collect(),
+    }
+}
+
+const AES_KEY: &str = "000102030405060708090a0b0c0d0e0f";
+const AES_PLAINTEXT: &str = "00112233445566778899aabbccddeeff";
+const AES_CIPHERTEXT: &str = "69c4e0d86a7b0430d8cdb78070b4c55a";
+
+fn aes128_encrypt() -> bool {
+    let key = Key::new(&ct_codec::hex_decode(AES_KEY).unwrap()).unwrap();
+    let plaintext = hex::decode(AES_PLAINTEXT).unwrap();
+
+    AES128.encrypt_block(&plaintext, &key).into_hex() == AES_CIPHERTEXT
+}
+
+fn aes128_decrypt() -> bool {
+    let key = Key::new(&ct_codec::hex_decode(AES_KEY).unwrap()).unwrap();
+    let ciphertext = hex::decode(AES_CIPHERTEXT).unwrap();
+
+    AES128.decrypt_block(&ciphertext, &key).into_hex() == AES_PLAINTEXT
+}
+
+fn md4() -> bool {
+    MD4::digest(b"abc").into_hex() == "a448017aaf21d8525fc10ae87aa6729d"
+}
+
+fn sha1() -> bool {
+    SHA1::digest(b"abc").into_hex() == "a9993e364706816aba3e25717850c26c9cd0d89d"
+}
+
+fn sha256() -> bool {
+    SHA256::digest(b"abc").into_hex()
+        == "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+}
+
+fn hmac_sha256() -> bool {
+    hmac::<SHA256>(b"Jefe", b"what do ya want for nothing?").into_hex()
+        == "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+}
+
+fn rsa_sign_verify() -> bool {
+    const MESSAGE: &[u8] = b"rustopals self-test";
+
+    let (p, q) = rsa_primes(0);
+
+    let Some((public_key, private_key)) = generate_rsa_keypair_from_primes(E.clone(), &p, &q)
+    else {
+        return false;
+    };
+
+    let Some(signature) = private_key.sign::<PKCS1v1_5, SHA256>(MESSAGE) else {
+        return false;
+    };
+
+    public_key.verify::<PKCS1v1_5, SHA256>(MESSAGE, &signature)
+        && !public_key.verify::<PKCS1v1_5, SHA256>(b"rustopals self-tesT", &signature)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{run, SELF_TESTS};
+
+    #[test]
+    fn test_all_pass() {
+        let report = run();
+
+        assert_eq!(report.results.len(), SELF_TESTS.len());
+        assert!(
+            report.all_passed(),
+            "{:?}",
+            report.failures().collect::<Vec<_>>()
+        );
+    }
+}
@@ -1,47 +1,207 @@
 //! [CTR](https://en.wikipedia.org/wiki/Counter_mode)-based stream cipher.
-use crate::block::{BlockCipher, BlockMode, ECB};
+use crate::block::{BlockCipher, BlockMode, Key, KeySchedule, LengthError, Nonce, ECB};
 use crate::stream::{SeekableStreamCipher, StreamCipher};
 
+/// How counter blocks are built from the nonce and the block index.
+///
+/// Cryptopals uses [`LittleEndian`](CounterLayout::LittleEndian), but other
+/// implementations (and their test vectors) usually use
+/// [`FullBlock`](CounterLayout::FullBlock).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CounterLayout {
+    /// `nonce || counter`, with a 64-bit little-endian counter.
+    #[default]
+    LittleEndian,
+
+    /// `nonce || counter`, with a 64-bit big-endian counter.
+    BigEndian,
+
+    /// The nonce is the whole initial counter block, incremented as a
+    /// big-endian integer (wrapping around). This is the layout in
+    /// [NIST SP 800-38A](https://csrc.nist.gov/publications/detail/sp/800-38a/final)
+    /// and OpenSSL.
+    FullBlock,
+}
+
+impl CounterLayout {
+    /// Length of the nonce for blocks of `block_size` bytes.
+    #[must_use]
+    pub const fn nonce_len(self, block_size: usize) -> usize {
+        match self {
+            CounterLayout::LittleEndian | CounterLayout::BigEndian => block_size - 8,
+            CounterLayout::FullBlock => block_size,
+        }
+    }
+
+    /// Build the counter block for block index `counter`.
+    fn counter_block(self, nonce: &[u8], counter: u64) -> Vec<u8> {
+        use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+        let mut counter_bytes = [0; 8];
+
+        match self {
+            CounterLayout::LittleEndian => LittleEndian::write_u64(&mut counter_bytes, counter),
+            CounterLayout::BigEndian => BigEndian::write_u64(&mut counter_bytes, counter),
+            CounterLayout::FullBlock => {
+                BigEndian::write_u64(&mut counter_bytes, counter);
+
+                let mut block = nonce.to_vec();
+                let mut carry = 0;
+
+                for (byte, &add) in block
+                    .iter_mut()
+                    .rev()
+                    .zip(counter_bytes.iter().rev().chain(std::iter::repeat(&0)))
+                {
+                    let sum = u16::from(*byte) + u16::from(add) + carry;
+                    *byte = sum as u8;
+                    carry = sum >> 8;
+                }
+
+                return block;
+            },
+        }
+
+        [nonce, &counter_bytes].concat()
+    }
+}
+
 /// [CTR](https://en.wikipedia.org/wiki/Counter_mode)-based stream cipher.
 ///
 /// Generate a stream cipher from any block cipher.
 pub struct CTR<'k, 'c, C: BlockCipher + 'c> {
     block_cipher: &'c C,
-    key: &'k [u8],
+    key: &'k Key<C>,
     nonce: Vec<u8>,
+    layout: CounterLayout,
 }
 
 impl<'k, 'c, C: BlockCipher + 'c> CTR<'k, 'c, C> {
-    /// Generate a stream cipher from any block cipher in CTR mode.
-    pub fn new(cipher: &'c C, key: &'k [u8]) -> CTR<'k, 'c, C> {
-        use crate::util::generate_bytes;
+    /// Generate a stream cipher from any block cipher in CTR mode, with a
+    /// random nonce.
+    pub fn new(cipher: &'c C, key: &'k Key<C>) -> CTR<'k, 'c, C> {
+        CTR::from_nonce(cipher, key, &Nonce::random())
+    }
 
+    /// Allows specifying the initial CTR nonce.
+    pub fn from_nonce(cipher: &'c C, key: &'k Key<C>, nonce: &Nonce<C>) -> CTR<'k, 'c, C> {
         CTR {
             block_cipher: cipher,
             key,
-            nonce: generate_bytes(C::BLOCK_SIZE / 2),
+            nonce: nonce.to_vec(),
+            layout: CounterLayout::LittleEndian,
         }
     }
 
-    /// Allows specifying the initial CTR nonce.
-    pub fn from_nonce(cipher: &'c C, key: &'k [u8], nonce: &[u8]) -> CTR<'k, 'c, C> {
-        CTR {
+    /// Allows specifying the initial CTR nonce and how counter blocks are
+    /// built.
+    ///
+    /// # Errors
+    ///
+    /// If `nonce` is not [`nonce_len`](CounterLayout::nonce_len) long.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustopals::block::{Key, AES128};
+    /// use rustopals::stream::ctr::CounterLayout;
+    /// use rustopals::stream::{StreamCipher, CTR};
+    ///
+    /// // From NIST SP 800-38A, appendix F.5.1
+    /// let key = Key::new(&hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap()).unwrap();
+    /// let counter = hex::decode("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").unwrap();
+    /// let plaintext = hex::decode("6bc1bee22e409f96e93d7e117393172a").unwrap();
+    ///
+    /// let ciphertext = CTR::with_layout(&AES128, &key, &counter, CounterLayout::FullBlock)
+    ///     .unwrap()
+    ///     .process(plaintext)
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(hex::encode(ciphertext), "874d6191b620e3261bef6864990db6ce");
+    /// ```
+    pub fn with_layout(
+        cipher: &'c C,
+        key: &'k Key<C>,
+        nonce: &[u8],
+        layout: CounterLayout,
+    ) -> Result<CTR<'k, 'c, C>, LengthError> {
+        check_nonce_len::<C>(nonce, layout)?;
+
+        Ok(CTR {
             block_cipher: cipher,
             key,
             nonce: nonce.to_vec(),
+            layout,
+        })
+    }
+
+    /// Encrypt `plaintext` with `key` using a fresh random nonce, returned
+    /// prepended to the ciphertext (`nonce || ciphertext`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustopals::block::{Key, AES128};
+    /// use rustopals::stream::CTR;
+    ///
+    /// let key = Key::new(b"YELLOW SUBMARINE").unwrap();
+    ///
+    /// let encrypted = CTR::encrypt_with_random_nonce(&AES128, &key, b"Hello");
+    ///
+    /// assert_eq!(encrypted.len(), 8 + 5);
+    /// assert_ne!(encrypted, CTR::encrypt_with_random_nonce(&AES128, &key, b"Hello"));
+    /// assert_eq!(
+    ///     CTR::decrypt_with_prepended_nonce(&AES128, &key, &encrypted),
+    ///     Some(b"Hello".to_vec()),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn encrypt_with_random_nonce(cipher: &'c C, key: &'k Key<C>, plaintext: &[u8]) -> Vec<u8> {
+        let ctr = CTR::new(cipher, key);
+        let mut encrypted = ctr.nonce.clone();
+
+        encrypted.extend(ctr.process(plaintext));
+
+        encrypted
+    }
+
+    /// Decrypt `nonce || ciphertext` (as output by
+    /// [`encrypt_with_random_nonce`](CTR::encrypt_with_random_nonce)) with
+    /// `key`.
+    ///
+    /// Returns `None` if `encrypted` is too short to contain a nonce.
+    #[must_use]
+    pub fn decrypt_with_prepended_nonce(
+        cipher: &'c C,
+        key: &'k Key<C>,
+        encrypted: &[u8],
+    ) -> Option<Vec<u8>> {
+        let nonce_len = Nonce::<C>::expected_len();
+
+        if encrypted.len() < nonce_len {
+            return None;
         }
+
+        let (nonce, ciphertext) = encrypted.split_at(nonce_len);
+        let nonce = Nonce::new(nonce).ok()?;
+
+        Some(
+            CTR::from_nonce(cipher, key, &nonce)
+                .process(ciphertext)
+                .collect(),
+        )
     }
 }
 
 impl<'k, 'c, C: BlockCipher> StreamCipher<u8, KeyStream<'k, 'c, C>> for CTR<'k, 'c, C> {
     fn keystream(self) -> KeyStream<'k, 'c, C> {
-        KeyStream::new(self.block_cipher, self.key, self.nonce.clone())
+        KeyStream::new(self.block_cipher, self.key, self.nonce, self.layout)
     }
 }
 
 impl<'k, 'c, C: BlockCipher> SeekableStreamCipher<u8, KeyStream<'k, 'c, C>> for CTR<'k, 'c, C> {
     fn keystream_from(self, offset: usize) -> KeyStream<'k, 'c, C> {
-        KeyStream::new_from(self.block_cipher, self.key, self.nonce.clone(), offset)
+        KeyStream::new_from(self.block_cipher, self.key, self.nonce, self.layout, offset)
     }
 }
 
@@ -51,42 +211,35 @@ impl<'k, 'c, C: BlockCipher> SeekableStreamCipher<u8, KeyStream<'k, 'c, C>> for
 
 pub struct KeyStream<'k, 'c, C: BlockCipher + 'c> {
     cipher: &'c C,
-    key: &'k [u8],
+    key: &'k Key<C>,
     nonce: Vec<u8>,
-    /// Index of current block
-    counter: u64,
-
-    /// Cached current block (cache for iterator)
-    current_block: Option<Vec<u8>>,
-    /// Byte in current block
-    current_block_byte: usize,
+    layout: CounterLayout,
+    position: BlockPosition,
 }
 
 impl<'k, 'c, C: BlockCipher> KeyStream<'k, 'c, C> {
-    pub fn new(cipher: &'c C, key: &'k [u8], nonce: Vec<u8>) -> KeyStream<'k, 'c, C> {
-        KeyStream {
-            cipher,
-            key,
-            nonce,
-            counter: 0,
-            current_block: None,
-            current_block_byte: 0,
-        }
+    pub fn new(
+        cipher: &'c C,
+        key: &'k Key<C>,
+        nonce: Vec<u8>,
+        layout: CounterLayout,
+    ) -> KeyStream<'k, 'c, C> {
+        KeyStream::new_from(cipher, key, nonce, layout, 0)
     }
 
     pub fn new_from(
         cipher: &'c C,
-        key: &'k [u8],
+        key: &'k Key<C>,
         nonce: Vec<u8>,
+        layout: CounterLayout,
         offset: usize,
     ) -> KeyStream<'k, 'c, C> {
         KeyStream {
             cipher,
             key,
             nonce,
-            counter: (offset / C::BLOCK_SIZE) as u64,
-            current_block: None,
-            current_block_byte: offset % C::BLOCK_SIZE,
+            layout,
+            position: BlockPosition::new(offset, C::BLOCK_SIZE),
         }
     }
 }
@@ -95,7 +248,63 @@ impl<'k, 'c, C: BlockCipher> Iterator for KeyStream<'k, 'c, C> {
     type Item = u8;
 
     fn next(&mut self) -> Option<u8> {
-        use byteorder::{ByteOrder, LittleEndian};
+        let KeyStream {
+            cipher,
+            key,
+            nonce,
+            layout,
+            position,
+        } = self;
+
+        position.next_byte(C::BLOCK_SIZE, |counter| {
+            ECB.encrypt(*cipher, &layout.counter_block(nonce, counter), key)
+        })
+    }
+}
+
+/// Check that `nonce` is [`nonce_len`](CounterLayout::nonce_len) long for
+/// `layout`.
+fn check_nonce_len<C: BlockCipher>(nonce: &[u8], layout: CounterLayout) -> Result<(), LengthError> {
+    let expected = layout.nonce_len(C::BLOCK_SIZE);
+
+    if nonce.len() == expected {
+        Ok(())
+    } else {
+        Err(LengthError {
+            expected,
+            actual: nonce.len(),
+        })
+    }
+}
+
+/// Position in a CTR keystream, shared by [`KeyStream`] and
+/// [`ContextKeyStream`].
+struct BlockPosition {
+    /// Index of current block
+    counter: u64,
+
+    /// Cached current block (cache for iterator)
+    current_block: Option<Vec<u8>>,
+    /// Byte in current block
+    current_block_byte: usize,
+}
+
+impl BlockPosition {
+    const fn new(offset: usize, block_size: usize) -> BlockPosition {
+        BlockPosition {
+            counter: (offset / block_size) as u64,
+            current_block: None,
+            current_block_byte: offset % block_size,
+        }
+    }
+
+    /// Get the next keystream byte, encrypting a new counter block with
+    /// `encrypt_counter` when needed.
+    fn next_byte(
+        &mut self,
+        block_size: usize,
+        encrypt_counter: impl FnOnce(u64) -> Vec<u8>,
+    ) -> Option<u8> {
         use num_traits::Bounded;
 
         if self.counter == Bounded::max_value() {
@@ -103,18 +312,13 @@ impl<'k, 'c, C: BlockCipher> Iterator for KeyStream<'k, 'c, C> {
         }
 
         if self.current_block.is_none() || self.current_block_byte == 0 {
-            let mut counter_bytes = [0; 8];
-            LittleEndian::write_u64(&mut counter_bytes, self.counter);
-
-            let plaintext_block: Vec<u8> = [self.nonce.as_slice(), &counter_bytes].concat();
-            let block = ECB.encrypt(self.cipher, &plaintext_block, self.key);
-            self.current_block = Some(block);
+            self.current_block = Some(encrypt_counter(self.counter));
         }
 
         let current_block = self.current_block.as_ref().unwrap();
         let val = current_block[self.current_block_byte];
 
-        if self.current_block_byte == C::BLOCK_SIZE - 1 {
+        if self.current_block_byte == block_size - 1 {
             self.current_block_byte = 0;
             self.counter += 1;
         } else {
@@ -124,3 +328,349 @@ impl<'k, 'c, C: BlockCipher> Iterator for KeyStream<'k, 'c, C> {
         Some(val)
     }
 }
+
+/*
+ *
+ */
+
+/// A key expanded once for encrypting many messages in CTR mode, each one
+/// under its own nonce.
+///
+/// [`CTR`] only borrows the raw key, so the block cipher expands it for every
+/// single block. This does it once per key instead, which pays off when the
+/// same key is used for lots of (short) messages.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::block::{Key, Nonce, AES128};
+/// use rustopals::stream::ctr::CtrContext;
+/// use rustopals::stream::{StreamCipher, CTR};
+///
+/// let key = Key::new(b"YELLOW SUBMARINE").unwrap();
+/// let context = CtrContext::new(&AES128, &key);
+///
+/// for nonce in 0..4_u64 {
+///     let nonce = Nonce::new(&nonce.to_le_bytes()).unwrap();
+///
+///     assert_eq!(
+///         context.with_nonce(&nonce).unwrap().process(b"Hello").collect::<Vec<_>>(),
+///         CTR::from_nonce(&AES128, &key, &nonce).process(b"Hello").collect::<Vec<_>>(),
+///     );
+/// }
+/// ```
+pub struct CtrContext<'c, C: KeySchedule + 'c> {
+    block_cipher: &'c C,
+    round_keys: C::RoundKeys,
+    layout: CounterLayout,
+}
+
+impl<'c, C: KeySchedule + 'c> CtrContext<'c, C> {
+    /// Expand `key` for CTR messages with the
+    /// [`LittleEndian`](CounterLayout::LittleEndian) layout.
+    pub fn new(cipher: &'c C, key: &Key<C>) -> CtrContext<'c, C> {
+        CtrContext::with_layout(cipher, key, CounterLayout::LittleEndian)
+    }
+
+    /// Expand `key` for CTR messages with some counter block `layout`.
+    pub fn with_layout(cipher: &'c C, key: &Key<C>, layout: CounterLayout) -> CtrContext<'c, C> {
+        CtrContext {
+            block_cipher: cipher,
+            round_keys: cipher.expand_key(key),
+            layout,
+        }
+    }
+
+    /// Replace the key (e.g. when rotating it), keeping the layout.
+    pub fn rekey(&mut self, key: &Key<C>) {
+        self.round_keys = self.block_cipher.expand_key(key);
+    }
+
+    /// Get the stream cipher for the message with some `nonce`.
+    ///
+    /// Nonces must be distinct for every message encrypted under the same key
+    /// (or else the keystream is reused).
+    ///
+    /// # Errors
+    ///
+    /// If `nonce` is not [`nonce_len`](CounterLayout::nonce_len) long.
+    pub fn with_nonce(&self, nonce: &[u8]) -> Result<CtrMessage<'_, 'c, C>, LengthError> {
+        check_nonce_len::<C>(nonce, self.layout)?;
+
+        Ok(CtrMessage {
+            context: self,
+            nonce: nonce.to_vec(),
+        })
+    }
+}
+
+/// A single message in a [`CtrContext`], as returned by
+/// [`CtrContext::with_nonce`].
+pub struct CtrMessage<'x, 'c, C: KeySchedule + 'c> {
+    context: &'x CtrContext<'c, C>,
+    nonce: Vec<u8>,
+}
+
+impl<'x, 'c, C: KeySchedule> StreamCipher<u8, ContextKeyStream<'x, 'c, C>>
+    for CtrMessage<'x, 'c, C>
+{
+    fn keystream(self) -> ContextKeyStream<'x, 'c, C> {
+        self.keystream_from(0)
+    }
+}
+
+impl<'x, 'c, C: KeySchedule> SeekableStreamCipher<u8, ContextKeyStream<'x, 'c, C>>
+    for CtrMessage<'x, 'c, C>
+{
+    fn keystream_from(self, offset: usize) -> ContextKeyStream<'x, 'c, C> {
+        ContextKeyStream {
+            context: self.context,
+            nonce: self.nonce,
+            position: BlockPosition::new(offset, C::BLOCK_SIZE),
+        }
+    }
+}
+
+pub struct ContextKeyStream<'x, 'c, C: KeySchedule + 'c> {
+    context: &'x CtrContext<'c, C>,
+    nonce: Vec<u8>,
+    position: BlockPosition,
+}
+
+impl<C: KeySchedule> Iterator for ContextKeyStream<'_, '_, C> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let ContextKeyStream {
+            context,
+            nonce,
+            position,
+        } = self;
+
+        position.next_byte(C::BLOCK_SIZE, |counter| {
+            context.block_cipher.encrypt_expanded(
+                &context.layout.counter_block(nonce, counter),
+                &context.round_keys,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use test::Bencher;
+
+    use super::{CounterLayout, CtrContext, CTR};
+    use crate::block::{BlockMode, Key, LengthError, Nonce, AES128, AES256, ECB};
+    use crate::stream::{SeekableStreamCipher, StreamCipher};
+    use crate::testsupport::check_seekable_keystream;
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+
+    fn key() -> Key<AES128> {
+        Key::new(KEY).unwrap()
+    }
+
+    // From NIST SP 800-38A, appendix F.5.1
+    #[test]
+    fn test_nist_vector() {
+        let key = Key::new(&hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap()).unwrap();
+        let counter = hex::decode("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff").unwrap();
+        let plaintext = hex::decode(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        )
+        .unwrap();
+
+        let ciphertext = CTR::with_layout(&AES128, &key, &counter, CounterLayout::FullBlock)
+            .unwrap()
+            .process(plaintext.iter().copied())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hex::encode(ciphertext),
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+             5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee"
+        );
+    }
+
+    #[test]
+    fn test_counter_blocks() {
+        let nonce = [0xff; 8];
+        let counter = |layout: CounterLayout, nonce: &[u8], counter| {
+            hex::encode(layout.counter_block(nonce, counter))
+        };
+
+        assert_eq!(
+            counter(CounterLayout::LittleEndian, &nonce, 0x0102),
+            "ffffffffffffffff0201000000000000"
+        );
+        assert_eq!(
+            counter(CounterLayout::BigEndian, &nonce, 0x0102),
+            "ffffffffffffffff0000000000000102"
+        );
+        assert_eq!(
+            counter(CounterLayout::FullBlock, &[0xff; 16], 1),
+            "00000000000000000000000000000000"
+        );
+        assert_eq!(
+            counter(CounterLayout::FullBlock, &[[0; 8], [0xff; 8]].concat(), 2),
+            "00000000000000010000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_big_endian() {
+        let ciphertext = CTR::with_layout(&AES128, &key(), &[0; 8], CounterLayout::BigEndian)
+            .unwrap()
+            .process(vec![0; 32])
+            .collect::<Vec<_>>();
+
+        let mut counter_block = [0; 16];
+        counter_block[15] = 1;
+
+        assert_eq!(
+            &ciphertext[16..],
+            ECB.encrypt(&AES128, &counter_block, &key())[..16].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut rng = StdRng::seed_from_u64(1337);
+        let key = key();
+
+        for layout in [
+            CounterLayout::LittleEndian,
+            CounterLayout::BigEndian,
+            CounterLayout::FullBlock,
+        ] {
+            let mut nonce = vec![0; layout.nonce_len(16)];
+            rng.fill(&mut nonce[..]);
+
+            let reference = CTR::with_layout(&AES128, &key, &nonce, layout)
+                .unwrap()
+                .keystream()
+                .take(100)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                check_seekable_keystream(
+                    || CTR::with_layout(&AES128, &key, &nonce, layout).unwrap(),
+                    &reference,
+                    &mut rng
+                ),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn test_context_matches_ctr() {
+        let mut rng = StdRng::seed_from_u64(1337);
+        let key = Key::new(&[0x42; 32]).unwrap();
+
+        for layout in [
+            CounterLayout::LittleEndian,
+            CounterLayout::BigEndian,
+            CounterLayout::FullBlock,
+        ] {
+            let context = CtrContext::with_layout(&AES256, &key, layout);
+
+            for _ in 0..4 {
+                let mut nonce = vec![0; layout.nonce_len(16)];
+                rng.fill(&mut nonce[..]);
+
+                let offset = rng.gen_range(0..64);
+
+                assert_eq!(
+                    context
+                        .with_nonce(&nonce)
+                        .unwrap()
+                        .keystream_from(offset)
+                        .take(100)
+                        .collect::<Vec<_>>(),
+                    CTR::with_layout(&AES256, &key, &nonce, layout)
+                        .unwrap()
+                        .keystream_from(offset)
+                        .take(100)
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_context_rekey() {
+        let mut context = CtrContext::new(&AES128, &key());
+        let other_key = Key::new(&[0; 16]).unwrap();
+
+        context.rekey(&other_key);
+
+        assert_eq!(
+            context
+                .with_nonce(&[1; 8])
+                .unwrap()
+                .process(b"Hello")
+                .collect::<Vec<_>>(),
+            CTR::from_nonce(&AES128, &other_key, &Nonce::new(&[1; 8]).unwrap())
+                .process(b"Hello")
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn test_bad_nonce() {
+        assert_eq!(
+            CtrContext::new(&AES128, &key()).with_nonce(&[0; 16]).err(),
+            Some(LengthError {
+                expected: 8,
+                actual: 16
+            })
+        );
+        assert_eq!(
+            CTR::with_layout(&AES128, &key(), &[0; 8], CounterLayout::FullBlock).err(),
+            Some(LengthError {
+                expected: 16,
+                actual: 8
+            })
+        );
+    }
+
+    // Many short messages under the same key, as in the fixed-nonce CTR
+    // challenges or a session's records
+    const BENCH_MESSAGES: u64 = 100;
+    const BENCH_MESSAGE: &[u8] = &[0; 64];
+
+    #[bench]
+    fn bench_messages_ctr(b: &mut Bencher) {
+        let key = key();
+
+        b.iter(|| {
+            for nonce in 0..BENCH_MESSAGES {
+                CTR::from_nonce(&AES128, &key, &Nonce::new(&nonce.to_le_bytes()).unwrap())
+                    .process(BENCH_MESSAGE)
+                    .for_each(drop);
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_messages_context(b: &mut Bencher) {
+        let key = key();
+
+        b.iter(|| {
+            let context = CtrContext::new(&AES128, &key);
+
+            for nonce in 0..BENCH_MESSAGES {
+                context
+                    .with_nonce(&nonce.to_le_bytes())
+                    .unwrap()
+                    .process(BENCH_MESSAGE)
+                    .for_each(drop);
+            }
+        });
+    }
+}
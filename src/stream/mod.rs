@@ -6,10 +6,12 @@ use std::ops::BitXor;
 use crate::util::iter::Xorable;
 
 pub mod ctr;
+pub mod ofb;
 pub mod rng;
 pub mod xor;
 
 pub use ctr::CTR;
+pub use ofb::OFB;
 pub use rng::RNG;
 pub use xor::{RepeatingXORCipher, SingleXORCipher};
 
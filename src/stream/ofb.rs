@@ -0,0 +1,210 @@
+//! [OFB](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Output_feedback_\(OFB\))-based stream cipher.
+use crate::block::{BlockCipher, Iv, Key};
+use crate::stream::{SeekableStreamCipher, StreamCipher};
+
+/// [OFB](https://en.wikipedia.org/wiki/Block_cipher_mode_of_operation#Output_feedback_\(OFB\))-based stream cipher.
+///
+/// Generate a stream cipher from any block cipher, by repeatedly encrypting
+/// the IV.
+pub struct OFB<'k, 'c, C: BlockCipher + 'c> {
+    block_cipher: &'c C,
+    key: &'k Key<C>,
+    iv: Vec<u8>,
+}
+
+impl<'k, 'c, C: BlockCipher + 'c> OFB<'k, 'c, C> {
+    /// Generate a stream cipher from any block cipher in OFB mode.
+    pub fn new(cipher: &'c C, key: &'k Key<C>) -> OFB<'k, 'c, C> {
+        OFB::from_iv(cipher, key, &Iv::random())
+    }
+
+    /// Allows specifying the OFB IV.
+    pub fn from_iv(cipher: &'c C, key: &'k Key<C>, iv: &Iv<C>) -> OFB<'k, 'c, C> {
+        OFB {
+            block_cipher: cipher,
+            key,
+            iv: iv.to_vec(),
+        }
+    }
+
+    /// Encrypt `plaintext` with `key` using a fresh random IV, returned
+    /// prepended to the ciphertext (`IV || ciphertext`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rustopals::block::{Key, AES128};
+    /// use rustopals::stream::OFB;
+    ///
+    /// let key = Key::new(b"YELLOW SUBMARINE").unwrap();
+    ///
+    /// let encrypted = OFB::encrypt_with_random_iv(&AES128, &key, b"Hello");
+    ///
+    /// assert_eq!(encrypted.len(), 16 + 5);
+    /// assert_ne!(encrypted, OFB::encrypt_with_random_iv(&AES128, &key, b"Hello"));
+    /// assert_eq!(
+    ///     OFB::decrypt_with_prepended_iv(&AES128, &key, &encrypted),
+    ///     Some(b"Hello".to_vec()),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn encrypt_with_random_iv(cipher: &'c C, key: &'k Key<C>, plaintext: &[u8]) -> Vec<u8> {
+        let ofb = OFB::new(cipher, key);
+        let mut encrypted = ofb.iv.clone();
+
+        encrypted.extend(ofb.process(plaintext));
+
+        encrypted
+    }
+
+    /// Decrypt `IV || ciphertext` (as output by
+    /// [`encrypt_with_random_iv`](OFB::encrypt_with_random_iv)) with `key`.
+    ///
+    /// Returns `None` if `encrypted` is too short to contain an IV.
+    #[must_use]
+    pub fn decrypt_with_prepended_iv(
+        cipher: &'c C,
+        key: &'k Key<C>,
+        encrypted: &[u8],
+    ) -> Option<Vec<u8>> {
+        if encrypted.len() < C::BLOCK_SIZE {
+            return None;
+        }
+
+        let (iv, ciphertext) = encrypted.split_at(C::BLOCK_SIZE);
+        let iv = Iv::new(iv).ok()?;
+
+        Some(OFB::from_iv(cipher, key, &iv).process(ciphertext).collect())
+    }
+}
+
+impl<'k, 'c, C: BlockCipher> StreamCipher<u8, KeyStream<'k, 'c, C>> for OFB<'k, 'c, C> {
+    fn keystream(self) -> KeyStream<'k, 'c, C> {
+        KeyStream::new(self.block_cipher, self.key, self.iv)
+    }
+}
+
+/// Seeking is supported, but not random access: every block before `offset`
+/// still has to be computed.
+impl<'k, 'c, C: BlockCipher> SeekableStreamCipher<u8, KeyStream<'k, 'c, C>> for OFB<'k, 'c, C> {
+    fn keystream_from(self, offset: usize) -> KeyStream<'k, 'c, C> {
+        let mut keystream = self.keystream();
+
+        for _ in 0..offset / C::BLOCK_SIZE {
+            keystream.next_block();
+        }
+
+        if !offset.is_multiple_of(C::BLOCK_SIZE) {
+            keystream.next_block();
+            keystream.current_block_byte = offset % C::BLOCK_SIZE;
+        }
+
+        keystream
+    }
+}
+
+/*
+ *
+ */
+
+pub struct KeyStream<'k, 'c, C: BlockCipher + 'c> {
+    cipher: &'c C,
+    key: &'k Key<C>,
+
+    /// Last output block (initially, the IV)
+    register: Vec<u8>,
+    /// Whether `register` holds keystream (i.e. it is not the IV anymore)
+    started: bool,
+    /// Byte in current block
+    current_block_byte: usize,
+}
+
+impl<'k, 'c, C: BlockCipher> KeyStream<'k, 'c, C> {
+    pub const fn new(cipher: &'c C, key: &'k Key<C>, iv: Vec<u8>) -> KeyStream<'k, 'c, C> {
+        KeyStream {
+            cipher,
+            key,
+            register: iv,
+            started: false,
+            current_block_byte: 0,
+        }
+    }
+
+    /// Encrypt the register to get the next keystream block.
+    fn next_block(&mut self) {
+        self.register = self.cipher.encrypt_block(&self.register, self.key);
+        self.started = true;
+    }
+}
+
+impl<C: BlockCipher> Iterator for KeyStream<'_, '_, C> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if !self.started || self.current_block_byte == 0 {
+            self.next_block();
+        }
+
+        let val = self.register[self.current_block_byte];
+
+        self.current_block_byte = (self.current_block_byte + 1) % C::BLOCK_SIZE;
+
+        Some(val)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::OFB;
+    use crate::block::{Iv, Key, AES128};
+    use crate::stream::StreamCipher;
+    use crate::testsupport::check_seekable_keystream;
+
+    // From NIST SP 800-38A, appendix F.4.1
+    #[test]
+    fn test_vector() {
+        let key = Key::new(&hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap()).unwrap();
+        let iv = Iv::new(&hex::decode("000102030405060708090a0b0c0d0e0f").unwrap()).unwrap();
+        let plaintext =
+            hex::decode("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
+                .unwrap();
+
+        let ciphertext = OFB::from_iv(&AES128, &key, &iv)
+            .process(plaintext.iter().copied())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hex::encode(&ciphertext),
+            "3b3fd92eb72dad20333449f8e83cfb4a7789508d16918f03f53c52dac54ed825"
+        );
+        assert_eq!(
+            OFB::from_iv(&AES128, &key, &iv)
+                .process(ciphertext)
+                .collect::<Vec<_>>(),
+            plaintext
+        );
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut rng = StdRng::seed_from_u64(1337);
+
+        for _ in 0..16 {
+            let key = Key::new(&rng.gen::<[u8; 16]>()).unwrap();
+            let iv = Iv::new(&rng.gen::<[u8; 16]>()).unwrap();
+
+            let reference = OFB::from_iv(&AES128, &key, &iv)
+                .keystream()
+                .take(100)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                check_seekable_keystream(|| OFB::from_iv(&AES128, &key, &iv), &reference, &mut rng),
+                Ok(())
+            );
+        }
+    }
+}
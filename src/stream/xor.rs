@@ -10,7 +10,8 @@ use iter::Cycle;
 use num_traits::Bounded;
 
 use crate::stream::StreamCipher;
-use crate::util::TextScorer;
+use crate::util::pool::ThreadPool;
+use crate::util::{NaiveTextScorer, TextScorer};
 
 /// [XOR](https://en.wikipedia.org/wiki/XOR_cipher) cipher with a single-item key
 /// (`AAAAAAAAAAAA...`)
@@ -48,6 +49,15 @@ impl<K> SingleXORCipher<K> {
     ///
     /// Returns `Some<(key, plaintext)>` if cracked successfully, `None` otherwise.
     pub fn crack<'t, T>(scorer: &dyn TextScorer, ciphertext: &'t [T]) -> Option<(K, String)>
+    where
+        &'t T: ops::BitXor<K, Output = u8>,
+        K: Bounded + iter::Step,
+    {
+        Self::crack_scored(scorer, ciphertext).map(|(key, _, plaintext)| (key, plaintext))
+    }
+
+    /// Like [`crack`](Self::crack), but also returning the winning score.
+    fn crack_scored<'t, T>(scorer: &dyn TextScorer, ciphertext: &'t [T]) -> Option<(K, f32, String)>
     where
         &'t T: ops::BitXor<K, Output = u8>,
         K: Bounded + iter::Step,
@@ -67,7 +77,6 @@ impl<K> SingleXORCipher<K> {
                     .partial_cmp(b_score)
                     .expect("Where did this NaN come from?")
             })
-            .map(|(key, _, plaintext)| (key, plaintext))
     }
 
     /// Detect single-item key XOR cipher by frequency analysis in a list of
@@ -76,26 +85,58 @@ impl<K> SingleXORCipher<K> {
     /// Returns `Some(index, key, plaintext)` if cracked successfully, `None`
     /// otherwise.
     pub fn detect<'t, T>(
-        scorer: &dyn TextScorer,
+        scorer: &(dyn TextScorer + Sync),
         ciphertexts: &[&'t [T]],
     ) -> Option<(usize, K, String)>
     where
         &'t T: ops::BitXor<K, Output = u8>,
-        K: Bounded + iter::Step,
+        T: Sync,
+        K: Bounded + iter::Step + Send,
     {
-        ciphertexts
-            .iter()
-            .enumerate()
-            .filter_map(|(pos, ciphertext)| {
-                Self::crack(scorer, ciphertext).map(|(key, plaintext)| (pos, key, plaintext))
+        Self::detect_ranked(scorer, ciphertexts, 1)
+            .pop()
+            .map(|(pos, key, _, plaintext)| (pos, key, plaintext))
+    }
+
+    /// Like [`detect`](Self::detect), but returning the `top_n` best
+    /// candidates (at most one per ciphertext), best first.
+    ///
+    /// Useful on noisy datasets where the right ciphertext might not get the
+    /// highest score. Ciphertexts are cracked in parallel, so the `scorer` is
+    /// shared between threads.
+    ///
+    /// Returns a list of `(index, key, score, plaintext)`. Ties are ranked by
+    /// ascending index.
+    pub fn detect_ranked<'t, T>(
+        scorer: &(dyn TextScorer + Sync),
+        ciphertexts: &[&'t [T]],
+        top_n: usize,
+    ) -> Vec<(usize, K, f32, String)>
+    where
+        &'t T: ops::BitXor<K, Output = u8>,
+        T: Sync,
+        K: Bounded + iter::Step + Send,
+    {
+        let mut candidates = ThreadPool::available()
+            .parallel_map(ciphertexts, |ciphertext| {
+                Self::crack_scored(scorer, ciphertext)
             })
-            .map(|(pos, key, plaintext)| (pos, key, scorer.score(&plaintext), plaintext))
-            .max_by(|(_, _, a_score, _), (_, _, b_score, _)| {
-                a_score
-                    .partial_cmp(b_score)
-                    .expect("Where did this NaN come from?")
+            .into_iter()
+            .enumerate()
+            .filter_map(|(pos, cracked)| {
+                cracked.map(|(key, score, plaintext)| (pos, key, score, plaintext))
             })
-            .map(|(pos, key, _, plaintext)| (pos, key, plaintext))
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|(_, _, a_score, _), (_, _, b_score, _)| {
+            b_score
+                .partial_cmp(a_score)
+                .expect("Where did this NaN come from?")
+        });
+
+        candidates.truncate(top_n);
+
+        candidates
     }
 }
 
@@ -128,6 +169,20 @@ impl<'k, K> StreamCipher<&'k K, Cycle<::std::slice::Iter<'k, K>>> for RepeatingX
 impl<'k, K> RepeatingXORCipher<'k, K> {
     /// Guess key size (up to `max_size`) for a given ciphertext.
     pub fn guess_keysize<'t, T>(ciphertext: &'t [T], max_keysize: usize) -> Option<usize>
+    where
+        T: 't,
+        &'t T: ops::BitXor<&'t T>,
+        <&'t T as ops::BitXor<&'t T>>::Output: ::num_traits::PrimInt,
+    {
+        (1..=max_keysize)
+            .map(|keysize| (keysize, Self::normalized_distance(ciphertext, keysize)))
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).expect("Where did this NaN come from?"))
+            .map(|(keysize, _)| keysize)
+    }
+
+    /// Average Hamming distance between consecutive `keysize` chunks, per
+    /// bit of key.
+    fn normalized_distance<'t, T>(ciphertext: &'t [T], keysize: usize) -> f32
     where
         T: 't,
         &'t T: ops::BitXor<&'t T>,
@@ -135,45 +190,251 @@ impl<'k, K> RepeatingXORCipher<'k, K> {
     {
         use crate::util::iter::Hammingable;
 
-        (1..=max_keysize)
-            .map(|keysize| {
-                let chunks = ciphertext.chunks(keysize).collect::<Vec<_>>();
+        let chunks = ciphertext.chunks(keysize).collect::<Vec<_>>();
 
-                let distance = chunks
-                    .chunks(2)
-                    .filter(|x| x.len() == 2)
-                    .map(|pair| pair[0].iter().hamming_distance(pair[1]))
-                    .sum::<u32>() as f32
-                    / (chunks.len() as f32);
+        let distance = chunks
+            .chunks(2)
+            .filter(|x| x.len() == 2)
+            .map(|pair| pair[0].iter().hamming_distance(pair[1]))
+            .sum::<u32>() as f32
+            / (chunks.len() as f32);
 
-                (keysize, distance as f32 / keysize as f32)
-            })
-            .min_by(|(_, a), (_, b)| a.partial_cmp(b).expect("Where did this NaN come from?"))
-            .map(|(keysize, _)| keysize)
+        distance / keysize as f32
     }
 
     /// Guess key of `guessed_keysize` for a given `ciphertext`.
-    pub fn guess_key<T>(scorer: &dyn TextScorer, ciphertext: &[T], guessed_keysize: usize) -> Vec<K>
+    ///
+    /// Each key position is cracked in parallel, so the `scorer` is shared
+    /// between threads.
+    pub fn guess_key<T>(
+        scorer: &(dyn TextScorer + Sync),
+        ciphertext: &[T],
+        guessed_keysize: usize,
+    ) -> Vec<K>
     where
-        T: Clone,
+        T: Clone + Sync,
         for<'t> &'t T: ops::BitXor<K, Output = u8>,
-        K: Bounded + iter::Step,
+        K: Bounded + iter::Step + Send,
     {
         let chunks = ciphertext
             .chunks(guessed_keysize)
             .filter(|x| x.len() == guessed_keysize)
             .collect::<Vec<_>>();
 
-        (0..guessed_keysize)
-            .filter_map(|i| {
-                let mut inner_vec = Vec::new();
-
-                for block in &chunks {
-                    inner_vec.push(block[i].clone());
-                }
+        let columns = (0..guessed_keysize)
+            .map(|i| {
+                chunks
+                    .iter()
+                    .map(|block| block[i].clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
 
-                SingleXORCipher::crack(scorer, &inner_vec).map(|(key, _)| key)
+        ThreadPool::available()
+            .parallel_map(&columns, |column| {
+                SingleXORCipher::crack(scorer, column).map(|(key, _)| key)
             })
+            .into_iter()
+            .flatten()
             .collect::<Vec<_>>()
     }
 }
+
+/// A key size candidate, as ranked by
+/// [`rank_keysizes`](RepeatingXORCipher::rank_keysizes).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeysizeCandidate {
+    /// The key size.
+    pub keysize: usize,
+
+    /// Normalized Hamming distance between consecutive chunks (lower is
+    /// better).
+    pub distance: f32,
+
+    /// Average normalized byte entropy of the columns, from 0 to 1 (lower is
+    /// better).
+    pub entropy: f32,
+
+    /// Average score per byte of the columns, once cracked as single-byte
+    /// XOR (higher is better).
+    pub score: f32,
+}
+
+impl KeysizeCandidate {
+    /// How much the columns look like single-byte XOR'd English.
+    fn fitness(&self) -> f32 {
+        self.score / self.entropy
+    }
+}
+
+/// Shannon entropy of `bytes`, normalized by the maximum for their length
+/// (so that short columns are not favored).
+///
+/// Near 1 for random bytes, and lower for natural language.
+fn normalized_entropy(bytes: &[u8]) -> f32 {
+    let mut counts = [0_usize; 256];
+
+    for &byte in bytes {
+        counts[usize::from(byte)] += 1;
+    }
+
+    let len = bytes.len() as f32;
+
+    let entropy = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f32 / len;
+
+            -p * p.log2()
+        })
+        .sum::<f32>();
+
+    let max_entropy = len.min(256.0).log2();
+
+    if max_entropy > 0.0 {
+        entropy / max_entropy
+    } else {
+        0.0
+    }
+}
+
+impl RepeatingXORCipher<'_, u8> {
+    /// Rank key sizes (up to `max_keysize`) for a given `ciphertext`, best
+    /// first.
+    ///
+    /// The Hamming distance used by [`guess_keysize`](Self::guess_keysize)
+    /// is unreliable on short ciphertexts, so every key size is confirmed by
+    /// splitting the ciphertext into columns (one per key byte): with the
+    /// right key size, each column is single-byte XOR'd English, so it has a
+    /// low entropy and it cracks to a high score.
+    #[must_use]
+    pub fn rank_keysizes(ciphertext: &[u8], max_keysize: usize) -> Vec<KeysizeCandidate> {
+        let mut candidates = (1..=max_keysize.min(ciphertext.len()))
+            .map(|keysize| {
+                let columns = (0..keysize)
+                    .map(|i| {
+                        ciphertext
+                            .iter()
+                            .skip(i)
+                            .step_by(keysize)
+                            .copied()
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                let entropy = columns
+                    .iter()
+                    .map(|column| normalized_entropy(column))
+                    .sum::<f32>()
+                    / keysize as f32;
+
+                let score = columns
+                    .iter()
+                    .map(|column| {
+                        SingleXORCipher::<u8>::crack_scored(&NaiveTextScorer, column)
+                            .map_or(0.0, |(_, score, _)| score / column.len() as f32)
+                    })
+                    .sum::<f32>()
+                    / keysize as f32;
+
+                KeysizeCandidate {
+                    keysize,
+                    distance: Self::normalized_distance(ciphertext, keysize),
+                    entropy,
+                    score,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|a, b| {
+            b.fitness()
+                .partial_cmp(&a.fitness())
+                .expect("Where did this NaN come from?")
+        });
+
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{normalized_entropy, RepeatingXORCipher, SingleXORCipher};
+    use crate::stream::StreamCipher;
+    use crate::util::NaiveTextScorer;
+
+    const PLAINTEXT: &[u8] = b"I'm back and I'm ringin' the bell\nA rockin' on the mike while the fly girls yell\nIn ecstasy in the back of me\nWell that's my DJ Deshay cuttin' all them Z's\nHittin' hard and the girlies goin' crazy\nVanilla's on the mike, man I'm not lazy.\n";
+
+    #[test]
+    fn test_rank_keysizes() {
+        for key in [&b"ICE"[..], b"YELLOW"] {
+            let ciphertext = RepeatingXORCipher(key)
+                .process(PLAINTEXT)
+                .collect::<Vec<_>>();
+            let ranked = RepeatingXORCipher::rank_keysizes(&ciphertext, 40);
+
+            assert_eq!(ranked.len(), 40);
+            assert_eq!(ranked[0].keysize, key.len());
+
+            assert!(ranked
+                .windows(2)
+                .all(|pair| pair[0].fitness() >= pair[1].fitness()));
+
+            // Key sizes which are not a multiple of the right one mix up
+            // columns, so they never crack to text as English-looking
+            assert!(ranked
+                .iter()
+                .filter(|candidate| candidate.keysize % key.len() != 0)
+                .all(|candidate| candidate.score < ranked[0].score));
+        }
+    }
+
+    #[test]
+    fn test_normalized_entropy() {
+        assert!(normalized_entropy(&[0x42; 100]).abs() < f32::EPSILON);
+        assert!(normalized_entropy(&[]).abs() < f32::EPSILON);
+        assert!((normalized_entropy(&[0, 1]) - 1.0).abs() < f32::EPSILON);
+        assert!((normalized_entropy(&(0..=255).collect::<Vec<_>>()) - 1.0).abs() < f32::EPSILON);
+        assert!(normalized_entropy(PLAINTEXT) < 0.8);
+    }
+
+    #[test]
+    fn test_detect_ranked() {
+        let english = SingleXORCipher(0x42_u8)
+            .process(b"Now that the party is jumping")
+            .collect::<Vec<_>>();
+        let other_english = SingleXORCipher(0x13_u8)
+            .process(b"Cooking MC's like a pound of bacon")
+            .collect::<Vec<_>>();
+
+        // Never valid UTF-8 (every key leaves a lone high byte)
+        let noise = [0x00_u8, 0x80].repeat(16);
+
+        let ciphertexts = [&noise[..], &english[..], &other_english[..]];
+
+        let ranked = SingleXORCipher::<u8>::detect_ranked(&NaiveTextScorer, &ciphertexts, 2);
+
+        assert_eq!(ranked.len(), 2);
+        assert!(ranked[0].2 >= ranked[1].2);
+
+        let mut found = ranked
+            .iter()
+            .map(|(pos, key, _, _)| (*pos, *key))
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+
+        assert_eq!(found, [(1, 0x42), (2, 0x13)]);
+
+        let (pos, key, plaintext) =
+            SingleXORCipher::<u8>::detect(&NaiveTextScorer, &ciphertexts).unwrap();
+
+        assert_eq!((pos, key), (ranked[0].0, ranked[0].1));
+        assert_eq!(plaintext, ranked[0].3);
+
+        // Uncrackable ciphertexts are skipped
+        assert_eq!(
+            SingleXORCipher::<u8>::detect_ranked(&NaiveTextScorer, &ciphertexts, 10).len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Precomputed keys and parameters for tests and benchmarks.
+//!
+//! Generating primes is slow, so tests share these lazily-initialized
+//! fixtures instead of generating (or copy-pasting) their own.
+//!
+//! **Everything here is public knowledge. Never use it outside of tests!**
+
+use num_bigint::BigUint;
+use num_traits::Num;
+use once_cell::sync::Lazy;
+
+pub use crate::dsa::{CHALLENGE_DSA, CHALLENGE_DSA_G, CHALLENGE_DSA_P, CHALLENGE_DSA_Q};
+pub use crate::key_exchange::dh::DHGroup;
+use crate::key_exchange::dh::{NIST_BASE, NIST_MODULUS};
+use crate::rsa::{generate_rsa_keypair_from_primes, RSAPrivateKey, RSAPublicKey, E};
+
+/// Pairs of 512-bit primes (hex), for 1024-bit RSA keys.
+pub const RSA_PRIMES: [(&str, &str); 3] = [
+    (
+        "c2daf71206b801d0d0805d3cad91c650dfe06f1d92ac44c72b41f2a362ff54670639cec218353e3a54fa68f9e1469800dee331e4b71b0a02284d42b9fad9cee9",
+        "f4ea8ee535b3c80af47b902604742ad2db7af89d6e9e7bb75139839c50bf478f7fc5290d359acff41e23a680311c31afbd7aaec2814e3e73962a77036ebb608f",
+    ),
+    (
+        "994f1aa62ee83a1dc305057068ede154d13f28031570e7357825c54d9c830616ebc05e541ef6c3d595cf56769d9322d6bee65c5b1bf184fa7a51035ea2dac549",
+        "b8ee0acba8cfbfc07f6ed0bbfaf572a0af5f72cc0a75ca4add92a5e0cb7a4b03410339fc24944982b5099908e72c253f2d55238693abaf7eeebacf0b9e69304f",
+    ),
+    (
+        "905793fc80550a17ce3a7c039c5aa739b3c1ebcb0fb2dabc09f9cedf0948d03bec7b7bffc4f037abecf998167b1d008519915f602134cde70a7be44809547ca3",
+        "b853a2656a1a62fb6dce1ea2ecdbe45e184677643f7ee859196d3b311a0dcf7a95f8cff4cfa04466ee4a4489279f40384296b8ae2b07230856e0b8a2e1cc0c15",
+    ),
+];
+
+/// Get the `index`-th pair of [`RSA_PRIMES`].
+///
+/// # Panics
+///
+/// If `index` is out of bounds.
+#[must_use]
+pub fn rsa_primes(index: usize) -> (BigUint, BigUint) {
+    let (p, q) = RSA_PRIMES[index];
+
+    (
+        BigUint::from_str_radix(p, 16).unwrap(),
+        BigUint::from_str_radix(q, 16).unwrap(),
+    )
+}
+
+fn rsa_keypair(index: usize) -> (RSAPublicKey, RSAPrivateKey) {
+    let (p, q) = rsa_primes(index);
+
+    generate_rsa_keypair_from_primes(E.clone(), &p, &q).unwrap()
+}
+
+/// 1024-bit RSA keypair (`e = 3`) from the first pair of [`RSA_PRIMES`].
+pub static RSA_KEYPAIR_0: Lazy<(RSAPublicKey, RSAPrivateKey)> = Lazy::new(|| rsa_keypair(0));
+
+/// 1024-bit RSA keypair (`e = 3`) from the second pair of [`RSA_PRIMES`].
+pub static RSA_KEYPAIR_1: Lazy<(RSAPublicKey, RSAPrivateKey)> = Lazy::new(|| rsa_keypair(1));
+
+/// 1024-bit RSA keypair (`e = 3`) from the third pair of [`RSA_PRIMES`].
+pub static RSA_KEYPAIR_2: Lazy<(RSAPublicKey, RSAPrivateKey)> = Lazy::new(|| rsa_keypair(2));
+
+/// The NIST-recommended DH group.
+pub static DH_NIST: Lazy<DHGroup> = Lazy::new(|| DHGroup {
+    modulus: NIST_MODULUS.clone(),
+    base: NIST_BASE.clone(),
+});
+
+/// A tiny DH group (`p = 37`, `g = 5`) for quick (and insecure) tests.
+pub static DH_TOY: Lazy<DHGroup> = Lazy::new(|| DHGroup {
+    modulus: BigUint::from(37_usize),
+    base: BigUint::from(5_usize),
+});
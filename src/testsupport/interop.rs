@@ -0,0 +1,494 @@
+//! Interop with [OpenSSL](https://www.openssl.org/)'s `enc` command (behind
+//! the `data` feature).
+//!
+//! The vectors in `data/openssl/vectors.txt` were generated by `openssl enc`
+//! (see `data/openssl/generate.sh`) and [`check_openssl_vectors`] checks them
+//! against our AES ECB/CBC/CTR byte for byte. [`Cipher`] follows OpenSSL's
+//! conventions (PKCS#7 padding for ECB and CBC, the IV as the whole initial
+//! counter block for CTR), so its output can be decrypted by `openssl enc`,
+//! either raw or in its salted format.
+//!
+//! ```
+//! use rustopals::testsupport::interop::{check_openssl_vectors, Cipher};
+//!
+//! assert_eq!(check_openssl_vectors(), Ok(()));
+//!
+//! // openssl enc -d -aes-128-cbc -md sha256 -pass "pass:YELLOW SUBMARINE"
+//! let cipher = Cipher::from_name("aes-128-cbc").unwrap();
+//! let encrypted = cipher.encrypt_salted(b"YELLOW SUBMARINE", &[0; 8], b"Hello");
+//!
+//! assert_eq!(
+//!     cipher.decrypt_salted(b"YELLOW SUBMARINE", &encrypted),
+//!     Some(b"Hello".to_vec())
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use once_cell::sync::Lazy;
+
+use crate::block::{BlockCipher, BlockMode, Iv, Key, AES128, AES192, AES256, CBC, ECB};
+use crate::digest::{Digest, SHA256};
+use crate::stream::ctr::CounterLayout;
+use crate::stream::{StreamCipher, CTR};
+
+/// Raw vectors, as output by `data/openssl/generate.sh`.
+const VECTORS: &str = include_str!("../../data/openssl/vectors.txt");
+
+/// Magic prefixing the salt in `openssl enc`'s salted format.
+pub const SALTED_MAGIC: &[u8] = b"Salted__";
+
+/// Length (in bytes) of the salt in `openssl enc`'s salted format.
+pub const SALT_LEN: usize = 8;
+
+/// AES variant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Aes {
+    Aes128,
+    Aes192,
+    Aes256,
+}
+
+/// Mode of operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    Ecb,
+    Cbc,
+    Ctr,
+}
+
+/// An `openssl enc` cipher (e.g. `aes-128-cbc`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cipher {
+    pub aes: Aes,
+    pub mode: Mode,
+}
+
+impl fmt::Display for Cipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bits = match self.aes {
+            Aes::Aes128 => 128,
+            Aes::Aes192 => 192,
+            Aes::Aes256 => 256,
+        };
+
+        let mode = match self.mode {
+            Mode::Ecb => "ecb",
+            Mode::Cbc => "cbc",
+            Mode::Ctr => "ctr",
+        };
+
+        write!(f, "aes-{bits}-{mode}")
+    }
+}
+
+impl Cipher {
+    /// Get a cipher from its OpenSSL `name` (as in `openssl enc -<name>`).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Cipher> {
+        let (bits, mode) = name.strip_prefix("aes-")?.split_once('-')?;
+
+        let aes = match bits {
+            "128" => Aes::Aes128,
+            "192" => Aes::Aes192,
+            "256" => Aes::Aes256,
+            _ => return None,
+        };
+
+        let mode = match mode {
+            "ecb" => Mode::Ecb,
+            "cbc" => Mode::Cbc,
+            "ctr" => Mode::Ctr,
+            _ => return None,
+        };
+
+        Some(Cipher { aes, mode })
+    }
+
+    /// Length of the key (in bytes).
+    #[must_use]
+    pub const fn key_len(self) -> usize {
+        match self.aes {
+            Aes::Aes128 => AES128::KEY_SIZE,
+            Aes::Aes192 => AES192::KEY_SIZE,
+            Aes::Aes256 => AES256::KEY_SIZE,
+        }
+    }
+
+    /// Length of the IV (in bytes). ECB takes none.
+    #[must_use]
+    pub const fn iv_len(self) -> usize {
+        match self.mode {
+            Mode::Ecb => 0,
+            Mode::Cbc | Mode::Ctr => AES128::BLOCK_SIZE,
+        }
+    }
+
+    /// Encrypt `plaintext` like `openssl enc -<name> -K <key> -iv <iv>`.
+    ///
+    /// # Panics
+    ///
+    /// If `key` or `iv` have the wrong length.
+    #[must_use]
+    pub fn encrypt(self, key: &[u8], iv: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        assert_eq!(iv.len(), self.iv_len());
+
+        match self.aes {
+            Aes::Aes128 => encrypt_with(&AES128, self.mode, key, iv, plaintext),
+            Aes::Aes192 => encrypt_with(&AES192, self.mode, key, iv, plaintext),
+            Aes::Aes256 => encrypt_with(&AES256, self.mode, key, iv, plaintext),
+        }
+    }
+
+    /// Decrypt `ciphertext` like `openssl enc -d -<name> -K <key> -iv <iv>`.
+    ///
+    /// Returns `None` on bad padding.
+    ///
+    /// # Panics
+    ///
+    /// If `key` or `iv` have the wrong length.
+    #[must_use]
+    pub fn decrypt(self, key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+        assert_eq!(iv.len(), self.iv_len());
+
+        match self.aes {
+            Aes::Aes128 => decrypt_with(&AES128, self.mode, key, iv, ciphertext),
+            Aes::Aes192 => decrypt_with(&AES192, self.mode, key, iv, ciphertext),
+            Aes::Aes256 => decrypt_with(&AES256, self.mode, key, iv, ciphertext),
+        }
+    }
+
+    /// Derive the key and IV from a `password` and `salt` like `openssl enc
+    /// -md sha256` (the legacy `EVP_BytesToKey` with a single iteration).
+    #[must_use]
+    pub fn derive_key_iv(self, password: &[u8], salt: &[u8; SALT_LEN]) -> (Vec<u8>, Vec<u8>) {
+        let len = self.key_len() + self.iv_len();
+        let mut derived = Vec::with_capacity(len);
+        let mut previous = vec![];
+
+        while derived.len() < len {
+            previous = SHA256::digest_parts(&[&previous, password, salt]).into();
+            derived.extend(&previous);
+        }
+
+        derived.truncate(len);
+        let iv = derived.split_off(self.key_len());
+
+        (derived, iv)
+    }
+
+    /// Encrypt `plaintext` in the salted format of `openssl enc -<name> -md
+    /// sha256 -pass pass:<password>` (`Salted__ || salt || ciphertext`).
+    #[must_use]
+    pub fn encrypt_salted(
+        self,
+        password: &[u8],
+        salt: &[u8; SALT_LEN],
+        plaintext: &[u8],
+    ) -> Vec<u8> {
+        let (key, iv) = self.derive_key_iv(password, salt);
+
+        [SALTED_MAGIC, salt, &self.encrypt(&key, &iv, plaintext)].concat()
+    }
+
+    /// Decrypt the output of [`encrypt_salted`](Self::encrypt_salted) (or
+    /// the matching `openssl enc` command).
+    ///
+    /// Returns `None` if `encrypted` is not in the salted format, or on bad
+    /// padding.
+    #[must_use]
+    pub fn decrypt_salted(self, password: &[u8], encrypted: &[u8]) -> Option<Vec<u8>> {
+        let encrypted = encrypted.strip_prefix(SALTED_MAGIC)?;
+
+        if encrypted.len() < SALT_LEN {
+            return None;
+        }
+
+        let (salt, ciphertext) = encrypted.split_at(SALT_LEN);
+        let mut salt_bytes = [0; SALT_LEN];
+        salt_bytes.copy_from_slice(salt);
+
+        let (key, iv) = self.derive_key_iv(password, &salt_bytes);
+
+        self.decrypt(&key, &iv, ciphertext)
+    }
+}
+
+fn encrypt_with<C: BlockCipher>(
+    cipher: &C,
+    mode: Mode,
+    key: &[u8],
+    iv: &[u8],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let key = Key::new(key).expect("Wrong key length");
+
+    match mode {
+        Mode::Ecb => ECB.encrypt(cipher, plaintext, &key),
+        Mode::Cbc => {
+            CBC::new(&Iv::new(iv).expect("Wrong IV length")).encrypt(cipher, plaintext, &key)
+        },
+        Mode::Ctr => CTR::with_layout(cipher, &key, iv, CounterLayout::FullBlock)
+            .expect("Wrong IV length")
+            .process(plaintext)
+            .collect(),
+    }
+}
+
+fn decrypt_with<C: BlockCipher>(
+    cipher: &C,
+    mode: Mode,
+    key: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+) -> Option<Vec<u8>> {
+    let key = Key::new(key).expect("Wrong key length");
+
+    match mode {
+        Mode::Ecb => ECB.decrypt(cipher, ciphertext, &key).ok(),
+        Mode::Cbc => CBC::new(&Iv::new(iv).expect("Wrong IV length"))
+            .decrypt(cipher, ciphertext, &key)
+            .ok(),
+        Mode::Ctr => Some(
+            CTR::with_layout(cipher, &key, iv, CounterLayout::FullBlock)
+                .expect("Wrong IV length")
+                .process(ciphertext)
+                .collect(),
+        ),
+    }
+}
+
+/// An OpenSSL-generated vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Vector {
+    /// `openssl enc -<cipher> -K <key> -iv <iv>`
+    Raw {
+        cipher: Cipher,
+        key: Vec<u8>,
+        iv: Vec<u8>,
+        plaintext: Vec<u8>,
+        ciphertext: Vec<u8>,
+    },
+
+    /// `openssl enc -<cipher> -md sha256 -pass pass:<password>`
+    Salted {
+        cipher: Cipher,
+        password: Vec<u8>,
+        plaintext: Vec<u8>,
+        salted: Vec<u8>,
+    },
+}
+
+impl Vector {
+    /// Get the vector's cipher.
+    #[must_use]
+    pub const fn cipher(&self) -> Cipher {
+        match self {
+            Vector::Raw { cipher, .. } | Vector::Salted { cipher, .. } => *cipher,
+        }
+    }
+
+    /// Parse a `key = value` block (with hex values).
+    fn parse(block: &str) -> Option<Vector> {
+        let fields = block
+            .lines()
+            .map(|line| {
+                let (key, value) = line.split_once('=')?;
+
+                Some((key.trim(), value.trim()))
+            })
+            .collect::<Option<HashMap<_, _>>>()?;
+
+        let cipher = Cipher::from_name(fields.get("cipher")?)?;
+        let hex_field = |name| hex::decode(fields.get(name)?).ok();
+
+        if fields.contains_key("salted") {
+            Some(Vector::Salted {
+                cipher,
+                password: hex_field("password")?,
+                plaintext: hex_field("plaintext")?,
+                salted: hex_field("salted")?,
+            })
+        } else {
+            Some(Vector::Raw {
+                cipher,
+                key: hex_field("key")?,
+                iv: hex_field("iv")?,
+                plaintext: hex_field("plaintext")?,
+                ciphertext: hex_field("ciphertext")?,
+            })
+        }
+    }
+
+    /// Check our implementation against this vector.
+    ///
+    /// # Errors
+    ///
+    /// If encrypting or decrypting does not match OpenSSL.
+    pub fn check(&self) -> Result<(), InteropCheck> {
+        match self {
+            Vector::Raw {
+                cipher,
+                key,
+                iv,
+                plaintext,
+                ciphertext,
+            } => {
+                if &cipher.encrypt(key, iv, plaintext) != ciphertext {
+                    return Err(InteropCheck::Encrypt);
+                }
+
+                if cipher.decrypt(key, iv, ciphertext).as_ref() != Some(plaintext) {
+                    return Err(InteropCheck::Decrypt);
+                }
+            },
+
+            Vector::Salted {
+                cipher,
+                password,
+                plaintext,
+                salted,
+            } => {
+                let mut salt = [0; SALT_LEN];
+                let salt_start = SALTED_MAGIC.len();
+                salt.copy_from_slice(&salted[salt_start..salt_start + SALT_LEN]);
+
+                if &cipher.encrypt_salted(password, &salt, plaintext) != salted {
+                    return Err(InteropCheck::Encrypt);
+                }
+
+                if cipher.decrypt_salted(password, salted).as_ref() != Some(plaintext) {
+                    return Err(InteropCheck::Decrypt);
+                }
+            },
+        }
+
+        Ok(())
+    }
+}
+
+static OPENSSL_VECTORS: Lazy<Vec<Vector>> = Lazy::new(|| {
+    let body = VECTORS
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    body.split("\n\n")
+        .map(str::trim)
+        .filter(|block| !block.is_empty())
+        .map(|block| Vector::parse(block).expect("Invalid OpenSSL vector"))
+        .collect()
+});
+
+/// The vectors generated by OpenSSL.
+#[must_use]
+pub fn openssl_vectors() -> &'static [Vector] {
+    &OPENSSL_VECTORS
+}
+
+/// Which interop check failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InteropCheck {
+    /// Our ciphertext differs from OpenSSL's.
+    Encrypt,
+
+    /// We do not decrypt OpenSSL's ciphertext back to the plaintext.
+    Decrypt,
+}
+
+/// A mismatch with OpenSSL found by [`check_openssl_vectors`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InteropMismatch {
+    /// The failed check.
+    pub check: InteropCheck,
+
+    /// Cipher of the failed vector.
+    pub cipher: Cipher,
+
+    /// Index of the failed vector in [`openssl_vectors`].
+    pub index: usize,
+}
+
+impl fmt::Display for InteropMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let check = match self.check {
+            InteropCheck::Encrypt => "encrypt",
+            InteropCheck::Decrypt => "decrypt",
+        };
+
+        write!(
+            f,
+            "{} {} mismatch in vector {}",
+            self.cipher, check, self.index
+        )
+    }
+}
+
+impl std::error::Error for InteropMismatch {}
+
+/// Check every vector in [`openssl_vectors`].
+///
+/// # Errors
+///
+/// On the first mismatch found.
+pub fn check_openssl_vectors() -> Result<(), InteropMismatch> {
+    for (index, vector) in openssl_vectors().iter().enumerate() {
+        vector.check().map_err(|check| InteropMismatch {
+            check,
+            cipher: vector.cipher(),
+            index,
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_openssl_vectors, openssl_vectors, Aes, Cipher, Mode, Vector};
+
+    #[test]
+    fn test_openssl_vectors() {
+        let vectors = openssl_vectors();
+
+        for &aes in &[Aes::Aes128, Aes::Aes192, Aes::Aes256] {
+            for &mode in &[Mode::Ecb, Mode::Cbc, Mode::Ctr] {
+                let cipher = Cipher { aes, mode };
+
+                assert_eq!(Cipher::from_name(&cipher.to_string()), Some(cipher));
+                assert!(vectors
+                    .iter()
+                    .any(|vector| matches!(vector, Vector::Salted { .. })
+                        && vector.cipher() == cipher));
+            }
+        }
+
+        assert_eq!(check_openssl_vectors(), Ok(()));
+    }
+
+    // From `openssl enc -aes-128-cbc -md sha256 -pass pass:secret -S 0001020304050607 -P`
+    #[test]
+    fn test_derive_key_iv() {
+        let (key, iv) = Cipher::from_name("aes-128-cbc")
+            .unwrap()
+            .derive_key_iv(b"secret", &[0, 1, 2, 3, 4, 5, 6, 7]);
+
+        assert_eq!(hex::encode(key), "9407a397f39fd21ffe27f6faa71ed3f0");
+        assert_eq!(hex::encode(iv), "b2cf77f4319ad49b0c3be4f1d8e34491");
+    }
+
+    #[test]
+    fn test_bad_salted() {
+        let cipher = Cipher::from_name("aes-256-ctr").unwrap();
+
+        assert_eq!(cipher.decrypt_salted(b"password", b"Salted__1234"), None);
+        assert_eq!(
+            cipher.decrypt_salted(b"password", b"NotSalted12345678"),
+            None
+        );
+        assert_eq!(Cipher::from_name("aes-512-cbc"), None);
+        assert_eq!(Cipher::from_name("des-ede3-cbc"), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Differential-testing harnesses for tests and benchmarks.
+//!
+//! These compare an implementation against a straightforward (slow, obviously
+//! correct) reference on random inputs, catching the off-by-one class of bugs
+//! hand-written examples tend to miss.
+
+#[cfg(feature = "data")]
+pub mod interop;
+
+use std::fmt;
+
+use byteorder::{ByteOrder, LittleEndian};
+use rand::Rng;
+
+use crate::block::{BlockCipher, BlockMode, Key, Nonce, ECB};
+use crate::stream::{SeekableStreamCipher, StreamCipher, CTR};
+
+/// Which keystream check failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeystreamCheck {
+    /// `process` does not match the reference keystream.
+    Process,
+
+    /// `process_from(offset)` does not match skipping `offset` bytes of the
+    /// full keystream.
+    ProcessFrom { offset: usize },
+}
+
+/// A keystream mismatch found by [`check_seekable_keystream`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeystreamMismatch {
+    /// The failed check.
+    pub check: KeystreamCheck,
+
+    /// Length of the processed text.
+    pub len: usize,
+
+    /// Index of the first mismatching byte in the processed text.
+    pub position: usize,
+}
+
+impl fmt::Display for KeystreamMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.check {
+            KeystreamCheck::Process => write!(f, "process")?,
+            KeystreamCheck::ProcessFrom { offset } => write!(f, "process_from({offset})")?,
+        }
+
+        write!(f, " mismatch at byte {} of {}", self.position, self.len)
+    }
+}
+
+impl std::error::Error for KeystreamMismatch {}
+
+/// Index of the first differing byte, if any.
+fn first_mismatch(a: &[u8], b: &[u8]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .position(|(x, y)| x != y)
+        .or_else(|| (a.len() != b.len()).then(|| a.len().min(b.len())))
+}
+
+/// Check a seekable stream cipher against a `reference` keystream, for a
+/// random text and offset within it.
+///
+/// `cipher` must build fresh (identically keyed) instances, since processing
+/// consumes them.
+///
+/// # Errors
+///
+/// On the first mismatch found.
+pub fn check_seekable_keystream<S, IK, IS>(
+    cipher: impl Fn() -> S,
+    reference: &[u8],
+    rng: &mut impl Rng,
+) -> Result<(), KeystreamMismatch>
+where
+    S: StreamCipher<u8, IK> + SeekableStreamCipher<u8, IS>,
+    IK: IntoIterator<Item = u8>,
+    IS: IntoIterator<Item = u8>,
+{
+    let len = rng.gen_range(0..=reference.len());
+    let mut text = vec![0; len];
+    rng.fill(&mut text[..]);
+
+    let expected = text
+        .iter()
+        .zip(reference)
+        .map(|(x, k)| x ^ k)
+        .collect::<Vec<_>>();
+
+    let processed = cipher().process(text.iter().copied()).collect::<Vec<_>>();
+
+    if let Some(position) = first_mismatch(&processed, &expected) {
+        return Err(KeystreamMismatch {
+            check: KeystreamCheck::Process,
+            len,
+            position,
+        });
+    }
+
+    let offset = rng.gen_range(0..=len);
+
+    let processed = cipher()
+        .process_from(offset, text[offset..].iter().copied())
+        .collect::<Vec<_>>();
+
+    if let Some(position) = first_mismatch(&processed, &expected[offset..]) {
+        return Err(KeystreamMismatch {
+            check: KeystreamCheck::ProcessFrom { offset },
+            len: len - offset,
+            position,
+        });
+    }
+
+    Ok(())
+}
+
+/// Reference [`CTR`] keystream: ECB-encrypt `nonce || counter` blocks (with
+/// a 64-bit little-endian counter).
+#[must_use]
+pub fn ctr_reference_keystream<C: BlockCipher>(
+    cipher: &C,
+    key: &Key<C>,
+    nonce: &Nonce<C>,
+    len: usize,
+) -> Vec<u8> {
+    let blocks = len.div_ceil(C::BLOCK_SIZE);
+
+    let counter_blocks = (0..blocks as u64)
+        .flat_map(|counter| {
+            let mut counter_bytes = [0; 8];
+            LittleEndian::write_u64(&mut counter_bytes, counter);
+
+            [&nonce[..], &counter_bytes].concat()
+        })
+        .collect::<Vec<_>>();
+
+    let mut keystream = ECB.encrypt(cipher, &counter_blocks, key);
+    keystream.truncate(len);
+
+    keystream
+}
+
+/// Run [`check_seekable_keystream`] on [`CTR`] over `cipher` for `rounds`
+/// random keys and nonces, with texts of up to `max_len` bytes.
+///
+/// # Errors
+///
+/// On the first mismatch found.
+pub fn fuzz_ctr<C: BlockCipher>(
+    cipher: &C,
+    rounds: usize,
+    max_len: usize,
+    rng: &mut impl Rng,
+) -> Result<(), KeystreamMismatch> {
+    for _ in 0..rounds {
+        let key = Key::from_rng(rng);
+        let nonce = Nonce::from_rng(rng);
+
+        let reference = ctr_reference_keystream(cipher, &key, &nonce, max_len);
+
+        check_seekable_keystream(|| CTR::from_nonce(cipher, &key, &nonce), &reference, rng)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{check_seekable_keystream, fuzz_ctr, KeystreamCheck};
+    use crate::block::AES128;
+    use crate::stream::{SeekableStreamCipher, StreamCipher};
+
+    #[test]
+    fn test_fuzz_ctr() {
+        let mut rng = StdRng::seed_from_u64(1337);
+
+        assert_eq!(fuzz_ctr(&AES128, 64, 100, &mut rng), Ok(()));
+    }
+
+    /// Keystream `0, 1, 2...` whose seek is off by one.
+    struct OffByOne;
+
+    impl StreamCipher<u8, std::ops::RangeFrom<u8>> for OffByOne {
+        fn keystream(self) -> std::ops::RangeFrom<u8> {
+            0..
+        }
+    }
+
+    impl SeekableStreamCipher<u8, std::ops::RangeFrom<u8>> for OffByOne {
+        fn keystream_from(self, offset: usize) -> std::ops::RangeFrom<u8> {
+            (offset as u8 + 1)..
+        }
+    }
+
+    #[test]
+    fn test_catches_off_by_one() {
+        let reference = (0..100).collect::<Vec<u8>>();
+        let mut rng = StdRng::seed_from_u64(1337);
+
+        let mismatch = (0..100)
+            .find_map(|_| check_seekable_keystream(|| OffByOne, &reference, &mut rng).err())
+            .unwrap();
+
+        assert!(matches!(mismatch.check, KeystreamCheck::ProcessFrom { .. }));
+        assert_eq!(mismatch.position, 0);
+    }
+}
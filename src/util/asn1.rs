@@ -0,0 +1,563 @@
+//! Minimal [ASN.1](https://en.wikipedia.org/wiki/ASN.1)
+//! [DER](https://en.wikipedia.org/wiki/X.690#DER_encoding) encoding and
+//! parsing.
+//!
+//! Only covers what RSA needs: the PKCS#1 `DigestInfo` structure for
+//! signatures, and the `INTEGER`s, `BIT STRING`s and `SEQUENCE`s making up
+//! [serialized keys](crate::rsa::pkcs8).
+//!
+//! ```text
+//! DigestInfo ::= SEQUENCE {
+//!     digestAlgorithm SEQUENCE { algorithm OBJECT IDENTIFIER, parameters NULL },
+//!     digest OCTET STRING
+//! }
+//! ```
+
+use num_bigint::BigUint;
+
+/// `INTEGER` tag.
+pub const INTEGER: u8 = 0x02;
+
+/// `BIT STRING` tag.
+pub const BIT_STRING: u8 = 0x03;
+
+/// `OCTET STRING` tag.
+pub const OCTET_STRING: u8 = 0x04;
+
+/// `NULL` tag.
+pub const NULL: u8 = 0x05;
+
+/// `OBJECT IDENTIFIER` tag.
+pub const OBJECT_IDENTIFIER: u8 = 0x06;
+
+/// `SEQUENCE` (constructed) tag.
+pub const SEQUENCE: u8 = 0x30;
+
+/// Maximum length of a [`DigestInfoPrefix`].
+pub const MAX_DIGEST_INFO_PREFIX_LEN: usize = 32;
+
+/// Length of the DER encoding of a `len` length field.
+const fn length_len(len: usize) -> usize {
+    if len < 0x80 {
+        return 1;
+    }
+
+    1 + (usize::BITS - len.leading_zeros()).div_ceil(8) as usize
+}
+
+/// Number of base-128 digits in an OID arc.
+const fn arc_len(mut arc: u64) -> usize {
+    let mut len = 1;
+
+    while arc >= 0x80 {
+        arc >>= 7;
+        len += 1;
+    }
+
+    len
+}
+
+/// Length of the contents of an `OBJECT IDENTIFIER` with the given `arcs`.
+const fn oid_len(arcs: &[u64]) -> usize {
+    let mut len = arc_len(arcs[0] * 40 + arcs[1]);
+    let mut i = 2;
+
+    while i < arcs.len() {
+        len += arc_len(arcs[i]);
+        i += 1;
+    }
+
+    len
+}
+
+/// The DER encoding of a `DigestInfo` up to (and excluding) the digest
+/// bytes, i.e. the `ASN1_PREFIX` prepended to a hash in PKCS#1 v1.5
+/// signatures.
+///
+/// Built in `const` contexts so digests only need to declare their OID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DigestInfoPrefix {
+    bytes: [u8; MAX_DIGEST_INFO_PREFIX_LEN],
+    len: usize,
+}
+
+impl DigestInfoPrefix {
+    /// Encode the prefix for a digest with `oid` arcs and `digest_len` bytes
+    /// of output.
+    ///
+    /// An empty `oid` (for digests without one) gives an empty prefix.
+    ///
+    /// # Panics
+    ///
+    /// If the OID has a single arc, or if the prefix would be longer than
+    /// [`MAX_DIGEST_INFO_PREFIX_LEN`].
+    #[must_use]
+    pub const fn new(oid: &[u64], digest_len: usize) -> Self {
+        let prefix = DigestInfoPrefix {
+            bytes: [0; MAX_DIGEST_INFO_PREFIX_LEN],
+            len: 0,
+        };
+
+        if oid.is_empty() {
+            return prefix;
+        }
+
+        let oid_len = oid_len(oid);
+        let oid_tlv_len = 1 + length_len(oid_len) + oid_len;
+        let algorithm_len = oid_tlv_len + 2;
+        let algorithm_tlv_len = 1 + length_len(algorithm_len) + algorithm_len;
+        let digest_tlv_len = 1 + length_len(digest_len) + digest_len;
+
+        let mut prefix = prefix
+            .push(SEQUENCE)
+            .push_length(algorithm_tlv_len + digest_tlv_len)
+            .push(SEQUENCE)
+            .push_length(algorithm_len)
+            .push(OBJECT_IDENTIFIER)
+            .push_length(oid_len)
+            .push_arc(oid[0] * 40 + oid[1]);
+
+        let mut i = 2;
+
+        while i < oid.len() {
+            prefix = prefix.push_arc(oid[i]);
+            i += 1;
+        }
+
+        prefix
+            .push(NULL)
+            .push(0x00)
+            .push(OCTET_STRING)
+            .push_length(digest_len)
+    }
+
+    /// Get the encoded bytes.
+    #[must_use]
+    pub const fn as_slice(&self) -> &[u8] {
+        self.bytes.split_at(self.len).0
+    }
+
+    const fn push(mut self, byte: u8) -> Self {
+        self.bytes[self.len] = byte;
+        self.len += 1;
+        self
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    const fn push_length(mut self, len: usize) -> Self {
+        let len_len = length_len(len);
+
+        if len_len == 1 {
+            return self.push(len as u8);
+        }
+
+        self = self.push(0x80 | (len_len - 1) as u8);
+
+        let mut i = len_len - 1;
+
+        while i > 0 {
+            i -= 1;
+            self = self.push((len >> (i * 8)) as u8);
+        }
+
+        self
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    const fn push_arc(mut self, arc: u64) -> Self {
+        let mut i = arc_len(arc);
+
+        while i > 1 {
+            i -= 1;
+            self = self.push(0x80 | (arc >> (i * 7)) as u8 & 0x7f);
+        }
+
+        self.push(arc as u8 & 0x7f)
+    }
+}
+
+/// Encode a DER TLV with some `tag` and `contents`.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn encode_tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let len = contents.len();
+    let len_len = length_len(len);
+
+    let mut tlv = Vec::with_capacity(1 + len_len + len);
+
+    tlv.push(tag);
+
+    if len_len == 1 {
+        tlv.push(len as u8);
+    } else {
+        tlv.push(0x80 | (len_len - 1) as u8);
+        tlv.extend((0..len_len - 1).rev().map(|i| (len >> (i * 8)) as u8));
+    }
+
+    tlv.extend_from_slice(contents);
+
+    tlv
+}
+
+/// Encode a `SEQUENCE` of already encoded `items`.
+#[must_use]
+pub fn encode_sequence(items: &[&[u8]]) -> Vec<u8> {
+    encode_tlv(SEQUENCE, &items.concat())
+}
+
+/// Encode a (non-negative) `INTEGER`.
+#[must_use]
+pub fn encode_integer(n: &BigUint) -> Vec<u8> {
+    let mut bytes = n.to_bytes_be();
+
+    // Two's complement: a leading 1 bit would make it negative
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0x00);
+    }
+
+    encode_tlv(INTEGER, &bytes)
+}
+
+/// Encode an `OBJECT IDENTIFIER` with the given `arcs`.
+///
+/// # Panics
+///
+/// If there are less than two arcs.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn encode_oid(arcs: &[u64]) -> Vec<u8> {
+    let mut contents = Vec::with_capacity(oid_len(arcs));
+
+    for arc in std::iter::once(arcs[0] * 40 + arcs[1]).chain(arcs[2..].iter().copied()) {
+        contents.extend((0..arc_len(arc)).rev().map(|i| {
+            let continuation = if i > 0 { 0x80 } else { 0x00 };
+
+            continuation | (arc >> (i * 7)) as u8 & 0x7f
+        }));
+    }
+
+    encode_tlv(OBJECT_IDENTIFIER, &contents)
+}
+
+/// Parse a single DER TLV off the start of `input`.
+///
+/// Only supports single-byte tags, and rejects non-minimal (i.e. BER) length
+/// encodings.
+///
+/// Returns `(tag, contents, rest)`, or `None` if `input` is malformed.
+#[must_use]
+pub fn parse_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first_len, mut input) = input.split_first()?;
+
+    let len = if first_len < 0x80 {
+        usize::from(first_len)
+    } else {
+        let len_len = usize::from(first_len & 0x7f);
+
+        // Indefinite lengths are BER-only
+        if len_len == 0 || len_len > std::mem::size_of::<usize>() || input.len() < len_len {
+            return None;
+        }
+
+        let (len_bytes, rest) = input.split_at(len_len);
+        input = rest;
+
+        // Minimal encoding: no leading zeros, and only for lengths >= 0x80
+        if len_bytes[0] == 0x00 {
+            return None;
+        }
+
+        let len = len_bytes
+            .iter()
+            .fold(0, |len, &byte| (len << 8) | usize::from(byte));
+
+        if len < 0x80 {
+            return None;
+        }
+
+        len
+    };
+
+    if input.len() < len {
+        return None;
+    }
+
+    let (contents, rest) = input.split_at(len);
+
+    Some((tag, contents, rest))
+}
+
+/// Parse a TLV with an `expected_tag` off the start of `input`.
+///
+/// Returns `(contents, rest)`.
+#[must_use]
+pub fn parse_expected(expected_tag: u8, input: &[u8]) -> Option<(&[u8], &[u8])> {
+    match parse_tlv(input)? {
+        (tag, contents, rest) if tag == expected_tag => Some((contents, rest)),
+        _ => None,
+    }
+}
+
+/// Parse the `contents` of a (non-negative) `INTEGER`.
+///
+/// Rejects negative and non-minimal (i.e. with redundant leading zeros)
+/// encodings.
+#[must_use]
+pub fn parse_integer(contents: &[u8]) -> Option<BigUint> {
+    match contents {
+        [] => None,
+        [first, ..] if first & 0x80 != 0 => None,
+        [0x00, second, ..] if second & 0x80 == 0 => None,
+        _ => Some(BigUint::from_bytes_be(contents)),
+    }
+}
+
+/// Parse the `contents` of an `OBJECT IDENTIFIER` into its arcs.
+#[must_use]
+pub fn parse_oid(contents: &[u8]) -> Option<Vec<u64>> {
+    let mut arcs = Vec::new();
+    let mut arc = 0_u64;
+
+    for (i, &byte) in contents.iter().enumerate() {
+        // Minimal encoding: no leading 0x80 digits
+        let is_first_digit = i == 0 || contents[i - 1] & 0x80 == 0;
+
+        if is_first_digit && byte == 0x80 {
+            return None;
+        }
+
+        if arc.leading_zeros() < 7 {
+            return None;
+        }
+
+        arc = (arc << 7) | u64::from(byte & 0x7f);
+
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (arc / 40).min(2);
+                arcs.push(first);
+                arcs.push(arc - first * 40);
+            } else {
+                arcs.push(arc);
+            }
+
+            arc = 0;
+        }
+    }
+
+    // Empty, or truncated in the middle of an arc
+    if contents.last().is_none_or(|&byte| byte & 0x80 != 0) {
+        return None;
+    }
+
+    Some(arcs)
+}
+
+/// Parse a DER `DigestInfo` spanning the whole `input`.
+///
+/// Returns `(oid, digest)`.
+#[must_use]
+pub fn parse_digest_info(input: &[u8]) -> Option<(Vec<u64>, &[u8])> {
+    let (digest_info, rest) = parse_expected(SEQUENCE, input)?;
+
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let (algorithm, digest_info) = parse_expected(SEQUENCE, digest_info)?;
+    let (oid, algorithm) = parse_expected(OBJECT_IDENTIFIER, algorithm)?;
+    let (parameters, algorithm) = parse_expected(NULL, algorithm)?;
+
+    if !parameters.is_empty() || !algorithm.is_empty() {
+        return None;
+    }
+
+    let (digest, digest_info) = parse_expected(OCTET_STRING, digest_info)?;
+
+    if !digest_info.is_empty() {
+        return None;
+    }
+
+    Some((parse_oid(oid)?, digest))
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{
+        encode_integer, encode_oid, encode_tlv, parse_digest_info, parse_expected, parse_integer,
+        parse_oid, parse_tlv, DigestInfoPrefix, INTEGER, OBJECT_IDENTIFIER,
+    };
+    use crate::digest::{Digest, MD4, SHA1, SHA256, SHA512};
+
+    const SHA256_OID: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 1];
+
+    // From RFC 8017, section 9.2
+    #[test]
+    fn test_digest_info_prefix() {
+        assert_eq!(
+            MD4::ASN1_PREFIX,
+            [
+                0x30, 0x20, 0x30, 0x0c, 0x06, 0x08, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x04,
+                0x05, 0x00, 0x04, 0x10,
+            ]
+        );
+
+        assert_eq!(
+            SHA1::ASN1_PREFIX,
+            [
+                0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04,
+                0x14,
+            ]
+        );
+
+        assert_eq!(
+            SHA256::ASN1_PREFIX,
+            [
+                0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+                0x01, 0x05, 0x00, 0x04, 0x20,
+            ]
+        );
+
+        assert_eq!(
+            SHA512::ASN1_PREFIX,
+            [
+                0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+                0x03, 0x05, 0x00, 0x04, 0x40,
+            ]
+        );
+
+        assert_eq!(DigestInfoPrefix::new(&[], 32).as_slice(), []);
+    }
+
+    #[test]
+    fn test_digest_info_prefix_long_length() {
+        let prefix = DigestInfoPrefix::new(SHA256_OID, 0x100);
+
+        assert_eq!(
+            prefix.as_slice(),
+            [
+                0x30, 0x82, 0x01, 0x13, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03,
+                0x04, 0x02, 0x01, 0x05, 0x00, 0x04, 0x82, 0x01, 0x00,
+            ]
+        );
+
+        let digest_info = [prefix.as_slice(), &[0x42; 0x100]].concat();
+
+        assert_eq!(
+            parse_digest_info(&digest_info),
+            Some((SHA256_OID.to_vec(), &[0x42; 0x100][..]))
+        );
+    }
+
+    #[test]
+    fn test_parse_digest_info() {
+        let hash = SHA256::digest(b"MESSAGE");
+        let digest_info = [SHA256::ASN1_PREFIX, &hash].concat();
+
+        assert_eq!(
+            parse_digest_info(&digest_info),
+            Some((SHA256_OID.to_vec(), &hash[..]))
+        );
+
+        // Trailing garbage
+        assert_eq!(
+            parse_digest_info(&[&digest_info[..], &[0x00]].concat()),
+            None
+        );
+
+        // Truncated
+        assert_eq!(
+            parse_digest_info(&digest_info[..digest_info.len() - 1]),
+            None
+        );
+
+        // Non-NULL parameters
+        let mut bad_parameters = digest_info;
+        bad_parameters[17] = 0x01;
+
+        assert_eq!(parse_digest_info(&bad_parameters), None);
+    }
+
+    #[test]
+    fn test_parse_tlv_rejects_ber() {
+        assert_eq!(
+            parse_tlv(&[0x04, 0x01, 0xff]),
+            Some((0x04, &[0xff][..], &[][..]))
+        );
+
+        // Indefinite length
+        assert_eq!(parse_tlv(&[0x04, 0x80, 0xff, 0x00, 0x00]), None);
+
+        // Long form for a short length
+        assert_eq!(parse_tlv(&[0x04, 0x81, 0x01, 0xff]), None);
+
+        // Leading zero in long form
+        assert_eq!(parse_tlv(&[0x04, 0x82, 0x00, 0x80]), None);
+    }
+
+    #[test]
+    fn test_parse_oid() {
+        assert_eq!(
+            parse_oid(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x02, 0x04]),
+            Some(vec![1, 2, 840, 113_549, 2, 4])
+        );
+
+        // Non-minimal arc
+        assert_eq!(parse_oid(&[0x2a, 0x80, 0x01]), None);
+
+        // Truncated arc
+        assert_eq!(parse_oid(&[0x2a, 0x86]), None);
+
+        assert_eq!(parse_oid(&[]), None);
+    }
+
+    #[test]
+    fn test_encode_tlv() {
+        assert_eq!(encode_tlv(0x04, &[0xff]), [0x04, 0x01, 0xff]);
+        assert_eq!(encode_tlv(0x04, &[0x00; 0x80])[..3], [0x04, 0x81, 0x80]);
+        assert_eq!(
+            encode_tlv(0x04, &[0x00; 0x100])[..4],
+            [0x04, 0x82, 0x01, 0x00]
+        );
+
+        let tlv = encode_tlv(0x04, &[0x42; 0x1234]);
+
+        assert_eq!(parse_tlv(&tlv), Some((0x04, &[0x42; 0x1234][..], &[][..])));
+    }
+
+    #[test]
+    fn test_integer() {
+        for (n, encoded) in [
+            (0_u32, &[0x02, 0x01, 0x00][..]),
+            (0x7f, &[0x02, 0x01, 0x7f]),
+            (0x80, &[0x02, 0x02, 0x00, 0x80]),
+            (0x0100, &[0x02, 0x02, 0x01, 0x00]),
+        ] {
+            assert_eq!(encode_integer(&BigUint::from(n)), encoded);
+
+            let (contents, _) = parse_expected(INTEGER, encoded).unwrap();
+            assert_eq!(parse_integer(contents), Some(BigUint::from(n)));
+        }
+
+        // Negative
+        assert_eq!(parse_integer(&[0x80]), None);
+
+        // Redundant leading zero
+        assert_eq!(parse_integer(&[0x00, 0x7f]), None);
+
+        assert_eq!(parse_integer(&[]), None);
+    }
+
+    #[test]
+    fn test_encode_oid() {
+        let encoded = encode_oid(SHA256_OID);
+
+        assert_eq!(&encoded[2..], &SHA256::ASN1_PREFIX[6..15]);
+
+        let (contents, _) = parse_expected(OBJECT_IDENTIFIER, &encoded).unwrap();
+        assert_eq!(parse_oid(contents), Some(SHA256_OID.to_vec()));
+    }
+}
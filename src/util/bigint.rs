@@ -0,0 +1,165 @@
+//! Thin facade over [arbitrary-precision](https://en.wikipedia.org/wiki/Arbitrary-precision_arithmetic)
+//! unsigned integer backends.
+//!
+//! Asymmetric primitives only need a handful of operations on big integers
+//! (modular exponentiation and inversion, random sampling and byte
+//! conversions). Code written against [`BigIntBackend`] can switch backends
+//! (e.g. a fixed-width, constant-time implementation) without touching its
+//! call sites. [`BigUint`] (from `num-bigint`) is the default backend.
+//!
+//! Textbook [RSA](crate::rsa::textbook_rsa) and
+//! [Diffie-Hellman](crate::key_exchange::dh::dh_modpow) are written against
+//! it, so they also run on the fixed-width [`U2048`](super::fixed_uint::U2048)
+//! and [`U4096`](super::fixed_uint::U4096).
+
+use std::fmt::Debug;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::Zero;
+
+use super::binary_inv_mod;
+use crate::rand::rng;
+
+/// Operations needed from an unsigned big-integer backend.
+///
+/// Every modular operation expects its operands already reduced modulo
+/// `modulus` (i.e. in `[0, modulus)`).
+pub trait BigIntBackend: Clone + Eq + Debug + Sized {
+    /// Parse a big-endian byte representation.
+    ///
+    /// Returns `None` if the value does not fit in the backend.
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self>;
+
+    /// Minimal big-endian byte representation (empty for zero).
+    fn to_bytes_be(&self) -> Vec<u8>;
+
+    /// Convert from a `u64`.
+    fn from_u64(x: u64) -> Self;
+
+    /// Whether this is zero.
+    fn is_zero(&self) -> bool;
+
+    /// Whether `self < other`.
+    fn less_than(&self, other: &Self) -> bool;
+
+    /// Compute `(self + other) mod modulus`.
+    #[must_use]
+    fn add_mod(&self, other: &Self, modulus: &Self) -> Self;
+
+    /// Compute `(self * other) mod modulus`.
+    #[must_use]
+    fn mul_mod(&self, other: &Self, modulus: &Self) -> Self;
+
+    /// Compute `self^exponent mod modulus`.
+    #[must_use]
+    fn modpow(&self, exponent: &Self, modulus: &Self) -> Self;
+
+    /// Compute `self^-1 mod modulus`, if it exists.
+    fn modinv(&self, modulus: &Self) -> Option<Self>;
+
+    /// Generate a uniformly random value in `[0, bound)`.
+    #[must_use]
+    fn random_below(bound: &Self) -> Self;
+}
+
+impl BigIntBackend for BigUint {
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self> {
+        Some(BigUint::from_bytes_be(bytes))
+    }
+
+    fn to_bytes_be(&self) -> Vec<u8> {
+        if Zero::is_zero(self) {
+            Vec::new()
+        } else {
+            BigUint::to_bytes_be(self)
+        }
+    }
+
+    fn from_u64(x: u64) -> Self {
+        BigUint::from(x)
+    }
+
+    fn is_zero(&self) -> bool {
+        Zero::is_zero(self)
+    }
+
+    fn less_than(&self, other: &Self) -> bool {
+        self < other
+    }
+
+    fn add_mod(&self, other: &Self, modulus: &Self) -> Self {
+        (self + other) % modulus
+    }
+
+    fn mul_mod(&self, other: &Self, modulus: &Self) -> Self {
+        (self * other) % modulus
+    }
+
+    fn modpow(&self, exponent: &Self, modulus: &Self) -> Self {
+        BigUint::modpow(self, exponent, modulus)
+    }
+
+    fn modinv(&self, modulus: &Self) -> Option<Self> {
+        binary_inv_mod(self, modulus)
+    }
+
+    fn random_below(bound: &Self) -> Self {
+        rng().gen_biguint_below(bound)
+    }
+}
+
+/// Convert `x` between backends.
+///
+/// Returns `None` if the value does not fit in `B`.
+#[must_use]
+pub fn convert<A: BigIntBackend, B: BigIntBackend>(x: &A) -> Option<B> {
+    B::from_bytes_be(&x.to_bytes_be())
+}
+
+/// Generic checks for [`BigIntBackend`] implementations, comparing them to
+/// [`BigUint`] on `modulus` (odd, and at least 64 bits), over `rounds`
+/// random operand pairs.
+#[cfg(test)]
+pub(crate) fn check_backend<B: BigIntBackend>(modulus: &BigUint, rounds: usize) {
+    let to_backend = |x: &BigUint| B::from_bytes_be(&x.to_bytes_be()).unwrap();
+    let to_biguint = |x: &B| BigUint::from_bytes_be(&x.to_bytes_be());
+
+    let n = to_backend(modulus);
+
+    assert!(B::from_u64(0).is_zero());
+    assert_eq!(B::from_u64(0).to_bytes_be(), Vec::<u8>::new());
+    assert_eq!(B::from_u64(0x1234).to_bytes_be(), vec![0x12, 0x34]);
+
+    for _ in 0..rounds {
+        let a = rng().gen_biguint_below(modulus);
+        let b = rng().gen_biguint_below(modulus);
+        let (a_b, b_b) = (to_backend(&a), to_backend(&b));
+
+        assert!(to_biguint(&B::random_below(&n)) < *modulus);
+
+        assert_eq!(a_b.less_than(&b_b), a < b);
+        assert!(!a_b.less_than(&a_b));
+
+        assert_eq!(to_biguint(&a_b.add_mod(&b_b, &n)), (&a + &b) % modulus);
+        assert_eq!(to_biguint(&a_b.mul_mod(&b_b, &n)), (&a * &b) % modulus);
+        assert_eq!(to_biguint(&a_b.modpow(&b_b, &n)), a.modpow(&b, modulus));
+        assert_eq!(
+            a_b.modinv(&n).map(|x| to_biguint(&x)),
+            binary_inv_mod(&a, modulus)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::check_backend;
+    use crate::testkeys::RSA_KEYPAIR_0;
+
+    #[test]
+    fn test_biguint_backend() {
+        check_backend::<BigUint>(&RSA_KEYPAIR_0.0.n, 20);
+        check_backend::<BigUint>(&BigUint::from(u64::MAX - 58), 20);
+    }
+}
@@ -0,0 +1,465 @@
+//! Big integer multiplication on little-endian 64-bit limb slices.
+//!
+//! [`mul`] picks between [schoolbook](https://en.wikipedia.org/wiki/Multiplication_algorithm#Long_multiplication),
+//! [Karatsuba](https://en.wikipedia.org/wiki/Karatsuba_algorithm) and
+//! [Toom-3](https://en.wikipedia.org/wiki/Toom%E2%80%93Cook_multiplication)
+//! multiplication depending on the operand length (see
+//! [`KARATSUBA_THRESHOLD`] and [`TOOM3_THRESHOLD`]). This is what
+//! [`FixedUint`](super::fixed_uint::FixedUint) uses for its products.
+//!
+//! Control flow only depends on operand lengths: Toom-3 intermediates
+//! (which can be negative) are kept in fixed-length two's complement, and
+//! signs are handled by masking.
+//!
+//! The `bench_*` benchmarks compare every algorithm (and `num-bigint`) at
+//! 2048, 4096 and 8192 bits, and are what the thresholds are based on:
+//! Karatsuba only starts paying off around 4096-bit operands, and Toom-3
+//! does not beat it yet at 8192 bits. `num-bigint` is still faster at every
+//! size.
+
+/// Limbs from which [`mul`] switches from schoolbook to Karatsuba.
+pub const KARATSUBA_THRESHOLD: usize = 48;
+
+/// Limbs from which [`mul`] switches from Karatsuba to Toom-3.
+pub const TOOM3_THRESHOLD: usize = 160;
+
+/// Multiply equal-length `a` and `b` into `out` (twice as long), picking the
+/// algorithm by length.
+///
+/// # Panics
+///
+/// If lengths do not match.
+pub fn mul(a: &[u64], b: &[u64], out: &mut [u64]) {
+    if a.len() >= TOOM3_THRESHOLD {
+        mul_toom3(a, b, out);
+    } else if a.len() >= KARATSUBA_THRESHOLD {
+        mul_karatsuba(a, b, out);
+    } else {
+        mul_schoolbook(a, b, out);
+    }
+}
+
+/// Multiply equal-length `a` and `b` into `out` (twice as long), using
+/// schoolbook multiplication.
+///
+/// # Panics
+///
+/// If lengths do not match.
+pub fn mul_schoolbook(a: &[u64], b: &[u64], out: &mut [u64]) {
+    check_lengths(a, b, out);
+
+    out.fill(0);
+
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0;
+
+        for (j, &y) in b.iter().enumerate() {
+            let sum = u128::from(out[i + j]) + u128::from(x) * u128::from(y) + carry;
+            out[i + j] = sum as u64;
+            carry = sum >> 64;
+        }
+
+        out[i + b.len()] = carry as u64;
+    }
+}
+
+/// Multiply equal-length `a` and `b` into `out` (twice as long), using one
+/// Karatsuba step (sub-products go through [`mul`]).
+///
+/// # Panics
+///
+/// If lengths do not match.
+pub fn mul_karatsuba(a: &[u64], b: &[u64], out: &mut [u64]) {
+    check_lengths(a, b, out);
+
+    if a.len() < 2 {
+        return mul_schoolbook(a, b, out);
+    }
+
+    let low = a.len() / 2;
+    let high = a.len() - low;
+
+    let (a_0, a_1) = a.split_at(low);
+    let (b_0, b_1) = b.split_at(low);
+
+    let mut z_0 = vec![0; 2 * low];
+    mul(a_0, b_0, &mut z_0);
+
+    let mut z_2 = vec![0; 2 * high];
+    mul(a_1, b_1, &mut z_2);
+
+    // (a_0 + a_1) * (b_0 + b_1), with an extra limb for each sum's carry
+    let mut a_sum = zero_extend(a_1, high + 1);
+    add_assign(&mut a_sum, a_0);
+
+    let mut b_sum = zero_extend(b_1, high + 1);
+    add_assign(&mut b_sum, b_0);
+
+    let mut z_1 = vec![0; 2 * high + 2];
+    mul(&a_sum, &b_sum, &mut z_1);
+
+    sub_assign(&mut z_1, &z_0);
+    sub_assign(&mut z_1, &z_2);
+
+    out[..2 * low].copy_from_slice(&z_0);
+    out[2 * low..].copy_from_slice(&z_2);
+    add_at(out, low, &z_1);
+}
+
+/// Multiply equal-length `a` and `b` into `out` (twice as long), using one
+/// Toom-3 step (sub-products go through [`mul`]).
+///
+/// Evaluates at `0`, `1`, `-1`, `-2` and infinity, and interpolates with
+/// Bodrato's sequence.
+///
+/// # Panics
+///
+/// If lengths do not match.
+pub fn mul_toom3(a: &[u64], b: &[u64], out: &mut [u64]) {
+    check_lengths(a, b, out);
+
+    if a.len() < 3 {
+        return mul_schoolbook(a, b, out);
+    }
+
+    let k = a.len().div_ceil(3);
+    // Interpolation needs room for `~50 * 2^(128k)`, plus the sign
+    let width = 2 * k + 3;
+
+    let [a_0, a_1, a_2] = split_3(a, k);
+    let [b_0, b_1, b_2] = split_3(b, k);
+
+    let [a_pos_1, a_neg_1, a_neg_2] = evaluate(&a_0, &a_1, &a_2);
+    let [b_pos_1, b_neg_1, b_neg_2] = evaluate(&b_0, &b_1, &b_2);
+
+    let mut r_0 = vec![0; 2 * k];
+    mul(&a_0, &b_0, &mut r_0);
+    let r_0 = zero_extend(&r_0, width);
+
+    let mut r_inf = vec![0; 2 * k];
+    mul(&a_2, &b_2, &mut r_inf);
+    let r_inf = zero_extend(&r_inf, width);
+
+    let r_pos_1 = mul_signed(&a_pos_1, &b_pos_1, width);
+    let r_neg_1 = mul_signed(&a_neg_1, &b_neg_1, width);
+    let r_neg_2 = mul_signed(&a_neg_2, &b_neg_2, width);
+
+    // r_3 = (r(-2) - r(1)) / 3
+    let mut r_3 = r_neg_2;
+    sub_assign(&mut r_3, &r_pos_1);
+    div_exact_3_assign(&mut r_3);
+
+    // r_1 = (r(1) - r(-1)) / 2
+    let mut r_1 = r_pos_1;
+    sub_assign(&mut r_1, &r_neg_1);
+    sar_1_assign(&mut r_1);
+
+    // r_2 = r(-1) - r(0)
+    let mut r_2 = r_neg_1;
+    sub_assign(&mut r_2, &r_0);
+
+    // r_3 = (r_2 - r_3) / 2 + 2 * r(inf)
+    let mut difference = r_2.clone();
+    sub_assign(&mut difference, &r_3);
+    sar_1_assign(&mut difference);
+    r_3 = difference;
+    add_assign(&mut r_3, &r_inf);
+    add_assign(&mut r_3, &r_inf);
+
+    // r_2 = r_2 + r_1 - r(inf)
+    add_assign(&mut r_2, &r_1);
+    sub_assign(&mut r_2, &r_inf);
+
+    // r_1 = r_1 - r_3
+    sub_assign(&mut r_1, &r_3);
+
+    // Every coefficient is now non-negative, and every term fits in `out`
+    out.fill(0);
+
+    for (i, coefficient) in [r_0, r_1, r_2, r_3, r_inf].iter().enumerate() {
+        add_at(out, i * k, coefficient);
+    }
+}
+
+fn check_lengths(a: &[u64], b: &[u64], out: &[u64]) {
+    assert_eq!(a.len(), b.len(), "Operands must have the same length");
+    assert_eq!(out.len(), 2 * a.len(), "Output must be twice as long");
+}
+
+/// Copy `a` into a `len`-limb buffer.
+fn zero_extend(a: &[u64], len: usize) -> Vec<u64> {
+    let mut result = vec![0; len];
+    result[..a.len()].copy_from_slice(a);
+
+    result
+}
+
+/// Split `a` into 3 `k`-limb pieces (zero-padding the last one).
+fn split_3(a: &[u64], k: usize) -> [Vec<u64>; 3] {
+    [
+        a[..k].to_vec(),
+        a[k..2 * k].to_vec(),
+        zero_extend(&a[2 * k..], k),
+    ]
+}
+
+/// Evaluate `p(x) = a_0 + a_1 * x + a_2 * x^2` at `1`, `-1` and `-2`, as
+/// `k + 1`-limb two's complement.
+fn evaluate(a_0: &[u64], a_1: &[u64], a_2: &[u64]) -> [Vec<u64>; 3] {
+    let len = a_0.len() + 1;
+
+    let mut sum_0_2 = zero_extend(a_0, len);
+    add_assign(&mut sum_0_2, a_2);
+
+    let mut pos_1 = sum_0_2.clone();
+    add_assign(&mut pos_1, a_1);
+
+    let mut neg_1 = sum_0_2;
+    sub_assign(&mut neg_1, a_1);
+
+    // p(-2) = (p(-1) + a_2) * 2 - a_0
+    let mut neg_2 = neg_1.clone();
+    add_assign(&mut neg_2, a_2);
+    shl_1_assign(&mut neg_2);
+    sub_assign(&mut neg_2, a_0);
+
+    [pos_1, neg_1, neg_2]
+}
+
+/// Multiply two's complement `a` and `b`, into a `width`-limb two's
+/// complement product.
+fn mul_signed(a: &[u64], b: &[u64], width: usize) -> Vec<u64> {
+    let (mut a, mut b) = (a.to_vec(), b.to_vec());
+
+    let a_sign = a[a.len() - 1] >> 63;
+    let b_sign = b[b.len() - 1] >> 63;
+
+    cond_negate_assign(&mut a, a_sign);
+    cond_negate_assign(&mut b, b_sign);
+
+    let mut product = vec![0; 2 * a.len()];
+    mul(&a, &b, &mut product);
+
+    let mut product = zero_extend(&product, width);
+    cond_negate_assign(&mut product, a_sign ^ b_sign);
+
+    product
+}
+
+/// Add `b` into `a` at limb `offset`, ignoring any limbs past `a` (which
+/// must be zero).
+fn add_at(a: &mut [u64], offset: usize, b: &[u64]) {
+    let a = &mut a[offset..];
+    let len = b.len().min(a.len());
+
+    add_assign(a, &b[..len]);
+}
+
+/// Add `b` into `a` (with `a.len() >= b.len()`), returning the carry.
+pub(crate) fn add_assign(a: &mut [u64], b: &[u64]) -> u64 {
+    let mut carry = 0;
+
+    for (i, x) in a.iter_mut().enumerate() {
+        let sum = u128::from(*x) + u128::from(b.get(i).copied().unwrap_or(0)) + carry;
+        *x = sum as u64;
+        carry = sum >> 64;
+    }
+
+    carry as u64
+}
+
+/// Subtract `b` from `a` (with `a.len() >= b.len()`), returning the borrow.
+pub(crate) fn sub_assign(a: &mut [u64], b: &[u64]) -> u64 {
+    let mut borrow = 0;
+
+    for (i, x) in a.iter_mut().enumerate() {
+        let (difference, borrow_1) = x.overflowing_sub(b.get(i).copied().unwrap_or(0));
+        let (difference, borrow_2) = difference.overflowing_sub(borrow);
+        *x = difference;
+        borrow = u64::from(borrow_1 | borrow_2);
+    }
+
+    borrow
+}
+
+/// Negate (two's complement) `a` if `choice == 1`, without branching.
+fn cond_negate_assign(a: &mut [u64], choice: u64) {
+    let mask = 0_u64.wrapping_sub(choice);
+    let mut carry = u128::from(choice);
+
+    for x in a {
+        let sum = u128::from(*x ^ mask) + carry;
+        *x = sum as u64;
+        carry = sum >> 64;
+    }
+}
+
+/// Shift `a` left by one bit.
+fn shl_1_assign(a: &mut [u64]) {
+    let mut carry = 0;
+
+    for x in a {
+        let shifted = (*x << 1) | carry;
+        carry = *x >> 63;
+        *x = shifted;
+    }
+}
+
+/// Arithmetic (sign-preserving) shift right of two's complement `a` by one
+/// bit.
+fn sar_1_assign(a: &mut [u64]) {
+    let mut carry = a[a.len() - 1] >> 63;
+
+    for x in a.iter_mut().rev() {
+        let shifted = (*x >> 1) | (carry << 63);
+        carry = *x & 1;
+        *x = shifted;
+    }
+}
+
+/// Divide two's complement `a` by 3, which must divide it exactly.
+///
+/// Multiplies by `3^-1 mod 2^64` limb by limb (as in GMP's `divexact_by3`).
+fn div_exact_3_assign(a: &mut [u64]) {
+    const INV_3: u64 = 0xaaaa_aaaa_aaaa_aaab;
+
+    let mut borrow = 0;
+
+    for x in a {
+        let (difference, underflow) = x.overflowing_sub(borrow);
+        let quotient = difference.wrapping_mul(INV_3);
+        *x = quotient;
+        borrow = ((u128::from(quotient) * 3) >> 64) as u64 + u64::from(underflow);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+    use rand::Rng;
+    use test::Bencher;
+
+    use super::{mul, mul_karatsuba, mul_schoolbook, mul_toom3};
+    use crate::rand::rng;
+
+    fn random_limbs(len: usize) -> Vec<u64> {
+        (0..len).map(|_| rng().gen()).collect()
+    }
+
+    fn check(algorithm: fn(&[u64], &[u64], &mut [u64]), a: &[u64], b: &[u64]) {
+        let (mut expected, mut actual) = (vec![0; 2 * a.len()], vec![0; 2 * a.len()]);
+
+        mul_schoolbook(a, b, &mut expected);
+        algorithm(a, b, &mut actual);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_matches_schoolbook() {
+        for len in (1..40).chain([64, 100, 128, 170]) {
+            let (a, b) = (random_limbs(len), random_limbs(len));
+            let (max, zero) = (vec![u64::MAX; len], vec![0; len]);
+
+            for algorithm in [mul_karatsuba, mul_toom3, mul] {
+                check(algorithm, &a, &b);
+                check(algorithm, &max, &max);
+                check(algorithm, &max, &zero);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_biguint() {
+        let (a, b) = (random_limbs(128), random_limbs(128));
+        let mut product = vec![0; 256];
+
+        mul(&a, &b, &mut product);
+
+        assert_eq!(
+            BigUint::from_slice(&to_u32_digits(&product)),
+            BigUint::from_slice(&to_u32_digits(&a)) * BigUint::from_slice(&to_u32_digits(&b))
+        );
+    }
+
+    fn to_u32_digits(limbs: &[u64]) -> Vec<u32> {
+        limbs
+            .iter()
+            .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+            .collect()
+    }
+
+    fn bench_algorithm(b: &mut Bencher, algorithm: fn(&[u64], &[u64], &mut [u64]), len: usize) {
+        let (x, y) = (random_limbs(len), random_limbs(len));
+        let mut out = vec![0; 2 * len];
+
+        b.iter(|| algorithm(&x, &y, &mut out));
+    }
+
+    fn bench_biguint(b: &mut Bencher, len: usize) {
+        let x = BigUint::from_slice(&to_u32_digits(&random_limbs(len)));
+        let y = BigUint::from_slice(&to_u32_digits(&random_limbs(len)));
+
+        b.iter(|| &x * &y);
+    }
+
+    #[bench]
+    fn bench_2048_schoolbook(b: &mut Bencher) {
+        bench_algorithm(b, mul_schoolbook, 32);
+    }
+
+    #[bench]
+    fn bench_2048_karatsuba(b: &mut Bencher) {
+        bench_algorithm(b, mul_karatsuba, 32);
+    }
+
+    #[bench]
+    fn bench_2048_toom3(b: &mut Bencher) {
+        bench_algorithm(b, mul_toom3, 32);
+    }
+
+    #[bench]
+    fn bench_2048_biguint(b: &mut Bencher) {
+        bench_biguint(b, 32);
+    }
+
+    #[bench]
+    fn bench_4096_schoolbook(b: &mut Bencher) {
+        bench_algorithm(b, mul_schoolbook, 64);
+    }
+
+    #[bench]
+    fn bench_4096_karatsuba(b: &mut Bencher) {
+        bench_algorithm(b, mul_karatsuba, 64);
+    }
+
+    #[bench]
+    fn bench_4096_toom3(b: &mut Bencher) {
+        bench_algorithm(b, mul_toom3, 64);
+    }
+
+    #[bench]
+    fn bench_4096_biguint(b: &mut Bencher) {
+        bench_biguint(b, 64);
+    }
+
+    #[bench]
+    fn bench_8192_schoolbook(b: &mut Bencher) {
+        bench_algorithm(b, mul_schoolbook, 128);
+    }
+
+    #[bench]
+    fn bench_8192_karatsuba(b: &mut Bencher) {
+        bench_algorithm(b, mul_karatsuba, 128);
+    }
+
+    #[bench]
+    fn bench_8192_toom3(b: &mut Bencher) {
+        bench_algorithm(b, mul_toom3, 128);
+    }
+
+    #[bench]
+    fn bench_8192_biguint(b: &mut Bencher) {
+        bench_biguint(b, 128);
+    }
+}
@@ -0,0 +1,291 @@
+//! Utilities to read and write individual [bits](https://en.wikipedia.org/wiki/Bit_numbering)
+//! of byte sequences.
+
+/// Order in which bits are read from (or written to) each byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Most significant bit first (i.e. bit `7` of the first byte comes first).
+    MsbFirst,
+    /// Least significant bit first (i.e. bit `0` of the first byte comes first).
+    LsbFirst,
+}
+
+impl BitOrder {
+    /// Shift amount to reach bit number `index` (`0..8`) inside a byte.
+    const fn shift(self, index: usize) -> usize {
+        match self {
+            BitOrder::MsbFirst => 7 - index,
+            BitOrder::LsbFirst => index,
+        }
+    }
+}
+
+/// Iterator over the bits of a byte slice. See [`bits`].
+#[derive(Clone)]
+pub struct Bits<'a> {
+    bytes: &'a [u8],
+    order: BitOrder,
+    front: usize,
+    back: usize,
+}
+
+/// Iterate over the bits of `bytes` in the given `order`.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::bits::{bits, BitOrder};
+///
+/// let msb = bits(&[0b1000_0001, 0b0100_0000], BitOrder::MsbFirst).collect::<Vec<_>>();
+/// let lsb = bits(&[0b1000_0001], BitOrder::LsbFirst).collect::<Vec<_>>();
+///
+/// assert_eq!(&msb[..10], &[true, false, false, false, false, false, false, true, false, true]);
+/// assert_eq!(lsb, &[true, false, false, false, false, false, false, true]);
+/// ```
+#[must_use]
+pub const fn bits(bytes: &[u8], order: BitOrder) -> Bits<'_> {
+    Bits {
+        bytes,
+        order,
+        front: 0,
+        back: bytes.len() * 8,
+    }
+}
+
+impl Bits<'_> {
+    fn bit_at(&self, position: usize) -> bool {
+        let byte = self.bytes[position / 8];
+
+        (byte >> self.order.shift(position % 8)) & 1 == 1
+    }
+}
+
+impl Iterator for Bits<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        let bit = self.bit_at(self.front);
+        self.front += 1;
+
+        Some(bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for Bits<'_> {
+    fn next_back(&mut self) -> Option<bool> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.back -= 1;
+
+        Some(self.bit_at(self.back))
+    }
+}
+
+impl ExactSizeIterator for Bits<'_> {}
+
+/// Accumulates individual bits into a `Vec<u8>`.
+///
+/// The last byte is padded with zero bits if the amount of written bits is not
+/// a multiple of 8.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::bits::{BitOrder, BitWriter};
+///
+/// let mut writer = BitWriter::new(BitOrder::MsbFirst);
+///
+/// writer.write_bit(true);
+/// writer.write_uint(0b010, 3);
+///
+/// assert_eq!(writer.len(), 4);
+/// assert_eq!(writer.into_bytes(), &[0b1010_0000]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    order: BitOrder,
+    len: usize,
+}
+
+impl BitWriter {
+    /// Create an empty `BitWriter` that fills each byte in the given `order`.
+    #[must_use]
+    pub const fn new(order: BitOrder) -> BitWriter {
+        BitWriter {
+            bytes: Vec::new(),
+            order,
+            len: 0,
+        }
+    }
+
+    /// Append a single bit.
+    pub fn write_bit(&mut self, bit: bool) {
+        let index = self.len % 8;
+
+        if index == 0 {
+            self.bytes.push(0);
+        }
+
+        if bit {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= 1 << self.order.shift(index);
+        }
+
+        self.len += 1;
+    }
+
+    /// Append all the bits yielded by an iterator.
+    pub fn write_bits<I: IntoIterator<Item = bool>>(&mut self, bits: I) {
+        for bit in bits {
+            self.write_bit(bit);
+        }
+    }
+
+    /// Append the lowest `width` bits of `value`, most significant first.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is greater than 64.
+    pub fn write_uint(&mut self, value: u64, width: usize) {
+        self.write_bits(uint_to_bits(value, width));
+    }
+
+    /// Number of bits written so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no bits have been written yet.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Consume the writer, returning the accumulated (zero-padded) bytes.
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Iterate over the lowest `width` bits of `value`, most significant first.
+///
+/// # Panics
+///
+/// If `width` is greater than 64.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::bits::uint_to_bits;
+///
+/// assert_eq!(
+///     uint_to_bits(0b1101, 5).collect::<Vec<_>>(),
+///     &[false, true, true, false, true],
+/// );
+/// ```
+pub fn uint_to_bits(value: u64, width: usize) -> impl Iterator<Item = bool> {
+    assert!(width <= 64, "Width must be at most 64 bits");
+
+    (0..width).rev().map(move |i| (value >> i) & 1 == 1)
+}
+
+/// Pack the next `width` bits of an iterator (most significant first) into an
+/// integer.
+///
+/// Returns `None` if the iterator exhausts before `width` bits are read.
+///
+/// # Panics
+///
+/// If `width` is greater than 64.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::bits::{bits, uint_from_bits, BitOrder};
+///
+/// let mut iter = bits(&[0xAB], BitOrder::MsbFirst);
+///
+/// assert_eq!(uint_from_bits(&mut iter, 4), Some(0xA));
+/// assert_eq!(uint_from_bits(&mut iter, 4), Some(0xB));
+/// assert_eq!(uint_from_bits(&mut iter, 1), None);
+/// ```
+pub fn uint_from_bits<I: Iterator<Item = bool>>(bits: &mut I, width: usize) -> Option<u64> {
+    assert!(width <= 64, "Width must be at most 64 bits");
+
+    let mut value = 0_u64;
+
+    for _ in 0..width {
+        value = (value << 1) | u64::from(bits.next()?);
+    }
+
+    Some(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bits, uint_from_bits, BitOrder, BitWriter};
+
+    const BYTES: &[u8] = &[0x12, 0x34, 0xAB, 0xFF, 0x00, 0x80];
+
+    #[test]
+    fn test_roundtrip() {
+        for &order in &[BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let mut writer = BitWriter::new(order);
+            writer.write_bits(bits(BYTES, order));
+
+            assert_eq!(writer.len(), BYTES.len() * 8);
+            assert_eq!(writer.into_bytes(), BYTES);
+        }
+    }
+
+    #[test]
+    fn test_orders_are_reversed_per_byte() {
+        let msb = bits(BYTES, BitOrder::MsbFirst).collect::<Vec<_>>();
+        let lsb = bits(BYTES, BitOrder::LsbFirst).collect::<Vec<_>>();
+
+        for (msb_byte, lsb_byte) in msb.chunks(8).zip(lsb.chunks(8)) {
+            assert!(msb_byte.iter().eq(lsb_byte.iter().rev()));
+        }
+    }
+
+    #[test]
+    fn test_double_ended() {
+        let forward = bits(BYTES, BitOrder::MsbFirst).collect::<Vec<_>>();
+        let mut backward = bits(BYTES, BitOrder::MsbFirst).rev().collect::<Vec<_>>();
+        backward.reverse();
+
+        assert_eq!(forward, backward);
+    }
+
+    #[test]
+    fn test_uint_packing() {
+        let mut writer = BitWriter::new(BitOrder::MsbFirst);
+        writer.write_uint(0x1, 3);
+        writer.write_uint(0xDEAD_BEEF, 32);
+        writer.write_uint(0x5, 7);
+
+        let bytes = writer.into_bytes();
+        let mut iter = bits(&bytes, BitOrder::MsbFirst);
+
+        assert_eq!(uint_from_bits(&mut iter, 3), Some(0x1));
+        assert_eq!(uint_from_bits(&mut iter, 32), Some(0xDEAD_BEEF));
+        assert_eq!(uint_from_bits(&mut iter, 7), Some(0x5));
+        assert_eq!(uint_from_bits(&mut iter, 6), Some(0));
+        assert_eq!(uint_from_bits(&mut iter, 1), None);
+    }
+}
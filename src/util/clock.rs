@@ -0,0 +1,91 @@
+//! Time providers, so that timestamp-dependent code can be tested without
+//! waiting for the wall clock.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::util::clock::{Clock, MockClock};
+//!
+//! let clock = MockClock::new(1_000_000);
+//!
+//! clock.advance(40);
+//!
+//! assert_eq!(clock.unix_time(), 1_000_040);
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::get_unix_time;
+
+/// Source of the current time.
+pub trait Clock {
+    /// Get Unix time (seconds since Unix epoch).
+    fn unix_time(&self) -> u64;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn unix_time(&self) -> u64 {
+        (**self).unix_time()
+    }
+}
+
+/// The real wall clock (see [`get_unix_time`]).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn unix_time(&self) -> u64 {
+        get_unix_time()
+    }
+}
+
+/// Manually-driven clock for tests. Never moves unless told to.
+#[derive(Debug, Default)]
+pub struct MockClock(AtomicU64);
+
+impl MockClock {
+    /// Create a clock stopped at Unix time `now`.
+    #[must_use]
+    pub const fn new(now: u64) -> MockClock {
+        MockClock(AtomicU64::new(now))
+    }
+
+    /// Set the current Unix time to `now`.
+    pub fn set(&self, now: u64) {
+        self.0.store(now, Ordering::SeqCst);
+    }
+
+    /// Move the clock `secs` seconds forward.
+    pub fn advance(&self, secs: u64) {
+        self.0.fetch_add(secs, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn unix_time(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Clock, MockClock, SystemClock};
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new(1337);
+
+        assert_eq!(clock.unix_time(), 1337);
+
+        clock.advance(3);
+        assert_eq!(clock.unix_time(), 1340);
+
+        clock.set(42);
+        assert_eq!(clock.unix_time(), 42);
+    }
+
+    #[test]
+    fn test_system_clock() {
+        assert!(SystemClock.unix_time() > 1_600_000_000);
+    }
+}
@@ -0,0 +1,447 @@
+//! Minimal [DEFLATE](https://en.wikipedia.org/wiki/Deflate)-like compression.
+//!
+//! A greedy [LZ77](https://en.wikipedia.org/wiki/LZ77_and_LZ78) matcher
+//! followed by [canonical Huffman coding](https://en.wikipedia.org/wiki/Canonical_Huffman_code).
+//!
+//! It is not compatible with any real-world format, but output sizes are
+//! deterministic and leak information about the input the same way real
+//! compressors do (which is what compression-oracle attacks need).
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::bits::{bits, uint_from_bits, BitOrder, BitWriter};
+
+/// Maximum distance a match can refer back to.
+const WINDOW_SIZE: usize = 1 << DISTANCE_BITS;
+
+/// Bits used to encode (raw, without Huffman) match distances.
+const DISTANCE_BITS: usize = 15;
+
+/// Shortest match that will be emitted as a back-reference.
+const MIN_MATCH: usize = 3;
+
+/// Longest match that will be emitted as a back-reference.
+const MAX_MATCH: usize = 258;
+
+/// Maximum amount of candidates checked for each position.
+const MAX_CHAIN: usize = 256;
+
+/// Literals (`0..256`) plus one symbol per match length.
+const ALPHABET_SIZE: usize = 256 + MAX_MATCH - MIN_MATCH + 1;
+
+/// Bits used to encode each Huffman code length in the header.
+const CODE_LENGTH_BITS: usize = 5;
+
+/// Bits used to encode the amount of tokens in the header.
+const TOKEN_COUNT_BITS: usize = 32;
+
+/// LZ77 output unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token {
+    /// A raw byte.
+    Literal(u8),
+    /// Copy `length` bytes starting `distance` bytes back.
+    Match { distance: usize, length: usize },
+}
+
+impl Token {
+    const fn symbol(self) -> usize {
+        match self {
+            Token::Literal(byte) => byte as usize,
+            Token::Match { length, .. } => 256 + length - MIN_MATCH,
+        }
+    }
+}
+
+/// Tokenize `data` by greedily choosing the longest earlier match at each
+/// position (closest one on ties).
+#[must_use]
+pub fn lz77_tokenize(data: &[u8]) -> Vec<Token> {
+    let mut chains = HashMap::<&[u8], Vec<usize>>::new();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < data.len() {
+        let mut best_length = 0;
+        let mut best_distance = 0;
+
+        if i + MIN_MATCH <= data.len() {
+            if let Some(candidates) = chains.get(&data[i..i + MIN_MATCH]) {
+                for &candidate in candidates.iter().rev().take(MAX_CHAIN) {
+                    if i - candidate > WINDOW_SIZE {
+                        break;
+                    }
+
+                    let length = data[candidate..]
+                        .iter()
+                        .zip(&data[i..])
+                        .take(MAX_MATCH)
+                        .take_while(|(a, b)| a == b)
+                        .count();
+
+                    if length > best_length {
+                        best_length = length;
+                        best_distance = i - candidate;
+                    }
+                }
+            }
+        }
+
+        let advance = if best_length >= MIN_MATCH {
+            tokens.push(Token::Match {
+                distance: best_distance,
+                length: best_length,
+            });
+
+            best_length
+        } else {
+            tokens.push(Token::Literal(data[i]));
+
+            1
+        };
+
+        for position in i..(i + advance).min(data.len().saturating_sub(MIN_MATCH - 1)) {
+            chains
+                .entry(&data[position..position + MIN_MATCH])
+                .or_default()
+                .push(position);
+        }
+
+        i += advance;
+    }
+
+    tokens
+}
+
+/// Rebuild the original data from LZ77 tokens.
+///
+/// Returns `None` if some match refers to data before the start of the output.
+#[must_use]
+pub fn lz77_expand(tokens: &[Token]) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+
+    for &token in tokens {
+        match token {
+            Token::Literal(byte) => data.push(byte),
+            Token::Match { distance, length } => {
+                if distance == 0 || distance > data.len() {
+                    return None;
+                }
+
+                let start = data.len() - distance;
+
+                // Byte by byte since matches can overlap their own output
+                for offset in 0..length {
+                    data.push(data[start + offset]);
+                }
+            },
+        }
+    }
+
+    Some(data)
+}
+
+/// Compute Huffman code lengths for each symbol given their frequencies.
+///
+/// Symbols with zero frequency get a zero length (i.e. no code). Ties are
+/// broken by symbol index so the result is deterministic.
+#[must_use]
+pub fn huffman_code_lengths(frequencies: &[usize]) -> Vec<usize> {
+    let mut lengths = vec![0; frequencies.len()];
+
+    let mut heap = frequencies
+        .iter()
+        .enumerate()
+        .filter(|(_, &frequency)| frequency > 0)
+        .map(|(symbol, &frequency)| Reverse((frequency, symbol)))
+        .collect::<BinaryHeap<_>>();
+
+    if heap.len() < 2 {
+        if let Some(&Reverse((_, symbol))) = heap.peek() {
+            lengths[symbol] = 1;
+        }
+
+        return lengths;
+    }
+
+    // Leaves are `0..frequencies.len()`, internal nodes are appended after them
+    let mut parents = vec![None; frequencies.len()];
+
+    while let Some(Reverse((weight_a, node_a))) = heap.pop() {
+        let Some(Reverse((weight_b, node_b))) = heap.pop() else {
+            break; // Only the root is left
+        };
+
+        let parent = parents.len();
+        parents.push(None);
+        parents[node_a] = Some(parent);
+        parents[node_b] = Some(parent);
+
+        heap.push(Reverse((weight_a + weight_b, parent)));
+    }
+
+    for (symbol, length) in lengths.iter_mut().enumerate() {
+        let mut node = symbol;
+
+        while let Some(parent) = parents[node] {
+            *length += 1;
+            node = parent;
+        }
+    }
+
+    lengths
+}
+
+/// Assign canonical Huffman codes given the code lengths of each symbol.
+///
+/// Codes are sorted by `(length, symbol)`. Symbols with zero length get a
+/// meaningless `0` code.
+#[must_use]
+pub fn canonical_codes(lengths: &[usize]) -> Vec<u64> {
+    let mut symbols = (0..lengths.len())
+        .filter(|&symbol| lengths[symbol] > 0)
+        .collect::<Vec<_>>();
+
+    symbols.sort_by_key(|&symbol| (lengths[symbol], symbol));
+
+    let mut codes = vec![0; lengths.len()];
+    let mut code = 0_u64;
+    let mut previous_length = 0;
+
+    for symbol in symbols {
+        code <<= lengths[symbol] - previous_length;
+        codes[symbol] = code;
+        code += 1;
+        previous_length = lengths[symbol];
+    }
+
+    codes
+}
+
+fn symbol_frequencies(tokens: &[Token]) -> Vec<usize> {
+    let mut frequencies = vec![0; ALPHABET_SIZE];
+
+    for token in tokens {
+        frequencies[token.symbol()] += 1;
+    }
+
+    frequencies
+}
+
+/// Compress `data` (LZ77 + canonical Huffman).
+///
+/// # Panics
+///
+/// If the input is so large and skewed that some Huffman code is longer than
+/// 31 bits.
+#[must_use]
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let tokens = lz77_tokenize(data);
+    let lengths = huffman_code_lengths(&symbol_frequencies(&tokens));
+    let codes = canonical_codes(&lengths);
+
+    let mut writer = BitWriter::new(BitOrder::MsbFirst);
+
+    writer.write_uint(tokens.len() as u64, TOKEN_COUNT_BITS);
+
+    for &length in &lengths {
+        assert!(length < 1 << CODE_LENGTH_BITS, "Huffman code too long");
+        writer.write_uint(length as u64, CODE_LENGTH_BITS);
+    }
+
+    for token in tokens {
+        let symbol = token.symbol();
+
+        writer.write_uint(codes[symbol], lengths[symbol]);
+
+        if let Token::Match { distance, .. } = token {
+            writer.write_uint((distance - 1) as u64, DISTANCE_BITS);
+        }
+    }
+
+    writer.into_bytes()
+}
+
+/// Decompress data produced by [`compress`].
+///
+/// Returns `None` if the data is malformed.
+#[must_use]
+pub fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    let mut iter = bits(data, BitOrder::MsbFirst);
+
+    let token_count = uint_from_bits(&mut iter, TOKEN_COUNT_BITS)?;
+
+    let lengths = (0..ALPHABET_SIZE)
+        .map(|_| uint_from_bits(&mut iter, CODE_LENGTH_BITS).map(|length| length as usize))
+        .collect::<Option<Vec<_>>>()?;
+
+    let decoding_table = canonical_codes(&lengths)
+        .into_iter()
+        .enumerate()
+        .filter(|&(symbol, _)| lengths[symbol] > 0)
+        .map(|(symbol, code)| ((lengths[symbol], code), symbol))
+        .collect::<HashMap<_, _>>();
+
+    let max_length = lengths.iter().copied().max().unwrap_or(0);
+
+    let mut tokens = Vec::new();
+
+    for _ in 0..token_count {
+        let mut code = 0_u64;
+        let mut length = 0;
+
+        let symbol = loop {
+            if length >= max_length {
+                return None;
+            }
+
+            code = (code << 1) | u64::from(iter.next()?);
+            length += 1;
+
+            if let Some(&symbol) = decoding_table.get(&(length, code)) {
+                break symbol;
+            }
+        };
+
+        let token = if symbol < 256 {
+            Token::Literal(symbol as u8)
+        } else {
+            let distance = uint_from_bits(&mut iter, DISTANCE_BITS)? as usize + 1;
+
+            Token::Match {
+                distance,
+                length: symbol - 256 + MIN_MATCH,
+            }
+        };
+
+        tokens.push(token);
+    }
+
+    lz77_expand(&tokens)
+}
+
+/// Length in bytes of `compress(data)`, without building the output.
+#[must_use]
+pub fn compressed_len(data: &[u8]) -> usize {
+    let tokens = lz77_tokenize(data);
+    let frequencies = symbol_frequencies(&tokens);
+    let lengths = huffman_code_lengths(&frequencies);
+
+    let header_bits = TOKEN_COUNT_BITS + ALPHABET_SIZE * CODE_LENGTH_BITS;
+
+    let token_bits = tokens
+        .iter()
+        .map(|token| match token {
+            Token::Literal(_) => lengths[token.symbol()],
+            Token::Match { .. } => lengths[token.symbol()] + DISTANCE_BITS,
+        })
+        .sum::<usize>();
+
+    (header_bits + token_bits).div_ceil(8)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        canonical_codes, compress, compressed_len, decompress, huffman_code_lengths, lz77_expand,
+        lz77_tokenize, Token,
+    };
+
+    const TEXT: &str = include_str!("../../tests/set1/6.solution.txt");
+
+    fn inputs() -> Vec<Vec<u8>> {
+        vec![
+            vec![],
+            vec![42],
+            vec![0; 1000],
+            b"abcabcabcabcabcabcabcabcabcabcabcabc".to_vec(),
+            TEXT.as_bytes().to_vec(),
+            crate::util::generate_bytes(2000),
+        ]
+    }
+
+    #[test]
+    fn test_lz77_roundtrip() {
+        for input in inputs() {
+            assert_eq!(lz77_expand(&lz77_tokenize(&input)), Some(input));
+        }
+    }
+
+    #[test]
+    fn test_lz77_overlapping_match() {
+        assert_eq!(
+            lz77_tokenize(b"aaaaaaaa"),
+            &[
+                Token::Literal(b'a'),
+                Token::Match {
+                    distance: 1,
+                    length: 7
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lz77_bad_distance() {
+        assert_eq!(
+            lz77_expand(&[Token::Match {
+                distance: 1,
+                length: 3
+            }]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_huffman_kraft_equality() {
+        let lengths = huffman_code_lengths(&[5, 9, 12, 13, 16, 45, 0]);
+
+        assert_eq!(lengths[6], 0);
+
+        let kraft = lengths
+            .iter()
+            .filter(|&&length| length > 0)
+            .map(|&length| 1.0 / f64::from(1 << length))
+            .sum::<f64>();
+
+        assert!((kraft - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_canonical_codes() {
+        // From RFC 1951, section 3.2.2
+        let lengths = [3, 3, 3, 3, 3, 2, 4, 4];
+        let codes = canonical_codes(&lengths);
+
+        assert_eq!(
+            codes,
+            &[0b010, 0b011, 0b100, 0b101, 0b110, 0b00, 0b1110, 0b1111]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for input in inputs() {
+            let compressed = compress(&input);
+
+            assert_eq!(compressed.len(), compressed_len(&input));
+            assert_eq!(decompress(&compressed), Some(input));
+        }
+    }
+
+    #[test]
+    fn test_compresses_text() {
+        assert!(compressed_len(TEXT.as_bytes()) < TEXT.len());
+    }
+
+    #[test]
+    fn test_leaks_repetitions() {
+        const SECRET: &[u8] = b"sessionid=TmV2ZXIgZ29ubmEgZ2l2ZSB5b3UgdXA";
+
+        let right_guess = [SECRET, b"sessionid=TmV2ZX"].concat();
+        let wrong_guess = [SECRET, b"sessionid=Qz8#wK"].concat();
+
+        assert!(compressed_len(&right_guess) < compressed_len(&wrong_guess));
+    }
+}
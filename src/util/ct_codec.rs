@@ -0,0 +1,252 @@
+//! [Constant-time](https://en.wikipedia.org/wiki/Timing_attack) hex and
+//! base64 codecs, for secret material like keys.
+//!
+//! Typical decoders map each character through a lookup table or a chain of
+//! branches, so which cache lines are touched (or which branches are taken)
+//! depends on the secret being decoded. A local attacker sharing the CPU
+//! cache could, in principle, learn something about it.
+//!
+//! Here every character goes through the same arithmetic (range checks are
+//! computed with masks instead of comparisons) and validity is accumulated
+//! instead of returning early. Only lengths, which are public anyway, affect
+//! control flow.
+//!
+//! This does not protect against anything else: use these to parse keys, not
+//! for bulk data (which is better served by the `hex` and `base64` crates).
+
+/// `0xff` if `lo <= c <= hi`, `0` otherwise.
+const fn in_range(c: u8, lo: u8, hi: u8) -> u8 {
+    let c = c as u32;
+    let below = (lo as u32).wrapping_sub(1).wrapping_sub(c);
+    let above = c.wrapping_sub(hi as u32 + 1);
+
+    ((below & above) >> 8) as u8
+}
+
+/// `0xff` if `c == value`, `0` otherwise.
+const fn equals(c: u8, value: u8) -> u8 {
+    in_range(c, value, value)
+}
+
+/// Decode a hex digit, returning its value and a mask (`0xff` if valid).
+const fn decode_hex_digit(c: u8) -> (u8, u8) {
+    let digit = in_range(c, b'0', b'9');
+    let lower = in_range(c, b'a', b'f');
+    let upper = in_range(c, b'A', b'F');
+
+    let value = (digit & c.wrapping_sub(b'0'))
+        | (lower & c.wrapping_sub(b'a' - 10))
+        | (upper & c.wrapping_sub(b'A' - 10));
+
+    (value, digit | lower | upper)
+}
+
+/// Encode a nibble as a (lowercase) hex digit.
+const fn encode_hex_digit(nibble: u8) -> u8 {
+    let letter = in_range(nibble, 10, 15);
+
+    nibble + b'0' + (letter & (b'a' - b'0' - 10))
+}
+
+/// Decode a base64 character, returning its value and a mask (`0xff` if
+/// valid).
+const fn decode_base64_char(c: u8) -> (u8, u8) {
+    let upper = in_range(c, b'A', b'Z');
+    let lower = in_range(c, b'a', b'z');
+    let digit = in_range(c, b'0', b'9');
+    let plus = equals(c, b'+');
+    let slash = equals(c, b'/');
+
+    let value = (upper & c.wrapping_sub(b'A'))
+        | (lower & c.wrapping_sub(b'a' - 26))
+        | (digit & c.wrapping_add(52 - b'0'))
+        | (plus & 0x3e)
+        | (slash & 0x3f);
+
+    (value, upper | lower | digit | plus | slash)
+}
+
+/// Encode a 6-bit value as a base64 character.
+const fn encode_base64_char(value: u8) -> u8 {
+    (in_range(value, 0, 25) & value.wrapping_add(b'A'))
+        | (in_range(value, 26, 51) & value.wrapping_add(b'a' - 26))
+        | (in_range(value, 52, 61) & value.wrapping_sub(52 - b'0'))
+        | (equals(value, 62) & b'+')
+        | (equals(value, 63) & b'/')
+}
+
+/// Decode a hex string (either case) in constant time.
+///
+/// Returns `None` if `hex` has odd length or contains non-hex characters.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::ct_codec::hex_decode;
+///
+/// assert_eq!(hex_decode("00ffA1"), Some(vec![0x00, 0xff, 0xa1]));
+/// assert_eq!(hex_decode("0g"), None);
+/// ```
+#[must_use]
+pub fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.as_bytes();
+
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let mut valid = 0xff;
+
+    let decoded = hex
+        .chunks(2)
+        .map(|pair| {
+            let (hi, hi_valid) = decode_hex_digit(pair[0]);
+            let (lo, lo_valid) = decode_hex_digit(pair[1]);
+
+            valid &= hi_valid & lo_valid;
+
+            (hi << 4) | lo
+        })
+        .collect();
+
+    (valid == 0xff).then_some(decoded)
+}
+
+/// Encode `bytes` as lowercase hex in constant time.
+#[must_use]
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|byte| [encode_hex_digit(byte >> 4), encode_hex_digit(byte & 0xf)])
+        .map(char::from)
+        .collect()
+}
+
+/// Decode (padded, standard alphabet) base64 in constant time.
+///
+/// Returns `None` if `base64` has a wrong length or padding, contains
+/// characters outside the alphabet, or is not canonical (i.e. has non-zero
+/// trailing bits).
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::ct_codec::base64_decode;
+///
+/// assert_eq!(base64_decode("aGk="), Some(b"hi".to_vec()));
+/// assert_eq!(base64_decode("aGk"), None);
+/// assert_eq!(base64_decode("aGl="), None);
+/// ```
+#[must_use]
+pub fn base64_decode(base64: &str) -> Option<Vec<u8>> {
+    let base64 = base64.as_bytes();
+
+    if !base64.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let unpadded = base64
+        .strip_suffix(b"==")
+        .or_else(|| base64.strip_suffix(b"="))
+        .unwrap_or(base64);
+
+    let mut valid = 0xff;
+    let mut acc = 0_u32;
+    let mut acc_bits = 0;
+    let mut decoded = Vec::with_capacity(unpadded.len() * 3 / 4);
+
+    for &c in unpadded {
+        let (value, value_valid) = decode_base64_char(c);
+
+        valid &= value_valid;
+        acc = (acc << 6) | u32::from(value);
+        acc_bits += 6;
+
+        if acc_bits >= 8 {
+            acc_bits -= 8;
+            decoded.push((acc >> acc_bits) as u8);
+        }
+    }
+
+    let trailing_bits = acc & ((1 << acc_bits) - 1);
+    let canonical = equals(trailing_bits as u8, 0);
+
+    (valid & canonical == 0xff).then_some(decoded)
+}
+
+/// Encode `bytes` as (padded, standard alphabet) base64 in constant time.
+#[must_use]
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0_u32, |acc, (i, &byte)| {
+            acc | u32::from(byte) << (16 - 8 * i)
+        });
+
+        for i in 0..=chunk.len() {
+            let value = (group >> (18 - 6 * i)) & 0x3f;
+            encoded.push(char::from(encode_base64_char(value as u8)));
+        }
+
+        for _ in chunk.len()..3 {
+            encoded.push('=');
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::{base64_decode, base64_encode, hex_decode, hex_encode};
+    use crate::util::generate_bytes;
+
+    #[test]
+    fn test_hex_matches_reference() {
+        for c in 0..=255_u8 {
+            let string = [c, b'0'];
+
+            assert_eq!(
+                std::str::from_utf8(&string).ok().and_then(hex_decode),
+                std::str::from_utf8(&string)
+                    .ok()
+                    .and_then(|string| hex::decode(string).ok())
+            );
+        }
+
+        for len in 0..40 {
+            let bytes = generate_bytes(len);
+
+            assert_eq!(hex_encode(&bytes), hex::encode(&bytes));
+            assert_eq!(hex_decode(&hex_encode(&bytes)), Some(bytes));
+        }
+
+        assert_eq!(hex_decode("abc"), None);
+    }
+
+    #[test]
+    fn test_base64_matches_reference() {
+        for c in 0..=255_u8 {
+            let string = [b'A', c, b'A', b'A'];
+
+            assert_eq!(
+                std::str::from_utf8(&string).ok().and_then(base64_decode),
+                std::str::from_utf8(&string)
+                    .ok()
+                    .and_then(|string| base64::decode(string).ok())
+            );
+        }
+
+        for len in 0..40 {
+            let bytes = generate_bytes(len);
+
+            assert_eq!(base64_encode(&bytes), base64::encode(&bytes));
+            assert_eq!(base64_decode(&base64_encode(&bytes)), Some(bytes));
+        }
+
+        assert_eq!(base64_decode("A==="), None);
+        assert_eq!(base64_decode("AB=="), None);
+        assert_eq!(base64_decode("AA=A"), None);
+    }
+}
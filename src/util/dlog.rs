@@ -0,0 +1,663 @@
+//! [Discrete logarithms](https://en.wikipedia.org/wiki/Discrete_logarithm)
+//! modulo a prime, and the integer factoring they depend on.
+//!
+//! [`discrete_log`] factors `p - 1` to find the order of the base, splits the
+//! problem along the prime factors of that order
+//! ([Pohlig-Hellman](https://en.wikipedia.org/wiki/Pohlig%E2%80%93Hellman_algorithm))
+//! and solves each part with
+//! [Pollard's rho](https://en.wikipedia.org/wiki/Pollard%27s_rho_algorithm_for_logarithms).
+//! There is no index calculus here: the cost grows with the square root of the
+//! biggest prime factor of the order, so this only works against deliberately
+//! weak groups.
+//!
+//! [`DiscreteLogTable`] precomputes random walks for a group first (rho with
+//! precomputation, as described by Bernstein and Lange). The table costs more
+//! than a single log, but after that every log in the group is much cheaper.
+//! That is why a few widely shared groups are such a juicy target (see
+//! [Logjam](https://weakdh.org/)).
+//!
+//! # Example
+//!
+//! ```
+//! use num_bigint::BigUint;
+//! use rustopals::util::dlog::{discrete_log, DiscreteLogTable};
+//!
+//! let modulus = BigUint::from(1_000_000_007_u64);
+//! let base = BigUint::from(5_usize);
+//! let target = base.modpow(&BigUint::from(123_456_789_u64), &modulus);
+//!
+//! let log = discrete_log(&base, &target, &modulus).unwrap();
+//!
+//! assert_eq!(base.modpow(&log, &modulus), target);
+//!
+//! let table = DiscreteLogTable::new(&base, &modulus, 64);
+//!
+//! assert_eq!(table.log(&target), Some(log));
+//! ```
+
+use std::collections::HashMap;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+
+use crate::rand::rng;
+use crate::rsa::primes::{is_probable_prime, FIRST_PRIMES};
+use crate::util::binary_inv_mod;
+use crate::util::pool::ThreadPool;
+
+/// Prime factors of the order up to this many bits are solved by brute force.
+const SMALL_FACTOR_BITS: u64 = 16;
+
+/// Amount of different steps in the random walks.
+const WALK_PARTITIONS: u64 = 32;
+
+/// Walks this many times longer than expected are abandoned (they are
+/// probably stuck in a cycle).
+const MAX_WALK_FACTOR: u64 = 16;
+
+/// Random restarts before giving up.
+const MAX_ATTEMPTS: usize = 64;
+
+/// Factor `n` into `(prime, exponent)` pairs, sorted by prime.
+///
+/// Uses trial division by the first few primes, then
+/// [Pollard's rho](https://en.wikipedia.org/wiki/Pollard%27s_rho_algorithm)
+/// for whatever is left. Only practical if at most one of the prime factors
+/// is big (i.e. over 40-something bits).
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::util::dlog::factor;
+///
+/// assert_eq!(
+///     factor(&BigUint::from(360_usize)),
+///     vec![
+///         (BigUint::from(2_usize), 3),
+///         (BigUint::from(3_usize), 2),
+///         (BigUint::from(5_usize), 1),
+///     ],
+/// );
+/// ```
+#[must_use]
+pub fn factor(n: &BigUint) -> Vec<(BigUint, u32)> {
+    if n.is_zero() {
+        return Vec::new();
+    }
+
+    let mut factors = Vec::new();
+    let mut n = n.clone();
+
+    for prime in FIRST_PRIMES.iter() {
+        let mut exponent = 0;
+
+        while (&n % prime).is_zero() {
+            n /= prime;
+            exponent += 1;
+        }
+
+        if exponent > 0 {
+            factors.push((prime.clone(), exponent));
+        }
+    }
+
+    // Whatever is left has no factors below this
+    let trial_bound = FIRST_PRIMES
+        .last()
+        .map_or_else(BigUint::zero, |prime| prime * prime);
+
+    let mut composites = vec![n];
+
+    while let Some(n) = composites.pop() {
+        if n.is_one() {
+            continue;
+        }
+
+        if n < trial_bound || is_probable_prime(&n) {
+            factors.push((n, 1));
+        } else {
+            let divisor = pollard_rho_factor(&n);
+
+            composites.push(&n / &divisor);
+            composites.push(divisor);
+        }
+    }
+
+    factors.sort();
+
+    let mut merged: Vec<(BigUint, u32)> = Vec::with_capacity(factors.len());
+
+    for (prime, exponent) in factors {
+        match merged.last_mut() {
+            Some((last, last_exponent)) if *last == prime => *last_exponent += exponent,
+            _ => merged.push((prime, exponent)),
+        }
+    }
+
+    merged
+}
+
+/// Find a non-trivial divisor of the composite `n` with Pollard's rho (and
+/// Floyd's cycle detection).
+fn pollard_rho_factor(n: &BigUint) -> BigUint {
+    loop {
+        let constant = rng().gen_biguint_range(&BigUint::one(), n);
+        let next = |x: &BigUint| (x * x + &constant) % n;
+
+        let mut tortoise = rng().gen_biguint_below(n);
+        let mut hare = tortoise.clone();
+
+        let divisor = loop {
+            tortoise = next(&tortoise);
+            hare = next(&next(&hare));
+
+            let distance = if tortoise > hare {
+                &tortoise - &hare
+            } else {
+                &hare - &tortoise
+            };
+
+            let divisor = distance.gcd(n);
+
+            if !divisor.is_one() {
+                break divisor;
+            }
+        };
+
+        if &divisor != n {
+            return divisor;
+        }
+    }
+}
+
+/// Factored order of `base` modulo the prime `modulus` (see [`factor`]).
+#[must_use]
+pub fn order_factors(base: &BigUint, modulus: &BigUint) -> Vec<(BigUint, u32)> {
+    let mut order = modulus - 1_u32;
+    let mut factors = factor(&order);
+
+    for (prime, exponent) in &mut factors {
+        while *exponent > 0 {
+            let smaller = &order / &*prime;
+
+            if !base.modpow(&smaller, modulus).is_one() {
+                break;
+            }
+
+            order = smaller;
+            *exponent -= 1;
+        }
+    }
+
+    factors.retain(|(_, exponent)| *exponent > 0);
+
+    factors
+}
+
+/// Solve `base^x = target` modulo the prime `modulus`.
+///
+/// Returns the smallest `x`, or `None` if there is none.
+#[must_use]
+pub fn discrete_log(base: &BigUint, target: &BigUint, modulus: &BigUint) -> Option<BigUint> {
+    let order_factors = order_factors(base, modulus);
+
+    pohlig_hellman(
+        base,
+        target,
+        modulus,
+        &order_factors,
+        |_, subgroup_base, subgroup_target, prime| {
+            pollard_rho(subgroup_base, subgroup_target, modulus, prime)
+        },
+    )
+}
+
+/// Solve `base^x = target` modulo `modulus` with Pollard's rho, where `base`
+/// has prime `order`.
+///
+/// Small orders are brute-forced instead.
+///
+/// Returns `x` (smaller than `order`), or `None` if there is none.
+#[must_use]
+pub fn pollard_rho(
+    base: &BigUint,
+    target: &BigUint,
+    modulus: &BigUint,
+    order: &BigUint,
+) -> Option<BigUint> {
+    if !target.modpow(order, modulus).is_one() {
+        return None;
+    }
+
+    if order.bits() <= SMALL_FACTOR_BITS {
+        return brute_force_log(base, target, modulus, order);
+    }
+
+    for _ in 0..MAX_ATTEMPTS {
+        // Each step multiplies by `base^c * target^d`, keeping track of the
+        // exponents of `base` and `target` in the current element
+        let steps = (0..WALK_PARTITIONS)
+            .map(|_| {
+                let (c, d) = (
+                    rng().gen_biguint_below(order),
+                    rng().gen_biguint_below(order),
+                );
+                let element = base.modpow(&c, modulus) * target.modpow(&d, modulus) % modulus;
+
+                (element, c, d)
+            })
+            .collect::<Vec<_>>();
+
+        let step = |(element, c, d): &mut (BigUint, BigUint, BigUint)| {
+            let (step, step_c, step_d) = &steps[partition(element)];
+
+            *element = &*element * step % modulus;
+            *c = (&*c + step_c) % order;
+            *d = (&*d + step_d) % order;
+        };
+
+        let c = rng().gen_biguint_below(order);
+        let mut tortoise = (base.modpow(&c, modulus), c, BigUint::zero());
+        let mut hare = tortoise.clone();
+
+        loop {
+            step(&mut tortoise);
+            step(&mut hare);
+            step(&mut hare);
+
+            if tortoise.0 == hare.0 {
+                break;
+            }
+        }
+
+        // `base^c1 * target^d1 = base^c2 * target^d2`, so
+        // `x = (c1 - c2) / (d2 - d1) mod order`
+        let d_difference = (&hare.2 + order - &tortoise.2) % order;
+
+        let Some(d_inverse) = binary_inv_mod(&d_difference, order) else {
+            continue;
+        };
+
+        let log = (&tortoise.1 + order - &hare.1) * d_inverse % order;
+
+        if base.modpow(&log, modulus) == *target {
+            return Some(log);
+        }
+    }
+
+    None
+}
+
+/// Solve `base^x = target` modulo `modulus` by trying every `x` below `order`.
+fn brute_force_log(
+    base: &BigUint,
+    target: &BigUint,
+    modulus: &BigUint,
+    order: &BigUint,
+) -> Option<BigUint> {
+    let mut power = BigUint::one();
+
+    for log in num_iter::range(BigUint::zero(), order.clone()) {
+        if power == *target {
+            return Some(log);
+        }
+
+        power = power * base % modulus;
+    }
+
+    None
+}
+
+/// Pick the step for `element` in a random walk.
+fn partition(element: &BigUint) -> usize {
+    (low_digit(element) % WALK_PARTITIONS) as usize
+}
+
+fn low_digit(element: &BigUint) -> u64 {
+    element.iter_u64_digits().next().unwrap_or(0)
+}
+
+/// Solve `base^x = target` modulo `modulus` given the factored order of
+/// `base`, with `solve(factor_index, subgroup_base, subgroup_target, prime)`
+/// computing logs in the subgroup of each prime order.
+fn pohlig_hellman(
+    base: &BigUint,
+    target: &BigUint,
+    modulus: &BigUint,
+    order_factors: &[(BigUint, u32)],
+    solve: impl Fn(usize, &BigUint, &BigUint, &BigUint) -> Option<BigUint>,
+) -> Option<BigUint> {
+    let order = order_factors
+        .iter()
+        .fold(BigUint::one(), |order, (prime, exponent)| {
+            order * prime.pow(*exponent)
+        });
+
+    // The group is cyclic, so this means `target` is generated by `base`
+    if !target.modpow(&order, modulus).is_one() {
+        return None;
+    }
+
+    let base_inverse = binary_inv_mod(base, modulus)?;
+
+    let mut log = BigUint::zero();
+    let mut log_modulus = BigUint::one();
+
+    for (i, (prime, exponent)) in order_factors.iter().enumerate() {
+        let subgroup_base = base.modpow(&(&order / prime), modulus);
+
+        // Find `x mod prime^exponent`, one base-`prime` digit at a time
+        let mut prime_power = BigUint::one();
+        let mut partial_log = BigUint::zero();
+
+        for _ in 0..*exponent {
+            let stripped = target * base_inverse.modpow(&partial_log, modulus) % modulus;
+            let subgroup_target = stripped.modpow(&(&order / (&prime_power * prime)), modulus);
+
+            let digit = solve(i, &subgroup_base, &subgroup_target, prime)?;
+
+            partial_log += digit * &prime_power;
+            prime_power *= prime;
+        }
+
+        // Chinese remainder theorem
+        let difference = (&partial_log + &prime_power - &log % &prime_power) % &prime_power;
+        let lift = difference * binary_inv_mod(&log_modulus, &prime_power)? % &prime_power;
+
+        log += &log_modulus * lift;
+        log_modulus *= prime_power;
+    }
+
+    Some(log)
+}
+
+/// Precomputed random walks for a subgroup of prime order.
+#[derive(Clone, Debug)]
+struct WalkTable {
+    /// `(base^c, c)` for each partition.
+    steps: Vec<(BigUint, BigUint)>,
+    distinguishing_bits: u32,
+    max_walk: u64,
+    /// Distinguished points and their logs.
+    points: HashMap<BigUint, BigUint>,
+}
+
+impl WalkTable {
+    fn new(base: &BigUint, modulus: &BigUint, order: &BigUint, table_size: usize) -> WalkTable {
+        // Walks of about `sqrt(order / table_size)` steps make lookups about
+        // as expensive as a single walk
+        let walk_bits = order.bits().saturating_sub(u64::from(table_size.ilog2())) / 2;
+
+        let steps = (0..WALK_PARTITIONS)
+            .map(|_| {
+                let c = rng().gen_biguint_below(order);
+
+                (base.modpow(&c, modulus), c)
+            })
+            .collect();
+
+        let mut table = WalkTable {
+            steps,
+            distinguishing_bits: walk_bits as u32,
+            max_walk: MAX_WALK_FACTOR << walk_bits,
+            points: HashMap::with_capacity(table_size),
+        };
+
+        let pool = ThreadPool::available();
+
+        // Some walks get lost and some end up in the same points, so there
+        // may be a few rounds
+        for _ in 0..MAX_ATTEMPTS {
+            let missing = table_size.saturating_sub(table.points.len());
+
+            if missing == 0 {
+                break;
+            }
+
+            let starts = (0..missing)
+                .map(|_| rng().gen_biguint_below(order))
+                .collect::<Vec<_>>();
+
+            let ends = pool.parallel_map(&starts, |start| {
+                table.walk(base.modpow(start, modulus), start.clone(), modulus, order)
+            });
+
+            table.points.extend(ends.into_iter().flatten());
+        }
+
+        table
+    }
+
+    /// Walk from `element` (which is `base^log` times some unknown) to the
+    /// next distinguished point, returning it along with its updated `log`.
+    fn walk(
+        &self,
+        mut element: BigUint,
+        mut log: BigUint,
+        modulus: &BigUint,
+        order: &BigUint,
+    ) -> Option<(BigUint, BigUint)> {
+        for _ in 0..self.max_walk {
+            let digit = low_digit(&element);
+
+            if (digit / WALK_PARTITIONS).trailing_zeros() >= self.distinguishing_bits {
+                return Some((element, log));
+            }
+
+            let (step, step_log) = &self.steps[partition(&element)];
+
+            element = element * step % modulus;
+            log = (log + step_log) % order;
+        }
+
+        None
+    }
+
+    fn log(
+        &self,
+        base: &BigUint,
+        target: &BigUint,
+        modulus: &BigUint,
+        order: &BigUint,
+    ) -> Option<BigUint> {
+        for _ in 0..MAX_ATTEMPTS {
+            // Start from a random point in `target`'s coset until we run into
+            // a precomputed walk
+            let offset = rng().gen_biguint_below(order);
+            let start = target * base.modpow(&offset, modulus) % modulus;
+
+            let Some((point, offset)) = self.walk(start, offset, modulus, order) else {
+                continue;
+            };
+
+            if let Some(point_log) = self.points.get(&point) {
+                let log = (point_log + order - offset) % order;
+
+                if base.modpow(&log, modulus) == *target {
+                    return Some(log);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Precomputed state to quickly solve many discrete logs to the same base
+/// and modulus.
+#[derive(Clone, Debug)]
+pub struct DiscreteLogTable {
+    base: BigUint,
+    modulus: BigUint,
+    order_factors: Vec<(BigUint, u32)>,
+    /// One per prime factor of the order (`None` for the small ones).
+    walks: Vec<Option<WalkTable>>,
+}
+
+impl DiscreteLogTable {
+    /// Precompute logs to `base` modulo the prime `modulus`.
+    ///
+    /// Stores up to `table_size` walks per big prime factor of the order of
+    /// `base`. Bigger tables take longer to precompute, but make each log
+    /// faster.
+    #[must_use]
+    pub fn new(base: &BigUint, modulus: &BigUint, table_size: usize) -> DiscreteLogTable {
+        let order_factors = order_factors(base, modulus);
+        let order = order_factors
+            .iter()
+            .fold(BigUint::one(), |order, (prime, exponent)| {
+                order * prime.pow(*exponent)
+            });
+
+        let walks = order_factors
+            .iter()
+            .map(|(prime, _)| {
+                (prime.bits() > SMALL_FACTOR_BITS).then(|| {
+                    let subgroup_base = base.modpow(&(&order / prime), modulus);
+
+                    WalkTable::new(&subgroup_base, modulus, prime, table_size.max(1))
+                })
+            })
+            .collect();
+
+        DiscreteLogTable {
+            base: base.clone(),
+            modulus: modulus.clone(),
+            order_factors,
+            walks,
+        }
+    }
+
+    /// Get the order of the base.
+    #[must_use]
+    pub fn order(&self) -> BigUint {
+        self.order_factors
+            .iter()
+            .fold(BigUint::one(), |order, (prime, exponent)| {
+                order * prime.pow(*exponent)
+            })
+    }
+
+    /// Solve `base^x = target`.
+    ///
+    /// Returns the smallest `x`, or `None` if there is none.
+    #[must_use]
+    pub fn log(&self, target: &BigUint) -> Option<BigUint> {
+        pohlig_hellman(
+            &self.base,
+            target,
+            &self.modulus,
+            &self.order_factors,
+            |i, subgroup_base, subgroup_target, prime| {
+                self.walks[i]
+                    .as_ref()
+                    .and_then(|walks| {
+                        walks.log(subgroup_base, subgroup_target, &self.modulus, prime)
+                    })
+                    .or_else(|| pollard_rho(subgroup_base, subgroup_target, &self.modulus, prime))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{discrete_log, factor, order_factors, pollard_rho, DiscreteLogTable};
+    use crate::rand::with_seed;
+
+    const PRIME: u64 = 1_000_000_007;
+
+    #[test]
+    fn test_factor() {
+        let n = BigUint::from(2_usize).pow(5) * 3_u32 * PRIME * PRIME * 1_000_000_009_u64;
+
+        assert_eq!(
+            with_seed(1337, || factor(&n)),
+            vec![
+                (BigUint::from(2_usize), 5),
+                (BigUint::from(3_usize), 1),
+                (BigUint::from(PRIME), 2),
+                (BigUint::from(1_000_000_009_u64), 1),
+            ]
+        );
+
+        assert_eq!(factor(&BigUint::from(1_usize)), vec![]);
+        assert_eq!(
+            factor(&BigUint::from(PRIME)),
+            vec![(BigUint::from(PRIME), 1)]
+        );
+    }
+
+    #[test]
+    fn test_order_factors() {
+        let modulus = BigUint::from(PRIME);
+
+        // `(p - 1) / 2` is prime and 4 is a square
+        assert_eq!(
+            order_factors(&BigUint::from(4_usize), &modulus),
+            vec![(BigUint::from((PRIME - 1) / 2), 1)]
+        );
+        assert_eq!(
+            order_factors(&(&modulus - 1_u32), &modulus),
+            vec![(BigUint::from(2_usize), 1)]
+        );
+        assert_eq!(order_factors(&BigUint::from(1_usize), &modulus), vec![]);
+    }
+
+    #[test]
+    fn test_discrete_log() {
+        // `p - 1 = 2^16` (prime powers) and `p - 1 = 2 * 500000003` (rho)
+        for (base, modulus) in [(3_u64, 65537_u64), (5, PRIME)] {
+            let (base, modulus) = (BigUint::from(base), BigUint::from(modulus));
+
+            for exponent in [0_u64, 1, 2, 12345, 31337] {
+                let target = base.modpow(&BigUint::from(exponent), &modulus);
+
+                assert_eq!(
+                    discrete_log(&base, &target, &modulus),
+                    Some(BigUint::from(exponent))
+                );
+            }
+        }
+
+        // 5 is not a square, so it is not generated by 4
+        let modulus = BigUint::from(PRIME);
+
+        assert_eq!(
+            discrete_log(&BigUint::from(4_usize), &BigUint::from(5_usize), &modulus),
+            None
+        );
+        assert_eq!(
+            pollard_rho(
+                &BigUint::from(4_usize),
+                &BigUint::from(5_usize),
+                &modulus,
+                &BigUint::from((PRIME - 1) / 2)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_discrete_log_table() {
+        let (base, modulus) = (BigUint::from(5_usize), BigUint::from(PRIME));
+
+        let table = with_seed(1337, || DiscreteLogTable::new(&base, &modulus, 64));
+
+        assert_eq!(table.order(), &modulus - 1_u32);
+
+        for exponent in [0_u64, 1, 31337, 123_456_789, PRIME - 2] {
+            let target = base.modpow(&BigUint::from(exponent), &modulus);
+
+            assert_eq!(table.log(&target), Some(BigUint::from(exponent)));
+        }
+
+        let table = DiscreteLogTable::new(&BigUint::from(4_usize), &modulus, 64);
+
+        assert_eq!(table.log(&BigUint::from(5_usize)), None);
+    }
+}
@@ -0,0 +1,272 @@
+//! [Finite fields](https://en.wikipedia.org/wiki/Finite_field) for generic
+//! algebra (see [`Poly`](super::poly::Poly)).
+//!
+//! A [`Field`] is a context object (e.g. holding the modulus) that performs
+//! arithmetic on plain element values, so elements stay cheap to store.
+
+use std::fmt::Debug;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::Rng;
+
+use super::binary_inv_mod;
+use crate::rand::rng;
+
+/// Arithmetic over a finite field.
+pub trait Field {
+    /// Field element. Must always be kept in canonical (reduced) form.
+    type Elem: Clone + Eq + Debug;
+
+    /// Additive identity.
+    fn zero(&self) -> Self::Elem;
+
+    /// Multiplicative identity.
+    fn one(&self) -> Self::Elem;
+
+    /// Compute `a + b`.
+    fn add(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
+
+    /// Compute `-a`.
+    fn neg(&self, a: &Self::Elem) -> Self::Elem;
+
+    /// Compute `a * b`.
+    fn mul(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
+
+    /// Compute `a^-1`.
+    ///
+    /// Returns `None` for zero (or for non-invertible elements when the
+    /// "field" is actually a ring, like `Z_n` for composite `n`).
+    fn inv(&self, a: &Self::Elem) -> Option<Self::Elem>;
+
+    /// Amount of elements in the field.
+    fn order(&self) -> BigUint;
+
+    /// Generate a uniformly random element.
+    fn random(&self) -> Self::Elem;
+
+    /// Compute `a - b`.
+    fn sub(&self, a: &Self::Elem, b: &Self::Elem) -> Self::Elem {
+        self.add(a, &self.neg(b))
+    }
+
+    /// Whether `a` is zero.
+    fn is_zero(&self, a: &Self::Elem) -> bool {
+        a == &self.zero()
+    }
+}
+
+/// Integers modulo `n` (a field only when `n` is prime).
+///
+/// Elements are [`BigUint`]s in `[0, n)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ModP(pub BigUint);
+
+impl ModP {
+    /// Reduce `x` into a field element.
+    #[must_use]
+    pub fn elem(&self, x: &BigUint) -> BigUint {
+        x % &self.0
+    }
+}
+
+impl Field for ModP {
+    type Elem = BigUint;
+
+    fn zero(&self) -> BigUint {
+        BigUint::zero()
+    }
+
+    fn one(&self) -> BigUint {
+        BigUint::one() % &self.0
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + b) % &self.0
+    }
+
+    fn neg(&self, a: &BigUint) -> BigUint {
+        (&self.0 - a) % &self.0
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.0
+    }
+
+    fn inv(&self, a: &BigUint) -> Option<BigUint> {
+        if a.is_zero() {
+            return None;
+        }
+
+        binary_inv_mod(a, &self.0)
+    }
+
+    fn order(&self) -> BigUint {
+        self.0.clone()
+    }
+
+    fn random(&self) -> BigUint {
+        rng().gen_biguint_below(&self.0)
+    }
+}
+
+/// `GF(2^128)` as used by [GCM](https://en.wikipedia.org/wiki/Galois/Counter_Mode)
+/// (modulo `x^128 + x^7 + x^2 + x + 1`).
+///
+/// Elements are `u128`s holding the big-endian interpretation of a 16-byte
+/// block. Like in GCM the bit order is reflected: the most significant bit is
+/// the coefficient of `x^0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GF128;
+
+/// Reduction constant (`x^7 + x^2 + x + 1`, reflected).
+const GF128_R: u128 = 0xE1 << 120;
+
+impl GF128 {
+    /// Element from a 16-byte block.
+    #[must_use]
+    pub const fn from_block(block: [u8; 16]) -> u128 {
+        u128::from_be_bytes(block)
+    }
+
+    /// Element into a 16-byte block.
+    #[must_use]
+    pub const fn to_block(elem: u128) -> [u8; 16] {
+        elem.to_be_bytes()
+    }
+
+    /// Compute `a^exp`.
+    #[must_use]
+    pub fn pow(self, a: u128, mut exp: u128) -> u128 {
+        let mut base = a;
+        let mut result = self.one();
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = self.mul(&result, &base);
+            }
+
+            base = self.mul(&base, &base);
+            exp >>= 1;
+        }
+
+        result
+    }
+}
+
+impl Field for GF128 {
+    type Elem = u128;
+
+    fn zero(&self) -> u128 {
+        0
+    }
+
+    fn one(&self) -> u128 {
+        1 << 127
+    }
+
+    fn add(&self, a: &u128, b: &u128) -> u128 {
+        a ^ b
+    }
+
+    fn neg(&self, a: &u128) -> u128 {
+        *a
+    }
+
+    /// Algorithm 1 from NIST SP 800-38D.
+    fn mul(&self, a: &u128, b: &u128) -> u128 {
+        let mut z = 0;
+        let mut v = *b;
+
+        for i in (0..128).rev() {
+            if (a >> i) & 1 == 1 {
+                z ^= v;
+            }
+
+            v = if v & 1 == 1 {
+                (v >> 1) ^ GF128_R
+            } else {
+                v >> 1
+            };
+        }
+
+        z
+    }
+
+    /// By Fermat's little theorem: `a^-1 = a^(2^128 - 2)`.
+    fn inv(&self, a: &u128) -> Option<u128> {
+        if *a == 0 {
+            return None;
+        }
+
+        Some(self.pow(*a, u128::MAX - 1))
+    }
+
+    fn order(&self) -> BigUint {
+        BigUint::one() << 128
+    }
+
+    fn random(&self) -> u128 {
+        rng().gen()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{Field, ModP, GF128};
+
+    /// Check field axioms on random elements.
+    fn check_axioms<F: Field>(field: &F) {
+        for _ in 0..50 {
+            let (a, b, c) = (field.random(), field.random(), field.random());
+
+            assert_eq!(field.add(&a, &b), field.add(&b, &a));
+            assert_eq!(field.mul(&a, &b), field.mul(&b, &a));
+            assert_eq!(
+                field.mul(&a, &field.add(&b, &c)),
+                field.add(&field.mul(&a, &b), &field.mul(&a, &c))
+            );
+            assert_eq!(
+                field.mul(&field.mul(&a, &b), &c),
+                field.mul(&a, &field.mul(&b, &c))
+            );
+            assert_eq!(field.sub(&a, &a), field.zero());
+            assert_eq!(field.mul(&a, &field.one()), a);
+
+            match field.inv(&a) {
+                Some(a_inv) => assert_eq!(field.mul(&a, &a_inv), field.one()),
+                None => assert!(field.is_zero(&a)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mod_p_axioms() {
+        check_axioms(&ModP(BigUint::from(1_000_000_007_usize)));
+    }
+
+    #[test]
+    fn test_gf128_axioms() {
+        check_axioms(&GF128);
+    }
+
+    #[test]
+    fn test_gf128_mul() {
+        // Test case 2 from the GCM spec: H = E(K, 0^128), GHASH(H, {}, C)
+        let h =
+            GF128::from_block(*b"\x66\xe9\x4b\xd4\xef\x8a\x2c\x3b\x88\x4c\xfa\x59\xca\x34\x2b\x2e");
+        let c =
+            GF128::from_block(*b"\x03\x88\xda\xce\x60\xb6\xa3\x92\xf3\x28\xc2\xb9\x71\xb2\xfe\x78");
+        let len_block =
+            GF128::from_block(*b"\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x80");
+
+        let ghash = GF128.mul(&GF128.add(&GF128.mul(&c, &h), &len_block), &h);
+
+        assert_eq!(
+            GF128::to_block(ghash),
+            *b"\xf3\x8c\xbb\x1a\xd6\x92\x23\xdc\xc3\x45\x7a\xe5\xb6\xb0\xf8\x85"
+        );
+    }
+}
@@ -0,0 +1,466 @@
+//! Fixed-width unsigned integers, for [constant-time](https://en.wikipedia.org/wiki/Timing_attack)
+//! experiments.
+//!
+//! `BigUint` trims leading zeros, so both its memory layout and running time
+//! depend on the magnitude of each value. [`FixedUint`] instead always works
+//! on every limb, avoiding secret-dependent branches and memory accesses:
+//! conditional operations are done by masking (see [`FixedUint::ct_select`])
+//! and modular exponentiation always performs the same sequence of
+//! [Montgomery](https://en.wikipedia.org/wiki/Montgomery_modular_multiplication)
+//! multiplications, whatever the exponent.
+//!
+//! Modular operations need an odd modulus (as Montgomery reduction does),
+//! which is always the case for RSA and Diffie-Hellman. Both can run on
+//! these types through [`BigIntBackend`], see
+//! [`RSAPrivateKey::textbook_process_without_crt_on`](crate::rsa::RSAPrivateKey::textbook_process_without_crt_on)
+//! and [`DHOffer::shared_secret_on`](crate::key_exchange::DHOffer::shared_secret_on).
+//!
+//! Keep in mind that the compiler is free to reintroduce branches, so any
+//! constant-time claim must be checked on the generated code.
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use rand::Rng;
+
+use super::bigint::BigIntBackend;
+use super::bigmul::{add_assign, mul, sub_assign};
+use super::binary_inv_mod;
+use crate::rand::rng;
+
+/// Unsigned integer of `LIMBS` 64-bit limbs (least significant first).
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedUint<const LIMBS: usize>([u64; LIMBS]);
+
+/// 2048-bit unsigned integer.
+pub type U2048 = FixedUint<32>;
+
+/// 4096-bit unsigned integer.
+pub type U4096 = FixedUint<64>;
+
+impl<const LIMBS: usize> FixedUint<LIMBS> {
+    /// Width in bits.
+    pub const BITS: usize = 64 * LIMBS;
+
+    /// Zero.
+    pub const ZERO: Self = FixedUint([0; LIMBS]);
+
+    /// Create from limbs (least significant first).
+    #[must_use]
+    pub const fn from_limbs(limbs: [u64; LIMBS]) -> Self {
+        FixedUint(limbs)
+    }
+
+    /// Get the limbs (least significant first).
+    #[must_use]
+    pub const fn limbs(&self) -> &[u64; LIMBS] {
+        &self.0
+    }
+
+    /// Convert from a `u64`.
+    #[must_use]
+    pub const fn from_u64(x: u64) -> Self {
+        let mut limbs = [0; LIMBS];
+        limbs[0] = x;
+
+        FixedUint(limbs)
+    }
+
+    /// Compute `self + other`, returning the carry (`0` or `1`).
+    #[must_use]
+    pub fn overflowing_add(&self, other: &Self) -> (Self, u64) {
+        let mut result = *self;
+        let carry = add_assign(&mut result.0, &other.0);
+
+        (result, carry)
+    }
+
+    /// Compute `self - other`, returning the borrow (`0` or `1`).
+    #[must_use]
+    pub fn overflowing_sub(&self, other: &Self) -> (Self, u64) {
+        let mut result = *self;
+        let borrow = sub_assign(&mut result.0, &other.0);
+
+        (result, borrow)
+    }
+
+    /// Select `a` if `choice == 0` or `b` if `choice == 1`, without branching.
+    #[must_use]
+    pub fn ct_select(a: &Self, b: &Self, choice: u64) -> Self {
+        let mask = 0_u64.wrapping_sub(choice);
+        let mut result = *a;
+
+        for (x, y) in result.0.iter_mut().zip(&b.0) {
+            *x ^= mask & (*x ^ y);
+        }
+
+        result
+    }
+
+    /// `1` if `self == other`, `0` otherwise, without branching.
+    #[must_use]
+    pub fn ct_eq(&self, other: &Self) -> u64 {
+        let diff = self
+            .0
+            .iter()
+            .zip(&other.0)
+            .fold(0, |acc, (x, y)| acc | (x ^ y));
+
+        ((diff | diff.wrapping_neg()) >> 63) ^ 1
+    }
+
+    /// `1` if `self < other`, `0` otherwise, without branching.
+    #[must_use]
+    pub fn ct_lt(&self, other: &Self) -> u64 {
+        self.overflowing_sub(other).1
+    }
+
+    /// Compute `(self + other) mod modulus` (both operands must be reduced).
+    #[must_use]
+    pub fn add_mod(&self, other: &Self, modulus: &Self) -> Self {
+        let (sum, carry) = self.overflowing_add(other);
+        let (reduced, borrow) = sum.overflowing_sub(modulus);
+
+        // Reduce if the sum overflowed, or if it did not underflow when reducing
+        Self::ct_select(&sum, &reduced, carry | (borrow ^ 1))
+    }
+
+    /// Compute `(self - other) mod modulus` (both operands must be reduced).
+    #[must_use]
+    pub fn sub_mod(&self, other: &Self, modulus: &Self) -> Self {
+        let (difference, borrow) = self.overflowing_sub(other);
+        let (wrapped, _) = difference.overflowing_add(modulus);
+
+        Self::ct_select(&difference, &wrapped, borrow)
+    }
+
+    /// Compute the full `2 * LIMBS`-limb product `self * other`.
+    #[must_use]
+    pub fn widening_mul(&self, other: &Self) -> Vec<u64> {
+        let mut product = vec![0; 2 * LIMBS];
+        mul(&self.0, &other.0, &mut product);
+
+        product
+    }
+
+    /// Bit length (position of the highest set bit).
+    ///
+    /// Not constant-time: only use it on public values.
+    #[must_use]
+    pub fn bits(&self) -> usize {
+        self.0
+            .iter()
+            .rposition(|&limb| limb != 0)
+            .map_or(0, |i| 64 * (i + 1) - self.0[i].leading_zeros() as usize)
+    }
+
+    /// Value of bit number `index`.
+    #[must_use]
+    pub const fn bit(&self, index: usize) -> u64 {
+        (self.0[index / 64] >> (index % 64)) & 1
+    }
+}
+
+impl<const LIMBS: usize> fmt::Debug for FixedUint<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FixedUint(0x")?;
+
+        for limb in self.0.iter().rev() {
+            write!(f, "{limb:016x}")?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl<const LIMBS: usize> From<&FixedUint<LIMBS>> for BigUint {
+    fn from(x: &FixedUint<LIMBS>) -> Self {
+        BigUint::from_bytes_be(&BigIntBackend::to_bytes_be(x))
+    }
+}
+
+/// [Montgomery](https://en.wikipedia.org/wiki/Montgomery_modular_multiplication)
+/// arithmetic modulo an odd `n`, with `R = 2^(64 * LIMBS)`.
+#[derive(Clone, Debug)]
+pub struct Montgomery<const LIMBS: usize> {
+    n: FixedUint<LIMBS>,
+    /// `-n^-1 mod 2^64`
+    n_prime: u64,
+    /// `R^2 mod n`
+    r2: FixedUint<LIMBS>,
+}
+
+impl<const LIMBS: usize> Montgomery<LIMBS> {
+    /// Create a Montgomery context for modulus `n`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is even or one.
+    #[must_use]
+    pub fn new(n: &FixedUint<LIMBS>) -> Self {
+        assert_eq!(n.bit(0), 1, "Montgomery reduction needs an odd modulus");
+        assert_eq!(n.ct_eq(&FixedUint::from_u64(1)), 0, "Modulus must be > 1");
+
+        // Newton's iteration doubles the correct low bits of `n^-1` each step
+        let mut inv = 1_u64;
+
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2_u64.wrapping_sub(n.0[0].wrapping_mul(inv)));
+        }
+
+        // `R^2 mod n` by doubling `1` (mod n) `2 * BITS` times
+        let mut r2 = FixedUint::from_u64(1);
+
+        for _ in 0..2 * FixedUint::<LIMBS>::BITS {
+            r2 = r2.add_mod(&r2, n);
+        }
+
+        Montgomery {
+            n: *n,
+            n_prime: inv.wrapping_neg(),
+            r2,
+        }
+    }
+
+    /// Get the modulus.
+    #[must_use]
+    pub const fn modulus(&self) -> &FixedUint<LIMBS> {
+        &self.n
+    }
+
+    /// [REDC](https://en.wikipedia.org/wiki/Montgomery_modular_multiplication#The_REDC_algorithm):
+    /// compute `t * R^-1 mod n` for a `2 * LIMBS`-limb `t < n * R`.
+    fn redc(&self, t: &[u64]) -> FixedUint<LIMBS> {
+        // One extra limb for the final carry
+        let mut t = [t, &[0]].concat();
+
+        for i in 0..LIMBS {
+            let m = t[i].wrapping_mul(self.n_prime);
+            let mut carry = 0;
+
+            for j in 0..LIMBS {
+                let sum = u128::from(t[i + j]) + u128::from(m) * u128::from(self.n.0[j]) + carry;
+                t[i + j] = sum as u64;
+                carry = sum >> 64;
+            }
+
+            for limb in &mut t[i + LIMBS..] {
+                let sum = u128::from(*limb) + carry;
+                *limb = sum as u64;
+                carry = sum >> 64;
+            }
+        }
+
+        let mut result = FixedUint::ZERO;
+        result.0.copy_from_slice(&t[LIMBS..2 * LIMBS]);
+
+        let (reduced, borrow) = result.overflowing_sub(&self.n);
+
+        FixedUint::ct_select(&result, &reduced, t[2 * LIMBS] | (borrow ^ 1))
+    }
+
+    /// Montgomery product: `a * b * R^-1 mod n`.
+    #[must_use]
+    pub fn mul(&self, a: &FixedUint<LIMBS>, b: &FixedUint<LIMBS>) -> FixedUint<LIMBS> {
+        self.redc(&a.widening_mul(b))
+    }
+
+    /// Convert `a` (reduced mod `n`) into Montgomery form (`a * R mod n`).
+    #[must_use]
+    pub fn to_montgomery(&self, a: &FixedUint<LIMBS>) -> FixedUint<LIMBS> {
+        self.mul(a, &self.r2)
+    }
+
+    /// Convert `a` back from Montgomery form.
+    #[must_use]
+    pub fn from_montgomery(&self, a: &FixedUint<LIMBS>) -> FixedUint<LIMBS> {
+        self.redc(&[&a.0[..], &[0; LIMBS]].concat())
+    }
+
+    /// Compute `a * b mod n` (both operands must be reduced).
+    #[must_use]
+    pub fn mul_mod(&self, a: &FixedUint<LIMBS>, b: &FixedUint<LIMBS>) -> FixedUint<LIMBS> {
+        // (a * b * R^-1) * R^2 * R^-1 = a * b
+        self.mul(&self.mul(a, b), &self.r2)
+    }
+
+    /// Compute `base^exponent mod n` (`base` must be reduced).
+    ///
+    /// Always performs a squaring and a multiplication per exponent bit (over
+    /// the whole width), so the running time does not depend on the
+    /// exponent.
+    #[must_use]
+    pub fn pow(&self, base: &FixedUint<LIMBS>, exponent: &FixedUint<LIMBS>) -> FixedUint<LIMBS> {
+        let base = self.to_montgomery(base);
+        let mut result = self.to_montgomery(&FixedUint::from_u64(1));
+
+        for i in (0..FixedUint::<LIMBS>::BITS).rev() {
+            result = self.mul(&result, &result);
+
+            let multiplied = self.mul(&result, &base);
+            result = FixedUint::ct_select(&result, &multiplied, exponent.bit(i));
+        }
+
+        self.from_montgomery(&result)
+    }
+}
+
+impl<const LIMBS: usize> BigIntBackend for FixedUint<LIMBS> {
+    fn from_bytes_be(bytes: &[u8]) -> Option<Self> {
+        let split = bytes.len().saturating_sub(8 * LIMBS);
+        let (overflow, bytes) = bytes.split_at(split);
+
+        if overflow.iter().any(|&byte| byte != 0) {
+            return None;
+        }
+
+        let mut result = Self::ZERO;
+
+        for (i, &byte) in bytes.iter().rev().enumerate() {
+            result.0[i / 8] |= u64::from(byte) << (8 * (i % 8));
+        }
+
+        Some(result)
+    }
+
+    /// Not constant-time (leading zero bytes are trimmed).
+    fn to_bytes_be(&self) -> Vec<u8> {
+        let bytes = self
+            .0
+            .iter()
+            .rev()
+            .flat_map(|limb| limb.to_be_bytes())
+            .collect::<Vec<_>>();
+
+        let first = bytes
+            .iter()
+            .position(|&byte| byte != 0)
+            .unwrap_or(bytes.len());
+
+        bytes[first..].to_vec()
+    }
+
+    fn from_u64(x: u64) -> Self {
+        FixedUint::from_u64(x)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.ct_eq(&Self::ZERO) == 1
+    }
+
+    fn less_than(&self, other: &Self) -> bool {
+        self.ct_lt(other) == 1
+    }
+
+    fn add_mod(&self, other: &Self, modulus: &Self) -> Self {
+        FixedUint::add_mod(self, other, modulus)
+    }
+
+    /// # Panics
+    ///
+    /// If `modulus` is even or one.
+    fn mul_mod(&self, other: &Self, modulus: &Self) -> Self {
+        Montgomery::new(modulus).mul_mod(self, other)
+    }
+
+    /// # Panics
+    ///
+    /// If `modulus` is even or one.
+    fn modpow(&self, exponent: &Self, modulus: &Self) -> Self {
+        Montgomery::new(modulus).pow(self, exponent)
+    }
+
+    /// Not constant-time: goes through `BigUint`.
+    fn modinv(&self, modulus: &Self) -> Option<Self> {
+        let inverse = binary_inv_mod(&BigUint::from(self), &BigUint::from(modulus))?;
+
+        BigIntBackend::from_bytes_be(&inverse.to_bytes_be())
+    }
+
+    /// Rejection sampling: the amount of attempts does not depend on the
+    /// result.
+    fn random_below(bound: &Self) -> Self {
+        let bits = bound.bits();
+        let mut rng = rng();
+
+        loop {
+            let mut candidate = Self::ZERO;
+
+            for (i, limb) in candidate.0.iter_mut().enumerate() {
+                let limb_bits = bits.saturating_sub(64 * i).min(64);
+
+                *limb = match limb_bits {
+                    0 => 0,
+                    64 => rng.gen(),
+                    _ => rng.gen::<u64>() >> (64 - limb_bits),
+                };
+            }
+
+            if candidate.ct_lt(bound) == 1 {
+                return candidate;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::{BigUint, RandBigInt};
+
+    use super::{FixedUint, Montgomery, U2048, U4096};
+    use crate::rand::rng;
+    use crate::testkeys::{RSA_KEYPAIR_0, RSA_KEYPAIR_1};
+    use crate::util::bigint::{check_backend, BigIntBackend};
+
+    #[test]
+    fn test_ct_helpers() {
+        let (a, b) = (U2048::from_u64(5), U2048::from_u64(7));
+
+        assert_eq!(FixedUint::ct_select(&a, &b, 0), a);
+        assert_eq!(FixedUint::ct_select(&a, &b, 1), b);
+        assert_eq!(a.ct_eq(&a), 1);
+        assert_eq!(a.ct_eq(&b), 0);
+        assert_eq!(a.ct_lt(&b), 1);
+        assert_eq!(b.ct_lt(&a), 0);
+        assert_eq!(a.ct_lt(&a), 0);
+    }
+
+    #[test]
+    fn test_montgomery() {
+        let modulus = &RSA_KEYPAIR_0.0.n;
+        let n = U2048::from_bytes_be(&modulus.to_bytes_be()).unwrap();
+        let montgomery = Montgomery::new(&n);
+
+        let a = rng().gen_biguint_below(modulus);
+        let a_fixed = U2048::from_bytes_be(&a.to_bytes_be()).unwrap();
+
+        assert_eq!(
+            montgomery.from_montgomery(&montgomery.to_montgomery(&a_fixed)),
+            a_fixed
+        );
+        assert_eq!(
+            BigUint::from(&montgomery.mul_mod(&a_fixed, &a_fixed)),
+            (&a * &a) % modulus
+        );
+    }
+
+    #[test]
+    fn test_u2048_backend() {
+        check_backend::<U2048>(&RSA_KEYPAIR_0.0.n, 5);
+        check_backend::<U2048>(&BigUint::from(u64::MAX - 58), 5);
+    }
+
+    #[test]
+    fn test_u4096_backend() {
+        check_backend::<U4096>(&(&RSA_KEYPAIR_0.0.n * &RSA_KEYPAIR_1.0.n), 1);
+    }
+
+    #[test]
+    fn test_from_bytes_be() {
+        assert_eq!(
+            FixedUint::<1>::from_bytes_be(&[0, 0, 1, 2, 3, 4, 5, 6, 7, 8]),
+            Some(FixedUint::from_u64(0x0102030405060708))
+        );
+        assert_eq!(FixedUint::<1>::from_bytes_be(&[1; 9]), None);
+    }
+}
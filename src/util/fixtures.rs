@@ -0,0 +1,381 @@
+//! Loaders for the text fixtures the challenges come with, and for bigger
+//! generated or packed ones.
+//!
+//! Challenge inputs are either a single base64 blob wrapped over many lines,
+//! or one base64/hex value per line.
+//!
+//! Tests needing multi-megabyte corpora (e.g. to gather statistics) should
+//! not bloat the repository with them. A [`Corpus`] is generated from a seed
+//! as it is read, so only the seed needs to be stored. Fixtures that can't be
+//! generated can be [`pack`]ed instead: compressed (optionally) and encrypted
+//! at rest, so they are compact and don't show up when searching the tree.
+//!
+//! # Example
+//!
+//! ```
+//! use std::io::Read;
+//!
+//! use rustopals::util::fixtures::{pack, unpack, Corpus};
+//!
+//! let mut corpus = Vec::new();
+//! Corpus::new(1337, 4096).read_to_end(&mut corpus).unwrap();
+//!
+//! assert_eq!(corpus.len(), 4096);
+//! assert_eq!(Corpus::new(1337, 4096).chunks(1000).flatten().collect::<Vec<_>>(), corpus);
+//!
+//! let fixture = b"Some rather repetitive fixture.\n".repeat(100);
+//! let packed = pack(1337, &fixture, true);
+//!
+//! assert!(packed.len() < fixture.len());
+//! assert_eq!(unpack(1337, &packed), Ok(fixture));
+//! ```
+
+use std::fmt;
+use std::io::{self, Read};
+
+use crate::block::{BlockCipher, Key, AES128};
+use crate::digest::ds::hash_labeled;
+use crate::digest::{Digest, SHA256};
+use crate::stream::ctr::{CtrContext, CtrMessage};
+use crate::stream::{SeekableStreamCipher, StreamCipher};
+use crate::util::compress::{compress, decompress};
+use crate::util::ct_eq;
+
+/// Domain separation label for [`Corpus`] keys.
+pub const CORPUS_LABEL: &[u8] = b"rustopals-fixture-corpus";
+
+/// Domain separation label for [`pack`] keys.
+pub const PACKED_LABEL: &[u8] = b"rustopals-fixture-packed";
+
+/// Header of [`pack`]ed fixtures.
+pub const PACKED_MAGIC: &[u8; 4] = b"RFIX";
+
+/// Flag for [`pack`]ed fixtures that were compressed.
+const PACKED_COMPRESSED: u8 = 0x01;
+
+/// Nonce for every fixture keystream (each seed gets its own key).
+const FIXTURE_NONCE: [u8; 8] = [0; 8];
+
+/// An invalid line in a fixture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FixtureError {
+    /// Invalid base64 on the given line (1-based).
+    Base64 { line: usize },
+
+    /// Invalid hex on the given line (1-based).
+    Hex { line: usize },
+
+    /// Not a [`pack`]ed fixture, or packed under a different seed.
+    Packed,
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixtureError::Base64 { line } => write!(f, "invalid base64 on line {line}"),
+            FixtureError::Hex { line } => write!(f, "invalid hex on line {line}"),
+            FixtureError::Packed => write!(f, "invalid packed fixture"),
+        }
+    }
+}
+
+impl std::error::Error for FixtureError {}
+
+/// Iterate over the base64-decoded lines of `input`.
+pub fn lines_base64(input: &str) -> impl Iterator<Item = Result<Vec<u8>, FixtureError>> + '_ {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| base64::decode(line).map_err(|_| FixtureError::Base64 { line: i + 1 }))
+}
+
+/// Iterate over the hex-decoded lines of `input`.
+pub fn lines_hex(input: &str) -> impl Iterator<Item = Result<Vec<u8>, FixtureError>> + '_ {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| hex::decode(line).map_err(|_| FixtureError::Hex { line: i + 1 }))
+}
+
+/// Decode a single base64 value wrapped over multiple lines.
+///
+/// # Errors
+///
+/// If the joined lines are not valid base64. The reported line is the first
+/// one with non-base64 characters (or the last one, if the error is in the
+/// padding).
+pub fn decode_base64_multiline(input: &str) -> Result<Vec<u8>, FixtureError> {
+    base64::decode(input.lines().collect::<String>()).map_err(|_| {
+        let line = input
+            .lines()
+            .position(|line| {
+                !line
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || b"+/=".contains(&byte))
+            })
+            .unwrap_or_else(|| input.lines().count().saturating_sub(1));
+
+        FixtureError::Base64 { line: line + 1 }
+    })
+}
+
+/// Decode each line of `input` as an independent base64 value.
+///
+/// # Errors
+///
+/// On the first line that is not valid base64.
+pub fn decode_base64_lines(input: &str) -> Result<Vec<Vec<u8>>, FixtureError> {
+    lines_base64(input).collect()
+}
+
+/// Decode each line of `input` as an independent hex value.
+///
+/// # Errors
+///
+/// On the first line that is not valid hex.
+pub fn decode_hex_lines(input: &str) -> Result<Vec<Vec<u8>>, FixtureError> {
+    lines_hex(input).collect()
+}
+
+fn fixture_key(label: &[u8], seed: u64) -> Key<AES128> {
+    Key::new(&hash_labeled::<SHA256>(label, &[&seed.to_be_bytes()])[..AES128::KEY_SIZE])
+        .expect("SHA-256 digests are longer than AES-128 keys")
+}
+
+/// The single fixture message under `context`.
+fn fixture_message<'x>(
+    context: &'x CtrContext<'static, AES128>,
+) -> CtrMessage<'x, 'static, AES128> {
+    context
+        .with_nonce(&FIXTURE_NONCE)
+        .expect("The fixture nonce fits the default counter layout")
+}
+
+/// A deterministic pseudo-random corpus, derived from a seed.
+///
+/// It is the AES-128-CTR keystream under a key derived from the seed.
+/// Nothing is stored: bytes are generated as they are read.
+#[must_use]
+pub struct Corpus {
+    context: CtrContext<'static, AES128>,
+    position: usize,
+    len: usize,
+}
+
+impl Corpus {
+    /// Create a `len` bytes long corpus from `seed`.
+    pub fn new(seed: u64, len: usize) -> Corpus {
+        Corpus {
+            context: CtrContext::new(&AES128, &fixture_key(CORPUS_LABEL, seed)),
+            position: 0,
+            len,
+        }
+    }
+
+    /// Get the total length of the corpus.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the corpus is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the rest of the corpus in `chunk_len` bytes long chunks
+    /// (the last one may be shorter).
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_len` is zero.
+    pub fn chunks(mut self, chunk_len: usize) -> impl Iterator<Item = Vec<u8>> {
+        assert!(chunk_len > 0, "Chunks should not be empty");
+
+        std::iter::from_fn(move || {
+            let mut chunk = vec![0; chunk_len.min(self.len - self.position)];
+
+            match self.read(&mut chunk) {
+                Ok(0) | Err(_) => None,
+                Ok(_) => Some(chunk),
+            }
+        })
+    }
+}
+
+impl Read for Corpus {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = buf.len().min(self.len - self.position);
+
+        let keystream = fixture_message(&self.context).keystream_from(self.position);
+
+        for (byte, key) in buf[..read].iter_mut().zip(keystream) {
+            *byte = key;
+        }
+
+        self.position += read;
+
+        Ok(read)
+    }
+}
+
+/// Pack some fixture `data`, encrypting it at rest under a key derived from
+/// `seed` (and optionally `compress`ing it first).
+///
+/// The format is:
+///
+/// ```text
+/// PACKED_MAGIC || flags (1 byte) || SHA-256(body) || AES-128-CTR(body)
+/// ```
+///
+/// Where `body` is either `data` or `compress(data)`.
+#[must_use]
+pub fn pack(seed: u64, data: &[u8], compress_data: bool) -> Vec<u8> {
+    let (flags, body) = if compress_data {
+        (PACKED_COMPRESSED, compress(data))
+    } else {
+        (0, data.to_vec())
+    };
+
+    let context = CtrContext::new(&AES128, &fixture_key(PACKED_LABEL, seed));
+
+    let mut packed = PACKED_MAGIC.to_vec();
+    packed.push(flags);
+    packed.extend_from_slice(&SHA256::digest(&body));
+    packed.extend(fixture_message(&context).process(body));
+
+    packed
+}
+
+/// Unpack a fixture from [`pack`], with the same `seed` it was packed with.
+///
+/// # Errors
+///
+/// If `packed` is malformed, corrupted or was packed with a different seed.
+pub fn unpack(seed: u64, packed: &[u8]) -> Result<Vec<u8>, FixtureError> {
+    let header_len = PACKED_MAGIC.len() + 1 + SHA256::OUTPUT_LENGTH;
+
+    if packed.len() < header_len || !packed.starts_with(PACKED_MAGIC) {
+        return Err(FixtureError::Packed);
+    }
+
+    let flags = packed[PACKED_MAGIC.len()];
+    let digest = &packed[PACKED_MAGIC.len() + 1..header_len];
+
+    let context = CtrContext::new(&AES128, &fixture_key(PACKED_LABEL, seed));
+    let body = fixture_message(&context)
+        .process(packed[header_len..].iter().copied())
+        .collect::<Vec<_>>();
+
+    if !ct_eq(&SHA256::digest(&body), digest) {
+        return Err(FixtureError::Packed);
+    }
+
+    match flags {
+        0 => Ok(body),
+        PACKED_COMPRESSED => decompress(&body).ok_or(FixtureError::Packed),
+        _ => Err(FixtureError::Packed),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use super::{
+        decode_base64_lines, decode_base64_multiline, decode_hex_lines, pack, unpack, Corpus,
+        FixtureError,
+    };
+
+    #[test]
+    fn test_decode_base64_multiline() {
+        assert_eq!(
+            decode_base64_multiline("SGVs\nbG8s\nIFdvcmxkIQ==\n"),
+            Ok(b"Hello, World!".to_vec())
+        );
+
+        assert_eq!(
+            decode_base64_multiline("SGVs\nb!8s\nIFdvcmxkIQ==\n"),
+            Err(FixtureError::Base64 { line: 2 })
+        );
+    }
+
+    #[test]
+    fn test_decode_lines() {
+        assert_eq!(
+            decode_base64_lines("SGVsbG8=\r\nV29ybGQ=\r\n"),
+            Ok(vec![b"Hello".to_vec(), b"World".to_vec()])
+        );
+
+        assert_eq!(
+            decode_hex_lines("00ff\n1337\n"),
+            Ok(vec![vec![0x00, 0xff], vec![0x13, 0x37]])
+        );
+
+        assert_eq!(
+            decode_hex_lines("00ff\n133\n").unwrap_err().to_string(),
+            "invalid hex on line 2"
+        );
+    }
+
+    #[test]
+    fn test_corpus() {
+        let mut corpus = Vec::new();
+        Corpus::new(1337, 100_000).read_to_end(&mut corpus).unwrap();
+
+        assert_eq!(corpus.len(), 100_000);
+        assert_eq!(
+            Corpus::new(1337, 100_000)
+                .chunks(4096)
+                .flatten()
+                .collect::<Vec<_>>(),
+            corpus
+        );
+        assert_eq!(
+            Corpus::new(1337, 1000)
+                .chunks(4096)
+                .flatten()
+                .collect::<Vec<_>>(),
+            corpus[..1000]
+        );
+        assert_ne!(
+            Corpus::new(7331, 1000)
+                .chunks(4096)
+                .flatten()
+                .collect::<Vec<_>>(),
+            corpus[..1000]
+        );
+
+        // Looks random
+        let ones = corpus.iter().map(|byte| byte.count_ones()).sum::<u32>();
+
+        assert!((398_000..402_000).contains(&ones));
+
+        assert!(Corpus::new(1337, 0).chunks(1).next().is_none());
+    }
+
+    #[test]
+    fn test_pack() {
+        let fixture = b"Repeated fixture lines.\n".repeat(100);
+
+        for compress in [false, true] {
+            let packed = pack(1337, &fixture, compress);
+
+            assert_eq!(unpack(1337, &packed), Ok(fixture.clone()));
+            assert_eq!(unpack(7331, &packed), Err(FixtureError::Packed));
+
+            // Encrypted at rest
+            assert!(!packed.windows(8).any(|window| window == b"Repeated"));
+
+            let mut corrupted = packed.clone();
+            *corrupted.last_mut().unwrap() ^= 1;
+
+            assert_eq!(unpack(1337, &corrupted), Err(FixtureError::Packed));
+        }
+
+        assert!(pack(1337, &fixture, true).len() < fixture.len() / 4);
+        assert_eq!(unpack(1337, b"RFIX"), Err(FixtureError::Packed));
+        assert_eq!(unpack(1337, &[]), Err(FixtureError::Packed));
+    }
+}
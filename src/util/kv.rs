@@ -0,0 +1,74 @@
+//! `key=value&key=value` encoding, as used by the profile in [Cryptopals
+//! challenge 13](https://cryptopals.com/sets/2/challenges/13).
+//!
+//! There is no escaping: metacharacters (`&` and `=`) are stripped from keys
+//! and values when encoding.
+
+use std::collections::HashMap;
+
+/// Remove `&` and `=` from `value`.
+#[must_use]
+pub fn sanitize(value: &str) -> String {
+    value.replace(['&', '='], "")
+}
+
+/// Encode `pairs` in order, [sanitizing](sanitize) keys and values.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::kv;
+///
+/// assert_eq!(
+///     kv::encode([("email", "foo@bar.com&role=admin"), ("uid", "10")]),
+///     "email=foo@bar.comroleadmin&uid=10",
+/// );
+/// ```
+pub fn encode<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    pairs
+        .into_iter()
+        .map(|(key, value)| [sanitize(key), sanitize(value)].join("="))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Parse `data` into a `key -> value` mapping.
+///
+/// Keys without `=` map to `"true"`, and repeated keys keep their last value.
+#[must_use]
+pub fn parse(data: &str) -> HashMap<&str, &str> {
+    data.split('&')
+        .map(|part| part.split_once('=').unwrap_or((part, "true")))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{encode, parse};
+
+    #[test]
+    fn test_parse() {
+        let expected = [
+            ("foo", "bar"),
+            ("baz", "qux"),
+            ("zap", "zazzle"),
+            ("inga", "true"),
+        ]
+        .iter()
+        .copied()
+        .collect::<HashMap<_, _>>();
+
+        assert_eq!(parse("foo=bar&baz=qux&zap=zazzle&inga"), expected);
+        assert_eq!(parse("role=user&role=admin")["role"], "admin");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let encoded = encode([("role", "user"), ("uid", "10"), ("email", "f=o&o@bar.com")]);
+
+        assert_eq!(encoded, "role=user&uid=10&email=foo@bar.com");
+        assert_eq!(parse(&encoded)["email"], "foo@bar.com");
+    }
+}
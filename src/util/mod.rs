@@ -1,6 +1,27 @@
 //! Various convenience utilities.
 
+pub mod asn1;
+pub mod bigint;
+pub mod bigmul;
+pub mod bits;
+pub mod clock;
+pub mod compress;
+pub mod ct_codec;
+pub mod dlog;
+pub mod field;
+pub mod fixed_uint;
+pub mod fixtures;
 pub mod iter;
+pub mod kv;
+pub mod ngram;
+pub mod pem;
+pub mod poly;
+pub mod pool;
+pub mod secret;
+pub mod text;
+pub mod transcript;
+#[cfg(feature = "data")]
+pub mod wordlist;
 
 use ::std::cmp::Ordering;
 
@@ -29,13 +50,18 @@ impl Ord for Probability {
     }
 }
 
+/// Compare without short-circuiting on the first mismatching byte.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 /// Generate `n` random bytes.
 #[must_use]
 pub fn generate_bytes(n: usize) -> Vec<u8> {
     use rand::distributions::Standard;
     use rand::Rng;
 
-    let rng = rand::thread_rng();
+    let rng = crate::rand::rng();
     rng.sample_iter(&Standard).take(n).collect()
 }
 
@@ -73,6 +99,7 @@ impl TextScorer for NaiveTextScorer {
 /// # Panics
 ///
 /// If run before Unix epoch.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
 #[must_use]
 pub fn get_unix_time() -> u64 {
     use std::time::SystemTime;
@@ -83,6 +110,16 @@ pub fn get_unix_time() -> u64 {
         .as_secs()
 }
 
+/// Get Unix time (seconds since Unix epoch).
+///
+/// `SystemTime` is unavailable on `wasm32-unknown-unknown`, so ask JS instead.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+#[must_use]
+#[allow(clippy::cast_sign_loss)]
+pub fn get_unix_time() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
 use num_bigint::{BigInt, BigUint};
 use num_traits::{One, Zero};
 
@@ -127,11 +164,407 @@ pub fn inv_mod(a: BigUint, n: &BigUint) -> Option<BigUint> {
     Some(math_mod(&x, n))
 }
 
+/// [Modular multiplicative inverse](https://en.wikipedia.org/wiki/Modular_multiplicative_inverse)
+///
+/// Uses the [binary](https://en.wikipedia.org/wiki/Binary_GCD_algorithm)
+/// extended Euclidean algorithm over unsigned integers: only halvings and
+/// subtractions, no divisions nor `BigInt` round trips. Prefer it over
+/// [`inv_mod`] in hot paths.
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::util::binary_inv_mod;
+///
+/// assert_eq!(
+///     binary_inv_mod(&BigUint::from(17_usize), &BigUint::from(3120_usize)),
+///     Some(BigUint::from(2753_usize)),
+/// );
+/// assert_eq!(
+///     binary_inv_mod(&BigUint::from(6_usize), &BigUint::from(9_usize)),
+///     None,
+/// );
+/// ```
+#[must_use]
+pub fn binary_inv_mod(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    if n.is_zero() {
+        return None;
+    }
+
+    let a = a % n;
+
+    if n.bit(0) {
+        return binary_inv_mod_odd(&a, n);
+    }
+
+    // Halving mod `n` needs an odd `n`, so invert `n` mod `a` instead:
+    // if `n * b = 1 + a * t` then `a * (n - t) = 1 (mod n)`
+    if !a.bit(0) {
+        return None;
+    }
+
+    if a.is_one() {
+        return Some(a);
+    }
+
+    let b = binary_inv_mod_odd(&(n % &a), &a)?;
+    let t = (n * b - 1_u32) / &a;
+
+    Some(n - t)
+}
+
+/// [`binary_inv_mod`] for odd `n` (and `a < n`).
+fn binary_inv_mod_odd(a: &BigUint, n: &BigUint) -> Option<BigUint> {
+    // Halve `x` mod `n` (odd)
+    let half = |x: BigUint| if x.bit(0) { (x + n) >> 1 } else { x >> 1 };
+
+    // Subtract `y` from `x` mod `n` (both already reduced)
+    let sub = |x: BigUint, y: &BigUint| if &x >= y { x - y } else { x + n - y };
+
+    // Invariants: `a * x_u = u` and `a * x_v = v` (mod n)
+    let (mut u, mut v) = (a.clone(), n.clone());
+    let (mut x_u, mut x_v) = (BigUint::one(), BigUint::zero());
+
+    while !u.is_zero() {
+        while !u.bit(0) {
+            u >>= 1;
+            x_u = half(x_u);
+        }
+
+        while !v.bit(0) {
+            v >>= 1;
+            x_v = half(x_v);
+        }
+
+        if u >= v {
+            u -= &v;
+            x_u = sub(x_u, &x_v);
+        } else {
+            v -= &u;
+            x_v = sub(x_v, &x_u);
+        }
+    }
+
+    // Now `v = gcd(a, n)`
+    if v.is_one() {
+        Some(x_v % n)
+    } else {
+        None
+    }
+}
+
+/// [Barrett reduction](https://en.wikipedia.org/wiki/Barrett_reduction)
+/// context, for repeated reductions modulo the same `n`.
+///
+/// Precomputes `floor(4^k / n)` (where `n` has `k` bits) once, so that each
+/// reduction costs two multiplications and some shifts instead of a long
+/// division.
+///
+/// Note that `num-bigint` has no truncated (half) multiplication, so for
+/// ~1024-bit moduli this is no faster than plain `%`: both take around 1µs
+/// per multiplication in the `bench_mul_*` benchmarks, within noise of each
+/// other. That is why DSA and RSA still reduce with `%`.
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::util::Barrett;
+///
+/// let barrett = Barrett::new(BigUint::from(1009_usize));
+///
+/// assert_eq!(
+///     barrett.mul(&BigUint::from(1000_usize), &BigUint::from(1000_usize)),
+///     BigUint::from(1_000_000_usize % 1009),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Barrett {
+    n: BigUint,
+    mu: BigUint,
+    k: u64,
+}
+
+impl Barrett {
+    /// Precompute the reduction context for modulus `n`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    #[must_use]
+    pub fn new(n: BigUint) -> Barrett {
+        assert!(!n.is_zero(), "Barrett modulus must be non-zero");
+
+        let k = n.bits();
+        let mu = (BigUint::one() << (2 * k)) / &n;
+
+        Barrett { n, mu, k }
+    }
+
+    /// Get the modulus.
+    #[must_use]
+    pub const fn modulus(&self) -> &BigUint {
+        &self.n
+    }
+
+    /// Compute `x mod n`.
+    ///
+    /// Falls back to regular division if `x >= n^2`.
+    #[must_use]
+    pub fn reduce(&self, x: &BigUint) -> BigUint {
+        if x.bits() > 2 * self.k {
+            return x % &self.n;
+        }
+
+        let q = ((x >> (self.k - 1)) * &self.mu) >> (self.k + 1);
+        let mut r = x - q * &self.n;
+
+        while r >= self.n {
+            r -= &self.n;
+        }
+
+        r
+    }
+
+    /// Compute `a * b mod n`.
+    #[must_use]
+    pub fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        self.reduce(&(a * b))
+    }
+}
+
+/// Simultaneous [modular exponentiation](https://en.wikipedia.org/wiki/Modular_exponentiation)
+/// of a fixed set of bases (Straus' algorithm, a.k.a. "Shamir's trick").
+///
+/// Computes `base_0^exp_0 * base_1^exp_1 * ... mod modulus` sharing the
+/// squarings between all terms. The products of every subset of bases are
+/// precomputed once, so reusing a `MultiExp` for several exponent sets (e.g.
+/// when verifying a batch of signatures) amortizes that cost.
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::util::MultiExp;
+///
+/// let modulus = BigUint::from(1000_usize);
+/// let multi_exp = MultiExp::new(&[BigUint::from(2_usize), BigUint::from(3_usize)], &modulus);
+///
+/// // 2^10 * 3^4 mod 1000
+/// assert_eq!(
+///     multi_exp.pow(&[BigUint::from(10_usize), BigUint::from(4_usize)]),
+///     BigUint::from(944_usize),
+/// );
+/// ```
+pub struct MultiExp<'a> {
+    modulus: &'a BigUint,
+    bases: usize,
+    table: Vec<BigUint>,
+}
+
+impl<'a> MultiExp<'a> {
+    /// Precompute the subset products of `bases`.
+    ///
+    /// The table has `2^bases.len()` entries, so keep the amount of bases small.
+    #[must_use]
+    pub fn new(bases: &[BigUint], modulus: &'a BigUint) -> MultiExp<'a> {
+        let mut table = Vec::with_capacity(1 << bases.len());
+        table.push(BigUint::one() % modulus);
+
+        for mask in 1_usize..1 << bases.len() {
+            let lowest = mask.trailing_zeros() as usize;
+            let product = (&table[mask & (mask - 1)] * &bases[lowest]) % modulus;
+
+            table.push(product);
+        }
+
+        MultiExp {
+            modulus,
+            bases: bases.len(),
+            table,
+        }
+    }
+
+    /// Raise each base to its corresponding exponent in `exps` and multiply
+    /// the results.
+    ///
+    /// # Panics
+    ///
+    /// If the amount of exponents does not match the amount of bases.
+    #[must_use]
+    pub fn pow(&self, exps: &[BigUint]) -> BigUint {
+        assert_eq!(exps.len(), self.bases, "Wrong amount of exponents");
+
+        let bits = exps.iter().map(BigUint::bits).max().unwrap_or(0);
+
+        let mut result = self.table[0].clone();
+
+        for bit in (0..bits).rev() {
+            result = (&result * &result) % self.modulus;
+
+            let mask = exps
+                .iter()
+                .enumerate()
+                .filter(|(_, exp)| exp.bit(bit))
+                .fold(0, |mask, (i, _)| mask | 1 << i);
+
+            if mask != 0 {
+                result = (result * &self.table[mask]) % self.modulus;
+            }
+        }
+
+        result
+    }
+}
+
+/// Outcome of verifying a batch of signatures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchVerification {
+    /// Whether each signature (in input order) is valid.
+    pub results: Vec<bool>,
+}
+
+impl BatchVerification {
+    /// Whether every signature in the batch is valid.
+    #[must_use]
+    pub fn all_valid(&self) -> bool {
+        self.results.iter().all(|&valid| valid)
+    }
+
+    /// Iterate over the indices of invalid signatures.
+    pub fn invalid(&self) -> impl Iterator<Item = usize> + '_ {
+        self.results
+            .iter()
+            .enumerate()
+            .filter(|(_, &valid)| !valid)
+            .map(|(i, _)| i)
+    }
+}
+
+/// Integer-to-Octet-String primitive (`I2OSP` in
+/// [RFC 8017](https://www.rfc-editor.org/rfc/rfc8017#section-4.1)): encode
+/// `x` as exactly `len` big-endian bytes, left-padding with zeros.
+///
+/// Unlike `BigUint::to_bytes_be` leading zero bytes are kept (and zero is
+/// encoded as `len` zero bytes instead of `[0]`), which is what most protocols
+/// (e.g. PKCS#1 blocks, `PAD()` in SRP) expect.
+///
+/// Returns `None` if `x` does not fit in `len` bytes.
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::util::i2osp;
+///
+/// let x = BigUint::from(0x0102_usize);
+///
+/// assert_eq!(i2osp(&x, 4), Some(vec![0x00, 0x00, 0x01, 0x02]));
+/// assert_eq!(i2osp(&x, 1), None);
+/// ```
+#[must_use]
+pub fn i2osp(x: &BigUint, len: usize) -> Option<Vec<u8>> {
+    let mut fixed = vec![0; len];
+
+    write_fixed_be(x, &mut fixed)?;
+
+    Some(fixed)
+}
+
+/// Write `x` as big-endian bytes filling all of `out`, left-padding with
+/// zeros (see [`i2osp`]).
+///
+/// Returns `None` (leaving `out` untouched) if `x` does not fit.
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::util::write_fixed_be;
+///
+/// let mut out = [0xff; 4];
+///
+/// assert_eq!(write_fixed_be(&BigUint::from(0x0102_usize), &mut out), Some(()));
+/// assert_eq!(out, [0x00, 0x00, 0x01, 0x02]);
+/// ```
+pub fn write_fixed_be(x: &BigUint, out: &mut [u8]) -> Option<()> {
+    let len = byte_len(x);
+
+    if len > out.len() {
+        return None;
+    }
+
+    let (zeros, value) = out.split_at_mut(out.len() - len);
+
+    zeros.fill(0);
+
+    if len > 0 {
+        value.copy_from_slice(&x.to_bytes_be());
+    }
+
+    Some(())
+}
+
+/// Length of `x` in bits (`0` for zero).
+#[must_use]
+pub fn bit_len(x: &BigUint) -> usize {
+    x.bits() as usize
+}
+
+/// Minimum length of `x` in bytes (`0` for zero), e.g. the size of blocks
+/// modulo `x`.
+#[must_use]
+pub fn byte_len(x: &BigUint) -> usize {
+    bit_len(x).div_ceil(8)
+}
+
+/// Octet-String-to-Integer primitive (`OS2IP` in
+/// [RFC 8017](https://www.rfc-editor.org/rfc/rfc8017#section-4.2)): decode
+/// big-endian `bytes` (leading zeros allowed).
+///
+/// See [`from_fixed_bytes_be`] to also check the length.
+#[must_use]
+pub fn os2ip(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes)
+}
+
+/// [`os2ip`] exactly `len` big-endian bytes (leading zeros allowed).
+///
+/// Returns `None` if `bytes.len() != len`.
+///
+/// # Example
+///
+/// ```
+/// use num_bigint::BigUint;
+/// use rustopals::util::from_fixed_bytes_be;
+///
+/// assert_eq!(
+///     from_fixed_bytes_be(&[0x00, 0x01, 0x02], 3),
+///     Some(BigUint::from(0x0102_usize)),
+/// );
+/// assert_eq!(from_fixed_bytes_be(&[0x00, 0x01, 0x02], 2), None);
+/// ```
+#[must_use]
+pub fn from_fixed_bytes_be(bytes: &[u8], len: usize) -> Option<BigUint> {
+    if bytes.len() != len {
+        return None;
+    }
+
+    Some(os2ip(bytes))
+}
+
 #[cfg(test)]
 mod test {
-    use num_bigint::{BigInt, BigUint};
+    use num_bigint::{BigInt, BigUint, RandBigInt};
+    use test::Bencher;
 
-    use super::{egcd, inv_mod};
+    use super::{
+        binary_inv_mod, bit_len, byte_len, egcd, from_fixed_bytes_be, i2osp, inv_mod, os2ip,
+        write_fixed_be, Barrett, MultiExp,
+    };
+    use crate::dsa::CHALLENGE_DSA_P;
+    use crate::rand::rng;
 
     #[test]
     fn test_egcd() {
@@ -151,4 +584,195 @@ mod test {
             Some(BigUint::from(2753_usize)),
         );
     }
+
+    #[test]
+    fn test_binary_inv_mod() {
+        for n in 1_usize..200 {
+            let n_big = BigUint::from(n);
+
+            for a in 0..2 * n {
+                let a_big = BigUint::from(a);
+
+                assert_eq!(
+                    binary_inv_mod(&a_big, &n_big),
+                    inv_mod(a_big, &n_big),
+                    "{a}^-1 mod {n}"
+                );
+            }
+        }
+
+        assert_eq!(
+            binary_inv_mod(&BigUint::from(3_usize), &BigUint::from(0_usize)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_binary_inv_mod_big() {
+        let p: &BigUint = &CHALLENGE_DSA_P;
+        let a = rng().gen_biguint_below(p);
+
+        assert_eq!(binary_inv_mod(&a, p), inv_mod(a, p));
+    }
+
+    #[test]
+    fn test_barrett() {
+        let p: &BigUint = &CHALLENGE_DSA_P;
+        let barrett = Barrett::new(p.clone());
+
+        for _ in 0..100 {
+            let a = rng().gen_biguint_below(p);
+            let b = rng().gen_biguint_below(p);
+
+            assert_eq!(barrett.mul(&a, &b), (&a * &b) % p);
+        }
+
+        let huge = p * p * p;
+        assert_eq!(barrett.reduce(&huge), BigUint::from(0_usize));
+
+        for n in 1_usize..100 {
+            let barrett = Barrett::new(BigUint::from(n));
+
+            for x in 0..n * n {
+                assert_eq!(barrett.reduce(&BigUint::from(x)), BigUint::from(x % n));
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_exp() {
+        let modulus = BigUint::from(1_000_003_usize);
+        let bases = [
+            BigUint::from(2_usize),
+            BigUint::from(12345_usize),
+            BigUint::from(999_999_usize),
+        ];
+        let exps = [
+            BigUint::from(65537_usize),
+            BigUint::from(0_usize),
+            BigUint::from(123_456_789_usize),
+        ];
+
+        let expected = bases
+            .iter()
+            .zip(&exps)
+            .map(|(base, exp)| base.modpow(exp, &modulus))
+            .fold(BigUint::from(1_usize), |acc, x| (acc * x) % &modulus);
+
+        assert_eq!(MultiExp::new(&bases, &modulus).pow(&exps), expected);
+    }
+
+    #[test]
+    fn test_fixed_bytes_roundtrip() {
+        for x in &[0_usize, 1, 0xFF, 0x100, 0xDEAD_BEEF] {
+            let x = BigUint::from(*x);
+            let bytes = i2osp(&x, 8).unwrap();
+
+            assert_eq!(bytes.len(), 8);
+            assert_eq!(from_fixed_bytes_be(&bytes, 8), Some(x));
+        }
+
+        assert_eq!(i2osp(&BigUint::from(0_usize), 0), Some(vec![]));
+        assert_eq!(i2osp(&BigUint::from(1_usize), 0), None);
+    }
+
+    #[test]
+    fn test_byte_len() {
+        assert_eq!(
+            (
+                bit_len(&BigUint::from(0_usize)),
+                byte_len(&BigUint::from(0_usize))
+            ),
+            (0, 0)
+        );
+        assert_eq!(
+            (
+                bit_len(&BigUint::from(1_usize)),
+                byte_len(&BigUint::from(1_usize))
+            ),
+            (1, 1)
+        );
+        assert_eq!(
+            (
+                bit_len(&BigUint::from(0xff_usize)),
+                byte_len(&BigUint::from(0xff_usize))
+            ),
+            (8, 1)
+        );
+        assert_eq!(
+            (
+                bit_len(&BigUint::from(0x100_usize)),
+                byte_len(&BigUint::from(0x100_usize))
+            ),
+            (9, 2)
+        );
+
+        let mut out = [0xff; 3];
+
+        assert_eq!(
+            write_fixed_be(&BigUint::from(0x10000_usize), &mut out),
+            Some(())
+        );
+        assert_eq!(out, [0x01, 0x00, 0x00]);
+        assert_eq!(
+            write_fixed_be(&BigUint::from(0x1000000_usize), &mut out),
+            None
+        );
+        assert_eq!(out, [0x01, 0x00, 0x00]);
+        assert_eq!(write_fixed_be(&BigUint::from(0_usize), &mut out), Some(()));
+        assert_eq!(out, [0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_leading_zeros() {
+        // `to_bytes_be` encodes zero as `[0]` and drops leading zeros
+        assert_eq!(BigUint::from(0_usize).to_bytes_be(), [0x00]);
+        assert_eq!(i2osp(&BigUint::from(0_usize), 2), Some(vec![0x00, 0x00]));
+        assert_eq!(i2osp(&BigUint::from(0_usize), 1), Some(vec![0x00]));
+
+        let bytes = [0x00, 0x00, 0x01, 0x00];
+        let x = os2ip(&bytes);
+
+        assert_eq!(x, BigUint::from(0x100_usize));
+        assert_eq!(x.to_bytes_be(), [0x01, 0x00]);
+        assert_eq!(i2osp(&x, bytes.len()), Some(bytes.to_vec()));
+        assert_eq!(i2osp(&x, 2), Some(vec![0x01, 0x00]));
+        assert_eq!(i2osp(&x, 1), None);
+
+        assert_eq!(os2ip(&[]), BigUint::from(0_usize));
+        assert_eq!(os2ip(&[0x00; 16]), BigUint::from(0_usize));
+    }
+
+    #[bench]
+    fn bench_inv_mod(b: &mut Bencher) {
+        let p: &BigUint = &CHALLENGE_DSA_P;
+        let a = rng().gen_biguint_below(p);
+
+        b.iter(|| inv_mod(a.clone(), p));
+    }
+
+    #[bench]
+    fn bench_binary_inv_mod(b: &mut Bencher) {
+        let p: &BigUint = &CHALLENGE_DSA_P;
+        let a = rng().gen_biguint_below(p);
+
+        b.iter(|| binary_inv_mod(&a, p));
+    }
+
+    #[bench]
+    fn bench_mul_rem(b: &mut Bencher) {
+        let p: &BigUint = &CHALLENGE_DSA_P;
+        let (x, y) = (rng().gen_biguint_below(p), rng().gen_biguint_below(p));
+
+        b.iter(|| (&x * &y) % p);
+    }
+
+    #[bench]
+    fn bench_mul_barrett(b: &mut Bencher) {
+        let p: &BigUint = &CHALLENGE_DSA_P;
+        let barrett = Barrett::new(p.clone());
+        let (x, y) = (rng().gen_biguint_below(p), rng().gen_biguint_below(p));
+
+        b.iter(|| barrett.mul(&x, &y));
+    }
 }
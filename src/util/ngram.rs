@@ -0,0 +1,205 @@
+//! [N-gram](https://en.wikipedia.org/wiki/N-gram) frequency scoring built
+//! from a corpus at runtime.
+//!
+//! [`NaiveTextScorer`](super::NaiveTextScorer) only knows about English.
+//! Feeding a different corpus (another language, source code, base64...) to
+//! [`NgramScorer::from_corpus`] lets the XOR/CTR crackers target other kinds
+//! of plaintext. Tables can be saved with [`NgramScorer::to_table`] and
+//! loaded back with [`NgramScorer::from_table`] to skip re-reading the
+//! corpus.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::stream::{SingleXORCipher, StreamCipher};
+//! use rustopals::util::ngram::NgramScorer;
+//!
+//! const CORPUS: &[u8] = b"el veloz murcielago hindu comia feliz cardillo y kiwi";
+//!
+//! let scorer = NgramScorer::from_corpus(CORPUS, 2).unwrap();
+//! let ciphertext = SingleXORCipher(0x2a_u8)
+//!     .process(b"el murcielago comia kiwi y cardillo feliz")
+//!     .collect::<Vec<_>>();
+//!
+//! let (key, _) = SingleXORCipher::<u8>::crack(&scorer, &ciphertext).unwrap();
+//!
+//! assert_eq!(key, 0x2a);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::{self, Read};
+
+use super::TextScorer;
+
+/// Log-probability given to n-grams unseen in the corpus, relative to a
+/// single occurrence.
+const UNSEEN_PENALTY: f32 = 0.01;
+
+/// Scores text by the average log-probability of its byte n-grams.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NgramScorer {
+    n: usize,
+    counts: BTreeMap<Vec<u8>, u64>,
+    log_probs: BTreeMap<Vec<u8>, f32>,
+    unseen_log_prob: f32,
+}
+
+impl NgramScorer {
+    /// Build a scorer from the `n`-gram counts of a `corpus`.
+    ///
+    /// # Errors
+    ///
+    /// If the corpus cannot be read.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    pub fn from_corpus<R: Read>(mut corpus: R, n: usize) -> io::Result<Self> {
+        assert!(n > 0);
+
+        let mut bytes = Vec::new();
+        corpus.read_to_end(&mut bytes)?;
+
+        let mut counts = BTreeMap::new();
+
+        for ngram in bytes.windows(n) {
+            *counts.entry(ngram.to_vec()).or_insert(0) += 1;
+        }
+
+        Ok(NgramScorer::from_counts(n, counts))
+    }
+
+    /// Load a scorer from a table generated by [`to_table`](Self::to_table).
+    ///
+    /// Returns `None` if the table is malformed.
+    #[must_use]
+    pub fn from_table(table: &str) -> Option<Self> {
+        let mut lines = table.lines();
+
+        let n = lines.next()?.strip_prefix("n=")?.parse::<usize>().ok()?;
+
+        if n == 0 {
+            return None;
+        }
+
+        let mut counts = BTreeMap::new();
+
+        for line in lines {
+            let (ngram, count) = line.split_once(' ')?;
+            let ngram = hex::decode(ngram).ok()?;
+
+            if ngram.len() != n {
+                return None;
+            }
+
+            counts.insert(ngram, count.parse().ok()?);
+        }
+
+        Some(NgramScorer::from_counts(n, counts))
+    }
+
+    /// Serialize the n-gram counts into a plain text table: an `n=<n>`
+    /// header followed by one `<hex n-gram> <count>` line per n-gram.
+    #[must_use]
+    pub fn to_table(&self) -> String {
+        let mut table = format!("n={}\n", self.n);
+
+        for (ngram, count) in &self.counts {
+            writeln!(table, "{} {count}", hex::encode(ngram)).unwrap();
+        }
+
+        table
+    }
+
+    /// Get the n-gram length.
+    #[must_use]
+    pub const fn n(&self) -> usize {
+        self.n
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn from_counts(n: usize, counts: BTreeMap<Vec<u8>, u64>) -> Self {
+        let total = counts.values().sum::<u64>().max(1) as f32;
+
+        let log_probs = counts
+            .iter()
+            .map(|(ngram, &count)| (ngram.clone(), (count as f32 / total).ln()))
+            .collect();
+
+        NgramScorer {
+            n,
+            counts,
+            log_probs,
+            unseen_log_prob: (UNSEEN_PENALTY / total).ln(),
+        }
+    }
+}
+
+impl TextScorer for NgramScorer {
+    #[allow(clippy::cast_precision_loss)]
+    fn score(&self, string: &str) -> f32 {
+        let ngrams = string.as_bytes().windows(self.n);
+        let len = ngrams.len();
+
+        if len == 0 {
+            return self.unseen_log_prob;
+        }
+
+        let total = ngrams
+            .map(|ngram| {
+                self.log_probs
+                    .get(ngram)
+                    .copied()
+                    .unwrap_or(self.unseen_log_prob)
+            })
+            .sum::<f32>();
+
+        total / len as f32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NgramScorer;
+    use crate::stream::{SingleXORCipher, StreamCipher};
+    use crate::util::TextScorer;
+
+    const CORPUS: &[u8] = b"Ein Elefant, der auf dem Spinnennetz schaukelt, \
+        ruft einen zweiten Elefanten, denn das Netz ist stark genug. \
+        Zwei Elefanten, die auf dem Spinnennetz schaukeln, rufen einen dritten.";
+
+    #[test]
+    fn test_score() {
+        let scorer = NgramScorer::from_corpus(CORPUS, 2).unwrap();
+
+        assert!(scorer.score("Die Elefanten schaukeln") > scorer.score("Xq#vZ!Kq@wJ%bQ&"));
+    }
+
+    #[test]
+    fn test_crack_single_xor() {
+        let scorer = NgramScorer::from_corpus(CORPUS, 1).unwrap();
+
+        let ciphertext = SingleXORCipher(0x55_u8)
+            .process(b"Der Elefant schaukelt auf dem Netz")
+            .collect::<Vec<_>>();
+
+        let (key, plaintext) = SingleXORCipher::<u8>::crack(&scorer, &ciphertext).unwrap();
+
+        assert_eq!(key, 0x55);
+        assert_eq!(plaintext, "Der Elefant schaukelt auf dem Netz");
+    }
+
+    #[test]
+    fn test_table_roundtrip() {
+        let scorer = NgramScorer::from_corpus(CORPUS, 3).unwrap();
+        let table = scorer.to_table();
+
+        assert!(table.starts_with("n=3\n"));
+        assert_eq!(NgramScorer::from_table(&table), Some(scorer));
+
+        assert_eq!(NgramScorer::from_table("n=2\n61 1\n"), None);
+        assert_eq!(NgramScorer::from_table("n=0\n"), None);
+        assert_eq!(NgramScorer::from_table("6161 1\n"), None);
+    }
+}
@@ -0,0 +1,542 @@
+//! Univariate [polynomial arithmetic](https://en.wikipedia.org/wiki/Polynomial_ring)
+//! over a [`Field`].
+//!
+//! Enough to compute polynomial GCDs and find roots for algebraic attacks
+//! (e.g. [Franklin-Reiter](crate::rsa::attacks::franklin_reiter)). Every
+//! operation takes the field explicitly, as [`Field`] values hold the context
+//! (e.g. the modulus) while polynomials only hold plain elements.
+//!
+//! # Example
+//!
+//! ```
+//! use num_bigint::BigUint;
+//! use rustopals::util::field::ModP;
+//! use rustopals::util::poly::Poly;
+//!
+//! let field = ModP(BigUint::from(7_usize));
+//!
+//! // (x + 1) * (x + 2) = x^2 + 3x + 2
+//! let a = field.poly(&[1, 1]);
+//! let b = field.poly(&[2, 1]);
+//! let product = a.mul(&b, &field);
+//!
+//! assert_eq!(product, field.poly(&[2, 3, 1]));
+//! assert_eq!(Poly::gcd(&product, &a, &field), Some(a));
+//!
+//! let mut roots = product.roots(&field).unwrap();
+//! roots.sort();
+//!
+//! assert_eq!(roots, [BigUint::from(5_usize), BigUint::from(6_usize)]);
+//! ```
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::One;
+
+use super::field::{Field, ModP};
+
+/// Maximum amount of random splits tried by [`Poly::roots`] per factor.
+const MAX_SPLIT_TRIES: usize = 64;
+
+/// Polynomial with coefficients in field `F`, lowest degree first.
+///
+/// Always normalized: no trailing (highest-degree) zero coefficients, so the
+/// zero polynomial has no coefficients at all.
+pub struct Poly<F: Field> {
+    coeffs: Vec<F::Elem>,
+}
+
+impl<F: Field> Poly<F> {
+    /// Create from `coeffs` (lowest degree first).
+    #[must_use]
+    pub fn new(coeffs: Vec<F::Elem>, field: &F) -> Poly<F> {
+        Poly::normalized(coeffs, field)
+    }
+
+    /// The zero polynomial.
+    #[must_use]
+    pub const fn zero() -> Poly<F> {
+        Poly { coeffs: Vec::new() }
+    }
+
+    /// The constant polynomial `c`.
+    #[must_use]
+    pub fn constant(c: F::Elem, field: &F) -> Poly<F> {
+        Poly::new(vec![c], field)
+    }
+
+    /// The polynomial `x`.
+    #[must_use]
+    pub fn x(field: &F) -> Poly<F> {
+        Poly::new(vec![field.zero(), field.one()], field)
+    }
+
+    fn normalized(mut coeffs: Vec<F::Elem>, field: &F) -> Poly<F> {
+        while coeffs.last().is_some_and(|coeff| field.is_zero(coeff)) {
+            coeffs.pop();
+        }
+
+        Poly { coeffs }
+    }
+
+    /// Coefficients, lowest degree first.
+    #[must_use]
+    pub fn coeffs(&self) -> &[F::Elem] {
+        &self.coeffs
+    }
+
+    /// Whether this is the zero polynomial.
+    #[must_use]
+    pub const fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    /// Degree (`None` for the zero polynomial).
+    #[must_use]
+    pub const fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    /// Leading (highest-degree) coefficient.
+    #[must_use]
+    pub fn leading(&self) -> Option<&F::Elem> {
+        self.coeffs.last()
+    }
+
+    /// Evaluate at `x`.
+    #[must_use]
+    pub fn eval(&self, x: &F::Elem, field: &F) -> F::Elem {
+        self.coeffs.iter().rev().fold(field.zero(), |acc, coeff| {
+            field.add(&field.mul(&acc, x), coeff)
+        })
+    }
+
+    /// Compute `self + other`.
+    #[must_use]
+    pub fn add(&self, other: &Poly<F>, field: &F) -> Poly<F> {
+        let len = self.coeffs.len().max(other.coeffs.len());
+        let zero = field.zero();
+
+        Poly::normalized(
+            (0..len)
+                .map(|i| {
+                    let a = self.coeffs.get(i).unwrap_or(&zero);
+                    let b = other.coeffs.get(i).unwrap_or(&zero);
+
+                    field.add(a, b)
+                })
+                .collect(),
+            field,
+        )
+    }
+
+    /// Compute `self - other`.
+    #[must_use]
+    pub fn sub(&self, other: &Poly<F>, field: &F) -> Poly<F> {
+        self.add(&other.neg(field), field)
+    }
+
+    /// Compute `-self`.
+    #[must_use]
+    pub fn neg(&self, field: &F) -> Poly<F> {
+        Poly::normalized(
+            self.coeffs.iter().map(|coeff| field.neg(coeff)).collect(),
+            field,
+        )
+    }
+
+    /// Compute `self * other`.
+    #[must_use]
+    pub fn mul(&self, other: &Poly<F>, field: &F) -> Poly<F> {
+        if self.is_zero() || other.is_zero() {
+            return Poly::zero();
+        }
+
+        let mut coeffs = vec![field.zero(); self.coeffs.len() + other.coeffs.len() - 1];
+
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in other.coeffs.iter().enumerate() {
+                coeffs[i + j] = field.add(&coeffs[i + j], &field.mul(a, b));
+            }
+        }
+
+        Poly::normalized(coeffs, field)
+    }
+
+    /// Multiply every coefficient by `scalar`.
+    #[must_use]
+    pub fn scale(&self, scalar: &F::Elem, field: &F) -> Poly<F> {
+        Poly::normalized(
+            self.coeffs
+                .iter()
+                .map(|coeff| field.mul(coeff, scalar))
+                .collect(),
+            field,
+        )
+    }
+
+    /// Compute `self^exp`.
+    #[must_use]
+    pub fn pow(&self, mut exp: u64, field: &F) -> Poly<F> {
+        let mut base = self.clone();
+        let mut result = Poly::constant(field.one(), field);
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.mul(&base, field);
+            }
+
+            base = base.mul(&base, field);
+            exp >>= 1;
+        }
+
+        result
+    }
+
+    /// Compute `self^exp mod modulus`.
+    ///
+    /// Returns `None` if `modulus` is zero or not monic-able (see
+    /// [`div_rem`](Poly::div_rem)).
+    #[must_use]
+    pub fn pow_mod(&self, exp: &BigUint, modulus: &Poly<F>, field: &F) -> Option<Poly<F>> {
+        let mut base = self.rem(modulus, field)?;
+        let mut result = Poly::constant(field.one(), field).rem(modulus, field)?;
+
+        for i in 0..exp.bits() {
+            if exp.bit(i) {
+                result = result.mul(&base, field).rem(modulus, field)?;
+            }
+
+            base = base.mul(&base, field).rem(modulus, field)?;
+        }
+
+        Some(result)
+    }
+
+    /// Divide by `divisor`, returning `(quotient, remainder)`.
+    ///
+    /// Returns `None` if `divisor` is zero or its leading coefficient is not
+    /// invertible (only possible when `F` is not really a field, e.g. `Z_n`
+    /// for composite `n`, where it reveals a factor).
+    #[must_use]
+    pub fn div_rem(&self, divisor: &Poly<F>, field: &F) -> Option<(Poly<F>, Poly<F>)> {
+        let divisor_degree = divisor.degree()?;
+        let leading_inv = field.inv(divisor.leading()?)?;
+
+        let mut quotient = vec![field.zero(); self.coeffs.len().saturating_sub(divisor_degree)];
+        let mut remainder = self.clone();
+
+        while let Some(remainder_degree) = remainder.degree() {
+            if remainder_degree < divisor_degree {
+                break;
+            }
+
+            let shift = remainder_degree - divisor_degree;
+            let factor = field.mul(remainder.leading()?, &leading_inv);
+
+            let mut subtrahend = vec![field.zero(); shift];
+            subtrahend.extend(divisor.scale(&factor, field).coeffs);
+
+            remainder = remainder.sub(&Poly::normalized(subtrahend, field), field);
+            quotient[shift] = factor;
+        }
+
+        Some((Poly::normalized(quotient, field), remainder))
+    }
+
+    /// Compute `self mod divisor`. See [`div_rem`](Poly::div_rem).
+    #[must_use]
+    pub fn rem(&self, divisor: &Poly<F>, field: &F) -> Option<Poly<F>> {
+        self.div_rem(divisor, field).map(|(_, remainder)| remainder)
+    }
+
+    /// Make the leading coefficient `1`.
+    ///
+    /// Returns `None` if the leading coefficient is not invertible. The zero
+    /// polynomial is returned as-is.
+    #[must_use]
+    pub fn monic(&self, field: &F) -> Option<Poly<F>> {
+        match self.leading() {
+            None => Some(Poly::zero()),
+            Some(leading) => Some(self.scale(&field.inv(leading)?, field)),
+        }
+    }
+
+    /// Monic [greatest common divisor](https://en.wikipedia.org/wiki/Polynomial_greatest_common_divisor)
+    /// of `a` and `b`, by the Euclidean algorithm.
+    ///
+    /// Returns `None` if a non-invertible leading coefficient shows up (only
+    /// possible when `F` is not really a field).
+    #[must_use]
+    pub fn gcd(a: &Poly<F>, b: &Poly<F>, field: &F) -> Option<Poly<F>> {
+        let (mut a, mut b) = (a.clone(), b.clone());
+
+        while !b.is_zero() {
+            let remainder = a.rem(&b, field)?;
+
+            a = b;
+            b = remainder;
+        }
+
+        a.monic(field)
+    }
+
+    /// Find all distinct roots in the field.
+    ///
+    /// Uses [Cantor-Zassenhaus](https://en.wikipedia.org/wiki/Cantor%E2%80%93Zassenhaus_algorithm):
+    /// `gcd(self, x^q - x)` keeps only the linear factors, which are then
+    /// split apart by random GCDs. Costs `O(deg^2 * log q)` field operations
+    /// per split, so it is meant for small degrees.
+    ///
+    /// Returns `None` for the zero polynomial, if a non-invertible element
+    /// shows up (when `F` is not really a field), or (with negligible
+    /// probability) if random splitting keeps failing.
+    #[must_use]
+    pub fn roots(&self, field: &F) -> Option<Vec<F::Elem>> {
+        let f = self.monic(field)?;
+
+        if f.is_zero() {
+            return None;
+        }
+
+        let x = Poly::x(field);
+        let x_q = x.pow_mod(&field.order(), &f, field)?;
+        let linear_part = Poly::gcd(&f, &x_q.sub(&x, field), field)?;
+
+        let mut roots = Vec::new();
+        linear_part.split_roots(field, &mut roots)?;
+
+        Some(roots)
+    }
+
+    /// Split a monic product of distinct linear factors, collecting its roots.
+    fn split_roots(&self, field: &F, roots: &mut Vec<F::Elem>) -> Option<()> {
+        match self.degree()? {
+            0 => return Some(()),
+            1 => {
+                roots.push(field.neg(&self.coeffs[0]));
+                return Some(());
+            },
+            _ => {},
+        }
+
+        let order = field.order();
+        let characteristic_two = !order.bit(0);
+
+        for _ in 0..MAX_SPLIT_TRIES {
+            let a = field.random();
+
+            // Map to a polynomial vanishing at about half of the roots
+            let splitter = if characteristic_two {
+                // Absolute trace of `a * x`: sum of (a * x)^(2^i), which is
+                // either 0 or 1 at each root
+                let mut term = Poly::new(vec![field.zero(), a], field).rem(self, field)?;
+                let mut trace = term.clone();
+
+                for _ in 1..order.bits() - 1 {
+                    term = term.mul(&term, field).rem(self, field)?;
+                    trace = trace.add(&term, field);
+                }
+
+                trace
+            } else {
+                // (x + a)^((q - 1) / 2) - 1, zero when `x + a` is a square
+                let half_order = (order.clone() - BigUint::one()) >> 1;
+
+                Poly::new(vec![a, field.one()], field)
+                    .pow_mod(&half_order, self, field)?
+                    .sub(&Poly::constant(field.one(), field), field)
+            };
+
+            let factor = Poly::gcd(self, &splitter, field)?;
+
+            if let Some(degree) = factor.degree() {
+                if degree > 0 && Some(degree) < self.degree() {
+                    let (cofactor, _) = self.div_rem(&factor, field)?;
+
+                    factor.split_roots(field, roots)?;
+                    return cofactor.monic(field)?.split_roots(field, roots);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<F: Field> Clone for Poly<F> {
+    fn clone(&self) -> Self {
+        Poly {
+            coeffs: self.coeffs.clone(),
+        }
+    }
+}
+
+impl<F: Field> PartialEq for Poly<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.coeffs == other.coeffs
+    }
+}
+
+impl<F: Field> Eq for Poly<F> {}
+
+impl<F: Field> fmt::Debug for Poly<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Poly").field(&self.coeffs).finish()
+    }
+}
+
+impl ModP {
+    /// Polynomial with small `coeffs` (lowest degree first).
+    #[must_use]
+    pub fn poly(&self, coeffs: &[u64]) -> Poly<ModP> {
+        Poly::new(
+            coeffs
+                .iter()
+                .map(|&coeff| self.elem(&BigUint::from(coeff)))
+                .collect(),
+            self,
+        )
+    }
+
+    /// Polynomial with `coeffs` (lowest degree first), reducing them.
+    #[must_use]
+    pub fn poly_from_biguints(&self, coeffs: &[BigUint]) -> Poly<ModP> {
+        Poly::new(coeffs.iter().map(|coeff| self.elem(coeff)).collect(), self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::Poly;
+    use crate::util::field::{Field, ModP, GF128};
+
+    fn random_poly<F: Field>(degree: usize, field: &F) -> Poly<F> {
+        Poly::new((0..=degree).map(|_| field.random()).collect(), field)
+    }
+
+    /// `(x - r_0) * (x - r_1) * ...`
+    fn from_roots<F: Field>(roots: &[F::Elem], field: &F) -> Poly<F> {
+        roots
+            .iter()
+            .fold(Poly::constant(field.one(), field), |acc, root| {
+                acc.mul(&Poly::new(vec![field.neg(root), field.one()], field), field)
+            })
+    }
+
+    /// Check ring properties and division on random polynomials.
+    fn check_properties<F: Field>(field: &F) {
+        for degree in 0..6 {
+            let a = random_poly(degree + 2, field);
+            let b = random_poly(degree, field);
+            let c = random_poly(degree + 1, field);
+
+            assert_eq!(a.mul(&b, field), b.mul(&a, field));
+            assert_eq!(
+                a.mul(&b.add(&c, field), field),
+                a.mul(&b, field).add(&a.mul(&c, field), field)
+            );
+            assert!(a.sub(&a, field).is_zero());
+
+            let (quotient, remainder) = a.div_rem(&b, field).unwrap();
+
+            assert_eq!(quotient.mul(&b, field).add(&remainder, field), a);
+            assert!(remainder.degree() < b.degree());
+
+            // Common factor is kept by the GCD
+            let gcd = Poly::gcd(&a.mul(&c, field), &b.mul(&c, field), field).unwrap();
+
+            assert!(gcd.rem(&c, field).unwrap().is_zero());
+
+            let x = field.random();
+            assert_eq!(
+                a.mul(&b, field).eval(&x, field),
+                field.mul(&a.eval(&x, field), &b.eval(&x, field))
+            );
+        }
+    }
+
+    /// Check that roots of a product of linear factors are found.
+    fn check_roots<F: Field>(field: &F)
+    where
+        F::Elem: Ord,
+    {
+        for degree in 1..5 {
+            let mut roots = (0..degree).map(|_| field.random()).collect::<Vec<_>>();
+            roots.sort();
+            roots.dedup();
+
+            // Throw in a random quadratic factor (its roots, if any, must be
+            // found too)
+            let extra = random_poly(2, field);
+            let poly = from_roots(&roots, field).mul(&extra, field);
+
+            let mut found = poly.roots(field).unwrap();
+            found.sort();
+
+            for root in &roots {
+                assert!(found.contains(root));
+            }
+
+            for root in &found {
+                assert!(field.is_zero(&poly.eval(root, field)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mod_p_properties() {
+        check_properties(&ModP(BigUint::from(1_000_000_007_usize)));
+    }
+
+    #[test]
+    fn test_gf128_properties() {
+        check_properties(&GF128);
+    }
+
+    #[test]
+    fn test_mod_p_roots() {
+        check_roots(&ModP(BigUint::from(1_000_000_007_usize)));
+    }
+
+    #[test]
+    fn test_gf128_roots() {
+        check_roots(&GF128);
+    }
+
+    #[test]
+    fn test_normalization() {
+        let field = ModP(BigUint::from(5_usize));
+
+        assert_eq!(field.poly(&[1, 2, 5, 10]).degree(), Some(1));
+        assert!(field.poly(&[5, 0]).is_zero());
+        assert_eq!(Poly::<ModP>::zero().degree(), None);
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let field = ModP(BigUint::from(101_usize));
+
+        // x^3 + 2x + 7 = (x^2 + 3x + 11) * (x - 3) + 40
+        let dividend = field.poly(&[7, 2, 0, 1]);
+        let divisor = field.poly(&[98, 1]);
+
+        let (quotient, remainder) = dividend.div_rem(&divisor, &field).unwrap();
+
+        assert_eq!(quotient, field.poly(&[11, 3, 1]));
+        assert_eq!(remainder, field.poly(&[40]));
+
+        assert_eq!(dividend.div_rem(&Poly::zero(), &field), None);
+    }
+
+    #[test]
+    fn test_no_roots() {
+        let field = ModP(BigUint::from(7_usize));
+
+        // x^2 + 1 is irreducible mod 7
+        assert_eq!(field.poly(&[1, 0, 1]).roots(&field), Some(vec![]));
+    }
+}
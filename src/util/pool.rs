@@ -0,0 +1,421 @@
+//! A small thread pool for the fork-join parallelism in the attacks, so that
+//! every parallel scan splits its work the same way (and without pulling in
+//! `rayon`).
+//!
+//! Worker threads only live for a [`scope`](ThreadPool::scope), so tasks can
+//! borrow from the caller like with [`std::thread::scope`], but there are at
+//! most [`workers`](ThreadPool::workers) of them no matter how many tasks are
+//! spawned. A single-worker pool spawns no threads at all: tasks run inline,
+//! as soon as they are spawned. That is always the case on `wasm32`, which
+//! can't spawn threads.
+//!
+//! Tasks spawned inside [`rand::with_seed`](crate::rand::with_seed) run with
+//! a seed drawn from the spawning thread's RNG (in spawn order), so seeded
+//! runs stay reproducible for a given amount of workers.
+//!
+//! ```
+//! use rustopals::util::pool::ThreadPool;
+//!
+//! let numbers = (1..=100).collect::<Vec<u32>>();
+//! let pool = ThreadPool::new(4);
+//!
+//! assert_eq!(
+//!     pool.parallel_map_reduce(&numbers, |&x| x * x, |a, b| a + b),
+//!     Some(338350)
+//! );
+//!
+//! let sum = pool.scope(|scope| {
+//!     let halves = numbers
+//!         .chunks(50)
+//!         .map(|half| scope.spawn(move || half.iter().sum::<u32>()))
+//!         .collect::<Vec<_>>();
+//!
+//!     halves.into_iter().map(|task| task.join()).sum::<u32>()
+//! });
+//!
+//! assert_eq!(sum, 5050);
+//! ```
+
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+use crate::rand::{fork_seed, with_seed};
+
+/// Fork-join thread pool. See the [module docs](self).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ThreadPool {
+    workers: usize,
+}
+
+impl Default for ThreadPool {
+    fn default() -> Self {
+        ThreadPool::available()
+    }
+}
+
+impl ThreadPool {
+    /// A pool with some amount of `workers` (at least one).
+    #[must_use]
+    pub fn new(workers: usize) -> ThreadPool {
+        ThreadPool {
+            workers: workers.max(1),
+        }
+    }
+
+    /// A pool with a worker per available core.
+    #[must_use]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn available() -> ThreadPool {
+        ThreadPool::new(thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get))
+    }
+
+    /// A pool with a single (inline) worker, since `wasm32` can't spawn
+    /// threads.
+    #[must_use]
+    #[cfg(target_arch = "wasm32")]
+    pub fn available() -> ThreadPool {
+        ThreadPool::new(1)
+    }
+
+    /// Amount of worker threads.
+    #[must_use]
+    pub const fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Run `f`, which can [`spawn`](Scope::spawn) tasks borrowing from the
+    /// environment. Every task is finished by the time this returns.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, or if any task panicked (with the task's panic if it
+    /// was [joined](Task::join)).
+    pub fn scope<'env, T>(&self, f: impl FnOnce(&Scope<'env>) -> T) -> T {
+        let scope = Scope {
+            queue: Mutex::new(Queue {
+                tasks: VecDeque::new(),
+                closed: false,
+            }),
+            available: Condvar::new(),
+            panicked: Arc::new(AtomicBool::new(false)),
+            inline: self.workers == 1,
+        };
+
+        let result = if scope.inline {
+            panic::catch_unwind(AssertUnwindSafe(|| f(&scope)))
+        } else {
+            thread::scope(|threads| {
+                for _ in 0..self.workers {
+                    threads.spawn(|| scope.work());
+                }
+
+                let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+                scope.close();
+
+                result
+            })
+        };
+
+        match result {
+            Err(payload) => panic::resume_unwind(payload),
+            Ok(_) if scope.panicked.load(Ordering::Relaxed) => panic!("A pool task panicked"),
+            Ok(value) => value,
+        }
+    }
+
+    /// Map every item in parallel, keeping the order.
+    ///
+    /// Items are split in contiguous chunks, one per worker.
+    ///
+    /// # Panics
+    ///
+    /// If `map` panics.
+    pub fn parallel_map<T, R>(&self, items: &[T], map: impl Fn(&T) -> R + Sync) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+    {
+        self.parallel_map_reduce(
+            items,
+            |item| vec![map(item)],
+            |mut a, b| {
+                a.extend(b);
+                a
+            },
+        )
+        .unwrap_or_default()
+    }
+
+    /// Map every item in parallel and then fold the results (in order) with
+    /// `reduce`.
+    ///
+    /// Items are split in contiguous chunks, one per worker, which are reduced
+    /// on their own before being reduced together, so `reduce` must be
+    /// associative.
+    ///
+    /// Returns `None` if there are no `items`.
+    ///
+    /// # Panics
+    ///
+    /// If `map` or `reduce` panic.
+    pub fn parallel_map_reduce<T, R>(
+        &self,
+        items: &[T],
+        map: impl Fn(&T) -> R + Sync,
+        reduce: impl Fn(R, R) -> R + Sync,
+    ) -> Option<R>
+    where
+        T: Sync,
+        R: Send,
+    {
+        if items.is_empty() {
+            return None;
+        }
+
+        let chunk_size = items.len().div_ceil(self.workers);
+        let (map, reduce) = (&map, &reduce);
+
+        self.scope(|scope| {
+            let tasks = items
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || chunk.iter().map(map).reduce(reduce)))
+                .collect::<Vec<_>>();
+
+            tasks.into_iter().filter_map(Task::join).reduce(reduce)
+        })
+    }
+}
+
+type Job<'env> = Box<dyn FnOnce() + Send + 'env>;
+
+struct Queue<'env> {
+    tasks: VecDeque<Job<'env>>,
+    closed: bool,
+}
+
+/// Spawns tasks into a [`ThreadPool`]. See [`ThreadPool::scope`].
+pub struct Scope<'env> {
+    queue: Mutex<Queue<'env>>,
+    available: Condvar,
+    panicked: Arc<AtomicBool>,
+    inline: bool,
+}
+
+impl<'env> Scope<'env> {
+    /// Queue `f` to be run by some worker (or run it right away in a
+    /// single-worker pool).
+    pub fn spawn<T: Send + 'env>(&self, f: impl FnOnce() -> T + Send + 'env) -> Task<T> {
+        let slot = Arc::new(Slot {
+            result: Mutex::new(None),
+            done: Condvar::new(),
+        });
+
+        let job_slot = Arc::clone(&slot);
+        let panicked = Arc::clone(&self.panicked);
+        let seed = fork_seed();
+
+        let job = move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| match seed {
+                Some(seed) => with_seed(seed, f),
+                None => f(),
+            }));
+
+            if result.is_err() {
+                panicked.store(true, Ordering::Relaxed);
+            }
+
+            *lock(&job_slot.result) = Some(result);
+            job_slot.done.notify_all();
+        };
+
+        if self.inline {
+            job();
+        } else {
+            lock(&self.queue).tasks.push_back(Box::new(job));
+            self.available.notify_one();
+        }
+
+        Task { slot }
+    }
+
+    /// Run tasks until the scope is closed and there are none left.
+    fn work(&self) {
+        loop {
+            let job = {
+                let mut queue = lock(&self.queue);
+
+                loop {
+                    if let Some(job) = queue.tasks.pop_front() {
+                        break job;
+                    }
+
+                    if queue.closed {
+                        return;
+                    }
+
+                    queue = self
+                        .available
+                        .wait(queue)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+            };
+
+            job();
+        }
+    }
+
+    /// Let the workers exit once the queue is empty.
+    fn close(&self) {
+        lock(&self.queue).closed = true;
+        self.available.notify_all();
+    }
+}
+
+/// Lock a `mutex`. Jobs catch their panics, so it is never poisoned.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Slot<T> {
+    result: Mutex<Option<thread::Result<T>>>,
+    done: Condvar,
+}
+
+/// A task spawned with [`Scope::spawn`].
+pub struct Task<T> {
+    slot: Arc<Slot<T>>,
+}
+
+impl<T> Task<T> {
+    /// Wait for the task to finish and get its result.
+    ///
+    /// Joining from another task can deadlock (all workers could be waiting).
+    ///
+    /// # Panics
+    ///
+    /// With the task's panic, if it panicked.
+    #[must_use]
+    pub fn join(self) -> T {
+        let mut result = lock(&self.slot.result);
+
+        loop {
+            match result.take() {
+                Some(Ok(value)) => return value,
+                Some(Err(payload)) => panic::resume_unwind(payload),
+                None => {
+                    result = self
+                        .slot
+                        .done
+                        .wait(result)
+                        .unwrap_or_else(PoisonError::into_inner);
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+
+    use super::ThreadPool;
+    use crate::rand::with_seed;
+    use crate::util::generate_bytes;
+
+    #[test]
+    fn test_parallel_map() {
+        for workers in [1, 3, 8, 200] {
+            let pool = ThreadPool::new(workers);
+            let items = (0..100).collect::<Vec<u64>>();
+
+            assert_eq!(
+                pool.parallel_map(&items, |x| x * 2),
+                items.iter().map(|x| x * 2).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                pool.parallel_map_reduce(&items, ToString::to_string, |a, b| a + &b),
+                Some(items.iter().map(ToString::to_string).collect::<String>())
+            );
+        }
+
+        assert_eq!(
+            ThreadPool::new(4).parallel_map_reduce(&[] as &[u8], |&x| x, |a, b| a + b),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bounded_workers() {
+        let pool = ThreadPool::new(3);
+        let threads = Mutex::new(Vec::new());
+        let counter = AtomicUsize::new(0);
+
+        pool.scope(|scope| {
+            for _ in 0..50 {
+                scope.spawn(|| {
+                    counter.fetch_add(1, Ordering::Relaxed);
+
+                    let mut threads = threads.lock().unwrap();
+                    let id = thread::current().id();
+
+                    if !threads.contains(&id) {
+                        threads.push(id);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(counter.into_inner(), 50);
+        assert!(threads.into_inner().unwrap().len() <= 3);
+    }
+
+    #[test]
+    fn test_single_worker_inline() {
+        let caller = thread::current().id();
+
+        ThreadPool::new(1).scope(|scope| {
+            for _ in 0..10 {
+                scope.spawn(|| assert_eq!(thread::current().id(), caller));
+            }
+        });
+    }
+
+    #[test]
+    fn test_seeded_tasks() {
+        let run = |workers| {
+            with_seed(1337, || {
+                ThreadPool::new(workers).parallel_map(&[0; 8], |_| generate_bytes(16))
+            })
+        };
+
+        for workers in [1, 4] {
+            assert_eq!(run(workers), run(workers));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "boom")]
+    fn test_joined_panic() {
+        ThreadPool::new(2).scope(|scope| scope.spawn(|| panic!("boom")).join());
+    }
+
+    #[test]
+    #[should_panic(expected = "A pool task panicked")]
+    fn test_unjoined_panic() {
+        ThreadPool::new(2).scope(|scope| {
+            scope.spawn(|| panic!("boom"));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "boom")]
+    fn test_map_panic() {
+        ThreadPool::new(2).parallel_map(&[1, 2, 3], |&x| assert_ne!(x, 2, "boom"));
+    }
+}
@@ -0,0 +1,99 @@
+//! Helpers to keep secret values out of `Debug` output (and thus out of logs,
+//! panic messages and failed assertions).
+//!
+//! Secret-holding types implement `Debug` by hand, printing [`Redacted`]
+//! placeholders (which only reveal the secret length) instead of the values.
+//! Code that genuinely needs a secret (e.g. tests and attacks) must ask for it
+//! explicitly through an `expose_secret` accessor, which is easy to grep for.
+
+use std::fmt;
+
+use num_bigint::BigUint;
+
+/// Placeholder for a secret value in `Debug` output.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Redacted {
+    bits: u64,
+}
+
+impl Redacted {
+    /// Placeholder for a secret integer.
+    #[must_use]
+    pub fn biguint(secret: &BigUint) -> Self {
+        Redacted {
+            bits: secret.bits(),
+        }
+    }
+
+    /// Placeholder for secret bytes.
+    #[must_use]
+    pub const fn bytes(secret: &[u8]) -> Self {
+        Redacted {
+            bits: 8 * secret.len() as u64,
+        }
+    }
+}
+
+impl fmt::Debug for Redacted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted {}-bit secret>", self.bits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::Redacted;
+    use crate::block::params::{Iv, Key};
+    use crate::block::AES128;
+    use crate::dsa::CHALLENGE_DSA;
+    use crate::key_exchange::DHOffer;
+    use crate::testkeys::RSA_KEYPAIR_0;
+
+    #[test]
+    fn test_redacted() {
+        assert_eq!(
+            format!("{:?}", Redacted::biguint(&BigUint::from(255_usize))),
+            "<redacted 8-bit secret>"
+        );
+        assert_eq!(
+            format!("{:?}", Redacted::bytes(b"YELLOW SUBMARINE")),
+            "<redacted 128-bit secret>"
+        );
+    }
+
+    #[test]
+    fn test_secrets_not_in_debug() {
+        let (_, rsa_private) = &*RSA_KEYPAIR_0;
+        let (_, dsa_private) = CHALLENGE_DSA.gen_keypair();
+        let dh_offer = DHOffer::new();
+        let dh_session = dh_offer.clone().establish(dh_offer.get_public()).unwrap();
+        let key = Key::<AES128>::new(b"YELLOW SUBMARINE").unwrap();
+
+        for (debug, secret) in [
+            (
+                format!("{rsa_private:?}"),
+                rsa_private.expose_secret().to_string(),
+            ),
+            (
+                format!("{dsa_private:?}"),
+                dsa_private.expose_secret().to_string(),
+            ),
+            (
+                format!("{dh_offer:?}"),
+                dh_offer.expose_secret().to_string(),
+            ),
+            (
+                format!("{dh_session:?}"),
+                dh_session.get_shared_secret().to_string(),
+            ),
+            (format!("{key:?}"), format!("{:?}", key.as_ref())),
+        ] {
+            assert!(debug.contains("<redacted"));
+            assert!(!debug.contains(&secret));
+        }
+
+        assert!(format!("{:?}", Iv::<AES128>::new(&[7; 16]).unwrap()).contains("7, 7"));
+    }
+}
@@ -0,0 +1,199 @@
+//! Text metrics to rank candidate plaintexts.
+//!
+//! Each of these can be used as a [`TextScorer`] component, or to assert an
+//! attack recovered *close enough* plaintext when the exact one is not known
+//! (e.g. statistical attacks that get a few bytes wrong).
+
+use std::collections::HashSet;
+
+use super::TextScorer;
+
+/// [Levenshtein distance](https://en.wikipedia.org/wiki/Levenshtein_distance)
+/// between `a` and `b`: the minimum number of single-item insertions,
+/// deletions or substitutions to turn one into the other.
+///
+/// # Example
+///
+/// ```
+/// use rustopals::util::text::levenshtein;
+///
+/// assert_eq!(levenshtein(b"kitten", b"sitting"), 3);
+/// ```
+#[must_use]
+pub fn levenshtein<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // Only keep the previous row of the DP matrix
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Is `byte` printable ASCII (or common whitespace)?
+#[must_use]
+pub const fn is_printable(byte: u8) -> bool {
+    matches!(byte, 0x20..=0x7e | b'\t' | b'\n' | b'\r')
+}
+
+/// Ratio of [printable](is_printable) bytes in `bytes`, in `[0, 1]`.
+///
+/// Empty input is considered fully printable.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn printable_ratio(bytes: &[u8]) -> f32 {
+    if bytes.is_empty() {
+        return 1.0;
+    }
+
+    let printable = bytes.iter().filter(|&&byte| is_printable(byte)).count();
+
+    printable as f32 / bytes.len() as f32
+}
+
+/// Split `text` into lowercase words (runs of alphabetic characters).
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|ch: char| !ch.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Average length of the words (runs of alphabetic characters) in `text`.
+///
+/// Returns `0` if there are no words.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn average_word_length(text: &str) -> f32 {
+    let (count, total) = words(text).fold((0, 0), |(count, total), word| {
+        (count + 1, total + word.chars().count())
+    });
+
+    if count == 0 {
+        return 0.0;
+    }
+
+    total as f32 / count as f32
+}
+
+/// A set of known words, matched case-insensitively.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dictionary(HashSet<String>);
+
+impl Dictionary {
+    /// Build from a wordlist.
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Dictionary(
+            words
+                .into_iter()
+                .map(|word| word.as_ref().to_lowercase())
+                .collect(),
+        )
+    }
+
+    /// Is `word` in the dictionary?
+    #[must_use]
+    pub fn contains(&self, word: &str) -> bool {
+        self.0.contains(&word.to_lowercase())
+    }
+
+    /// Ratio of the words in `text` found in the dictionary, in `[0, 1]`.
+    ///
+    /// Returns `0` if there are no words.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn hit_rate(&self, text: &str) -> f32 {
+        let (count, hits) = words(text).fold((0, 0), |(count, hits), word| {
+            (count + 1, hits + usize::from(self.0.contains(&word)))
+        });
+
+        if count == 0 {
+            return 0.0;
+        }
+
+        hits as f32 / count as f32
+    }
+}
+
+/// Scores by [`Dictionary::hit_rate`].
+impl TextScorer for Dictionary {
+    fn score(&self, string: &str) -> f32 {
+        self.hit_rate(string)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{average_word_length, levenshtein, printable_ratio, Dictionary};
+    use crate::stream::{SingleXORCipher, StreamCipher};
+    use crate::util::TextScorer;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein(b"", b""), 0);
+        assert_eq!(levenshtein(b"abc", b""), 3);
+        assert_eq!(levenshtein(b"", b"abc"), 3);
+        assert_eq!(levenshtein(b"flaw", b"lawn"), 2);
+        assert_eq!(levenshtein(b"saturday", b"sunday"), 3);
+        assert_eq!(levenshtein(&[1, 2, 3], &[1, 2, 3]), 0);
+    }
+
+    #[test]
+    fn test_printable_ratio() {
+        assert!((printable_ratio(b"Hello\n") - 1.0).abs() < f32::EPSILON);
+        assert!((printable_ratio(b"Hi\x00\xff") - 0.5).abs() < f32::EPSILON);
+        assert!((printable_ratio(b"") - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_average_word_length() {
+        assert!((average_word_length("I like big, bold words!") - 3.4).abs() < f32::EPSILON);
+        assert!(average_word_length("1234 !!").abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let dictionary = Dictionary::from_words(["the", "quick", "brown", "fox"]);
+
+        assert!(dictionary.contains("QUICK"));
+        assert!((dictionary.hit_rate("The quick red fox") - 0.75).abs() < f32::EPSILON);
+        assert!(dictionary.hit_rate("").abs() < f32::EPSILON);
+    }
+
+    /// Dictionary hits alone tie on case flips and separator changes.
+    struct DictionaryAndPrintable(Dictionary);
+
+    impl TextScorer for DictionaryAndPrintable {
+        fn score(&self, string: &str) -> f32 {
+            self.0.hit_rate(string) + printable_ratio(string.as_bytes())
+        }
+    }
+
+    #[test]
+    fn test_dictionary_crack_single_xor() {
+        let scorer = DictionaryAndPrintable(Dictionary::from_words([
+            "cooking", "mc", "like", "pound", "of", "bacon",
+        ]));
+
+        let ciphertext = SingleXORCipher(0x58_u8)
+            .process(b"Cooking MC's like a pound of bacon")
+            .collect::<Vec<_>>();
+
+        let (key, plaintext) = SingleXORCipher::<u8>::crack(&scorer, &ciphertext).unwrap();
+
+        assert_eq!(key, 0x58);
+        assert_eq!(plaintext, "Cooking MC's like a pound of bacon");
+    }
+}
@@ -0,0 +1,249 @@
+//! Hash commitments to oracle transcripts.
+//!
+//! A [`Transcript`] wraps an oracle and chains the digests of every query and
+//! response into a single hash. An attack run through it yields a compact
+//! [`TranscriptCommitment`] along with its result, so tests can check not
+//! only that a secret was recovered, but that it was recovered the same way
+//! (same queries, in the same order) and in the expected amount of calls.
+//!
+//! Commitments are only reproducible if the oracle and the attack are
+//! deterministic (e.g. seeded oracles, no parallel scans).
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::util::transcript::recorded;
+//!
+//! const SECRET: u32 = 31337;
+//!
+//! // Binary search with a "less than" oracle
+//! let attack = |oracle: &dyn Fn(&u32) -> bool| {
+//!     let (mut low, mut high) = (0, 1 << 16);
+//!
+//!     while high - low > 1 {
+//!         let middle = (low + high) / 2;
+//!
+//!         if oracle(&middle) {
+//!             high = middle;
+//!         } else {
+//!             low = middle;
+//!         }
+//!     }
+//!
+//!     low
+//! };
+//!
+//! let (guess, commitment) = recorded(|&guess: &u32| SECRET < guess, attack);
+//! let (_, again) = recorded(|&guess: &u32| SECRET < guess, attack);
+//!
+//! assert_eq!(guess, SECRET);
+//! assert_eq!(commitment.queries, 16);
+//! assert_eq!(commitment, again);
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+
+use num_bigint::BigUint;
+
+use crate::digest::ds::hash_labeled;
+use crate::digest::{Digest, SHA256};
+
+/// Domain separation label for transcript chaining.
+pub const TRANSCRIPT_LABEL: &[u8] = b"rustopals-oracle-transcript";
+
+/// An oracle query or response that can be recorded in a [`Transcript`].
+pub trait Record {
+    /// Encode the value for hashing.
+    fn record_bytes(&self) -> Cow<'_, [u8]>;
+}
+
+impl Record for [u8] {
+    fn record_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl Record for Vec<u8> {
+    fn record_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl Record for bool {
+    fn record_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(vec![u8::from(*self)])
+    }
+}
+
+impl Record for u32 {
+    fn record_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.to_be_bytes().to_vec())
+    }
+}
+
+impl Record for BigUint {
+    fn record_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.to_bytes_be())
+    }
+}
+
+/// Commitment to a whole [`Transcript`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TranscriptCommitment {
+    /// Amount of recorded queries.
+    pub queries: u64,
+
+    /// Chained digest of every query and response.
+    pub digest: <SHA256 as Digest>::Output,
+}
+
+impl fmt::Display for TranscriptCommitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} queries, {}", self.queries, hex::encode(self.digest))
+    }
+}
+
+/// Running record of oracle queries and responses.
+///
+/// Shareable between threads, so wrapped oracles can still be used in
+/// parallel scans (at the cost of a reproducible order).
+#[derive(Debug)]
+pub struct Transcript {
+    commitment: Mutex<TranscriptCommitment>,
+}
+
+impl Transcript {
+    /// Create an empty transcript.
+    #[must_use]
+    pub fn new() -> Transcript {
+        Transcript {
+            commitment: Mutex::new(TranscriptCommitment {
+                queries: 0,
+                digest: <SHA256 as Digest>::Output::default(),
+            }),
+        }
+    }
+
+    /// Record a `query` to some oracle and its `response`.
+    pub fn record(&self, query: &[u8], response: &[u8]) {
+        let query_digest = SHA256::digest(query);
+        let response_digest = SHA256::digest(response);
+
+        let mut commitment = self
+            .commitment
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        commitment.queries += 1;
+        commitment.digest = hash_labeled::<SHA256>(
+            TRANSCRIPT_LABEL,
+            &[&commitment.digest, &query_digest, &response_digest],
+        );
+    }
+
+    /// Wrap an `oracle` so that every call gets recorded.
+    pub fn wrap<'a, Q, R>(&'a self, oracle: impl Fn(&Q) -> R + 'a) -> impl Fn(&Q) -> R + 'a
+    where
+        Q: Record + ?Sized,
+        R: Record,
+    {
+        move |query| {
+            let response = oracle(query);
+
+            self.record(&query.record_bytes(), &response.record_bytes());
+
+            response
+        }
+    }
+
+    /// Get the commitment to everything recorded so far.
+    #[must_use]
+    pub fn commitment(&self) -> TranscriptCommitment {
+        *self
+            .commitment
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Transcript {
+    fn default() -> Transcript {
+        Transcript::new()
+    }
+}
+
+/// Run an `attack` against a recorded `oracle`, returning its result along
+/// with the commitment to its transcript.
+///
+/// For attacks needing a `Sync` oracle, use [`Transcript::wrap`] instead.
+pub fn recorded<Q, R, T>(
+    oracle: impl Fn(&Q) -> R,
+    attack: impl FnOnce(&dyn Fn(&Q) -> R) -> T,
+) -> (T, TranscriptCommitment)
+where
+    Q: Record + ?Sized,
+    R: Record,
+{
+    let transcript = Transcript::new();
+    let result = attack(&transcript.wrap(oracle));
+
+    (result, transcript.commitment())
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{recorded, Transcript};
+    use crate::rsa::attacks::parity_oracle_attack;
+    use crate::rsa::{RSAPrivateKey, RSAPublicKey};
+    use crate::testkeys::RSA_KEYPAIR_0;
+    use crate::util::pool::ThreadPool;
+
+    const PLAINTEXT: &[u8] = b"Transcripts or it didn't happen";
+
+    #[test]
+    fn test_parity_oracle_transcript() {
+        let (public_key, private_key) = &RSA_KEYPAIR_0 as &(RSAPublicKey, RSAPrivateKey);
+        let ciphertext = public_key
+            .textbook_process(&BigUint::from_bytes_be(PLAINTEXT))
+            .unwrap();
+
+        let oracle =
+            |ciphertext: &BigUint| !private_key.textbook_process(ciphertext).unwrap().bit(0);
+
+        let attack = |oracle: &dyn Fn(&BigUint) -> bool| {
+            parity_oracle_attack(public_key, &ciphertext, oracle)
+        };
+
+        let (plaintext, commitment) = recorded(oracle, attack);
+
+        assert_eq!(plaintext, PLAINTEXT);
+        assert_eq!(commitment.queries, public_key.n.bits());
+        assert_eq!(recorded(oracle, attack).1, commitment);
+
+        // A different pattern of queries (even if equally successful) commits
+        // to a different transcript
+        let (_, extra_query) = recorded(oracle, |oracle: &dyn Fn(&BigUint) -> bool| {
+            oracle(&ciphertext);
+            attack(oracle)
+        });
+
+        assert_eq!(extra_query.queries, commitment.queries + 1);
+        assert_ne!(extra_query.digest, commitment.digest);
+    }
+
+    #[test]
+    fn test_wrap_is_sync() {
+        let transcript = Transcript::new();
+        let oracle = transcript.wrap(|input: &[u8]| input.to_vec());
+
+        let inputs = (0..100_u8).map(|i| vec![i]).collect::<Vec<_>>();
+        let outputs = ThreadPool::new(4).parallel_map(&inputs, |input| oracle(input));
+
+        assert_eq!(outputs, inputs);
+        assert_eq!(transcript.commitment().queries, 100);
+    }
+}
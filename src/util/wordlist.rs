@@ -0,0 +1,51 @@
+//! Embedded wordlist for dictionary attacks (behind the `data` feature).
+//!
+//! [`common`] has about a thousand of the most frequent English words,
+//! followed by a couple hundred of the most common passwords, so that
+//! offline dictionary attacks can be tried without bringing a list along.
+//!
+//! ```
+//! use rustopals::util::wordlist;
+//!
+//! assert_eq!(wordlist::common()[0], "the");
+//! assert!(wordlist::common().contains(&"password"));
+//! ```
+
+use once_cell::sync::Lazy;
+
+/// Raw wordlist, one entry per line (`#` starts a comment line).
+const COMMON: &str = include_str!("../../data/common.txt");
+
+static WORDS: Lazy<Vec<&str>> = Lazy::new(|| {
+    COMMON
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+});
+
+/// Common English words (most frequent first) and then common passwords,
+/// without duplicates.
+#[must_use]
+pub fn common() -> &'static [&'static str] {
+    &WORDS
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::common;
+
+    #[test]
+    fn test_common() {
+        let words = common();
+
+        assert!(words.len() > 1000);
+        assert!(words.iter().all(|word| !word.is_empty()
+            && !word.starts_with('#')
+            && !word.contains(char::is_whitespace)));
+        assert_eq!(words.iter().collect::<HashSet<_>>().len(), words.len());
+        assert!(words.contains(&"qwerty"));
+    }
+}
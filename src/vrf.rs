@@ -0,0 +1,174 @@
+//! [Verifiable random function](https://en.wikipedia.org/wiki/Verifiable_random_function)
+//! based on RSA with a full-domain hash (RSA-FDH-VRF).
+//!
+//! The private key holder maps an input to a pseudorandom output, and can
+//! prove to anybody holding the public key that the output is the right one.
+//! Since the proof is a deterministic RSA signature of a full-domain hash,
+//! there is exactly one valid output for each input (uniqueness).
+//!
+//! Loosely follows the RSA-FDH-VRF construction in
+//! [RFC 9381](https://www.rfc-editor.org/rfc/rfc9381), with custom labels
+//! for domain separation (and no interoperability guarantees).
+
+use num_bigint::BigUint;
+
+use crate::digest::Digest;
+use crate::rsa::{RSAPrivateKey, RSAPublicKey};
+use crate::util::os2ip;
+
+/// Domain separation label for the full-domain hash of the input.
+const HASH_TO_DOMAIN_LABEL: &[u8] = b"rustopals-rsa-fdh-vrf-hash-to-domain";
+
+/// Domain separation label for the proof-to-output hash.
+const PROOF_TO_HASH_LABEL: &[u8] = b"rustopals-rsa-fdh-vrf-proof-to-hash";
+
+/// A VRF proof (i.e. an RSA signature of the full-domain hash of the input).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VRFProof(pub BigUint);
+
+/// Expand `message` to `len` bytes by hashing it with an incrementing counter
+/// ([MGF1](https://en.wikipedia.org/wiki/Mask_generation_function#MGF1)-like),
+/// prefixing every block with `label`.
+#[must_use]
+pub fn full_domain_hash<D: Digest>(label: &[u8], message: &[u8], len: usize) -> Vec<u8> {
+    (0_u32..)
+        .flat_map(|counter| {
+            D::digest_parts(&[label, message, &counter.to_be_bytes()])
+                .as_ref()
+                .to_vec()
+        })
+        .take(len)
+        .collect()
+}
+
+/// Full-domain hash of `input`, always smaller than the modulus (its top byte
+/// is left as zero).
+fn hash_to_domain<D: Digest>(len_bytes: usize, input: &[u8]) -> BigUint {
+    os2ip(&full_domain_hash::<D>(
+        HASH_TO_DOMAIN_LABEL,
+        input,
+        len_bytes - 1,
+    ))
+}
+
+/// Prove the VRF output for `input`.
+#[must_use]
+pub fn prove<D: Digest>(private_key: &RSAPrivateKey, input: &[u8]) -> Option<VRFProof> {
+    private_key
+        .textbook_process(&hash_to_domain::<D>(private_key.len_bytes(), input))
+        .map(VRFProof)
+}
+
+/// Get the VRF output from a proof, without verifying it.
+#[must_use]
+pub fn proof_to_hash<D: Digest>(proof: &VRFProof) -> D::Output {
+    let VRFProof(signature) = proof;
+
+    D::digest_parts(&[PROOF_TO_HASH_LABEL, &signature.to_bytes_be()])
+}
+
+/// Verify a `proof` for `input`, returning the VRF output if it is valid.
+#[must_use]
+pub fn verify<D: Digest>(
+    public_key: &RSAPublicKey,
+    input: &[u8],
+    proof: &VRFProof,
+) -> Option<D::Output> {
+    let VRFProof(signature) = proof;
+
+    let expected = hash_to_domain::<D>(public_key.len_bytes(), input);
+
+    if public_key.textbook_process(signature)? == expected {
+        Some(proof_to_hash::<D>(proof))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{full_domain_hash, proof_to_hash, prove, verify, VRFProof};
+    use crate::digest::{Digest, SHA1, SHA256};
+    use crate::testkeys::RSA_KEYPAIR_0;
+
+    const INPUT: &[u8] = b"lottery round 42";
+
+    #[test]
+    fn test_full_domain_hash() {
+        let expanded = full_domain_hash::<SHA1>(b"label", b"message", 100);
+
+        assert_eq!(expanded.len(), 100);
+        assert_eq!(
+            &expanded[..SHA1::OUTPUT_LENGTH],
+            SHA1::new()
+                .chain(b"label")
+                .chain(b"message")
+                .chain(&[0, 0, 0, 0])
+                .finalize()
+                .as_ref()
+        );
+        assert_ne!(
+            expanded,
+            full_domain_hash::<SHA1>(b"other", b"message", 100)
+        );
+    }
+
+    #[test]
+    fn test_prove_verify() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let proof = prove::<SHA256>(private_key, INPUT).unwrap();
+
+        assert_eq!(
+            verify::<SHA256>(public_key, INPUT, &proof),
+            Some(proof_to_hash::<SHA256>(&proof))
+        );
+    }
+
+    #[test]
+    fn test_uniqueness() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let proof_1 = prove::<SHA256>(private_key, INPUT).unwrap();
+        let proof_2 = prove::<SHA256>(private_key, INPUT).unwrap();
+
+        assert_eq!(proof_1, proof_2);
+        assert_eq!(
+            verify::<SHA256>(public_key, INPUT, &proof_1),
+            verify::<SHA256>(public_key, INPUT, &proof_2)
+        );
+
+        let other_proof = prove::<SHA256>(private_key, b"lottery round 43").unwrap();
+
+        assert_ne!(
+            proof_to_hash::<SHA256>(&proof_1),
+            proof_to_hash::<SHA256>(&other_proof)
+        );
+    }
+
+    #[test]
+    fn test_bad_proofs() {
+        let (public_key, private_key) = &*RSA_KEYPAIR_0;
+
+        let VRFProof(signature) = prove::<SHA256>(private_key, INPUT).unwrap();
+
+        assert_eq!(
+            verify::<SHA256>(
+                public_key,
+                INPUT,
+                &VRFProof(signature.clone() + BigUint::from(1_usize))
+            ),
+            None
+        );
+        assert_eq!(
+            verify::<SHA256>(public_key, b"lottery round 43", &VRFProof(signature)),
+            None
+        );
+        assert_eq!(
+            verify::<SHA256>(public_key, INPUT, &VRFProof(public_key.n.clone() * 2_u32)),
+            None
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! [WebAssembly](https://en.wikipedia.org/wiki/WebAssembly) bindings, so the
+//! challenge tooling can run in a browser playground.
+//!
+//! Only a thin, byte-oriented subset of the crate is exposed through
+//! [`wasm_bindgen`](https://rustwasm.github.io/docs/wasm-bindgen/). Byte
+//! slices map to `Uint8Array` and `None` maps to `undefined` on the JS side.
+//!
+//! Requires the `wasm` feature. Build with:
+//!
+//! ```text
+//! cargo +nightly rustc --release --lib --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/rustopals.wasm
+//! ```
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::block::params::{Iv, Key};
+use crate::block::{BlockMode, AES128, CBC};
+use crate::digest::{Digest, MD4, SHA1, SHA256};
+use crate::mac;
+use crate::stream::{RepeatingXORCipher, SingleXORCipher, StreamCipher};
+use crate::util::NaiveTextScorer;
+
+/// Hash `data` with `algorithm` (`"md4"`, `"sha1"` or `"sha256"`).
+///
+/// Returns `undefined` for unknown algorithms.
+#[wasm_bindgen]
+#[must_use]
+pub fn hash(algorithm: &str, data: &[u8]) -> Option<Vec<u8>> {
+    match algorithm {
+        "md4" => Some(MD4::digest(data).into()),
+        "sha1" => Some(SHA1::digest(data).into()),
+        "sha256" => Some(SHA256::digest(data).into()),
+        _ => None,
+    }
+}
+
+/// Compute the HMAC of `message` with `key`, using `algorithm` (`"md4"`,
+/// `"sha1"` or `"sha256"`).
+///
+/// Returns `undefined` for unknown algorithms.
+#[wasm_bindgen]
+#[must_use]
+pub fn hmac(algorithm: &str, key: &[u8], message: &[u8]) -> Option<Vec<u8>> {
+    match algorithm {
+        "md4" => Some(mac::hmac::<MD4>(key, message).into()),
+        "sha1" => Some(mac::hmac::<SHA1>(key, message).into()),
+        "sha256" => Some(mac::hmac::<SHA256>(key, message).into()),
+        _ => None,
+    }
+}
+
+/// Encrypt `plaintext` with AES-128 in CBC mode (PKCS#7 padded).
+///
+/// Returns `undefined` if `key` or `iv` are not 16 bytes long.
+#[wasm_bindgen]
+#[must_use]
+pub fn aes_cbc_encrypt(key: &[u8], iv: &[u8], plaintext: &[u8]) -> Option<Vec<u8>> {
+    let key = Key::<AES128>::new(key).ok()?;
+    let iv = Iv::<AES128>::new(iv).ok()?;
+
+    Some(CBC::new(&iv).encrypt(&AES128, plaintext, &key))
+}
+
+/// Decrypt `ciphertext` with AES-128 in CBC mode (PKCS#7 padded).
+///
+/// Returns `undefined` if `key` or `iv` are not 16 bytes long, if the
+/// ciphertext is not a whole number of blocks, or if the padding is wrong.
+#[wasm_bindgen]
+#[must_use]
+pub fn aes_cbc_decrypt(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+    let key = Key::<AES128>::new(key).ok()?;
+    let iv = Iv::<AES128>::new(iv).ok()?;
+
+    if !ciphertext.len().is_multiple_of(16) {
+        return None;
+    }
+
+    CBC::new(&iv).decrypt(&AES128, ciphertext, &key).ok()
+}
+
+/// Crack a single-byte XOR `ciphertext` by frequency analysis, returning the
+/// plaintext.
+#[wasm_bindgen]
+#[must_use]
+pub fn crack_single_xor(ciphertext: &[u8]) -> Option<String> {
+    SingleXORCipher::<u8>::crack(&NaiveTextScorer, ciphertext).map(|(_, plaintext)| plaintext)
+}
+
+/// Crack a repeating-key XOR `ciphertext` (with a key up to `max_keysize`
+/// bytes long), returning the plaintext.
+///
+/// Key positions are cracked one after the other: on `wasm32` the
+/// [thread pool](crate::util::pool) runs its tasks inline.
+#[wasm_bindgen]
+#[must_use]
+pub fn crack_repeating_xor(ciphertext: &[u8], max_keysize: usize) -> Option<Vec<u8>> {
+    let keysize = RepeatingXORCipher::<u8>::guess_keysize(ciphertext, max_keysize)?;
+    let key = RepeatingXORCipher::<u8>::guess_key(&NaiveTextScorer, ciphertext, keysize);
+
+    Some(RepeatingXORCipher(&key).process(ciphertext).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        aes_cbc_decrypt, aes_cbc_encrypt, crack_repeating_xor, crack_single_xor, hash, hmac,
+    };
+    use crate::stream::{RepeatingXORCipher, StreamCipher};
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+    const IV: &[u8] = &[0; 16];
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            hash("sha1", b"abc"),
+            Some(hex::decode("a9993e364706816aba3e25717850c26c9cd0d89d").unwrap())
+        );
+        assert_eq!(hash("sha3", b"abc"), None);
+        assert_eq!(hmac("sha3", b"key", b"abc"), None);
+    }
+
+    #[test]
+    fn test_aes_cbc() {
+        let ciphertext = aes_cbc_encrypt(KEY, IV, b"Hello, browser!").unwrap();
+
+        assert_eq!(
+            aes_cbc_decrypt(KEY, IV, &ciphertext),
+            Some(b"Hello, browser!".to_vec())
+        );
+        assert_eq!(aes_cbc_encrypt(b"short", IV, b"Hello"), None);
+        assert_eq!(aes_cbc_decrypt(KEY, IV, &ciphertext[1..]), None);
+    }
+
+    #[test]
+    fn test_crack_single_xor() {
+        let ciphertext = b"Cooking MC's like a pound of bacon"
+            .iter()
+            .map(|x| x ^ 0x58)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            crack_single_xor(&ciphertext).as_deref(),
+            Some("Cooking MC's like a pound of bacon")
+        );
+    }
+
+    #[test]
+    fn test_crack_repeating_xor() {
+        const PLAINTEXT: &[u8] = include_bytes!("../tests/set1/6.solution.txt");
+
+        let ciphertext = RepeatingXORCipher(b"Terminator X: Bring the noise")
+            .process(PLAINTEXT)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            crack_repeating_xor(&ciphertext, 40).as_deref(),
+            Some(PLAINTEXT)
+        );
+    }
+}
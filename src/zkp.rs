@@ -0,0 +1,445 @@
+//! [Zero-knowledge proofs](https://en.wikipedia.org/wiki/Zero-knowledge_proof)
+//! of knowledge of a discrete logarithm.
+//!
+//! Implements [Schnorr identification](https://en.wikipedia.org/wiki/Proof_of_knowledge#Schnorr_protocol).
+//! The prover knows `x` such that `y = g^x mod p` and convinces a verifier
+//! without revealing anything else about `x`:
+//!
+//! 1. **Commit**: the prover picks a random nonce `r` and sends `t = g^r`.
+//! 2. **Challenge**: the verifier replies with a random `c` (mod `q`).
+//! 3. **Respond**: the prover sends `s = r + c·x mod q`.
+//!
+//! The verifier accepts if `g^s = t·y^c`. The
+//! [Fiat-Shamir heuristic](https://en.wikipedia.org/wiki/Fiat%E2%80%93Shamir_heuristic)
+//! makes it non-interactive by deriving the challenge from a hash of the
+//! statement, the commitment and some context (see [`Schnorr::prove`]).
+//!
+//! Nonces must never be reused: two responses for the same commitment leak
+//! the secret (see [`Schnorr::extract`]), which is why [`SchnorrNonce`] is
+//! consumed when responding.
+//!
+//! # Example
+//!
+//! ```
+//! use rustopals::digest::SHA256;
+//! use rustopals::zkp::Schnorr;
+//!
+//! let schnorr = Schnorr::new();
+//! let (public_key, private_key) = schnorr.gen_keypair();
+//!
+//! // Interactive
+//! let (commitment, nonce) = schnorr.commit();
+//! let challenge = schnorr.gen_challenge();
+//! let response = schnorr.respond(&private_key, nonce, &challenge);
+//!
+//! assert!(schnorr.verify(&public_key, &commitment, &challenge, &response));
+//!
+//! // Non-interactive
+//! let proof = schnorr.prove::<SHA256>(&private_key, b"login as alice");
+//!
+//! assert!(schnorr.verify_proof::<SHA256>(&public_key, b"login as alice", &proof));
+//! assert!(!schnorr.verify_proof::<SHA256>(&public_key, b"login as bob", &proof));
+//! ```
+
+use std::fmt;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+
+use crate::digest::ds::hash_labeled;
+use crate::digest::Digest;
+use crate::key_exchange::dh::{NIST_BASE, NIST_MODULUS};
+use crate::rand::rng;
+use crate::util::secret::Redacted;
+use crate::util::{binary_inv_mod, byte_len, i2osp, os2ip};
+
+/// Domain separation label for the Fiat-Shamir challenge.
+pub const CHALLENGE_LABEL: &[u8] = b"rustopals-schnorr-challenge";
+
+/// Schnorr identification over a prime-order subgroup of `Z_p^*`.
+pub struct Schnorr {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+impl Schnorr {
+    /// Schnorr identification over the NIST [DH group](crate::key_exchange::dh).
+    ///
+    /// Its modulus is a safe prime `p = 2q + 1`, and the base generates the
+    /// subgroup of order `q`.
+    #[must_use]
+    pub fn new() -> Schnorr {
+        let q = (&*NIST_MODULUS - 1_u32) >> 1;
+
+        Schnorr::new_from_params(NIST_MODULUS.clone(), q, NIST_BASE.clone())
+    }
+
+    /// Schnorr identification with custom parameters: `g` must generate a
+    /// subgroup of prime order `q` modulo the prime `p`.
+    #[must_use]
+    pub const fn new_from_params(p: BigUint, q: BigUint, g: BigUint) -> Schnorr {
+        Schnorr { p, q, g }
+    }
+
+    /// Generate a keypair.
+    #[must_use]
+    pub fn gen_keypair(&self) -> (SchnorrPublicKey, SchnorrPrivateKey) {
+        let x = rng().gen_biguint_range(&BigUint::one(), &self.q);
+        let y = self.g.modpow(&x, &self.p);
+
+        (SchnorrPublicKey(y), SchnorrPrivateKey(x))
+    }
+
+    /// Whether `x` belongs to the subgroup generated by `g`.
+    #[must_use]
+    pub fn is_element(&self, x: &BigUint) -> bool {
+        !x.is_zero() && x < &self.p && x.modpow(&self.q, &self.p).is_one()
+    }
+
+    /// Commit to a random nonce, returning the commitment to send and the
+    /// nonce to respond with.
+    #[must_use]
+    pub fn commit(&self) -> (BigUint, SchnorrNonce) {
+        let r = rng().gen_biguint_below(&self.q);
+
+        (self.g.modpow(&r, &self.p), SchnorrNonce(r))
+    }
+
+    /// Generate a random challenge (as the verifier).
+    #[must_use]
+    pub fn gen_challenge(&self) -> BigUint {
+        rng().gen_biguint_below(&self.q)
+    }
+
+    /// Respond to a `challenge` for the commitment of `nonce`.
+    #[must_use]
+    pub fn respond(
+        &self,
+        SchnorrPrivateKey(x): &SchnorrPrivateKey,
+        SchnorrNonce(r): SchnorrNonce,
+        challenge: &BigUint,
+    ) -> BigUint {
+        (r + challenge * x) % &self.q
+    }
+
+    /// Verify an interactive transcript.
+    #[must_use]
+    pub fn verify(
+        &self,
+        SchnorrPublicKey(y): &SchnorrPublicKey,
+        commitment: &BigUint,
+        challenge: &BigUint,
+        response: &BigUint,
+    ) -> bool {
+        if !self.is_element(y) || !self.is_element(commitment) {
+            return false;
+        }
+
+        if challenge >= &self.q || response >= &self.q {
+            return false;
+        }
+
+        self.g.modpow(response, &self.p) == (commitment * y.modpow(challenge, &self.p)) % &self.p
+    }
+
+    /// Simulate an accepting transcript for a known `challenge`, without the
+    /// private key: pick the response first and solve for the commitment.
+    ///
+    /// Simulated transcripts are distributed exactly like honest ones, so an
+    /// honest verifier learns nothing from a real run (it could have produced
+    /// the transcript itself). This does not allow impersonation, since the
+    /// commitment must be sent *before* the challenge is known.
+    ///
+    /// Returns `(commitment, response)`.
+    ///
+    /// # Panics
+    ///
+    /// If the public key is not invertible modulo `p`.
+    #[must_use]
+    pub fn simulate(
+        &self,
+        SchnorrPublicKey(y): &SchnorrPublicKey,
+        challenge: &BigUint,
+    ) -> (BigUint, BigUint) {
+        let response = rng().gen_biguint_below(&self.q);
+
+        let y_inv = binary_inv_mod(y, &self.p).expect("public key should be invertible");
+        let commitment =
+            (self.g.modpow(&response, &self.p) * y_inv.modpow(challenge, &self.p)) % &self.p;
+
+        (commitment, response)
+    }
+
+    /// Extract the private key from two accepting `(challenge, response)`
+    /// pairs for the same commitment (e.g. by rewinding the prover, or from a
+    /// reused nonce): `x = (s_1 - s_2) / (c_1 - c_2) mod q`.
+    ///
+    /// This is why the protocol is sound: anybody who can answer two
+    /// different challenges knows the private key.
+    ///
+    /// Returns `None` if both challenges are equal.
+    #[must_use]
+    pub fn extract(
+        &self,
+        (challenge_1, response_1): (&BigUint, &BigUint),
+        (challenge_2, response_2): (&BigUint, &BigUint),
+    ) -> Option<SchnorrPrivateKey> {
+        let challenge_diff = (challenge_1 + &self.q - challenge_2) % &self.q;
+        let response_diff = (response_1 + &self.q - response_2) % &self.q;
+
+        let x = (response_diff * binary_inv_mod(&challenge_diff, &self.q)?) % &self.q;
+
+        Some(SchnorrPrivateKey(x))
+    }
+
+    /// Derive the non-interactive challenge for a `commitment`, bound to the
+    /// group, the public key and a `context`.
+    fn fiat_shamir_challenge<D: Digest>(
+        &self,
+        SchnorrPublicKey(y): &SchnorrPublicKey,
+        commitment: &BigUint,
+        context: &[u8],
+    ) -> Option<BigUint> {
+        let len = byte_len(&self.p);
+
+        let hash = hash_labeled::<D>(
+            CHALLENGE_LABEL,
+            &[
+                &i2osp(&self.p, len)?,
+                &i2osp(&self.g, len)?,
+                &i2osp(y, len)?,
+                &i2osp(commitment, len)?,
+                context,
+            ],
+        );
+
+        Some(os2ip(hash.as_ref()) % &self.q)
+    }
+
+    /// Prove knowledge of `private_key` non-interactively, bound to a
+    /// `context` (e.g. a message or session identifier).
+    ///
+    /// # Panics
+    ///
+    /// If the private key is out of range for this group.
+    #[must_use]
+    pub fn prove<D: Digest>(
+        &self,
+        private_key: &SchnorrPrivateKey,
+        context: &[u8],
+    ) -> SchnorrProof {
+        let SchnorrPrivateKey(x) = private_key;
+        let public_key = SchnorrPublicKey(self.g.modpow(x, &self.p));
+
+        let (commitment, nonce) = self.commit();
+        let challenge = self
+            .fiat_shamir_challenge::<D>(&public_key, &commitment, context)
+            .expect("group elements should fit the modulus length");
+        let response = self.respond(private_key, nonce, &challenge);
+
+        SchnorrProof {
+            commitment,
+            response,
+        }
+    }
+
+    /// Verify a non-interactive proof for `context`.
+    #[must_use]
+    pub fn verify_proof<D: Digest>(
+        &self,
+        public_key: &SchnorrPublicKey,
+        context: &[u8],
+        proof: &SchnorrProof,
+    ) -> bool {
+        let Some(challenge) =
+            self.fiat_shamir_challenge::<D>(public_key, &proof.commitment, context)
+        else {
+            return false;
+        };
+
+        self.verify(public_key, &proof.commitment, &challenge, &proof.response)
+    }
+}
+
+impl Default for Schnorr {
+    fn default() -> Schnorr {
+        Schnorr::new()
+    }
+}
+
+/// A Schnorr private key `x`.
+#[derive(PartialEq, Eq)]
+pub struct SchnorrPrivateKey(BigUint);
+
+impl fmt::Debug for SchnorrPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SchnorrPrivateKey")
+            .field(&Redacted::biguint(&self.0))
+            .finish()
+    }
+}
+
+impl SchnorrPrivateKey {
+    /// Get the private key `x`.
+    #[must_use]
+    pub const fn expose_secret(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+/// A Schnorr public key `y = g^x mod p`.
+#[derive(PartialEq, Eq, Debug)]
+pub struct SchnorrPublicKey(pub BigUint);
+
+/// The secret nonce `r` behind a commitment. Consumed when responding, so it
+/// cannot be reused.
+pub struct SchnorrNonce(BigUint);
+
+impl fmt::Debug for SchnorrNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SchnorrNonce")
+            .field(&Redacted::biguint(&self.0))
+            .finish()
+    }
+}
+
+/// A non-interactive (Fiat-Shamir) Schnorr proof.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SchnorrProof {
+    pub commitment: BigUint,
+    pub response: BigUint,
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+
+    use super::{Schnorr, SchnorrNonce, SchnorrProof, SchnorrPublicKey};
+    use crate::digest::{SHA1, SHA256};
+
+    #[test]
+    fn test_interactive() {
+        let schnorr = Schnorr::new();
+        let (public_key, private_key) = schnorr.gen_keypair();
+
+        let (commitment, nonce) = schnorr.commit();
+        let challenge = schnorr.gen_challenge();
+        let response = schnorr.respond(&private_key, nonce, &challenge);
+
+        assert!(schnorr.verify(&public_key, &commitment, &challenge, &response));
+
+        let (other_public_key, _) = schnorr.gen_keypair();
+
+        assert!(!schnorr.verify(&other_public_key, &commitment, &challenge, &response));
+        assert!(!schnorr.verify(&public_key, &commitment, &(&challenge + 1_u32), &response));
+    }
+
+    #[test]
+    fn test_non_interactive() {
+        let schnorr = Schnorr::new();
+        let (public_key, private_key) = schnorr.gen_keypair();
+
+        let proof = schnorr.prove::<SHA256>(&private_key, b"context");
+
+        assert!(schnorr.verify_proof::<SHA256>(&public_key, b"context", &proof));
+        assert!(!schnorr.verify_proof::<SHA256>(&public_key, b"other context", &proof));
+        assert!(!schnorr.verify_proof::<SHA1>(&public_key, b"context", &proof));
+
+        let (other_public_key, _) = schnorr.gen_keypair();
+
+        assert!(!schnorr.verify_proof::<SHA256>(&other_public_key, b"context", &proof));
+    }
+
+    #[test]
+    fn test_rejects_non_elements() {
+        let schnorr = Schnorr::new();
+        let (public_key, private_key) = schnorr.gen_keypair();
+
+        let proof = schnorr.prove::<SHA256>(&private_key, b"context");
+
+        // `p - 1` has order 2, not `q`
+        let minus_one = &schnorr.p - 1_u32;
+
+        assert!(!schnorr.verify_proof::<SHA256>(
+            &SchnorrPublicKey(minus_one.clone()),
+            b"context",
+            &proof
+        ));
+        assert!(!schnorr.verify_proof::<SHA256>(
+            &public_key,
+            b"context",
+            &SchnorrProof {
+                commitment: minus_one,
+                ..proof.clone()
+            }
+        ));
+        assert!(!schnorr.verify_proof::<SHA256>(
+            &public_key,
+            b"context",
+            &SchnorrProof {
+                response: &proof.response + &schnorr.q,
+                ..proof
+            }
+        ));
+    }
+
+    // Honest-verifier zero-knowledge: the verifier can produce accepting
+    // transcripts for its own challenges without the prover
+    #[test]
+    fn test_simulator() {
+        let schnorr = Schnorr::new();
+        let (public_key, _) = schnorr.gen_keypair();
+
+        for _ in 0..8 {
+            let challenge = schnorr.gen_challenge();
+            let (commitment, response) = schnorr.simulate(&public_key, &challenge);
+
+            assert!(schnorr.is_element(&commitment));
+            assert!(schnorr.verify(&public_key, &commitment, &challenge, &response));
+        }
+    }
+
+    // Special soundness: rewinding a prover to answer two challenges for the
+    // same commitment reveals the private key
+    #[test]
+    fn test_rewinding_extracts_key() {
+        let schnorr = Schnorr::new();
+        let (public_key, private_key) = schnorr.gen_keypair();
+
+        let (commitment, SchnorrNonce(r)) = schnorr.commit();
+
+        let challenge_1 = schnorr.gen_challenge();
+        let response_1 = schnorr.respond(&private_key, SchnorrNonce(r.clone()), &challenge_1);
+
+        let challenge_2 = schnorr.gen_challenge();
+        let response_2 = schnorr.respond(&private_key, SchnorrNonce(r), &challenge_2);
+
+        assert!(schnorr.verify(&public_key, &commitment, &challenge_1, &response_1));
+        assert!(schnorr.verify(&public_key, &commitment, &challenge_2, &response_2));
+
+        assert_eq!(
+            schnorr.extract((&challenge_1, &response_1), (&challenge_2, &response_2)),
+            Some(private_key)
+        );
+        assert_eq!(
+            schnorr.extract((&challenge_1, &response_1), (&challenge_1, &response_1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_small_group() {
+        // 2 has order 11 modulo 23
+        let schnorr = Schnorr::new_from_params(
+            BigUint::from(23_u32),
+            BigUint::from(11_u32),
+            BigUint::from(2_u32),
+        );
+        let (public_key, private_key) = schnorr.gen_keypair();
+
+        let proof = schnorr.prove::<SHA256>(&private_key, b"context");
+
+        assert!(schnorr.verify_proof::<SHA256>(&public_key, b"context", &proof));
+    }
+}
@@ -20,7 +20,8 @@ fn challenge1_hex_to_base64() {
 #[test]
 /// Fixed XOR - https://cryptopals.com/sets/1/challenges/2
 fn challenge2_fixed_xor() {
-    use rustopals::util::iter::{bytes_from_hex, ToHexable, Xorable};
+    use rustopals::prelude::*;
+    use rustopals::util::iter::bytes_from_hex;
 
     const INPUT_A: &str = "1c0111001f010100061a024b53535009181c";
     const INPUT_B: &str = "686974207468652062756c6c277320657965";
@@ -37,7 +38,7 @@ fn challenge2_fixed_xor() {
 #[test]
 /// Single-byte XOR cipher - https://cryptopals.com/sets/1/challenges/3
 fn challenge3_single_byte_xor_cipher() {
-    use rustopals::stream::SingleXORCipher;
+    use rustopals::prelude::*;
     use rustopals::util::iter::bytes_from_hex;
 
     const INPUT: &str = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
@@ -57,20 +58,15 @@ fn challenge3_single_byte_xor_cipher() {
 #[test]
 /// Detect single-character XOR - https://cryptopals.com/sets/1/challenges/4
 fn challenge4_detect_single_byte_xor() {
-    use rustopals::stream::SingleXORCipher;
-    use rustopals::util::iter::bytes_from_hex;
+    use rustopals::prelude::*;
+    use rustopals::util::fixtures::decode_hex_lines;
 
     const INPUT: &str = include_str!("4.txt");
     const EXPECTED_POS: usize = 170;
     const EXPECTED_KEY: u8 = 53;
     const EXPECTED_PLAINTEXT: &str = "Now that the party is jumping\n";
 
-    let input = INPUT
-        .lines()
-        .map(bytes_from_hex)
-        .map(Iterator::collect::<Result<Vec<_>, _>>)
-        .map(Result::unwrap)
-        .collect::<Vec<_>>();
+    let input = decode_hex_lines(INPUT).unwrap();
 
     let input_slices = input.iter().map(Vec::as_slice).collect::<Vec<_>>();
 
@@ -85,8 +81,7 @@ fn challenge4_detect_single_byte_xor() {
 #[test]
 /// Implement repeating-key XOR - https://cryptopals.com/sets/1/challenges/5
 fn challenge5_implement_repeating_key_xor() {
-    use rustopals::stream::{RepeatingXORCipher, StreamCipher};
-    use rustopals::util::iter::ToHexable;
+    use rustopals::prelude::*;
 
     const PLAINTEXT: &[u8] = b"Burning 'em, if you ain't quick and nimble
 I go crazy when I hear a cymbal";
@@ -101,7 +96,8 @@ I go crazy when I hear a cymbal";
 /// Break repeating-key XOR - https://cryptopals.com/sets/1/challenges/6
 #[test]
 fn challenge6_repeating_key_xor() {
-    use rustopals::stream::{RepeatingXORCipher, StreamCipher};
+    use rustopals::prelude::*;
+    use rustopals::util::fixtures::decode_base64_multiline;
 
     const CIPHERTEXT: &str = include_str!("6.txt");
     const MAX_KEYSIZE_GUESS: usize = 40;
@@ -109,14 +105,16 @@ fn challenge6_repeating_key_xor() {
     const EXPECTED_KEY: &[u8] = b"Terminator X: Bring the noise";
     const EXPECTED_PLAINTEXT: &[u8] = include_bytes!("6.solution.txt");
 
-    let ciphertext_no_newlines = CIPHERTEXT.lines().collect::<String>();
-
-    let ciphertext = ::base64::decode(&ciphertext_no_newlines).unwrap();
+    let ciphertext = decode_base64_multiline(CIPHERTEXT).unwrap();
 
     let guessed_keysize =
         RepeatingXORCipher::<u8>::guess_keysize(&ciphertext, MAX_KEYSIZE_GUESS).unwrap();
 
     assert_eq!(guessed_keysize, EXPECTED_KEY.len());
+    assert_eq!(
+        RepeatingXORCipher::rank_keysizes(&ciphertext, MAX_KEYSIZE_GUESS)[0].keysize,
+        EXPECTED_KEY.len()
+    );
 
     let guessed_key =
         RepeatingXORCipher::<u8>::guess_key(&NaiveTextScorer, &ciphertext, guessed_keysize);
@@ -132,7 +130,8 @@ fn challenge6_repeating_key_xor() {
 
 /// AES in ECB mode - https://cryptopals.com/sets/1/challenges/7
 mod challenge7_aes_ecb {
-    use rustopals::block::{BlockMode, AES128, ECB};
+    use rustopals::prelude::*;
+    use rustopals::util::fixtures::decode_base64_multiline;
 
     const CIPHERTEXT: &str = include_str!("7.txt");
     const PLAINTEXT: &[u8] = include_bytes!("7.solution.txt");
@@ -140,20 +139,18 @@ mod challenge7_aes_ecb {
 
     #[test]
     fn encrypt() {
-        let ciphertext_no_newlines = CIPHERTEXT.lines().collect::<String>();
-        let expected_ciphertext = ::base64::decode(&ciphertext_no_newlines).unwrap();
+        let expected_ciphertext = decode_base64_multiline(CIPHERTEXT).unwrap();
 
         assert_eq!(
-            ECB.encrypt_impl(&AES128, PLAINTEXT, KEY),
+            ECB.encrypt_impl(&AES128, PLAINTEXT, &Key::new(KEY).unwrap()),
             expected_ciphertext,
         );
     }
 
     #[test]
     fn decrypt() {
-        let ciphertext_no_newlines = CIPHERTEXT.lines().collect::<String>();
-        let ciphertext = ::base64::decode(&ciphertext_no_newlines).unwrap();
-        let decrypted = ECB.decrypt_impl(&AES128, &ciphertext, KEY);
+        let ciphertext = decode_base64_multiline(CIPHERTEXT).unwrap();
+        let decrypted = ECB.decrypt_impl(&AES128, &ciphertext, &Key::new(KEY).unwrap());
 
         assert_eq!(decrypted, PLAINTEXT);
     }
@@ -162,20 +159,17 @@ mod challenge7_aes_ecb {
 /// Detect AES in ECB mode - https://cryptopals.com/sets/1/challenges/8
 #[test]
 fn challenge8_detect_ecb() {
-    use rustopals::block::{BlockCipher, AES128, ECB};
-    use rustopals::util::iter::bytes_from_hex;
+    use rustopals::prelude::*;
+    use rustopals::util::fixtures::decode_hex_lines;
 
     const INPUT: &str = include_str!("8.txt");
     const EXPECTED_RESULT: usize = 132;
 
-    let (line_num, _) = INPUT
-        .lines()
+    let (line_num, _) = decode_hex_lines(INPUT)
+        .unwrap()
+        .iter()
         .enumerate()
-        .max_by_key(|&(_, line)| {
-            let bytes = bytes_from_hex(line).collect::<Result<Vec<_>, _>>().unwrap();
-
-            ECB::score(&bytes, AES128::BLOCK_SIZE)
-        })
+        .max_by_key(|&(_, bytes)| ECB::score(bytes, AES128::BLOCK_SIZE))
         .unwrap();
 
     assert_eq!(line_num, EXPECTED_RESULT);
@@ -1,69 +1,5 @@
 const UNKNOWN_STRING: &str = "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK";
 
-mod adversary {
-    use rustopals::block::{BlockCipher, BlockMode, AES128, ECB};
-
-    pub trait Encryptor {
-        fn encrypt(&self, plaintext: &[u8]) -> Vec<u8>;
-    }
-
-    pub struct EasyOracle {
-        key: Vec<u8>,
-    }
-
-    impl EasyOracle {
-        pub fn new() -> EasyOracle {
-            EasyOracle {
-                key: crate::gen_random_bytes(AES128::KEY_SIZE),
-            }
-        }
-    }
-
-    impl Encryptor for EasyOracle {
-        fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
-            let decoded_base64 = ::base64::decode(super::UNKNOWN_STRING).unwrap();
-
-            let extended_plaintext = plaintext
-                .iter()
-                .copied()
-                .chain(decoded_base64)
-                .collect::<Vec<_>>();
-
-            ECB.encrypt(&AES128, &extended_plaintext, &self.key)
-        }
-    }
-
-    pub struct HardOracle {
-        key: Vec<u8>,
-        pub prepend: Vec<u8>,
-    }
-
-    impl HardOracle {
-        pub fn new(min: usize, max: usize) -> HardOracle {
-            HardOracle {
-                key: crate::gen_random_bytes(AES128::KEY_SIZE),
-                prepend: crate::gen_random_bytes_between(min, max),
-            }
-        }
-    }
-
-    impl Encryptor for HardOracle {
-        fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
-            let decoded_base64 = ::base64::decode(super::UNKNOWN_STRING).unwrap();
-
-            let extended_plaintext = self
-                .prepend
-                .iter()
-                .copied()
-                .chain(plaintext.iter().copied())
-                .chain(decoded_base64)
-                .collect::<Vec<_>>();
-
-            ECB.encrypt(&AES128, &extended_plaintext, &self.key)
-        }
-    }
-}
-
 fn discover_block_size(oracle: impl Fn(&[u8]) -> Vec<u8>) -> Option<usize> {
     let pad_size = oracle(&[]).len();
 
@@ -83,82 +19,6 @@ fn discover_block_size(oracle: impl Fn(&[u8]) -> Vec<u8>) -> Option<usize> {
     None
 }
 
-fn discover_prepended_length(
-    oracle: impl Fn(&[u8]) -> Vec<u8>,
-    block_size: usize,
-) -> Option<usize> {
-    use rustopals::block::count_repeated;
-
-    use crate::gen_random_bytes;
-
-    let empty = oracle(&[]);
-    let repeats_in_empty = count_repeated(&empty, block_size);
-
-    let random_block = gen_random_bytes(block_size);
-
-    (0_usize..=empty.len())
-        .filter_map(|guessed_size| {
-            let mut guessed_bytes = gen_random_bytes(guessed_size);
-
-            for _ in 0_usize..100 {
-                guessed_bytes.extend_from_slice(&random_block);
-            }
-
-            let encrypted = oracle(&guessed_bytes);
-
-            let (repeats, idx) = max_continuous_repeated_blocks_and_idx(&encrypted, block_size);
-
-            /*println!(
-                "in find {} {} {} {}",
-                guessed_size,
-                repeats_in_empty + 2,
-                repeats,
-                idx
-            );*/
-
-            if repeats == 99 {
-                Some(if guessed_size > 0 {
-                    //println!("{:?}", encrypted.chunks(16).collect::<Vec<_>>());
-                    //println!("{} {} {}", idx, block_size, guessed_size);
-                    idx * block_size - guessed_size
-                } else {
-                    0
-                })
-            } else {
-                None
-            }
-        })
-        .last()
-}
-
-fn max_continuous_repeated_blocks_and_idx(input: &[u8], block_size: usize) -> (usize, usize) {
-    let mut contiguous_blocks = 0_usize;
-    let mut max_contiguous_blocks = 1_usize;
-
-    let mut last_start_idx = 0_usize;
-    let mut max_contiguous_idx = 0_usize;
-
-    let mut last_block = &input[..block_size];
-
-    for (idx, block) in input.chunks(block_size).enumerate().skip(1) {
-        if block == last_block {
-            contiguous_blocks += 1;
-
-            if contiguous_blocks > max_contiguous_blocks {
-                max_contiguous_blocks = contiguous_blocks;
-                max_contiguous_idx = last_start_idx;
-            }
-        } else {
-            last_start_idx = idx;
-            contiguous_blocks = 0;
-        }
-
-        last_block = block;
-    }
-
-    (max_contiguous_blocks, max_contiguous_idx)
-}
-
 fn discover_payload_length_without_padding(
     oracle: impl Fn(&[u8]) -> Vec<u8>,
     block_size: usize,
@@ -180,6 +40,7 @@ fn discover_payload_length_without_padding(
 }
 
 pub fn decrypt(oracle: impl Fn(&[u8]) -> Vec<u8>) -> Vec<u8> {
+    use rustopals::block::attacks::byte_at_a_time::{discover_prefix_len, QueryPlanner};
     use rustopals::block::Mode;
 
     let block_size = discover_block_size(|input| oracle(input)).unwrap();
@@ -190,46 +51,49 @@ pub fn decrypt(oracle: impl Fn(&[u8]) -> Vec<u8>) -> Vec<u8> {
 
     let payload_length =
         discover_payload_length_without_padding(|input| oracle(input), block_size).unwrap();
-    let prepeneded_length = discover_prepended_length(|input| oracle(input), block_size).unwrap();
-
-    let mut decrypted = Vec::new();
+    let prepended_length = discover_prefix_len(|input| oracle(input), block_size)
+        .prefix_len
+        .unwrap();
 
-    for decrypting_pos in prepeneded_length..payload_length {
-        let cur_block = decrypting_pos / block_size;
+    let mut planner = QueryPlanner::new(|input: &[u8]| oracle(input), block_size, prepended_length);
+    let decrypted = planner
+        .decrypt(payload_length - prepended_length)
+        .expect("Couldn't match!");
 
-        let plaintext = vec![0_u8; block_size - 1 - decrypting_pos % block_size];
-        let encrypted = oracle(&plaintext);
-        let block = &encrypted[cur_block * block_size..(cur_block + 1) * block_size];
+    // The textbook attack needs one call for the target block plus one per
+    // guess, trying them in order
+    let naive_calls = decrypted
+        .iter()
+        .map(|&byte| 2 + usize::from(byte))
+        .sum::<usize>();
 
-        for last_byte in 0_u8..=255 {
-            let new_plaintext = &[plaintext.clone(), decrypted.clone(), vec![last_byte]].concat();
-            let new_encrypted = oracle(new_plaintext);
-            let new_block = &new_encrypted[cur_block * block_size..(cur_block + 1) * block_size];
-
-            if new_block == block {
-                //println!("{:?}", block);
-                decrypted.push(last_byte);
-                break;
-            } else if last_byte == 255 {
-                panic!("Couldn't match!")
-            }
-        }
-    }
+    assert!(planner.calls() < naive_calls);
 
     decrypted
 }
 
 mod test {
-    use rustopals::block::{BlockCipher, AES128};
+    use rustopals::block::attacks::byte_at_a_time::discover_prefix_len;
+    use rustopals::insecure::oracles::{EcbSuffixOracle, EncryptionOracle};
+    use rustopals::prelude::*;
+    use rustopals::rand::{replayable_seed, with_seed};
+
+    const TEST_PREPEND: std::ops::RangeInclusive<usize> = 10..=31;
 
-    use super::adversary::Encryptor;
+    fn easy_oracle() -> EcbSuffixOracle {
+        EcbSuffixOracle::new(&base64::decode(super::UNKNOWN_STRING).unwrap(), 0..=0)
+    }
 
-    const TEST_MIN_PREPEND: usize = 10;
-    const TEST_MAX_PREPEND: usize = 32;
+    fn hard_oracle() -> EcbSuffixOracle {
+        EcbSuffixOracle::new(
+            &base64::decode(super::UNKNOWN_STRING).unwrap(),
+            TEST_PREPEND,
+        )
+    }
 
     #[test]
     fn test_discover_block_size_easy() {
-        let easy_oracle = super::adversary::EasyOracle::new();
+        let easy_oracle = easy_oracle();
         let easy_fn = |plaintext: &[u8]| easy_oracle.encrypt(plaintext);
 
         assert_eq!(
@@ -240,55 +104,41 @@ mod test {
 
     #[test]
     fn test_discover_block_size_hard() {
-        let hard_oracle = super::adversary::HardOracle::new(TEST_MIN_PREPEND, TEST_MAX_PREPEND);
+        let hard_oracle = hard_oracle();
         let hard_fn = |plaintext: &[u8]| hard_oracle.encrypt(plaintext);
 
         assert_eq!(
             super::discover_block_size(hard_fn),
-            Some(AES128::BLOCK_SIZE)
+            Some(AES128::BLOCK_SIZE),
+            "oracle state: {}",
+            hard_oracle.state_fingerprint()
         );
     }
 
-    /*
-
     #[test]
-    fn test_discover_prepended_length_repeated() {
-        use rustopals::block::pkcs7;
-
-        let random_bytes = &crate::gen_random_bytes(1024);
-
-        for i in 0..128 {
-            let oracle = |input: &[u8]| {
-                let value = [&vec![0_u8; i], input, random_bytes].concat();
-
-                pkcs7::pad(&value, 16)
-            };
-
-            assert_eq!(super::discover_prepended_length(oracle, 16), Some(i));
-        }
-    }
-
-    */
-
-    #[test]
-    fn test_discover_prepended_length_easy() {
-        let easy_oracle = super::adversary::EasyOracle::new();
+    fn test_discover_prefix_len_easy() {
+        let easy_oracle = easy_oracle();
         let easy_fn = |plaintext: &[u8]| easy_oracle.encrypt(plaintext);
 
-        assert_eq!(super::discover_prepended_length(easy_fn, 16), Some(0));
+        let discovery = discover_prefix_len(easy_fn, 16);
+
+        assert_eq!(discovery.prefix_len, Some(0));
+        assert!((discovery.confidence - 1.0).abs() < f64::EPSILON);
     }
 
     #[test]
-    fn test_discover_prepended_length_hard() {
-        let hard_oracle = super::adversary::HardOracle::new(TEST_MIN_PREPEND, TEST_MAX_PREPEND);
-        let hard_fn = |plaintext: &[u8]| hard_oracle.encrypt(plaintext);
+    fn test_discover_prefix_len_hard() {
+        with_seed(replayable_seed(), || {
+            let hard_oracle = hard_oracle();
+            let hard_fn = |plaintext: &[u8]| hard_oracle.encrypt(plaintext);
 
-        //println!("real prepend = {}", hard_oracle.prepend.len());
-
-        assert_eq!(
-            super::discover_prepended_length(hard_fn, 16),
-            Some(hard_oracle.prepend.len())
-        );
+            assert_eq!(
+                discover_prefix_len(hard_fn, 16).prefix_len,
+                Some(hard_oracle.prefix_len()),
+                "oracle state: {}",
+                hard_oracle.state_fingerprint()
+            );
+        });
     }
 
     #[test]
@@ -310,7 +160,7 @@ mod test {
 
     #[test]
     fn test_discover_payload_length_without_padding_easy() {
-        let easy_oracle = super::adversary::EasyOracle::new();
+        let easy_oracle = easy_oracle();
         let easy_fn = |plaintext: &[u8]| easy_oracle.encrypt(plaintext);
 
         assert_eq!(
@@ -321,18 +171,20 @@ mod test {
 
     #[test]
     fn test_discover_payload_length_without_padding_hard() {
-        let hard_oracle = super::adversary::HardOracle::new(TEST_MIN_PREPEND, TEST_MAX_PREPEND);
+        let hard_oracle = hard_oracle();
         let hard_fn = |plaintext: &[u8]| hard_oracle.encrypt(plaintext);
 
         assert_eq!(
             super::discover_payload_length_without_padding(hard_fn, 16),
-            Some(138 + hard_oracle.prepend.len())
+            Some(138 + hard_oracle.prefix_len()),
+            "oracle state: {}",
+            hard_oracle.state_fingerprint()
         );
     }
 
     #[test]
     fn test_decrypt_easy() {
-        let easy_oracle = super::adversary::EasyOracle::new();
+        let easy_oracle = easy_oracle();
         let easy_fn = |plaintext: &[u8]| easy_oracle.encrypt(plaintext);
 
         assert_eq!(
@@ -343,12 +195,43 @@ mod test {
 
     #[test]
     fn test_decrypt_hard() {
-        let hard_oracle = super::adversary::HardOracle::new(TEST_MIN_PREPEND, TEST_MAX_PREPEND);
-        let hard_fn = |plaintext: &[u8]| hard_oracle.encrypt(plaintext);
+        with_seed(replayable_seed(), || {
+            let hard_oracle = hard_oracle();
+            let hard_fn = |plaintext: &[u8]| hard_oracle.encrypt(plaintext);
 
-        assert_eq!(
-            super::decrypt(hard_fn),
-            base64::decode(super::UNKNOWN_STRING).unwrap(),
-        );
+            assert_eq!(
+                super::decrypt(hard_fn),
+                base64::decode(super::UNKNOWN_STRING).unwrap(),
+                "oracle state: {}",
+                hard_oracle.state_fingerprint()
+            );
+        });
+    }
+
+    #[test]
+    fn test_decrypt_transcript() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        use rustopals::util::transcript::recorded;
+
+        let secret = base64::decode(super::UNKNOWN_STRING).unwrap();
+
+        let run = || {
+            let oracle =
+                EcbSuffixOracle::with_rng(&secret, TEST_PREPEND, &mut StdRng::seed_from_u64(1337));
+
+            recorded(
+                |plaintext: &[u8]| oracle.encrypt(plaintext),
+                |oracle: &dyn Fn(&[u8]) -> Vec<u8>| super::decrypt(oracle),
+            )
+        };
+
+        let (decrypted, commitment) = run();
+
+        assert_eq!(decrypted, secret);
+        assert_eq!(run().1, commitment);
+
+        // About one call per byte (see `QueryPlanner`), plus discovery
+        assert!(commitment.queries < 2 * secret.len() as u64);
     }
 }
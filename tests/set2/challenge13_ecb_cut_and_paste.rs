@@ -1,17 +1,17 @@
 mod adversary {
-    use std::collections::HashMap;
+    use rustopals::prelude::*;
+    use rustopals::util::kv;
 
-    use rustopals::block::{BlockCipher, BlockMode, AES128, ECB};
+    /// Fields of a user profile, in order (`email` is filled in).
+    pub const PROFILE_LAYOUT: &[(&str, &str)] = &[("email", ""), ("uid", "10"), ("role", "user")];
 
     pub struct LoginSystem {
-        key: Vec<u8>,
+        key: Key<AES128>,
     }
 
     impl LoginSystem {
         pub fn new() -> LoginSystem {
-            LoginSystem {
-                key: crate::gen_random_bytes(AES128::BLOCK_SIZE),
-            }
+            LoginSystem { key: Key::random() }
         }
 
         pub fn generate_payload(&self, email: &str) -> Vec<u8> {
@@ -23,7 +23,7 @@ mod adversary {
 
             match ECB.decrypt(&AES128, payload, &self.key) {
                 Ok(decrypted) => match str::from_utf8(&decrypted) {
-                    Ok(string) => parse(string)["role"] == "admin",
+                    Ok(string) => kv::parse(string).get("role") == Some(&"admin"),
                     Err(_) => false,
                 },
                 Err(_) => false,
@@ -31,42 +31,17 @@ mod adversary {
         }
     }
 
-    /// Parse a querystring-like string into a `K -> V` mapping.
-    fn parse(data: &str) -> HashMap<&str, &str> {
-        data.split('&')
-            .map(|part| {
-                let pair = part.splitn(2, '=').collect::<Vec<_>>();
-                (pair[0], if pair.len() == 2 { pair[1] } else { "true" })
-            })
-            .collect()
-    }
-
     /// Build a user profile for a certain email.
     fn profile_for(email: &str) -> String {
-        let sanitized = email.replace("=", "").replace("&", "");
-
-        ["email=", &sanitized, "&uid=10&role=user"].concat()
+        kv::encode(
+            PROFILE_LAYOUT
+                .iter()
+                .map(|&(key, value)| (key, if key == "email" { email } else { value })),
+        )
     }
 
     #[cfg(test)]
     mod test {
-        #[test]
-        fn parse() {
-            use std::collections::HashMap;
-
-            let expected = [
-                ("foo", "bar"),
-                ("baz", "qux"),
-                ("zap", "zazzle"),
-                ("inga", "true"),
-            ]
-            .iter()
-            .copied()
-            .collect::<HashMap<_, _>>();
-
-            assert_eq!(super::parse("foo=bar&baz=qux&zap=zazzle&inga"), expected)
-        }
-
         #[test]
         fn profile_for() {
             assert_eq!(
@@ -87,23 +62,16 @@ mod adversary {
  */
 
 fn crack(login: &adversary::LoginSystem) -> Vec<u8> {
-    // CUT
-    // 0123456789abcdef0123456789abcdef0123456789abcdef
-    //                 ----------------
-    // email=0000000000admin___________&uid=10&role=user (where _ is pkcs7 padding)
-    let admin =
-        login.generate_payload("0000000000admin\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b\x0b");
-    let admin_bytes = &admin[0x10..0x20];
-
-    // PASTE
-    // 0123456789abcdef0123456789abcdef0123456789abcdef
-    //                                 ----------------
-    // email=trust@mee.com&uid=10&role=user____________
-    let mut my_email = login.generate_payload("trust@mee.com");
-    my_email.truncate(0x20);
-    my_email.extend_from_slice(admin_bytes);
-
-    my_email
+    use rustopals::block::attacks::cut_and_paste::forge_field;
+
+    forge_field(
+        |email| login.generate_payload(email),
+        adversary::PROFILE_LAYOUT,
+        "email",
+        "role",
+        "admin",
+    )
+    .unwrap()
 }
 
 #[cfg(test)]
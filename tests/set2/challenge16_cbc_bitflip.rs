@@ -1,18 +1,17 @@
 mod adversary {
-    use rustopals::block::{BlockCipher, BlockMode, AES128, CBC};
+    use rustopals::block::params::{Iv, Key};
+    use rustopals::prelude::*;
 
     pub struct LoginSystem {
-        key: Vec<u8>,
-        iv: Vec<u8>,
+        key: Key<AES128>,
+        iv: Iv<AES128>,
     }
 
     impl LoginSystem {
         pub fn new() -> LoginSystem {
-            use crate::gen_random_bytes;
-
             LoginSystem {
-                key: gen_random_bytes(AES128::KEY_SIZE),
-                iv: gen_random_bytes(AES128::BLOCK_SIZE),
+                key: Key::random(),
+                iv: Iv::random(),
             }
         }
 
@@ -46,18 +45,29 @@ mod adversary {
     }
 }
 
+use rustopals::block::attacks::bitflip::{make_bitflip_mask, FlipMode};
+
+const PREFIX_LEN: usize = "comment1=cooking%20MCs;userdata=".len();
+const SUFFIX_LEN: usize = ";comment2=%20like%20a%20pound%20of%20bacon".len();
+
 fn crack(login: &adversary::LoginSystem) -> Vec<u8> {
     // 0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef0123456
     // comment1=cooking%20MCs;userdata=0123456789abcdef0123456789a;comment2...
     //                                 ----------------;admin=true
     //                                 0-----6---------!admin!true
 
-    let mut payload = login.generate_payload("0123456789abcdef!admin!true");
+    const USERDATA: &str = "0123456789abcdef!admin!true";
 
-    payload[0x20] ^= b';' ^ b'!';
-    payload[0x20 + 0x06] ^= b'=' ^ b'!';
+    let plan = make_bitflip_mask(
+        FlipMode::CBC,
+        &[0x30, 0x30 + 0x06],
+        &[b';' ^ b'!', b'=' ^ b'!'],
+        16,
+        PREFIX_LEN + USERDATA.len() + SUFFIX_LEN,
+    )
+    .unwrap();
 
-    payload
+    plan.apply(&login.generate_payload(USERDATA))
 }
 
 mod test {
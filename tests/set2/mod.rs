@@ -12,40 +12,46 @@ fn challenge9_pkcs7_padding() {
 
 /// Implement CBC mode - https://cryptopals.com/sets/2/challenges/10
 mod challenge10_cbc_mode {
-    use rustopals::block::{BlockMode, AES128, CBC};
+    use rustopals::prelude::*;
+    use rustopals::util::fixtures::decode_base64_multiline;
 
     const CIPHERTEXT: &str = include_str!("10.txt");
     const PLAINTEXT: &[u8] = include_bytes!("10.solution.txt");
     const KEY: &[u8] = b"YELLOW SUBMARINE";
     const IV: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
 
+    fn cbc() -> CBC<AES128> {
+        CBC::new(&Iv::new(IV).unwrap())
+    }
+
+    fn key() -> Key<AES128> {
+        Key::new(KEY).unwrap()
+    }
+
     #[test]
     fn encrypt() {
-        let ciphertext_no_newlines = CIPHERTEXT.lines().collect::<String>();
-        let expected_ciphertext = ::base64::decode(&ciphertext_no_newlines).unwrap();
+        let expected_ciphertext = decode_base64_multiline(CIPHERTEXT).unwrap();
 
         assert_eq!(
-            CBC::new(IV).encrypt_impl(&AES128, PLAINTEXT, KEY,),
+            cbc().encrypt_impl(&AES128, PLAINTEXT, &key()),
             expected_ciphertext,
         );
     }
 
     #[test]
     fn decrypt() {
-        let ciphertext_no_newlines = CIPHERTEXT.lines().collect::<String>();
-        let ciphertext = base64::decode(&ciphertext_no_newlines).unwrap();
+        let ciphertext = decode_base64_multiline(CIPHERTEXT).unwrap();
 
-        assert_eq!(
-            CBC::new(IV).decrypt_impl(&AES128, &ciphertext, KEY),
-            PLAINTEXT,
-        );
+        assert_eq!(cbc().decrypt_impl(&AES128, &ciphertext, &key()), PLAINTEXT,);
     }
 }
 
 /// An ECB/CBC detection oracle - https://cryptopals.com/sets/2/challenges/11
 mod challenge11_ecb_cbc_detection_oracle {
+    use rand::Rng;
     use rustopals::block;
-    use rustopals::block::{BlockCipher, BlockMode, AES128, CBC, ECB};
+    use rustopals::prelude::*;
+    use rustopals::rand::{replayable_seed, rng, with_seed};
 
     /// An oracle as required by https://cryptopals.com/sets/2/ but snitching its chosen cipher mode
     fn snitch_oracle(plaintext: &[u8]) -> (block::Mode, Vec<u8>) {
@@ -61,16 +67,16 @@ mod challenge11_ecb_cbc_detection_oracle {
             .collect::<Vec<_>>();
 
         // Generate random key
-        let key = crate::gen_random_bytes(AES128::KEY_SIZE);
+        let key = Key::random();
 
         // Choose randomly between ECB and CBC
-        if rand::random::<bool>() {
+        if rng().gen::<bool>() {
             (
                 block::Mode::ECB,
                 ECB.encrypt(&AES128, &extended_plaintext, &key),
             )
         } else {
-            let iv = crate::gen_random_bytes(AES128::BLOCK_SIZE);
+            let iv = Iv::random();
 
             (
                 block::Mode::CBC,
@@ -83,22 +89,24 @@ mod challenge11_ecb_cbc_detection_oracle {
     fn detect_mode() {
         const TEST_TIMES: usize = 100;
 
-        for _ in 0..TEST_TIMES {
-            let mut snitched_mode = None;
+        with_seed(replayable_seed(), || {
+            for _ in 0..TEST_TIMES {
+                let mut snitched_mode = None;
 
-            let detected_mode = {
-                // Wrap oracle into the expected signature, saving the snitched cipher mode
-                let wrapper = |plaintext: &[u8]| {
-                    let (mode, ciphertext) = snitch_oracle(plaintext);
-                    snitched_mode = Some(mode);
-                    ciphertext
-                };
+                let detected_mode = {
+                    // Wrap oracle into the expected signature, saving the snitched cipher mode
+                    let wrapper = |plaintext: &[u8]| {
+                        let (mode, ciphertext) = snitch_oracle(plaintext);
+                        snitched_mode = Some(mode);
+                        ciphertext
+                    };
 
-                block::Mode::detect(wrapper, AES128::BLOCK_SIZE)
-            };
+                    block::Mode::detect(wrapper, AES128::BLOCK_SIZE)
+                };
 
-            assert_eq!(detected_mode, snitched_mode.unwrap());
-        }
+                assert_eq!(detected_mode, snitched_mode.unwrap());
+            }
+        });
     }
 }
 
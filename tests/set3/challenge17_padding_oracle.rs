@@ -1,46 +1,10 @@
-use rustopals::block::{BlockCipher, AES128};
+use rustopals::insecure::oracles::CbcPaddingOracle;
+use rustopals::prelude::*;
 
 const STRINGS: &str = include_str!("17.txt");
 
-mod adversary {
-    use rustopals::block::{BlockCipher, BlockMode, AES128, CBC};
-    use rustopals::util::generate_bytes;
-
-    pub struct PaddingOracle {
-        key: Vec<u8>,
-    }
-
-    impl PaddingOracle {
-        pub fn new() -> PaddingOracle {
-            PaddingOracle {
-                key: generate_bytes(AES128::BLOCK_SIZE),
-            }
-        }
-
-        pub fn get_string(&self) -> (Vec<u8>, Vec<u8>) {
-            use rand::seq::IteratorRandom;
-            use rand::thread_rng;
-
-            let mut rng = thread_rng();
-            let chosen_string = super::STRINGS.lines().choose(&mut rng).unwrap();
-
-            let iv = generate_bytes(AES128::BLOCK_SIZE);
-
-            let encrypted =
-                CBC::new(&iv).encrypt(&AES128, &base64::decode(chosen_string).unwrap(), &self.key);
-
-            (encrypted, iv)
-        }
-
-        pub fn is_correct_padding(&self, ciphertext: &[u8], iv: &[u8]) -> bool {
-            CBC::new(iv).decrypt(&AES128, ciphertext, &self.key).is_ok()
-        }
-    }
-}
-
-fn decrypt_block(oracle: &adversary::PaddingOracle, block: &[u8], iv: &[u8]) -> Vec<u8> {
+fn decrypt_block(oracle: &CbcPaddingOracle, block: &[u8], iv: &[u8]) -> Vec<u8> {
     use num_traits::Bounded;
-    use rustopals::util::iter::Xorable;
 
     let mut known: Vec<u8> = vec![];
 
@@ -60,7 +24,7 @@ fn decrypt_block(oracle: &adversary::PaddingOracle, block: &[u8], iv: &[u8]) ->
 
             let my_iv = my_block.iter().xor(iv.iter()).collect::<Vec<u8>>();
 
-            if oracle.is_correct_padding(block, &my_iv) {
+            if oracle.is_valid_padding(block, &my_iv) {
                 if !known.is_empty() {
                     known.insert(0, possible_byte ^ padding_len_goal);
                     continue 'next;
@@ -73,13 +37,17 @@ fn decrypt_block(oracle: &adversary::PaddingOracle, block: &[u8], iv: &[u8]) ->
 
                     let masked_iv = my_iv.iter().xor(mask.iter()).collect::<Vec<u8>>();
 
-                    let result = oracle.is_correct_padding(block, &masked_iv);
+                    let result = oracle.is_valid_padding(block, &masked_iv);
 
                     if !result {
-                        let length = (AES128::BLOCK_SIZE - corrupt_len + 1) as u8;
-                        for _ in 0..length {
-                            known.insert(0, possible_byte ^ length);
-                        }
+                        let length = AES128::BLOCK_SIZE - corrupt_len + 1;
+
+                        // Only the last byte was guessed, the rest are unmasked
+                        known = my_block[AES128::BLOCK_SIZE - length..]
+                            .iter()
+                            .map(|byte| byte ^ length as u8)
+                            .collect();
+
                         continue 'next;
                     }
                 }
@@ -93,11 +61,17 @@ fn decrypt_block(oracle: &adversary::PaddingOracle, block: &[u8], iv: &[u8]) ->
 mod test {
     #[test]
     fn padding_oracle() {
-        use rustopals::block::{pkcs7, BlockCipher, AES128};
+        use rand::seq::SliceRandom;
+        use rustopals::block::pkcs7;
+        use rustopals::insecure::oracles::CbcPaddingOracle;
+        use rustopals::prelude::*;
+        use rustopals::rand::rng;
+        use rustopals::util::fixtures::decode_base64_lines;
 
-        let oracle = super::adversary::PaddingOracle::new();
+        let strings = decode_base64_lines(super::STRINGS).unwrap();
+        let oracle = CbcPaddingOracle::new();
 
-        let (encrypted, iv) = oracle.get_string();
+        let (encrypted, iv) = oracle.encrypt(strings.choose(&mut rng()).unwrap());
 
         let blocks = encrypted.chunks(AES128::BLOCK_SIZE).collect::<Vec<_>>();
 
@@ -113,13 +87,43 @@ mod test {
 
         let unpadded = pkcs7::unpad(&bytes, AES128::BLOCK_SIZE as u8).unwrap();
 
-        let in_string = super::STRINGS
-            .lines()
-            .map(|x| base64::decode(x).unwrap())
-            .find(|x| x == unpadded);
+        let in_string = strings.into_iter().find(|x| x == unpadded);
 
         if in_string == None {
             panic!("Decrypted is not found in STRINGS")
         }
     }
+
+    /// Plaintexts ending in (almost) valid padding, where the first guessed
+    /// byte for a block can itself yield valid padding.
+    #[test]
+    fn padding_oracle_edge_cases() {
+        use rustopals::block::pkcs7::{self, Corruption};
+        use rustopals::insecure::oracles::CbcPaddingOracle;
+        use rustopals::prelude::*;
+
+        let oracle = CbcPaddingOracle::new();
+
+        for corruption in Corruption::ALL {
+            for payload in [&b""[..], b"YELLOW", b"YELLOW SUBMARIN"] {
+                let plaintext = pkcs7::corrupt(payload, AES128::BLOCK_SIZE as u8, corruption);
+                let (encrypted, iv) = oracle.encrypt(&plaintext);
+
+                let mut ivs = vec![iv.as_slice()];
+                ivs.extend(encrypted.chunks(AES128::BLOCK_SIZE));
+
+                let bytes = encrypted
+                    .chunks(AES128::BLOCK_SIZE)
+                    .zip(ivs)
+                    .map(|(block, iv)| super::decrypt_block(&oracle, block, iv))
+                    .collect::<Vec<_>>()
+                    .concat();
+
+                assert_eq!(
+                    pkcs7::unpad(&bytes, AES128::BLOCK_SIZE as u8),
+                    Ok(&plaintext[..])
+                );
+            }
+        }
+    }
 }
@@ -1,23 +1,26 @@
+use rustopals::util::fixtures::decode_base64_lines;
+
 mod adversary {
-    use rustopals::block::{BlockCipher, AES128};
-    use rustopals::stream::{StreamCipher, CTR};
-    use rustopals::util;
+    use rustopals::prelude::*;
+    use rustopals::stream::ctr::CtrContext;
 
     pub struct Encryptor {
-        key: Vec<u8>,
+        context: CtrContext<'static, AES128>,
     }
 
     impl Encryptor {
         pub fn new() -> Encryptor {
             Encryptor {
-                key: util::generate_bytes(AES128::BLOCK_SIZE),
+                context: CtrContext::new(&AES128, &Key::random()),
             }
         }
 
         pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
             let nonce = vec![0; AES128::BLOCK_SIZE / 2];
 
-            CTR::from_nonce(&AES128, &self.key, &nonce)
+            self.context
+                .with_nonce(&nonce)
+                .unwrap()
                 .process(plaintext)
                 .collect()
         }
@@ -44,10 +47,7 @@ Points for automating this, but part of the reason I'm having you do this is tha
 fn crack_substitutions_19() {
     const STRINGS: &str = include_str!("19.txt");
 
-    let strings = STRINGS
-        .lines()
-        .map(|x| base64::decode(x).unwrap())
-        .collect::<Vec<_>>();
+    let strings = decode_base64_lines(STRINGS).unwrap();
 
     let encryptor = adversary::Encryptor::new();
 
@@ -73,10 +73,7 @@ Solve the resulting concatenation of ciphertexts as if for repeating- key XOR, w
 fn crack_statistically_20() {
     const STRINGS: &str = include_str!("20.txt");
 
-    let strings = STRINGS
-        .lines()
-        .map(|x| base64::decode(x).unwrap())
-        .collect::<Vec<_>>();
+    let strings = decode_base64_lines(STRINGS).unwrap();
 
     let encryptor = adversary::Encryptor::new();
 
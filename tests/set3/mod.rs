@@ -4,18 +4,20 @@ mod challenge17_padding_oracle;
 /// Implement CTR, the stream cipher mode - http://cryptopals.com/sets/3/challenges/18
 #[test]
 fn challenge18_implement_ctr() {
-    use rustopals::block::AES128;
-    use rustopals::stream::{StreamCipher, CTR};
+    use rustopals::prelude::*;
 
     const BASE64_INPUT: &str =
         "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==";
-    const KEY: &str = "YELLOW SUBMARINE";
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
     const NONCE: &[u8] = &[0, 0, 0, 0, 0, 0, 0, 0];
     const EXPECTED: &str = "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ";
 
     let input = base64::decode(BASE64_INPUT).unwrap();
 
-    let result = CTR::from_nonce(&AES128, KEY.as_bytes(), NONCE)
+    let key = Key::new(KEY).unwrap();
+    let nonce = Nonce::new(NONCE).unwrap();
+
+    let result = CTR::from_nonce(&AES128, &key, &nonce)
         .process(input)
         .collect::<Vec<_>>();
 
@@ -82,33 +84,36 @@ fn challenge21_mt19937() {
 /// Crack an MT19937 seed - http://cryptopals.com/sets/3/challenges/22
 mod challenge22_crack_mt19937_seed {
     use rand::Rng;
+    use rustopals::prelude::*;
     use rustopals::rand::MT19937;
-    use rustopals::util::get_unix_time;
+    use rustopals::util::clock::MockClock;
+
+    const STARTING_TIME: u64 = 1_600_000_000;
 
-    fn get_random() -> (u64, u32) {
+    /// Seed from `clock`, then let a random amount of time pass.
+    fn get_random(clock: &MockClock) -> u32 {
         use rand::distributions::uniform::SampleRange;
         use rand::thread_rng;
 
-        let starting_time = get_unix_time();
+        let mut rng = MT19937::new_from_clock(clock);
 
-        let mut rng = thread_rng();
-        let wait_secs = (40_u64..1000).sample_single(&mut rng);
+        let wait_secs = (40_u64..1000).sample_single(&mut thread_rng());
+        clock.advance(wait_secs);
 
-        let mut rng = MT19937::new(starting_time as u32);
-
-        (starting_time + wait_secs, rng.gen())
+        rng.gen()
     }
 
     #[test]
     fn crack() {
-        let (now, rand) = get_random();
+        let clock = MockClock::new(STARTING_TIME);
+        let rand = get_random(&clock);
 
-        let mut i = now as u32;
+        let mut i = clock.unix_time() as u32;
         while i > 0 {
             let mut rng = MT19937::new(i);
 
             if rng.gen::<u32>() == rand {
-                println!("{}", i);
+                assert_eq!(u64::from(i), STARTING_TIME);
                 return;
             }
 
@@ -117,6 +122,22 @@ mod challenge22_crack_mt19937_seed {
 
         unreachable!();
     }
+
+    #[test]
+    fn crack_with_table() {
+        use rustopals::rand::seed_table::{SeedTable, SeedTableParams};
+
+        // Every timestamp in the hour before and after the seed was taken
+        let table = SeedTable::build(
+            STARTING_TIME as u32 - 3600..STARTING_TIME as u32 + 3600,
+            SeedTableParams::default(),
+        );
+
+        let clock = MockClock::new(STARTING_TIME);
+        let rand = get_random(&clock);
+
+        assert_eq!(table.lookup(rand), vec![STARTING_TIME as u32]);
+    }
 }
 
 /// Clone an MT19937 RNG from its output - http://cryptopals.com/sets/3/challenges/23
@@ -150,16 +171,48 @@ fn challenge23_clone_mt19937_state() {
     ));
 }
 
+// Same, for the 64-bit variant
+#[test]
+fn challenge23_clone_mt19937_64_state() {
+    use rand::distributions::Standard;
+    use rand::{thread_rng, Rng, SeedableRng};
+    use rustopals::rand::MT19937_64;
+
+    const TAKE: usize = 10;
+    const TAP_LENGTH: usize = 312;
+
+    let rng_original: MT19937_64 = SeedableRng::from_seed(thread_rng().gen());
+    let rng_clone = rng_original.clone();
+
+    let tapped = rng_original
+        .sample_iter(&Standard)
+        .take(TAP_LENGTH)
+        .collect::<Vec<u64>>();
+
+    let rng_from_tap: MT19937_64 = MT19937_64::from_tap(&tapped);
+
+    assert!(Iterator::eq(
+        rng_clone
+            .sample_iter::<u64, &Standard>(&Standard)
+            .skip(TAP_LENGTH)
+            .take(TAKE),
+        rng_from_tap
+            .sample_iter::<u64, &Standard>(&Standard)
+            .take(TAKE)
+    ));
+}
+
 // Create the MT19937 stream cipher and break it - http://cryptopals.com/sets/3/challenges/24
 mod challenge24_break_mt19937_stream_cipher {
     use rand::distributions::Standard;
     use rand::Rng;
+    use rustopals::prelude::*;
     use rustopals::rand::MT19937;
-    use rustopals::stream::{StreamCipher, RNG};
-    use rustopals::util::get_unix_time;
+    use rustopals::stream::RNG;
+    use rustopals::util::clock::SystemClock;
 
-    fn generate_reset_token() -> Vec<u8> {
-        let time = get_unix_time() % 0xffff;
+    fn generate_reset_token(clock: &impl Clock) -> Vec<u8> {
+        let time = clock.unix_time() % 0xffff;
 
         MT19937::new(time as u32)
             .sample_iter(&Standard)
@@ -200,7 +253,7 @@ mod challenge24_break_mt19937_stream_cipher {
 
     #[test]
     fn detect_token() {
-        let token = generate_reset_token();
+        let token = generate_reset_token(&SystemClock);
 
         for guessed_seed in u16::min_value()..=u16::max_value() {
             let guessed_token = MT19937::new(u32::from(guessed_seed))
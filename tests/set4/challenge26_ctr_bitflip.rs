@@ -1,19 +1,17 @@
 mod adversary {
-    use rustopals::block::{BlockCipher, AES128};
-    use rustopals::stream::{StreamCipher, CTR};
+    use rustopals::block::params::{Key, Nonce};
+    use rustopals::prelude::*;
 
     pub struct LoginSystem {
-        key: Vec<u8>,
-        nonce: Vec<u8>,
+        key: Key<AES128>,
+        nonce: Nonce<AES128>,
     }
 
     impl LoginSystem {
         pub fn new() -> LoginSystem {
-            use crate::gen_random_bytes;
-
             LoginSystem {
-                key: gen_random_bytes(AES128::KEY_SIZE),
-                nonce: gen_random_bytes(AES128::BLOCK_SIZE),
+                key: Key::random(),
+                nonce: Nonce::random(),
             }
         }
 
@@ -48,18 +46,29 @@ mod adversary {
     }
 }
 
+use rustopals::block::attacks::bitflip::{make_bitflip_mask, FlipMode};
+
+const PREFIX_LEN: usize = "comment1=cooking%20MCs;userdata=".len();
+const SUFFIX_LEN: usize = ";comment2=%20like%20a%20pound%20of%20bacon".len();
+
 fn crack(login: &adversary::LoginSystem) -> Vec<u8> {
     // 0123456789abcdef0123456789abcdef0123456789abcdef0123456
     // comment1=cooking%20MCs;userdata=0123456789a;comment2...
     //                                 ;admin=true
     //                                 !admin!true
 
-    let mut payload = login.generate_payload("!admin!true");
+    const USERDATA: &str = "!admin!true";
 
-    payload[0x20] ^= b';' ^ b'!';
-    payload[0x20 + 0x06] ^= b'=' ^ b'!';
+    let plan = make_bitflip_mask(
+        FlipMode::CTR,
+        &[0x20, 0x20 + 0x06],
+        &[b';' ^ b'!', b'=' ^ b'!'],
+        16,
+        PREFIX_LEN + USERDATA.len() + SUFFIX_LEN,
+    )
+    .unwrap();
 
-    payload
+    plan.apply(&login.generate_payload(USERDATA))
 }
 
 mod test {
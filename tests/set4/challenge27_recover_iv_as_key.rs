@@ -1,4 +1,4 @@
-use rustopals::block::{BlockCipher, BlockMode, PKCS7Error, AES128, CBC};
+use rustopals::prelude::*;
 
 pub enum AdversaryError {
     PKCS7Error(PKCS7Error),
@@ -6,24 +6,25 @@ pub enum AdversaryError {
 }
 
 pub struct Adversary {
-    pub key: Vec<u8>,
+    pub key: Key<AES128>,
 }
 
 impl Adversary {
     pub fn new() -> Adversary {
-        use crate::gen_random_bytes;
+        Adversary { key: Key::random() }
+    }
 
-        Adversary {
-            key: gen_random_bytes(AES128::KEY_SIZE),
-        }
+    /// The key, reused as IV.
+    fn iv(&self) -> Iv<AES128> {
+        Iv::new(&self.key).unwrap()
     }
 
     pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
-        CBC::new(&self.key).encrypt(&AES128, plaintext, &self.key)
+        CBC::new(&self.iv()).encrypt(&AES128, plaintext, &self.key)
     }
 
     pub fn decrypt(&self, ciphertext: &[u8]) -> Result<(), AdversaryError> {
-        match CBC::new(&self.key).decrypt(&AES128, ciphertext, &self.key) {
+        match CBC::new(&self.iv()).decrypt(&AES128, ciphertext, &self.key) {
             Ok(plaintext) => {
                 if plaintext.iter().any(|&x| x > 127) {
                     Err(AdversaryError::ASCIIError(plaintext))
@@ -39,8 +40,7 @@ impl Adversary {
 mod test {
     use std::iter::repeat;
 
-    use rustopals::block::{BlockCipher, AES128};
-    use rustopals::util::iter::Xorable;
+    use rustopals::prelude::*;
 
     #[test]
     fn test_crack() {
@@ -71,7 +71,7 @@ mod test {
 
                     let recovered_key = p1.xor(p3).collect::<Vec<_>>();
 
-                    assert_eq!(recovered_key, adversary.key);
+                    assert_eq!(recovered_key, &*adversary.key);
                     return;
                 },
                 _ => continue,
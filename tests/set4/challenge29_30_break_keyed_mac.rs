@@ -1,12 +1,12 @@
-use rustopals::digest::{Digest, ExtensibleDigest, MD4, SHA1};
+use rustopals::prelude::*;
 
 const TARGET: &[u8] = b";admin=true";
 
 mod adversary {
     use std::marker::PhantomData;
 
-    use rustopals::digest::Digest;
-    use rustopals::mac::bad_mac;
+    use rustopals::insecure::bad_mac;
+    use rustopals::prelude::*;
 
     use super::TARGET;
 
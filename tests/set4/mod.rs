@@ -1,14 +1,14 @@
 /// Break "random access read/write" AES CTR - https://cryptopals.com/sets/4/challenges/25
 mod challenge25_break_random_access_aes_ctr {
-    use rustopals::block::{BlockCipher, AES128};
-    use rustopals::stream::{SeekableStreamCipher, StreamCipher, CTR};
+    use rustopals::prelude::*;
+    use rustopals::util::fixtures::decode_base64_multiline;
 
     const PLAINTEXT: &str = include_str!("25.txt");
 
     fn edit(
         ciphertext: &[u8],
-        key: &[u8],
-        nonce: &[u8],
+        key: &Key<AES128>,
+        nonce: &Nonce<AES128>,
         offset: usize,
         new_plaintext: &[u8],
     ) -> Vec<u8> {
@@ -24,10 +24,9 @@ mod challenge25_break_random_access_aes_ctr {
 
     #[test]
     fn crack() {
-        let plaintext_no_newlines = PLAINTEXT.lines().collect::<String>();
-        let plaintext = base64::decode(plaintext_no_newlines).unwrap();
-        let key = crate::gen_random_bytes(AES128::KEY_SIZE);
-        let nonce = crate::gen_random_bytes(8);
+        let plaintext = decode_base64_multiline(PLAINTEXT).unwrap();
+        let key = Key::random();
+        let nonce = Nonce::random();
         let ciphertext = CTR::from_nonce(&AES128, &key, &nonce)
             .process(&plaintext)
             .collect::<Vec<_>>();
@@ -60,8 +59,9 @@ mod challenge27_recover_iv_as_key;
 // Implement a SHA-1 keyed MAC - https://cryptopals.com/sets/4/challenges/28
 #[test]
 fn challenge28_implement_sha1_keyed_mac() {
-    use rustopals::digest::SHA1;
-    use rustopals::mac::bad_mac;
+    use rustopals::insecure::bad_mac;
+    use rustopals::mac::PrefixMac;
+    use rustopals::prelude::*;
 
     const KEY: &[u8] = b"YELLLOW SUBMARINE";
     const MESSAGE: &[u8] = b"This is a random message!";
@@ -70,7 +70,13 @@ fn challenge28_implement_sha1_keyed_mac() {
     assert_ne!(
         bad_mac::<SHA1>(KEY, MESSAGE),
         bad_mac::<SHA1>(KEY, TAMPERED_MESSAGE)
-    )
+    );
+
+    let mac = PrefixMac::<SHA1>::new(KEY);
+    let tag = mac.tag(MESSAGE);
+
+    assert!(mac.verify(MESSAGE, tag.as_ref()));
+    assert!(!mac.verify(TAMPERED_MESSAGE, tag.as_ref()));
 }
 
 // Break a SHA-1 keyed MAC using length extension - https://cryptopals.com/sets/4/challenges/29
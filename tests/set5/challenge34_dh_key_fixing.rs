@@ -3,9 +3,8 @@ use std::thread;
 
 use num_bigint::BigUint;
 use num_traits::Zero;
-use rustopals::block::{BlockCipher, BlockMode, AES128, CBC};
-use rustopals::digest::{Digest, SHA1};
-use rustopals::key_exchange::dh::{DHOffer, NIST_BASE, NIST_MODULUS};
+use rustopals::key_exchange::dh::{derive_key_material, DHOffer, NIST_BASE, NIST_MODULUS};
+use rustopals::prelude::*;
 
 enum Message {
     Offer {
@@ -18,7 +17,7 @@ enum Message {
     },
     Message {
         message: Vec<u8>,
-        iv: Vec<u8>,
+        iv: Iv<AES128>,
     },
 }
 
@@ -43,11 +42,12 @@ fn alice(tx: SyncSender<Message>, rx: Receiver<Message>) -> Vec<u8> {
         .establish(&their_public)
         .expect("Detected an error!")
         .to_key_material::<SHA1>();
+    let key = Key::new(&key_material[0..16]).unwrap();
 
     // (3) A->B -- Send AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv
     let my_message = crate::gen_random_bytes_between(123, 456);
-    let my_iv = crate::gen_random_bytes(AES128::BLOCK_SIZE);
-    let my_encrypted_message = CBC::new(&my_iv).encrypt(&AES128, &my_message, &key_material[0..16]);
+    let my_iv = Iv::random();
+    let my_encrypted_message = CBC::new(&my_iv).encrypt(&AES128, &my_message, &key);
 
     tx.send(Message::Message {
         message: my_encrypted_message,
@@ -62,7 +62,7 @@ fn alice(tx: SyncSender<Message>, rx: Receiver<Message>) -> Vec<u8> {
     };
 
     let their_message = CBC::new(&their_iv)
-        .decrypt(&AES128, &their_ecrypted_message, &key_material[0..16])
+        .decrypt(&AES128, &their_ecrypted_message, &key)
         .unwrap();
 
     assert_eq!(my_message, their_message); // Check that echo is OK!
@@ -98,6 +98,7 @@ fn bob(tx: SyncSender<Message>, rx: Receiver<Message>) {
         .establish(&their_public)
         .expect("Detected an error!")
         .to_key_material::<SHA1>();
+    let key = Key::new(&key_material[0..16]).unwrap();
 
     // (3) A->B -- Send AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv
     let (their_ecrypted_message, their_iv) = match rx.recv().unwrap() {
@@ -106,13 +107,12 @@ fn bob(tx: SyncSender<Message>, rx: Receiver<Message>) {
     };
 
     let their_message = CBC::new(&their_iv)
-        .decrypt(&AES128, &their_ecrypted_message, &key_material[0..16])
+        .decrypt(&AES128, &their_ecrypted_message, &key)
         .unwrap();
 
     // (4) B->A -- Send AES-CBC(SHA1(s)[0:16], iv=random(16), A's msg) + iv
-    let my_iv = crate::gen_random_bytes(AES128::BLOCK_SIZE);
-    let my_encrypted_message =
-        CBC::new(&my_iv).encrypt(&AES128, &their_message, &key_material[0..16]);
+    let my_iv = Iv::random();
+    let my_encrypted_message = CBC::new(&my_iv).encrypt(&AES128, &their_message, &key);
 
     tx.send(Message::Message {
         message: my_encrypted_message,
@@ -194,13 +194,14 @@ fn eve(
         .unwrap();
 
     // Okay, let's see if your nefarious deeds were successful...
-    let expected_key_material = &SHA1::digest(&BigUint::zero().to_bytes_be())[0..16];
+    let expected_key =
+        Key::new(&derive_key_material::<SHA1>(&NIST_MODULUS, &BigUint::zero())[0..16]).unwrap();
 
     let alice_message = CBC::new(&alice_iv)
-        .decrypt(&AES128, &alice_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &alice_encrypted_message, &expected_key)
         .unwrap();
     let bob_message = CBC::new(&bob_iv)
-        .decrypt(&AES128, &bob_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &bob_encrypted_message, &expected_key)
         .unwrap();
 
     assert_eq!(alice_message, bob_message);
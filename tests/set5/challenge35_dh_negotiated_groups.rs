@@ -2,16 +2,21 @@ use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 use std::thread;
 
 use num_bigint::BigUint;
-use rustopals::block::{BlockCipher, BlockMode, AES128, CBC};
-use rustopals::digest::{Digest, SHA1};
-use rustopals::key_exchange::dh::{DHOffer, NIST_BASE, NIST_MODULUS};
+use rustopals::key_exchange::dh::{derive_key_material, DHGroup, DHOffer, NIST_BASE, NIST_MODULUS};
+use rustopals::key_exchange::handshake::{
+    relay, transcript, Client, ClientHello, GroupDowngrade, Mitm, ServerHello,
+};
+use rustopals::key_exchange::suite::{Direction, SessionKeys, Suite};
+use rustopals::prelude::*;
+use rustopals::testkeys::DH_NIST;
+use rustopals::util::dlog::DiscreteLogTable;
 
 enum Message {
     Negotiate { modulus: BigUint, base: BigUint },
     Accept,
     Offer { public_key: BigUint },
     Response { public_key: BigUint },
-    Message { message: Vec<u8>, iv: Vec<u8> },
+    Message { message: Vec<u8>, iv: Iv<AES128> },
 }
 
 fn alice(tx: SyncSender<Message>, rx: Receiver<Message>) -> Vec<u8> {
@@ -47,12 +52,13 @@ fn alice(tx: SyncSender<Message>, rx: Receiver<Message>) -> Vec<u8> {
         .expect("Detected an error!");
 
     let key_material = session.to_key_material::<SHA1>();
+    let key = Key::new(&key_material[0..16]).unwrap();
 
     // (5) A->B -- Send AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv
 
     let my_message = crate::gen_random_bytes_between(123, 456);
-    let my_iv = crate::gen_random_bytes(AES128::BLOCK_SIZE);
-    let my_encrypted_message = CBC::new(&my_iv).encrypt(&AES128, &my_message, &key_material[0..16]);
+    let my_iv = Iv::random();
+    let my_encrypted_message = CBC::new(&my_iv).encrypt(&AES128, &my_message, &key);
 
     tx.send(Message::Message {
         message: my_encrypted_message,
@@ -67,7 +73,7 @@ fn alice(tx: SyncSender<Message>, rx: Receiver<Message>) -> Vec<u8> {
     };
 
     let their_message = CBC::new(&their_iv)
-        .decrypt(&AES128, &their_ecrypted_message, &key_material[0..16])
+        .decrypt(&AES128, &their_ecrypted_message, &key)
         .unwrap();
 
     assert_eq!(my_message, their_message); // Check that echo is OK!
@@ -109,6 +115,7 @@ fn bob(tx: SyncSender<Message>, rx: Receiver<Message>) {
         .expect("Detected an error!");
 
     let key_material = session.to_key_material::<SHA1>();
+    let key = Key::new(&key_material[0..16]).unwrap();
 
     // (5) A->B -- Send AES-CBC(SHA1(s)[0:16], iv=random(16), msg) + iv
     let (their_ecrypted_message, their_iv) = match rx.recv().unwrap() {
@@ -117,13 +124,12 @@ fn bob(tx: SyncSender<Message>, rx: Receiver<Message>) {
     };
 
     let their_message = CBC::new(&their_iv)
-        .decrypt(&AES128, &their_ecrypted_message, &key_material[0..16])
+        .decrypt(&AES128, &their_ecrypted_message, &key)
         .unwrap();
 
     // (6) B->A -- Send AES-CBC(SHA1(s)[0:16], iv=random(16), A's msg) + iv
-    let my_iv = crate::gen_random_bytes(AES128::BLOCK_SIZE);
-    let my_encrypted_message =
-        CBC::new(&my_iv).encrypt(&AES128, &their_message, &key_material[0..16]);
+    let my_iv = Iv::random();
+    let my_encrypted_message = CBC::new(&my_iv).encrypt(&AES128, &their_message, &key);
 
     tx.send(Message::Message {
         message: my_encrypted_message,
@@ -224,13 +230,15 @@ fn eve_g_1(
         .unwrap();
 
     // Okay, let's see if your nefarious deeds were successful...
-    let expected_key_material = &SHA1::digest(&BigUint::from(1_usize).to_bytes_be())[0..16];
+    let expected_key =
+        Key::new(&derive_key_material::<SHA1>(&NIST_MODULUS, &BigUint::from(1_usize))[0..16])
+            .unwrap();
 
     let alice_message = CBC::new(&alice_iv)
-        .decrypt(&AES128, &alice_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &alice_encrypted_message, &expected_key)
         .unwrap();
     let bob_message = CBC::new(&bob_iv)
-        .decrypt(&AES128, &bob_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &bob_encrypted_message, &expected_key)
         .unwrap();
 
     assert_eq!(alice_message, bob_message);
@@ -333,13 +341,15 @@ fn eve_g_p(
         .unwrap();
 
     // Okay, let's see if your nefarious deeds were successful...
-    let expected_key_material = &SHA1::digest(&BigUint::from(0_usize).to_bytes_be())[0..16];
+    let expected_key =
+        Key::new(&derive_key_material::<SHA1>(&NIST_MODULUS, &BigUint::from(0_usize))[0..16])
+            .unwrap();
 
     let alice_message = CBC::new(&alice_iv)
-        .decrypt(&AES128, &alice_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &alice_encrypted_message, &expected_key)
         .unwrap();
     let bob_message = CBC::new(&bob_iv)
-        .decrypt(&AES128, &bob_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &bob_encrypted_message, &expected_key)
         .unwrap();
 
     assert_eq!(alice_message, bob_message);
@@ -461,13 +471,15 @@ fn eve_g_p_minus_1(
         .unwrap();
 
     // Okay, let's see if your nefarious deeds were successful...
-    let expected_key_material = &SHA1::digest(&BigUint::from(1_usize).to_bytes_be())[0..16];
+    let expected_key =
+        Key::new(&derive_key_material::<SHA1>(&NIST_MODULUS, &BigUint::from(1_usize))[0..16])
+            .unwrap();
 
     let alice_message = CBC::new(&alice_iv)
-        .decrypt(&AES128, &alice_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &alice_encrypted_message, &expected_key)
         .unwrap();
     let bob_message = CBC::new(&bob_iv)
-        .decrypt(&AES128, &bob_encrypted_message, expected_key_material)
+        .decrypt(&AES128, &bob_encrypted_message, &expected_key)
         .unwrap();
 
     assert_eq!(alice_message, bob_message);
@@ -557,3 +569,108 @@ fn test_g_p_minus_1() {
     // Double check
     assert_eq!(alice_message, eve_message);
 }
+
+// Logjam: if the client still accepts some weak group, a MITM can strip the
+// strong ones from its offer. Finished messages would catch that, but not if
+// the MITM can break the discrete log of the client's share: then it knows
+// the session keys and can forge both Finished messages.
+//
+// The expensive part only depends on the group, so it's precomputed once and
+// then every single exchange in that group is cheap to break. Back in the
+// day, lots of servers shared the same few 512-bit export groups.
+struct Logjam<'a> {
+    table: &'a DiscreteLogTable,
+    client_view: Option<ClientHello>,
+    server_view: Option<ClientHello>,
+    server_transcript: Vec<u8>,
+    keys: Option<SessionKeys>,
+}
+
+impl<'a> Logjam<'a> {
+    const fn new(table: &'a DiscreteLogTable) -> Logjam<'a> {
+        Logjam {
+            table,
+            client_view: None,
+            server_view: None,
+            server_transcript: Vec::new(),
+            keys: None,
+        }
+    }
+}
+
+impl Mitm for Logjam<'_> {
+    fn client_hello(&mut self, hello: &mut ClientHello) {
+        self.client_view = Some(hello.clone());
+
+        GroupDowngrade { max_bits: 80 }.client_hello(hello);
+
+        self.server_view = Some(hello.clone());
+    }
+
+    fn server_hello(&mut self, hello: &mut ServerHello) {
+        let client_view = self.client_view.as_ref().unwrap();
+        let server_view = self.server_view.as_ref().unwrap();
+
+        // Break the client's share in the weak group
+        let share = &server_view.shares[0];
+        let client_private = self.table.log(&share.public).unwrap();
+
+        let session = DHOffer::new_custom_from_private(
+            share.group.modulus.clone(),
+            &share.group.base,
+            client_private,
+        )
+        .unwrap()
+        .establish(&hello.public)
+        .unwrap();
+
+        let keys = SessionKeys::derive(&session, hello.suite, &[]);
+
+        // Each side gets a Finished message matching its own view
+        self.server_transcript = transcript(server_view, hello.suite, &hello.group, &hello.public);
+        hello.finished = keys.finished(
+            Direction::ServerToClient,
+            &transcript(client_view, hello.suite, &hello.group, &hello.public),
+        );
+
+        self.keys = Some(keys);
+    }
+
+    fn client_finished(&mut self, finished: &mut Vec<u8>) {
+        let keys = self.keys.as_ref().unwrap();
+
+        *finished = keys.finished(Direction::ClientToServer, &self.server_transcript);
+    }
+}
+
+#[test]
+fn test_logjam() {
+    const EXCHANGES: usize = 4;
+    const MESSAGE: &[u8] = b"Nobody else can read this, right?";
+
+    // An 80-bit "export" group, with a small subgroup to keep the test fast
+    let weak = DHGroup::gen_weak(80, 32).unwrap();
+    let groups = [DH_NIST.clone(), weak];
+
+    // Once per group
+    let table = DiscreteLogTable::new(&groups[1].base, &groups[1].modulus, 256);
+
+    // Once per exchange
+    for _ in 0..EXCHANGES {
+        let mut logjam = Logjam::new(&table);
+
+        let client = Client::new_with_groups(&groups, Suite::ALL);
+        let (client_keys, server_keys) = relay(client, Suite::ALL, &mut logjam).unwrap();
+
+        assert_eq!(client_keys, server_keys);
+        assert_eq!(logjam.keys.as_ref(), Some(&client_keys));
+
+        let record = client_keys.seal(Direction::ClientToServer, MESSAGE);
+        let eve_keys = logjam.keys.unwrap();
+
+        assert_eq!(
+            eve_keys.open(Direction::ClientToServer, &record),
+            Some(MESSAGE.to_vec())
+        );
+    }
+}
@@ -1,16 +1,12 @@
-use rustopals::rsa::{RSAPrivateKey, RSAPublicKey};
+use rustopals::prelude::*;
 
 // Implement Diffie-Hellman - https://cryptopals.com/sets/5/challenges/33
 #[test]
 fn challenge33_implement_diffie_hellman() {
-    use num_bigint::BigUint;
-    use rustopals::key_exchange::DHOffer;
-
-    let p = BigUint::from(37_usize);
-    let g = BigUint::from(5_usize);
+    use rustopals::testkeys::DH_TOY;
 
-    let alice_offer = DHOffer::new_custom(p.clone(), &g);
-    let bob_offer = DHOffer::new_custom(p, &g);
+    let alice_offer = DH_TOY.offer();
+    let bob_offer = DH_TOY.offer();
 
     let alice_session = alice_offer
         .clone()
@@ -1,6 +1,6 @@
 use num_bigint::BigUint;
-use rustopals::digest::{Digest, SHA1};
-use rustopals::rsa::{BadPKCS1v1_5, RSAPrivateKey, RSAPublicKey};
+use rustopals::insecure::BadPKCS1v1_5;
+use rustopals::prelude::*;
 
 use crate::RSA_KEYPAIR_0;
 
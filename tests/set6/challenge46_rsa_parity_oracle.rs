@@ -2,7 +2,7 @@ mod adversary {
     use num_bigint::BigUint;
     use num_integer::Integer;
     use once_cell::sync::Lazy;
-    use rustopals::rsa::RSAPublicKey;
+    use rustopals::prelude::*;
 
     use crate::RSA_KEYPAIR_0;
 
@@ -35,34 +35,13 @@ mod adversary {
 #[test]
 fn crack() {
     use num_bigint::BigUint;
+    use rustopals::rsa::attacks::parity_oracle_attack;
 
     use self::adversary::{assert_solution, get_ciphertext, get_public_key, oracle};
 
-    let public_key = get_public_key();
-
-    let one = BigUint::from(1_usize);
-    let two = BigUint::from(2_usize);
-    let double = two.modpow(&public_key.e, &public_key.n);
-
-    let mut ciphertext = get_ciphertext().unwrap();
-    let mut low_bound = BigUint::from(0_usize);
-    let mut high_bound = public_key.n.clone();
-
-    while (&high_bound - &low_bound) > one {
-        // `cargo test challenge46 -- --nocapture` to enjoy Hollywood-like cracking
-        println!("{}", high_bound);
-
-        ciphertext = (&ciphertext * &double) % &public_key.n;
-        let half_bound = (&high_bound - &low_bound) / &two;
-
-        let is_even = oracle(&ciphertext).unwrap();
-
-        if is_even {
-            high_bound -= half_bound;
-        } else {
-            low_bound += half_bound;
-        }
-    }
+    let plaintext = parity_oracle_attack(get_public_key(), &get_ciphertext().unwrap(), |c| {
+        oracle(c).unwrap()
+    });
 
-    assert_solution(&high_bound);
+    assert_solution(&BigUint::from_bytes_be(&plaintext));
 }
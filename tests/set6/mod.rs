@@ -1,9 +1,39 @@
 use num_bigint::BigUint;
 use num_traits::Num;
-use rustopals::digest::SHA1;
+use rustopals::dsa::attacks::{brute_force_k, BruteForceOutcome};
 use rustopals::dsa::{
-    DSAPublicKey, DSASignature, CHALLENGE_DSA, CHALLENGE_DSA_P, CHALLENGE_DSA_Q, DSA,
+    corpus, DSAPrivateKey, DSAPublicKey, DSASignature, CHALLENGE_DSA, CHALLENGE_DSA_P,
+    CHALLENGE_DSA_Q, DSA,
 };
+use rustopals::prelude::*;
+
+/// Cryptopals' own check for recovered keys: SHA-1 of the hex-encoded `x`.
+fn cryptopals_fingerprint(private_key: &DSAPrivateKey) -> [u8; 20] {
+    SHA1::digest(
+        private_key
+            .expose_secret()
+            .to_bytes_be()
+            .into_hex()
+            .as_bytes(),
+    )
+}
+
+/// Public key used in challenges 44 and 45.
+fn challenge44_public_key() -> DSAPublicKey {
+    DSAPublicKey(
+        BigUint::from_str_radix(
+            "\
+        2d026f4bf30195ede3a088da85e398ef869611d0f68f07\
+        13d51c9c1a3a26c95105d915e2d8cdf26d056b86b8a7b8\
+        5519b1c23cc3ecdc6062650462e3063bd179c2a6581519\
+        f674a61f1d89a1fff27171ebc1b93d4dc57bceb7ae2430\
+        f98a6a4d83d8279ee65d71c1203d2c96d65ebbf7cce9d3\
+        2971c3de5084cce04a2e147821",
+            16,
+        )
+        .unwrap(),
+    )
+}
 
 // Implement unpadded message recovery oracle - https://cryptopals.com/sets/1/challenges/41
 mod challenge41_unpadded_message_recovery;
@@ -21,8 +51,19 @@ fn challenge43_dsa_key_from_nonce() {
 
     let dsa: &DSA<_> = &CHALLENGE_DSA;
 
-    let min_k = BigUint::from(0_usize);
-    let max_k = BigUint::from(2_usize.pow(16));
+    let public_key = DSAPublicKey(
+        BigUint::from_str_radix(
+            "\
+            84ad4719d044495496a3201c8ff484feb45b962e7302e56a3\
+            92aee4abab3e4bdebf2955b4736012f21a08084056b19bcd7\
+            fee56048e004e44984e2f411788efdc837a0d2e5abb7b5550\
+            39fd243ac01f0fb2ed1dec568280ce678e931868d23eb095f\
+            de9d3779191b8c0299d6e07bbb283e6633451e535c45513b2\
+            d33c99ea17",
+            16,
+        )
+        .unwrap(),
+    );
 
     let signature = DSASignature {
         r: BigUint::from_str_radix("548099063082341131477253921760299949438196259240", 10).unwrap(),
@@ -31,24 +72,26 @@ fn challenge43_dsa_key_from_nonce() {
 
     let h_m = BigUint::from_str_radix("d2d0714f014a9784047eaeccf956520045c45265", 16).unwrap();
 
-    for k in num_iter::range_inclusive(min_k, max_k) {
-        let cracked_pk = dsa.crack_private_key_guess(&signature, &h_m, &k);
-        let pk_fingerprint = cracked_pk.fingerprint_after_hex::<SHA1>();
+    let private_key = match brute_force_k(
+        dsa,
+        &signature,
+        &h_m,
+        0..2_u64.pow(16) + 1,
+        &public_key,
+        |_| true,
+    ) {
+        BruteForceOutcome::Found { private_key, .. } => private_key,
+        outcome => panic!("Key should've been cracked by now: {:?}", outcome),
+    };
 
-        if pk_fingerprint == EXPECTED_FINGERPRINT {
-            return;
-        }
-    }
+    assert!(dsa.private_key_matches(&private_key, &public_key));
 
-    panic!("Key should've been cracked by now")
+    assert_eq!(cryptopals_fingerprint(&private_key), EXPECTED_FINGERPRINT);
 }
 
 // DSA nonce recovery from repeated nonce - https://cryptopals.com/sets/1/challenges/44
 #[test]
 fn challenge44_dsa_key_from_repeated_nonce() {
-    use itertools::Itertools;
-    use regex::Regex;
-
     const INPUT_FILE: &str = include_str!("44.txt");
 
     const EXPECTED_FINGERPRINT: [u8; 20] = [
@@ -56,44 +99,19 @@ fn challenge44_dsa_key_from_repeated_nonce() {
         0xb8, 0x52, 0x7d, 0x3d, 0x52,
     ];
 
-    let re = Regex::new(
-        r"msg: (?P<msg>[^\n]+)\ns: (?P<s>\d+)\nr: (?P<r>\d+)\nm: (?P<m>[a-f0-9]{40})(?:\n|$)",
-    )
-    .unwrap();
-
-    let duplicates = re
-        .captures_iter(INPUT_FILE)
-        .map(|x| (x["r"].to_string(), x))
-        .into_group_map()
-        .into_iter()
-        .find(|(_, v)| v.len() > 1)
-        .expect("No duplicate `r` found")
-        .1;
-
-    let sig_1 = (
-        DSASignature {
-            r: BigUint::from_str_radix(&duplicates[0]["r"], 10).unwrap(),
-            s: BigUint::from_str_radix(&duplicates[0]["s"], 10).unwrap(),
-        },
-        BigUint::from_str_radix(&duplicates[0]["m"], 16).unwrap(),
-    );
-
-    let sig_2 = (
-        DSASignature {
-            r: BigUint::from_str_radix(&duplicates[1]["r"], 10).unwrap(),
-            s: BigUint::from_str_radix(&duplicates[1]["s"], 10).unwrap(),
-        },
-        BigUint::from_str_radix(&duplicates[1]["m"], 16).unwrap(),
-    );
+    let messages = corpus::parse::<SHA1>(INPUT_FILE).unwrap();
+    let [message_1, message_2] =
+        corpus::find_repeated_nonce(&messages).expect("No duplicate `r` found");
 
     let dsa: &DSA<_> = &CHALLENGE_DSA;
 
     let cracked_pk = dsa
-        .crack_private_key_repeated_nonce([(&sig_1.0, &sig_1.1), (&sig_2.0, &sig_2.1)])
+        .crack_private_key_repeated_nonce([message_1.as_pair(), message_2.as_pair()])
         .unwrap();
-    let pk_fingerprint = cracked_pk.fingerprint_after_hex::<SHA1>();
 
-    assert_eq!(pk_fingerprint, EXPECTED_FINGERPRINT);
+    assert!(dsa.private_key_matches(&cracked_pk, &challenge44_public_key()));
+
+    assert_eq!(cryptopals_fingerprint(&cracked_pk), EXPECTED_FINGERPRINT);
 }
 
 // DSA parameter tampering - https://cryptopals.com/sets/1/challenges/45
@@ -105,23 +123,13 @@ fn challenge45_dsa_param_tampering() {
         (&CHALLENGE_DSA_P as &BigUint) + BigUint::from(1_usize),
     );
 
-    let y = BigUint::from_str_radix(
-        "\
-        2d026f4bf30195ede3a088da85e398ef869611d0f68f07\
-        13d51c9c1a3a26c95105d915e2d8cdf26d056b86b8a7b8\
-        5519b1c23cc3ecdc6062650462e3063bd179c2a6581519\
-        f674a61f1d89a1fff27171ebc1b93d4dc57bceb7ae2430\
-        f98a6a4d83d8279ee65d71c1203d2c96d65ebbf7cce9d3\
-        2971c3de5084cce04a2e147821",
-        16,
-    )
-    .unwrap();
-
-    let public_key = DSAPublicKey(y);
+    let public_key = challenge44_public_key();
     let magic_signature = dsa.gen_magic_signature(&public_key).unwrap();
 
     assert!(dsa.verify(&public_key, b"Hello, world", &magic_signature));
     assert!(dsa.verify(&public_key, b"Goodbye, world", &magic_signature));
+
+    assert!(!dsa.verify_strict(&public_key, b"Hello, world", &magic_signature));
 }
 
 // RSA parity oracle - https://cryptopals.com/sets/1/challenges/46